@@ -0,0 +1,118 @@
+/**
+ * automaton-cli maintenance <schedule|list|cancel>
+ *
+ * Schedule maintenance windows. The automaton winds down to the window's
+ * safe posture beforehand, halts during it, and resumes afterwards.
+ */
+
+import { loadConfig, resolvePath } from "@conway/automaton/config.js";
+import { createDatabase } from "@conway/automaton/state/database.js";
+import {
+  cancelMaintenanceWindow,
+  getMaintenanceStatus,
+  loadMaintenanceWindows,
+  scheduleMaintenanceWindow,
+  DEFAULT_WIND_DOWN_MINUTES,
+} from "@conway/automaton/survival/maintenance.js";
+
+const args = process.argv.slice(3);
+const subcommand = args[0];
+
+function usage(): never {
+  console.log(`
+Usage:
+  automaton-cli maintenance schedule <start> <end|duration> [--reason TEXT] [--wind-down MIN] [--posture TEXT]
+      start: ISO time or "now"; end: ISO time or a duration like 30m, 2h
+      wind-down defaults to ${DEFAULT_WIND_DOWN_MINUTES} minutes before start
+  automaton-cli maintenance list
+  automaton-cli maintenance cancel <id>
+`);
+  process.exit(1);
+}
+
+function flag(name: string): string | undefined {
+  const idx = args.indexOf(name);
+  return idx !== -1 ? args[idx + 1] : undefined;
+}
+
+function parseStart(value: string): Date {
+  return value === "now" ? new Date() : new Date(value);
+}
+
+function parseEnd(value: string, start: Date): Date {
+  const duration = value.match(/^(\d+)(m|h|d)$/);
+  if (!duration) return new Date(value);
+  const unitMs = { m: 60_000, h: 3_600_000, d: 86_400_000 }[duration[2] as "m" | "h" | "d"];
+  return new Date(start.getTime() + Number(duration[1]) * unitMs);
+}
+
+const config = loadConfig();
+if (!config) {
+  console.log("No automaton configuration found.");
+  process.exit(1);
+}
+
+const db = createDatabase(resolvePath(config.dbPath));
+
+try {
+  switch (subcommand) {
+    case "schedule": {
+      if (!args[1] || !args[2]) usage();
+      const startsAt = parseStart(args[1]);
+      const endsAt = parseEnd(args[2], startsAt);
+      const windDown = flag("--wind-down");
+      const window = scheduleMaintenanceWindow(db, {
+        startsAt,
+        endsAt,
+        reason: flag("--reason") || "scheduled maintenance",
+        windDownMinutes: windDown !== undefined ? Number(windDown) : undefined,
+        safePosture: flag("--posture"),
+      });
+      console.log(`Scheduled ${window.id}: ${window.startsAt} -> ${window.endsAt}`);
+      console.log(`Wind-down starts ${window.windDownMinutes} minutes before.`);
+      break;
+    }
+
+    case "list": {
+      const status = getMaintenanceStatus(db);
+      console.log(`Current phase: ${status.phase}${status.window ? ` (${status.window.id})` : ""}`);
+      const windows = loadMaintenanceWindows(db);
+      if (windows.length === 0) {
+        console.log("No maintenance windows.");
+        break;
+      }
+      for (const w of windows) {
+        const state = w.cancelledAt
+          ? "cancelled"
+          : w.endedAt
+            ? "ended"
+            : w.startedAt
+              ? "in progress"
+              : w.windDownStartedAt
+                ? "winding down"
+                : "scheduled";
+        console.log(`  ${w.id}  ${w.startsAt} -> ${w.endsAt}  [${state}]  ${w.reason}`);
+      }
+      break;
+    }
+
+    case "cancel": {
+      if (!args[1]) usage();
+      if (cancelMaintenanceWindow(db, args[1])) {
+        console.log(`Cancelled ${args[1]}.`);
+      } else {
+        console.log(`No active maintenance window ${args[1]}.`);
+        process.exitCode = 1;
+      }
+      break;
+    }
+
+    default:
+      usage();
+  }
+} catch (err: any) {
+  console.error(`maintenance ${subcommand}: ${err.message}`);
+  process.exitCode = 1;
+} finally {
+  db.close();
+}
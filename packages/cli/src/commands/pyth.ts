@@ -0,0 +1,369 @@
+/**
+ * automaton-cli pyth <resolve|inspect|watch>
+ *
+ * Resolve Pyth symbols to feed IDs, inspect a feed's latest Hermes price
+ * next to its on-chain receiver account, and stream live updates.
+ */
+
+import { Connection, PublicKey } from "@solana/web3.js";
+
+const args = process.argv.slice(3);
+const subcommand = args[0];
+
+const HERMES_URL = process.env.HERMES_URL || "https://hermes.pyth.network";
+
+const CLUSTER_RPC: Record<string, string> = {
+  mainnet: "https://api.mainnet-beta.solana.com",
+  "mainnet-beta": "https://api.mainnet-beta.solana.com",
+  devnet: "https://api.devnet.solana.com",
+};
+
+// Pyth push oracle: sponsored PriceUpdateV2 accounts per (shard, feed)
+const PUSH_ORACLE_PROGRAM_ID = new PublicKey(
+  "pythWSnswVUd12oZpeFP8e9CVaEqJg25g1Vtc2biRsT",
+);
+
+// ─── Types ───────────────────────────────────────────────────────
+
+interface FeedMetadata {
+  id: string;
+  attributes: {
+    asset_type: string;
+    base?: string;
+    display_symbol?: string;
+    quote_currency?: string;
+    symbol: string;
+  };
+}
+
+interface HermesPrice {
+  price: string;
+  conf: string;
+  expo: number;
+  publish_time: number;
+}
+
+interface HermesParsedUpdate {
+  id: string;
+  price: HermesPrice;
+  ema_price: HermesPrice;
+  metadata?: { slot?: number };
+}
+
+interface OnChainPriceUpdate {
+  writeAuthority: string;
+  verification: string;
+  feedId: string;
+  price: bigint;
+  conf: bigint;
+  exponent: number;
+  publishTime: number;
+  emaPrice: bigint;
+  postedSlot: bigint;
+}
+
+// ─── Helpers ─────────────────────────────────────────────────────
+
+function usage(): never {
+  console.log(`
+Usage:
+  automaton-cli pyth resolve <symbol>                    Print the feed ID for a symbol (e.g. SOL/USD)
+  automaton-cli pyth inspect <feed-id|symbol> [--cluster mainnet|devnet] [--rpc URL] [--shard N]
+                                                         Latest Hermes price and on-chain receiver state
+  automaton-cli pyth watch <feed-id|symbol>...           Stream price updates from Hermes
+`);
+  process.exit(1);
+}
+
+function flag(name: string): string | undefined {
+  const idx = args.indexOf(name);
+  return idx !== -1 ? args[idx + 1] : undefined;
+}
+
+/** Positional arguments after the subcommand, skipping flags and their values */
+function positionals(): string[] {
+  const out: string[] = [];
+  for (let i = 1; i < args.length; i++) {
+    if (args[i].startsWith("--")) {
+      i++;
+      continue;
+    }
+    out.push(args[i]);
+  }
+  return out;
+}
+
+function isFeedId(value: string): boolean {
+  return /^(0x)?[0-9a-fA-F]{64}$/.test(value);
+}
+
+function normalizeFeedId(value: string): string {
+  return value.replace(/^0x/, "").toLowerCase();
+}
+
+function hexToBytes(hex: string): Uint8Array {
+  const bytes = new Uint8Array(hex.length / 2);
+  for (let i = 0; i < bytes.length; i++) {
+    bytes[i] = parseInt(hex.slice(i * 2, i * 2 + 2), 16);
+  }
+  return bytes;
+}
+
+function bytesToHex(bytes: Uint8Array): string {
+  return Array.from(bytes, (b) => b.toString(16).padStart(2, "0")).join("");
+}
+
+function formatFixed(value: bigint | string, expo: number): string {
+  const raw = BigInt(value);
+  const negative = raw < 0n;
+  const digits = (negative ? -raw : raw).toString();
+  if (expo >= 0) {
+    return `${negative ? "-" : ""}${digits}${"0".repeat(expo)}`;
+  }
+  const places = -expo;
+  const padded = digits.padStart(places + 1, "0");
+  const whole = padded.slice(0, padded.length - places);
+  const fraction = padded.slice(padded.length - places);
+  return `${negative ? "-" : ""}${whole}.${fraction}`;
+}
+
+function age(publishTime: number): string {
+  return `${Math.max(0, Math.floor(Date.now() / 1000) - publishTime)}s`;
+}
+
+async function hermesGet<T>(path: string): Promise<T> {
+  const resp = await fetch(`${HERMES_URL}${path}`);
+  if (!resp.ok) {
+    throw new Error(`Hermes returned ${resp.status}: ${await resp.text()}`);
+  }
+  return (await resp.json()) as T;
+}
+
+/** Feeds whose display symbol (SOL/USD) or Pyth symbol (Crypto.SOL/USD) matches */
+async function searchFeeds(symbol: string): Promise<FeedMetadata[]> {
+  const base = symbol.split(".").pop()!.split("/")[0];
+  const feeds = await hermesGet<FeedMetadata[]>(
+    `/v2/price_feeds?query=${encodeURIComponent(base)}`,
+  );
+  const wanted = symbol.toUpperCase();
+  return feeds.filter(
+    (feed) =>
+      feed.attributes.symbol.toUpperCase() === wanted ||
+      feed.attributes.display_symbol?.toUpperCase() === wanted,
+  );
+}
+
+async function resolveFeedId(symbolOrId: string): Promise<string> {
+  if (isFeedId(symbolOrId)) {
+    return normalizeFeedId(symbolOrId);
+  }
+  const matches = await searchFeeds(symbolOrId);
+  if (matches.length === 0) {
+    throw new Error(`No Pyth feed found for ${symbolOrId}`);
+  }
+  if (matches.length > 1) {
+    const options = matches.map((m) => `  ${m.attributes.symbol}  0x${normalizeFeedId(m.id)}`);
+    throw new Error(
+      `${symbolOrId} is ambiguous; use the full symbol or feed ID:\n${options.join("\n")}`,
+    );
+  }
+  return normalizeFeedId(matches[0].id);
+}
+
+/** Decode a PriceUpdateV2 account (Anchor discriminator first) */
+function decodePriceUpdate(data: Uint8Array): OnChainPriceUpdate {
+  const view = new DataView(data.buffer, data.byteOffset, data.byteLength);
+  let offset = 8;
+
+  const writeAuthority = new PublicKey(data.slice(offset, offset + 32)).toBase58();
+  offset += 32;
+
+  // VerificationLevel: Partial { num_signatures: u8 } = 0, Full = 1
+  const tag = data[offset++];
+  let verification = "Full";
+  if (tag === 0) {
+    verification = `Partial (${data[offset++]} signatures)`;
+  }
+
+  const feedId = bytesToHex(data.slice(offset, offset + 32));
+  offset += 32;
+  const price = view.getBigInt64(offset, true);
+  const conf = view.getBigUint64(offset + 8, true);
+  const exponent = view.getInt32(offset + 16, true);
+  const publishTime = Number(view.getBigInt64(offset + 20, true));
+  // prev_publish_time at offset + 28
+  const emaPrice = view.getBigInt64(offset + 36, true);
+  // ema_conf at offset + 44
+  const postedSlot = view.getBigUint64(offset + 52, true);
+
+  return {
+    writeAuthority,
+    verification,
+    feedId,
+    price,
+    conf,
+    exponent,
+    publishTime,
+    emaPrice,
+    postedSlot,
+  };
+}
+
+function priceFeedAccount(feedId: string, shard: number): PublicKey {
+  const shardSeed = new Uint8Array(2);
+  new DataView(shardSeed.buffer).setUint16(0, shard, true);
+  const [address] = PublicKey.findProgramAddressSync(
+    [shardSeed, hexToBytes(feedId)],
+    PUSH_ORACLE_PROGRAM_ID,
+  );
+  return address;
+}
+
+// ─── Subcommands ─────────────────────────────────────────────────
+
+async function resolve(): Promise<void> {
+  const [symbol] = positionals();
+  if (!symbol) usage();
+
+  const matches = await searchFeeds(symbol);
+  if (matches.length === 0) {
+    console.error(`No Pyth feed found for ${symbol}`);
+    process.exit(1);
+  }
+  for (const feed of matches) {
+    const { symbol: pythSymbol, asset_type, quote_currency } = feed.attributes;
+    console.log(`0x${normalizeFeedId(feed.id)}`);
+    console.log(`  Symbol: ${pythSymbol}`);
+    console.log(`  Asset:  ${asset_type}${quote_currency ? ` (quote ${quote_currency})` : ""}`);
+  }
+}
+
+async function inspect(): Promise<void> {
+  const [target] = positionals();
+  if (!target) usage();
+
+  const cluster = flag("--cluster") || "mainnet";
+  const rpcUrl = flag("--rpc") || process.env.SOLANA_RPC_URL || CLUSTER_RPC[cluster];
+  if (!rpcUrl) {
+    throw new Error(`Unknown cluster ${cluster}; pass --rpc`);
+  }
+  const shard = parseInt(flag("--shard") || "0", 10);
+
+  const feedId = await resolveFeedId(target);
+  console.log(`Feed 0x${feedId}\n`);
+
+  const latest = await hermesGet<{ parsed: HermesParsedUpdate[] }>(
+    `/v2/updates/price/latest?ids[]=0x${feedId}&parsed=true`,
+  );
+  const update = latest.parsed[0];
+  if (update) {
+    const { price, ema_price } = update;
+    console.log("Hermes (latest):");
+    console.log(`  Price:        ${formatFixed(price.price, price.expo)} ± ${formatFixed(price.conf, price.expo)}`);
+    console.log(`  EMA price:    ${formatFixed(ema_price.price, ema_price.expo)}`);
+    console.log(`  Exponent:     ${price.expo}`);
+    console.log(`  Published:    ${new Date(price.publish_time * 1000).toISOString()} (${age(price.publish_time)} ago)`);
+    if (update.metadata?.slot !== undefined) {
+      console.log(`  Pythnet slot: ${update.metadata.slot}`);
+    }
+  } else {
+    console.log("Hermes: no price published for this feed");
+  }
+
+  const address = priceFeedAccount(feedId, shard);
+  const connection = new Connection(rpcUrl, "confirmed");
+  const account = await connection.getAccountInfo(address);
+
+  console.log(`\nOn-chain (${cluster}, shard ${shard}):`);
+  console.log(`  Account:      ${address.toBase58()}`);
+  if (!account) {
+    console.log("  Not initialized - no sponsored updates for this feed on this shard");
+    return;
+  }
+
+  const onChain = decodePriceUpdate(account.data);
+  console.log(`  Owner:        ${account.owner.toBase58()}`);
+  console.log(`  Verification: ${onChain.verification}`);
+  console.log(`  Price:        ${formatFixed(onChain.price, onChain.exponent)} ± ${formatFixed(onChain.conf, onChain.exponent)}`);
+  console.log(`  EMA price:    ${formatFixed(onChain.emaPrice, onChain.exponent)}`);
+  console.log(`  Published:    ${new Date(onChain.publishTime * 1000).toISOString()} (${age(onChain.publishTime)} ago)`);
+  console.log(`  Posted slot:  ${onChain.postedSlot}`);
+  if (onChain.feedId !== feedId) {
+    console.log(`  WARNING: account holds feed 0x${onChain.feedId}`);
+  }
+  if (update && update.price.publish_time > onChain.publishTime) {
+    console.log(`  Lag:          ${update.price.publish_time - onChain.publishTime}s behind Hermes`);
+  }
+}
+
+async function watch(): Promise<void> {
+  const targets = positionals();
+  if (targets.length === 0) usage();
+
+  const feedIds = await Promise.all(targets.map(resolveFeedId));
+  const labels = new Map(feedIds.map((id, i) => [id, targets[i]]));
+  const query = feedIds.map((id) => `ids[]=0x${id}`).join("&");
+
+  const resp = await fetch(`${HERMES_URL}/v2/updates/price/stream?${query}&parsed=true`, {
+    headers: { Accept: "text/event-stream" },
+  });
+  if (!resp.ok || !resp.body) {
+    throw new Error(`Hermes returned ${resp.status}: ${await resp.text()}`);
+  }
+  console.log(`Streaming ${feedIds.length} feed(s) from ${HERMES_URL} (Ctrl+C to stop)\n`);
+
+  const reader = resp.body.getReader();
+  const decoder = new TextDecoder();
+  let buffer = "";
+  for (;;) {
+    const { done, value } = await reader.read();
+    if (done) {
+      throw new Error("Hermes closed the stream");
+    }
+    buffer += decoder.decode(value, { stream: true });
+
+    // Server-sent events are separated by a blank line
+    let boundary: number;
+    while ((boundary = buffer.indexOf("\n\n")) !== -1) {
+      const event = buffer.slice(0, boundary);
+      buffer = buffer.slice(boundary + 2);
+      const data = event
+        .split("\n")
+        .filter((line) => line.startsWith("data:"))
+        .map((line) => line.slice(5).trim())
+        .join("");
+      if (!data) continue;
+
+      const { parsed } = JSON.parse(data) as { parsed?: HermesParsedUpdate[] };
+      for (const update of parsed ?? []) {
+        const id = normalizeFeedId(update.id);
+        const { price } = update;
+        console.log(
+          `${new Date(price.publish_time * 1000).toISOString()}  ${labels.get(id) ?? id}  ` +
+            `${formatFixed(price.price, price.expo)} ± ${formatFixed(price.conf, price.expo)}`,
+        );
+      }
+    }
+  }
+}
+
+// ─── Dispatch ────────────────────────────────────────────────────
+
+try {
+  switch (subcommand) {
+    case "resolve":
+      await resolve();
+      break;
+    case "inspect":
+      await inspect();
+      break;
+    case "watch":
+      await watch();
+      break;
+    default:
+      usage();
+  }
+} catch (err: any) {
+  console.error(`pyth ${subcommand}: ${err.message}`);
+  process.exit(1);
+}
@@ -23,6 +23,12 @@ async function main(): Promise<void> {
     case "send":
       await import("./commands/send.js");
       break;
+    case "pyth":
+      await import("./commands/pyth.js");
+      break;
+    case "maintenance":
+      await import("./commands/maintenance.js");
+      break;
     default:
       console.log(`
 Conway Automaton CLI - Creator Tools
@@ -32,6 +38,8 @@ Usage:
   automaton-cli logs [--tail N]     View automaton logs
   automaton-cli fund <amount> [--to 0x...]  Transfer Conway credits
   automaton-cli send <to-address> <message> Send a social message
+  automaton-cli pyth <resolve|inspect|watch> Resolve, inspect, and stream Pyth feeds
+  automaton-cli maintenance <schedule|list|cancel> Schedule maintenance windows
 `);
   }
 }
@@ -0,0 +1,89 @@
+/**
+ * Config Migration Tests
+ *
+ * Tests for upgrading older automaton.json files.
+ */
+
+import fs from "fs";
+import os from "os";
+import path from "path";
+import { describe, it, expect } from "vitest";
+import {
+  CONFIG_SCHEMA_VERSION,
+  migrateConfig,
+  migrateConfigFile,
+} from "../config-migration.js";
+import { DEFAULT_CONFIG } from "../types.js";
+
+const LEGACY_CONFIG = {
+  name: "legacy",
+  genesisPrompt: "Survive.",
+  creatorAddress: "0x0000000000000000000000000000000000000001",
+  registeredWithConway: true,
+  sandboxId: "sbx",
+  conwayApiKey: "key",
+  walletAddress: "0x0000000000000000000000000000000000000002",
+  openRouterApiKey: "or-key",
+  solanaAddress: "So1ana",
+  customField: 42,
+};
+
+describe("migrateConfig", () => {
+  it("renames legacy fields and applies defaults", () => {
+    const { config, report } = migrateConfig(LEGACY_CONFIG);
+
+    expect(report.fromVersion).toBe(1);
+    expect(report.toVersion).toBe(CONFIG_SCHEMA_VERSION);
+    expect(config.configVersion).toBe(CONFIG_SCHEMA_VERSION);
+    expect(config.openrouterApiKey).toBe("or-key");
+    expect(config.solanaWalletAddress).toBe("So1ana");
+    expect(config).not.toHaveProperty("openRouterApiKey");
+    expect(report.renamed).toContainEqual({ from: "openRouterApiKey", to: "openrouterApiKey" });
+    expect(config.dbPath).toBe(DEFAULT_CONFIG.dbPath);
+    expect(report.defaultsApplied.map((d) => d.field)).toContain("dbPath");
+    expect(report.unknown).toEqual(["customField"]);
+    expect(report.changed).toBe(true);
+  });
+
+  it("keeps the value under the new name when both are present", () => {
+    const { config } = migrateConfig({ ...LEGACY_CONFIG, openrouterApiKey: "new" });
+    expect(config.openrouterApiKey).toBe("new");
+  });
+
+  it("is a no-op for a current config", () => {
+    const { config } = migrateConfig(LEGACY_CONFIG);
+    const { report } = migrateConfig(config as Record<string, unknown>);
+
+    expect(report.changed).toBe(false);
+    expect(report.renamed).toEqual([]);
+    expect(report.defaultsApplied).toEqual([]);
+  });
+
+  it("rejects configs from a newer release", () => {
+    expect(() =>
+      migrateConfig({ ...LEGACY_CONFIG, configVersion: CONFIG_SCHEMA_VERSION + 1 }),
+    ).toThrow(/newer/);
+  });
+});
+
+describe("migrateConfigFile", () => {
+  it("writes a backup and leaves dry runs untouched", () => {
+    const dir = fs.mkdtempSync(path.join(os.tmpdir(), "automaton-config-"));
+    const configPath = path.join(dir, "automaton.json");
+    const original = JSON.stringify(LEGACY_CONFIG);
+    fs.writeFileSync(configPath, original);
+
+    const dryRun = migrateConfigFile(configPath, { dryRun: true });
+    expect(dryRun.changed).toBe(true);
+    expect(fs.readFileSync(configPath, "utf-8")).toBe(original);
+
+    const report = migrateConfigFile(configPath);
+    expect(report.backupPath).toBe(`${configPath}.v1.bak`);
+    expect(fs.readFileSync(report.backupPath!, "utf-8")).toBe(original);
+    expect(JSON.parse(fs.readFileSync(configPath, "utf-8")).configVersion).toBe(
+      CONFIG_SCHEMA_VERSION,
+    );
+
+    fs.rmSync(dir, { recursive: true, force: true });
+  });
+});
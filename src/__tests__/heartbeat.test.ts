@@ -6,6 +6,12 @@
 
 import { describe, it, expect, beforeEach, afterEach } from "vitest";
 import { BUILTIN_TASKS } from "../heartbeat/tasks.js";
+import {
+  advanceMaintenance,
+  cancelMaintenanceWindow,
+  loadMaintenanceWindows,
+  scheduleMaintenanceWindow,
+} from "../survival/maintenance.js";
 import {
   MockConwayClient,
   MockSocialClient,
@@ -148,4 +154,95 @@ describe("Heartbeat Tasks", () => {
       expect(result.shouldWake).toBe(false);
     });
   });
+
+  describe("check_maintenance", () => {
+    const minutes = (n: number) => new Date(Date.now() + n * 60_000);
+
+    it("wakes and alerts the creator when wind-down starts", async () => {
+      const social = new MockSocialClient();
+      const config = createTestConfig();
+      scheduleMaintenanceWindow(db, {
+        startsAt: minutes(10),
+        endsAt: minutes(70),
+        reason: "RPC migration",
+      });
+
+      const result = await BUILTIN_TASKS.check_maintenance({
+        identity: createTestIdentity(),
+        config,
+        db,
+        conway,
+        social,
+      });
+
+      expect(result.shouldWake).toBe(true);
+      expect(result.message).toContain("RPC migration");
+      expect(social.sentMessages).toHaveLength(1);
+      expect(social.sentMessages[0].to).toBe(config.creatorAddress);
+
+      // Same phase on the next tick: no repeat
+      const again = await BUILTIN_TASKS.check_maintenance({
+        identity: createTestIdentity(),
+        config,
+        db,
+        conway,
+        social,
+      });
+      expect(again.shouldWake).toBe(false);
+      expect(social.sentMessages).toHaveLength(1);
+    });
+
+    it("fires start and end transitions once each", () => {
+      const start = minutes(60);
+      const end = minutes(120);
+      const window = scheduleMaintenanceWindow(db, {
+        startsAt: start,
+        endsAt: end,
+        reason: "upgrade",
+        windDownMinutes: 15,
+      });
+
+      expect(advanceMaintenance(db, minutes(0))).toHaveLength(0);
+      expect(advanceMaintenance(db, minutes(50)).map((e) => e.kind)).toEqual(["wind_down_started"]);
+      expect(advanceMaintenance(db, minutes(61)).map((e) => e.kind)).toEqual(["maintenance_started"]);
+      expect(advanceMaintenance(db, minutes(62))).toHaveLength(0);
+      expect(advanceMaintenance(db, minutes(121)).map((e) => e.kind)).toEqual(["maintenance_ended"]);
+      expect(cancelMaintenanceWindow(db, window.id)).toBe(false);
+    });
+
+    it("ends a window early when cancelled", () => {
+      const window = scheduleMaintenanceWindow(db, {
+        startsAt: minutes(-1),
+        endsAt: minutes(60),
+        reason: "upgrade",
+      });
+      expect(advanceMaintenance(db).map((e) => e.kind)).toEqual(["maintenance_started"]);
+
+      expect(cancelMaintenanceWindow(db, window.id)).toBe(true);
+      const events = advanceMaintenance(db);
+      expect(events.map((e) => e.kind)).toEqual(["maintenance_ended"]);
+      expect(events[0].message).toContain("cancelled");
+    });
+
+    it("rejects overlapping windows", () => {
+      scheduleMaintenanceWindow(db, { startsAt: minutes(10), endsAt: minutes(70), reason: "a" });
+      expect(() =>
+        scheduleMaintenanceWindow(db, { startsAt: minutes(60), endsAt: minutes(90), reason: "b" }),
+      ).toThrow(/Overlaps/);
+    });
+
+    it("rejects wind-downs that are not finite and non-negative", () => {
+      for (const windDownMinutes of [NaN, Infinity, -5]) {
+        expect(() =>
+          scheduleMaintenanceWindow(db, {
+            startsAt: minutes(10),
+            endsAt: minutes(70),
+            reason: "a",
+            windDownMinutes,
+          }),
+        ).toThrow(/Invalid wind-down/);
+      }
+      expect(loadMaintenanceWindows(db)).toEqual([]);
+    });
+  });
 });
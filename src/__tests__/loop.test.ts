@@ -6,6 +6,7 @@
 
 import { describe, it, expect, beforeEach, afterEach } from "vitest";
 import { runAgentLoop } from "../agent/loop.js";
+import { scheduleMaintenanceWindow } from "../survival/maintenance.js";
 import {
   MockInferenceClient,
   MockConwayClient,
@@ -191,4 +192,47 @@ describe("Agent Loop", () => {
     expect(inboxTurn).toBeDefined();
     expect(inboxTurn!.inputSource).toBe("agent");
   });
+
+  it("halts without inference during a maintenance window", async () => {
+    const window = scheduleMaintenanceWindow(db, {
+      startsAt: new Date(Date.now() - 60_000),
+      endsAt: new Date(Date.now() + 3_600_000),
+      reason: "test",
+    });
+    const inference = new MockInferenceClient([]);
+
+    await runAgentLoop({
+      identity,
+      config,
+      db,
+      conway,
+      inference,
+    });
+
+    expect(inference.calls).toHaveLength(0);
+    expect(db.getAgentState()).toBe("maintenance");
+    expect(db.getKV("sleep_until")).toBe(window.endsAt);
+  });
+
+  it("injects the wind-down prompt before a maintenance window", async () => {
+    scheduleMaintenanceWindow(db, {
+      startsAt: new Date(Date.now() + 10 * 60_000),
+      endsAt: new Date(Date.now() + 70 * 60_000),
+      reason: "test",
+      safePosture: "Close every perp position.",
+    });
+    const inference = new MockInferenceClient([noToolResponse("Winding down.")]);
+    const turns: AgentTurn[] = [];
+
+    await runAgentLoop({
+      identity,
+      config,
+      db,
+      conway,
+      inference,
+      onTurnComplete: (turn) => turns.push(turn),
+    });
+
+    expect(turns[0].input).toContain("Close every perp position.");
+  });
 });
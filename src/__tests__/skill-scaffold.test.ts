@@ -0,0 +1,97 @@
+/**
+ * Skill Scaffolding Tests
+ *
+ * Tests for `automaton skill new`.
+ */
+
+import fs from "fs";
+import os from "os";
+import path from "path";
+import { describe, it, expect } from "vitest";
+import { parseSkillMd } from "../skills/format.js";
+import {
+  addOracleImplementation,
+  scaffoldSkill,
+  validateSkillName,
+  writeSkillScaffold,
+} from "../skills/scaffold.js";
+
+const ORACLE_SKILL = `# Oracle
+
+## Implementations
+
+| Provider | Account type | Staleness |
+|----------|--------------|-----------|
+| Pyth | \`PriceUpdateV2\` | Seconds |
+
+## Skill Structure
+`;
+
+describe("scaffoldSkill", () => {
+  it("generates the shared layout", () => {
+    const files = scaffoldSkill("my-feed", "oracle").map((f) => f.path);
+
+    expect(files).toEqual([
+      "SKILL.md",
+      "templates/my-feed-oracle.rs",
+      "examples/on-chain/my-feed-example.rs",
+      "examples/off-chain/my-feed-client.rs",
+      "tests/my-feed.rs",
+    ]);
+  });
+
+  it("writes a SKILL.md the loader accepts", () => {
+    const skillMd = scaffoldSkill("my-feed", "defi")[0];
+    const skill = parseSkillMd(skillMd.content, "/skills/my-feed/SKILL.md");
+
+    expect(skill?.name).toBe("my-feed");
+    expect(skill?.description).toContain("MyFeed");
+    expect(skillMd.content).toContain("└── tests/my-feed.rs");
+  });
+
+  it("implements PriceSource for oracle skills", () => {
+    const template = scaffoldSkill("my-feed", "oracle")[1].content;
+
+    expect(template).toContain("impl PriceSource for MyFeedPrice");
+    expect(template).toContain("pub fn get_validated_my_feed_price(");
+  });
+
+  it("rejects names that are not kebab-case", () => {
+    for (const name of ["MyFeed", "my_feed", "-feed", "feed-", "../feed", ""]) {
+      expect(() => validateSkillName(name)).toThrow(/kebab-case/);
+    }
+    expect(() => validateSkillName("feed2-v2")).not.toThrow();
+  });
+});
+
+describe("addOracleImplementation", () => {
+  it("appends a row once", () => {
+    const updated = addOracleImplementation(ORACLE_SKILL, "my-feed");
+
+    expect(updated).toContain("| Pyth | `PriceUpdateV2` | Seconds |\n| MyFeed | `MyFeedPrice` | Seconds |\n\n## Skill");
+    expect(addOracleImplementation(updated, "my-feed")).toBe(updated);
+  });
+
+  it("leaves files without the table alone", () => {
+    expect(addOracleImplementation("# Oracle\n", "my-feed")).toBe("# Oracle\n");
+  });
+});
+
+describe("writeSkillScaffold", () => {
+  it("writes the skill and lists oracle providers", () => {
+    const root = fs.mkdtempSync(path.join(os.tmpdir(), "automaton-skills-"));
+    fs.mkdirSync(path.join(root, "oracle"));
+    fs.writeFileSync(path.join(root, "oracle", "SKILL.md"), ORACLE_SKILL);
+
+    const result = writeSkillScaffold(root, "my-feed", "oracle");
+    expect(result.written).toHaveLength(5);
+    expect(fs.existsSync(path.join(root, "my-feed", "examples", "off-chain", "my-feed-client.rs"))).toBe(true);
+    expect(result.wired).toBe(path.join(root, "oracle", "SKILL.md"));
+    expect(fs.readFileSync(result.wired!, "utf-8")).toContain("| MyFeed |");
+
+    // Never overwrites
+    expect(() => writeSkillScaffold(root, "my-feed", "oracle")).toThrow(/already exists/);
+
+    fs.rmSync(root, { recursive: true, force: true });
+  });
+});
@@ -29,6 +29,7 @@ import {
 } from "./tools.js";
 import { getSurvivalTier } from "../conway/credits.js";
 import { getUsdcBalance } from "../conway/x402.js";
+import { buildWindDownPrompt, getMaintenanceStatus } from "../survival/maintenance.js";
 import { ulid } from "ulid";
 
 const MAX_TOOL_CALLS_PER_TURN = 10;
@@ -115,6 +116,28 @@ export async function runAgentLoop(
         break;
       }
 
+      // Scheduled maintenance: wind down beforehand, halt during the window
+      const maintenance = getMaintenanceStatus(db);
+      if (maintenance.phase === "in_maintenance" && maintenance.window) {
+        log(config, `[MAINTENANCE] Halted until ${maintenance.window.endsAt}`);
+        db.setKV("sleep_until", maintenance.window.endsAt);
+        db.setAgentState("maintenance");
+        onStateChange?.("maintenance");
+        running = false;
+        break;
+      }
+      if (
+        maintenance.phase === "winding_down" &&
+        maintenance.window &&
+        db.getKV("maintenance_wind_down_prompted") !== maintenance.window.id
+      ) {
+        const windDown = buildWindDownPrompt(maintenance.window);
+        pendingInput = pendingInput
+          ? { ...pendingInput, content: `${windDown}\n\n${pendingInput.content}` }
+          : { content: windDown, source: "system" };
+        db.setKV("maintenance_wind_down_prompted", maintenance.window.id);
+      }
+
       // Check for unprocessed inbox messages
       if (!pendingInput) {
         const inboxMessages = db.getUnprocessedInboxMessages(5);
@@ -0,0 +1,189 @@
+/**
+ * Config Migration
+ *
+ * Upgrades automaton.json files written by older releases to the current
+ * schema. Each migration renames or removes fields; missing fields are then
+ * filled from DEFAULT_CONFIG. The report lists every change so operators
+ * can review an upgrade before writing it.
+ */
+
+import fs from "fs";
+import type { AutomatonConfig } from "./types.js";
+import { DEFAULT_CONFIG } from "./types.js";
+
+/** Schema version written by this release. Files without one are version 1. */
+export const CONFIG_SCHEMA_VERSION = 2;
+
+interface ConfigMigration {
+  to: number;
+  description: string;
+  renamed: Record<string, string>;
+  removed: string[];
+}
+
+const MIGRATIONS: ConfigMigration[] = [
+  {
+    to: 2,
+    description: "Versioned schema; normalize OpenRouter and Solana field names",
+    renamed: {
+      apiKey: "conwayApiKey",
+      model: "inferenceModel",
+      openRouterApiKey: "openrouterApiKey",
+      openRouterModel: "openrouterModel",
+      openRouterSiteUrl: "openrouterSiteUrl",
+      openRouterSiteName: "openrouterSiteName",
+      solanaAddress: "solanaWalletAddress",
+    },
+    removed: [],
+  },
+];
+
+/** Every field of the current schema */
+const KNOWN_FIELDS = new Set<string>([
+  "configVersion",
+  "name",
+  "genesisPrompt",
+  "creatorMessage",
+  "creatorAddress",
+  "registeredWithConway",
+  "sandboxId",
+  "conwayApiUrl",
+  "conwayApiKey",
+  "inferenceModel",
+  "maxTokensPerTurn",
+  "heartbeatConfigPath",
+  "dbPath",
+  "logLevel",
+  "walletAddress",
+  "version",
+  "skillsDir",
+  "agentId",
+  "maxChildren",
+  "parentAddress",
+  "socialRelayUrl",
+  "network",
+  "solanaNetwork",
+  "solanaWalletAddress",
+  "creatorSolanaAddress",
+  "inferenceProvider",
+  "openrouterApiKey",
+  "openrouterModel",
+  "openrouterSiteUrl",
+  "openrouterSiteName",
+]);
+
+export interface ConfigMigrationReport {
+  fromVersion: number;
+  toVersion: number;
+  /** Migration descriptions, oldest first */
+  applied: string[];
+  renamed: { from: string; to: string }[];
+  /** Removed fields; values are kept so they can be printed */
+  removed: { field: string; value: unknown }[];
+  defaultsApplied: { field: string; value: unknown }[];
+  /** Fields this release does not know about; left untouched */
+  unknown: string[];
+  changed: boolean;
+}
+
+/**
+ * Migrate a parsed config object. Pure: the input is not modified.
+ * Throws if the file was written by a newer release.
+ */
+export function migrateConfig(raw: Record<string, unknown>): {
+  config: Partial<AutomatonConfig>;
+  report: ConfigMigrationReport;
+} {
+  const fromVersion =
+    typeof raw.configVersion === "number" ? raw.configVersion : 1;
+  if (fromVersion > CONFIG_SCHEMA_VERSION) {
+    throw new Error(
+      `Config schema v${fromVersion} is newer than this release (v${CONFIG_SCHEMA_VERSION})`,
+    );
+  }
+
+  const config: Record<string, unknown> = { ...raw };
+  const report: ConfigMigrationReport = {
+    fromVersion,
+    toVersion: CONFIG_SCHEMA_VERSION,
+    applied: [],
+    renamed: [],
+    removed: [],
+    defaultsApplied: [],
+    unknown: [],
+    changed: false,
+  };
+
+  for (const migration of MIGRATIONS) {
+    if (migration.to <= fromVersion) continue;
+
+    for (const [from, to] of Object.entries(migration.renamed)) {
+      if (!(from in config)) continue;
+      // An explicit value under the new name wins
+      if (config[to] === undefined) config[to] = config[from];
+      delete config[from];
+      report.renamed.push({ from, to });
+    }
+    for (const field of migration.removed) {
+      if (!(field in config)) continue;
+      report.removed.push({ field, value: config[field] });
+      delete config[field];
+    }
+    report.applied.push(`v${migration.to}: ${migration.description}`);
+  }
+
+  for (const [field, value] of Object.entries(DEFAULT_CONFIG)) {
+    if (config[field] === undefined) {
+      config[field] = value;
+      report.defaultsApplied.push({ field, value });
+    }
+  }
+
+  config.configVersion = CONFIG_SCHEMA_VERSION;
+  report.unknown = Object.keys(config).filter((f) => !KNOWN_FIELDS.has(f));
+  report.changed =
+    fromVersion !== CONFIG_SCHEMA_VERSION ||
+    report.renamed.length > 0 ||
+    report.removed.length > 0 ||
+    report.defaultsApplied.length > 0;
+
+  return { config: config as Partial<AutomatonConfig>, report };
+}
+
+/**
+ * Migrate a config file in place. Unless `dryRun` is set, the original is
+ * copied to `<path>.v<fromVersion>.bak` before the new file is written.
+ */
+export function migrateConfigFile(
+  configPath: string,
+  options: { dryRun?: boolean } = {},
+): ConfigMigrationReport & { backupPath?: string } {
+  const raw = JSON.parse(fs.readFileSync(configPath, "utf-8"));
+  const { config, report } = migrateConfig(raw);
+  if (!report.changed || options.dryRun) return report;
+
+  const backupPath = `${configPath}.v${report.fromVersion}.bak`;
+  fs.copyFileSync(configPath, backupPath);
+  fs.writeFileSync(configPath, JSON.stringify(config, null, 2), {
+    mode: 0o600,
+  });
+  return { ...report, backupPath };
+}
+
+/**
+ * Human-readable summary of a migration report.
+ */
+export function formatMigrationReport(report: ConfigMigrationReport): string {
+  const lines = [`Config schema: v${report.fromVersion} -> v${report.toVersion}`];
+  for (const step of report.applied) lines.push(`  migration ${step}`);
+  for (const { from, to } of report.renamed) lines.push(`  renamed   ${from} -> ${to}`);
+  for (const { field, value } of report.removed) {
+    lines.push(`  removed   ${field} (was ${JSON.stringify(value)})`);
+  }
+  for (const { field, value } of report.defaultsApplied) {
+    lines.push(`  default   ${field} = ${JSON.stringify(value)}`);
+  }
+  for (const field of report.unknown) lines.push(`  unknown   ${field} (left as is)`);
+  if (!report.changed) lines.push("  Already up to date.");
+  return lines.join("\n");
+}
@@ -11,6 +11,7 @@ import type { Address } from "viem";
 import { DEFAULT_CONFIG } from "./types.js";
 import { getAutomatonDir } from "./identity/wallet.js";
 import { loadApiKeyFromConfig } from "./identity/provision.js";
+import { CONFIG_SCHEMA_VERSION, migrateConfig } from "./config-migration.js";
 
 const CONFIG_FILENAME = "automaton.json";
 
@@ -20,7 +21,8 @@ export function getConfigPath(): string {
 
 /**
  * Load the automaton config from disk.
- * Merges with defaults for any missing fields.
+ * Older schemas are migrated in memory (see `automaton config migrate`)
+ * and merged with defaults for any missing fields.
  */
 export function loadConfig(): AutomatonConfig | null {
   const configPath = getConfigPath();
@@ -29,7 +31,11 @@ export function loadConfig(): AutomatonConfig | null {
   }
 
   try {
-    const raw = JSON.parse(fs.readFileSync(configPath, "utf-8"));
+    const stored = JSON.parse(fs.readFileSync(configPath, "utf-8"));
+    const raw =
+      (stored.configVersion ?? 1) < CONFIG_SCHEMA_VERSION
+        ? migrateConfig(stored).config
+        : stored;
     const apiKey = raw.conwayApiKey || loadApiKeyFromConfig();
 
     return {
@@ -82,6 +88,7 @@ export function createConfig(params: {
   parentAddress?: Address;
 }): AutomatonConfig {
   return {
+    configVersion: CONFIG_SCHEMA_VERSION,
     name: params.name,
     genesisPrompt: params.genesisPrompt,
     creatorMessage: params.creatorMessage,
@@ -14,6 +14,7 @@ import type {
 } from "../types.js";
 import { getSurvivalTier } from "../conway/credits.js";
 import { getUsdcBalance } from "../conway/x402.js";
+import { advanceMaintenance, alertOperators } from "../survival/maintenance.js";
 
 export interface HeartbeatTaskContext {
   identity: AutomatonIdentity;
@@ -197,4 +198,19 @@ export const BUILTIN_TASKS: Record<string, HeartbeatTaskFn> = {
     return { shouldWake: false };
   },
 
+  check_maintenance: async (ctx) => {
+    const events = advanceMaintenance(ctx.db);
+    for (const event of events) {
+      await alertOperators(ctx.config, ctx.db, ctx.social, event.message);
+    }
+
+    // Wake to wind down before a window, and to resume after one
+    const wake = events.find(
+      (e) => e.kind === "wind_down_started" || e.kind === "maintenance_ended",
+    );
+    return wake
+      ? { shouldWake: true, message: wake.message }
+      : { shouldWake: false };
+  },
+
 };
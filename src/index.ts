@@ -7,9 +7,11 @@
  * the heartbeat daemon + agent loop.
  */
 
+import fs from "fs";
+import path from "path";
 import { getWallet, getAutomatonDir } from "./identity/wallet.js";
 import { provision, loadApiKeyFromConfig } from "./identity/provision.js";
-import { loadConfig, resolvePath, saveConfig } from "./config.js";
+import { getConfigPath, loadConfig, resolvePath, saveConfig } from "./config.js";
 import { createDatabase } from "./state/database.js";
 import { hasEnvConfig, loadConfigFromEnv } from "./setup/env-config.js";
 import { createConwayClient } from "./conway/client.js";
@@ -23,6 +25,7 @@ import { runAgentLoop } from "./agent/loop.js";
 import { loadSkills } from "./skills/loader.js";
 import { initStateRepo } from "./git/state-versioning.js";
 import { createSocialClient } from "./social/client.js";
+import { getMaintenanceStatus } from "./survival/maintenance.js";
 import type { AutomatonIdentity, AgentState, Skill, SocialClientInterface } from "./types.js";
 
 const VERSION = "0.1.0";
@@ -48,6 +51,10 @@ Usage:
   automaton --init         Initialize wallet and config directory
   automaton --provision    Provision Conway API key via SIWE
   automaton --status       Show current automaton status
+  automaton config migrate [--dry-run]
+                           Upgrade automaton.json to the current schema
+  automaton skill new <name> --kind oracle|defi|infra [--dir <skills dir>]
+                           Scaffold a skill (default dir: ./src/skills)
   automaton --version      Show version
   automaton --help         Show this help
 
@@ -86,6 +93,16 @@ Environment:
     process.exit(0);
   }
 
+  if (args[0] === "config") {
+    await configCommand(args.slice(1));
+    process.exit(0);
+  }
+
+  if (args[0] === "skill") {
+    await skillCommand(args.slice(1));
+    process.exit(0);
+  }
+
   if (args.includes("--setup")) {
     const { runSetupWizard } = await import("./setup/wizard.js");
     await runSetupWizard();
@@ -102,6 +119,74 @@ Environment:
   console.log('Run "automaton --run" to start the automaton.');
 }
 
+// ─── Config Command ────────────────────────────────────────────
+
+async function configCommand(args: string[]): Promise<void> {
+  if (args[0] !== "migrate") {
+    console.log("Usage: automaton config migrate [--dry-run]");
+    process.exit(1);
+  }
+
+  const { migrateConfigFile, formatMigrationReport } = await import(
+    "./config-migration.js"
+  );
+  const configPath = getConfigPath();
+  if (!fs.existsSync(configPath)) {
+    console.log(`No config at ${configPath}.`);
+    process.exit(1);
+  }
+
+  const dryRun = args.includes("--dry-run");
+  try {
+    const report = migrateConfigFile(configPath, { dryRun });
+    console.log(formatMigrationReport(report));
+    if (report.backupPath) {
+      console.log(`\nWrote ${configPath} (previous version: ${report.backupPath})`);
+    } else if (dryRun && report.changed) {
+      console.log("\nDry run; nothing written.");
+    }
+  } catch (err: any) {
+    console.error(`Config migration failed: ${err.message}`);
+    process.exit(1);
+  }
+}
+
+// ─── Skill Command ─────────────────────────────────────────────
+
+async function skillCommand(args: string[]): Promise<void> {
+  const usage = "Usage: automaton skill new <name> --kind oracle|defi|infra [--dir <skills dir>]";
+  const flag = (name: string): string | undefined => {
+    const index = args.indexOf(name);
+    return index === -1 ? undefined : args[index + 1];
+  };
+  const name = args[1];
+  const kind = flag("--kind");
+  if (args[0] !== "new" || !name || name.startsWith("--") || !kind) {
+    console.log(usage);
+    process.exit(1);
+  }
+
+  const { isSkillKind, writeSkillScaffold } = await import("./skills/scaffold.js");
+  if (!isSkillKind(kind)) {
+    console.error(`Unknown kind "${kind}".`);
+    console.log(usage);
+    process.exit(1);
+  }
+
+  const skillsRoot = path.resolve(flag("--dir") ?? path.join("src", "skills"));
+  try {
+    const result = writeSkillScaffold(skillsRoot, name, kind);
+    console.log(`Created ${result.skillDir}`);
+    for (const file of result.written) {
+      console.log(`  ${path.relative(result.skillDir, file)}`);
+    }
+    if (result.wired) console.log(`Listed in ${result.wired}`);
+  } catch (err: any) {
+    console.error(`Skill scaffold failed: ${err.message}`);
+    process.exit(1);
+  }
+}
+
 // ─── Status Command ────────────────────────────────────────────
 
 async function showStatus(): Promise<void> {
@@ -326,7 +411,7 @@ async function run(): Promise<void> {
         continue;
       }
 
-      if (state === "sleeping") {
+      if (state === "sleeping" || state === "maintenance") {
         const sleepUntilStr = db.getKV("sleep_until");
         const sleepUntil = sleepUntilStr
           ? new Date(sleepUntilStr).getTime()
@@ -343,6 +428,12 @@ async function run(): Promise<void> {
           await sleep(checkInterval);
           slept += checkInterval;
 
+          // Maintenance ignores wake requests, but ends early if cancelled
+          if (state === "maintenance") {
+            if (getMaintenanceStatus(db).phase !== "in_maintenance") break;
+            continue;
+          }
+
           // Check for wake request from heartbeat
           const wakeRequest = db.getKV("wake_request");
           if (wakeRequest) {
@@ -355,8 +446,9 @@ async function run(): Promise<void> {
           }
         }
 
-        // Clear sleep state
+        // Clear sleep state; the heartbeat's resume wake is already served
         db.deleteKV("sleep_until");
+        if (state === "maintenance") db.deleteKV("wake_request");
         continue;
       }
     } catch (err: any) {
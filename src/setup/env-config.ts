@@ -10,6 +10,7 @@ import type { AutomatonConfig } from "../types.js";
 import type { Address } from "viem";
 import { DEFAULT_CONFIG } from "../types.js";
 import { getAutomatonDir } from "../identity/wallet.js";
+import { CONFIG_SCHEMA_VERSION } from "../config-migration.js";
 
 export function hasEnvConfig(): boolean {
   return !!(
@@ -81,6 +82,7 @@ export function loadConfigFromEnv(): AutomatonConfig | null {
   }
 
   const config: AutomatonConfig = {
+    configVersion: CONFIG_SCHEMA_VERSION,
     name: process.env.AUTOMATON_NAME || "automaton",
     genesisPrompt,
     creatorMessage: process.env.CREATOR_MESSAGE,
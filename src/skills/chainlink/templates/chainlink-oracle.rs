@@ -0,0 +1,238 @@
+/**
+ * Chainlink Oracle Template for Anchor Programs
+ *
+ * Reads Chainlink OCR2 feed accounts on Solana directly (no CPI), checks
+ * round staleness, normalizes the feed decimals and validates through the
+ * same `get_validated_price` pipeline as Pyth, returning a `ValidatedPrice`.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    chainlink_solana = "2.0"
+ *    anchor-lang = "0.30.1"
+ *    pyth-oracle = { path = "../pyth-oracle", features = ["no-entrypoint"] }
+ *    (pyth-oracle is pyth/templates/anchor-oracle.rs built as a library)
+ *
+ * 2. Import this module in your program
+ * 3. Use `get_validated_chainlink_price` with one of the presets below
+ */
+
+use anchor_lang::prelude::*;
+use chainlink_solana::v2::read_feed_v2;
+use pyth_oracle::oracle::{Now, OraclePrice, PriceSource, PriceSourceError, StalenessMode};
+use pyth_oracle::{get_validated_price, PriceValidationConfig, ValidatedPrice};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Chainlink OCR2 store program ID (mainnet/devnet)
+pub const CHAINLINK_STORE_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny");
+
+/// Common feed accounts
+/// Full list: https://docs.chain.link/data-feeds/price-feeds/addresses?network=solana
+pub mod feeds {
+    pub const SOL_USD: &str = "CH31Xns5z3M1cTAbKW34jcxPPciazARpijcHj9rxtemt";
+}
+
+/// Most Chainlink Solana feeds have a heartbeat well above Pyth's 400ms, so
+/// staleness presets are measured in minutes rather than seconds
+pub const DEFAULT_MAX_ROUND_AGE_SECS: u64 = 300;
+
+// ============================================================================
+// CONFIG PRESETS
+// ============================================================================
+
+/// Chainlink equivalents of the Pyth `PriceValidationConfig` presets
+///
+/// Chainlink rounds carry no confidence interval, so the confidence limits
+/// are inherited unchanged and always pass.
+pub mod presets {
+    use super::*;
+
+    /// Default config (5 minute round age)
+    pub fn default() -> PriceValidationConfig {
+        PriceValidationConfig {
+            max_age_secs: DEFAULT_MAX_ROUND_AGE_SECS,
+            ..PriceValidationConfig::default()
+        }
+    }
+
+    /// Strict config for high-value operations (2 minute round age)
+    pub fn strict() -> PriceValidationConfig {
+        PriceValidationConfig {
+            max_age_secs: 120,
+            ..PriceValidationConfig::strict()
+        }
+    }
+
+    /// Lenient config for less critical operations (1 hour round age)
+    pub fn lenient() -> PriceValidationConfig {
+        PriceValidationConfig {
+            max_age_secs: 3_600,
+            ..PriceValidationConfig::lenient()
+        }
+    }
+
+    /// Pin the config to a specific feed account
+    pub fn with_feed_account(
+        mut config: PriceValidationConfig,
+        feed: &Pubkey,
+    ) -> PriceValidationConfig {
+        config.expected_feed_id = Some(feed.to_bytes());
+        config
+    }
+}
+
+// ============================================================================
+// FEED ACCOUNT
+// ============================================================================
+
+/// Latest round of a Chainlink feed, deserialized from the feed account
+#[derive(Clone, Debug)]
+pub struct ChainlinkFeed {
+    /// Feed account address (used as the feed identity)
+    pub address: Pubkey,
+    pub description: String,
+    pub decimals: u8,
+    pub round_id: u32,
+    /// Raw answer scaled by 10^decimals
+    pub answer: i128,
+    /// Unix timestamp of the round
+    pub timestamp: u32,
+    pub slot: u64,
+}
+
+impl ChainlinkFeed {
+    /// Deserialize an OCR2 feed account
+    pub fn load(feed_account: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(
+            *feed_account.owner,
+            CHAINLINK_STORE_PROGRAM_ID,
+            ChainlinkOracleError::InvalidFeedOwner
+        );
+
+        let data = feed_account.try_borrow_data()?;
+        let feed = read_feed_v2(data, feed_account.owner.to_bytes())
+            .map_err(|_| error!(ChainlinkOracleError::InvalidFeedAccount))?;
+        let round = feed
+            .latest_round_data()
+            .ok_or(error!(ChainlinkOracleError::NoRoundData))?;
+
+        Ok(Self {
+            address: feed_account.key(),
+            description: feed.description_string(),
+            decimals: feed.decimals(),
+            round_id: round.round_id,
+            answer: round.answer,
+            timestamp: round.timestamp,
+            slot: round.slot,
+        })
+    }
+
+    /// Normalize the answer into an i64 price and exponent
+    ///
+    /// Feeds with more precision than fits in an i64 lose the excess
+    /// trailing digits.
+    pub fn normalized(&self) -> Result<(i64, i32)> {
+        let mut answer = self.answer;
+        let mut exponent = -(self.decimals as i32);
+        while answer > i64::MAX as i128 || answer < i64::MIN as i128 {
+            answer /= 10;
+            exponent += 1;
+            require!(exponent <= 0, ChainlinkOracleError::AnswerOutOfRange);
+        }
+        Ok((answer as i64, exponent))
+    }
+}
+
+impl PriceSource for ChainlinkFeed {
+    fn feed_id(&self) -> [u8; 32] {
+        self.address.to_bytes()
+    }
+
+    fn staleness_mode(&self) -> StalenessMode {
+        StalenessMode::Seconds
+    }
+
+    fn get_price(&self, clock: &impl Now, max_age: u64) -> Result<OraclePrice> {
+        let publish_time = self.timestamp as i64;
+        let age = clock.unix_timestamp() - publish_time;
+        require!(
+            age >= 0 && (age as u64) <= max_age,
+            ChainlinkOracleError::RoundTooStale
+        );
+
+        let (price, exponent) = self.normalized()?;
+        Ok(OraclePrice {
+            price,
+            conf: 0,
+            exponent,
+            publish_time,
+        })
+    }
+
+    fn get_ema_price(&self, _clock: &impl Now, _max_age: u64) -> Result<OraclePrice> {
+        // OCR2 feeds publish a single aggregated answer per round
+        Err(error!(PriceSourceError::PriceUnavailable))
+    }
+}
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+/// Load and validate a Chainlink feed account
+pub fn get_validated_chainlink_price(
+    feed_account: &AccountInfo,
+    config: &PriceValidationConfig,
+    clock: &impl Now,
+) -> Result<ValidatedPrice> {
+    let feed = ChainlinkFeed::load(feed_account)?;
+    get_validated_price(&feed, config, clock)
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+/// Accounts for a single Chainlink feed operation
+#[derive(Accounts)]
+pub struct SingleChainlinkFeedContext<'info> {
+    /// The Chainlink OCR2 feed account
+    /// CHECK: owner and layout are verified by ChainlinkFeed::load
+    pub feed: AccountInfo<'info>,
+}
+
+/// Accounts for a dual feed operation (e.g., swaps)
+#[derive(Accounts)]
+pub struct DualChainlinkFeedContext<'info> {
+    /// Feed for the input asset
+    /// CHECK: owner and layout are verified by ChainlinkFeed::load
+    pub input_feed: AccountInfo<'info>,
+    /// Feed for the output asset
+    /// CHECK: owner and layout are verified by ChainlinkFeed::load
+    pub output_feed: AccountInfo<'info>,
+}
+
+// ============================================================================
+// ERROR CODES
+// ============================================================================
+
+#[error_code]
+pub enum ChainlinkOracleError {
+    #[msg("Feed account is not owned by the Chainlink store program")]
+    InvalidFeedOwner,
+
+    #[msg("Feed account could not be parsed")]
+    InvalidFeedAccount,
+
+    #[msg("Feed has no round data")]
+    NoRoundData,
+
+    #[msg("Latest round is too stale")]
+    RoundTooStale,
+
+    #[msg("Feed answer does not fit the target price format")]
+    AnswerOutOfRange,
+}
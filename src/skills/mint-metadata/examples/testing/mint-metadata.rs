@@ -0,0 +1,226 @@
+/**
+ * Tests for Mint Metadata Resolution
+ *
+ * Everything here is pure: account data is built in the test, so these
+ * cover the Metaplex and Token-2022 layouts, the fallback to the URI's
+ * JSON, and each finding of `check_mint` without an RPC.
+ *
+ * Setup:
+ * 1. Copy this file to `tests/mint-metadata.rs` of the mint-metadata crate
+ * 2. Run: cargo test --test mint-metadata
+ */
+
+use anchor_lang::solana_program::pubkey::Pubkey;
+use mint_metadata::{
+    check_mint, feed_for_symbol, Finding, MetadataError, MetadataSource, MintInfo, OffChainMetadata, TokenMetadata,
+    CANONICAL_MINTS,
+};
+use pyth_oracle::feed_registry;
+
+/// Base mint layout: initialized, 6 decimals
+fn mint_data(decimals: u8) -> Vec<u8> {
+    let mut data = vec![0u8; 82];
+    data[44] = decimals;
+    data[45] = 1;
+    data
+}
+
+fn push_string(data: &mut Vec<u8>, value: &str, padded_len: usize) {
+    let mut bytes = value.as_bytes().to_vec();
+    bytes.resize(padded_len.max(bytes.len()), 0);
+    data.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    data.extend_from_slice(&bytes);
+}
+
+/// Metaplex `MetadataV1`, strings padded as the program writes them
+fn metaplex_data(mint: &Pubkey, symbol: &str) -> Vec<u8> {
+    let mut data = vec![4u8];
+    data.extend_from_slice(&[1; 32]);
+    data.extend_from_slice(mint.as_ref());
+    push_string(&mut data, "Some Token", 32);
+    push_string(&mut data, symbol, 10);
+    push_string(&mut data, "https://example.com/token.json", 200);
+    data.extend_from_slice(&[0; 16]); // fee, creators, ...
+    data
+}
+
+/// Token-2022 mint with a metadata pointer and a metadata extension
+fn token_2022_data(mint: &Pubkey, symbol: &str) -> Vec<u8> {
+    let mut data = mint_data(9);
+    data.resize(165, 0);
+    data.push(1); // account type: mint
+
+    // Metadata pointer (18), skipped
+    data.extend_from_slice(&18u16.to_le_bytes());
+    data.extend_from_slice(&64u16.to_le_bytes());
+    data.extend_from_slice(&[0; 64]);
+
+    let mut value = vec![0u8; 32];
+    value.extend_from_slice(mint.as_ref());
+    push_string(&mut value, "Extension Token", 0);
+    push_string(&mut value, symbol, 0);
+    push_string(&mut value, "", 0);
+    value.extend_from_slice(&0u32.to_le_bytes()); // additional metadata
+    data.extend_from_slice(&19u16.to_le_bytes());
+    data.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    data.extend_from_slice(&value);
+    data
+}
+
+fn off_chain(symbol: &str) -> OffChainMetadata {
+    OffChainMetadata {
+        name: "From URI".to_string(),
+        symbol: symbol.to_string(),
+    }
+}
+
+fn canonical(base: &str) -> Pubkey {
+    CANONICAL_MINTS.iter().find(|(b, _)| *b == base).unwrap().1
+}
+
+// ============================================================================
+// PARSING
+// ============================================================================
+
+#[test]
+fn metaplex_metadata_is_parsed_without_padding() {
+    let mint = Pubkey::new_unique();
+    let metadata = TokenMetadata::parse_metaplex(&metaplex_data(&mint, "ABC")).unwrap();
+
+    assert_eq!(metadata.mint, mint);
+    assert_eq!(metadata.name, "Some Token");
+    assert_eq!(metadata.symbol, "ABC");
+    assert_eq!(metadata.uri, "https://example.com/token.json");
+}
+
+#[test]
+fn token_2022_extension_is_found_among_others() {
+    let mint = Pubkey::new_unique();
+    let metadata = TokenMetadata::parse_token_2022(&token_2022_data(&mint, "XYZ"))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(metadata.source, MetadataSource::Token2022Extension);
+    assert_eq!(metadata.symbol, "XYZ");
+    // A plain mint has no extensions
+    assert_eq!(TokenMetadata::parse_token_2022(&mint_data(6)).unwrap(), None);
+}
+
+#[test]
+fn malformed_accounts_are_rejected() {
+    let mint = Pubkey::new_unique();
+    let mut truncated = metaplex_data(&mint, "ABC");
+    truncated.truncate(100);
+    let mut wrong_key = metaplex_data(&mint, "ABC");
+    wrong_key[0] = 6;
+    let mut uninitialized = mint_data(6);
+    uninitialized[45] = 0;
+
+    assert!(TokenMetadata::parse_metaplex(&truncated).is_err());
+    assert!(TokenMetadata::parse_metaplex(&wrong_key).is_err());
+    assert!(matches!(
+        MintInfo::resolve(&mint, &uninitialized, None, None),
+        Err(MetadataError::InvalidMint)
+    ));
+}
+
+// ============================================================================
+// RESOLUTION
+// ============================================================================
+
+#[test]
+fn decimals_come_from_the_mint() {
+    let mint = Pubkey::new_unique();
+    let info = MintInfo::resolve(&mint, &mint_data(6), Some(&metaplex_data(&mint, "ABC")), None).unwrap();
+
+    assert_eq!((info.decimals, info.symbol.as_str()), (6, "ABC"));
+    assert_eq!(info.source, MetadataSource::Metaplex);
+}
+
+#[test]
+fn empty_on_chain_symbol_falls_back_to_the_uri() {
+    let mint = Pubkey::new_unique();
+    let info = MintInfo::resolve(&mint, &mint_data(6), Some(&metaplex_data(&mint, "")), Some(&off_chain("ABC")))
+        .unwrap();
+    assert_eq!((info.symbol.as_str(), info.source), ("ABC", MetadataSource::OffChain));
+
+    assert!(matches!(
+        MintInfo::resolve(&mint, &mint_data(6), Some(&metaplex_data(&mint, "")), None),
+        Err(MetadataError::NoSymbol)
+    ));
+}
+
+#[test]
+fn metadata_of_another_mint_is_rejected() {
+    let (mint, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+    assert!(matches!(
+        MintInfo::resolve(&mint, &mint_data(6), Some(&metaplex_data(&other, "ABC")), None),
+        Err(MetadataError::MintMismatch(m)) if m == other
+    ));
+}
+
+// ============================================================================
+// FEED MATCHING
+// ============================================================================
+
+#[test]
+fn symbols_map_to_usd_feeds() {
+    let sol = feed_for_symbol(" $sol ").unwrap();
+    assert_eq!(sol.feed.symbol, "SOL/USD");
+    assert_eq!(sol.alias_of, None);
+
+    let wsol = feed_for_symbol("WSOL").unwrap();
+    assert_eq!((wsol.feed.symbol, wsol.alias_of), ("SOL/USD", Some("SOL")));
+
+    assert!(feed_for_symbol("Crypto.SOL").is_none());
+    assert!(feed_for_symbol("NOT-A-TOKEN").is_none());
+}
+
+#[test]
+fn canonical_mint_passes_clean() {
+    let usdc = canonical("USDC");
+    let info = MintInfo::resolve(&usdc, &mint_data(6), Some(&metaplex_data(&usdc, "USDC")), Some(&off_chain("USDC")))
+        .unwrap();
+    let bound = feed_registry::feed_id("USDC/USD");
+
+    let (feed_match, findings) = check_mint(&info, Some(&bound));
+    assert_eq!(feed_match.unwrap().feed.feed_id, bound);
+    assert!(findings.is_empty());
+}
+
+#[test]
+fn impostor_and_mismatches_are_flagged() {
+    let fake = Pubkey::new_unique();
+    let info = MintInfo::resolve(&fake, &mint_data(6), Some(&metaplex_data(&fake, "USDC")), Some(&off_chain("USDT")))
+        .unwrap();
+    let bound = feed_registry::feed_id("SOL/USD");
+
+    let (_, findings) = check_mint(&info, Some(&bound));
+    assert_eq!(
+        findings,
+        vec![
+            Finding::SymbolsDiffer {
+                on_chain: "USDC".to_string(),
+                off_chain: "USDT".to_string(),
+            },
+            Finding::NotCanonicalMint {
+                canonical: canonical("USDC"),
+            },
+            Finding::BindingMismatch {
+                bound,
+                bound_symbol: Some("SOL/USD"),
+                expected: Some("USDC/USD"),
+            },
+        ]
+    );
+}
+
+#[test]
+fn unknown_symbol_has_no_feed() {
+    let mint = Pubkey::new_unique();
+    let info = MintInfo::resolve(&mint, &mint_data(6), Some(&metaplex_data(&mint, "ZZZQ")), None).unwrap();
+
+    let (feed_match, findings) = check_mint(&info, None);
+    assert!(feed_match.is_none());
+    assert_eq!(findings, vec![Finding::NoFeed]);
+}
@@ -0,0 +1,403 @@
+/**
+ * Mint Metadata - Symbol and Decimals for Any Mint
+ *
+ * Resolves what a mint calls itself and how many decimals it has, then
+ * picks the Pyth feed for it from the feed registry:
+ *
+ * 1. Decimals come from the mint account (Token and Token-2022 share the
+ *    base layout)
+ * 2. Name, symbol and URI come from the Metaplex Token Metadata account at
+ *    [METADATA_SEED, TOKEN_METADATA_PROGRAM_ID, mint], or from the
+ *    Token-2022 metadata extension in the mint itself
+ * 3. The JSON at the URI (off chain) fills in a symbol left empty on chain,
+ *    and is compared with it when both exist
+ * 4. The symbol is looked up as "<SYMBOL>/USD" in `feed_registry`
+ *
+ * A symbol is a claim, not an identity: anyone can mint a token called
+ * "USDC". `check_mint` flags a mint that claims the symbol of a known
+ * canonical mint, metadata that disagrees with itself, and a `FeedBinding`
+ * that points at another asset than the metadata names. Treat the
+ * suggested feed as input for a human to confirm before binding it.
+ *
+ * Setup:
+ * 1. Cargo.toml of the library crate:
+ *    [package]
+ *    name = "mint-metadata"
+ *
+ *    [dependencies]
+ *    anchor-lang = "0.30.1"
+ *    pyth-oracle = { path = "../pyth-oracle", features = ["no-entrypoint"] }
+ *    serde = { version = "1", features = ["derive"] }
+ *    thiserror = "1"
+ *
+ * 2. Fetch the accounts (and optionally the URI) yourself, or run
+ *    `automaton resolve-mint <MINT>` from the pyth skill
+ */
+
+use anchor_lang::solana_program::{self, pubkey::Pubkey};
+use pyth_oracle::feed_registry::{self, FeedInfo};
+use serde::Deserialize;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Metaplex Token Metadata program
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// PDA seed of metadata accounts: [METADATA_SEED, program ID, mint]
+pub const METADATA_SEED: &[u8] = b"metadata";
+
+/// `Key::MetadataV1`
+const METADATA_V1_KEY: u8 = 4;
+
+const MINT_LEN: usize = 82;
+const MINT_DECIMALS_OFFSET: usize = 44;
+const MINT_INITIALIZED_OFFSET: usize = 45;
+
+/// Token-2022 mints with extensions: the account type byte follows the
+/// base account length, then the extensions as type-length-value entries
+const ACCOUNT_TYPE_OFFSET: usize = 165;
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const EXTENSION_UNINITIALIZED: u16 = 0;
+const EXTENSION_TOKEN_METADATA: u16 = 19;
+
+/// Mints whose symbol others copy, by feed base. A mint claiming one of
+/// these symbols under another address is flagged.
+pub const CANONICAL_MINTS: &[(&str, Pubkey)] = &[
+    ("SOL", solana_program::pubkey!("So11111111111111111111111111111111111111112")),
+    ("USDC", solana_program::pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v")),
+    ("USDT", solana_program::pubkey!("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB")),
+    ("JUP", solana_program::pubkey!("JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN")),
+    ("BONK", solana_program::pubkey!("DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263")),
+    ("MSOL", solana_program::pubkey!("mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So")),
+    ("JITOSOL", solana_program::pubkey!("J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn")),
+];
+
+/// Symbols priced by another asset's feed when they have none of their own
+const ALIASES: &[(&str, &str)] = &[("WSOL", "SOL"), ("WETH", "ETH"), ("WBTC", "BTC")];
+
+#[derive(Debug, thiserror::Error)]
+pub enum MetadataError {
+    #[error("account is not an initialized token mint")]
+    InvalidMint,
+
+    #[error("metadata is truncated or malformed")]
+    InvalidMetadata,
+
+    #[error("metadata describes mint {0}")]
+    MintMismatch(Pubkey),
+
+    #[error("no symbol in on-chain or off-chain metadata")]
+    NoSymbol,
+}
+
+// ============================================================================
+// ON-CHAIN METADATA
+// ============================================================================
+
+/// Metaplex metadata account of `mint`
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[METADATA_SEED, TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Decimals of an initialized Token or Token-2022 mint
+pub fn mint_decimals(mint_data: &[u8]) -> Result<u8, MetadataError> {
+    if mint_data.len() < MINT_LEN || mint_data[MINT_INITIALIZED_OFFSET] != 1 {
+        return Err(MetadataError::InvalidMint);
+    }
+    Ok(mint_data[MINT_DECIMALS_OFFSET])
+}
+
+/// Where a mint's symbol came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetadataSource {
+    Metaplex,
+    Token2022Extension,
+    OffChain,
+}
+
+/// Name, symbol and URI as stored on chain, padding removed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenMetadata {
+    pub source: MetadataSource,
+    pub mint: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+impl TokenMetadata {
+    /// Parse a Metaplex Token Metadata account
+    pub fn parse_metaplex(data: &[u8]) -> Result<Self, MetadataError> {
+        let mut reader = Reader::new(data);
+        if reader.u8()? != METADATA_V1_KEY {
+            return Err(MetadataError::InvalidMetadata);
+        }
+        let _update_authority = reader.pubkey()?;
+        Self::read_fields(&mut reader, MetadataSource::Metaplex)
+    }
+
+    /// Parse the metadata extension of a Token-2022 mint; `None` if the
+    /// mint has no extensions or no metadata among them
+    pub fn parse_token_2022(mint_data: &[u8]) -> Result<Option<Self>, MetadataError> {
+        if mint_data.len() <= ACCOUNT_TYPE_OFFSET {
+            return Ok(None);
+        }
+        if mint_data[ACCOUNT_TYPE_OFFSET] != ACCOUNT_TYPE_MINT {
+            return Err(MetadataError::InvalidMint);
+        }
+
+        let mut reader = Reader::new(&mint_data[ACCOUNT_TYPE_OFFSET + 1..]);
+        while reader.remaining() >= 4 {
+            let extension = reader.u16()?;
+            let len = reader.u16()? as usize;
+            if extension == EXTENSION_UNINITIALIZED {
+                break;
+            }
+            let value = reader.take(len)?;
+            if extension == EXTENSION_TOKEN_METADATA {
+                let mut reader = Reader::new(value);
+                let _update_authority = reader.pubkey()?;
+                return Self::read_fields(&mut reader, MetadataSource::Token2022Extension).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn read_fields(reader: &mut Reader, source: MetadataSource) -> Result<Self, MetadataError> {
+        Ok(Self {
+            source,
+            mint: reader.pubkey()?,
+            name: reader.string()?,
+            symbol: reader.string()?,
+            uri: reader.string()?,
+        })
+    }
+}
+
+/// Borsh-style reader: little-endian integers, u32-prefixed strings
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], MetadataError> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or(MetadataError::InvalidMetadata)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, MetadataError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, MetadataError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn pubkey(&mut self) -> Result<Pubkey, MetadataError> {
+        Ok(Pubkey::new_from_array(self.take(32)?.try_into().unwrap()))
+    }
+
+    /// Metaplex pads strings with NULs to a fixed length
+    fn string(&mut self) -> Result<String, MetadataError> {
+        let len = u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as usize;
+        let text = std::str::from_utf8(self.take(len)?).map_err(|_| MetadataError::InvalidMetadata)?;
+        Ok(text.trim_end_matches('\0').trim().to_string())
+    }
+}
+
+// ============================================================================
+// OFF-CHAIN METADATA
+// ============================================================================
+
+/// The fields of the JSON at the metadata URI this module reads
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct OffChainMetadata {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub symbol: String,
+}
+
+// ============================================================================
+// RESOLUTION
+// ============================================================================
+
+/// What a mint says about itself
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MintInfo {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub name: String,
+    pub symbol: String,
+    /// Where `symbol` came from
+    pub source: MetadataSource,
+    /// Empty when the mint has no on-chain metadata
+    pub uri: String,
+    /// Symbol in the URI's JSON, when it was fetched
+    pub off_chain_symbol: Option<String>,
+}
+
+impl MintInfo {
+    /// Combine the mint account, its Metaplex metadata account (if it
+    /// exists), and the JSON at its URI (if fetched). Metaplex metadata
+    /// takes precedence over the Token-2022 extension.
+    pub fn resolve(
+        mint: &Pubkey,
+        mint_data: &[u8],
+        metaplex_data: Option<&[u8]>,
+        off_chain: Option<&OffChainMetadata>,
+    ) -> Result<Self, MetadataError> {
+        let decimals = mint_decimals(mint_data)?;
+        let on_chain = match metaplex_data {
+            Some(data) => Some(TokenMetadata::parse_metaplex(data)?),
+            None => TokenMetadata::parse_token_2022(mint_data)?,
+        };
+        if let Some(metadata) = &on_chain {
+            if metadata.mint != *mint {
+                return Err(MetadataError::MintMismatch(metadata.mint));
+            }
+        }
+
+        let off_chain_symbol = off_chain.map(|json| json.symbol.trim().to_string());
+        let (name, uri, on_chain_symbol) = match on_chain {
+            Some(metadata) => (metadata.name, metadata.uri, Some((metadata.symbol, metadata.source))),
+            None => (String::new(), String::new(), None),
+        };
+        let (symbol, source) = match (on_chain_symbol, &off_chain_symbol) {
+            (Some((symbol, source)), _) if !symbol.is_empty() => (symbol, source),
+            (_, Some(symbol)) if !symbol.is_empty() => (symbol.clone(), MetadataSource::OffChain),
+            _ => return Err(MetadataError::NoSymbol),
+        };
+        let name = match off_chain {
+            Some(json) if name.is_empty() => json.name.trim().to_string(),
+            _ => name,
+        };
+
+        Ok(Self {
+            mint: *mint,
+            decimals,
+            name,
+            symbol,
+            source,
+            uri,
+            off_chain_symbol,
+        })
+    }
+}
+
+// ============================================================================
+// FEED MATCHING
+// ============================================================================
+
+/// The registry feed picked for a symbol
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeedMatch {
+    pub feed: &'static FeedInfo,
+    /// Set when the symbol has no feed and is priced as this asset
+    pub alias_of: Option<&'static str>,
+}
+
+/// Upper case, without surrounding whitespace or a leading '$'
+fn normalize_symbol(symbol: &str) -> String {
+    symbol.trim().trim_start_matches('$').to_ascii_uppercase()
+}
+
+/// USD feed for a token symbol
+pub fn feed_for_symbol(symbol: &str) -> Option<FeedMatch> {
+    let symbol = normalize_symbol(symbol);
+    // A '.' would be read as a Pyth symbol ("Crypto.SOL/USD")
+    if symbol.is_empty() || symbol.contains(['.', '/']) {
+        return None;
+    }
+    if let Some(feed) = feed_registry::feed_info(&format!("{symbol}/USD")) {
+        return Some(FeedMatch { feed, alias_of: None });
+    }
+    let (_, base) = ALIASES.iter().find(|(alias, _)| *alias == symbol)?;
+    let feed = feed_registry::feed_info(&format!("{base}/USD"))?;
+    Some(FeedMatch {
+        feed,
+        alias_of: Some(base),
+    })
+}
+
+/// Something about a mint's metadata or binding that needs a human
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Finding {
+    /// The registry has no USD feed for the symbol
+    NoFeed,
+    /// On-chain and off-chain metadata name different symbols
+    SymbolsDiffer { on_chain: String, off_chain: String },
+    /// The symbol belongs to a well-known mint at another address
+    NotCanonicalMint { canonical: Pubkey },
+    /// The mint's `FeedBinding` points at another feed than the symbol's
+    BindingMismatch {
+        bound: [u8; 32],
+        bound_symbol: Option<&'static str>,
+        expected: Option<&'static str>,
+    },
+}
+
+/// Pick the feed for `info` and list findings. Pass the feed ID of the
+/// mint's `FeedBinding` to check it against the pick.
+pub fn check_mint(info: &MintInfo, bound_feed: Option<&[u8; 32]>) -> (Option<FeedMatch>, Vec<Finding>) {
+    let mut findings = Vec::new();
+
+    if let Some(off_chain) = &info.off_chain_symbol {
+        if info.source != MetadataSource::OffChain
+            && !off_chain.is_empty()
+            && normalize_symbol(off_chain) != normalize_symbol(&info.symbol)
+        {
+            findings.push(Finding::SymbolsDiffer {
+                on_chain: info.symbol.clone(),
+                off_chain: off_chain.clone(),
+            });
+        }
+    }
+
+    let feed_match = feed_for_symbol(&info.symbol);
+    match &feed_match {
+        None => findings.push(Finding::NoFeed),
+        Some(feed_match) => {
+            let canonical = CANONICAL_MINTS
+                .iter()
+                .find(|(base, _)| base.eq_ignore_ascii_case(feed_match.feed.base));
+            if let Some((_, canonical)) = canonical {
+                if *canonical != info.mint {
+                    findings.push(Finding::NotCanonicalMint { canonical: *canonical });
+                }
+            }
+        }
+    }
+
+    if let Some(bound) = bound_feed {
+        if feed_match.map(|m| m.feed.feed_id) != Some(*bound) {
+            findings.push(Finding::BindingMismatch {
+                bound: *bound,
+                bound_symbol: feed_registry::symbol_for(bound),
+                expected: feed_match.map(|m| m.feed.symbol),
+            });
+        }
+    }
+
+    (feed_match, findings)
+}
@@ -0,0 +1,291 @@
+/**
+ * Oracle-Agnostic Price Source
+ *
+ * A `PriceSource` trait that abstracts over oracle providers so programs can
+ * swap backends without rewriting their validation or valuation code.
+ * Provider accounts implement the trait and return a common `OraclePrice`.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    anchor-lang = "0.30.1"
+ *    pyth-solana-receiver-sdk = "0.3.0"
+ *
+ * 2. Declare the module next to your program (the Pyth anchor-oracle
+ *    template does this as `oracle`)
+ * 3. Write validation code against `PriceSource` instead of a provider type
+ *
+ * Staleness checks read the time through `Now` rather than `Clock`: pass
+ * the `Clock` sysvar (or `SysvarClock::get()?`) on chain and a `MockClock`
+ * in unit tests, which need no runtime to build one.
+ */
+
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::error::GetPriceError;
+use pyth_solana_receiver_sdk::price_update::{Price, PriceUpdateV2, VerificationLevel};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Approximate Solana slot duration used to convert seconds to slots
+pub const SLOT_DURATION_MS: u64 = 400;
+
+/// First `PriceSourceError` code. Anchor numbers every `#[error_code]` enum
+/// from 6000 unless told otherwise, so the host program's own errors, the
+/// receiver SDK's and these would share codes. Keep in step with the
+/// `offset` on `PriceSourceError` below (the attribute takes a literal).
+pub const PRICE_SOURCE_ERROR_OFFSET: u32 = 9900;
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// Provider-neutral price in fixed-point format (price × 10^exponent)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct OraclePrice {
+    /// Price value in fixed-point format
+    pub price: i64,
+    /// Confidence interval (same units as price)
+    pub conf: u64,
+    /// Exponent for scaling
+    pub exponent: i32,
+    /// Unix timestamp at which the price was published
+    pub publish_time: i64,
+}
+
+impl From<Price> for OraclePrice {
+    fn from(price: Price) -> Self {
+        Self {
+            price: price.price,
+            conf: price.conf,
+            exponent: price.exponent,
+            publish_time: price.publish_time,
+        }
+    }
+}
+
+/// How a source measures staleness
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StalenessMode {
+    /// `max_age` is wall-clock seconds since publish time
+    Seconds,
+    /// `max_age` is slots since the update slot
+    Slots,
+}
+
+impl StalenessMode {
+    /// Convert a maximum age in seconds into this mode's units
+    pub fn max_age_from_secs(&self, max_age_secs: u64) -> u64 {
+        match self {
+            StalenessMode::Seconds => max_age_secs,
+            StalenessMode::Slots => max_age_secs.saturating_mul(1000) / SLOT_DURATION_MS,
+        }
+    }
+}
+
+// ============================================================================
+// TIME
+// ============================================================================
+
+/// The current time and slot, as staleness checks see them
+pub trait Now {
+    /// Unix timestamp in seconds
+    fn unix_timestamp(&self) -> i64;
+
+    /// Current slot
+    fn slot(&self) -> u64;
+
+    /// As a `Clock`, for APIs that take one (the receiver SDK). Fields
+    /// other than the time and slot are zero.
+    fn to_clock(&self) -> Clock {
+        Clock {
+            slot: self.slot(),
+            unix_timestamp: self.unix_timestamp(),
+            ..Clock::default()
+        }
+    }
+}
+
+impl Now for Clock {
+    fn unix_timestamp(&self) -> i64 {
+        self.unix_timestamp
+    }
+
+    fn slot(&self) -> u64 {
+        self.slot
+    }
+
+    fn to_clock(&self) -> Clock {
+        self.clone()
+    }
+}
+
+/// The `Clock` sysvar, read once
+#[derive(Clone, Debug)]
+pub struct SysvarClock(pub Clock);
+
+impl SysvarClock {
+    pub fn get() -> Result<Self> {
+        Ok(Self(Clock::get()?))
+    }
+}
+
+impl Now for SysvarClock {
+    fn unix_timestamp(&self) -> i64 {
+        self.0.unix_timestamp
+    }
+
+    fn slot(&self) -> u64 {
+        self.0.slot
+    }
+
+    fn to_clock(&self) -> Clock {
+        self.0.clone()
+    }
+}
+
+/// A fixed time and slot for tests
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MockClock {
+    pub unix_timestamp: i64,
+    pub slot: u64,
+}
+
+impl MockClock {
+    pub fn new(unix_timestamp: i64, slot: u64) -> Self {
+        Self { unix_timestamp, slot }
+    }
+
+    /// Move forward by `secs` seconds and the slots they take
+    pub fn advance_secs(&mut self, secs: u64) {
+        self.unix_timestamp = self.unix_timestamp.saturating_add(secs as i64);
+        self.slot = self.slot.saturating_add(secs.saturating_mul(1000) / SLOT_DURATION_MS);
+    }
+
+    /// Move forward by `slots` slots without the timestamp changing, as
+    /// when validator clocks lag
+    pub fn advance_slots(&mut self, slots: u64) {
+        self.slot = self.slot.saturating_add(slots);
+    }
+}
+
+impl Now for MockClock {
+    fn unix_timestamp(&self) -> i64 {
+        self.unix_timestamp
+    }
+
+    fn slot(&self) -> u64 {
+        self.slot
+    }
+}
+
+// ============================================================================
+// TRAIT
+// ============================================================================
+
+/// A source of oracle prices
+pub trait PriceSource {
+    /// 32-byte identity of the feed this account serves
+    fn feed_id(&self) -> [u8; 32];
+
+    /// Units in which `max_age` is interpreted
+    fn staleness_mode(&self) -> StalenessMode;
+
+    /// Latest price, rejecting it if older than `max_age`
+    fn get_price(&self, clock: &impl Now, max_age: u64) -> Result<OraclePrice>;
+
+    /// Latest EMA price, rejecting it if older than `max_age`
+    fn get_ema_price(&self, clock: &impl Now, max_age: u64) -> Result<OraclePrice>;
+
+    /// Slot the price was written on chain in, for slot-based staleness
+    ///
+    /// Sources that do not record it return `None`.
+    fn posted_slot(&self) -> Option<u64> {
+        None
+    }
+
+    /// Reject updates below the required verification level
+    ///
+    /// Sources without a signature-based verification scheme accept any level.
+    fn check_verification(&self, _level: &VerificationLevel) -> Result<()> {
+        Ok(())
+    }
+}
+
+// ============================================================================
+// PYTH
+// ============================================================================
+
+impl PriceSource for PriceUpdateV2 {
+    fn feed_id(&self) -> [u8; 32] {
+        self.price_message.feed_id
+    }
+
+    fn staleness_mode(&self) -> StalenessMode {
+        StalenessMode::Seconds
+    }
+
+    fn get_price(&self, clock: &impl Now, max_age: u64) -> Result<OraclePrice> {
+        Ok(self
+            .get_price_no_older_than(&clock.to_clock(), max_age)
+            .map_err(PriceSourceError::from)?
+            .into())
+    }
+
+    fn get_ema_price(&self, clock: &impl Now, max_age: u64) -> Result<OraclePrice> {
+        Ok(self
+            .get_ema_price_no_older_than(&clock.to_clock(), max_age)
+            .map_err(PriceSourceError::from)?
+            .into())
+    }
+
+    fn posted_slot(&self) -> Option<u64> {
+        Some(self.posted_slot)
+    }
+
+    fn check_verification(&self, level: &VerificationLevel) -> Result<()> {
+        require!(
+            self.verification_level.gte(*level),
+            PriceSourceError::InsufficientVerification
+        );
+        Ok(())
+    }
+}
+
+// ============================================================================
+// ERROR CODES
+// ============================================================================
+
+#[error_code(offset = 9900)]
+pub enum PriceSourceError {
+    #[msg("Price update verification level is insufficient")]
+    InsufficientVerification,
+
+    #[msg("Price source returned no price")]
+    PriceUnavailable,
+
+    #[msg("Price is too stale")]
+    PriceTooStale,
+
+    #[msg("Feed ID mismatch")]
+    FeedIdMismatch,
+
+    #[msg("Invalid feed ID format")]
+    InvalidFeedId,
+}
+
+/// The receiver SDK's errors, renumbered into this enum's range. Variant
+/// names follow `OracleError`, so a stale price reads `PriceTooStale`
+/// whichever check caught it.
+impl From<GetPriceError> for PriceSourceError {
+    fn from(error: GetPriceError) -> Self {
+        match error {
+            GetPriceError::PriceTooOld => PriceSourceError::PriceTooStale,
+            GetPriceError::MismatchedFeedId => PriceSourceError::FeedIdMismatch,
+            GetPriceError::InsufficientVerificationLevel => PriceSourceError::InsufficientVerification,
+            GetPriceError::FeedIdMustBe32Bytes | GetPriceError::FeedIdNonHexCharacter => {
+                PriceSourceError::InvalidFeedId
+            }
+        }
+    }
+}
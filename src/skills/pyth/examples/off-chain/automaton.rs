@@ -0,0 +1,267 @@
+/**
+ * Automaton - One Binary for the Off-Chain Oracle Tools
+ *
+ * Routes subcommands to the keeper, monitors, and CLI tools in this
+ * directory, so operators build and deploy a single artifact:
+ *
+ *   liquidator                      stream-driven liquidation keeper
+ *   pusher <FEED>... [flags]        post price updates ahead of consumers
+ *   event-indexer <PROGRAM_ID> [DB] index price events into SQLite
+ *   price-events <PROGRAM_ID>       print price events as they land
+ *   replay-failure <SIGNATURE> ...  explain a failed price validation
+ *   feed-analytics record|analyze|recommend|backfill|history|suggest ...
+ *                                   per-feed staleness, confidence, history,
+ *                                   and risk parameter suggestions
+ *   verify-observations <LOG>       audit an observation log via Hermes
+ *   market-check <IN> <FEED> <OUT> <FEED> <AMOUNT>
+ *                                   compare a Jupiter quote with Pyth
+ *   resolve-mint <MINT>... [--bindings]
+ *                                   pick the feed for a mint from its metadata
+ *   simulate price|swap ...         dry-run an instruction, decode its prices
+ *   trigger-watcher                 execute trigger orders once crossed
+ *
+ * Every subcommand gets the same `Context`: the RPC and WebSocket
+ * endpoints, the signer, the metrics registry, and the price stream of the
+ * keepers, set once by global flags before the subcommand or by the
+ * environment:
+ *
+ *   --rpc <URL>       SOLANA_RPC      (default mainnet-beta)
+ *   --ws <URL>        SOLANA_WS       (default: derived from the RPC URL)
+ *   --keypair <PATH>  SOLANA_KEYPAIR  (only for subcommands that sign)
+ *   --metrics <ADDR>  METRICS_ADDR    (serve /metrics and /healthz, e.g. 0.0.0.0:9100)
+ *   --prices <SRC>    PRICE_STREAM    (hermes, the default, or accounts: watch
+ *                                      the push feed accounts over --ws)
+ *
+ * Setup:
+ * 1. Add to Cargo.toml of the template crate:
+ *    [[bin]]
+ *    name = "automaton"
+ *    path = "examples/off-chain/automaton.rs"
+ *
+ *    [dependencies]
+ *    anchor-lang = "0.30.1"
+ *    base64 = "0.21"
+ *    bincode = "1"
+ *    borsh = "0.10"
+ *    futures = "0.3"
+ *    mint-metadata = { path = "../mint-metadata" }   # mint-metadata/templates/mint-metadata.rs
+ *    pyth-oracle = { path = "../..", features = ["no-entrypoint", "hermes", "client"] }
+ *    pyth-solana-receiver-sdk = "0.3.0"
+ *    reqwest = { version = "0.11", features = ["blocking", "json"] }
+ *    rusqlite = { version = "0.31", features = ["bundled"] }
+ *    serde = { version = "1", features = ["derive"] }
+ *    serde_json = "1"
+ *    solana-account-decoder = "1.18"
+ *    solana-client = "1.18"
+ *    solana-sdk = "1.18"
+ *    solana-transaction-status = "1.18"
+ *    tokio = { version = "1", features = ["macros", "rt-multi-thread"] }
+ *    toml = "0.8"
+ *
+ * 2. Run:
+ *    automaton --rpc $RPC --keypair keeper.json --metrics 0.0.0.0:9100 liquidator
+ *    automaton --rpc $RPC --prices accounts --keypair keeper.json trigger-watcher
+ *    automaton event-indexer <PROGRAM_ID> oracle-events.db
+ */
+
+use futures::Stream;
+use pyth_oracle::account_stream::AccountWatcher;
+use pyth_oracle::client::push_feed_address;
+use pyth_oracle::hermes::{HermesClient, HermesResult};
+use pyth_oracle::hermes_stream::{StreamConfig, StreamedUpdate};
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::signature::{read_keypair_file, Keypair};
+use std::pin::Pin;
+
+#[path = "event-indexer.rs"]
+mod event_indexer;
+
+#[path = "feed-analytics.rs"]
+mod feed_analytics;
+
+#[path = "liquidator.rs"]
+mod liquidator;
+
+#[path = "market-check.rs"]
+mod market_check;
+
+#[path = "metrics.rs"]
+mod metrics;
+pub use metrics::Metrics;
+
+#[path = "price-events.rs"]
+mod price_events;
+
+#[path = "pusher.rs"]
+mod pusher;
+
+#[path = "replay-failure.rs"]
+mod replay_failure;
+
+#[path = "resolve-mint.rs"]
+mod resolve_mint;
+
+#[path = "simulate.rs"]
+mod simulate;
+
+#[path = "trigger-watcher.rs"]
+mod trigger_watcher;
+
+#[path = "verify-observations.rs"]
+mod verify_observations;
+
+pub type CliResult = Result<(), Box<dyn std::error::Error>>;
+
+const DEFAULT_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+
+// Hermes endpoint (production)
+const HERMES_ENDPOINT: &str = "https://hermes.pyth.network";
+
+// ============================================================================
+// Context
+// ============================================================================
+
+/// Where the keepers stream prices from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriceStreamSource {
+    /// Hermes server-sent events
+    Hermes,
+    /// The sponsored push feed accounts, the ones the program reads
+    Accounts,
+}
+
+impl std::str::FromStr for PriceStreamSource {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        match source {
+            "hermes" => Ok(Self::Hermes),
+            "accounts" => Ok(Self::Accounts),
+            _ => Err(format!("unknown price stream {source}: expected hermes or accounts")),
+        }
+    }
+}
+
+pub type PriceStream = Pin<Box<dyn Stream<Item = HermesResult<StreamedUpdate>>>>;
+
+/// What every subcommand shares
+pub struct Context {
+    pub rpc_url: String,
+    pub ws_url: String,
+    keypair_path: Option<String>,
+    pub metrics: Metrics,
+    pub prices: PriceStreamSource,
+}
+
+impl Context {
+    pub fn rpc(&self) -> RpcClient {
+        RpcClient::new(self.rpc_url.clone())
+    }
+
+    pub fn nonblocking_rpc(&self) -> NonblockingRpcClient {
+        NonblockingRpcClient::new(self.rpc_url.clone())
+    }
+
+    /// The configured signer; only subcommands that send transactions ask
+    pub fn signer(&self) -> Result<Keypair, Box<dyn std::error::Error>> {
+        let path = self
+            .keypair_path
+            .as_deref()
+            .ok_or("this subcommand signs: pass --keypair or set SOLANA_KEYPAIR")?;
+        read_keypair_file(path).map_err(|e| format!("cannot read keypair {path}: {e}").into())
+    }
+
+    /// Prices of `feed_ids` from the configured source; the items are the
+    /// same either way
+    pub fn price_stream(&self, feed_ids: Vec<[u8; 32]>) -> PriceStream {
+        match self.prices {
+            PriceStreamSource::Hermes => {
+                Box::pin(HermesClient::new(HERMES_ENDPOINT).subscribe_price_updates(feed_ids, StreamConfig::default()))
+            }
+            PriceStreamSource::Accounts => {
+                let addresses = feed_ids.iter().map(push_feed_address).collect();
+                let watcher = AccountWatcher::new(&self.rpc_url, &self.ws_url);
+                Box::pin(watcher.subscribe_price_updates(addresses, StreamConfig::default()))
+            }
+        }
+    }
+}
+
+/// `https://` to `wss://`, `http://` to `ws://`
+fn ws_url_for(rpc_url: &str) -> String {
+    match rpc_url.split_once("://") {
+        Some(("https", rest)) => format!("wss://{rest}"),
+        Some(("http", rest)) => format!("ws://{rest}"),
+        _ => rpc_url.to_string(),
+    }
+}
+
+/// Global flags, with the environment as fallback; returns the context and
+/// the subcommand with its arguments
+fn parse_context(args: &[String]) -> Result<(Context, &[String]), Box<dyn std::error::Error>> {
+    let mut rpc_url = std::env::var("SOLANA_RPC").ok();
+    let mut ws_url = std::env::var("SOLANA_WS").ok();
+    let mut keypair_path = std::env::var("SOLANA_KEYPAIR").ok();
+    let mut metrics_addr = std::env::var("METRICS_ADDR").ok();
+    let mut prices = std::env::var("PRICE_STREAM").ok();
+
+    let mut rest = args;
+    while let [flag, value, tail @ ..] = rest {
+        let slot = match flag.as_str() {
+            "--rpc" => &mut rpc_url,
+            "--ws" => &mut ws_url,
+            "--keypair" => &mut keypair_path,
+            "--metrics" => &mut metrics_addr,
+            "--prices" => &mut prices,
+            _ => break,
+        };
+        *slot = Some(value.clone());
+        rest = tail;
+    }
+
+    let rpc_url = rpc_url.unwrap_or_else(|| DEFAULT_RPC_URL.to_string());
+    let context = Context {
+        ws_url: ws_url.unwrap_or_else(|| ws_url_for(&rpc_url)),
+        rpc_url,
+        keypair_path,
+        metrics: Metrics::default(),
+        prices: prices.as_deref().unwrap_or("hermes").parse()?,
+    };
+    if let Some(addr) = metrics_addr {
+        context.metrics.serve(&addr)?;
+    }
+    Ok((context, rest))
+}
+
+// ============================================================================
+// Main
+// ============================================================================
+
+const USAGE: &str = "usage: automaton [--rpc URL] [--ws URL] [--keypair PATH] [--metrics ADDR]
+       [--prices hermes|accounts] <SUBCOMMAND> [ARGS...]
+subcommands: liquidator, pusher, event-indexer, price-events, replay-failure, feed-analytics, verify-observations,
+market-check, resolve-mint, simulate, trigger-watcher";
+
+fn main() -> CliResult {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (context, rest) = parse_context(&args)?;
+    let (command, args) = rest.split_first().ok_or(USAGE)?;
+
+    // Async subcommands get a runtime; the blocking ones must run outside it
+    let runtime = || tokio::runtime::Runtime::new();
+    match command.as_str() {
+        "liquidator" => runtime()?.block_on(liquidator::run(&context, args)),
+        "pusher" => runtime()?.block_on(pusher::run(&context, args)),
+        "feed-analytics" => runtime()?.block_on(feed_analytics::run(&context, args)),
+        "simulate" => runtime()?.block_on(simulate::run(&context, args)),
+        "trigger-watcher" => runtime()?.block_on(trigger_watcher::run(&context, args)),
+        "event-indexer" => event_indexer::run(&context, args),
+        "price-events" => price_events::run(&context, args),
+        "replay-failure" => replay_failure::run(&context, args),
+        "verify-observations" => verify_observations::run(&context, args),
+        "market-check" => market_check::run(&context, args),
+        "resolve-mint" => resolve_mint::run(&context, args),
+        _ => Err(USAGE.into()),
+    }
+}
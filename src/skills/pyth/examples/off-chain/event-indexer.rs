@@ -0,0 +1,319 @@
+/**
+ * Oracle Event Indexer - SQLite Audit Trail
+ *
+ * Persists the price events of a program built from anchor-oracle.rs to
+ * SQLite: every price consumed (`PriceConsumed`), every price rejected
+ * (`PriceRejected`), circuit breaker trips (`CircuitBreakerTripped`), and
+ * oracle config changes (`OracleConfigUpdated`). Risk teams can then query
+ * which price each liquidation used, how often a feed was refused, or
+ * what the limits were at any slot.
+ *
+ * On start it backfills every transaction since the last one it indexed
+ * (`getSignaturesForAddress` + `getTransaction`), then follows new ones
+ * through a log subscription. Each event row is keyed by (signature,
+ * index in the transaction), so replaying a transaction is harmless.
+ *
+ * Events are decoded with the program crate's own types. A
+ * `PriceConsumed` from a failed transaction did not take effect, so every
+ * row records whether its transaction succeeded.
+ *
+ * Example queries:
+ *   -- Prices each liquidation used
+ *   SELECT signature, subject, feed_id, price, conf, exponent, publish_time
+ *   FROM price_consumed WHERE used_for = 'Liquidation' AND succeeded = 1;
+ *
+ *   -- Rejections per feed and reason over the last day
+ *   SELECT feed_id, reason, COUNT(*) FROM price_rejected
+ *   WHERE timestamp > strftime('%s', 'now') - 86400 GROUP BY 1, 2;
+ *
+ * Metrics: `events_indexed` and `transactions_seen`, plus RPC errors
+ * (metrics.rs). Ready once the backfill is done.
+ *
+ * Run (a subcommand of automaton.rs, which lists the dependencies):
+ * automaton --rpc $RPC --ws $WS event-indexer <PROGRAM_ID> [oracle-events.db]
+ */
+
+use anchor_lang::{AnchorDeserialize, Event};
+use base64::Engine;
+use pyth_oracle::{CircuitBreakerTripped, OracleConfigUpdated, PriceConsumed, PriceRejected};
+use rusqlite::{params, Connection, OptionalExtension};
+use solana_client::pubsub_client::PubsubClient;
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::{RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::UiTransactionEncoding;
+use std::str::FromStr;
+
+use crate::{CliResult, Context};
+
+// Signatures per getSignaturesForAddress page (RPC maximum)
+const SIGNATURE_PAGE: usize = 1_000;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS price_consumed (
+    signature TEXT NOT NULL, event_index INTEGER NOT NULL, slot INTEGER NOT NULL,
+    succeeded INTEGER NOT NULL, feed_id TEXT NOT NULL, price_update TEXT NOT NULL,
+    price INTEGER NOT NULL, conf INTEGER NOT NULL, exponent INTEGER NOT NULL,
+    publish_time INTEGER NOT NULL, used_for TEXT NOT NULL, subject TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE TABLE IF NOT EXISTS price_rejected (
+    signature TEXT NOT NULL, event_index INTEGER NOT NULL, slot INTEGER NOT NULL,
+    succeeded INTEGER NOT NULL, feed_id TEXT NOT NULL, price_update TEXT NOT NULL,
+    reason TEXT NOT NULL, error_code INTEGER NOT NULL, used_for TEXT NOT NULL,
+    subject TEXT NOT NULL, timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE TABLE IF NOT EXISTS breaker_trips (
+    signature TEXT NOT NULL, event_index INTEGER NOT NULL, slot INTEGER NOT NULL,
+    succeeded INTEGER NOT NULL, feed_id TEXT NOT NULL, status TEXT NOT NULL,
+    reference_price INTEGER NOT NULL, reference_exponent INTEGER NOT NULL,
+    price INTEGER NOT NULL, exponent INTEGER NOT NULL, deviation_bps INTEGER NOT NULL,
+    elapsed_secs INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE TABLE IF NOT EXISTS config_updates (
+    signature TEXT NOT NULL, event_index INTEGER NOT NULL, slot INTEGER NOT NULL,
+    succeeded INTEGER NOT NULL, max_age_secs INTEGER NOT NULL,
+    max_confidence_bps INTEGER NOT NULL, verification_level TEXT NOT NULL,
+    overrides INTEGER NOT NULL, timelock_secs INTEGER NOT NULL, timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS price_consumed_feed ON price_consumed (feed_id, slot);
+CREATE INDEX IF NOT EXISTS price_rejected_feed ON price_rejected (feed_id, slot);
+CREATE TABLE IF NOT EXISTS cursor (id INTEGER PRIMARY KEY CHECK (id = 1), signature TEXT NOT NULL);
+";
+
+// ============================================================================
+// Decoding
+// ============================================================================
+
+/// A decoded event of the indexed program
+#[derive(Debug)]
+pub enum OracleEvent {
+    Consumed(PriceConsumed),
+    Rejected(PriceRejected),
+    BreakerTripped(CircuitBreakerTripped),
+    ConfigUpdated(OracleConfigUpdated),
+}
+
+fn decode_as<E: Event + AnchorDeserialize>(data: &[u8]) -> Option<E> {
+    let body = data.strip_prefix(E::DISCRIMINATOR.as_slice())?;
+    E::try_from_slice(body).ok()
+}
+
+/// Decode one `Program data:` payload; `None` for other events
+pub fn decode_event(data: &[u8]) -> Option<OracleEvent> {
+    decode_as(data)
+        .map(OracleEvent::Consumed)
+        .or_else(|| decode_as(data).map(OracleEvent::Rejected))
+        .or_else(|| decode_as(data).map(OracleEvent::BreakerTripped))
+        .or_else(|| decode_as(data).map(OracleEvent::ConfigUpdated))
+}
+
+/// Events `program_id` logged in one transaction, in order. Lines logged
+/// while another program runs (a CPI) are skipped, so it cannot forge them.
+pub fn events_in_logs(program_id: &Pubkey, logs: &[String]) -> Vec<OracleEvent> {
+    let program = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(data) = rest.strip_prefix("data: ") {
+            if stack.last() != Some(&program.as_str()) {
+                continue;
+            }
+            let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(data) else {
+                continue;
+            };
+            events.extend(decode_event(&bytes));
+        } else if let Some((id, tail)) = rest.split_once(' ') {
+            if tail.starts_with("invoke [") {
+                stack.push(id);
+            } else if tail == "success" || tail.starts_with("failed") {
+                stack.pop();
+            }
+        }
+    }
+    events
+}
+
+fn hex(bytes: &[u8; 32]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+pub fn open(path: &str) -> rusqlite::Result<Connection> {
+    let db = Connection::open(path)?;
+    db.execute_batch(SCHEMA)?;
+    Ok(db)
+}
+
+/// Store the events of one transaction and advance the cursor to it
+pub fn index_transaction(
+    db: &mut Connection,
+    program_id: &Pubkey,
+    signature: &str,
+    slot: u64,
+    succeeded: bool,
+    logs: &[String],
+) -> rusqlite::Result<usize> {
+    let events = events_in_logs(program_id, logs);
+    let tx = db.transaction()?;
+    for (index, event) in events.iter().enumerate() {
+        let key = (signature, index as i64, slot as i64, succeeded);
+        match event {
+            OracleEvent::Consumed(e) => tx.execute(
+                "INSERT OR IGNORE INTO price_consumed VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                params![
+                    key.0, key.1, key.2, key.3,
+                    hex(&e.feed_id), e.price_update.to_string(), e.price, e.conf as i64, e.exponent,
+                    e.publish_time, format!("{:?}", e.used_for), e.subject.to_string(), e.timestamp
+                ],
+            )?,
+            OracleEvent::Rejected(e) => tx.execute(
+                "INSERT OR IGNORE INTO price_rejected VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    key.0, key.1, key.2, key.3,
+                    hex(&e.feed_id), e.price_update.to_string(), format!("{:?}", e.reason), e.error_code,
+                    format!("{:?}", e.used_for), e.subject.to_string(), e.timestamp
+                ],
+            )?,
+            OracleEvent::BreakerTripped(e) => tx.execute(
+                "INSERT OR IGNORE INTO breaker_trips VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                params![
+                    key.0, key.1, key.2, key.3,
+                    hex(&e.breaker), format!("{:?}", e.status), e.reference_price, e.reference_exponent,
+                    e.price, e.exponent, e.deviation_bps as i64, e.elapsed_secs
+                ],
+            )?,
+            OracleEvent::ConfigUpdated(e) => tx.execute(
+                "INSERT OR IGNORE INTO config_updates VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    key.0, key.1, key.2, key.3,
+                    e.config.params.max_age_secs as i64, e.config.params.max_confidence_bps as i64,
+                    format!("{:?}", e.config.params.verification_level), e.config.overrides.len() as i64,
+                    e.config.timelock_secs, e.timestamp
+                ],
+            )?,
+        };
+    }
+    tx.execute(
+        "INSERT INTO cursor (id, signature) VALUES (1, ?1) ON CONFLICT (id) DO UPDATE SET signature = ?1",
+        params![signature],
+    )?;
+    tx.commit()?;
+    Ok(events.len())
+}
+
+fn last_indexed(db: &Connection) -> rusqlite::Result<Option<Signature>> {
+    let cursor: Option<String> = db
+        .query_row("SELECT signature FROM cursor WHERE id = 1", [], |row| row.get(0))
+        .optional()?;
+    Ok(cursor.and_then(|signature| Signature::from_str(&signature).ok()))
+}
+
+// ============================================================================
+// Backfill
+// ============================================================================
+
+/// Index every transaction of the program since the cursor, oldest first
+pub fn backfill(rpc: &RpcClient, db: &mut Connection, program_id: &Pubkey) -> Result<usize, Box<dyn std::error::Error>> {
+    let until = last_indexed(db)?;
+    let mut pending = Vec::new();
+    let mut before = None;
+    loop {
+        let page = rpc.get_signatures_for_address_with_config(
+            program_id,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until,
+                limit: Some(SIGNATURE_PAGE),
+                commitment: Some(CommitmentConfig::confirmed()),
+            },
+        )?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(Signature::from_str(&last.signature)?);
+        let full = page.len() == SIGNATURE_PAGE;
+        pending.extend(page.into_iter().map(|entry| entry.signature));
+        if !full {
+            break;
+        }
+    }
+
+    let mut indexed = 0;
+    for signature in pending.iter().rev() {
+        let tx = rpc.get_transaction_with_config(
+            &Signature::from_str(signature)?,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?;
+        let Some(meta) = tx.transaction.meta else {
+            continue;
+        };
+        let logs = match meta.log_messages {
+            OptionSerializer::Some(logs) => logs,
+            _ => Vec::new(),
+        };
+        indexed += index_transaction(db, program_id, signature, tx.slot, meta.err.is_none(), &logs)?;
+    }
+    Ok(indexed)
+}
+
+pub fn run(context: &Context, args: &[String]) -> CliResult {
+    let mut args = args.iter();
+    let program_id = Pubkey::from_str(args.next().ok_or("usage: event-indexer <PROGRAM_ID> [DB_PATH]")?)?;
+    let db_path = args.next().cloned().unwrap_or_else(|| "oracle-events.db".to_string());
+
+    let rpc = context.rpc();
+    let mut db = open(&db_path)?;
+
+    // Subscribe first so nothing lands between the backfill and the stream;
+    // the subscription buffers until the backfill is done
+    let (_subscription, receiver) = PubsubClient::logs_subscribe(
+        &context.ws_url,
+        RpcTransactionLogsFilter::Mentions(vec![program_id.to_string()]),
+        RpcTransactionLogsConfig {
+            commitment: Some(CommitmentConfig::confirmed()),
+        },
+    )?;
+
+    let backfilled = context.metrics.track_rpc(backfill(&rpc, &mut db, &program_id))?;
+    context.metrics.add("events_indexed", backfilled as u64);
+    context.metrics.set_ready();
+    println!("Backfilled {} event(s) into {}", backfilled, db_path);
+
+    for response in receiver {
+        let logs = response.value;
+        let count = index_transaction(
+            &mut db,
+            &program_id,
+            &logs.signature,
+            response.context.slot,
+            logs.err.is_none(),
+            &logs.logs,
+        )?;
+        context.metrics.incr("transactions_seen");
+        context.metrics.add("events_indexed", count as u64);
+        if count > 0 {
+            println!("[slot {}] {}: {} event(s)", response.context.slot, logs.signature, count);
+        }
+    }
+
+    Ok(())
+}
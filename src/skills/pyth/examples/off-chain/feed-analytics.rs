@@ -0,0 +1,624 @@
+/**
+ * Feed Staleness and Confidence Analytics
+ *
+ * `DEFAULT_MAX_PRICE_AGE` (60 s) and `MAX_CONFIDENCE_BPS` (2%) are
+ * starting points, not measurements. A 60 s limit is loose for SOL/USD,
+ * which publishes several times a second, and may be tight for a thin
+ * feed that pauses outside market hours. This job records the Hermes
+ * stream for the feeds a program uses and reports, per feed:
+ *
+ * - the distribution of gaps between publish times (p50 to max)
+ * - the worst outages, with when they started
+ * - the confidence regime: percentiles of confidence in basis points
+ * - for candidate limits, the share of time a price would have been
+ *   rejected as stale, and the share of updates rejected as too wide
+ *
+ * and recommends a `max_age_secs` covering 99.9% of gaps and a
+ * `max_confidence_bps` covering 99% of updates, each with headroom.
+ * Record for days, not minutes: outages and wide-confidence regimes are
+ * rare by definition, and feeds with fewer than `MIN_SAMPLES` updates get
+ * no recommendation.
+ *
+ * `recommend` turns the recommendations into config:
+ *
+ *   --toml <FILE>  merge them into a TOML file of per-feed limits, one
+ *                  `[[feeds]]` table each with the `PriceValidationConfig`
+ *                  field names, for off-chain agents and keepers
+ *   --propose      propose them as feed overrides of the program's
+ *                  `OracleConfig` (templates/oracle-config.rs), starting
+ *                  from the current config; signed by the config authority
+ *
+ * Without a flag it only prints them. A proposal still waits out the
+ * config's timelock, and anyone may review it in the
+ * `OracleConfigChangeProposed` event before it applies.
+ *
+ * Recordings are JSON lines, one per update, so they can be appended to
+ * across restarts and analyzed while recording continues.
+ *
+ * Live recordings cover days at most. For months of history, `backfill`
+ * pages the Benchmarks API into a local columnar store
+ * (templates/price-history.rs), resuming after the newest stored update,
+ * and `history` reports each feed's realized volatility, maximum
+ * drawdown, and average confidence over a range of it: the inputs for
+ * LTVs, liquidation thresholds, and circuit breaker bands. `suggest`
+ * sizes them from the peak rolling volatility of the range
+ * (templates/risk-suggestions.rs): a maintenance margin, a circuit breaker
+ * band, and a `max_confidence_bps` within the margin. With `--tx <FILE>`
+ * it also writes the confidence limits as an unsigned
+ * `propose_oracle_config` transaction, base64, for the config authority
+ * to sign; margins and breakers live in their own accounts and are only
+ * reported.
+ *
+ * Metrics: `samples_recorded` while recording, plus price latency and
+ * staleness (metrics.rs).
+ *
+ * Run (a subcommand of automaton.rs, which lists the dependencies):
+ * automaton feed-analytics record prices.jsonl SOL/USD USDC/USD JTO/USD
+ * automaton feed-analytics analyze prices.jsonl
+ * automaton feed-analytics recommend prices.jsonl --toml feed-limits.toml
+ * automaton feed-analytics backfill history/ 1704067200 1735689600 SOL/USD JTO/USD
+ * automaton feed-analytics history history/ 1704067200 1735689600 SOL/USD JTO/USD
+ * automaton feed-analytics suggest history/ 1704067200 1735689600 SOL/USD --tx proposal.b64
+ * automaton --rpc $RPC --keypair authority.json feed-analytics recommend prices.jsonl --propose
+ */
+
+use anchor_lang::AccountDeserialize;
+use base64::Engine;
+use futures::StreamExt;
+use pyth_oracle::client::{oracle_config_address, propose_oracle_config};
+use pyth_oracle::hermes::HermesClient;
+use pyth_oracle::hermes_stream::StreamConfig;
+use pyth_oracle::price_history::{BackfillConfig, PriceHistoryStore};
+use pyth_oracle::risk_suggestions::{suggest_risk_parameters, RiskSuggestion, SuggestionConfig};
+use pyth_oracle::{confidence_bps, feed_id_for, parse_feed_id, symbol_for, ConfigChange, FeedOverride, OracleConfig};
+use serde::{Deserialize, Serialize};
+use solana_sdk::signature::Signer;
+use solana_sdk::transaction::Transaction;
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+
+use crate::{CliResult, Context, Metrics};
+
+// Hermes endpoint (production)
+const HERMES_ENDPOINT: &str = "https://hermes.pyth.network";
+
+// Outages listed per feed
+const WORST_OUTAGES: usize = 5;
+
+// Candidate limits to report rejection rates for
+const CANDIDATE_MAX_AGES: [i64; 5] = [10, 30, 60, 120, 300];
+const CANDIDATE_CONF_BPS: [u64; 5] = [25, 50, 100, 200, 500];
+
+// Return interval for realized volatility in `history`
+const VOLATILITY_INTERVAL_SECS: i64 = 3_600;
+
+// Fewest updates of a feed to recommend limits from
+const MIN_SAMPLES: usize = 1_000;
+
+// Added to the p99.9 gap: posting the update and landing the transaction
+const AGE_MARGIN_SECS: i64 = 5;
+
+// Added to the p99 confidence, in percent of it
+const CONF_HEADROOM_PERCENT: u64 = 25;
+
+const BPS_DENOMINATOR: u64 = 10_000;
+
+// ============================================================================
+// Recording
+// ============================================================================
+
+/// One recorded price update
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct Sample {
+    pub feed_id: [u8; 32],
+    pub publish_time: i64,
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    /// Hermes had to backfill it after a disconnect
+    pub backfilled: bool,
+}
+
+/// Feed IDs of symbols or hex IDs
+fn resolve_feeds(feeds: &[String]) -> Result<Vec<[u8; 32]>, String> {
+    feeds
+        .iter()
+        .map(|name| feed_id_for(name).map(Ok).unwrap_or_else(|| parse_feed_id(name)))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("unknown feed: {e}"))
+}
+
+async fn record(path: &str, feeds: &[String], metrics: &Metrics) -> CliResult {
+    let feed_ids = resolve_feeds(feeds)?;
+
+    let mut out = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    let hermes = HermesClient::new(HERMES_ENDPOINT);
+    let mut stream = Box::pin(hermes.subscribe_price_updates(feed_ids, StreamConfig::default()));
+    metrics.set_ready();
+
+    let mut recorded: u64 = 0;
+    while let Some(item) = stream.next().await {
+        let update = match item {
+            Ok(update) => update,
+            Err(e) => {
+                eprintln!("stream: {}", e);
+                continue;
+            }
+        };
+        for parsed in &update.updates.parsed {
+            let sample = Sample {
+                feed_id: parsed.feed_id,
+                publish_time: parsed.price.publish_time,
+                price: parsed.price.price,
+                conf: parsed.price.conf,
+                exponent: parsed.price.exponent,
+                backfilled: update.backfilled,
+            };
+            writeln!(out, "{}", serde_json::to_string(&sample)?)?;
+            recorded += 1;
+            metrics.incr("samples_recorded");
+            metrics.observe_price(&parsed.feed_id, parsed.price.publish_time);
+        }
+        if recorded % 10_000 < update.updates.parsed.len() as u64 {
+            println!("{} updates recorded", recorded);
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Analysis
+// ============================================================================
+
+/// An interval without a new publish time
+#[derive(Debug, Clone, Copy)]
+pub struct Outage {
+    pub from: i64,
+    pub secs: i64,
+}
+
+/// Statistics of one feed over a recording
+#[derive(Debug)]
+pub struct FeedReport {
+    pub updates: usize,
+    pub span_secs: i64,
+    /// Sorted gaps between consecutive publish times
+    pub gaps: Vec<i64>,
+    pub worst_outages: Vec<Outage>,
+    /// Sorted confidence of each update in basis points
+    pub conf_bps: Vec<u64>,
+}
+
+/// Value at quantile `q` of sorted `values` (nearest rank)
+pub fn percentile<T: Copy + Default>(values: &[T], q: f64) -> T {
+    if values.is_empty() {
+        return T::default();
+    }
+    let rank = (q * values.len() as f64).ceil() as usize;
+    values[rank.clamp(1, values.len()) - 1]
+}
+
+impl FeedReport {
+    /// `samples` of one feed, in any order
+    pub fn new(samples: &mut [Sample]) -> Self {
+        samples.sort_by_key(|sample| sample.publish_time);
+
+        let mut outages: Vec<Outage> = samples
+            .windows(2)
+            .map(|pair| Outage { from: pair[0].publish_time, secs: pair[1].publish_time - pair[0].publish_time })
+            .collect();
+        let mut gaps: Vec<i64> = outages.iter().map(|outage| outage.secs).collect();
+        gaps.sort_unstable();
+        outages.sort_by_key(|outage| std::cmp::Reverse(outage.secs));
+        outages.truncate(WORST_OUTAGES);
+
+        let mut conf_bps: Vec<u64> = samples
+            .iter()
+            .filter_map(|sample| confidence_bps(sample.price, sample.conf).ok().map(u64::from))
+            .collect();
+        conf_bps.sort_unstable();
+
+        Self {
+            updates: samples.len(),
+            span_secs: match (samples.first(), samples.last()) {
+                (Some(first), Some(last)) => last.publish_time - first.publish_time,
+                _ => 0,
+            },
+            gaps,
+            worst_outages: outages,
+            conf_bps,
+        }
+    }
+
+    /// Share of the recording during which the latest price was older than
+    /// `max_age` seconds
+    pub fn stale_share(&self, max_age: i64) -> f64 {
+        if self.span_secs == 0 {
+            return 0.0;
+        }
+        let stale: i64 = self.gaps.iter().map(|gap| (gap - max_age).max(0)).sum();
+        stale as f64 / self.span_secs as f64
+    }
+
+    /// Share of updates with confidence above `max_bps`
+    pub fn wide_share(&self, max_bps: u64) -> f64 {
+        if self.conf_bps.is_empty() {
+            return 0.0;
+        }
+        let wide = self.conf_bps.iter().filter(|&&bps| bps > max_bps).count();
+        wide as f64 / self.conf_bps.len() as f64
+    }
+
+    /// Limits covering 99.9% of gaps and 99% of updates with headroom;
+    /// `None` below `MIN_SAMPLES` updates
+    pub fn recommend(&self, feed_id: &[u8; 32]) -> Option<FeedLimits> {
+        if self.updates < MIN_SAMPLES || self.conf_bps.is_empty() {
+            return None;
+        }
+        let max_age_secs = (percentile(&self.gaps, 0.999) + AGE_MARGIN_SECS) as u64;
+        let conf = percentile(&self.conf_bps, 0.99);
+        let max_confidence_bps =
+            (conf + (conf * CONF_HEADROOM_PERCENT).div_ceil(100)).clamp(1, BPS_DENOMINATOR);
+        Some(FeedLimits {
+            symbol: symbol_for(feed_id).map(str::to_string),
+            feed_id: format!("0x{}", feed_id_hex(feed_id)),
+            max_age_secs,
+            max_confidence_bps,
+        })
+    }
+}
+
+fn feed_id_hex(feed_id: &[u8; 32]) -> String {
+    feed_id.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Symbol of a feed, or its 0x-prefixed ID
+fn feed_name(feed_id: &[u8; 32]) -> String {
+    symbol_for(feed_id)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("0x{}", feed_id_hex(feed_id)))
+}
+
+fn load_samples(path: &str) -> Result<BTreeMap<[u8; 32], Vec<Sample>>, Box<dyn std::error::Error>> {
+    let mut by_feed: BTreeMap<[u8; 32], Vec<Sample>> = BTreeMap::new();
+    for line in std::io::BufReader::new(std::fs::File::open(path)?).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let sample: Sample = serde_json::from_str(&line)?;
+        by_feed.entry(sample.feed_id).or_default().push(sample);
+    }
+    Ok(by_feed)
+}
+
+fn print_report(feed_id: &[u8; 32], report: &FeedReport) {
+    println!("=== {} ===", feed_name(feed_id));
+    println!("Updates: {} over {} s", report.updates, report.span_secs);
+    println!(
+        "Publish gaps (s): p50 {}  p90 {}  p99 {}  p99.9 {}  max {}",
+        percentile(&report.gaps, 0.5),
+        percentile(&report.gaps, 0.9),
+        percentile(&report.gaps, 0.99),
+        percentile(&report.gaps, 0.999),
+        report.gaps.last().copied().unwrap_or_default()
+    );
+    for outage in &report.worst_outages {
+        println!("  outage: {} s from publish time {}", outage.secs, outage.from);
+    }
+    println!(
+        "Confidence (bps): p50 {}  p90 {}  p99 {}  max {}",
+        percentile(&report.conf_bps, 0.5),
+        percentile(&report.conf_bps, 0.9),
+        percentile(&report.conf_bps, 0.99),
+        report.conf_bps.last().copied().unwrap_or_default()
+    );
+    for max_age in CANDIDATE_MAX_AGES {
+        println!("  max_age {:>4} s: stale {:.4}% of the time", max_age, 100.0 * report.stale_share(max_age));
+    }
+    for max_bps in CANDIDATE_CONF_BPS {
+        println!("  max_conf {:>4} bps: rejects {:.4}% of updates", max_bps, 100.0 * report.wide_share(max_bps));
+    }
+    match report.recommend(feed_id) {
+        Some(limits) => println!(
+            "Recommended: max_age_secs {}, max_confidence_bps {}",
+            limits.max_age_secs, limits.max_confidence_bps
+        ),
+        None => println!("No recommendation: fewer than {} updates", MIN_SAMPLES),
+    }
+    println!();
+}
+
+// ============================================================================
+// History
+// ============================================================================
+
+fn parse_time(arg: Option<&String>) -> Result<i64, String> {
+    arg.ok_or("missing <FROM> or <TO>")?
+        .parse()
+        .map_err(|_| "times are unix seconds".to_string())
+}
+
+/// Store the updates of `feeds` in [from, to) under `dir`, continuing after
+/// the newest update already stored
+async fn backfill(dir: &str, from: i64, to: i64, feeds: &[String]) -> CliResult {
+    let store = PriceHistoryStore::open(dir)?;
+    let hermes = HermesClient::new(HERMES_ENDPOINT);
+    for feed_id in resolve_feeds(feeds)? {
+        let start = store.last_publish_time(&feed_id)?.map_or(from, |last| (last + 1).max(from));
+        let name = feed_name(&feed_id);
+        let mut pages = Box::pin(hermes.backfill_price_updates(feed_id, start, to, BackfillConfig::default()));
+
+        let mut stored = 0;
+        while let Some(page) = pages.next().await {
+            match page {
+                Ok(updates) => stored += store.append(&feed_id, &updates)?,
+                Err(e) => eprintln!("{}: {}", name, e),
+            }
+        }
+        println!("{}: {} updates stored from {}", name, stored, start);
+    }
+    Ok(())
+}
+
+/// Volatility, drawdown, and confidence of `feeds` in [from, to)
+fn history(dir: &str, from: i64, to: i64, feeds: &[String]) -> CliResult {
+    let store = PriceHistoryStore::open(dir)?;
+    for feed_id in resolve_feeds(feeds)? {
+        let series = store.load(&feed_id, from, to)?;
+        let name = feed_name(&feed_id);
+        println!("=== {} ===", name);
+        println!("Updates: {}", series.len());
+        if let Some(volatility) = series.realized_volatility(VOLATILITY_INTERVAL_SECS) {
+            println!("Realized volatility (hourly returns, annualized): {:.2}%", 100.0 * volatility);
+        }
+        if let Some(drawdown) = series.max_drawdown() {
+            println!(
+                "Max drawdown: {:.2}% from publish time {} to {}",
+                100.0 * drawdown.fraction,
+                drawdown.peak_time,
+                drawdown.trough_time
+            );
+        }
+        if let Some(bps) = series.average_confidence_bps() {
+            println!("Average confidence: {:.2} bps", bps);
+        }
+        println!();
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Recommendations
+// ============================================================================
+
+/// Recommended `PriceValidationConfig` limits of one feed
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FeedLimits {
+    pub symbol: Option<String>,
+    /// 0x-prefixed
+    pub feed_id: String,
+    pub max_age_secs: u64,
+    pub max_confidence_bps: u64,
+}
+
+/// The TOML file `--toml` writes
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct FeedLimitsFile {
+    #[serde(default)]
+    pub feeds: Vec<FeedLimits>,
+}
+
+/// Replace the limits of recommended feeds in `path`, keeping the others
+fn write_toml(path: &str, recommended: &[FeedLimits]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file: FeedLimitsFile = match std::fs::read_to_string(path) {
+        Ok(text) => toml::from_str(&text)?,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => FeedLimitsFile::default(),
+        Err(error) => return Err(error.into()),
+    };
+    file.feeds.retain(|existing| recommended.iter().all(|limits| limits.feed_id != existing.feed_id));
+    file.feeds.extend_from_slice(recommended);
+    file.feeds.sort_by(|a, b| (&a.symbol, &a.feed_id).cmp(&(&b.symbol, &b.feed_id)));
+    std::fs::write(path, toml::to_string_pretty(&file)?)?;
+    println!("Wrote {} feeds to {}", file.feeds.len(), path);
+    Ok(())
+}
+
+/// `config` with the recommended limits as feed overrides, printing each
+/// change; `None` if it already has them
+fn with_overrides(config: &OracleConfig, recommended: &[FeedLimits]) -> Result<Option<ConfigChange>, String> {
+    let mut change = config.current();
+    for limits in recommended {
+        let feed_id = parse_feed_id(&limits.feed_id).map_err(|e| e.to_string())?;
+        let current = config.validation_config(&feed_id);
+        println!(
+            "{}: max_age_secs {} -> {}, max_confidence_bps {} -> {}",
+            limits.symbol.as_deref().unwrap_or(&limits.feed_id),
+            current.max_age_secs,
+            limits.max_age_secs,
+            current.max_confidence_bps,
+            limits.max_confidence_bps
+        );
+        // A feed matching the global limits needs no override
+        if limits.max_age_secs == config.params.max_age_secs
+            && limits.max_confidence_bps == config.params.max_confidence_bps
+        {
+            change.remove_override(&feed_id);
+        } else {
+            change
+                .set_override(FeedOverride {
+                    feed_id,
+                    max_age_secs: limits.max_age_secs,
+                    max_confidence_bps: limits.max_confidence_bps,
+                })
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    if change == config.current() {
+        println!("The config already matches; nothing to propose");
+        return Ok(None);
+    }
+    Ok(Some(change))
+}
+
+async fn load_oracle_config(context: &Context) -> Result<OracleConfig, Box<dyn std::error::Error>> {
+    let account = context.nonblocking_rpc().get_account(&oracle_config_address()).await?;
+    let config = OracleConfig::try_deserialize(&mut account.data.as_slice())?;
+    if config.pending_change.is_some() {
+        return Err("a config change is already pending; apply or cancel it first".into());
+    }
+    Ok(config)
+}
+
+/// Propose the recommendations as feed overrides of the current config
+async fn propose(context: &Context, recommended: &[FeedLimits]) -> CliResult {
+    let rpc = context.nonblocking_rpc();
+    let authority = context.signer()?;
+    let config = load_oracle_config(context).await?;
+    if config.authority != authority.pubkey() {
+        return Err(format!("{} is not the config authority {}", authority.pubkey(), config.authority).into());
+    }
+    let Some(change) = with_overrides(&config, recommended)? else {
+        return Ok(());
+    };
+
+    let instruction = propose_oracle_config(&authority.pubkey(), change);
+    let blockhash = rpc.get_latest_blockhash().await?;
+    let tx = Transaction::new_signed_with_payer(&[instruction], Some(&authority.pubkey()), &[&authority], blockhash);
+    let signature = rpc.send_and_confirm_transaction(&tx).await?;
+    println!("Proposed in {}; anyone may apply it after {} s", signature, config.timelock_secs);
+    Ok(())
+}
+
+async fn recommend(context: &Context, path: &str, flags: &[String]) -> CliResult {
+    let mut recommended = Vec::new();
+    for (feed_id, mut samples) in load_samples(path)? {
+        let report = FeedReport::new(&mut samples);
+        match report.recommend(&feed_id) {
+            Some(limits) => recommended.push(limits),
+            None => println!("0x{}: {} updates, too few to recommend", feed_id_hex(&feed_id), report.updates),
+        }
+    }
+    for limits in &recommended {
+        println!(
+            "{:<12} max_age_secs {:>4}  max_confidence_bps {:>5}",
+            limits.symbol.as_deref().unwrap_or(&limits.feed_id),
+            limits.max_age_secs,
+            limits.max_confidence_bps
+        );
+    }
+
+    let mut flags = flags.iter();
+    while let Some(flag) = flags.next() {
+        match flag.as_str() {
+            "--toml" => write_toml(flags.next().ok_or("--toml needs a file")?, &recommended)?,
+            "--propose" => propose(context, &recommended).await?,
+            other => return Err(format!("unknown flag {other}").into()),
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Risk Suggestions
+// ============================================================================
+
+fn print_suggestion(suggestion: &RiskSuggestion) {
+    println!("=== {} ===", feed_name(&suggestion.feed_id));
+    println!(
+        "Volatility: {:.2}% overall, peak {:.2}% in the window ending at {}",
+        100.0 * suggestion.volatility,
+        100.0 * suggestion.peak_volatility,
+        suggestion.peak_volatility_end_time
+    );
+    println!("Suggested max_confidence_bps: {}", suggestion.max_confidence_bps);
+    println!("Suggested maintenance margin: {} bps", suggestion.maintenance_margin_bps);
+    println!(
+        "Suggested circuit breaker: max_deviation_bps {} within {} s",
+        suggestion.breaker.max_deviation_bps, suggestion.breaker.window_secs
+    );
+    println!();
+}
+
+/// Write a `propose_oracle_config` transaction setting the suggested
+/// confidence limits to `path` as base64, unsigned, for the config
+/// authority to sign (a multisig, a hardware wallet); it expires with its
+/// blockhash, about a minute later
+async fn write_proposal_tx(context: &Context, suggestions: &[RiskSuggestion], path: &str) -> CliResult {
+    let config = load_oracle_config(context).await?;
+    let recommended: Vec<FeedLimits> = suggestions
+        .iter()
+        .map(|suggestion| FeedLimits {
+            symbol: symbol_for(&suggestion.feed_id).map(str::to_string),
+            feed_id: format!("0x{}", feed_id_hex(&suggestion.feed_id)),
+            max_age_secs: config.validation_config(&suggestion.feed_id).max_age_secs,
+            max_confidence_bps: suggestion.max_confidence_bps,
+        })
+        .collect();
+    let Some(change) = with_overrides(&config, &recommended)? else {
+        return Ok(());
+    };
+
+    let authority = config.authority;
+    let mut tx = Transaction::new_with_payer(&[propose_oracle_config(&authority, change)], Some(&authority));
+    tx.message.recent_blockhash = context.nonblocking_rpc().get_latest_blockhash().await?;
+    std::fs::write(path, base64::engine::general_purpose::STANDARD.encode(bincode::serialize(&tx)?))?;
+    println!("Wrote the unsigned proposal to {}, to be signed by {}", path, authority);
+    Ok(())
+}
+
+/// Margin, breaker, and confidence suggestions for `feeds` from their
+/// history in [from, to); `--tx <FILE>` also writes the confidence limits
+/// as a proposal to sign
+async fn suggest(context: &Context, dir: &str, from: i64, to: i64, args: &[String]) -> CliResult {
+    let (feeds, flags) = args.split_at(args.iter().position(|arg| arg.starts_with("--")).unwrap_or(args.len()));
+    let tx_path = match flags {
+        [] => None,
+        [flag, path] if flag == "--tx" => Some(path),
+        _ => return Err("suggest takes only --tx <FILE> after the feeds".into()),
+    };
+
+    let store = PriceHistoryStore::open(dir)?;
+    let config = SuggestionConfig::default();
+    let mut suggestions = Vec::new();
+    for feed_id in resolve_feeds(feeds)? {
+        let series = store.load(&feed_id, from, to)?;
+        match suggest_risk_parameters(&series, &config) {
+            Some(suggestion) => {
+                print_suggestion(&suggestion);
+                suggestions.push(suggestion);
+            }
+            None => println!("{}: {} updates, less than one volatility window\n", feed_name(&feed_id), series.len()),
+        }
+    }
+    match tx_path {
+        Some(path) if !suggestions.is_empty() => write_proposal_tx(context, &suggestions, path).await,
+        _ => Ok(()),
+    }
+}
+
+pub async fn run(context: &Context, args: &[String]) -> CliResult {
+    let usage = "usage: feed-analytics record <FILE> <FEED>... | analyze <FILE> \
+                 | recommend <FILE> [--toml <OUT>] [--propose] \
+                 | backfill|history <DIR> <FROM> <TO> <FEED>... \
+                 | suggest <DIR> <FROM> <TO> <FEED>... [--tx <OUT>]";
+    match (args.first().map(String::as_str), args.get(1)) {
+        (Some("record"), Some(path)) if args.len() > 2 => record(path, &args[2..], &context.metrics).await,
+        (Some("analyze"), Some(path)) => {
+            for (feed_id, mut samples) in load_samples(path)? {
+                print_report(&feed_id, &FeedReport::new(&mut samples));
+            }
+            Ok(())
+        }
+        (Some("recommend"), Some(path)) => recommend(context, path, &args[2..]).await,
+        (Some(command @ ("backfill" | "history" | "suggest")), Some(dir)) if args.len() > 4 => {
+            let (from, to) = (parse_time(args.get(2))?, parse_time(args.get(3))?);
+            match command {
+                "backfill" => backfill(dir, from, to, &args[4..]).await,
+                "history" => history(dir, from, to, &args[4..]),
+                _ => suggest(context, dir, from, to, &args[4..]).await,
+            }
+        }
+        _ => Err(usage.into()),
+    }
+}
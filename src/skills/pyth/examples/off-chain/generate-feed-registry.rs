@@ -0,0 +1,265 @@
+/**
+ * Generate the Pyth Feed Registry Table
+ *
+ * Downloads the full feed catalog from Hermes `/v2/price_feeds`, reads
+ * each feed's current exponent from the latest price update, and writes
+ * templates/feed-registry-data.rs: the const tables behind
+ * `feed_registry::feed_id_for`, `symbol_for`, and `feed_info`.
+ *
+ * Rerun it when Pyth lists new feeds; commit the output so builds never
+ * touch the network.
+ *
+ * Add to Cargo.toml:
+ * [dev-dependencies]
+ * reqwest = { version = "0.11", features = ["blocking", "json"] }
+ * serde = { version = "1", features = ["derive"] }
+ *
+ * [[example]]
+ * name = "generate-feed-registry"
+ * path = "examples/generate-feed-registry.rs"
+ *
+ * Run:
+ * cargo run --example generate-feed-registry -- templates/feed-registry-data.rs [HERMES_ENDPOINT]
+ */
+
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+
+// Hermes endpoint (production)
+const DEFAULT_HERMES_ENDPOINT: &str = "https://hermes.pyth.network";
+
+// Feed IDs per latest-price request, to keep URLs short
+const IDS_PER_REQUEST: usize = 100;
+
+// ============================================================================
+// Hermes Response Types
+// ============================================================================
+
+#[derive(Deserialize, Debug)]
+struct FeedAttributes {
+    asset_type: String,
+    base: Option<String>,
+    display_symbol: Option<String>,
+    quote_currency: Option<String>,
+    symbol: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct PriceFeedEntry {
+    id: String,
+    attributes: FeedAttributes,
+}
+
+#[derive(Deserialize, Debug)]
+struct HermesPrice {
+    expo: i32,
+}
+
+#[derive(Deserialize, Debug)]
+struct HermesParsedUpdate {
+    id: String,
+    price: HermesPrice,
+}
+
+#[derive(Deserialize, Debug)]
+struct HermesUpdateResponse {
+    parsed: Vec<HermesParsedUpdate>,
+}
+
+// ============================================================================
+// Registry Rows
+// ============================================================================
+
+struct Row {
+    pyth_symbol: String,
+    symbol: String,
+    asset_class: &'static str,
+    base: String,
+    quote: String,
+    exponent: i32,
+    feed_id: String,
+}
+
+/// `AssetClass` variant for a Hermes asset_type
+fn asset_class(asset_type: &str) -> &'static str {
+    match asset_type {
+        "Crypto" => "Crypto",
+        "Crypto Redemption Rate" => "CryptoRedemptionRate",
+        "Equity" => "Equity",
+        "FX" => "Fx",
+        "Metal" => "Metal",
+        "Rates" => "Rates",
+        "Commodities" => "Commodity",
+        _ => "Other",
+    }
+}
+
+/// When two feeds share a display symbol, the lower rank owns it
+fn class_rank(asset_class: &str) -> u8 {
+    match asset_class {
+        "Crypto" => 0,
+        "Fx" => 1,
+        "Metal" => 2,
+        "Equity" => 3,
+        _ => 4,
+    }
+}
+
+/// Sort key matching the registry's case-insensitive lookups
+fn lookup_key(s: &str) -> String {
+    s.to_ascii_uppercase()
+}
+
+fn into_row(entry: PriceFeedEntry, exponent: i32) -> Row {
+    let attributes = entry.attributes;
+    // "Crypto.SOL/USD" -> "SOL/USD" when display_symbol is absent
+    let symbol = attributes.display_symbol.unwrap_or_else(|| {
+        attributes
+            .symbol
+            .split_once('.')
+            .map_or(attributes.symbol.clone(), |(_, rest)| rest.to_string())
+    });
+    Row {
+        asset_class: asset_class(&attributes.asset_type),
+        base: attributes.base.unwrap_or_default(),
+        quote: attributes.quote_currency.unwrap_or_default(),
+        pyth_symbol: attributes.symbol,
+        symbol,
+        exponent,
+        feed_id: entry.id.trim_start_matches("0x").to_ascii_lowercase(),
+    }
+}
+
+// ============================================================================
+// Fetching
+// ============================================================================
+
+fn fetch_catalog(
+    http: &reqwest::blocking::Client,
+    endpoint: &str,
+) -> Result<Vec<PriceFeedEntry>, reqwest::Error> {
+    http.get(format!("{}/v2/price_feeds", endpoint))
+        .send()?
+        .error_for_status()?
+        .json()
+}
+
+/// Current exponent of every feed that has a price; feeds that have never
+/// published are left out
+fn fetch_exponents(
+    http: &reqwest::blocking::Client,
+    endpoint: &str,
+    ids: &[String],
+) -> Result<HashMap<String, i32>, reqwest::Error> {
+    let mut exponents = HashMap::new();
+    for chunk in ids.chunks(IDS_PER_REQUEST) {
+        let mut query: Vec<(&str, &str)> = chunk.iter().map(|id| ("ids[]", id.as_str())).collect();
+        query.push(("parsed", "true"));
+        query.push(("ignore_invalid_price_ids", "true"));
+
+        let body: HermesUpdateResponse = http
+            .get(format!("{}/v2/updates/price/latest", endpoint))
+            .query(&query)
+            .send()?
+            .error_for_status()?
+            .json()?;
+        for update in body.parsed {
+            exponents.insert(
+                update.id.trim_start_matches("0x").to_ascii_lowercase(),
+                update.price.expo,
+            );
+        }
+    }
+    Ok(exponents)
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+fn render(rows: &[Row], endpoint: &str) -> String {
+    let mut out = String::new();
+    writeln!(out, "// @generated by examples/off-chain/generate-feed-registry.rs from").unwrap();
+    writeln!(out, "// {}/v2/price_feeds. Do not edit by hand.", endpoint).unwrap();
+    writeln!(out, "// {} feeds", rows.len()).unwrap();
+    writeln!(out).unwrap();
+
+    writeln!(out, "pub const FEEDS: &[FeedInfo] = &[").unwrap();
+    for row in rows {
+        writeln!(
+            out,
+            "    FeedInfo::new({:?}, {:?}, AssetClass::{}, {:?}, {:?}, {}, {:?}),",
+            row.pyth_symbol, row.symbol, row.asset_class, row.base, row.quote, row.exponent, row.feed_id
+        )
+        .unwrap();
+    }
+    writeln!(out, "];").unwrap();
+
+    // One owner per display symbol
+    let mut by_symbol: BTreeMap<String, usize> = BTreeMap::new();
+    for (index, row) in rows.iter().enumerate() {
+        by_symbol
+            .entry(lookup_key(&row.symbol))
+            .and_modify(|owner| {
+                if class_rank(row.asset_class) < class_rank(rows[*owner].asset_class) {
+                    *owner = index;
+                }
+            })
+            .or_insert(index);
+    }
+    write_index(&mut out, "BY_SYMBOL", by_symbol.into_values());
+
+    // Hex is lowercase, so string order is byte order of the ID
+    let mut by_feed_id: Vec<usize> = (0..rows.len()).collect();
+    by_feed_id.sort_by(|a, b| rows[*a].feed_id.cmp(&rows[*b].feed_id));
+    write_index(&mut out, "BY_FEED_ID", by_feed_id.into_iter());
+
+    out
+}
+
+fn write_index(out: &mut String, name: &str, indices: impl Iterator<Item = usize>) {
+    let indices: Vec<String> = indices.map(|i| i.to_string()).collect();
+    writeln!(out).unwrap();
+    writeln!(out, "const {}: &[u16] = &[", name).unwrap();
+    for line in indices.chunks(16) {
+        writeln!(out, "    {},", line.join(", ")).unwrap();
+    }
+    writeln!(out, "];").unwrap();
+}
+
+// ============================================================================
+// Main
+// ============================================================================
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let output = args
+        .next()
+        .ok_or("usage: generate-feed-registry <OUTPUT_PATH> [HERMES_ENDPOINT]")?;
+    let endpoint = args.next().unwrap_or_else(|| DEFAULT_HERMES_ENDPOINT.to_string());
+    let endpoint = endpoint.trim_end_matches('/');
+
+    let http = reqwest::blocking::Client::new();
+    let catalog = fetch_catalog(&http, endpoint)?;
+    let ids: Vec<String> = catalog.iter().map(|entry| entry.id.clone()).collect();
+    let exponents = fetch_exponents(&http, endpoint, &ids)?;
+
+    let mut rows = Vec::with_capacity(catalog.len());
+    for entry in catalog {
+        let id = entry.id.trim_start_matches("0x").to_ascii_lowercase();
+        match exponents.get(&id) {
+            Some(exponent) => rows.push(into_row(entry, *exponent)),
+            None => eprintln!("skipping {} ({}): no price published", entry.attributes.symbol, id),
+        }
+    }
+    rows.sort_by(|a, b| lookup_key(&a.pyth_symbol).cmp(&lookup_key(&b.pyth_symbol)));
+    rows.dedup_by(|a, b| lookup_key(&a.pyth_symbol) == lookup_key(&b.pyth_symbol));
+    if rows.len() > u16::MAX as usize {
+        return Err("catalog no longer fits u16 indices".into());
+    }
+
+    std::fs::write(&output, render(&rows, endpoint))?;
+    println!("wrote {} feeds to {}", rows.len(), output);
+    Ok(())
+}
@@ -0,0 +1,285 @@
+/**
+ * Liquidator Bot - Off-Chain Keeper for the Liquidation Template
+ *
+ * Loads every `Position` and `Market` account of the example program,
+ * subscribes to the price stream for the markets' feeds
+ * (`Context::price_stream`: Hermes, or the push feed accounts with
+ * `--prices accounts`), and recomputes health on every update with the same `position_risk` and
+ * `quote_liquidation` the program runs, with each market's risk settings,
+ * on debt accrued to the current time through the debt market's
+ * `InterestMarket`. When a position turns liquidatable it sends
+ * `liquidate` for the close-factor maximum, with `min_collateral_out` one
+ * percent below the quote. Positions on a market with liquidations paused
+ * (guardian.rs) are skipped, as the program would reject them.
+ *
+ * The program reads prices from the sponsored push feed accounts, which
+ * can trail the Hermes stream by a few seconds. A liquidation sent before
+ * they catch up fails preflight with `NotLiquidatable` and is retried on a
+ * later update; watching the accounts avoids the lag.
+ *
+ * Metrics: `price_updates`, `liquidations_sent`, `liquidations_failed`,
+ * and the `positions_watched` gauge, plus price latency and staleness,
+ * transaction outcomes, and RPC errors (metrics.rs). Ready once the
+ * positions are loaded.
+ *
+ * Run (a subcommand of automaton.rs, which lists the dependencies):
+ * automaton --rpc $RPC --keypair ~/.config/solana/id.json liquidator
+ */
+
+use anchor_lang::{AccountDeserialize, Discriminator};
+use futures::StreamExt;
+use pyth_oracle::client::{liquidate, push_feed_address, LiquidationPrices};
+use pyth_oracle::{
+    position_risk, quote_liquidation, InterestMarket, Market, OraclePrice, PauseFlags, Position, ValidatedPrice,
+    LIQUIDATION_SIGMA,
+};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::{CliResult, Context, Metrics};
+
+// How often positions and markets are reloaded
+const RELOAD_INTERVAL: Duration = Duration::from_secs(60);
+
+// How long to wait before retrying a position after a send
+const RETRY_COOLDOWN: Duration = Duration::from_secs(10);
+
+// Slippage allowed between quote and execution
+const MIN_OUT_TOLERANCE_BPS: u64 = 100;
+
+// ============================================================================
+// Program State
+// ============================================================================
+
+/// Every account of type `T` owned by the example program
+pub async fn load_accounts<T: AccountDeserialize + Discriminator>(
+    rpc: &RpcClient,
+) -> Result<Vec<(Pubkey, T)>, Box<dyn std::error::Error>> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            0,
+            &T::DISCRIMINATOR,
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let accounts = rpc
+        .get_program_accounts_with_config(&pyth_oracle::ID, config)
+        .await?;
+    Ok(accounts
+        .into_iter()
+        .filter_map(|(address, account)| {
+            T::try_deserialize(&mut account.data.as_slice())
+                .ok()
+                .map(|value| (address, value))
+        })
+        .collect())
+}
+
+struct Book {
+    positions: Vec<(Pubkey, Position)>,
+    /// Keyed by mint
+    markets: HashMap<Pubkey, (Pubkey, Market)>,
+    /// Keyed by market address
+    interest: HashMap<Pubkey, InterestMarket>,
+}
+
+impl Book {
+    async fn load(rpc: &RpcClient) -> Result<Self, Box<dyn std::error::Error>> {
+        let positions = load_accounts::<Position>(rpc)
+            .await?
+            .into_iter()
+            .filter(|(_, position)| position.debt_amount > 0)
+            .collect();
+        let markets = load_accounts::<Market>(rpc)
+            .await?
+            .into_iter()
+            .map(|(address, market)| (market.mint, (address, market)))
+            .collect();
+        let interest = load_accounts::<InterestMarket>(rpc)
+            .await?
+            .into_iter()
+            .map(|(_, interest)| (interest.risk_params, interest))
+            .collect();
+        Ok(Self {
+            positions,
+            markets,
+            interest,
+        })
+    }
+
+    fn feed_ids(&self) -> Vec<[u8; 32]> {
+        let unique: HashSet<[u8; 32]> = self.markets.values().map(|(_, market)| market.feed_id).collect();
+        unique.into_iter().collect()
+    }
+}
+
+// ============================================================================
+// Liquidation
+// ============================================================================
+
+/// The `liquidate` instruction for a position, if it is liquidatable at the
+/// latest streamed prices
+fn liquidation_for(
+    liquidator: &Pubkey,
+    address: &Pubkey,
+    position: &Position,
+    book: &Book,
+    prices: &HashMap<[u8; 32], ValidatedPrice>,
+) -> Option<Instruction> {
+    let (_, collateral_market) = book.markets.get(&position.collateral_mint)?;
+    let (debt_market_address, debt_market) = book.markets.get(&position.debt_mint)?;
+    if collateral_market.paused.union(debt_market.paused).contains(PauseFlags::LIQUIDATIONS) {
+        return None;
+    }
+    let collateral_price = prices.get(&collateral_market.feed_id)?;
+    let debt_price = prices.get(&debt_market.feed_id)?;
+    let collateral_params = &collateral_market.risk_params();
+    let debt_params = &debt_market.risk_params();
+
+    // Accrue a copy to now, as the program will before its health check
+    let interest = book.interest.get(debt_market_address);
+    let mut position = position.clone();
+    if let Some(interest) = interest {
+        let mut interest = interest.clone();
+        interest.accrue(unix_now()).ok()?;
+        position.accrue_debt(&interest).ok()?;
+    }
+
+    let risk = position_risk(
+        collateral_params,
+        position.collateral_amount,
+        collateral_price,
+        debt_params,
+        position.debt_amount,
+        debt_price,
+        LIQUIDATION_SIGMA,
+    )
+    .ok()?;
+    if !risk.is_liquidatable().ok()? {
+        return None;
+    }
+
+    let quote = quote_liquidation(
+        collateral_params,
+        position.collateral_amount,
+        collateral_price,
+        debt_params,
+        position.debt_amount,
+        debt_price,
+        u64::MAX,
+    )
+    .ok()?;
+    let min_collateral_out =
+        (quote.seize_amount as u128 * (10_000 - MIN_OUT_TOLERANCE_BPS) as u128 / 10_000) as u64;
+    println!(
+        "{}: health {} -> repay {} for {} collateral",
+        address,
+        risk.health_factor().ok()?,
+        quote.repay_amount,
+        quote.seize_amount
+    );
+
+    let prices = LiquidationPrices {
+        collateral_mint: position.collateral_mint,
+        debt_mint: position.debt_mint,
+        collateral_price: push_feed_address(&collateral_market.feed_id),
+        debt_price: push_feed_address(&debt_market.feed_id),
+        accrue_debt: interest.is_some(),
+    };
+    Some(liquidate(liquidator, address, &prices, quote.repay_amount, min_collateral_out))
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+async fn send(rpc: &RpcClient, payer: &Keypair, instruction: Instruction, metrics: &Metrics) {
+    let blockhash = match metrics.track_rpc(rpc.get_latest_blockhash().await) {
+        Ok(blockhash) => blockhash,
+        Err(e) => return eprintln!("blockhash: {}", e),
+    };
+    let tx = Transaction::new_signed_with_payer(&[instruction], Some(&payer.pubkey()), &[payer], blockhash);
+    let result = metrics.track_rpc(rpc.send_and_confirm_transaction(&tx).await);
+    metrics.record_transaction(result.is_ok());
+    match result {
+        Ok(signature) => {
+            metrics.incr("liquidations_sent");
+            println!("  liquidated: {}", signature)
+        }
+        Err(e) => {
+            metrics.incr("liquidations_failed");
+            eprintln!("  failed: {}", e)
+        }
+    }
+}
+
+// ============================================================================
+// Run
+// ============================================================================
+
+pub async fn run(context: &Context, _args: &[String]) -> CliResult {
+    let rpc = context.nonblocking_rpc();
+    let payer = context.signer()?;
+
+    loop {
+        let book = context.metrics.track_rpc(Book::load(&rpc).await)?;
+        let feed_ids = book.feed_ids();
+        println!("Watching {} positions over {} feeds", book.positions.len(), feed_ids.len());
+        context.metrics.set("positions_watched", book.positions.len() as u64);
+        context.metrics.set_ready();
+
+        let mut prices: HashMap<[u8; 32], ValidatedPrice> = HashMap::new();
+        let mut cooldown: HashMap<Pubkey, Instant> = HashMap::new();
+        let reload_at = Instant::now() + RELOAD_INTERVAL;
+        let mut stream = context.price_stream(feed_ids);
+
+        while let Some(item) = stream.next().await {
+            let update = match item {
+                Ok(update) => update,
+                Err(e) => {
+                    eprintln!("stream: {}", e);
+                    continue;
+                }
+            };
+            context.metrics.add("price_updates", update.updates.parsed.len() as u64);
+            for parsed in &update.updates.parsed {
+                context.metrics.observe_price(&parsed.feed_id, parsed.price.publish_time);
+                prices.insert(
+                    parsed.feed_id,
+                    ValidatedPrice::from_price(parsed.feed_id, &OraclePrice::from(parsed.price)),
+                );
+            }
+
+            let now = Instant::now();
+            for (address, position) in &book.positions {
+                if cooldown.get(address).is_some_and(|until| *until > now) {
+                    continue;
+                }
+                if let Some(instruction) = liquidation_for(&payer.pubkey(), address, position, &book, &prices) {
+                    cooldown.insert(*address, now + RETRY_COOLDOWN);
+                    send(&rpc, &payer, instruction, &context.metrics).await;
+                }
+            }
+
+            // Reload to pick up new positions and amounts changed by liquidations
+            if now >= reload_at {
+                break;
+            }
+        }
+    }
+}
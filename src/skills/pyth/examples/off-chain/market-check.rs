@@ -0,0 +1,251 @@
+/**
+ * Market Check - Compare a Jupiter Quote with the Pyth Rate
+ *
+ * Fetches a Jupiter quote for swapping `AMOUNT` of one mint into another
+ * and the latest Pyth price of both mints from Hermes, and reports how far
+ * the market's output is from the oracle's. A keeper runs it before
+ * submitting a swap built from templates/oracle-swap.rs: the program only
+ * sees the oracle, so a large divergence means either the oracle or the
+ * market has moved away from the other, and the trade should wait.
+ *
+ * The divergence is measured against the oracle mid price. The quote
+ * includes price impact and the route's fees, so quote the size you intend
+ * to trade; the oracle's own uncertainty (the sum of both feeds' relative
+ * confidence) is printed next to it for comparison.
+ *
+ * Exits with status 1 when the divergence exceeds `--max-divergence-bps`
+ * (default 100), so a script can gate on it.
+ *
+ * Run (a subcommand of automaton.rs, which lists the dependencies):
+ * automaton --rpc $RPC market-check \
+ *     So11111111111111111111111111111111111111112 SOL/USD \
+ *     EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v USDC/USD \
+ *     1000000000 --max-divergence-bps 50
+ */
+
+use pyth_oracle::{feed_id_for, parse_feed_id};
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::{CliResult, Context};
+
+// Hermes endpoint (production)
+const HERMES_ENDPOINT: &str = "https://hermes.pyth.network";
+
+// Jupiter quote endpoint (keyless tier)
+const JUPITER_QUOTE_ENDPOINT: &str = "https://lite-api.jup.ag/swap/v1/quote";
+
+// Slippage passed to Jupiter; it does not change `outAmount`
+const QUOTE_SLIPPAGE_BPS: u16 = 50;
+
+const DEFAULT_MAX_DIVERGENCE_BPS: f64 = 100.0;
+
+// Offset of `decimals` in an SPL token (and Token-2022) mint
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+const USAGE: &str = "usage: market-check <INPUT_MINT> <INPUT_FEED> <OUTPUT_MINT> <OUTPUT_FEED> <AMOUNT> \
+[--max-divergence-bps N]";
+
+// ============================================================================
+// Response Types
+// ============================================================================
+
+#[derive(Deserialize, Debug)]
+struct HermesPrice {
+    price: String,
+    conf: String,
+    expo: i32,
+    publish_time: i64,
+}
+
+#[derive(Deserialize, Debug)]
+struct HermesParsedUpdate {
+    id: String,
+    price: HermesPrice,
+}
+
+#[derive(Deserialize, Debug)]
+struct HermesUpdateResponse {
+    parsed: Vec<HermesParsedUpdate>,
+}
+
+/// The fields of a Jupiter quote this check uses
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct JupiterQuote {
+    #[serde(with = "string_u64")]
+    pub in_amount: u64,
+    #[serde(with = "string_u64")]
+    pub out_amount: u64,
+    /// Fraction, not percent, despite the name
+    pub price_impact_pct: String,
+}
+
+mod string_u64 {
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// ============================================================================
+// Comparison
+// ============================================================================
+
+/// A Pyth price as a float, with its confidence
+#[derive(Clone, Copy, Debug)]
+pub struct OracleQuote {
+    pub price: f64,
+    pub conf: f64,
+    pub publish_time: i64,
+}
+
+/// Jupiter's output against the output the oracle rate implies
+#[derive(Debug)]
+pub struct MarketCheck {
+    /// Output at the oracle mid price, in the output mint's base units
+    pub oracle_out: f64,
+    pub market_out: u64,
+    /// Positive when the market pays more than the oracle
+    pub divergence_bps: f64,
+    /// Relative confidence of the oracle rate: both feeds' conf / price
+    pub oracle_conf_bps: f64,
+}
+
+impl MarketCheck {
+    pub fn compare(
+        amount_in: u64,
+        input: (&OracleQuote, u8),
+        output: (&OracleQuote, u8),
+        market_out: u64,
+    ) -> Result<Self, String> {
+        let ((input_price, input_decimals), (output_price, output_decimals)) = (input, output);
+        if input_price.price <= 0.0 || output_price.price <= 0.0 {
+            return Err("oracle price is not positive".into());
+        }
+
+        let input_value = amount_in as f64 / 10f64.powi(input_decimals as i32) * input_price.price;
+        let oracle_out = input_value / output_price.price * 10f64.powi(output_decimals as i32);
+        Ok(Self {
+            oracle_out,
+            market_out,
+            divergence_bps: (market_out as f64 / oracle_out - 1.0) * 10_000.0,
+            oracle_conf_bps: (input_price.conf / input_price.price + output_price.conf / output_price.price)
+                * 10_000.0,
+        })
+    }
+
+    pub fn exceeds(&self, max_divergence_bps: f64) -> bool {
+        self.divergence_bps.abs() > max_divergence_bps
+    }
+}
+
+// ============================================================================
+// Fetching
+// ============================================================================
+
+fn feed_id_hex(feed_id: &[u8; 32]) -> String {
+    feed_id.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Latest Pyth price of each feed, in the order given
+pub fn fetch_oracle_quotes(
+    http: &reqwest::blocking::Client,
+    feed_ids: &[[u8; 32]],
+) -> Result<Vec<OracleQuote>, Box<dyn std::error::Error>> {
+    let ids: Vec<String> = feed_ids.iter().map(feed_id_hex).collect();
+    let query: String = ids.iter().map(|id| format!("ids[]=0x{id}&")).collect();
+    let url = format!("{HERMES_ENDPOINT}/v2/updates/price/latest?{query}parsed=true");
+    let body: HermesUpdateResponse = http.get(url).send()?.error_for_status()?.json()?;
+
+    ids.iter()
+        .map(|id| {
+            let update = body
+                .parsed
+                .iter()
+                .find(|update| update.id.trim_start_matches("0x") == id)
+                .ok_or_else(|| format!("Hermes returned no price for 0x{id}"))?;
+            let scale = 10f64.powi(update.price.expo);
+            Ok(OracleQuote {
+                price: update.price.price.parse::<i64>()? as f64 * scale,
+                conf: update.price.conf.parse::<u64>()? as f64 * scale,
+                publish_time: update.price.publish_time,
+            })
+        })
+        .collect()
+}
+
+/// Jupiter's best route for `amount` of `input_mint` (base units)
+pub fn fetch_jupiter_quote(
+    http: &reqwest::blocking::Client,
+    input_mint: &Pubkey,
+    output_mint: &Pubkey,
+    amount: u64,
+) -> Result<JupiterQuote, reqwest::Error> {
+    let url = format!(
+        "{JUPITER_QUOTE_ENDPOINT}?inputMint={input_mint}&outputMint={output_mint}&amount={amount}\
+         &slippageBps={QUOTE_SLIPPAGE_BPS}"
+    );
+    http.get(url).send()?.error_for_status()?.json()
+}
+
+fn mint_decimals(context: &Context, mint: &Pubkey) -> Result<u8, Box<dyn std::error::Error>> {
+    let data = context.rpc().get_account_data(mint)?;
+    data.get(MINT_DECIMALS_OFFSET)
+        .copied()
+        .ok_or_else(|| format!("{mint} is not a token mint").into())
+}
+
+fn resolve_feed(name: &str) -> Result<[u8; 32], String> {
+    feed_id_for(name)
+        .map(Ok)
+        .unwrap_or_else(|| parse_feed_id(name))
+        .map_err(|e| format!("unknown feed {name}: {e}"))
+}
+
+// ============================================================================
+// Entry Point
+// ============================================================================
+
+pub fn run(context: &Context, args: &[String]) -> CliResult {
+    let [input_mint, input_feed, output_mint, output_feed, amount, flags @ ..] = args else {
+        return Err(USAGE.into());
+    };
+    let max_divergence_bps = match flags {
+        [] => DEFAULT_MAX_DIVERGENCE_BPS,
+        [flag, value] if flag == "--max-divergence-bps" => value.parse()?,
+        _ => return Err(USAGE.into()),
+    };
+
+    let (input_mint, output_mint) = (Pubkey::from_str(input_mint)?, Pubkey::from_str(output_mint)?);
+    let feed_ids = [resolve_feed(input_feed)?, resolve_feed(output_feed)?];
+    let amount: u64 = amount.parse()?;
+
+    let http = reqwest::blocking::Client::new();
+    let quote = fetch_jupiter_quote(&http, &input_mint, &output_mint, amount)?;
+    let oracle = fetch_oracle_quotes(&http, &feed_ids)?;
+    let check = MarketCheck::compare(
+        amount,
+        (&oracle[0], mint_decimals(context, &input_mint)?),
+        (&oracle[1], mint_decimals(context, &output_mint)?),
+        quote.out_amount,
+    )?;
+
+    println!("=== {} {} -> {} ===", amount, input_mint, output_mint);
+    println!(
+        "Pyth:    {:.0} (rate ± {:.1} bps, published {} / {})",
+        check.oracle_out, check.oracle_conf_bps, oracle[0].publish_time, oracle[1].publish_time
+    );
+    println!("Jupiter: {} (price impact {})", check.market_out, quote.price_impact_pct);
+    println!("Divergence: {:+.1} bps (max {})", check.divergence_bps, max_divergence_bps);
+
+    if check.exceeds(max_divergence_bps) {
+        eprintln!("Oracle and market disagree; hold swaps until they converge");
+        std::process::exit(1);
+    }
+
+    println!("Oracle and market agree");
+    Ok(())
+}
@@ -0,0 +1,220 @@
+/**
+ * Metrics - Prometheus Export and Readiness for the Off-Chain Tools
+ *
+ * One registry shared by every subcommand through `Context`, served over
+ * HTTP when automaton.rs gets `--metrics <ADDR>`:
+ *
+ *   GET /metrics   Prometheus text format (any path other than /healthz)
+ *   GET /healthz   200 when ready, 503 otherwise
+ *
+ * Besides the counters and gauges each tool sets by name, the registry
+ * exports, for tools that report them:
+ *
+ *   automaton_price_update_latency_seconds          histogram, publish to receipt
+ *   automaton_price_staleness_seconds{feed="0x…"}   age of the last price, at scrape
+ *   automaton_transactions_succeeded / _failed      transactions the tool sent
+ *   automaton_rpc_requests / automaton_rpc_errors   RPC calls made through `track_rpc`
+ *
+ * Readiness: a tool calls `set_ready` once it has loaded its state and
+ * subscribed. A tool that observes prices is also unready while no feed
+ * has delivered a price for `MAX_PRICE_SILENCE`, so a stuck stream takes
+ * the pod out of rotation.
+ *
+ * Latency is measured against `publish_time`, which has one-second
+ * resolution; the buckets start at half a second for that reason.
+ */
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// Upper bounds of the latency histogram, in seconds
+const LATENCY_BUCKETS: [f64; 8] = [0.5, 1.0, 2.0, 3.0, 5.0, 10.0, 30.0, 60.0];
+
+/// Longest time without a price before an observing tool reports unready
+pub const MAX_PRICE_SILENCE: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+struct Histogram {
+    /// Observations at or below each bucket bound
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if value <= bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+
+    fn render(&self, name: &str, out: &mut String) {
+        out.push_str(&format!("# TYPE automaton_{name} histogram\n"));
+        for (bucket, bound) in self.buckets.iter().zip(LATENCY_BUCKETS) {
+            out.push_str(&format!("automaton_{name}_bucket{{le=\"{bound}\"}} {bucket}\n"));
+        }
+        out.push_str(&format!("automaton_{name}_bucket{{le=\"+Inf\"}} {}\n", self.count));
+        out.push_str(&format!("automaton_{name}_sum {}\n", self.sum));
+        out.push_str(&format!("automaton_{name}_count {}\n", self.count));
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    values: BTreeMap<&'static str, u64>,
+    latency: Histogram,
+    /// Last publish time per feed
+    feeds: BTreeMap<[u8; 32], i64>,
+    last_price_at: Option<Instant>,
+    ready: bool,
+}
+
+/// Counters, gauges, and price freshness shared by every subcommand,
+/// exported as `automaton_<name>` in the Prometheus text format
+#[derive(Clone, Default)]
+pub struct Metrics {
+    registry: Arc<Mutex<Registry>>,
+}
+
+fn unix_now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs_f64())
+        .unwrap_or_default()
+}
+
+fn feed_label(feed_id: &[u8; 32]) -> String {
+    let hex: String = feed_id.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("0x{hex}")
+}
+
+impl Metrics {
+    pub fn incr(&self, name: &'static str) {
+        self.add(name, 1);
+    }
+
+    pub fn add(&self, name: &'static str, by: u64) {
+        let mut registry = self.registry.lock().unwrap();
+        let value = registry.values.entry(name).or_default();
+        *value = value.saturating_add(by);
+    }
+
+    pub fn set(&self, name: &'static str, value: u64) {
+        self.registry.lock().unwrap().values.insert(name, value);
+    }
+
+    /// A price received for `feed_id`: records its latency and freshness
+    pub fn observe_price(&self, feed_id: &[u8; 32], publish_time: i64) {
+        let mut registry = self.registry.lock().unwrap();
+        let last = registry.feeds.entry(*feed_id).or_insert(i64::MIN);
+        if publish_time <= *last {
+            return;
+        }
+        *last = publish_time;
+        registry.latency.observe((unix_now() - publish_time as f64).max(0.0));
+        registry.last_price_at = Some(Instant::now());
+    }
+
+    /// Outcome of a transaction the tool sent
+    pub fn record_transaction(&self, succeeded: bool) {
+        self.incr(if succeeded {
+            "transactions_succeeded"
+        } else {
+            "transactions_failed"
+        });
+    }
+
+    /// Count an RPC call and its failure; pass the result through
+    pub fn track_rpc<T, E>(&self, result: Result<T, E>) -> Result<T, E> {
+        self.incr("rpc_requests");
+        if result.is_err() {
+            self.incr("rpc_errors");
+        }
+        result
+    }
+
+    /// The tool has loaded its state and is doing its work
+    pub fn set_ready(&self) {
+        self.registry.lock().unwrap().ready = true;
+    }
+
+    /// Why the tool is not ready, if it is not
+    pub fn health(&self) -> Result<(), String> {
+        let registry = self.registry.lock().unwrap();
+        if !registry.ready {
+            return Err("starting".into());
+        }
+        match registry.last_price_at {
+            Some(at) if at.elapsed() > MAX_PRICE_SILENCE => {
+                Err(format!("no price for {}s", at.elapsed().as_secs()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn render(&self) -> String {
+        let registry = self.registry.lock().unwrap();
+        let mut out: String = registry
+            .values
+            .iter()
+            .map(|(name, value)| format!("automaton_{} {}\n", name, value))
+            .collect();
+
+        if registry.latency.count > 0 {
+            registry.latency.render("price_update_latency_seconds", &mut out);
+        }
+        let now = unix_now();
+        for (feed_id, publish_time) in &registry.feeds {
+            out.push_str(&format!(
+                "automaton_price_staleness_seconds{{feed=\"{}\"}} {:.0}\n",
+                feed_label(feed_id),
+                (now - *publish_time as f64).max(0.0)
+            ));
+        }
+        out
+    }
+
+    /// Serve `/healthz` and the metrics on `addr`
+    pub fn serve(&self, addr: &str) -> std::io::Result<()> {
+        let listener = TcpListener::bind(addr)?;
+        let metrics = self.clone();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                // The request line picks the endpoint; drain the headers
+                let mut reader = BufReader::new(&stream);
+                let mut request_line = String::new();
+                let _ = reader.read_line(&mut request_line);
+                let mut line = String::new();
+                while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+                    line.clear();
+                }
+
+                let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+                let (status, content_type, body) = if path == "/healthz" {
+                    match metrics.health() {
+                        Ok(()) => ("200 OK", "text/plain", "ok\n".to_string()),
+                        Err(reason) => ("503 Service Unavailable", "text/plain", format!("{reason}\n")),
+                    }
+                } else {
+                    ("200 OK", "text/plain; version=0.0.4", metrics.render())
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n{}",
+                    status,
+                    content_type,
+                    body.len(),
+                    body
+                );
+            }
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+/**
+ * Price Event Monitor - Off-Chain Subscriber
+ *
+ * Subscribes to the logs of a program built from anchor-oracle.rs and
+ * decodes the price events it emits (templates/price-events.rs,
+ * oracle-config.rs, circuit-breaker.rs): which price and confidence each
+ * swap, valuation, and liquidation used, and every price it rejected.
+ * Rejections arrive from failed transactions too, since their logs are
+ * kept.
+ *
+ * Anchor writes each event as a `Program data: <base64>` log line: an
+ * 8-byte discriminator (`sha256("event:<Name>")[..8]`) followed by the
+ * Borsh-encoded fields. Only lines logged while the monitored program is
+ * the innermost running program are decoded, so a CPI'd program cannot
+ * spoof its events.
+ *
+ * Metrics: `price_consumed`, `price_rejected`, `breaker_trips`, and
+ * `config_updates`. Ready once subscribed.
+ *
+ * Run (a subcommand of automaton.rs, which lists the dependencies):
+ * automaton --ws wss://api.mainnet-beta.solana.com price-events <PROGRAM_ID>
+ */
+
+use base64::Engine;
+use borsh::BorshDeserialize;
+use solana_client::pubsub_client::PubsubClient;
+use solana_client::rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::hash;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::{CliResult, Context};
+
+// Anchor event discriminator length
+const DISCRIMINATOR_LEN: usize = 8;
+
+// ============================================================================
+// Event Layouts (mirror templates/price-events.rs and friends)
+// ============================================================================
+
+#[derive(BorshDeserialize, Debug, Clone, Copy)]
+pub enum PriceUse {
+    Read,
+    Swap,
+    CollateralValuation,
+    HealthRefresh,
+    Liquidation,
+}
+
+#[derive(BorshDeserialize, Debug, Clone, Copy)]
+pub enum RejectionReason {
+    InsufficientVerification,
+    FeedIdMismatch,
+    Stale,
+    ConfidenceTooHigh,
+    ZeroPrice,
+    NegativePrice,
+    Other,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct PriceConsumed {
+    pub feed_id: [u8; 32],
+    pub price_update: Pubkey,
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub used_for: PriceUse,
+    pub subject: Pubkey,
+    pub timestamp: i64,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct PriceRejected {
+    pub feed_id: [u8; 32],
+    pub price_update: Pubkey,
+    pub reason: RejectionReason,
+    pub error_code: u32,
+    pub used_for: PriceUse,
+    pub subject: Pubkey,
+    pub timestamp: i64,
+}
+
+#[derive(BorshDeserialize, Debug, Clone, Copy)]
+pub enum BreakerStatus {
+    Normal,
+    Restricted,
+    Tripped,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct CircuitBreakerTripped {
+    pub breaker: [u8; 32],
+    pub status: BreakerStatus,
+    pub reference_price: i64,
+    pub reference_exponent: i32,
+    pub price: i64,
+    pub exponent: i32,
+    pub deviation_bps: u64,
+    pub elapsed_secs: i64,
+}
+
+/// A decoded event of the monitored program
+#[derive(Debug)]
+pub enum PriceEvent {
+    Consumed(PriceConsumed),
+    Rejected(PriceRejected),
+    BreakerTripped(CircuitBreakerTripped),
+    /// `OracleConfigUpdated`; fetch the config account for the new values
+    ConfigUpdated,
+}
+
+fn discriminator(name: &str) -> [u8; DISCRIMINATOR_LEN] {
+    let mut out = [0; DISCRIMINATOR_LEN];
+    out.copy_from_slice(&hash(format!("event:{name}").as_bytes()).to_bytes()[..DISCRIMINATOR_LEN]);
+    out
+}
+
+fn feed_id_hex(feed_id: &[u8; 32]) -> String {
+    feed_id.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// ============================================================================
+// Decoding
+// ============================================================================
+
+/// Decode one event payload; `None` for events this monitor ignores
+pub fn decode_event(data: &[u8]) -> Option<PriceEvent> {
+    if data.len() < DISCRIMINATOR_LEN {
+        return None;
+    }
+    let (tag, mut body) = data.split_at(DISCRIMINATOR_LEN);
+    let event = if tag == discriminator("PriceConsumed") {
+        PriceEvent::Consumed(PriceConsumed::deserialize(&mut body).ok()?)
+    } else if tag == discriminator("PriceRejected") {
+        PriceEvent::Rejected(PriceRejected::deserialize(&mut body).ok()?)
+    } else if tag == discriminator("CircuitBreakerTripped") {
+        PriceEvent::BreakerTripped(CircuitBreakerTripped::deserialize(&mut body).ok()?)
+    } else if tag == discriminator("OracleConfigUpdated") {
+        PriceEvent::ConfigUpdated
+    } else {
+        return None;
+    };
+    Some(event)
+}
+
+/// Events `program_id` logged in one transaction, in order
+pub fn events_in_logs(program_id: &Pubkey, logs: &[String]) -> Vec<PriceEvent> {
+    let program = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(data) = rest.strip_prefix("data: ") {
+            if stack.last() != Some(&program.as_str()) {
+                continue;
+            }
+            let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(data) else {
+                continue;
+            };
+            events.extend(decode_event(&bytes));
+        } else if let Some((id, tail)) = rest.split_once(' ') {
+            if tail.starts_with("invoke [") {
+                stack.push(id);
+            } else if tail == "success" || tail.starts_with("failed") {
+                stack.pop();
+            }
+        }
+    }
+    events
+}
+
+pub fn run(context: &Context, args: &[String]) -> CliResult {
+    let address = args.first().ok_or("usage: price-events <PROGRAM_ID>")?;
+    let program_id = Pubkey::from_str(address)?;
+
+    let (_subscription, receiver) = PubsubClient::logs_subscribe(
+        &context.ws_url,
+        RpcTransactionLogsFilter::Mentions(vec![program_id.to_string()]),
+        RpcTransactionLogsConfig {
+            commitment: Some(CommitmentConfig::confirmed()),
+        },
+    )?;
+
+    println!("=== Price events of {} ===", program_id);
+    context.metrics.set_ready();
+    for response in receiver {
+        let logs = response.value;
+        let status = if logs.err.is_some() { "failed" } else { "ok" };
+
+        for event in events_in_logs(&program_id, &logs.logs) {
+            context.metrics.incr(match event {
+                PriceEvent::Consumed(_) => "price_consumed",
+                PriceEvent::Rejected(_) => "price_rejected",
+                PriceEvent::BreakerTripped(_) => "breaker_trips",
+                PriceEvent::ConfigUpdated => "config_updates",
+            });
+            match event {
+                PriceEvent::Consumed(event) => println!(
+                    "[{} {}] {:?} on {}: feed 0x{} {} ± {} × 10^{} published {}",
+                    logs.signature,
+                    status,
+                    event.used_for,
+                    event.subject,
+                    feed_id_hex(&event.feed_id),
+                    event.price,
+                    event.conf,
+                    event.exponent,
+                    event.publish_time
+                ),
+                PriceEvent::Rejected(event) => println!(
+                    "[{} {}] REJECTED {:?} for {:?} on {}: feed 0x{} (error {})",
+                    logs.signature,
+                    status,
+                    event.reason,
+                    event.used_for,
+                    event.subject,
+                    feed_id_hex(&event.feed_id),
+                    event.error_code
+                ),
+                PriceEvent::BreakerTripped(event) => println!(
+                    "[{} {}] BREAKER {:?} feed 0x{}: {} × 10^{} is {} bps from reference {} × 10^{}",
+                    logs.signature,
+                    status,
+                    event.status,
+                    feed_id_hex(&event.breaker),
+                    event.price,
+                    event.exponent,
+                    event.deviation_bps,
+                    event.reference_price,
+                    event.reference_exponent
+                ),
+                PriceEvent::ConfigUpdated => {
+                    println!("[{} {}] oracle config updated", logs.signature, status)
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,640 @@
+/**
+ * Pusher - Keeper That Posts Price Updates Ahead of Liquidations
+ *
+ * Subscribes to the Hermes stream for a set of feeds and posts a fresh
+ * `PriceUpdateV2` account through the Pyth receiver whenever a price moves
+ * more than `--deviation-bps` from the last one posted, or `--heartbeat`
+ * seconds pass. Liquidators and other consumers then find a recent update
+ * on-chain instead of posting their own.
+ *
+ * Each feed is posted in its own transaction with `post_update_atomic`
+ * (templates/post-update.rs), from update data fetched for that feed
+ * alone. With `--consume`, the transaction also runs the example
+ * program's `get_verified_price` on the new account, so only updates that
+ * pass strict validation land; implement `Consumer` to append your own
+ * instruction instead. A transaction that outgrows the size limit with a
+ * consumer needs fewer guardian signatures or no consumer.
+ *
+ * Write accounts: the keeper is the write authority of everything it
+ * posts. It keeps the last `KEEP_PER_FEED` accounts of each feed alive
+ * (a consumer may be reading the previous one) and reclaims the rent of
+ * older ones as they are replaced. A sweep at startup and every
+ * `SWEEP_INTERVAL` reclaims anything else it owns, such as accounts left by
+ * a crash or by a retry whose first attempt landed after all.
+ *
+ * Sending: every transaction sets a compute unit limit and, with
+ * `--priority-fee`, a price: a fixed number of micro-lamports, or `auto`
+ * for the 75th percentile of recent fees on the receiver treasury. With
+ * `--jito`, transactions go to the block engine as bundles of up to five,
+ * the last one tipping `--jito-tip` lamports. Failed feeds are retried
+ * with fresh update data under `RetryPolicy`'s exponential backoff.
+ *
+ * Metrics: `price_updates`, `updates_pushed`, `push_retries`,
+ * `push_failures`, `rent_reclaimed`, and the `live_price_accounts` gauge,
+ * plus price latency and staleness, transaction outcomes, and RPC errors
+ * (metrics.rs). Ready after the startup sweep.
+ *
+ * Run (a subcommand of automaton.rs, which lists the dependencies):
+ * automaton --rpc $RPC --keypair keeper.json pusher SOL/USD USDC/USD \
+ *     --deviation-bps 25 --heartbeat 60 --priority-fee auto \
+ *     --jito https://mainnet.block-engine.jito.wtf --jito-tip 10000
+ */
+
+use anchor_lang::Discriminator;
+use base64::Engine;
+use futures::StreamExt;
+use pyth_oracle::client::get_verified_price;
+use pyth_oracle::hermes::{feed_id_to_hex, HermesClient};
+use pyth_oracle::hermes_stream::StreamConfig;
+use pyth_oracle::post_update::client::{
+    build_post_update_atomic_instructions, build_reclaim_rent_instruction, get_treasury_address,
+};
+use pyth_oracle::post_update::{DEFAULT_ATOMIC_SIGNATURES, DEFAULT_TREASURY_ID};
+use pyth_oracle::{feed_id_for, parse_feed_id, PYTH_RECEIVER_PROGRAM_ID};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::{CliResult, Context, Metrics};
+
+// Hermes endpoint (production)
+const HERMES_ENDPOINT: &str = "https://hermes.pyth.network";
+
+// Posted accounts kept alive per feed, newest included
+const KEEP_PER_FEED: usize = 2;
+
+// How often every account the keeper owns is checked for leftovers
+const SWEEP_INTERVAL: Duration = Duration::from_secs(300);
+
+// Compute units for post_update_atomic with the default signature count,
+// plus room for a consumer instruction
+const POST_COMPUTE_UNIT_LIMIT: u32 = 400_000;
+
+// reclaim_rent instructions per transaction
+const RECLAIMS_PER_TX: usize = 10;
+
+// How long to wait for sent transactions to confirm
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Ceiling for `--priority-fee auto`, in micro-lamports per compute unit
+const MAX_AUTO_PRIORITY_FEE: u64 = 1_000_000;
+
+// Jito limits bundles to five transactions
+const JITO_MAX_BUNDLE_LEN: usize = 5;
+
+// Jito tip accounts; any one of them receives a bundle's tip
+const JITO_TIP_ACCOUNTS: [Pubkey; 8] = [
+    solana_sdk::pubkey!("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5"),
+    solana_sdk::pubkey!("HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe"),
+    solana_sdk::pubkey!("Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY"),
+    solana_sdk::pubkey!("ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49"),
+    solana_sdk::pubkey!("DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh"),
+    solana_sdk::pubkey!("ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt"),
+    solana_sdk::pubkey!("DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL"),
+    solana_sdk::pubkey!("3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT"),
+];
+
+const USAGE: &str = "usage: pusher <FEED>... [--deviation-bps N] [--heartbeat SECS] \
+[--priority-fee MICROLAMPORTS|auto] [--jito URL] [--jito-tip LAMPORTS] [--max-attempts N] [--consume]";
+
+// ============================================================================
+// Policies
+// ============================================================================
+
+/// When a feed is pushed
+#[derive(Clone, Copy, Debug)]
+pub struct PushPolicy {
+    /// Move from the last pushed price that triggers a push
+    pub deviation_bps: u64,
+    /// Longest time between pushes of a feed
+    pub heartbeat: Duration,
+}
+
+impl Default for PushPolicy {
+    fn default() -> Self {
+        Self {
+            deviation_bps: 50,
+            heartbeat: Duration::from_secs(60),
+        }
+    }
+}
+
+/// The last price pushed for a feed
+#[derive(Clone, Copy, Debug)]
+pub struct LastPush {
+    pub price: i64,
+    pub publish_time: i64,
+}
+
+impl PushPolicy {
+    pub fn is_due(&self, last: Option<&LastPush>, price: i64, publish_time: i64) -> bool {
+        let Some(last) = last else { return true };
+        if publish_time <= last.publish_time {
+            return false;
+        }
+        if publish_time - last.publish_time >= self.heartbeat.as_secs() as i64 || last.price == 0 {
+            return true;
+        }
+        let moved = (price as i128 - last.price as i128).unsigned_abs();
+        let moved_bps = moved * 10_000 / last.price.unsigned_abs() as u128;
+        moved_bps >= self.deviation_bps as u128
+    }
+}
+
+/// How failed pushes are retried
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    /// Attempts per push, the first included
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(4),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before retry number `retry` (0 for the first retry)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(1 << retry.min(16))
+            .min(self.max_backoff)
+    }
+}
+
+/// Compute unit price of push transactions
+#[derive(Clone, Copy, Debug)]
+pub enum PriorityFee {
+    None,
+    /// Micro-lamports per compute unit
+    Fixed(u64),
+    /// Percentile of recent fees paid to write the receiver treasury
+    Auto { percentile: u8 },
+}
+
+impl PriorityFee {
+    async fn micro_lamports(&self, rpc: &RpcClient) -> u64 {
+        let percentile = match self {
+            PriorityFee::None => return 0,
+            PriorityFee::Fixed(micro_lamports) => return *micro_lamports,
+            PriorityFee::Auto { percentile } => *percentile as usize,
+        };
+        let treasury = get_treasury_address(DEFAULT_TREASURY_ID);
+        let mut fees: Vec<u64> = match rpc.get_recent_prioritization_fees(&[treasury]).await {
+            Ok(fees) => fees.into_iter().map(|fee| fee.prioritization_fee).collect(),
+            Err(e) => {
+                eprintln!("prioritization fees: {}", e);
+                return 0;
+            }
+        };
+        fees.sort_unstable();
+        let index = fees.len().saturating_sub(1) * percentile.min(100) / 100;
+        fees.get(index).copied().unwrap_or(0).min(MAX_AUTO_PRIORITY_FEE)
+    }
+}
+
+/// Where push transactions are sent
+#[derive(Clone, Debug)]
+pub enum Submitter {
+    Rpc,
+    /// Jito block engine, tipping each bundle
+    Jito { endpoint: String, tip_lamports: u64 },
+}
+
+// ============================================================================
+// Consumers
+// ============================================================================
+
+/// Instructions that read a posted update in the transaction that posts it
+pub trait Consumer {
+    fn instructions(&self, feed_id: &[u8; 32], price_update: &Pubkey) -> Vec<Instruction>;
+}
+
+/// The example program's `get_verified_price`: the push fails unless the
+/// update passes strict validation
+pub struct VerifiedPriceRead;
+
+impl Consumer for VerifiedPriceRead {
+    fn instructions(&self, feed_id: &[u8; 32], price_update: &Pubkey) -> Vec<Instruction> {
+        vec![get_verified_price(price_update, feed_id)]
+    }
+}
+
+// ============================================================================
+// Pusher
+// ============================================================================
+
+/// A transaction posting one feed
+struct PushTransaction {
+    feed_id: [u8; 32],
+    price_update: Pubkey,
+    last: LastPush,
+    transaction: Transaction,
+}
+
+pub struct Pusher {
+    rpc: RpcClient,
+    hermes: HermesClient,
+    http: reqwest::Client,
+    payer: Keypair,
+    pub policy: PushPolicy,
+    pub retry: RetryPolicy,
+    pub priority_fee: PriorityFee,
+    pub submitter: Submitter,
+    pub consumer: Option<Box<dyn Consumer>>,
+    metrics: Metrics,
+    last: HashMap<[u8; 32], LastPush>,
+    /// Posted accounts per feed, oldest first
+    live: HashMap<[u8; 32], VecDeque<Pubkey>>,
+}
+
+impl Pusher {
+    pub fn new(context: &Context, payer: Keypair) -> Self {
+        Self {
+            rpc: context.nonblocking_rpc(),
+            hermes: HermesClient::new(HERMES_ENDPOINT),
+            http: reqwest::Client::new(),
+            payer,
+            policy: PushPolicy::default(),
+            retry: RetryPolicy::default(),
+            priority_fee: PriorityFee::None,
+            submitter: Submitter::Rpc,
+            consumer: None,
+            metrics: context.metrics.clone(),
+            last: HashMap::new(),
+            live: HashMap::new(),
+        }
+    }
+
+    /// Post `feeds`, retrying the ones that do not land
+    pub async fn push(&mut self, mut feeds: Vec<[u8; 32]>) {
+        for attempt in 0..self.retry.max_attempts {
+            if attempt > 0 {
+                self.metrics.incr("push_retries");
+                tokio::time::sleep(self.retry.backoff(attempt - 1)).await;
+            }
+            match self.try_push(&feeds).await {
+                Ok(landed) => {
+                    for push in landed {
+                        feeds.retain(|feed_id| *feed_id != push.feed_id);
+                        self.record(push).await;
+                    }
+                }
+                Err(e) => eprintln!("push: {}", e),
+            }
+            if feeds.is_empty() {
+                return;
+            }
+        }
+        self.metrics.add("push_failures", feeds.len() as u64);
+        eprintln!("gave up on {} feed(s) after {} attempts", feeds.len(), self.retry.max_attempts);
+    }
+
+    /// One attempt: fetch, build, send, and confirm; returns what landed
+    async fn try_push(&self, feeds: &[[u8; 32]]) -> Result<Vec<PushTransaction>, Box<dyn std::error::Error>> {
+        let blockhash = self.metrics.track_rpc(self.rpc.get_latest_blockhash().await)?;
+        let micro_lamports = self.priority_fee.micro_lamports(&self.rpc).await;
+
+        // One Hermes request per feed, so each blob holds exactly that feed
+        let updates = futures::future::join_all(
+            feeds.iter().map(|feed_id| self.hermes.get_latest_price_updates(std::slice::from_ref(feed_id))),
+        )
+        .await;
+
+        let mut pushes = Vec::new();
+        for (feed_id, updates) in feeds.iter().zip(updates) {
+            let updates = updates?;
+            let (Some(parsed), Some(update_data)) = (updates.get(feed_id), updates.update_data.first()) else {
+                return Err(format!("Hermes returned no update for {}", feed_id_to_hex(feed_id)).into());
+            };
+            let mut posted = build_post_update_atomic_instructions(
+                &self.payer.pubkey(),
+                update_data,
+                DEFAULT_ATOMIC_SIGNATURES,
+                DEFAULT_TREASURY_ID,
+            )?;
+            let posted = posted.pop().ok_or("update data holds no price update")?;
+
+            let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(POST_COMPUTE_UNIT_LIMIT)];
+            if micro_lamports > 0 {
+                instructions.push(ComputeBudgetInstruction::set_compute_unit_price(micro_lamports));
+            }
+            instructions.push(posted.instruction.clone());
+            if let Some(consumer) = &self.consumer {
+                instructions.extend(consumer.instructions(feed_id, &posted.price_update_account()));
+            }
+            pushes.push((
+                *feed_id,
+                posted,
+                LastPush {
+                    price: parsed.price.price,
+                    publish_time: parsed.price.publish_time,
+                },
+                instructions,
+            ));
+        }
+
+        // The last transaction of each bundle carries the tip
+        if let Submitter::Jito { tip_lamports, .. } = &self.submitter {
+            let tip_account = JITO_TIP_ACCOUNTS[blockhash.as_ref()[0] as usize % JITO_TIP_ACCOUNTS.len()];
+            for bundle in pushes.chunks_mut(JITO_MAX_BUNDLE_LEN) {
+                let (.., instructions) = bundle.last_mut().unwrap();
+                instructions.push(system_instruction::transfer(&self.payer.pubkey(), &tip_account, *tip_lamports));
+            }
+        }
+
+        let transactions: Vec<PushTransaction> = pushes
+            .into_iter()
+            .map(|(feed_id, posted, last, instructions)| PushTransaction {
+                feed_id,
+                price_update: posted.price_update_account(),
+                last,
+                transaction: Transaction::new_signed_with_payer(
+                    &instructions,
+                    Some(&self.payer.pubkey()),
+                    &[&self.payer, &posted.price_update_keypair],
+                    blockhash,
+                ),
+            })
+            .collect();
+
+        let sent = self.send(&transactions).await;
+        let landed = self.confirm(&sent).await?;
+        Ok(transactions
+            .into_iter()
+            .filter(|push| landed.contains(&push.transaction.signatures[0]))
+            .collect())
+    }
+
+    /// Send every transaction; returns the signatures that were accepted
+    async fn send(&self, transactions: &[PushTransaction]) -> Vec<Signature> {
+        let mut sent = Vec::new();
+        match &self.submitter {
+            Submitter::Rpc => {
+                for push in transactions {
+                    match self.metrics.track_rpc(self.rpc.send_transaction(&push.transaction).await) {
+                        Ok(signature) => sent.push(signature),
+                        Err(e) => eprintln!("send {}: {}", feed_id_to_hex(&push.feed_id), e),
+                    }
+                }
+            }
+            Submitter::Jito { endpoint, .. } => {
+                for bundle in transactions.chunks(JITO_MAX_BUNDLE_LEN) {
+                    match self.send_bundle(endpoint, bundle).await {
+                        Ok(()) => sent.extend(bundle.iter().map(|push| push.transaction.signatures[0])),
+                        Err(e) => eprintln!("send bundle: {}", e),
+                    }
+                }
+            }
+        }
+        sent
+    }
+
+    async fn send_bundle(&self, endpoint: &str, bundle: &[PushTransaction]) -> CliResult {
+        let encoded = bundle
+            .iter()
+            .map(|push| Ok(base64::engine::general_purpose::STANDARD.encode(bincode::serialize(&push.transaction)?)))
+            .collect::<Result<Vec<String>, Box<dyn std::error::Error>>>()?;
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "sendBundle",
+            "params": [encoded, { "encoding": "base64" }],
+        });
+        let response: serde_json::Value = self
+            .http
+            .post(format!("{}/api/v1/bundles", endpoint.trim_end_matches('/')))
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        match response.get("error") {
+            Some(error) => Err(format!("sendBundle: {}", error).into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Wait for `signatures` to confirm; returns the ones that succeeded
+    async fn confirm(&self, signatures: &[Signature]) -> Result<HashSet<Signature>, Box<dyn std::error::Error>> {
+        let mut landed = HashSet::new();
+        let mut pending: Vec<Signature> = signatures.to_vec();
+        let deadline = Instant::now() + CONFIRM_TIMEOUT;
+
+        while !pending.is_empty() && Instant::now() < deadline {
+            tokio::time::sleep(CONFIRM_POLL_INTERVAL).await;
+            let statuses = self.metrics.track_rpc(self.rpc.get_signature_statuses(&pending).await)?.value;
+            let mut still_pending = Vec::new();
+            for (signature, status) in pending.iter().zip(statuses) {
+                match status {
+                    Some(status) if status.satisfies_commitment(self.rpc.commitment()) => {
+                        self.metrics.record_transaction(status.err.is_none());
+                        match status.err {
+                            None => {
+                                landed.insert(*signature);
+                            }
+                            Some(e) => eprintln!("{} failed: {}", signature, e),
+                        }
+                    }
+                    _ => still_pending.push(*signature),
+                }
+            }
+            pending = still_pending;
+        }
+        // Expired without landing
+        for _ in &pending {
+            self.metrics.record_transaction(false);
+        }
+        Ok(landed)
+    }
+
+    /// Remember a landed push and reclaim the accounts it retires
+    async fn record(&mut self, push: PushTransaction) {
+        println!(
+            "{}: {} @ {} -> {}",
+            feed_id_to_hex(&push.feed_id),
+            push.last.price,
+            push.last.publish_time,
+            push.price_update
+        );
+        self.metrics.incr("updates_pushed");
+        self.last.insert(push.feed_id, push.last);
+
+        let live = self.live.entry(push.feed_id).or_default();
+        live.push_back(push.price_update);
+        let retired: Vec<Pubkey> = live.drain(..live.len().saturating_sub(KEEP_PER_FEED)).collect();
+        self.reclaim(&retired).await;
+        self.metrics
+            .set("live_price_accounts", self.live.values().map(VecDeque::len).sum::<usize>() as u64);
+    }
+
+    /// Close `accounts`, returning their rent to the keeper
+    async fn reclaim(&self, accounts: &[Pubkey]) {
+        for batch in accounts.chunks(RECLAIMS_PER_TX) {
+            let instructions: Vec<Instruction> = batch
+                .iter()
+                .map(|account| build_reclaim_rent_instruction(&self.payer.pubkey(), account))
+                .collect();
+            let result = async {
+                let blockhash = self.rpc.get_latest_blockhash().await?;
+                let payer = Some(&self.payer.pubkey());
+                let tx = Transaction::new_signed_with_payer(&instructions, payer, &[&self.payer], blockhash);
+                self.rpc.send_and_confirm_transaction(&tx).await
+            }
+            .await;
+            let result = self.metrics.track_rpc(result);
+            self.metrics.record_transaction(result.is_ok());
+            match result {
+                Ok(_) => self.metrics.add("rent_reclaimed", batch.len() as u64),
+                // Left for the next sweep
+                Err(e) => eprintln!("reclaim: {}", e),
+            }
+        }
+    }
+
+    /// Reclaim every update account the keeper wrote that is not live
+    pub async fn sweep(&self) -> CliResult {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &PriceUpdateV2::DISCRIMINATOR)),
+                // write_authority follows the discriminator
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(8, self.payer.pubkey().as_ref())),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: Some(UiDataSliceConfig { offset: 0, length: 0 }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let owned = self.metrics.track_rpc(
+            self.rpc
+                .get_program_accounts_with_config(&PYTH_RECEIVER_PROGRAM_ID, config)
+                .await,
+        )?;
+
+        let live: HashSet<&Pubkey> = self.live.values().flatten().collect();
+        let stale: Vec<Pubkey> = owned
+            .into_iter()
+            .map(|(address, _)| address)
+            .filter(|address| !live.contains(address))
+            .collect();
+        if !stale.is_empty() {
+            println!("Sweeping {} leftover price update account(s)", stale.len());
+            self.reclaim(&stale).await;
+        }
+        Ok(())
+    }
+
+    /// Push `feed_ids` from the Hermes stream until it ends
+    pub async fn run(&mut self, feed_ids: Vec<[u8; 32]>) -> CliResult {
+        self.sweep().await?;
+        self.metrics.set_ready();
+        let mut next_sweep = Instant::now() + SWEEP_INTERVAL;
+        let mut stream = Box::pin(self.hermes.subscribe_price_updates(feed_ids, StreamConfig::default()));
+
+        while let Some(item) = stream.next().await {
+            let update = match item {
+                Ok(update) => update,
+                Err(e) => {
+                    eprintln!("stream: {}", e);
+                    continue;
+                }
+            };
+            self.metrics.add("price_updates", update.updates.parsed.len() as u64);
+            for parsed in &update.updates.parsed {
+                self.metrics.observe_price(&parsed.feed_id, parsed.price.publish_time);
+            }
+
+            let due: Vec<[u8; 32]> = update
+                .updates
+                .parsed
+                .iter()
+                .filter(|parsed| {
+                    self.policy
+                        .is_due(self.last.get(&parsed.feed_id), parsed.price.price, parsed.price.publish_time)
+                })
+                .map(|parsed| parsed.feed_id)
+                .collect();
+            if !due.is_empty() {
+                self.push(due).await;
+            }
+
+            if Instant::now() >= next_sweep {
+                if let Err(e) = self.sweep().await {
+                    eprintln!("sweep: {}", e);
+                }
+                next_sweep = Instant::now() + SWEEP_INTERVAL;
+            }
+        }
+        Err("Hermes stream ended".into())
+    }
+}
+
+// ============================================================================
+// Run
+// ============================================================================
+
+pub async fn run(context: &Context, args: &[String]) -> CliResult {
+    let mut pusher = Pusher::new(context, context.signer()?);
+    let mut feed_ids = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(USAGE);
+        match arg.as_str() {
+            "--deviation-bps" => pusher.policy.deviation_bps = value()?.parse()?,
+            "--heartbeat" => pusher.policy.heartbeat = Duration::from_secs(value()?.parse()?),
+            "--priority-fee" => {
+                pusher.priority_fee = match value()?.as_str() {
+                    "auto" => PriorityFee::Auto { percentile: 75 },
+                    micro_lamports => PriorityFee::Fixed(micro_lamports.parse()?),
+                }
+            }
+            "--jito" => {
+                pusher.submitter = Submitter::Jito {
+                    endpoint: value()?.clone(),
+                    tip_lamports: 10_000,
+                }
+            }
+            "--jito-tip" => match &mut pusher.submitter {
+                Submitter::Jito { tip_lamports, .. } => *tip_lamports = value()?.parse()?,
+                Submitter::Rpc => return Err("--jito-tip needs --jito first".into()),
+            },
+            "--max-attempts" => pusher.retry.max_attempts = value()?.parse::<u32>()?.max(1),
+            "--consume" => pusher.consumer = Some(Box::new(VerifiedPriceRead)),
+            flag if flag.starts_with("--") => return Err(USAGE.into()),
+            name => feed_ids.push(
+                feed_id_for(name)
+                    .map(Ok)
+                    .unwrap_or_else(|| parse_feed_id(name))
+                    .map_err(|e| format!("unknown feed {name}: {e}"))?,
+            ),
+        }
+    }
+    if feed_ids.is_empty() {
+        return Err(USAGE.into());
+    }
+
+    println!("Pushing {} feed(s) as {}", feed_ids.len(), pusher.payer.pubkey());
+    pusher.run(feed_ids).await
+}
@@ -0,0 +1,318 @@
+/**
+ * Replay a Failed Price Validation - Off-Chain Diagnosis
+ *
+ * A failed transaction reports `custom program error: 0x232a`, which says
+ * nothing about which price was off and by how much. This CLI fetches the
+ * transaction, rebuilds the clock of its slot, loads the price update
+ * accounts of the failed instruction, and re-runs each validation check of
+ * `get_validated_price` locally with the actual numbers:
+ *
+ *   price update 7Gx...: feed 0xef0d8b6f...
+ *     verification  Full (requires Full)              ok
+ *     age           74 s (max 60 s)                   FAILED
+ *     confidence    12 bps (max 200 bps)              ok
+ *
+ * It then runs `get_validated_price` itself and checks that it fails the
+ * same way as on chain, by error name (Anchor logs it next to the number;
+ * the same failure can come from `OracleError` or `PriceSourceError`).
+ *
+ * The clock is the block time of the failed slot, which is the
+ * `Clock::unix_timestamp` the program saw. Account state is read now: a
+ * price update account rewritten after the failure (its `posted_slot` is
+ * later) no longer holds the price that failed and is reported as such.
+ * Ephemeral updates that were closed after use cannot be replayed either;
+ * the `PriceRejected` event (templates/price-events.rs) in the logs still
+ * names the feed and reason.
+ *
+ * The program's validation config is not visible from outside; pass the
+ * one the instruction uses (`--preset`, `--max-age`, `--max-age-slots`,
+ * `--max-conf-bps`, `--feed`).
+ *
+ * Run (a subcommand of automaton.rs, which lists the dependencies):
+ * automaton --rpc $RPC replay-failure <SIGNATURE> [--preset default|strict|lenient]
+ *     [--max-age SECS] [--max-age-slots SLOTS] [--max-conf-bps BPS] [--feed 0x<FEED_ID>]
+ */
+
+use anchor_lang::error::Error;
+use anchor_lang::prelude::Clock;
+use anchor_lang::AccountDeserialize;
+use pyth_oracle::{
+    confidence_bps, confidence_within, get_validated_price, PriceValidationConfig, RejectionReason,
+    PYTH_RECEIVER_PROGRAM_ID,
+};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::TransactionError;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::UiTransactionEncoding;
+use std::str::FromStr;
+
+use crate::{CliResult, Context};
+
+// ============================================================================
+// Failure Context
+// ============================================================================
+
+/// What the failed transaction itself tells
+#[derive(Debug)]
+pub struct FailureContext {
+    pub slot: u64,
+    pub clock: Clock,
+    /// Index of the failed instruction and its custom error number
+    pub instruction: u8,
+    pub error_number: Option<u32>,
+    /// Error name from the Anchor error log, e.g. `PriceTooStale`
+    pub error_name: Option<String>,
+    /// Accounts of the failed instruction
+    pub accounts: Vec<Pubkey>,
+}
+
+/// `Error Code: <name>.` of the last Anchor error log
+pub fn anchor_error_name(logs: &[String]) -> Option<String> {
+    logs.iter().rev().find_map(|line| {
+        let (_, rest) = line.split_once("Error Code: ")?;
+        Some(rest.split('.').next()?.to_string())
+    })
+}
+
+fn error_name(error: &Error) -> String {
+    match error {
+        Error::AnchorError(error) => error.error_name.clone(),
+        Error::ProgramError(error) => format!("{:?}", error.program_error),
+    }
+}
+
+/// Fetch the transaction and collect its failure context
+pub fn load_failure(rpc: &RpcClient, signature: &Signature) -> Result<FailureContext, Box<dyn std::error::Error>> {
+    let tx = rpc.get_transaction_with_config(
+        signature,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        },
+    )?;
+    let meta = tx.transaction.meta.ok_or("transaction has no status meta")?;
+    let Some(TransactionError::InstructionError(instruction, error)) = meta.err else {
+        return Err(format!("transaction did not fail in an instruction: {:?}", meta.err).into());
+    };
+    let error_number = match error {
+        InstructionError::Custom(code) => Some(code),
+        _ => None,
+    };
+    let logs = match meta.log_messages {
+        OptionSerializer::Some(logs) => logs,
+        _ => Vec::new(),
+    };
+
+    let versioned = tx.transaction.transaction.decode().ok_or("cannot decode transaction")?;
+    let mut keys = versioned.message.static_account_keys().to_vec();
+    if let OptionSerializer::Some(loaded) = meta.loaded_addresses {
+        for key in loaded.writable.iter().chain(&loaded.readonly) {
+            keys.push(Pubkey::from_str(key)?);
+        }
+    }
+    let compiled = versioned
+        .message
+        .instructions()
+        .get(instruction as usize)
+        .ok_or("failed instruction index out of range")?;
+    let accounts = compiled.accounts.iter().map(|&index| keys[index as usize]).collect();
+
+    let block_time = tx.block_time.ok_or("block time unavailable for this slot")?;
+    Ok(FailureContext {
+        slot: tx.slot,
+        clock: Clock {
+            slot: tx.slot,
+            unix_timestamp: block_time,
+            ..Clock::default()
+        },
+        instruction,
+        error_number,
+        error_name: anchor_error_name(&logs),
+        accounts,
+    })
+}
+
+// ============================================================================
+// Diagnosis
+// ============================================================================
+
+fn verdict(ok: bool) -> &'static str {
+    if ok {
+        "ok"
+    } else {
+        "FAILED"
+    }
+}
+
+fn feed_id_hex(feed_id: &[u8; 32]) -> String {
+    feed_id.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Print every check for one price update; returns the local result's
+/// error name, if validation fails
+pub fn diagnose(
+    address: &Pubkey,
+    update: &PriceUpdateV2,
+    config: &PriceValidationConfig,
+    clock: &Clock,
+) -> Option<String> {
+    let message = &update.price_message;
+    println!("price update {}: feed 0x{}", address, feed_id_hex(&message.feed_id));
+    println!(
+        "  price         {} ± {} × 10^{} published {}",
+        message.price, message.conf, message.exponent, message.publish_time
+    );
+
+    let verified = update.verification_level.gte(config.verification_level);
+    println!(
+        "  verification  {:?} (requires {:?})  {}",
+        update.verification_level,
+        config.verification_level,
+        verdict(verified)
+    );
+
+    if let Some(expected) = config.expected_feed_id {
+        println!(
+            "  feed id       expected 0x{}  {}",
+            feed_id_hex(&expected),
+            verdict(expected == message.feed_id)
+        );
+    }
+
+    let age = clock.unix_timestamp - message.publish_time;
+    println!(
+        "  age           {} s (max {} s)  {}",
+        age,
+        config.max_age_secs,
+        verdict(age <= config.max_age_secs as i64)
+    );
+
+    if let Some(max_age_slots) = config.max_age_slots {
+        let slot_age = clock.slot.saturating_sub(update.posted_slot);
+        println!(
+            "  slot age      {} slots (max {} slots)  {}",
+            slot_age,
+            max_age_slots,
+            verdict(slot_age <= max_age_slots)
+        );
+    }
+
+    // `confidence_bps` rounds up, as the check does
+    if message.price == 0 {
+        println!("  confidence    price is zero  FAILED");
+    } else {
+        println!(
+            "  confidence    {} bps (max {} bps)  {}",
+            confidence_bps(message.price, message.conf).unwrap_or(u16::MAX),
+            config.max_confidence_bps,
+            verdict(confidence_within(message.price, message.conf, config.max_confidence_bps))
+        );
+    }
+
+    match get_validated_price(update, config, clock) {
+        Ok(_) => {
+            println!("  => passes get_validated_price at this clock");
+            None
+        }
+        Err(error) => {
+            println!(
+                "  => get_validated_price fails: {} ({:?})",
+                error_name(&error),
+                RejectionReason::from_error(&error)
+            );
+            Some(error_name(&error))
+        }
+    }
+}
+
+// ============================================================================
+// CLI
+// ============================================================================
+
+fn parse_config(args: &[String]) -> Result<PriceValidationConfig, Box<dyn std::error::Error>> {
+    let mut config = PriceValidationConfig::default();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or_else(|| format!("{flag} needs a value"))?;
+        match flag.as_str() {
+            "--preset" => {
+                config = match value.as_str() {
+                    "default" => PriceValidationConfig::default(),
+                    "strict" => PriceValidationConfig::strict(),
+                    "lenient" => PriceValidationConfig::lenient(),
+                    other => return Err(format!("unknown preset {other}").into()),
+                }
+            }
+            "--max-age" => config.max_age_secs = value.parse()?,
+            "--max-age-slots" => config.max_age_slots = Some(value.parse()?),
+            "--max-conf-bps" => config.max_confidence_bps = value.parse()?,
+            "--feed" => config = config.with_feed_id(value).map_err(|error| error.to_string())?,
+            other => return Err(format!("unknown flag {other}").into()),
+        }
+    }
+    Ok(config)
+}
+
+pub fn run(context: &Context, args: &[String]) -> CliResult {
+    let signature = args.first().ok_or(
+        "usage: replay-failure <SIGNATURE> [--preset default|strict|lenient] [--max-age SECS] \
+         [--max-age-slots SLOTS] [--max-conf-bps BPS] [--feed 0x<FEED_ID>]",
+    )?;
+    let signature = Signature::from_str(signature)?;
+    let config = parse_config(&args[1..])?;
+
+    let rpc = context.rpc();
+
+    let failure = load_failure(&rpc, &signature)?;
+    println!("=== Transaction {} ===", signature);
+    println!(
+        "Failed instruction {} at slot {} (clock {}) with {} (error {:?})",
+        failure.instruction,
+        failure.slot,
+        failure.clock.unix_timestamp,
+        failure.error_name.as_deref().unwrap_or("an unnamed error"),
+        failure.error_number
+    );
+
+    let accounts = rpc.get_multiple_accounts(&failure.accounts)?;
+    let mut replayed = 0;
+    let mut reproduced = false;
+    for (address, account) in failure.accounts.iter().zip(accounts) {
+        let Some(account) = account else {
+            continue;
+        };
+        if account.owner != PYTH_RECEIVER_PROGRAM_ID {
+            continue;
+        }
+        let Ok(update) = PriceUpdateV2::try_deserialize(&mut account.data.as_slice()) else {
+            continue;
+        };
+        if update.posted_slot > failure.slot {
+            println!(
+                "price update {}: rewritten at slot {} after the failure, cannot replay",
+                address, update.posted_slot
+            );
+            continue;
+        }
+
+        replayed += 1;
+        let local = diagnose(address, &update, &config, &failure.clock);
+        reproduced |= local.is_some() && local == failure.error_name;
+    }
+
+    if replayed == 0 {
+        println!("No price update of the failed instruction is still readable as it was");
+    } else if reproduced {
+        println!("Reproduced the on-chain error locally");
+    } else {
+        println!("Not reproduced: the instruction may use a different config, or failed after validation");
+    }
+    Ok(())
+}
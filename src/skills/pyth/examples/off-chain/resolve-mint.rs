@@ -0,0 +1,166 @@
+/**
+ * Resolve Mint - Pick the Pyth Feed for Any Mint
+ *
+ * Reads each mint's decimals and symbol (Metaplex metadata, the Token-2022
+ * metadata extension, or the JSON at the metadata URI) with the
+ * mint-metadata skill, and looks the symbol up in the feed registry. With
+ * `--bindings` it also loads the mint's `FeedBinding` in the example
+ * program and checks that it points at the same feed.
+ *
+ * Findings (a symbol with no feed, metadata that disagrees with itself, a
+ * mint posing as a well-known one, a binding to another asset) are printed
+ * under each mint, and the command exits with status 1 if there are any,
+ * so a script can gate `init_feed_binding` on it.
+ *
+ * Run (a subcommand of automaton.rs, which lists the dependencies):
+ * automaton --rpc $RPC resolve-mint EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v [MINT...] [--bindings]
+ */
+
+use anchor_lang::AccountDeserialize;
+use mint_metadata::{check_mint, metadata_address, Finding, MintInfo, OffChainMetadata, TokenMetadata};
+use pyth_oracle::client::feed_binding_address;
+use pyth_oracle::FeedBinding;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::{CliResult, Context};
+
+// Metadata URIs are arbitrary hosts; do not let one stall the run
+const URI_TIMEOUT: Duration = Duration::from_secs(5);
+
+const USAGE: &str = "usage: resolve-mint <MINT>... [--bindings]";
+
+// ============================================================================
+// Resolution
+// ============================================================================
+
+fn feed_id_hex(feed_id: &[u8; 32]) -> String {
+    feed_id.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The JSON at the metadata URI; `None` if there is none or it cannot be read
+fn fetch_off_chain(http: &reqwest::blocking::Client, uri: &str) -> Option<OffChainMetadata> {
+    if !uri.starts_with("http") {
+        return None;
+    }
+    match http.get(uri).send().and_then(|r| r.error_for_status()).and_then(|r| r.json()) {
+        Ok(json) => Some(json),
+        Err(e) => {
+            eprintln!("  (metadata URI {uri}: {e})");
+            None
+        }
+    }
+}
+
+/// Resolve one mint; the URI is fetched only once the on-chain metadata
+/// names it
+fn resolve(
+    context: &Context,
+    http: &reqwest::blocking::Client,
+    mint: &Pubkey,
+) -> Result<MintInfo, Box<dyn std::error::Error>> {
+    let rpc = context.rpc();
+    let accounts = rpc.get_multiple_accounts(&[*mint, metadata_address(mint)])?;
+    let mint_account = accounts[0].as_ref().ok_or_else(|| format!("{mint} does not exist"))?;
+    let metaplex = accounts[1].as_ref().map(|account| account.data.as_slice());
+
+    let on_chain = match metaplex {
+        Some(data) => Some(TokenMetadata::parse_metaplex(data)?),
+        None => TokenMetadata::parse_token_2022(&mint_account.data)?,
+    };
+    let off_chain = on_chain.and_then(|metadata| fetch_off_chain(http, &metadata.uri));
+    Ok(MintInfo::resolve(mint, &mint_account.data, metaplex, off_chain.as_ref())?)
+}
+
+/// Feed ID of the mint's binding in the example program, if it has one
+fn bound_feed(context: &Context, mint: &Pubkey) -> Result<Option<[u8; 32]>, Box<dyn std::error::Error>> {
+    let account = context
+        .rpc()
+        .get_account_with_commitment(&feed_binding_address(mint), Default::default())?
+        .value;
+    match account {
+        Some(account) => Ok(Some(FeedBinding::try_deserialize(&mut account.data.as_slice())?.feed_id)),
+        None => Ok(None),
+    }
+}
+
+fn describe(finding: &Finding) -> String {
+    match finding {
+        Finding::NoFeed => "no USD feed in the registry for this symbol".to_string(),
+        Finding::SymbolsDiffer { on_chain, off_chain } => {
+            format!("on-chain symbol {on_chain:?} but URI says {off_chain:?}")
+        }
+        Finding::NotCanonicalMint { canonical } => {
+            format!("claims the symbol of {canonical}; this is not that mint")
+        }
+        Finding::BindingMismatch {
+            bound,
+            bound_symbol,
+            expected,
+        } => format!(
+            "bound to 0x{} ({}), expected {}",
+            feed_id_hex(bound),
+            bound_symbol.unwrap_or("not in registry"),
+            expected.unwrap_or("no feed")
+        ),
+    }
+}
+
+// ============================================================================
+// Entry Point
+// ============================================================================
+
+pub fn run(context: &Context, args: &[String]) -> CliResult {
+    let check_bindings = args.iter().any(|arg| arg == "--bindings");
+    let mints: Vec<Pubkey> = args
+        .iter()
+        .filter(|arg| *arg != "--bindings")
+        .map(|arg| Pubkey::from_str(arg))
+        .collect::<Result<_, _>>()?;
+    if mints.is_empty() {
+        return Err(USAGE.into());
+    }
+
+    let http = reqwest::blocking::Client::builder().timeout(URI_TIMEOUT).build()?;
+    let mut flagged = 0;
+    for mint in &mints {
+        println!("=== {mint} ===");
+        let info = match resolve(context, &http, mint) {
+            Ok(info) => info,
+            Err(e) => {
+                println!("  error: {e}");
+                flagged += 1;
+                continue;
+            }
+        };
+        let bound = if check_bindings { bound_feed(context, mint)? } else { None };
+        let (feed_match, findings) = check_mint(&info, bound.as_ref());
+
+        println!("  {} ({}), {} decimals, from {:?}", info.symbol, info.name, info.decimals, info.source);
+        match feed_match {
+            Some(feed_match) => println!(
+                "  feed: {} 0x{}{}",
+                feed_match.feed.symbol,
+                feed_id_hex(&feed_match.feed.feed_id),
+                feed_match.alias_of.map(|base| format!(" (as {base})")).unwrap_or_default()
+            ),
+            None => println!("  feed: none"),
+        }
+        if check_bindings && bound.is_none() {
+            println!("  binding: none");
+        }
+        for finding in &findings {
+            println!("  ! {}", describe(finding));
+        }
+        if !findings.is_empty() {
+            flagged += 1;
+        }
+    }
+
+    if flagged > 0 {
+        eprintln!("{flagged} of {} mints need review", mints.len());
+        std::process::exit(1);
+    }
+    Ok(())
+}
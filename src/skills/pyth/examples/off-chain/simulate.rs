@@ -0,0 +1,392 @@
+/**
+ * Simulate - Dry-Run the Example Program Against Fresh Prices
+ *
+ * Sends nothing: builds the transaction a bot is about to send, runs it
+ * through `simulateTransaction` with signature verification off and the
+ * blockhash replaced, and decodes what the program did from the logs:
+ *
+ *   - the `ValidatedPrice` of every `PriceConsumed` event, i.e. the price
+ *     the program acted on after its own staleness and confidence checks
+ *   - every `PriceRejected` event and the Anchor error name on failure
+ *     (`PriceTooStale`, `SlippageExceeded`, ...)
+ *   - the `Name: value` lines the program logged, such as the swap's
+ *     `Output` and `Fee`, and the compute units it used
+ *
+ * No fee is charged and no account changes, so a bot can check slippage
+ * and staleness before paying for a transaction that would fail.
+ *
+ * Fresh prices reach the program in one of two ways:
+ *
+ *   price  The latest Hermes update travels inside the instruction
+ *          (`post_and_get_price`). Its VAA carries
+ *          `DEFAULT_ATOMIC_SIGNATURES`, so it is partially verified and
+ *          passes only lenient validation.
+ *   swap   Instructions validating with `default()` or `strict()` need
+ *          full verification, which an inline VAA cannot carry. They read
+ *          the push feed accounts, which the sponsored pusher updates from
+ *          the same Hermes stream; the Hermes price is printed next to the
+ *          consumed one to show how far behind the account is.
+ *
+ * `Simulator::simulate` takes any instructions, so a bot can dry-run its
+ * own transaction the same way before sending it.
+ *
+ * Run (a subcommand of automaton.rs, which lists the dependencies):
+ * automaton --rpc $RPC --keypair trader.json simulate price SOL/USD
+ * automaton --rpc $RPC --keypair trader.json simulate swap <INPUT_MINT> <OUTPUT_MINT> \
+ *     <USER_INPUT> <USER_OUTPUT> <AMOUNT_IN> <MIN_OUT>
+ */
+
+use anchor_lang::AccountDeserialize;
+use pyth_oracle::client::{self, market_address, push_feed_address, swap_pool_address, SwapAccounts};
+use pyth_oracle::hermes::{feed_id_to_hex, HermesClient, PriceUpdates};
+use pyth_oracle::{feed_id_for, parse_feed_id, Market, ValidatedPrice};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use std::str::FromStr;
+
+use crate::price_events::{events_in_logs, PriceConsumed, PriceEvent, PriceRejected};
+use crate::replay_failure::anchor_error_name;
+use crate::{CliResult, Context};
+
+// Hermes endpoint (production)
+const HERMES_ENDPOINT: &str = "https://hermes.pyth.network";
+
+// The transaction maximum, so the simulation never fails on the default
+// per-instruction limit; `units_consumed` tells what to request for real
+const SIMULATION_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+const USAGE: &str = "usage: simulate price <FEED>
+       simulate swap <INPUT_MINT> <OUTPUT_MINT> <USER_INPUT> <USER_OUTPUT> <AMOUNT_IN> <MIN_OUT>";
+
+// ============================================================================
+// Simulation Result
+// ============================================================================
+
+/// What the program did in a simulated transaction
+#[derive(Debug)]
+pub struct Simulation {
+    pub error: Option<TransactionError>,
+    /// Anchor error name of the failure, e.g. `SlippageExceeded`
+    pub error_name: Option<String>,
+    /// Prices the program consumed, in order
+    pub consumed: Vec<PriceConsumed>,
+    pub rejected: Vec<PriceRejected>,
+    /// `Name: value` lines the program logged, in order
+    pub values: Vec<(String, String)>,
+    pub units_consumed: Option<u64>,
+    pub logs: Vec<String>,
+}
+
+impl Simulation {
+    fn from_logs(
+        program_id: &Pubkey,
+        error: Option<TransactionError>,
+        logs: Vec<String>,
+        units_consumed: Option<u64>,
+    ) -> Self {
+        let mut consumed = Vec::new();
+        let mut rejected = Vec::new();
+        for event in events_in_logs(program_id, &logs) {
+            match event {
+                PriceEvent::Consumed(event) => consumed.push(event),
+                PriceEvent::Rejected(event) => rejected.push(event),
+                PriceEvent::BreakerTripped(_) | PriceEvent::ConfigUpdated => {}
+            }
+        }
+        Self {
+            error_name: error.as_ref().and_then(|_| anchor_error_name(&logs)),
+            error,
+            consumed,
+            rejected,
+            values: logged_values(program_id, &logs),
+            units_consumed,
+            logs,
+        }
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// The validated prices behind the consumed events, in order
+    pub fn prices(&self) -> Vec<ValidatedPrice> {
+        self.consumed
+            .iter()
+            .map(|event| {
+                let conf = event.conf as i64;
+                ValidatedPrice {
+                    feed_id: event.feed_id,
+                    price: event.price,
+                    conf: event.conf,
+                    exponent: event.exponent,
+                    publish_time: event.publish_time,
+                    lower_bound: event.price.saturating_sub(conf),
+                    upper_bound: event.price.saturating_add(conf),
+                }
+            })
+            .collect()
+    }
+
+    /// Last value logged as `name: ...`
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .rev()
+            .find(|(logged, _)| logged == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Leading integer of the value logged as `name`, e.g. the swap's
+    /// `Output` or the bps of its `Fee`
+    pub fn amount(&self, name: &str) -> Option<u64> {
+        self.value(name)?.split_whitespace().next()?.parse().ok()
+    }
+}
+
+/// `Program log: Name: value` lines logged while `program_id` is the
+/// innermost running program
+fn logged_values(program_id: &Pubkey, logs: &[String]) -> Vec<(String, String)> {
+    let program = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut values = Vec::new();
+
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(message) = rest.strip_prefix("log: ") {
+            let framework = message.starts_with("Instruction: ") || message.starts_with("AnchorError");
+            if stack.last() != Some(&program.as_str()) || framework {
+                continue;
+            }
+            if let Some((name, value)) = message.split_once(": ") {
+                values.push((name.to_string(), value.to_string()));
+            }
+        } else if let Some((id, tail)) = rest.split_once(' ') {
+            if tail.starts_with("invoke [") {
+                stack.push(id);
+            } else if tail == "success" || tail.starts_with("failed") {
+                stack.pop();
+            }
+        }
+    }
+    values
+}
+
+// ============================================================================
+// Simulator
+// ============================================================================
+
+pub struct Simulator {
+    rpc: RpcClient,
+    hermes: HermesClient,
+    /// Fee payer of the simulated transaction; nothing is signed
+    payer: Pubkey,
+}
+
+impl Simulator {
+    pub fn new(rpc: RpcClient, payer: Pubkey) -> Self {
+        Self {
+            rpc,
+            hermes: HermesClient::new(HERMES_ENDPOINT),
+            payer,
+        }
+    }
+
+    /// Latest Hermes update of one feed, alone in its blob
+    pub async fn fresh_update(&self, feed_id: &[u8; 32]) -> Result<PriceUpdates, Box<dyn std::error::Error>> {
+        let updates = self.hermes.get_latest_price_updates(std::slice::from_ref(feed_id)).await?;
+        if updates.get(feed_id).is_none() || updates.update_data.is_empty() {
+            return Err(format!("Hermes returned no update for {}", feed_id_to_hex(feed_id)).into());
+        }
+        Ok(updates)
+    }
+
+    /// Simulate `instructions` of the example program in one transaction
+    pub async fn simulate(&self, instructions: &[Instruction]) -> Result<Simulation, Box<dyn std::error::Error>> {
+        let mut all = vec![ComputeBudgetInstruction::set_compute_unit_limit(SIMULATION_COMPUTE_UNIT_LIMIT)];
+        all.extend_from_slice(instructions);
+        let tx = Transaction::new_with_payer(&all, Some(&self.payer));
+
+        // The RPC refuses oversized transactions without simulating them
+        let size = bincode::serialized_size(&tx)?;
+        if size > PACKET_DATA_SIZE as u64 {
+            return Err(format!("transaction is {size} bytes, over the {PACKET_DATA_SIZE}-byte limit").into());
+        }
+
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(CommitmentConfig::confirmed()),
+            ..Default::default()
+        };
+        let result = self.rpc.simulate_transaction_with_config(&tx, config).await?.value;
+        Ok(Simulation::from_logs(
+            &pyth_oracle::ID,
+            result.err,
+            result.logs.unwrap_or_default(),
+            result.units_consumed,
+        ))
+    }
+}
+
+// ============================================================================
+// Instructions
+// ============================================================================
+
+/// `post_and_get_price` carrying the update of `updates` inline
+pub fn post_and_get_price(payer: &Pubkey, updates: &PriceUpdates) -> Result<Instruction, Box<dyn std::error::Error>> {
+    let update_data = updates.update_data.first().ok_or("no update data")?;
+    // A throwaway address; the program creates the account
+    let price_update = Keypair::new().pubkey();
+    Ok(client::post_and_get_price(payer, &price_update, update_data)?)
+}
+
+/// A swap to dry-run: the accounts `swap_with_oracle` does not derive
+pub struct SwapRequest {
+    pub user: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub user_input: Pubkey,
+    pub user_output: Pubkey,
+    pub amount_in: u64,
+    pub min_amount_out: u64,
+}
+
+/// Pool of the pair, whichever way round it was created
+async fn find_swap_pool(rpc: &RpcClient, a: &Pubkey, b: &Pubkey) -> Result<Pubkey, Box<dyn std::error::Error>> {
+    for pool in [swap_pool_address(a, b), swap_pool_address(b, a)] {
+        if rpc.get_account(&pool).await.is_ok() {
+            return Ok(pool);
+        }
+    }
+    Err(format!("no swap pool for {a} and {b}").into())
+}
+
+/// `swap_with_oracle` reading the push feed accounts of both mints'
+/// market feeds; returns the instruction and the two feed ids
+pub async fn swap_with_oracle(
+    rpc: &RpcClient,
+    request: &SwapRequest,
+) -> Result<(Instruction, [[u8; 32]; 2]), Box<dyn std::error::Error>> {
+    let mut feed_ids = [[0; 32]; 2];
+    for (feed_id, mint) in feed_ids.iter_mut().zip([&request.input_mint, &request.output_mint]) {
+        let data = rpc.get_account_data(&market_address(mint)).await?;
+        *feed_id = Market::try_deserialize(&mut data.as_slice())?.feed_id;
+    }
+
+    let accounts = SwapAccounts {
+        user: request.user,
+        pool: find_swap_pool(rpc, &request.input_mint, &request.output_mint).await?,
+        input_mint: request.input_mint,
+        output_mint: request.output_mint,
+        user_input: request.user_input,
+        user_output: request.user_output,
+        input_price: push_feed_address(&feed_ids[0]),
+        output_price: push_feed_address(&feed_ids[1]),
+        token_program: rpc.get_account(&request.input_mint).await?.owner,
+    };
+    let instruction = client::swap_with_oracle(&accounts, request.amount_in, request.min_amount_out, false);
+    Ok((instruction, feed_ids))
+}
+
+// ============================================================================
+// Entry Point
+// ============================================================================
+
+fn print_simulation(simulation: &Simulation) {
+    for (event, price) in simulation.consumed.iter().zip(simulation.prices()) {
+        println!(
+            "Consumed {:?}: feed 0x{} {} ± {} × 10^{} [{}, {}] published {}",
+            event.used_for,
+            feed_id_to_hex(&price.feed_id),
+            price.price,
+            price.conf,
+            price.exponent,
+            price.lower_bound,
+            price.upper_bound,
+            price.publish_time
+        );
+    }
+    for event in &simulation.rejected {
+        println!(
+            "Rejected {:?}: feed 0x{} {:?} (error {})",
+            event.used_for,
+            feed_id_to_hex(&event.feed_id),
+            event.reason,
+            event.error_code
+        );
+    }
+    for (name, value) in &simulation.values {
+        println!("{}: {}", name, value);
+    }
+    if let Some(units) = simulation.units_consumed {
+        println!("Compute units: {}", units);
+    }
+    match (&simulation.error, &simulation.error_name) {
+        (None, _) => println!("Would succeed"),
+        (Some(_), Some(name)) => println!("Would fail: {}", name),
+        (Some(error), None) => println!("Would fail: {}", error),
+    }
+}
+
+/// The fee payer and swap user: the signer's address; nothing is signed
+fn payer(context: &Context) -> Result<Pubkey, Box<dyn std::error::Error>> {
+    Ok(context.signer()?.pubkey())
+}
+
+pub async fn run(context: &Context, args: &[String]) -> CliResult {
+    let rpc = context.nonblocking_rpc();
+
+    let simulation = match args {
+        [command, feed] if command == "price" => {
+            let feed_id = feed_id_for(feed).map(Ok).unwrap_or_else(|| parse_feed_id(feed))?;
+            let payer = payer(context)?;
+            let simulator = Simulator::new(rpc, payer);
+            let updates = simulator.fresh_update(&feed_id).await?;
+            let hermes = updates.get(&feed_id).unwrap().price;
+            println!(
+                "Hermes: {} ± {} × 10^{} published {}",
+                hermes.price, hermes.conf, hermes.exponent, hermes.publish_time
+            );
+            simulator.simulate(&[post_and_get_price(&payer, &updates)?]).await?
+        }
+        [command, input_mint, output_mint, user_input, user_output, amount_in, min_amount_out] if command == "swap" => {
+            let request = SwapRequest {
+                user: payer(context)?,
+                input_mint: Pubkey::from_str(input_mint)?,
+                output_mint: Pubkey::from_str(output_mint)?,
+                user_input: Pubkey::from_str(user_input)?,
+                user_output: Pubkey::from_str(user_output)?,
+                amount_in: amount_in.parse()?,
+                min_amount_out: min_amount_out.parse()?,
+            };
+            let (instruction, feed_ids) = swap_with_oracle(&rpc, &request).await?;
+            let simulator = Simulator::new(rpc, request.user);
+            for feed_id in &feed_ids {
+                let updates = simulator.fresh_update(feed_id).await?;
+                let hermes = updates.get(feed_id).unwrap().price;
+                println!(
+                    "Hermes: feed 0x{} {} ± {} × 10^{} published {}",
+                    feed_id_to_hex(feed_id),
+                    hermes.price,
+                    hermes.conf,
+                    hermes.exponent,
+                    hermes.publish_time
+                );
+            }
+            simulator.simulate(&[instruction]).await?
+        }
+        _ => return Err(USAGE.into()),
+    };
+
+    print_simulation(&simulation);
+    Ok(())
+}
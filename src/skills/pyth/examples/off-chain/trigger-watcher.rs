@@ -0,0 +1,167 @@
+/**
+ * Trigger Watcher - Off-Chain Keeper for Trigger Orders
+ *
+ * Loads every open `TriggerOrder` of the example program, subscribes to
+ * the price stream for their feeds (`Context::price_stream`: Hermes, or
+ * the push feed accounts with `--prices accounts`), and checks each order
+ * on every update with the same `TriggerOrder::is_triggered` the program
+ * runs. When an unexpired order's conservative bound crosses its trigger,
+ * it sends `execute_trigger`.
+ *
+ * The program reads prices from the sponsored push feed accounts, which
+ * can trail the Hermes stream by a few seconds. An execution sent before
+ * they catch up fails preflight with `TriggerNotReached` and is retried on
+ * a later update; watching the accounts avoids the lag. Executed and
+ * cancelled orders drop out on the next reload.
+ *
+ * Metrics: `price_updates`, `triggers_sent`, `triggers_failed`, and the
+ * `orders_watched` gauge, plus price latency and staleness, transaction
+ * outcomes, and RPC errors (metrics.rs). Ready once the orders are loaded.
+ *
+ * Run (a subcommand of automaton.rs, which lists the dependencies):
+ * automaton --rpc $RPC --keypair ~/.config/solana/id.json trigger-watcher
+ */
+
+use futures::StreamExt;
+use pyth_oracle::client::{execute_trigger, push_feed_address};
+use pyth_oracle::{OraclePrice, TriggerOrder, ValidatedPrice};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::liquidator::load_accounts;
+use crate::{CliResult, Context, Metrics};
+
+// How often orders are reloaded
+const RELOAD_INTERVAL: Duration = Duration::from_secs(60);
+
+// How long to wait before retrying an order after a send
+const RETRY_COOLDOWN: Duration = Duration::from_secs(10);
+
+// ============================================================================
+// Orders
+// ============================================================================
+
+/// The `execute_trigger` instruction for an order, if the latest streamed
+/// price has crossed its trigger
+fn execution_for(
+    executor: &Pubkey,
+    order: &TriggerOrder,
+    prices: &HashMap<[u8; 32], ValidatedPrice>,
+) -> Option<Instruction> {
+    let price = prices.get(&order.feed_id)?;
+    if order.is_expired(price.publish_time) || !order.is_triggered(price).ok()? {
+        return None;
+    }
+    println!(
+        "order {} of {}: {:?} {} × 10^{} crossed by {} ± {} × 10^{}",
+        order.order_id,
+        order.owner,
+        order.direction,
+        order.trigger_price,
+        order.trigger_exponent,
+        price.price,
+        price.conf,
+        price.exponent
+    );
+    Some(execute_trigger(
+        executor,
+        &order.owner,
+        order.order_id,
+        &push_feed_address(&order.feed_id),
+    ))
+}
+
+/// Whether the order was executed
+async fn send(rpc: &RpcClient, payer: &Keypair, instruction: Instruction, metrics: &Metrics) -> bool {
+    let blockhash = match metrics.track_rpc(rpc.get_latest_blockhash().await) {
+        Ok(blockhash) => blockhash,
+        Err(e) => {
+            eprintln!("blockhash: {}", e);
+            return false;
+        }
+    };
+    let tx = Transaction::new_signed_with_payer(&[instruction], Some(&payer.pubkey()), &[payer], blockhash);
+    let result = metrics.track_rpc(rpc.send_and_confirm_transaction(&tx).await);
+    metrics.record_transaction(result.is_ok());
+    match result {
+        Ok(signature) => {
+            metrics.incr("triggers_sent");
+            println!("  executed: {}", signature);
+            true
+        }
+        Err(e) => {
+            metrics.incr("triggers_failed");
+            eprintln!("  failed: {}", e);
+            false
+        }
+    }
+}
+
+// ============================================================================
+// Run
+// ============================================================================
+
+pub async fn run(context: &Context, _args: &[String]) -> CliResult {
+    let rpc = context.nonblocking_rpc();
+    let payer = context.signer()?;
+
+    loop {
+        let orders = context.metrics.track_rpc(load_accounts::<TriggerOrder>(&rpc).await)?;
+        let feed_ids: Vec<[u8; 32]> = orders
+            .iter()
+            .map(|(_, order)| order.feed_id)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        println!("Watching {} trigger orders over {} feeds", orders.len(), feed_ids.len());
+        context.metrics.set("orders_watched", orders.len() as u64);
+        context.metrics.set_ready();
+
+        let mut prices: HashMap<[u8; 32], ValidatedPrice> = HashMap::new();
+        let mut cooldown: HashMap<Pubkey, Instant> = HashMap::new();
+        let mut executed: HashSet<Pubkey> = HashSet::new();
+        let reload_at = Instant::now() + RELOAD_INTERVAL;
+        let mut stream = context.price_stream(feed_ids);
+
+        while let Some(item) = stream.next().await {
+            let update = match item {
+                Ok(update) => update,
+                Err(e) => {
+                    eprintln!("stream: {}", e);
+                    continue;
+                }
+            };
+            context.metrics.add("price_updates", update.updates.parsed.len() as u64);
+            for parsed in &update.updates.parsed {
+                context.metrics.observe_price(&parsed.feed_id, parsed.price.publish_time);
+                prices.insert(
+                    parsed.feed_id,
+                    ValidatedPrice::from_price(parsed.feed_id, &OraclePrice::from(parsed.price)),
+                );
+            }
+
+            let now = Instant::now();
+            for (address, order) in &orders {
+                if executed.contains(address) || cooldown.get(address).is_some_and(|until| *until > now) {
+                    continue;
+                }
+                if let Some(instruction) = execution_for(&payer.pubkey(), order, &prices) {
+                    cooldown.insert(*address, now + RETRY_COOLDOWN);
+                    if send(&rpc, &payer, instruction, &context.metrics).await {
+                        executed.insert(*address);
+                    }
+                }
+            }
+
+            // Reload to drop executed orders and pick up new ones
+            if now >= reload_at {
+                break;
+            }
+        }
+    }
+}
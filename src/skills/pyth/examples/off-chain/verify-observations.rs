@@ -0,0 +1,191 @@
+/**
+ * Verify Recorded Price Observations - Off-Chain Auditor
+ *
+ * Reads an ObservationLog account (templates/observation-log.rs) and checks
+ * every recorded (feed, price, conf, publish_time) tuple against the signed
+ * historical price served by Hermes, proving which oracle values a protocol
+ * actually consumed.
+ *
+ * Run (a subcommand of automaton.rs, which lists the dependencies):
+ * automaton --rpc $RPC verify-observations <OBSERVATION_LOG_ADDRESS>
+ */
+
+use borsh::BorshDeserialize;
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::{CliResult, Context};
+
+// Hermes endpoint (production)
+const HERMES_ENDPOINT: &str = "https://hermes.pyth.network";
+
+// Anchor account discriminator length
+const DISCRIMINATOR_LEN: usize = 8;
+
+// ============================================================================
+// Account Layout (mirrors templates/observation-log.rs)
+// ============================================================================
+
+#[derive(BorshDeserialize, Debug, Clone, Copy)]
+pub struct PriceObservation {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub slot: u64,
+    pub consumer: Pubkey,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct ObservationLog {
+    pub authority: Pubkey,
+    pub head: u32,
+    pub total_recorded: u64,
+    pub observations: Vec<PriceObservation>,
+    pub bump: u8,
+}
+
+// ============================================================================
+// Hermes Response Types
+// ============================================================================
+
+#[derive(Deserialize, Debug)]
+struct HermesPrice {
+    price: String,
+    conf: String,
+    expo: i32,
+    publish_time: i64,
+}
+
+#[derive(Deserialize, Debug)]
+struct HermesParsedUpdate {
+    id: String,
+    price: HermesPrice,
+}
+
+#[derive(Deserialize, Debug)]
+struct HermesUpdateResponse {
+    parsed: Vec<HermesParsedUpdate>,
+}
+
+// ============================================================================
+// Verification
+// ============================================================================
+
+/// Outcome of checking one observation against Hermes
+#[derive(Debug)]
+pub enum VerificationResult {
+    /// Hermes returned the exact same price, conf and exponent
+    Verified,
+    /// Hermes returned a different value for that feed and publish time
+    Mismatch { expected: String },
+    /// Hermes has no update for that feed at that publish time
+    NotFound,
+}
+
+fn feed_id_hex(feed_id: &[u8; 32]) -> String {
+    feed_id.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decode an ObservationLog from raw account data
+pub fn decode_observation_log(data: &[u8]) -> Result<ObservationLog, std::io::Error> {
+    let mut body = &data[DISCRIMINATOR_LEN..];
+    ObservationLog::deserialize(&mut body)
+}
+
+/// Fetch the historical price for a feed at its exact publish time
+fn fetch_historical_price(
+    http: &reqwest::blocking::Client,
+    feed_id: &[u8; 32],
+    publish_time: i64,
+) -> Result<Option<HermesPrice>, reqwest::Error> {
+    let id = feed_id_hex(feed_id);
+    let url = format!(
+        "{}/v2/updates/price/{}?ids[]=0x{}&parsed=true",
+        HERMES_ENDPOINT, publish_time, id
+    );
+
+    let response = http.get(url).send()?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    let body: HermesUpdateResponse = response.error_for_status()?.json()?;
+    Ok(body
+        .parsed
+        .into_iter()
+        .find(|update| update.id.trim_start_matches("0x") == id)
+        .map(|update| update.price))
+}
+
+/// Check a single observation against Hermes
+pub fn verify_observation(
+    http: &reqwest::blocking::Client,
+    observation: &PriceObservation,
+) -> Result<VerificationResult, reqwest::Error> {
+    let Some(reference) =
+        fetch_historical_price(http, &observation.feed_id, observation.publish_time)?
+    else {
+        return Ok(VerificationResult::NotFound);
+    };
+
+    let matches = reference.price == observation.price.to_string()
+        && reference.conf == observation.conf.to_string()
+        && reference.expo == observation.exponent
+        && reference.publish_time == observation.publish_time;
+
+    if matches {
+        Ok(VerificationResult::Verified)
+    } else {
+        Ok(VerificationResult::Mismatch {
+            expected: format!(
+                "{} ± {} × 10^{} @ {}",
+                reference.price, reference.conf, reference.expo, reference.publish_time
+            ),
+        })
+    }
+}
+
+pub fn run(context: &Context, args: &[String]) -> CliResult {
+    let address = args.first().ok_or("usage: verify-observations <OBSERVATION_LOG_ADDRESS>")?;
+    let log_address = Pubkey::from_str(address)?;
+
+    let rpc = context.rpc();
+    let http = reqwest::blocking::Client::new();
+
+    let data = rpc.get_account_data(&log_address)?;
+    let log = decode_observation_log(&data)?;
+
+    println!("=== Observation Log {} ===", log_address);
+    println!("Authority: {}", log.authority);
+    println!("Total recorded: {}", log.total_recorded);
+    println!("Stored: {}", log.observations.len());
+
+    let mut failures = 0;
+    for observation in &log.observations {
+        let result = verify_observation(&http, observation)?;
+        println!(
+            "[slot {}] feed 0x{} consumer {}: {} ± {} × 10^{} -> {:?}",
+            observation.slot,
+            feed_id_hex(&observation.feed_id),
+            observation.consumer,
+            observation.price,
+            observation.conf,
+            observation.exponent,
+            result
+        );
+        if !matches!(result, VerificationResult::Verified) {
+            failures += 1;
+        }
+    }
+
+    if failures > 0 {
+        eprintln!("{} observation(s) failed verification", failures);
+        std::process::exit(1);
+    }
+
+    println!("All observations verified against Hermes");
+    Ok(())
+}
@@ -131,7 +131,8 @@ pub mod pyth_oracle_example {
         msg!("Swap: {} in -> {} out (min: {})", amount_in, amount_out, min_amount_out);
         msg!("Price used: {} × 10^{}", safe_price, price.exponent);
 
-        // Execute the actual swap logic here...
+        // Move the tokens here: templates/oracle-swap.rs is the complete
+        // version, with pool vaults and transfer_checked on both legs
 
         Ok(())
     }
@@ -177,6 +178,17 @@ pub mod pyth_oracle_example {
 // ============================================================================
 
 /// Calculate USD value from token amount and price
+/// 10^0 through 10^38, built at compile time so scaling is a table load
+const POW10: [u128; 39] = {
+    let mut table = [1u128; 39];
+    let mut i = 1;
+    while i < 39 {
+        table[i] = table[i - 1] * 10;
+        i += 1;
+    }
+    table
+};
+
 fn calculate_usd_value(
     token_amount: u64,
     token_decimals: u8,
@@ -189,12 +201,12 @@ fn calculate_usd_value(
     let token_value = token_amount as i128;
 
     // Adjust for decimals: token_decimals, price.exponent, and target 6 decimals
-    let decimal_adjustment = 6i32 - (token_decimals as i32) - price.exponent;
+    let decimal_adjustment = 6i32 + price.exponent - (token_decimals as i32);
 
     let usd_value = if decimal_adjustment >= 0 {
-        (token_value * price_value * 10i128.pow(decimal_adjustment as u32)) as u64
+        (token_value * price_value * POW10[decimal_adjustment as usize] as i128) as u64
     } else {
-        (token_value * price_value / 10i128.pow((-decimal_adjustment) as u32)) as u64
+        (token_value * price_value / POW10[(-decimal_adjustment) as usize] as i128) as u64
     };
 
     Ok(usd_value)
@@ -203,7 +215,7 @@ fn calculate_usd_value(
 /// Calculate swap output amount
 fn calculate_swap_output(amount_in: u64, price: i64, exponent: i32) -> Result<u64> {
     // Simplified calculation - adjust based on your token pair
-    let amount_out = (amount_in as i128 * price as i128 / 10i128.pow((-exponent) as u32)) as u64;
+    let amount_out = (amount_in as i128 * price as i128 / POW10[(-exponent) as usize] as i128) as u64;
     Ok(amount_out)
 }
 
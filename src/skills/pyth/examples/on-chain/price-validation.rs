@@ -8,6 +8,10 @@
  * [dependencies]
  * anchor-lang = "0.30.1"
  * pyth-solana-receiver-sdk = "0.3.0"
+ * serde = { version = "1", features = ["derive"], optional = true }
+ *
+ * [features]
+ * serde = ["dep:serde"]   # Serialize/Deserialize on SafePrice
  */
 
 use anchor_lang::prelude::*;
@@ -81,15 +85,13 @@ pub mod price_validation {
             PriceValidationError::PriceTooStale
         );
 
-        // Check confidence ratio
-        let confidence_bps = if price.price != 0 {
-            ((price.conf as u128) * 10000 / (price.price.unsigned_abs() as u128)) as u64
-        } else {
-            u64::MAX
-        };
+        // Check confidence ratio; a zero price saturates it, which a
+        // permissive limit would still accept
+        require!(price.price > 0, PriceValidationError::NegativePrice);
+        let confidence_bps = crate::safe_math::confidence_bps(price)?;
 
         require!(
-            confidence_bps <= config.max_confidence_bps,
+            confidence_bps as u64 <= config.max_confidence_bps,
             PriceValidationError::ConfidenceTooHigh
         );
 
@@ -136,10 +138,54 @@ pub mod price_validation {
 // Safe Price Calculations
 // ============================================================================
 
+/// Decimal, confidence, and fee helpers on the receiver SDK's `Price`
+///
+/// Intentionally standalone: this example depends on anchor-lang and the
+/// receiver SDK alone, so `Rounding`, `POW10`, `confidence_bps`,
+/// `dynamic_fee_bps`, and `deduct_fee` repeat those of anchor-oracle.rs
+/// instead of importing them. examples/testing/price-validation.rs checks
+/// that both give the same results, rounding included.
 pub mod safe_math {
     use super::*;
 
+    /// Direction to round when a conversion drops precision. Round against
+    /// the user: floor what they receive or are credited (collateral value,
+    /// swap output), ceil what they owe (debt, repayment, input required).
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Rounding {
+        Floor,
+        Ceil,
+        /// Halves round up
+        Nearest,
+    }
+
+    /// 10^0 through 10^38, built at compile time: one load per scaling
+    /// instead of a `10u128.pow` multiplication loop
+    pub const POW10: [u128; 39] = {
+        let mut table = [1u128; 39];
+        let mut i = 1;
+        while i < 39 {
+            table[i] = table[i - 1] * 10;
+            i += 1;
+        }
+        table
+    };
+
+    /// `numerator / denominator` rounded as requested
+    fn div_rounded(numerator: u128, denominator: u128, rounding: Rounding) -> u128 {
+        let quotient = numerator / denominator;
+        let remainder = numerator % denominator;
+        let round_up = match rounding {
+            Rounding::Floor => false,
+            Rounding::Ceil => remainder > 0,
+            Rounding::Nearest => remainder >= denominator - remainder,
+        };
+        quotient + round_up as u128
+    }
+
     /// Price with safety bounds applied
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct SafePrice {
         /// Lower bound of price (price - confidence)
         pub lower: i64,
@@ -184,20 +230,180 @@ pub mod safe_math {
         }
 
         /// Convert to u64 with decimals adjustment
-        /// Returns None if price is negative
-        pub fn to_u64(&self, target_decimals: u8) -> Option<u64> {
+        /// Returns None if price is negative or does not fit
+        pub fn to_u64(&self, target_decimals: u8, rounding: Rounding) -> Option<u64> {
             if self.mid < 0 {
                 return None;
             }
 
             let decimal_adjustment = (target_decimals as i32) + self.exponent;
             let scaled = if decimal_adjustment >= 0 {
-                (self.mid as u128) * 10u128.pow(decimal_adjustment as u32)
+                (self.mid as u128).checked_mul(*POW10.get(decimal_adjustment as usize)?)?
             } else {
-                (self.mid as u128) / 10u128.pow((-decimal_adjustment) as u32)
+                div_rounded(
+                    self.mid as u128,
+                    *POW10.get((-decimal_adjustment) as usize)?,
+                    rounding,
+                )
             };
 
-            Some(scaled as u64)
+            u64::try_from(scaled).ok()
+        }
+    }
+
+    /// Mantissa bound for the operands of `PriceWithConf::div`, which keeps
+    /// every intermediate of the error propagation inside u128
+    const DIV_OPERAND_MAX: u128 = 1_000_000_000;
+
+    /// Digits the quotient of `PriceWithConf::div` gains over its operands
+    const DIV_PRECISION: usize = 9;
+
+    /// A price and its confidence carried through arithmetic, so that a
+    /// derived price (ETH/BTC from ETH/USD and BTC/USD) keeps usable bounds
+    ///
+    /// Confidence propagates to first order: the relative confidences of the
+    /// operands add, for products and quotients alike. Digits dropped to fit
+    /// a mantissa are added to the confidence, so the interval never narrows.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct PriceWithConf {
+        pub price: i64,
+        pub conf: u64,
+        pub exponent: i32,
+    }
+
+    impl PriceWithConf {
+        pub fn from_pyth_price(price: &Price) -> Self {
+            Self {
+                price: price.price,
+                conf: price.conf,
+                exponent: price.exponent,
+            }
+        }
+
+        /// Drop digits until `|price| <= max_price` and `conf <= max_conf`
+        fn normalize(
+            mut price: i128,
+            mut conf: u128,
+            mut exponent: i32,
+            max_price: u128,
+            max_conf: u128,
+        ) -> Option<(i128, u128, i32)> {
+            while price.unsigned_abs() > max_price || conf > max_conf {
+                let truncated = price % 10 != 0;
+                price /= 10;
+                conf = div_rounded(conf, 10, Rounding::Ceil) + truncated as u128;
+                exponent = exponent.checked_add(1)?;
+            }
+            Some((price, conf, exponent))
+        }
+
+        fn fit(price: i128, conf: u128, exponent: i32) -> Option<Self> {
+            let (price, conf, exponent) =
+                Self::normalize(price, conf, exponent, i64::MAX as u128, u64::MAX as u128)?;
+            Some(Self {
+                price: price as i64,
+                conf: conf as u64,
+                exponent,
+            })
+        }
+
+        /// `self × other`: conf = |a|·conf_b + |b|·conf_a
+        pub fn mul(&self, other: &Self) -> Option<Self> {
+            let price = self.price as i128 * other.price as i128;
+            let conf = (self.price.unsigned_abs() as u128 * other.conf as u128)
+                .checked_add(other.price.unsigned_abs() as u128 * self.conf as u128)?;
+            Self::fit(price, conf, self.exponent.checked_add(other.exponent)?)
+        }
+
+        /// `self / other`: conf = conf_a / |b| + |a|·conf_b / b².
+        /// Returns None for a zero divisor.
+        pub fn div(&self, other: &Self) -> Option<Self> {
+            if other.price == 0 {
+                return None;
+            }
+            let (num, num_conf, num_exponent) = Self::normalize(
+                self.price as i128,
+                self.conf as u128,
+                self.exponent,
+                DIV_OPERAND_MAX,
+                u64::MAX as u128,
+            )?;
+            // A nonzero divisor stays nonzero: it is only scaled above the bound
+            let (den, den_conf, den_exponent) = Self::normalize(
+                other.price as i128,
+                other.conf as u128,
+                other.exponent,
+                DIV_OPERAND_MAX,
+                u64::MAX as u128,
+            )?;
+
+            let scale = POW10[DIV_PRECISION];
+            let den_abs = den.unsigned_abs();
+            let scaled = num * scale as i128;
+            let price = scaled / den;
+            let truncated = scaled % den != 0;
+            let conf = div_rounded(num_conf * scale, den_abs, Rounding::Ceil)
+                + div_rounded(
+                    num.unsigned_abs() * den_conf * scale,
+                    den_abs * den_abs,
+                    Rounding::Ceil,
+                )
+                + truncated as u128;
+            let exponent = num_exponent
+                .checked_sub(den_exponent)?
+                .checked_sub(DIV_PRECISION as i32)?;
+            Self::fit(price, conf, exponent)
+        }
+
+        /// The same price at `exponent`, e.g. -6 for six decimals: the one
+        /// path by which prices change exponent
+        ///
+        /// A larger exponent drops digits, widening the confidence so the
+        /// interval never narrows; past 10^38 everything is dropped. A
+        /// smaller exponent adds digits, failing with `MathOverflow` if the
+        /// mantissas no longer fit.
+        pub fn rescale_to(&self, exponent: i32) -> Result<Self> {
+            let overflow = || error!(PriceValidationError::MathOverflow);
+            let diff = exponent.checked_sub(self.exponent).ok_or_else(overflow)?;
+            if diff >= 0 {
+                let price = self.price as i128;
+                let (price, truncated, conf) = match POW10.get(diff as usize) {
+                    Some(&divisor) => (
+                        price / divisor as i128,
+                        price % divisor as i128 != 0,
+                        div_rounded(self.conf as u128, divisor, Rounding::Ceil),
+                    ),
+                    // The divisor exceeds both mantissas
+                    None => (0, price != 0, (self.conf != 0) as u128),
+                };
+                // Exact at the same exponent; dividing by 10 or more leaves
+                // room for the extra unit
+                Ok(Self {
+                    price: price as i64,
+                    conf: u64::try_from(conf + truncated as u128).map_err(|_| overflow())?,
+                    exponent,
+                })
+            } else {
+                let factor = *POW10.get(diff.unsigned_abs() as usize).ok_or_else(overflow)?;
+                let price = (self.price as i128).checked_mul(factor as i128).ok_or_else(overflow)?;
+                let conf = (self.conf as u128).checked_mul(factor).ok_or_else(overflow)?;
+                Ok(Self {
+                    price: i64::try_from(price).map_err(|_| overflow())?,
+                    conf: u64::try_from(conf).map_err(|_| overflow())?,
+                    exponent,
+                })
+            }
+        }
+
+        /// Bounds for buying and selling at this price
+        pub fn safe_price(&self) -> SafePrice {
+            let conf = i64::try_from(self.conf).unwrap_or(i64::MAX);
+            SafePrice {
+                lower: self.price.saturating_sub(conf),
+                mid: self.price,
+                upper: self.price.saturating_add(conf),
+                exponent: self.exponent,
+            }
         }
     }
 
@@ -207,27 +413,37 @@ pub mod safe_math {
         token_decimals: u8,
         price: &Price,
         usd_decimals: u8,
+        rounding: Rounding,
     ) -> Result<u64> {
-        // Formula: value = amount * price / 10^(token_decimals - price_exponent - usd_decimals)
+        // Formula: value = amount * price * 10^(usd_decimals + price_exponent - token_decimals)
 
+        let overflow = || error!(PriceValidationError::MathOverflow);
         let amount = token_amount as u128;
         let price_val = price.price as i128;
 
         require!(price_val > 0, PriceValidationError::NegativePrice);
 
         let price_val = price_val as u128;
+        let product = amount.checked_mul(price_val).ok_or_else(overflow)?;
 
         // Calculate decimal adjustment
-        let exp_adjustment =
-            (token_decimals as i32) + price.exponent - (usd_decimals as i32);
+        let exp_adjustment = (usd_decimals as i32)
+            .checked_add(price.exponent)
+            .and_then(|e| e.checked_sub(token_decimals as i32))
+            .ok_or_else(overflow)?;
 
         let value = if exp_adjustment >= 0 {
-            (amount * price_val) / 10u128.pow(exp_adjustment as u32)
+            let factor = *POW10.get(exp_adjustment as usize).ok_or_else(overflow)?;
+            product.checked_mul(factor).ok_or_else(overflow)?
         } else {
-            (amount * price_val) * 10u128.pow((-exp_adjustment) as u32)
+            match POW10.get(exp_adjustment.unsigned_abs() as usize) {
+                Some(&divisor) => div_rounded(product, divisor, rounding),
+                // The divisor exceeds any u128: everything is dropped
+                None => (rounding == Rounding::Ceil && product > 0) as u128,
+            }
         };
 
-        Ok(value as u64)
+        u64::try_from(value).map_err(|_| overflow())
     }
 
     /// Calculate how many tokens a USD amount can buy
@@ -236,7 +452,9 @@ pub mod safe_math {
         usd_decimals: u8,
         token_decimals: u8,
         price: &Price,
+        rounding: Rounding,
     ) -> Result<u64> {
+        let overflow = || error!(PriceValidationError::MathOverflow);
         let usd = usd_amount as u128;
         let price_val = price.price as i128;
 
@@ -245,16 +463,75 @@ pub mod safe_math {
         let price_val = price_val as u128;
 
         // Calculate decimal adjustment
-        let exp_adjustment =
-            (usd_decimals as i32) - price.exponent - (token_decimals as i32);
+        let exp_adjustment = (token_decimals as i32)
+            .checked_sub(price.exponent)
+            .and_then(|e| e.checked_sub(usd_decimals as i32))
+            .ok_or_else(overflow)?;
 
         let tokens = if exp_adjustment >= 0 {
-            (usd * 10u128.pow(exp_adjustment as u32)) / price_val
+            let factor = *POW10.get(exp_adjustment as usize).ok_or_else(overflow)?;
+            div_rounded(usd.checked_mul(factor).ok_or_else(overflow)?, price_val, rounding)
         } else {
-            usd / (price_val * 10u128.pow((-exp_adjustment) as u32))
+            let divisor = POW10
+                .get(exp_adjustment.unsigned_abs() as usize)
+                .and_then(|factor| price_val.checked_mul(*factor));
+            match divisor {
+                Some(divisor) => div_rounded(usd, divisor, rounding),
+                // The divisor exceeds any u128: everything is dropped
+                None => (rounding == Rounding::Ceil && usd > 0) as u128,
+            }
         };
 
-        Ok(tokens as u64)
+        u64::try_from(tokens).map_err(|_| overflow())
+    }
+
+    /// Fee curve scaled by oracle confidence:
+    /// `fee_bps = base_fee_bps + conf_bps * confidence_multiplier_bps / 10_000`,
+    /// capped at `max_fee_bps`. Charges more for uncertain prices instead of
+    /// rejecting the trade.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct DynamicFeeConfig {
+        pub base_fee_bps: u16,
+        /// 10_000 adds the full confidence width
+        pub confidence_multiplier_bps: u32,
+        /// At most 10_000
+        pub max_fee_bps: u16,
+    }
+
+    /// Confidence as basis points of the price, rounded up so it never
+    /// understates the uncertainty. Saturates at `u16::MAX`, which is also
+    /// the result for a zero price.
+    pub fn confidence_bps(price: &Price) -> Result<u16> {
+        if price.price == 0 {
+            return Ok(u16::MAX);
+        }
+        let bps = div_rounded(
+            price.conf as u128 * 10_000,
+            price.price.unsigned_abs() as u128,
+            Rounding::Ceil,
+        );
+        Ok(u16::try_from(bps).unwrap_or(u16::MAX))
+    }
+
+    /// Fee in basis points for a price with `conf_bps` confidence
+    pub fn dynamic_fee_bps(conf_bps: u16, config: &DynamicFeeConfig) -> u16 {
+        let confidence_fee = div_rounded(
+            conf_bps as u128 * config.confidence_multiplier_bps as u128,
+            10_000,
+            Rounding::Ceil,
+        );
+        let fee = (config.base_fee_bps as u128).saturating_add(confidence_fee);
+        fee.min(config.max_fee_bps.min(10_000) as u128) as u16
+    }
+
+    /// Split `amount` into (amount after fee, fee), rounding the fee up.
+    /// Returns None if `fee_bps` is above 10_000.
+    pub fn deduct_fee(amount: u64, fee_bps: u16) -> Option<(u64, u64)> {
+        if fee_bps > 10_000 {
+            return None;
+        }
+        let fee = div_rounded(amount as u128 * fee_bps as u128, 10_000, Rounding::Ceil) as u64;
+        Some((amount - fee, fee))
     }
 }
 
@@ -263,6 +540,7 @@ pub mod safe_math {
 // ============================================================================
 
 pub mod multi_price {
+    use super::safe_math::PriceWithConf;
     use super::*;
 
     /// Validate that two prices are from the same timestamp (within tolerance)
@@ -279,45 +557,52 @@ pub mod multi_price {
         Ok(())
     }
 
-    /// Calculate a price ratio (e.g., ETH/BTC from ETH/USD and BTC/USD)
+    /// Calculate a price ratio (e.g., ETH/BTC from ETH/USD and BTC/USD) with
+    /// `result_decimals` decimals. The confidence of both legs carries over,
+    /// so `ratio.safe_price()` gives buy and sell bounds for the derived pair.
     pub fn calculate_price_ratio(
         numerator_price: &Price,
         denominator_price: &Price,
         result_decimals: u8,
-    ) -> Result<u64> {
+    ) -> Result<PriceWithConf> {
         require!(
-            denominator_price.price > 0,
+            numerator_price.price >= 0 && denominator_price.price > 0,
             PriceValidationError::NegativePrice
         );
 
-        let num = numerator_price.price as i128;
-        let denom = denominator_price.price as i128;
-
-        // Adjust for exponent difference
-        let exp_diff = numerator_price.exponent - denominator_price.exponent;
-
-        let ratio = if exp_diff >= 0 {
-            (num * 10i128.pow(result_decimals as u32) * 10i128.pow(exp_diff as u32)) / denom
-        } else {
-            (num * 10i128.pow(result_decimals as u32)) / (denom * 10i128.pow((-exp_diff) as u32))
-        };
-
-        require!(ratio >= 0, PriceValidationError::NegativePrice);
-
-        Ok(ratio as u64)
+        PriceWithConf::from_pyth_price(numerator_price)
+            .div(&PriceWithConf::from_pyth_price(denominator_price))
+            .ok_or_else(|| error!(PriceValidationError::MathOverflow))?
+            .rescale_to(-(result_decimals as i32))
     }
 
     /// TWAP (Time-Weighted Average Price) calculation helper
     /// Combines spot price with EMA for smoother pricing
-    pub fn calculate_twap(spot_price: &Price, ema_price: &Price, spot_weight_bps: u16) -> i64 {
-        let spot_weight = spot_weight_bps as i128;
-        let ema_weight = (10000 - spot_weight_bps) as i128;
+    ///
+    /// Both are brought to the smaller of their exponents first, and the
+    /// confidences are blended with the same weights. A weight above 10_000
+    /// fails with `InvalidWeight`.
+    pub fn calculate_twap(spot_price: &Price, ema_price: &Price, spot_weight_bps: u16) -> Result<PriceWithConf> {
+        require!(spot_weight_bps <= 10_000, PriceValidationError::InvalidWeight);
+        let exponent = spot_price.exponent.min(ema_price.exponent);
+        let spot = PriceWithConf::from_pyth_price(spot_price).rescale_to(exponent)?;
+        let ema = PriceWithConf::from_pyth_price(ema_price).rescale_to(exponent)?;
 
-        let weighted_price = (spot_price.price as i128 * spot_weight
-            + ema_price.price as i128 * ema_weight)
-            / 10000;
+        let spot_weight = spot_weight_bps as i128;
+        let ema_weight = (10_000 - spot_weight_bps) as i128;
+        let price = (spot.price as i128 * spot_weight + ema.price as i128 * ema_weight) / 10_000;
+        let conf = div_rounded(
+            spot.conf as u128 * spot_weight as u128 + ema.conf as u128 * ema_weight as u128,
+            10_000,
+            Rounding::Ceil,
+        );
 
-        weighted_price as i64
+        // A weighted mean lies between its inputs, so both fit
+        Ok(PriceWithConf {
+            price: price as i64,
+            conf: conf as u64,
+            exponent,
+        })
     }
 }
 
@@ -353,6 +638,12 @@ pub enum PriceValidationError {
 
     #[msg("Price verification failed")]
     VerificationFailed,
+
+    #[msg("Math overflow")]
+    MathOverflow,
+
+    #[msg("Weight exceeds 10,000 basis points")]
+    InvalidWeight,
 }
 
 // ============================================================================
@@ -386,7 +677,17 @@ pub mod price_validated_program {
 
         msg!("Using validated sell price: {} × 10^{}", sell_price, price.exponent);
 
-        // Calculate output...
+        // Charge more when the price is uncertain
+        let fee_config = safe_math::DynamicFeeConfig {
+            base_fee_bps: 30,
+            confidence_multiplier_bps: 20_000,
+            max_fee_bps: 300,
+        };
+        let conf_bps = safe_math::confidence_bps(&price)?;
+        let fee_bps = safe_math::dynamic_fee_bps(conf_bps, &fee_config);
+        msg!("Fee: {} bps at {} bps confidence", fee_bps, conf_bps);
+
+        // Calculate output, then safe_math::deduct_fee(amount_out, fee_bps)...
 
         Ok(())
     }
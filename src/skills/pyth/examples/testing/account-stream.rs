@@ -0,0 +1,142 @@
+/**
+ * Tests for the On-Chain Price Account Subscriber
+ *
+ * Feeds mock `PriceUpdateV2` accounts through `price_updates_from_accounts`
+ * and checks that they come out as the items the Hermes subscriber yields:
+ * the same prices and EMA, the posted slot as metadata, one update per new
+ * publish time per feed, and foreign or partially verified accounts as
+ * errors that do not end the stream. The WebSocket and RPC plumbing of
+ * `AccountWatcher` is not exercised here.
+ *
+ * Setup:
+ * 1. Copy this file to `tests/account_stream.rs` of the program crate
+ * 2. Add to Cargo.toml:
+ *    [dev-dependencies]
+ *    futures = "0.3"
+ * 3. Run: cargo test --test account_stream --features client,hermes,test-utils
+ */
+
+use futures::executor::block_on;
+use futures::stream::{self, StreamExt};
+use pyth_oracle::account_stream::{decode_account_change, price_updates_from_accounts, AccountChange};
+use pyth_oracle::hermes::{HermesError, HermesResult};
+use pyth_oracle::hermes_stream::StreamedUpdate;
+use pyth_oracle::test_utils::PriceUpdateBuilder;
+use pyth_oracle::{parse_feed_id, price_feeds};
+use solana_sdk::pubkey::Pubkey;
+
+fn sol() -> [u8; 32] {
+    parse_feed_id(price_feeds::SOL_USD).unwrap()
+}
+
+fn btc() -> [u8; 32] {
+    parse_feed_id(price_feeds::BTC_USD).unwrap()
+}
+
+fn change(address: Pubkey, update: PriceUpdateBuilder) -> AccountChange {
+    AccountChange {
+        address,
+        account: update.to_account(),
+        slot: 100,
+    }
+}
+
+fn collect(changes: Vec<AccountChange>) -> Vec<HermesResult<StreamedUpdate>> {
+    block_on(price_updates_from_accounts(stream::iter(changes)).collect())
+}
+
+/// (feed, publish time) of each update yielded, errors skipped
+fn published(items: &[HermesResult<StreamedUpdate>]) -> Vec<([u8; 32], i64)> {
+    items
+        .iter()
+        .flatten()
+        .flat_map(|item| &item.updates.parsed)
+        .map(|update| (update.feed_id, update.price.publish_time))
+        .collect()
+}
+
+// ============================================================================
+// DECODING
+// ============================================================================
+
+#[test]
+fn accounts_decode_like_hermes_updates() {
+    let update = PriceUpdateBuilder::new(sol())
+        .with_price(150_0000_0000, -8)
+        .with_conf(7_500_000)
+        .with_ema(149_0000_0000, 9_000_000)
+        .published_at(1_700_000_010)
+        .posted_at_slot(42);
+    let parsed = decode_account_change(&change(Pubkey::new_unique(), update)).unwrap();
+
+    assert_eq!(parsed.feed_id, sol());
+    assert_eq!(
+        (parsed.price.price, parsed.price.conf, parsed.price.exponent, parsed.price.publish_time),
+        (150_0000_0000, 7_500_000, -8, 1_700_000_010)
+    );
+    assert_eq!(
+        (parsed.ema_price.price, parsed.ema_price.conf, parsed.ema_price.exponent),
+        (149_0000_0000, 9_000_000, -8)
+    );
+    assert_eq!(parsed.metadata.slot, Some(42));
+    assert_eq!(parsed.metadata.prev_publish_time, Some(1_700_000_009));
+    assert_eq!(parsed.metadata.proof_available_time, None);
+}
+
+#[test]
+fn foreign_and_partially_verified_accounts_are_rejected() {
+    let address = Pubkey::new_unique();
+    let mut foreign = change(address, PriceUpdateBuilder::new(sol()));
+    foreign.account.owner = Pubkey::new_unique();
+    let partial = change(address, PriceUpdateBuilder::new(sol()).partially_verified(5));
+    let mut truncated = change(address, PriceUpdateBuilder::new(sol()));
+    truncated.account.data.truncate(60);
+
+    for change in [foreign, partial, truncated] {
+        assert!(matches!(
+            decode_account_change(&change),
+            Err(HermesError::Decode { value, .. }) if value == address.to_string()
+        ));
+    }
+}
+
+// ============================================================================
+// STREAM
+// ============================================================================
+
+#[test]
+fn each_feed_yields_only_newer_publish_times() {
+    let (sol_feed, sol_shard, btc_feed) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let items = collect(vec![
+        change(sol_feed, PriceUpdateBuilder::new(sol()).published_at(10)),
+        change(btc_feed, PriceUpdateBuilder::new(btc()).published_at(5)),
+        // Rewritten with the same update, and an older one in another shard
+        change(sol_feed, PriceUpdateBuilder::new(sol()).published_at(10)),
+        change(sol_shard, PriceUpdateBuilder::new(sol()).published_at(9)),
+        change(sol_shard, PriceUpdateBuilder::new(sol()).published_at(11)),
+        change(btc_feed, PriceUpdateBuilder::new(btc()).published_at(6)),
+    ]);
+
+    assert_eq!(published(&items), vec![(sol(), 10), (btc(), 5), (sol(), 11), (btc(), 6)]);
+    for item in &items {
+        let item = item.as_ref().unwrap();
+        assert!(!item.backfilled);
+        assert!(item.updates.update_data.is_empty());
+        assert_eq!(item.updates.parsed.len(), 1);
+    }
+}
+
+#[test]
+fn decode_errors_do_not_end_the_stream() {
+    let mut foreign = change(Pubkey::new_unique(), PriceUpdateBuilder::new(sol()).published_at(10));
+    foreign.account.owner = Pubkey::new_unique();
+    let items = collect(vec![
+        change(Pubkey::new_unique(), PriceUpdateBuilder::new(sol()).published_at(9)),
+        foreign,
+        change(Pubkey::new_unique(), PriceUpdateBuilder::new(sol()).published_at(11)),
+    ]);
+
+    assert_eq!(items.len(), 3);
+    assert!(items[1].is_err());
+    assert_eq!(published(&items), vec![(sol(), 9), (sol(), 11)]);
+}
@@ -0,0 +1,207 @@
+/**
+ * Tests for the CLMM Price Conversions
+ *
+ * Known values of clmm-math.rs (SOL at $150 in a SOL/USDC pool, one in
+ * Q64.64, the tick range of Orca and Raydium), range and overflow errors,
+ * and round-trip properties: Q64.64 prices bracket the exact value, square
+ * roots come back to the price, and every tick survives tick -> sqrt price
+ * -> tick and tick -> price -> nearest tick.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml of the program crate:
+ *    [dev-dependencies]
+ *    proptest = "1"
+ *
+ * 2. Copy this file to `tests/clmm-math.rs`
+ * 3. Run: cargo test --test clmm-math
+ */
+
+use std::cmp::Ordering;
+
+use anchor_lang::error::Error;
+use proptest::prelude::*;
+use pyth_oracle::{
+    decimal_to_q64, decimal_to_sqrt_price_x64, decimal_to_tick_index, q64_to_decimal, sqrt_price_x64_to_decimal,
+    sqrt_price_x64_to_tick_index, tick_index_to_sqrt_price_x64, OracleDecimal, OracleError, Rounding, ValidatedPrice,
+    MAX_SQRT_PRICE_X64, MAX_TICK_INDEX, MIN_SQRT_PRICE_X64, MIN_TICK_INDEX, Q64,
+};
+
+const SOL_DECIMALS: u8 = 9;
+const USDC_DECIMALS: u8 = 6;
+
+/// SOL at $150.00: 0.15 USDC base units per lamport
+fn sol_price() -> ValidatedPrice {
+    ValidatedPrice {
+        feed_id: [1; 32],
+        price: 150_0000_0000,
+        conf: 1500_0000,
+        exponent: -8,
+        publish_time: 1_700_000_000,
+        lower_bound: 149_8500_0000,
+        upper_bound: 150_1500_0000,
+    }
+}
+
+fn compare(a: OracleDecimal, b: OracleDecimal) -> Ordering {
+    let scale = a.scale.max(b.scale);
+    let (a, b) = (
+        a.rescale(scale, Rounding::Floor).unwrap(),
+        b.rescale(scale, Rounding::Floor).unwrap(),
+    );
+    a.mantissa.cmp(&b.mantissa)
+}
+
+fn to_f64(value: OracleDecimal) -> f64 {
+    value.mantissa as f64 / 10f64.powi(value.scale)
+}
+
+// ============================================================================
+// KNOWN VALUES
+// ============================================================================
+
+#[test]
+fn one_is_q64_and_tick_zero() {
+    let one = OracleDecimal::new(1, 0);
+    assert_eq!(decimal_to_q64(one, Rounding::Floor), Ok(Q64));
+    assert_eq!(decimal_to_sqrt_price_x64(one, Rounding::Floor), Ok(Q64));
+    assert_eq!(decimal_to_tick_index(one, 1), Ok(0));
+    assert_eq!(sqrt_price_x64_to_tick_index(Q64), Ok(0));
+    assert_eq!(tick_index_to_sqrt_price_x64(0), Ok(Q64));
+}
+
+#[test]
+fn sol_usdc_price_in_pool_units() {
+    let price = sol_price();
+    // floor(0.15 × 2^64) and floor(sqrt(0.15) × 2^64)
+    assert_eq!(price.to_q64(SOL_DECIMALS, USDC_DECIMALS, Rounding::Floor), Ok(2_767_011_611_056_432_742));
+    assert_eq!(price.to_q64(SOL_DECIMALS, USDC_DECIMALS, Rounding::Ceil), Ok(2_767_011_611_056_432_743));
+    assert_eq!(
+        price.to_sqrt_price_x64(SOL_DECIMALS, USDC_DECIMALS, Rounding::Floor),
+        Ok(7_144_393_258_922_745_604)
+    );
+
+    // log_1.0001(0.15) = -18972.15
+    assert_eq!(price.to_tick_index(SOL_DECIMALS, USDC_DECIMALS, 1), Ok(-18_972));
+    assert_eq!(price.to_tick_index(SOL_DECIMALS, USDC_DECIMALS, 64), Ok(-18_944));
+    assert_eq!(sqrt_price_x64_to_tick_index(7_144_393_258_922_745_604), Ok(-18_973));
+}
+
+#[test]
+fn square_roots_of_squares_are_exact() {
+    let quarter = OracleDecimal::new(25, 2);
+    let two_and_a_quarter = OracleDecimal::new(225, 2);
+    assert_eq!(decimal_to_sqrt_price_x64(quarter, Rounding::Floor), Ok(Q64 / 2));
+    assert_eq!(decimal_to_sqrt_price_x64(two_and_a_quarter, Rounding::Ceil), Ok(3 * Q64 / 2));
+    assert_eq!(sqrt_price_x64_to_decimal(3 * Q64 / 2, 2, Rounding::Floor), Ok(two_and_a_quarter));
+}
+
+#[test]
+fn tick_range_matches_pool_bounds() {
+    assert_eq!(tick_index_to_sqrt_price_x64(MIN_TICK_INDEX), Ok(MIN_SQRT_PRICE_X64));
+    // Within 1e-16 of the pools' own bound
+    let max = tick_index_to_sqrt_price_x64(MAX_TICK_INDEX).unwrap();
+    assert!(max.abs_diff(MAX_SQRT_PRICE_X64) < MAX_SQRT_PRICE_X64 / 10u128.pow(16));
+    assert_eq!(sqrt_price_x64_to_tick_index(MIN_SQRT_PRICE_X64), Ok(MIN_TICK_INDEX));
+}
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[test]
+fn out_of_range_ticks_and_prices_fail() {
+    let out_of_range = Error::from(OracleError::TickOutOfRange);
+    assert_eq!(tick_index_to_sqrt_price_x64(MAX_TICK_INDEX + 1).unwrap_err(), out_of_range);
+    assert_eq!(tick_index_to_sqrt_price_x64(MIN_TICK_INDEX - 1).unwrap_err(), out_of_range);
+    assert_eq!(sqrt_price_x64_to_tick_index(MIN_SQRT_PRICE_X64 - 1).unwrap_err(), out_of_range);
+    assert_eq!(sqrt_price_x64_to_tick_index(MAX_SQRT_PRICE_X64 + 1).unwrap_err(), out_of_range);
+
+    // 10^-30 is below tick -443636 (about 5.4e-20)
+    assert_eq!(decimal_to_tick_index(OracleDecimal::new(1, 30), 1).unwrap_err(), out_of_range);
+    // $1e-12 per 9-decimal token, quoted in a token without decimals: 1e-21
+    // per base unit, below the smallest square-root price
+    let dust = ValidatedPrice { price: 1, exponent: -12, ..sol_price() };
+    assert_eq!(dust.to_sqrt_price_x64(SOL_DECIMALS, 0, Rounding::Floor).unwrap_err(), out_of_range);
+}
+
+#[test]
+fn zero_tick_spacing_and_overflow_fail() {
+    let one = OracleDecimal::new(1, 0);
+    assert_eq!(decimal_to_tick_index(one, 0), Err(Error::from(OracleError::InvalidTickSpacing)));
+    // 2^64 does not fit Q64.64
+    assert_eq!(
+        decimal_to_q64(OracleDecimal::new(Q64, 0), Rounding::Floor),
+        Err(Error::from(OracleError::MathOverflow))
+    );
+    let negative = ValidatedPrice { price: -1, ..sol_price() };
+    assert_eq!(
+        negative.to_q64(SOL_DECIMALS, USDC_DECIMALS, Rounding::Floor),
+        Err(Error::from(OracleError::NegativePrice))
+    );
+}
+
+// ============================================================================
+// ROUND TRIPS
+// ============================================================================
+
+/// Pyth-like mantissas at scales that keep the value in the tick range
+fn pool_price() -> impl Strategy<Value = OracleDecimal> {
+    (100_000_000u128..=1_000_000_000_000, -4i32..=24)
+        .prop_map(|(mantissa, scale)| OracleDecimal::new(mantissa, scale))
+        .prop_filter("inside the tick range", |value| (1e-12..=1e12).contains(&to_f64(*value)))
+}
+
+proptest! {
+    /// Floor and ceil Q64.64 values are the integers either side of value × 2^64
+    #[test]
+    fn q64_brackets_the_exact_value(value in pool_price()) {
+        let exact = value.checked_mul(OracleDecimal::new(Q64, 0)).unwrap();
+        let floor = decimal_to_q64(value, Rounding::Floor).unwrap();
+        let ceil = decimal_to_q64(value, Rounding::Ceil).unwrap();
+
+        prop_assert_ne!(compare(OracleDecimal::new(floor, 0), exact), Ordering::Greater);
+        prop_assert_eq!(compare(OracleDecimal::new(floor + 1, 0), exact), Ordering::Greater);
+        prop_assert_ne!(compare(OracleDecimal::new(ceil, 0), exact), Ordering::Less);
+        prop_assert!(ceil - floor <= 1);
+        prop_assert_eq!(q64_to_decimal(floor, 0, Rounding::Floor).unwrap().mantissa, floor >> 64);
+    }
+
+    /// Squaring the square-root price gives the price back
+    #[test]
+    fn sqrt_price_squares_back_to_the_price(value in pool_price()) {
+        let sqrt_price_x64 = decimal_to_sqrt_price_x64(value, Rounding::Nearest).unwrap();
+        let back = sqrt_price_x64_to_decimal(sqrt_price_x64, value.scale + 12, Rounding::Nearest).unwrap();
+
+        let (value, back) = (to_f64(value), to_f64(back));
+        prop_assert!((back - value).abs() <= value * 1e-12, "{} came back as {}", value, back);
+    }
+
+    /// Every tick survives tick -> sqrt price -> tick, and the sqrt price
+    /// just below it is in the tick before. `MAX_TICK_INDEX` is left out:
+    /// its computed sqrt price is a hair above `MAX_SQRT_PRICE_X64`.
+    #[test]
+    fn tick_round_trips_through_its_sqrt_price(tick in MIN_TICK_INDEX + 1..MAX_TICK_INDEX) {
+        let sqrt_price_x64 = tick_index_to_sqrt_price_x64(tick).unwrap();
+        prop_assert_eq!(sqrt_price_x64_to_tick_index(sqrt_price_x64).unwrap(), tick);
+        prop_assert_eq!(sqrt_price_x64_to_tick_index(sqrt_price_x64 - 1).unwrap(), tick - 1);
+    }
+
+    /// The price of a tick is nearest to that tick
+    #[test]
+    fn tick_price_is_nearest_to_its_tick(tick in -200_000i32..=200_000) {
+        let sqrt_price_x64 = tick_index_to_sqrt_price_x64(tick).unwrap();
+        let price = sqrt_price_x64_to_decimal(sqrt_price_x64, 28, Rounding::Nearest).unwrap();
+        prop_assert_eq!(decimal_to_tick_index(price, 1).unwrap(), tick);
+    }
+
+    /// The nearest initializable tick is a multiple of the spacing within
+    /// half a spacing of log_1.0001(price)
+    #[test]
+    fn nearest_tick_respects_spacing(value in pool_price(), tick_spacing in 1u16..=256) {
+        let tick = decimal_to_tick_index(value, tick_spacing).unwrap();
+        let exact = to_f64(value).ln() / 1.0001f64.ln();
+
+        prop_assert_eq!(tick % tick_spacing as i32, 0);
+        prop_assert!((exact - tick as f64).abs() <= tick_spacing as f64 / 2.0 + 1e-6);
+    }
+}
@@ -0,0 +1,537 @@
+/**
+ * Compute-Unit Benchmark for the Oracle Example Program
+ *
+ * Runs each `oracle_example` instruction from anchor-oracle.rs under
+ * LiteSVM with mock price updates and prints the compute units consumed,
+ * grouped by what is being compared: validation path, typed `Account`
+ * versus the raw `PriceUpdateView` reader, price cache hit versus miss,
+ * single versus dual price, 1 to 8 feeds from remaining accounts, and the
+ * optional collateral guards. Use it to
+ * check that the validation you need fits your instruction's CU budget
+ * before adding your own logic on top.
+ *
+ * Numbers are for the template as built; rerun after changing it. They
+ * include Anchor's dispatch and account checks, so compare rows with each
+ * other rather than with a bare `get_price_no_older_than` call.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml of the program crate:
+ *    [dev-dependencies]
+ *    litesvm = "0.1"
+ *    anchor-spl = "0.30.1"
+ *    solana-sdk = "1.18"
+ *    pyth-oracle = { path = ".", features = ["no-entrypoint", "test-utils"] }
+ *
+ *    [[bench]]
+ *    name = "compute_units"
+ *    harness = false
+ *
+ * 2. Copy this file to `benches/compute_units.rs`
+ * 3. Build the program, then run the benchmark:
+ *    anchor build && cargo bench --bench compute_units
+ *    (set ORACLE_EXAMPLE_SO to load the .so from another path, and
+ *    CU_BUDGET to fail when any row exceeds that many units)
+ */
+
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use litesvm::LiteSVM;
+use pyth_oracle::test_utils::{PriceUpdateBuilder, DEFAULT_TEST_TIMESTAMP};
+use pyth_oracle::{
+    parse_feed_id, price_feeds, BreakerConfig, BreakerState, DepegAction, DepegConfig, DepegGuard, DepegStatus,
+    LastObservedPrice, Market, OracleConfigParams, PairLimits, PauseFlags, Position, PriceCache,
+    PriceValidationConfig, RiskParamsConfig, SwapPool, TripAction, DEFAULT_SWAP_FEE, MARKET_SEED, PRICE_CACHE_SEED,
+    SWAP_POOL_SEED, SWAP_VAULT_SEED,
+};
+use pyth_solana_receiver_sdk::price_update::VerificationLevel;
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+
+const DEFAULT_PROGRAM_PATH: &str = "target/deploy/pyth_oracle.so";
+
+// ============================================================================
+// HARNESS
+// ============================================================================
+
+struct Bench {
+    svm: LiteSVM,
+    payer: Keypair,
+    rows: Vec<(&'static str, &'static str, u64)>,
+}
+
+impl Bench {
+    fn new() -> Self {
+        let mut svm = LiteSVM::new();
+        let path = std::env::var("ORACLE_EXAMPLE_SO").unwrap_or(DEFAULT_PROGRAM_PATH.to_string());
+        svm.add_program_from_file(pyth_oracle::ID, &path)
+            .expect("build the program with `anchor build` first");
+
+        let payer = Keypair::new();
+        svm.airdrop(&payer.pubkey(), 10_000_000_000).unwrap();
+        Self {
+            svm,
+            payer,
+            rows: Vec::new(),
+        }
+    }
+
+    fn seed_price(&mut self, builder: PriceUpdateBuilder) -> Pubkey {
+        let address = Pubkey::new_unique();
+        let mut clock: solana_sdk::clock::Clock = self.svm.get_sysvar();
+        clock.unix_timestamp = builder.reference_time();
+        self.svm.set_sysvar(&clock);
+        self.svm.set_account(address, builder.to_account()).unwrap();
+        address
+    }
+
+    fn sol_price(&mut self) -> Pubkey {
+        self.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8))
+    }
+
+    fn seed_program_account<T: AccountSerialize>(&mut self, value: &T, len: usize) -> Pubkey {
+        self.seed_program_account_at(Pubkey::new_unique(), value, len)
+    }
+
+    fn seed_program_account_at<T: AccountSerialize>(&mut self, address: Pubkey, value: &T, len: usize) -> Pubkey {
+        let mut data = Vec::with_capacity(len);
+        value.try_serialize(&mut data).unwrap();
+        data.resize(len, 0);
+        let lamports = self.svm.minimum_balance_for_rent_exemption(data.len());
+        self.svm
+            .set_account(
+                address,
+                Account {
+                    lamports,
+                    data,
+                    owner: pyth_oracle::ID,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .unwrap();
+        address
+    }
+
+    fn seed_position(&mut self) -> Pubkey {
+        let position = Position {
+            owner: self.payer.pubkey(),
+            collateral_mint: SOL_MINT,
+            collateral_amount: 2_000_000_000,
+            usd_value: 0,
+            debt_mint: Pubkey::new_unique(),
+            debt_amount: 0,
+            debt_index: 0,
+            debt_usd_value: 0,
+            health_factor: u64::MAX,
+            last_price_update: 0,
+            bump: 255,
+        };
+        self.seed_program_account(&position, Position::LEN)
+    }
+
+    fn seed_mint(&mut self, address: Pubkey, decimals: u8) {
+        let mint = spl_token::state::Mint {
+            decimals,
+            is_initialized: true,
+            ..Default::default()
+        };
+        let mut data = vec![0u8; spl_token::state::Mint::LEN];
+        spl_token::state::Mint::pack(mint, &mut data).unwrap();
+        self.set_token_program_account(address, data);
+    }
+
+    fn seed_token_account_at(&mut self, address: Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) -> Pubkey {
+        let account = spl_token::state::Account {
+            mint,
+            owner,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        let mut data = vec![0u8; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(account, &mut data).unwrap();
+        self.set_token_program_account(address, data);
+        address
+    }
+
+    fn set_token_program_account(&mut self, address: Pubkey, data: Vec<u8>) {
+        let lamports = self.svm.minimum_balance_for_rent_exemption(data.len());
+        self.svm
+            .set_account(
+                address,
+                Account {
+                    lamports,
+                    data,
+                    owner: spl_token::ID,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .unwrap();
+    }
+
+    /// Market at its PDA with the default validation limits and swap fee
+    fn seed_market(&mut self, mint: Pubkey, feed_id: [u8; 32], decimals: u8) -> Pubkey {
+        let (address, bump) = Pubkey::find_program_address(&[MARKET_SEED, mint.as_ref()], &pyth_oracle::ID);
+        let limits = PriceValidationConfig::default();
+        let market = Market {
+            mint,
+            decimals,
+            feed_id,
+            oracle: OracleConfigParams {
+                max_age_secs: limits.max_age_secs,
+                max_confidence_bps: limits.max_confidence_bps,
+                verification_level: limits.verification_level,
+            },
+            pair: PairLimits { max_skew_secs: 10, max_combined_confidence_bps: 300 },
+            risk: RiskParamsConfig {
+                max_ltv_bps: 7_500,
+                liquidation_threshold_bps: 8_000,
+                liquidation_bonus_bps: 500,
+                close_factor_bps: 5_000,
+                borrow_cap: u64::MAX,
+            },
+            swap_fee: DEFAULT_SWAP_FEE,
+            breaker: BreakerState::new(BreakerConfig {
+                max_deviation_bps: 500,
+                window_secs: 60,
+                on_trip: TripAction::Reject,
+            }),
+            paused: PauseFlags::NONE,
+            total_borrowed: 0,
+            has_interest_market: false,
+            pending_change: None,
+            bump,
+        };
+        self.seed_program_account_at(address, &market, Market::LEN)
+    }
+
+    /// Price cache at its PDA, validated in `slot`
+    fn seed_price_cache(&mut self, slot: u64) -> Pubkey {
+        let (address, bump) =
+            Pubkey::find_program_address(&[PRICE_CACHE_SEED, sol_feed().as_ref()], &pyth_oracle::ID);
+        let cache = PriceCache {
+            feed_id: sol_feed(),
+            price: 150_0000_0000,
+            conf: 0,
+            exponent: -8,
+            publish_time: DEFAULT_TEST_TIMESTAMP,
+            posted_slot: slot,
+            slot,
+            verification_level: VerificationLevel::Full,
+            bump,
+        };
+        self.seed_program_account_at(address, &cache, PriceCache::LEN)
+    }
+
+    /// Run one instruction and record its compute units under `group`
+    fn measure(
+        &mut self,
+        group: &'static str,
+        name: &'static str,
+        accounts: impl ToAccountMetas,
+        data: impl InstructionData,
+    ) {
+        self.measure_with_remaining(group, name, accounts, &[], data);
+    }
+
+    /// `measure` with read-only `remaining` accounts after the named ones
+    fn measure_with_remaining(
+        &mut self,
+        group: &'static str,
+        name: &'static str,
+        accounts: impl ToAccountMetas,
+        remaining: &[Pubkey],
+        data: impl InstructionData,
+    ) {
+        let mut metas = accounts.to_account_metas(None);
+        metas.extend(remaining.iter().map(|key| AccountMeta::new_readonly(*key, false)));
+        let instruction = Instruction {
+            program_id: pyth_oracle::ID,
+            accounts: metas,
+            data: data.data(),
+        };
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            self.svm.latest_blockhash(),
+        );
+        self.svm.expire_blockhash();
+        let meta = self
+            .svm
+            .send_transaction(tx)
+            .unwrap_or_else(|failed| panic!("{name} failed: {:?}\n{:#?}", failed.err, failed.meta.logs));
+        self.rows.push((group, name, meta.compute_units_consumed));
+    }
+
+    /// Print the table; returns the rows over `budget`
+    fn report(&self, budget: Option<u64>) -> usize {
+        println!("{:<22} {:<40} {:>8}", "group", "instruction", "CU");
+        println!("{}", "-".repeat(72));
+        let mut over = 0;
+        for (group, name, units) in &self.rows {
+            let flag = match budget {
+                Some(budget) if *units > budget => {
+                    over += 1;
+                    "  over budget"
+                }
+                _ => "",
+            };
+            println!("{:<22} {:<40} {:>8}{}", group, name, units, flag);
+        }
+        over
+    }
+}
+
+const SOL_MINT: Pubkey = solana_sdk::pubkey!("So11111111111111111111111111111111111111112");
+const USDC_MINT: Pubkey = solana_sdk::pubkey!("EPjFWdd5AufqSSqeM2qrxzJ6orvjbs5iMAvPRBEtzpdx");
+
+fn sol_feed() -> [u8; 32] {
+    parse_feed_id(price_feeds::SOL_USD).unwrap()
+}
+
+fn usdc_feed() -> [u8; 32] {
+    parse_feed_id(price_feeds::USDC_USD).unwrap()
+}
+
+// ============================================================================
+// SCENARIOS
+// ============================================================================
+
+fn validation_paths(bench: &mut Bench) {
+    let price_update = bench.sol_price();
+    bench.measure(
+        "validation",
+        "get_price (default config)",
+        pyth_oracle::accounts::SinglePriceContext { price_update },
+        pyth_oracle::instruction::GetPrice {},
+    );
+
+    let price_update = bench.sol_price();
+    bench.measure(
+        "validation",
+        "get_verified_price (strict + feed ID)",
+        pyth_oracle::accounts::SinglePriceContext { price_update },
+        pyth_oracle::instruction::GetVerifiedPrice {
+            feed_id_hex: price_feeds::SOL_USD.to_string(),
+        },
+    );
+}
+
+fn account_vs_raw(bench: &mut Bench) {
+    let price_update = bench.sol_price();
+    bench.measure(
+        "account vs raw",
+        "get_price (Account<PriceUpdateV2>)",
+        pyth_oracle::accounts::SinglePriceContext { price_update },
+        pyth_oracle::instruction::GetPrice {},
+    );
+    bench.measure(
+        "account vs raw",
+        "get_price_raw (PriceUpdateView)",
+        pyth_oracle::accounts::SinglePriceContextRaw { price_update },
+        pyth_oracle::instruction::GetPriceRaw {},
+    );
+}
+
+fn price_cache(bench: &mut Bench) {
+    let config = PriceValidationConfig::default();
+    let price_update = bench.sol_price();
+    bench.svm.warp_to_slot(100);
+
+    let price_cache = bench.seed_price_cache(99);
+    bench.measure(
+        "price cache",
+        "get_cached_price (miss, refill)",
+        pyth_oracle::accounts::ReadPriceCache {
+            price_cache,
+            price_update: Some(price_update),
+        },
+        pyth_oracle::instruction::GetCachedPrice { config },
+    );
+    bench.measure(
+        "price cache",
+        "get_cached_price (hit)",
+        pyth_oracle::accounts::ReadPriceCache {
+            price_cache,
+            price_update: None,
+        },
+        pyth_oracle::instruction::GetCachedPrice { config },
+    );
+}
+
+fn single_vs_dual(bench: &mut Bench) {
+    let price_update = bench.sol_price();
+    bench.measure(
+        "single vs dual",
+        "get_price (one feed)",
+        pyth_oracle::accounts::SinglePriceContext { price_update },
+        pyth_oracle::instruction::GetPrice {},
+    );
+
+    let input_price = bench.sol_price();
+    let output_price = bench.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+    let input_market = bench.seed_market(SOL_MINT, sol_feed(), 9);
+    let output_market = bench.seed_market(USDC_MINT, usdc_feed(), 6);
+
+    // SOL/USDC pool with 1,000 USDC, and a user holding 1 SOL
+    let (pool, bump) =
+        Pubkey::find_program_address(&[SWAP_POOL_SEED, SOL_MINT.as_ref(), USDC_MINT.as_ref()], &pyth_oracle::ID);
+    let authority = bench.payer.pubkey();
+    let swap_pool = SwapPool {
+        authority,
+        mint_a: SOL_MINT,
+        mint_b: USDC_MINT,
+        bump,
+    };
+    bench.seed_program_account_at(pool, &swap_pool, SwapPool::LEN);
+    let vault = |mint: Pubkey| Pubkey::find_program_address(&[SWAP_VAULT_SEED, pool.as_ref(), mint.as_ref()], &pyth_oracle::ID).0;
+    let (input_vault, output_vault) = (vault(SOL_MINT), vault(USDC_MINT));
+    bench.seed_mint(SOL_MINT, 9);
+    bench.seed_mint(USDC_MINT, 6);
+    bench.seed_token_account_at(input_vault, SOL_MINT, pool, 0);
+    bench.seed_token_account_at(output_vault, USDC_MINT, pool, 1_000_000_000);
+    let user_input = bench.seed_token_account_at(Pubkey::new_unique(), SOL_MINT, authority, 1_000_000_000);
+    let user_output = bench.seed_token_account_at(Pubkey::new_unique(), USDC_MINT, authority, 0);
+
+    bench.measure(
+        "single vs dual",
+        "swap_with_oracle (two feeds, fee, transfers)",
+        pyth_oracle::accounts::SwapWithOracle {
+            user: authority,
+            pool,
+            input_mint: SOL_MINT,
+            output_mint: USDC_MINT,
+            input_vault,
+            output_vault,
+            user_input,
+            user_output,
+            input_price,
+            output_price,
+            input_market,
+            output_market,
+            token_program: spl_token::ID,
+            pyth_receiver: None,
+        },
+        pyth_oracle::instruction::SwapWithOracle {
+            amount_in: 1_000_000_000,
+            min_amount_out: 0,
+        },
+    );
+}
+
+fn multi_feed(bench: &mut Bench) {
+    for (name, feeds) in [
+        ("get_basket_prices (1 feed)", 1u8),
+        ("get_basket_prices (4 feeds)", 4),
+        ("get_basket_prices (8 feeds)", 8),
+    ] {
+        let feed_ids: Vec<[u8; 32]> = (1..=feeds).map(|i| [i; 32]).collect();
+        let price_updates: Vec<Pubkey> = feed_ids
+            .iter()
+            .map(|feed_id| bench.seed_price(PriceUpdateBuilder::new(*feed_id).with_price(150_0000_0000, -8)))
+            .collect();
+        bench.measure_with_remaining(
+            "multi-feed",
+            name,
+            pyth_oracle::accounts::BasketPrices {},
+            &price_updates,
+            pyth_oracle::instruction::GetBasketPrices {
+                feed_ids,
+                config: PriceValidationConfig::default(),
+            },
+        );
+    }
+}
+
+fn collateral_guards(bench: &mut Bench) {
+    let collateral_price = bench.sol_price();
+    let position = bench.seed_position();
+    let collateral_market = bench.seed_market(SOL_MINT, sol_feed(), 9);
+    bench.measure(
+        "collateral",
+        "update_collateral_value",
+        pyth_oracle::accounts::ValueCollateral {
+            owner: bench.payer.pubkey(),
+            collateral_price,
+            position,
+            collateral_market,
+            last_observed_price: None,
+            depeg_guard: None,
+        },
+        pyth_oracle::instruction::UpdateCollateralValue {},
+    );
+
+    let last_observed_price = bench.seed_program_account(
+        &LastObservedPrice {
+            authority: bench.payer.pubkey(),
+            feed_id: sol_feed(),
+            price: 0,
+            exponent: 0,
+            publish_time: 0,
+            accepted_at: 0,
+            max_gap_secs: 0,
+            bump: 255,
+        },
+        LastObservedPrice::LEN,
+    );
+    let depeg_guard = bench.seed_program_account(
+        &DepegGuard {
+            authority: bench.payer.pubkey(),
+            feed_id: sol_feed(),
+            config: DepegConfig {
+                peg_price: 150,
+                peg_exponent: 0,
+                max_deviation_bps: 50,
+                min_duration_secs: 600,
+                action: DepegAction::Freeze,
+            },
+            status: DepegStatus::Pegged,
+            pending_since: 0,
+            last_deviation_bps: 0,
+            last_publish_time: 0,
+            bump: 255,
+        },
+        DepegGuard::LEN,
+    );
+    let collateral_price = bench.sol_price();
+    bench.measure(
+        "collateral",
+        "update_collateral_value (+ both guards)",
+        pyth_oracle::accounts::ValueCollateral {
+            owner: bench.payer.pubkey(),
+            collateral_price,
+            position,
+            collateral_market,
+            last_observed_price: Some(last_observed_price),
+            depeg_guard: Some(depeg_guard),
+        },
+        pyth_oracle::instruction::UpdateCollateralValue {},
+    );
+}
+
+// ============================================================================
+// MAIN
+// ============================================================================
+
+fn main() {
+    let mut bench = Bench::new();
+    validation_paths(&mut bench);
+    account_vs_raw(&mut bench);
+    price_cache(&mut bench);
+    single_vs_dual(&mut bench);
+    multi_feed(&mut bench);
+    collateral_guards(&mut bench);
+
+    let budget = std::env::var("CU_BUDGET").ok().map(|v| v.parse().expect("CU_BUDGET is a number"));
+    let over = bench.report(budget);
+    if over > 0 {
+        eprintln!("{over} instruction(s) over the {} CU budget", budget.unwrap());
+        std::process::exit(1);
+    }
+}
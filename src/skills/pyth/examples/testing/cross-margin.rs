@@ -0,0 +1,256 @@
+/**
+ * Tests for the Cross-Margin Engine
+ *
+ * `portfolio_margin` is a pure function, so these run off chain with
+ * hand-built exposures: haircut arithmetic, confidence-bound valuation,
+ * netting within a correlation group (and none in group 0), the bound on
+ * netting relief when a group holds several correlated mints, and the
+ * initial and maintenance thresholds. The `PriceSnapshot` that
+ * `basket_exposures` prices from rejects prices published too far apart.
+ *
+ * Prices have a zero confidence interval unless a test is about the band,
+ * so the expected USD values (6 decimals) can be read off directly.
+ *
+ * Setup:
+ * 1. Copy this file to `tests/cross_margin.rs` of the program crate
+ * 2. Run: cargo test --test cross_margin
+ */
+
+use anchor_lang::prelude::Pubkey;
+use pyth_oracle::{
+    basket_exposures, portfolio_margin, AssetExposure, AssetMarginConfig, BasketHolding, BasketPosition,
+    MarginGroups, MarginParams, PortfolioMargin, PriceSnapshot, ValidatedPrice, MAX_CORRELATION_GROUPS,
+};
+
+const USD: u64 = 1_000_000;
+
+fn price(feed: u8, dollars: i64, conf_dollars: u64) -> ValidatedPrice {
+    let (price, conf) = (dollars * 1_0000_0000, conf_dollars * 1_0000_0000);
+    ValidatedPrice {
+        feed_id: [feed; 32],
+        price,
+        conf,
+        exponent: -8,
+        publish_time: 1_700_000_000,
+        lower_bound: price - conf as i64,
+        upper_bound: price + conf as i64,
+    }
+}
+
+fn config(group: u8, initial_haircut_bps: u16, maintenance_haircut_bps: u16) -> AssetMarginConfig {
+    AssetMarginConfig { group, initial_haircut_bps, maintenance_haircut_bps }
+}
+
+/// SOL-like asset at $150 with 20% / 10% haircuts
+fn sol(group: u8, long_sol: u64, short_sol: u64) -> AssetExposure {
+    AssetExposure {
+        config: config(group, 2_000, 1_000),
+        decimals: 9,
+        long: long_sol * 1_000_000_000,
+        short: short_sol * 1_000_000_000,
+        price: price(1, 150, 0),
+    }
+}
+
+/// USDC at $1 with 5% / 2.5% haircuts
+fn usdc(long_usdc: u64, short_usdc: u64) -> AssetExposure {
+    AssetExposure {
+        config: config(0, 500, 250),
+        decimals: 6,
+        long: long_usdc * USD,
+        short: short_usdc * USD,
+        price: price(2, 1, 0),
+    }
+}
+
+fn offsets(group: usize, offset_bps: u16) -> [u16; MAX_CORRELATION_GROUPS] {
+    let mut offsets = [0; MAX_CORRELATION_GROUPS];
+    offsets[group] = offset_bps;
+    offsets
+}
+
+// ============================================================================
+// REQUIREMENTS
+// ============================================================================
+
+#[test]
+fn single_long_requires_its_haircut() {
+    let margin = portfolio_margin(&[sol(0, 1, 0)], &offsets(0, 0)).unwrap();
+    assert_eq!(
+        margin,
+        PortfolioMargin {
+            collateral_usd: 150 * USD,
+            liability_usd: 0,
+            initial_margin_usd: 30 * USD,
+            maintenance_margin_usd: 15 * USD,
+        }
+    );
+    assert_eq!(margin.equity(), 150 * USD as i128);
+    assert_eq!(margin.free_collateral(), 120 * USD as i128);
+}
+
+#[test]
+fn uncorrelated_sides_add_up() {
+    // $1,500 of SOL against $1,000 of USDC debt
+    let margin = portfolio_margin(&[sol(0, 10, 0), usdc(0, 1_000)], &offsets(0, 0)).unwrap();
+    assert_eq!(margin.initial_margin_usd, (300 + 50) * USD);
+    assert_eq!(margin.maintenance_margin_usd, (150 + 25) * USD);
+    assert_eq!(margin.equity(), 500 * USD as i128);
+    assert!(margin.meets_initial_margin());
+}
+
+#[test]
+fn longs_use_lower_bound_and_shorts_upper_bound() {
+    let mut long = sol(0, 1, 0);
+    long.price = price(1, 150, 1);
+    let mut short = sol(0, 0, 1);
+    short.price = price(1, 150, 1);
+
+    let margin = portfolio_margin(&[long, short], &offsets(0, 0)).unwrap();
+    assert_eq!(margin.collateral_usd, 149 * USD);
+    assert_eq!(margin.liability_usd, 151 * USD);
+}
+
+#[test]
+fn non_positive_lower_bound_values_long_at_zero() {
+    let mut long = sol(0, 1, 0);
+    long.price = price(1, 150, 150);
+    let margin = portfolio_margin(&[long], &offsets(0, 0)).unwrap();
+    assert_eq!(margin.collateral_usd, 0);
+    assert_eq!(margin.initial_margin_usd, 0);
+}
+
+// ============================================================================
+// CORRELATION GROUPS
+// ============================================================================
+
+#[test]
+fn correlated_sides_net() {
+    // $3,000 long SOL against $1,500 short of a SOL derivative, 80% offset
+    let mut lst = sol(1, 0, 10);
+    lst.price.feed_id = [3; 32];
+    let exposures = [sol(1, 20, 0), lst];
+
+    let netted = portfolio_margin(&exposures, &offsets(1, 8_000)).unwrap();
+    // max(600, 300) + 20% × 300
+    assert_eq!(netted.initial_margin_usd, 660 * USD);
+    // max(300, 150) + 20% × 150
+    assert_eq!(netted.maintenance_margin_usd, 330 * USD);
+
+    let gross = portfolio_margin(&exposures, &offsets(1, 0)).unwrap();
+    assert_eq!(gross.initial_margin_usd, 900 * USD);
+}
+
+#[test]
+fn relief_is_bounded_by_the_smaller_side() {
+    // One $1,500 long against two $1,500 correlated shorts: netting each
+    // pair separately would waive 80% of the long twice
+    let exposures = [sol(1, 10, 0), sol(1, 0, 10), sol(1, 0, 10)];
+    let margin = portfolio_margin(&exposures, &offsets(1, 8_000)).unwrap();
+
+    let gross = (300 + 300 + 300) * USD;
+    assert_eq!(margin.initial_margin_usd, 660 * USD);
+    assert_eq!(gross - margin.initial_margin_usd, 240 * USD);
+}
+
+#[test]
+fn group_zero_never_nets() {
+    let exposures = [sol(0, 10, 0), sol(0, 0, 10)];
+    let margin = portfolio_margin(&exposures, &offsets(0, 8_000)).unwrap();
+    assert_eq!(margin.initial_margin_usd, 600 * USD);
+}
+
+#[test]
+fn groups_do_not_net_with_each_other() {
+    let exposures = [sol(1, 10, 0), sol(2, 0, 10)];
+    let mut group_offsets = offsets(1, 8_000);
+    group_offsets[2] = 8_000;
+    let margin = portfolio_margin(&exposures, &group_offsets).unwrap();
+    assert_eq!(margin.initial_margin_usd, 600 * USD);
+}
+
+// ============================================================================
+// THRESHOLDS
+// ============================================================================
+
+#[test]
+fn initial_margin_gates_new_risk_before_liquidation() {
+    // $150 of SOL against $115 of USDC debt: equity $35, IM $35.75, MM $17.875
+    let margin = portfolio_margin(&[sol(0, 1, 0), usdc(0, 115)], &offsets(0, 0)).unwrap();
+    assert!(!margin.meets_initial_margin());
+    assert!(!margin.is_liquidatable());
+}
+
+#[test]
+fn equity_below_maintenance_is_liquidatable() {
+    // Equity $10 against MM $15 + $3.50
+    let margin = portfolio_margin(&[sol(0, 1, 0), usdc(0, 140)], &offsets(0, 0)).unwrap();
+    assert_eq!(margin.maintenance_margin_usd, 18_500_000);
+    assert!(margin.is_liquidatable());
+    assert!(margin.free_collateral() < 0);
+}
+
+// ============================================================================
+// PARAMETERS
+// ============================================================================
+
+#[test]
+fn rejects_invalid_configs() {
+    assert!(config(0, 1_000, 2_000).validate().is_err());
+    assert!(config(0, 10_001, 10_000).validate().is_err());
+    assert!(config(MAX_CORRELATION_GROUPS as u8, 2_000, 1_000).validate().is_err());
+    assert!(config(0, 10_000, 10_000).validate().is_ok());
+
+    let mut bad = sol(0, 1, 0);
+    bad.config.group = MAX_CORRELATION_GROUPS as u8;
+    assert!(portfolio_margin(&[bad], &offsets(0, 0)).is_err());
+}
+
+#[test]
+fn rejects_offsets_for_group_zero() {
+    assert!(MarginGroups::validate_offsets(&offsets(0, 1)).is_err());
+    assert!(MarginGroups::validate_offsets(&offsets(1, 10_001)).is_err());
+    assert!(MarginGroups::validate_offsets(&offsets(1, 10_000)).is_ok());
+}
+
+#[test]
+fn basket_exposures_follow_holding_order() {
+    let (sol_mint, usdc_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let position = BasketPosition {
+        owner: Pubkey::new_unique(),
+        holdings: vec![
+            BasketHolding { mint: sol_mint, feed_id: [1; 32], decimals: 9, amount: 1_000_000_000, borrowed: 0 },
+            BasketHolding { mint: usdc_mint, feed_id: [2; 32], decimals: 6, amount: 0, borrowed: 50 * USD },
+        ],
+        total_usd_value: 0,
+        worst_case_usd_value: 0,
+        last_valued_at: 0,
+        bump: 0,
+    };
+    let params = |mint, config| MarginParams { authority: Pubkey::default(), mint, config, bump: 0 };
+    let prices = PriceSnapshot::try_new(&[price(2, 1, 0), price(1, 150, 0)], 0).unwrap();
+
+    let in_order = [params(sol_mint, config(0, 2_000, 1_000)), params(usdc_mint, config(0, 500, 250))];
+    let exposures = basket_exposures(&position, &in_order, &prices).unwrap();
+    assert_eq!(exposures[1].short, 50 * USD);
+    assert_eq!(exposures[1].price.feed_id, [2; 32]);
+
+    let swapped = [in_order[1].clone(), in_order[0].clone()];
+    assert!(basket_exposures(&position, &swapped, &prices).is_err());
+    assert!(basket_exposures(&position, &in_order[..1], &prices).is_err());
+}
+
+#[test]
+fn snapshot_bounds_the_skew_of_its_prices() {
+    let published = |feed, publish_time| ValidatedPrice { publish_time, ..price(feed, 150, 0) };
+    let prices = [published(1, 1_700_000_004), published(2, 1_700_000_000), published(3, 1_700_000_002)];
+
+    let snapshot = PriceSnapshot::try_new(&prices, 4).unwrap();
+    assert_eq!(snapshot.timestamp(), 1_700_000_000);
+    assert_eq!(snapshot.skew_secs(), 4);
+    assert_eq!(snapshot.get(&[3; 32]).unwrap().publish_time, 1_700_000_002);
+
+    assert!(PriceSnapshot::try_new(&prices, 3).is_err());
+    assert!(PriceSnapshot::try_new(&[prices[0], prices[0]], 60).is_err());
+    assert!(PriceSnapshot::try_new(&[], 0).unwrap().is_empty());
+}
@@ -0,0 +1,487 @@
+/**
+ * Property Tests for the Decimal Math Helpers
+ *
+ * Round-trip, monotonicity, rounding, and exponent-invariance properties for
+ * `calculate_usd_value`, `calculate_tokens_for_usd`, and
+ * `calculate_price_ratio` in anchor-oracle.rs. Exponent invariance
+ * (`price × 10^e == (price × 10) × 10^(e - 1)`) catches sign mistakes in the
+ * exponent formula that happen to pass hand-picked examples.
+ *
+ * `rescale_price`, the path every helper takes to bring prices to a common
+ * exponent, is checked to round-trip when adding digits and to widen the
+ * interval, never narrow it, when dropping them.
+ *
+ * The roots, logarithms, and powers of `OracleDecimal` are checked against
+ * their defining identities (`isqrt` brackets the root, `ln(a × b) =
+ * ln(a) + ln(b)`, `exp(ln(x)) = x`) within the error bounds documented in
+ * oracle-decimal.rs.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml of the program crate:
+ *    [dev-dependencies]
+ *    proptest = "1"
+ *
+ * 2. Copy this file to `tests/decimal_math.rs`
+ * 3. Run: cargo test --test decimal_math
+ *    (PROPTEST_CASES=100000 for a longer search)
+ */
+
+use proptest::prelude::*;
+use anchor_lang::error::Error;
+use pyth_oracle::decimal::{exp_wad, isqrt, pow10, POW10, POW10_U64, WAD_SCALE};
+use pyth_oracle::multi_oracle::{aggregate_prices, AggregationConfig};
+use pyth_oracle::{
+    calculate_price_ratio, calculate_tokens_for_usd, calculate_usd_value, confidence_bps, confidence_within,
+    rescale_price, OracleDecimal, OracleError, OraclePrice, Rounding, ValidatedPrice,
+};
+
+// ============================================================================
+// STRATEGIES
+// ============================================================================
+
+/// Pyth exponents in practice
+fn exponent() -> impl Strategy<Value = i32> {
+    -12i32..=0
+}
+
+fn decimals() -> impl Strategy<Value = u8> {
+    0u8..=12
+}
+
+/// Positive prices small enough to be scaled by 10 without overflow
+fn price() -> impl Strategy<Value = i64> {
+    1i64..=i64::MAX / 10
+}
+
+fn rounding() -> impl Strategy<Value = Rounding> {
+    prop_oneof![Just(Rounding::Floor), Just(Rounding::Ceil), Just(Rounding::Nearest)]
+}
+
+// ============================================================================
+// KNOWN VALUES
+// ============================================================================
+
+#[test]
+fn one_sol_at_150_is_150_usd() {
+    // 1 SOL (9 decimals) at 150.00000000 (exponent -8)
+    assert_eq!(calculate_usd_value(1_000_000_000, 9, 150_0000_0000, -8, Rounding::Floor).unwrap(), 150_000_000);
+    assert_eq!(
+        calculate_tokens_for_usd(150_000_000, 6, 9, 150_0000_0000, -8, Rounding::Floor).unwrap(),
+        1_000_000_000
+    );
+}
+
+#[test]
+fn eth_btc_ratio() {
+    // ETH 3000, BTC 60000 -> 0.05 with 6 decimals
+    assert_eq!(
+        calculate_price_ratio(3_000_0000_0000, -8, 60_000_0000_0000, -8, 6, Rounding::Floor).unwrap(),
+        50_000
+    );
+}
+
+#[test]
+fn non_positive_prices_are_rejected() {
+    assert!(calculate_usd_value(1, 9, 0, -8, Rounding::Floor).is_err());
+    assert!(calculate_usd_value(1, 9, -1, -8, Rounding::Floor).is_err());
+    assert!(calculate_tokens_for_usd(1, 6, 9, 0, -8, Rounding::Floor).is_err());
+    assert!(calculate_price_ratio(1, -8, 0, -8, 6, Rounding::Floor).is_err());
+    assert!(calculate_price_ratio(-1, -8, 1, -8, 6, Rounding::Floor).is_err());
+}
+
+#[test]
+fn rescaling_known_values() {
+    // Exact both ways
+    assert_eq!(rescale_price(150_0000_0000, 7_500_000, -8, -6).unwrap(), (150_000_000, 75_000));
+    assert_eq!(rescale_price(15, 1, -1, -8).unwrap(), (150_000_000, 10_000_000));
+    // 123.456 ± 0 to one decimal: 123.4 ± 0.1 covers it
+    assert_eq!(rescale_price(123_456, 0, -3, -1).unwrap(), (1_234, 1));
+    assert_eq!(rescale_price(-123_456, 1, -3, -1).unwrap(), (-1_234, 2));
+    assert_eq!(rescale_price(i64::MIN, 0, -8, -8).unwrap(), (i64::MIN, 0));
+    // Past 10^38 every digit is dropped
+    assert_eq!(rescale_price(1, 1, -8, 40).unwrap(), (0, 2));
+
+    let overflow = Error::from(OracleError::MathOverflow);
+    assert_eq!(rescale_price(i64::MAX, 0, 0, -1).unwrap_err(), overflow);
+    assert_eq!(rescale_price(1, u64::MAX, 0, -1).unwrap_err(), overflow);
+    assert_eq!(rescale_price(1, 0, 0, -39).unwrap_err(), overflow);
+    assert_eq!(rescale_price(1, 0, 0, i32::MIN).unwrap_err(), overflow);
+}
+
+#[test]
+fn aggregation_normalizes_mixed_exponents() {
+    let price = |price: i64, conf: u64, exponent: i32| {
+        let price = OraclePrice {
+            price,
+            conf,
+            exponent,
+            publish_time: 1_700_000_000,
+        };
+        ValidatedPrice::from_price([1; 32], &price)
+    };
+    let cents = price(15_000, 10, -2);
+    let micros = price(150_010_000, 5_000, -6);
+
+    let aggregate = aggregate_prices(&[cents, micros], &AggregationConfig::default()).unwrap();
+    assert_eq!((aggregate.price, aggregate.conf, aggregate.exponent), (150_005_000, 100_000, -6));
+    let rescaled = aggregate_prices(&[cents.rescale_to(-6).unwrap(), micros], &AggregationConfig::default()).unwrap();
+    assert_eq!(aggregate, rescaled);
+}
+
+#[test]
+fn known_roots_logs_and_powers() {
+    assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+    // sqrt(2) = 1.414213562373095048801...
+    let two = OracleDecimal::new(2, 0);
+    assert_eq!(two.checked_sqrt(18, Rounding::Floor).unwrap().mantissa, 1_414_213_562_373_095_048);
+    assert_eq!(two.checked_sqrt(18, Rounding::Ceil).unwrap().mantissa, 1_414_213_562_373_095_049);
+
+    assert_eq!(OracleDecimal::new(100, 2).ln().unwrap(), 0);
+    // ln(150) = 5.010635294096255713...
+    assert!((OracleDecimal::new(150, 0).ln().unwrap() - 5_010_635_294_096_255_713).abs() <= 100);
+    // e = 2.718281828459045235...
+    assert!(exp_wad(1_000_000_000_000_000_000).unwrap().abs_diff(2_718_281_828_459_045_235) <= 100);
+
+    // 150^(-1/2) = 0.081649658092772603...
+    let root = OracleDecimal::new(150_0000_0000, 8).pow_frac(-1, 2, 18).unwrap();
+    assert!(root.mantissa.abs_diff(81_649_658_092_772_603) <= 100);
+}
+
+#[test]
+fn const_tables_match_runtime_powers() {
+    for exp in 0..POW10.len() as u32 {
+        assert_eq!(pow10(exp), Some(10u128.pow(exp)));
+    }
+    assert_eq!(pow10(39), None);
+    for (exp, value) in POW10_U64.iter().enumerate() {
+        assert_eq!(*value, 10u64.pow(exp as u32));
+    }
+}
+
+#[test]
+fn bps_multipliers() {
+    const BONUS: OracleDecimal = OracleDecimal::one_plus_bps(500);
+    assert_eq!(BONUS, OracleDecimal::new(10_500, 4));
+    assert_eq!(OracleDecimal::from_bps(8_000), OracleDecimal::new(8_000, 4));
+    assert_eq!(OracleDecimal::one_minus_bps(2_000), OracleDecimal::new(8_000, 4));
+    assert_eq!(OracleDecimal::one_minus_bps(12_000), OracleDecimal::new(0, 4));
+}
+
+#[test]
+fn logs_and_powers_reject_undefined_inputs() {
+    assert!(OracleDecimal::ZERO.ln().is_err());
+    assert!(OracleDecimal::ZERO.pow_frac(-1, 2, 18).is_err());
+    assert!(OracleDecimal::new(2, 0).pow_frac(1, 0, 18).is_err());
+    // e^100 does not fit 18 decimals in a u128
+    assert!(exp_wad(100_000_000_000_000_000_000).is_err());
+}
+
+// ============================================================================
+// PROPERTIES
+// ============================================================================
+
+proptest! {
+    /// The same price expressed with one more decimal gives the same value
+    #[test]
+    fn usd_value_is_exponent_invariant(
+        amount in any::<u64>(),
+        token_decimals in decimals(),
+        price in price(),
+        exponent in exponent(),
+    ) {
+        let a = calculate_usd_value(amount, token_decimals, price, exponent, Rounding::Floor);
+        let b = calculate_usd_value(amount, token_decimals, price * 10, exponent - 1, Rounding::Floor);
+        prop_assert_eq!(a.ok(), b.ok());
+    }
+
+    #[test]
+    fn tokens_for_usd_is_exponent_invariant(
+        usd in any::<u64>(),
+        token_decimals in decimals(),
+        price in price(),
+        exponent in exponent(),
+    ) {
+        let a = calculate_tokens_for_usd(usd, 6, token_decimals, price, exponent, Rounding::Floor);
+        let b = calculate_tokens_for_usd(usd, 6, token_decimals, price * 10, exponent - 1, Rounding::Floor);
+        prop_assert_eq!(a.ok(), b.ok());
+    }
+
+    #[test]
+    fn usd_value_is_monotonic_in_amount(
+        a in any::<u64>(),
+        b in any::<u64>(),
+        token_decimals in decimals(),
+        price in price(),
+        exponent in exponent(),
+    ) {
+        let (low, high) = (a.min(b), a.max(b));
+        if let Ok(high_value) = calculate_usd_value(high, token_decimals, price, exponent, Rounding::Floor) {
+            let low_value = calculate_usd_value(low, token_decimals, price, exponent, Rounding::Floor).unwrap();
+            prop_assert!(low_value <= high_value);
+        }
+    }
+
+    #[test]
+    fn usd_value_is_monotonic_in_price(
+        amount in any::<u64>(),
+        token_decimals in decimals(),
+        a in price(),
+        b in price(),
+        exponent in exponent(),
+    ) {
+        let (low, high) = (a.min(b), a.max(b));
+        if let Ok(high_value) = calculate_usd_value(amount, token_decimals, high, exponent, Rounding::Floor) {
+            let low_value = calculate_usd_value(amount, token_decimals, low, exponent, Rounding::Floor).unwrap();
+            prop_assert!(low_value <= high_value);
+        }
+    }
+
+    /// A higher price never buys more tokens
+    #[test]
+    fn tokens_for_usd_is_antitonic_in_price(
+        usd in any::<u64>(),
+        token_decimals in decimals(),
+        a in price(),
+        b in price(),
+        exponent in exponent(),
+    ) {
+        let (low, high) = (a.min(b), a.max(b));
+        if let Ok(at_low) = calculate_tokens_for_usd(usd, 6, token_decimals, low, exponent, Rounding::Floor) {
+            let at_high = calculate_tokens_for_usd(usd, 6, token_decimals, high, exponent, Rounding::Floor).unwrap();
+            prop_assert!(at_high <= at_low);
+        }
+    }
+
+    /// Both conversions floor, so a round trip never creates tokens and
+    /// loses little more than what one USD unit buys
+    #[test]
+    fn token_round_trip_never_gains(
+        amount in any::<u64>(),
+        token_decimals in decimals(),
+        price in price(),
+        exponent in exponent(),
+    ) {
+        if let Ok(usd) = calculate_usd_value(amount, token_decimals, price, exponent, Rounding::Floor) {
+            let back = calculate_tokens_for_usd(usd, 6, token_decimals, price, exponent, Rounding::Floor).unwrap();
+            prop_assert!(back <= amount);
+
+            let one_unit = calculate_tokens_for_usd(1, 6, token_decimals, price, exponent, Rounding::Floor).unwrap();
+            prop_assert!(amount - back <= one_unit.saturating_add(2));
+        }
+    }
+
+    #[test]
+    fn usd_round_trip_never_gains(
+        usd in any::<u64>(),
+        token_decimals in decimals(),
+        price in price(),
+        exponent in exponent(),
+    ) {
+        if let Ok(tokens) = calculate_tokens_for_usd(usd, 6, token_decimals, price, exponent, Rounding::Floor) {
+            if let Ok(back) = calculate_usd_value(tokens, token_decimals, price, exponent, Rounding::Floor) {
+                prop_assert!(back <= usd);
+            }
+        }
+    }
+
+    /// Ceil and nearest land on floor or one unit above it
+    #[test]
+    fn rounding_brackets_the_exact_value(
+        amount in any::<u64>(),
+        token_decimals in decimals(),
+        price in price(),
+        exponent in exponent(),
+    ) {
+        let usd = |rounding| calculate_usd_value(amount, token_decimals, price, exponent, rounding);
+        if let (Ok(floor), Ok(nearest), Ok(ceil)) =
+            (usd(Rounding::Floor), usd(Rounding::Nearest), usd(Rounding::Ceil))
+        {
+            prop_assert!(floor <= nearest && nearest <= ceil);
+            prop_assert!(ceil - floor <= 1);
+        }
+
+        let tokens = |rounding| calculate_tokens_for_usd(amount, 6, token_decimals, price, exponent, rounding);
+        if let (Ok(floor), Ok(nearest), Ok(ceil)) =
+            (tokens(Rounding::Floor), tokens(Rounding::Nearest), tokens(Rounding::Ceil))
+        {
+            prop_assert!(floor <= nearest && nearest <= ceil);
+            prop_assert!(ceil - floor <= 1);
+        }
+    }
+
+    /// Owed amounts rounded up always cover the value they were priced from
+    #[test]
+    fn ceil_round_trip_never_loses(
+        usd in 0u64..=u64::MAX / 2,
+        token_decimals in decimals(),
+        price in price(),
+        exponent in exponent(),
+    ) {
+        if let Ok(tokens) = calculate_tokens_for_usd(usd, 6, token_decimals, price, exponent, Rounding::Ceil) {
+            if let Ok(back) = calculate_usd_value(tokens, token_decimals, price, exponent, Rounding::Ceil) {
+                prop_assert!(back >= usd);
+            }
+        }
+    }
+
+    #[test]
+    fn ratio_of_a_price_to_itself_is_one(
+        price in price(),
+        exponent in exponent(),
+        result_decimals in 0u8..=18,
+    ) {
+        let ratio = calculate_price_ratio(price, exponent, price, exponent, result_decimals, Rounding::Floor).unwrap();
+        prop_assert_eq!(ratio, 10u64.pow(result_decimals as u32));
+    }
+
+    #[test]
+    fn ratio_is_exponent_invariant(
+        numerator in 0i64..=i64::MAX / 10,
+        numerator_exponent in exponent(),
+        denominator in price(),
+        denominator_exponent in exponent(),
+        result_decimals in 0u8..=6,
+    ) {
+        let a = calculate_price_ratio(
+            numerator, numerator_exponent, denominator, denominator_exponent, result_decimals, Rounding::Floor,
+        );
+        let b = calculate_price_ratio(
+            numerator * 10, numerator_exponent - 1, denominator, denominator_exponent, result_decimals, Rounding::Floor,
+        );
+        let c = calculate_price_ratio(
+            numerator, numerator_exponent, denominator * 10, denominator_exponent - 1, result_decimals, Rounding::Floor,
+        );
+        prop_assert_eq!(a.as_ref().ok(), b.as_ref().ok());
+        prop_assert_eq!(a.ok(), c.ok());
+    }
+
+    /// Adding digits is exact, so dropping them again restores the price
+    #[test]
+    fn rescale_round_trips(
+        price in any::<i64>(),
+        conf in any::<u64>(),
+        exponent in exponent(),
+        digits in 0i32..=20,
+    ) {
+        if let Ok((finer, finer_conf)) = rescale_price(price, conf, exponent, exponent - digits) {
+            prop_assert_eq!(rescale_price(finer, finer_conf, exponent - digits, exponent).unwrap(), (price, conf));
+        }
+    }
+
+    /// Dropping digits truncates the price and widens the interval to cover
+    /// the original one
+    #[test]
+    fn coarser_exponent_covers_the_interval(
+        price in any::<i64>(),
+        conf in any::<u64>(),
+        exponent in exponent(),
+        digits in 0i32..=18,
+    ) {
+        let (coarse, coarse_conf) = rescale_price(price, conf, exponent, exponent + digits).unwrap();
+        let factor = 10i128.pow(digits as u32);
+        let (price, conf) = (price as i128, conf as i128);
+        let (coarse, coarse_conf) = (coarse as i128 * factor, coarse_conf as i128 * factor);
+        prop_assert!(coarse.abs() <= price.abs() && price.abs() - coarse.abs() < factor);
+        prop_assert!(coarse - coarse_conf <= price - conf);
+        prop_assert!(coarse + coarse_conf >= price + conf);
+    }
+
+    /// The cross-multiplied check accepts exactly what dividing would
+    #[test]
+    fn confidence_check_matches_division(
+        price in any::<i64>().prop_filter("non-zero", |price| *price != 0),
+        conf in any::<u64>(),
+        max_bps in any::<u64>(),
+    ) {
+        let conf_bps = (conf as u128 * 10_000).div_ceil(price.unsigned_abs() as u128);
+        prop_assert_eq!(confidence_within(price, conf, max_bps), conf_bps <= max_bps as u128);
+    }
+
+    /// `confidence_bps` agrees with the check and never understates
+    #[test]
+    fn confidence_bps_rounds_up_and_saturates(price in any::<i64>(), conf in any::<u64>()) {
+        let bps = confidence_bps(price, conf).unwrap();
+        if price == 0 {
+            prop_assert_eq!(bps, u16::MAX);
+        } else {
+            prop_assert!(bps as u128 * price.unsigned_abs() as u128 >= conf as u128 * 10_000 || bps == u16::MAX);
+            prop_assert!(bps == u16::MAX || confidence_within(price, conf, bps as u64));
+            prop_assert!(bps == 0 || !confidence_within(price, conf, bps as u64 - 1));
+        }
+    }
+
+    #[test]
+    fn isqrt_brackets_the_root(value in any::<u128>()) {
+        let root = isqrt(value);
+        prop_assert!(root * root <= value);
+        prop_assert!(root == u64::MAX as u128 || (root + 1) * (root + 1) > value);
+    }
+
+    /// Floor and ceil square roots straddle the exact root at any scale
+    #[test]
+    fn sqrt_rounding_brackets_the_root(
+        mantissa in 0u128..=u64::MAX as u128,
+        scale in 0i32..=18,
+        result_scale in 0i32..=9,
+    ) {
+        let value = OracleDecimal::new(mantissa, scale);
+        let floor = value.checked_sqrt(result_scale, Rounding::Floor).unwrap();
+        let ceil = value.checked_sqrt(result_scale, Rounding::Ceil).unwrap();
+        prop_assert!(value.checked_sub(floor.checked_mul(floor).unwrap()).is_ok());
+        prop_assert!(ceil.checked_mul(ceil).unwrap().checked_sub(value).is_ok());
+        prop_assert!(ceil.mantissa - floor.mantissa <= 1);
+    }
+
+    #[test]
+    fn ln_of_product_is_sum_of_lns(
+        a in 1u128..=u64::MAX as u128,
+        a_scale in -12i32..=12,
+        b in 1u128..=u64::MAX as u128,
+        b_scale in -12i32..=12,
+    ) {
+        let (a, b) = (OracleDecimal::new(a, a_scale), OracleDecimal::new(b, b_scale));
+        let product = a.checked_mul(b).unwrap();
+        let error = product.ln().unwrap() - (a.ln().unwrap() + b.ln().unwrap());
+        prop_assert!(error.abs() <= 300, "error {} × 1e-18", error);
+    }
+
+    /// exp(ln(x)) returns x within 1e-15 relative, for x in [1e-6, 1e18]
+    #[test]
+    fn exp_inverts_ln(mantissa in 1_000_000_000_000u128..=1_000_000_000_000_000_000_000_000_000_000_000_000) {
+        let x = OracleDecimal::new(mantissa, WAD_SCALE);
+        let back = exp_wad(x.ln().unwrap()).unwrap();
+        prop_assert!(back.abs_diff(mantissa) <= mantissa / 1_000_000_000_000_000 + 10);
+    }
+
+    #[test]
+    fn square_root_power_matches_sqrt(mantissa in 1u128..=u64::MAX as u128, scale in 0i32..=12) {
+        let x = OracleDecimal::new(mantissa, scale);
+        let power = x.pow_frac(1, 2, 6).unwrap();
+        let root = x.checked_sqrt(6, Rounding::Floor).unwrap();
+        prop_assert!(power.mantissa.abs_diff(root.mantissa) <= root.mantissa / 1_000_000_000_000 + 1);
+    }
+
+    /// Arbitrary inputs return Ok or Err, never panic
+    #[test]
+    fn never_panics(
+        amount in any::<u64>(),
+        price in any::<i64>(),
+        exponent in any::<i32>(),
+        other_price in any::<i64>(),
+        other_exponent in any::<i32>(),
+        token_decimals in any::<u8>(),
+        usd_decimals in any::<u8>(),
+        rounding in rounding(),
+    ) {
+        let _ = calculate_usd_value(amount, token_decimals, price, exponent, rounding);
+        let _ = calculate_tokens_for_usd(amount, usd_decimals, token_decimals, price, exponent, rounding);
+        let _ = calculate_price_ratio(price, exponent, other_price, other_exponent, usd_decimals, rounding);
+        let _ = rescale_price(price, amount, exponent, other_exponent);
+
+        let decimal = OracleDecimal::new(price.unsigned_abs() as u128, exponent);
+        let _ = decimal.checked_sqrt(other_exponent, rounding);
+        let _ = decimal.ln();
+        let _ = decimal.pow_frac(other_price, amount, usd_decimals as i32);
+        let _ = exp_wad(price as i128 * other_price as i128);
+    }
+}
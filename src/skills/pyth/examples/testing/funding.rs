@@ -0,0 +1,220 @@
+/**
+ * Tests for Perpetual Funding Rates
+ *
+ * The funding helpers are pure, so these run off chain: the sign of the
+ * premium on each side of the index's confidence band, the clamp to
+ * `max_rate_bps`, accrual of the previous rate over at most one interval,
+ * and who pays whom when positions settle.
+ *
+ * The index is SOL at $150 ± $0.15 (exponent -8) unless a test says
+ * otherwise. Rates are per hourly interval, with 9 decimals.
+ *
+ * Setup:
+ * 1. Copy this file to `tests/funding.rs` of the program crate
+ * 2. Run: cargo test --test funding
+ */
+
+use anchor_lang::prelude::Pubkey;
+use pyth_oracle::{
+    funding_payment, funding_premium, funding_rate, FundingParams, FundingState, ValidatedPrice,
+    FUNDING_RATE_ONE,
+};
+
+const FEED: [u8; 32] = [1; 32];
+const HOUR: i64 = 3_600;
+const START: i64 = 1_700_000_000;
+const SOL: i64 = 1_000_000_000;
+
+/// 1% of the rate scale
+const ONE_PERCENT: i64 = FUNDING_RATE_ONE / 100;
+
+fn index(price: i64, conf: u64) -> ValidatedPrice {
+    ValidatedPrice {
+        feed_id: FEED,
+        price,
+        conf,
+        exponent: -8,
+        publish_time: START,
+        lower_bound: price - conf as i64,
+        upper_bound: price + conf as i64,
+    }
+}
+
+fn sol_index() -> ValidatedPrice {
+    index(150_0000_0000, 1500_0000)
+}
+
+/// Hourly funding capped at 0.5%
+fn params() -> FundingParams {
+    FundingParams { interval_secs: HOUR as u32, max_rate_bps: 50 }
+}
+
+fn state(rate: i64) -> FundingState {
+    FundingState {
+        authority: Pubkey::new_unique(),
+        feed_id: FEED,
+        params: params(),
+        cumulative_funding: 0,
+        rate,
+        last_update: START,
+        bump: 255,
+    }
+}
+
+// ============================================================================
+// PREMIUM AND RATE
+// ============================================================================
+
+#[test]
+fn mark_above_the_band_has_positive_premium() {
+    // $1.50 above the upper edge of $150.15
+    assert_eq!(funding_premium(151_6500_0000, -8, &sol_index()).unwrap(), ONE_PERCENT);
+}
+
+#[test]
+fn mark_below_the_band_has_negative_premium() {
+    // $1.50 below the lower edge of $149.85
+    assert_eq!(funding_premium(148_3500_0000, -8, &sol_index()).unwrap(), -ONE_PERCENT);
+}
+
+#[test]
+fn mark_inside_the_band_pays_no_funding() {
+    for mark in [149_8500_0000, 150_0000_0000, 150_1000_0000, 150_1500_0000] {
+        assert_eq!(funding_premium(mark, -8, &sol_index()).unwrap(), 0);
+    }
+}
+
+#[test]
+fn mark_exponent_is_rescaled() {
+    assert_eq!(funding_premium(15_165, -2, &sol_index()).unwrap(), ONE_PERCENT);
+    assert_eq!(
+        funding_premium(151_650_000_000_000, -12, &sol_index()).unwrap(),
+        ONE_PERCENT
+    );
+}
+
+#[test]
+fn non_positive_mark_is_rejected() {
+    assert!(funding_premium(0, -8, &sol_index()).is_err());
+    assert!(funding_premium(-1, -8, &sol_index()).is_err());
+}
+
+#[test]
+fn rate_is_clamped_both_ways() {
+    let cap = ONE_PERCENT / 2;
+    assert_eq!(funding_rate(ONE_PERCENT, &params()), cap);
+    assert_eq!(funding_rate(-ONE_PERCENT, &params()), -cap);
+    assert_eq!(funding_rate(cap - 1, &params()), cap - 1);
+    assert_eq!(funding_rate(0, &params()), 0);
+}
+
+#[test]
+fn params_are_validated() {
+    assert!(params().validate().is_ok());
+    for params in [
+        FundingParams { interval_secs: 0, ..params() },
+        FundingParams { max_rate_bps: 0, ..params() },
+        FundingParams { max_rate_bps: 10_001, ..params() },
+    ] {
+        assert!(params.validate().is_err());
+    }
+}
+
+// ============================================================================
+// ACCRUAL
+// ============================================================================
+
+#[test]
+fn one_interval_accrues_rate_times_index() {
+    let mut state = state(ONE_PERCENT / 2);
+    // 0.5% of $150 per SOL, with 18 decimals
+    assert_eq!(state.accrue(&sol_index(), START + HOUR).unwrap(), 750_000_000_000_000_000);
+    assert_eq!(state.cumulative_funding, 750_000_000_000_000_000);
+    assert_eq!(state.last_update, START + HOUR);
+}
+
+#[test]
+fn partial_interval_accrues_pro_rata() {
+    let mut state = state(ONE_PERCENT / 2);
+    state.accrue(&sol_index(), START + HOUR / 2).unwrap();
+    assert_eq!(state.cumulative_funding, 375_000_000_000_000_000);
+}
+
+#[test]
+fn stale_rate_applies_for_at_most_one_interval() {
+    let mut state = state(ONE_PERCENT / 2);
+    state.accrue(&sol_index(), START + 3 * HOUR).unwrap();
+    assert_eq!(state.cumulative_funding, 750_000_000_000_000_000);
+    assert_eq!(state.last_update, START + 3 * HOUR);
+}
+
+#[test]
+fn clock_behind_last_update_accrues_nothing() {
+    let mut state = state(ONE_PERCENT / 2);
+    assert_eq!(state.accrue(&sol_index(), START - HOUR).unwrap(), 0);
+    assert_eq!(state.last_update, START);
+}
+
+#[test]
+fn negative_rate_lowers_cumulative_funding() {
+    let mut state = state(-ONE_PERCENT / 2);
+    state.accrue(&sol_index(), START + HOUR).unwrap();
+    assert_eq!(state.cumulative_funding, -750_000_000_000_000_000);
+}
+
+#[test]
+fn new_rate_applies_only_after_it_is_set() {
+    let mut state = state(0);
+    let rate = state.update(151_6500_0000, -8, &sol_index(), START + HOUR).unwrap();
+
+    assert_eq!(rate, ONE_PERCENT / 2);
+    assert_eq!(state.cumulative_funding, 0);
+
+    state.update(151_6500_0000, -8, &sol_index(), START + 2 * HOUR).unwrap();
+    assert_eq!(state.cumulative_funding, 750_000_000_000_000_000);
+}
+
+#[test]
+fn index_of_another_feed_is_rejected() {
+    let mut state = state(ONE_PERCENT / 2);
+    let other = ValidatedPrice { feed_id: [2; 32], ..sol_index() };
+    assert!(state.accrue(&other, START + HOUR).is_err());
+}
+
+// ============================================================================
+// SETTLEMENT
+// ============================================================================
+
+#[test]
+fn longs_pay_shorts_when_funding_is_positive() {
+    let funding = 750_000_000_000_000_000;
+    // 2 SOL × $0.75
+    assert_eq!(funding_payment(2 * SOL, 9, 0, funding).unwrap(), 1_500_000);
+    assert_eq!(funding_payment(-2 * SOL, 9, 0, funding).unwrap(), -1_500_000);
+}
+
+#[test]
+fn shorts_pay_longs_when_funding_is_negative() {
+    let funding = -750_000_000_000_000_000;
+    assert_eq!(funding_payment(2 * SOL, 9, 0, funding).unwrap(), -1_500_000);
+    assert_eq!(funding_payment(-2 * SOL, 9, 0, funding).unwrap(), 1_500_000);
+}
+
+#[test]
+fn payers_round_up_and_receivers_round_down() {
+    let funding = 750_000_000_000_000_000;
+    // One lamport owes $0.00000075
+    assert_eq!(funding_payment(1, 9, 0, funding).unwrap(), 1);
+    assert_eq!(funding_payment(-1, 9, 0, funding).unwrap(), 0);
+}
+
+#[test]
+fn settle_pays_only_since_the_entry() {
+    let mut state = state(ONE_PERCENT / 2);
+    state.accrue(&sol_index(), START + HOUR).unwrap();
+
+    let mut entry_funding = 0;
+    assert_eq!(state.settle(2 * SOL, 9, &mut entry_funding).unwrap(), 1_500_000);
+    assert_eq!(entry_funding, state.cumulative_funding);
+    assert_eq!(state.settle(2 * SOL, 9, &mut entry_funding).unwrap(), 0);
+}
@@ -0,0 +1,77 @@
+/**
+ * cargo-fuzz Target for the Decimal Math Helpers
+ *
+ * Feeds arbitrary amounts, prices, exponents, and decimals to
+ * `calculate_usd_value`, `calculate_tokens_for_usd`, and
+ * `calculate_price_ratio`. Any panic (overflow, shift, pow) is a finding;
+ * for inputs in the realistic range it also checks that round trips never
+ * create value. Complements decimal-math-proptest.rs with coverage-guided
+ * search.
+ *
+ * Setup:
+ * 1. In the program crate: cargo install cargo-fuzz && cargo fuzz init
+ * 2. Add to fuzz/Cargo.toml:
+ *    [dependencies]
+ *    libfuzzer-sys = "0.4"
+ *    arbitrary = { version = "1", features = ["derive"] }
+ *    pyth-oracle = { path = "..", features = ["no-entrypoint"] }
+ *
+ *    [[bin]]
+ *    name = "decimal_math"
+ *    path = "fuzz_targets/decimal_math.rs"
+ *    test = false
+ *    doc = false
+ *
+ * 3. Copy this file to `fuzz/fuzz_targets/decimal_math.rs`
+ * 4. Run: cargo +nightly fuzz run decimal_math
+ */
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use pyth_oracle::{calculate_price_ratio, calculate_tokens_for_usd, calculate_usd_value, Rounding};
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    amount: u64,
+    token_decimals: u8,
+    usd_decimals: u8,
+    price: i64,
+    exponent: i32,
+    other_price: i64,
+    other_exponent: i32,
+    result_decimals: u8,
+}
+
+fuzz_target!(|input: Input| {
+    let Input {
+        amount,
+        token_decimals,
+        usd_decimals,
+        price,
+        exponent,
+        other_price,
+        other_exponent,
+        result_decimals,
+    } = input;
+
+    // Must never panic, whatever the inputs
+    let usd = calculate_usd_value(amount, token_decimals, price, exponent, Rounding::Floor);
+    let _ = calculate_tokens_for_usd(amount, usd_decimals, token_decimals, price, exponent, Rounding::Floor);
+    let _ = calculate_price_ratio(price, exponent, other_price, other_exponent, result_decimals, Rounding::Floor);
+
+    // Round trip through USD (6 decimals) never gains tokens
+    if let Ok(usd) = usd {
+        if let Ok(back) = calculate_tokens_for_usd(usd, 6, token_decimals, price, exponent, Rounding::Floor) {
+            assert!(back <= amount, "{amount} -> ${usd} -> {back}");
+        }
+    }
+
+    // A price compared to itself is exactly 1
+    if price > 0 && result_decimals <= 18 {
+        if let Ok(ratio) = calculate_price_ratio(price, exponent, price, exponent, result_decimals, Rounding::Floor) {
+            assert_eq!(ratio, 10u64.pow(result_decimals as u32));
+        }
+    }
+});
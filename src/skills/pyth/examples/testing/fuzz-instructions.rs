@@ -0,0 +1,468 @@
+/**
+ * cargo-fuzz Target for the Example Program's Instruction Handlers
+ *
+ * fuzz-decimal-math.rs searches the math; this searches the account
+ * checks. Each input builds a mock price update, mutates the account
+ * (owner, discriminator, flipped bytes, truncated or extended data, or
+ * replaced outright), and sends it to one `oracle_example` instruction
+ * under LiteSVM: `get_price`, `get_price_raw`, `get_cached_price` on a
+ * cache miss, `get_fallback_price`, `check_circuit_breaker`, or
+ * `update_collateral_value`. Findings:
+ *
+ * - a failure other than a program error code: a panic in a handler or a
+ *   deserialization path surfaces as `ProgramFailedToComplete`
+ * - an instruction that succeeds on an account not owned by the Pyth
+ *   receiver, without the `PriceUpdateV2` discriminator, that the SDK
+ *   cannot deserialize, or that is only partially verified: a missing
+ *   owner, discriminator, or verification check
+ * - an instruction bound to SOL/USD that succeeds on another feed
+ * - `PriceUpdateView::parse` and `PriceUpdateV2::try_deserialize`
+ *   disagreeing on the same bytes (checked off-chain, before sending)
+ *
+ * One LiteSVM instance is reused across inputs; the guard accounts the
+ * instructions write are reset before each one, so a crash input
+ * reproduces on its own.
+ *
+ * Setup:
+ * 1. In the program crate: cargo install cargo-fuzz && cargo fuzz init
+ * 2. Add to fuzz/Cargo.toml:
+ *    [dependencies]
+ *    libfuzzer-sys = "0.4"
+ *    arbitrary = { version = "1", features = ["derive"] }
+ *    anchor-lang = "0.30.1"
+ *    litesvm = "0.1"
+ *    solana-sdk = "1.18"
+ *    pyth-solana-receiver-sdk = "0.3"
+ *    pyth-oracle = { path = "..", features = ["no-entrypoint", "test-utils"] }
+ *
+ *    [[bin]]
+ *    name = "instructions"
+ *    path = "fuzz_targets/instructions.rs"
+ *    test = false
+ *    doc = false
+ *
+ * 3. Copy this file to `fuzz/fuzz_targets/instructions.rs`
+ * 4. Build the program, then run from the program crate:
+ *    anchor build && cargo +nightly fuzz run instructions
+ *    (set ORACLE_EXAMPLE_SO to load the .so from another path)
+ *
+ * For honggfuzz, call `fuzz_instruction` from `honggfuzz::fuzz!` with the
+ * same `Input` in place of `fuzz_target!`.
+ */
+
+#![no_main]
+
+use std::cell::RefCell;
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, Discriminator, InstructionData, ToAccountMetas};
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use litesvm::LiteSVM;
+use pyth_oracle::test_utils::{PriceUpdateBuilder, DEFAULT_TEST_TIMESTAMP};
+use pyth_oracle::{
+    parse_feed_id, price_feeds, BreakerConfig, BreakerState, BreakerStatus, CircuitBreaker, FallbackPolicy,
+    LastGoodPrice, LastObservedPrice, Market, OracleConfigParams, PairLimits, PauseFlags, Position, PriceCache,
+    PriceUpdateView, PriceValidationConfig, RiskParamsConfig, TripAction, CIRCUIT_BREAKER_SEED, DEFAULT_SWAP_FEE,
+    LAST_GOOD_PRICE_SEED, MARKET_SEED, PRICE_CACHE_SEED, PYTH_RECEIVER_PROGRAM_ID,
+};
+use pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, VerificationLevel};
+use solana_sdk::account::Account;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+const DEFAULT_PROGRAM_PATH: &str = "target/deploy/pyth_oracle.so";
+
+const SOL_MINT: Pubkey = solana_sdk::pubkey!("So11111111111111111111111111111111111111112");
+
+/// Slot the cache was filled in; the program runs one slot later, so every
+/// read is a miss that loads the price update
+const CACHE_SLOT: u64 = 1;
+
+// ============================================================================
+// INPUT
+// ============================================================================
+
+#[derive(Arbitrary, Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    GetPrice,
+    GetPriceRaw,
+    GetCachedPrice,
+    GetFallbackPrice,
+    CheckCircuitBreaker,
+    UpdateCollateralValue,
+}
+
+impl Target {
+    /// Bound to SOL/USD by its accounts
+    fn checks_feed(self) -> bool {
+        !matches!(self, Target::GetPrice | Target::GetPriceRaw)
+    }
+}
+
+/// The update before mutation
+#[derive(Arbitrary, Debug)]
+struct Update {
+    price: i64,
+    conf: u64,
+    exponent: i32,
+    /// Negative publishes in the future
+    age_secs: i16,
+    /// Signatures of a partially verified update
+    partial: Option<u8>,
+    usdc_feed: bool,
+}
+
+#[derive(Arbitrary, Debug)]
+enum Owner {
+    /// The example program: a look-alike account it could have created
+    OracleProgram,
+    SystemProgram,
+    Other([u8; 32]),
+}
+
+#[derive(Arbitrary, Debug)]
+enum Mutation {
+    Owner(Owner),
+    Discriminator([u8; 8]),
+    /// XOR the byte at each offset, wrapping over the data
+    Flip(Vec<(u16, u8)>),
+    Truncate(u16),
+    Extend(Vec<u8>),
+    Replace(Vec<u8>),
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    target: Target,
+    update: Update,
+    mutations: Vec<Mutation>,
+}
+
+fn sol_feed() -> [u8; 32] {
+    parse_feed_id(price_feeds::SOL_USD).unwrap()
+}
+
+fn price_update_account(update: &Update, mutations: &[Mutation]) -> Account {
+    let feed_id = if update.usdc_feed { parse_feed_id(price_feeds::USDC_USD).unwrap() } else { sol_feed() };
+    let mut builder = PriceUpdateBuilder::new(feed_id)
+        .with_price(update.price, update.exponent)
+        .with_conf(update.conf)
+        .stale_by(update.age_secs as i64);
+    if let Some(num_signatures) = update.partial {
+        builder = builder.partially_verified(num_signatures);
+    }
+
+    let mut account = builder.to_account();
+    for mutation in mutations {
+        match mutation {
+            Mutation::Owner(Owner::OracleProgram) => account.owner = pyth_oracle::ID,
+            Mutation::Owner(Owner::SystemProgram) => account.owner = solana_sdk::system_program::ID,
+            Mutation::Owner(Owner::Other(owner)) => account.owner = Pubkey::new_from_array(*owner),
+            Mutation::Discriminator(discriminator) => {
+                let len = account.data.len().min(8);
+                account.data[..len].copy_from_slice(&discriminator[..len]);
+            }
+            Mutation::Flip(flips) => {
+                if account.data.is_empty() {
+                    continue;
+                }
+                for &(offset, mask) in flips {
+                    let len = account.data.len();
+                    account.data[offset as usize % len] ^= mask;
+                }
+            }
+            Mutation::Truncate(len) => account.data.truncate(*len as usize),
+            Mutation::Extend(bytes) => account.data.extend_from_slice(bytes),
+            Mutation::Replace(bytes) => account.data = bytes.clone(),
+        }
+    }
+    account
+}
+
+// ============================================================================
+// HARNESS
+// ============================================================================
+
+/// The example program with a SOL/USD price cache, last good price,
+/// circuit breaker, and collateral position and market
+struct FuzzHarness {
+    svm: LiteSVM,
+    payer: Keypair,
+    price_cache: Pubkey,
+    last_good_price: Pubkey,
+    circuit_breaker: Pubkey,
+    position: Pubkey,
+    collateral_market: Pubkey,
+    last_observed_price: Pubkey,
+    /// Accounts the instructions write, as seeded
+    initial: Vec<(Pubkey, Account)>,
+}
+
+impl FuzzHarness {
+    fn new() -> Self {
+        let mut svm = LiteSVM::new();
+        let path = std::env::var("ORACLE_EXAMPLE_SO").unwrap_or(DEFAULT_PROGRAM_PATH.to_string());
+        svm.add_program_from_file(pyth_oracle::ID, &path)
+            .expect("build the program with `anchor build` first");
+        let payer = Keypair::new();
+        svm.airdrop(&payer.pubkey(), 1_000_000_000_000).unwrap();
+        svm.warp_to_slot(CACHE_SLOT + 1);
+        let mut clock: Clock = svm.get_sysvar();
+        clock.unix_timestamp = DEFAULT_TEST_TIMESTAMP;
+        svm.set_sysvar(&clock);
+
+        let authority = payer.pubkey();
+        let feed_id = sol_feed();
+        let mut harness = Self {
+            svm,
+            payer,
+            price_cache: Pubkey::default(),
+            last_good_price: Pubkey::default(),
+            circuit_breaker: Pubkey::default(),
+            position: Pubkey::default(),
+            collateral_market: Pubkey::default(),
+            last_observed_price: Pubkey::default(),
+            initial: Vec::new(),
+        };
+
+        let (address, bump) = Pubkey::find_program_address(&[PRICE_CACHE_SEED, &feed_id], &pyth_oracle::ID);
+        let cache = PriceCache {
+            feed_id,
+            price: 150_0000_0000,
+            conf: 0,
+            exponent: -8,
+            publish_time: DEFAULT_TEST_TIMESTAMP,
+            posted_slot: CACHE_SLOT,
+            slot: CACHE_SLOT,
+            verification_level: VerificationLevel::Full,
+            bump,
+        };
+        harness.price_cache = harness.seed_program_account(address, &cache, PriceCache::LEN);
+
+        let (address, bump) = Pubkey::find_program_address(&[LAST_GOOD_PRICE_SEED, &feed_id], &pyth_oracle::ID);
+        let last_good = LastGoodPrice {
+            feed_id,
+            price: 150_0000_0000,
+            conf: 0,
+            exponent: -8,
+            publish_time: DEFAULT_TEST_TIMESTAMP - 120,
+            bump,
+        };
+        harness.last_good_price = harness.seed_program_account(address, &last_good, LastGoodPrice::LEN);
+
+        let (address, bump) =
+            Pubkey::find_program_address(&[CIRCUIT_BREAKER_SEED, authority.as_ref(), &feed_id], &pyth_oracle::ID);
+        // Referenced to $150 ten seconds ago, as is the collateral market's
+        let breaker_state = BreakerState {
+            config: BreakerConfig { max_deviation_bps: 500, window_secs: 60, on_trip: TripAction::Reject },
+            status: BreakerStatus::Normal,
+            last_price: 150_0000_0000,
+            last_exponent: -8,
+            last_publish_time: DEFAULT_TEST_TIMESTAMP - 10,
+            trip_price: 0,
+            trip_exponent: 0,
+            tripped_at: 0,
+            trip_count: 0,
+        };
+        let breaker = CircuitBreaker { authority, feed_id, state: breaker_state, bump };
+        harness.circuit_breaker = harness.seed_program_account(address, &breaker, CircuitBreaker::LEN);
+
+        let position = Position {
+            owner: authority,
+            collateral_mint: SOL_MINT,
+            collateral_amount: 2_000_000_000,
+            usd_value: 0,
+            debt_mint: Pubkey::new_unique(),
+            debt_amount: 0,
+            debt_index: 0,
+            debt_usd_value: 0,
+            health_factor: u64::MAX,
+            last_price_update: 0,
+            bump: 255,
+        };
+        harness.position = harness.seed_program_account(Pubkey::new_unique(), &position, Position::LEN);
+
+        let (address, bump) = Pubkey::find_program_address(&[MARKET_SEED, SOL_MINT.as_ref()], &pyth_oracle::ID);
+        let limits = PriceValidationConfig::default();
+        let market = Market {
+            mint: SOL_MINT,
+            decimals: 9,
+            feed_id,
+            oracle: OracleConfigParams {
+                max_age_secs: limits.max_age_secs,
+                max_confidence_bps: limits.max_confidence_bps,
+                verification_level: limits.verification_level,
+            },
+            pair: PairLimits { max_skew_secs: 10, max_combined_confidence_bps: 300 },
+            risk: RiskParamsConfig {
+                max_ltv_bps: 7_500,
+                liquidation_threshold_bps: 8_000,
+                liquidation_bonus_bps: 500,
+                close_factor_bps: 5_000,
+                borrow_cap: u64::MAX,
+            },
+            swap_fee: DEFAULT_SWAP_FEE,
+            breaker: breaker_state,
+            paused: PauseFlags::NONE,
+            total_borrowed: 0,
+            has_interest_market: false,
+            pending_change: None,
+            bump,
+        };
+        harness.collateral_market = harness.seed_program_account(address, &market, Market::LEN);
+
+        let last_observed = LastObservedPrice {
+            authority,
+            feed_id,
+            price: 150_0000_0000,
+            exponent: -8,
+            publish_time: DEFAULT_TEST_TIMESTAMP - 3_600,
+            accepted_at: DEFAULT_TEST_TIMESTAMP - 3_600,
+            max_gap_secs: 300,
+            bump: 255,
+        };
+        harness.last_observed_price =
+            harness.seed_program_account(Pubkey::new_unique(), &last_observed, LastObservedPrice::LEN);
+        harness
+    }
+
+    fn seed_program_account<T: AccountSerialize>(&mut self, address: Pubkey, value: &T, len: usize) -> Pubkey {
+        let mut data = Vec::with_capacity(len);
+        value.try_serialize(&mut data).unwrap();
+        data.resize(len, 0);
+        let account = Account {
+            lamports: self.svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: pyth_oracle::ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.svm.set_account(address, account.clone()).unwrap();
+        self.initial.push((address, account));
+        address
+    }
+
+    /// Undo the writes of the previous input
+    fn reset(&mut self) {
+        for (address, account) in &self.initial {
+            self.svm.set_account(*address, account.clone()).unwrap();
+        }
+    }
+
+    fn run(&mut self, target: Target, price_update: Pubkey) -> Result<(), TransactionError> {
+        match target {
+            Target::GetPrice => self.send(
+                pyth_oracle::accounts::SinglePriceContext { price_update },
+                pyth_oracle::instruction::GetPrice {},
+            ),
+            Target::GetPriceRaw => self.send(
+                pyth_oracle::accounts::SinglePriceContextRaw { price_update },
+                pyth_oracle::instruction::GetPriceRaw {},
+            ),
+            Target::GetCachedPrice => self.send(
+                pyth_oracle::accounts::ReadPriceCache {
+                    price_cache: self.price_cache,
+                    price_update: Some(price_update),
+                },
+                pyth_oracle::instruction::GetCachedPrice { config: PriceValidationConfig::default() },
+            ),
+            Target::GetFallbackPrice => self.send(
+                pyth_oracle::accounts::FallbackPriceContext { price_update, last_good_price: self.last_good_price },
+                pyth_oracle::instruction::GetFallbackPrice {
+                    policy: FallbackPolicy::LastGoodWithHaircut { bps_per_minute: 100 },
+                },
+            ),
+            Target::CheckCircuitBreaker => self.send(
+                pyth_oracle::accounts::CheckCircuitBreaker { price_update, circuit_breaker: self.circuit_breaker },
+                pyth_oracle::instruction::CheckCircuitBreaker { config: PriceValidationConfig::default() },
+            ),
+            Target::UpdateCollateralValue => self.send(
+                pyth_oracle::accounts::ValueCollateral {
+                    owner: self.payer.pubkey(),
+                    collateral_price: price_update,
+                    position: self.position,
+                    collateral_market: self.collateral_market,
+                    last_observed_price: Some(self.last_observed_price),
+                    depeg_guard: None,
+                },
+                pyth_oracle::instruction::UpdateCollateralValue {},
+            ),
+        }
+    }
+
+    fn send(&mut self, accounts: impl ToAccountMetas, data: impl InstructionData) -> Result<(), TransactionError> {
+        let instruction = Instruction {
+            program_id: pyth_oracle::ID,
+            accounts: accounts.to_account_metas(None),
+            data: data.data(),
+        };
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            self.svm.latest_blockhash(),
+        );
+        // Identical inputs would otherwise be rejected as duplicates
+        self.svm.expire_blockhash();
+        self.svm.send_transaction(tx).map(|_| ()).map_err(|failed| failed.err)
+    }
+}
+
+thread_local! {
+    static HARNESS: RefCell<Option<FuzzHarness>> = const { RefCell::new(None) };
+}
+
+// ============================================================================
+// TARGET
+// ============================================================================
+
+fn fuzz_instruction(input: &Input) {
+    let account = price_update_account(&input.update, &input.mutations);
+
+    // The raw reader and the SDK must agree on every byte string
+    let view = PriceUpdateView::parse(&account.data);
+    let typed = PriceUpdateV2::try_deserialize(&mut account.data.as_slice());
+    match (&view, &typed) {
+        (Ok(view), Ok(typed)) => {
+            let message = &typed.price_message;
+            assert_eq!(view.verification_level, typed.verification_level);
+            assert_eq!(view.feed_id, message.feed_id);
+            assert_eq!((view.price, view.conf, view.exponent), (message.price, message.conf, message.exponent));
+            assert_eq!((view.publish_time, view.prev_publish_time), (message.publish_time, message.prev_publish_time));
+            assert_eq!((view.ema_price, view.ema_conf), (message.ema_price, message.ema_conf));
+            assert_eq!(view.posted_slot, typed.posted_slot);
+        }
+        (Err(_), Err(_)) => {}
+        _ => panic!("PriceUpdateView {view:?} but PriceUpdateV2 {}", typed.is_ok()),
+    }
+
+    let result = HARNESS.with(|cell| {
+        let mut cell = cell.borrow_mut();
+        let harness = cell.get_or_insert_with(FuzzHarness::new);
+        harness.reset();
+        let price_update = Pubkey::new_unique();
+        harness.svm.set_account(price_update, account.clone()).unwrap();
+        harness.run(input.target, price_update)
+    });
+
+    match result {
+        Ok(()) => {
+            assert_eq!(account.owner, PYTH_RECEIVER_PROGRAM_ID, "accepted a foreign owner");
+            let discriminator = account.data.get(..8);
+            assert_eq!(discriminator, Some(PriceUpdateV2::DISCRIMINATOR.as_ref()), "accepted a bad discriminator");
+            let typed = typed.expect("accepted an update the SDK cannot deserialize");
+            assert_eq!(typed.verification_level, VerificationLevel::Full, "accepted a partial verification");
+            if input.target.checks_feed() {
+                assert_eq!(typed.price_message.feed_id, sol_feed(), "accepted another feed");
+            }
+        }
+        // Program and Anchor errors
+        Err(TransactionError::InstructionError(0, InstructionError::Custom(_))) => {}
+        Err(error) => panic!("{:?} failed outside the program's errors: {error:?}", input.target),
+    }
+}
+
+fuzz_target!(|input: Input| fuzz_instruction(&input));
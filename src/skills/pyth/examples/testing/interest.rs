@@ -0,0 +1,207 @@
+/**
+ * Tests for Interest Accrual
+ *
+ * `RateModel` and `InterestMarket` are plain data with pure methods, so
+ * these run off chain: the kinked rate curve on both sides of the kink,
+ * the supply rate after the reserve factor, index growth and the split of
+ * interest between depositors and reserves, rounding of debt and deposit
+ * balances, and `Position::accrue_debt`.
+ *
+ * Setup:
+ * 1. Copy this file to `tests/interest.rs` of the program crate
+ * 2. Run: cargo test --test interest
+ */
+
+use anchor_lang::prelude::Pubkey;
+use pyth_oracle::{InterestMarket, Position, RateModel, INDEX_ONE, SECONDS_PER_YEAR};
+
+const YEAR: i64 = SECONDS_PER_YEAR as i64;
+const START: i64 = 1_700_000_000;
+
+/// 2% base, 10% more up to the 80% kink, then 100% more to full utilization
+fn model() -> RateModel {
+    RateModel {
+        base_rate_bps: 200,
+        optimal_utilization_bps: 8_000,
+        slope1_bps: 1_000,
+        slope2_bps: 10_000,
+        reserve_factor_bps: 1_000,
+    }
+}
+
+fn market(total_deposits: u64, total_borrows: u64) -> InterestMarket {
+    InterestMarket {
+        authority: Pubkey::new_unique(),
+        risk_params: Pubkey::new_unique(),
+        mint: Pubkey::new_unique(),
+        rate_model: model(),
+        total_deposits,
+        total_borrows,
+        total_reserves: 0,
+        borrow_index: INDEX_ONE,
+        supply_index: INDEX_ONE,
+        last_accrual: START,
+        bump: 255,
+    }
+}
+
+fn position(market: &InterestMarket, debt_amount: u64, debt_index: u128) -> Position {
+    Position {
+        owner: Pubkey::new_unique(),
+        collateral_mint: Pubkey::new_unique(),
+        collateral_amount: 0,
+        usd_value: 0,
+        debt_mint: market.mint,
+        debt_amount,
+        debt_index,
+        debt_usd_value: 0,
+        health_factor: u64::MAX,
+        last_price_update: 0,
+        bump: 255,
+    }
+}
+
+// ============================================================================
+// RATE MODEL
+// ============================================================================
+
+#[test]
+fn borrow_rate_follows_the_kink() {
+    let model = model();
+    assert_eq!(model.borrow_rate_bps(0), 200);
+    assert_eq!(model.borrow_rate_bps(4_000), 700);
+    assert_eq!(model.borrow_rate_bps(8_000), 1_200);
+    assert_eq!(model.borrow_rate_bps(9_000), 6_200);
+    assert_eq!(model.borrow_rate_bps(10_000), 11_200);
+}
+
+#[test]
+fn utilization_above_full_is_capped() {
+    assert_eq!(model().borrow_rate_bps(20_000), model().borrow_rate_bps(10_000));
+    assert_eq!(market(100, 200).utilization_bps(), 10_000);
+    assert_eq!(market(0, 0).utilization_bps(), 0);
+}
+
+#[test]
+fn supply_rate_is_borrow_rate_times_utilization_less_reserves() {
+    // 12% × 80% × 90%
+    assert_eq!(model().supply_rate_bps(8_000), 864);
+    assert_eq!(model().supply_rate_bps(0), 0);
+}
+
+#[test]
+fn rate_model_is_validated() {
+    assert!(model().validate().is_ok());
+    for model in [
+        RateModel { optimal_utilization_bps: 0, ..model() },
+        RateModel { optimal_utilization_bps: 10_000, ..model() },
+        RateModel { reserve_factor_bps: 10_001, ..model() },
+    ] {
+        assert!(model.validate().is_err());
+    }
+}
+
+// ============================================================================
+// ACCRUAL
+// ============================================================================
+
+#[test]
+fn one_year_at_the_kink() {
+    // 80% utilization: 12% borrow rate
+    let mut market = market(1_000_000_000, 800_000_000);
+    market.accrue(START + YEAR).unwrap();
+
+    assert_eq!(market.borrow_index, INDEX_ONE * 112 / 100);
+    assert_eq!(market.total_borrows, 896_000_000);
+    // 10% of the 96 of interest is kept; depositors earn the rest
+    assert_eq!(market.total_reserves, 9_600_000);
+    assert_eq!(market.total_deposits, 1_086_400_000);
+    assert_eq!(market.supply_index, INDEX_ONE + INDEX_ONE * 864 / 10_000);
+    assert_eq!(market.last_accrual, START + YEAR);
+}
+
+#[test]
+fn accrual_at_the_same_time_is_a_noop() {
+    let mut market = market(1_000_000_000, 800_000_000);
+    market.accrue(START).unwrap();
+    market.accrue(START - 60).unwrap();
+
+    assert_eq!(market.borrow_index, INDEX_ONE);
+    assert_eq!(market.total_borrows, 800_000_000);
+    assert_eq!(market.last_accrual, START);
+}
+
+#[test]
+fn frequent_accrual_compounds() {
+    let mut once = market(1_000_000_000, 800_000_000);
+    once.accrue(START + YEAR).unwrap();
+
+    let mut monthly = market(1_000_000_000, 800_000_000);
+    for month in 1..=12 {
+        monthly.accrue(START + YEAR * month / 12).unwrap();
+    }
+    assert!(monthly.borrow_index > once.borrow_index);
+    assert!(monthly.total_borrows > once.total_borrows);
+}
+
+#[test]
+fn idle_market_only_moves_its_timestamp() {
+    let mut market = market(0, 0);
+    market.accrue(START + YEAR).unwrap();
+
+    // The base rate still grows the index, but there is nothing to charge
+    assert_eq!(market.borrow_index, INDEX_ONE * 102 / 100);
+    assert_eq!(market.total_borrows, 0);
+    assert_eq!(market.supply_index, INDEX_ONE);
+    assert_eq!(market.last_accrual, START + YEAR);
+}
+
+// ============================================================================
+// BALANCES
+// ============================================================================
+
+#[test]
+fn debt_rounds_up_and_deposits_round_down() {
+    let mut market = market(3, 1);
+    market.borrow_index = INDEX_ONE * 3 / 2;
+    market.supply_index = INDEX_ONE * 3 / 2;
+
+    assert_eq!(market.debt_balance(1, INDEX_ONE).unwrap(), 2);
+    assert_eq!(market.supply_balance(1, INDEX_ONE).unwrap(), 1);
+}
+
+#[test]
+fn borrow_and_withdraw_need_liquidity() {
+    let mut market = market(1_000, 800);
+    assert!(market.record_borrow(201).is_err());
+    assert!(market.record_withdraw(201).is_err());
+
+    market.record_borrow(200).unwrap();
+    assert_eq!(market.available_liquidity(), 0);
+    market.record_repay(500);
+    market.record_withdraw(500).unwrap();
+    assert_eq!((market.total_deposits, market.total_borrows), (500, 500));
+}
+
+#[test]
+fn position_debt_follows_the_borrow_index() {
+    let mut market = market(1_000_000_000, 800_000_000);
+    let mut position = position(&market, 100_000_000, INDEX_ONE);
+    market.accrue(START + YEAR).unwrap();
+
+    assert_eq!(position.accrue_debt(&market).unwrap(), 12_000_000);
+    assert_eq!(position.debt_amount, 112_000_000);
+    assert_eq!(position.debt_index, market.borrow_index);
+
+    // Already current
+    assert_eq!(position.accrue_debt(&market).unwrap(), 0);
+}
+
+#[test]
+fn position_debt_of_another_mint_is_rejected() {
+    let market = market(1_000_000_000, 800_000_000);
+    let mut position = position(&market, 100_000_000, INDEX_ONE);
+    position.debt_mint = Pubkey::new_unique();
+
+    assert!(position.accrue_debt(&market).is_err());
+}
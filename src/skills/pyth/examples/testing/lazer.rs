@@ -0,0 +1,195 @@
+/**
+ * Tests for Pyth Lazer Payloads
+ *
+ * Parsing and the `PriceSource` adapter are pure, so these run off chain:
+ * the `solana` message envelope, payload properties and absent prices,
+ * rejection of malformed or unknown input, the confidence fallback to the
+ * bid-ask spread, and staleness in whole seconds. Signature checks need the
+ * Ed25519 program and are covered in litesvm-oracle-example.rs.
+ *
+ * Payloads carry BTC (feed 1) at $100,000.00 with exponent -8 unless a test
+ * says otherwise.
+ *
+ * Setup:
+ * 1. Copy this file to `tests/lazer.rs` of the program crate
+ * 2. Run: cargo test --test lazer
+ */
+
+use anchor_lang::prelude::Clock;
+use pyth_oracle::{
+    get_validated_price, lazer_feed_id, LazerMessage, LazerPayload, LazerPrice, PriceValidationConfig,
+    LAZER_PAYLOAD_MAGIC, LAZER_SOLANA_MAGIC,
+};
+
+const BTC: u32 = 1;
+const PRICE: i64 = 100_000_0000_0000;
+const TIMESTAMP_US: u64 = 1_700_000_000_250_000;
+
+/// Property IDs
+const PRICE_PROPERTY: u8 = 0;
+const BID: u8 = 1;
+const ASK: u8 = 2;
+const EXPONENT: u8 = 4;
+const CONFIDENCE: u8 = 5;
+
+/// Payload of one feed with `properties` as (ID, little-endian value)
+fn payload(feed_id: u32, properties: &[(u8, Vec<u8>)]) -> Vec<u8> {
+    let mut data = LAZER_PAYLOAD_MAGIC.to_le_bytes().to_vec();
+    data.extend_from_slice(&TIMESTAMP_US.to_le_bytes());
+    data.push(1); // real-time channel
+    data.push(1);
+    data.extend_from_slice(&feed_id.to_le_bytes());
+    data.push(properties.len() as u8);
+    for (id, value) in properties {
+        data.push(*id);
+        data.extend_from_slice(value);
+    }
+    data
+}
+
+/// Price, exponent, and a $50 confidence
+fn btc_payload() -> Vec<u8> {
+    payload(
+        BTC,
+        &[
+            (PRICE_PROPERTY, PRICE.to_le_bytes().to_vec()),
+            (EXPONENT, (-8i16).to_le_bytes().to_vec()),
+            (CONFIDENCE, 50_0000_0000i64.to_le_bytes().to_vec()),
+        ],
+    )
+}
+
+fn clock_at(unix_timestamp: i64) -> Clock {
+    Clock { unix_timestamp, ..Clock::default() }
+}
+
+// ============================================================================
+// PARSING
+// ============================================================================
+
+#[test]
+fn solana_envelope_splits_signature_key_and_payload() {
+    let payload = btc_payload();
+    let mut message = LAZER_SOLANA_MAGIC.to_le_bytes().to_vec();
+    message.extend_from_slice(&[7; 64]);
+    message.extend_from_slice(&[9; 32]);
+    message.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+    message.extend_from_slice(&payload);
+
+    let parsed = LazerMessage::parse(&message).unwrap();
+    assert_eq!(parsed.signature, &[7; 64][..]);
+    assert_eq!(parsed.public_key, &[9; 32][..]);
+    assert_eq!(parsed.payload, &payload[..]);
+
+    // A length that disagrees with the data
+    message.pop();
+    assert!(LazerMessage::parse(&message).is_err());
+}
+
+#[test]
+fn payload_properties_are_decoded() {
+    let payload = LazerPayload::parse(&btc_payload()).unwrap();
+    assert_eq!(payload.timestamp_us, TIMESTAMP_US);
+
+    let feed = payload.feed(BTC).unwrap();
+    assert_eq!(feed.price, Some(PRICE));
+    assert_eq!(feed.exponent, Some(-8));
+    assert_eq!(feed.confidence, Some(50_0000_0000));
+    assert_eq!(feed.best_bid_price, None);
+    assert!(payload.feed(2).is_none());
+}
+
+#[test]
+fn zero_price_means_no_price() {
+    let data = payload(BTC, &[(PRICE_PROPERTY, 0i64.to_le_bytes().to_vec())]);
+    assert_eq!(LazerPayload::parse(&data).unwrap().feed(BTC).unwrap().price, None);
+}
+
+#[test]
+fn malformed_payloads_are_rejected() {
+    let mut wrong_magic = btc_payload();
+    wrong_magic[0] ^= 1;
+    let mut truncated = btc_payload();
+    truncated.pop();
+    let mut trailing = btc_payload();
+    trailing.push(0);
+    let unknown_property = payload(BTC, &[(9, vec![0; 8])]);
+
+    for data in [wrong_magic, truncated, trailing, unknown_property] {
+        assert!(LazerPayload::parse(&data).is_err());
+    }
+}
+
+// ============================================================================
+// PRICE SOURCE
+// ============================================================================
+
+#[test]
+fn lazer_price_is_validated_like_any_source() {
+    let payload = LazerPayload::parse(&btc_payload()).unwrap();
+    let price = LazerPrice::from_payload(&payload, BTC).unwrap();
+    let config = PriceValidationConfig {
+        expected_feed_id: Some(lazer_feed_id(BTC)),
+        ..PriceValidationConfig::default()
+    };
+
+    let validated = get_validated_price(&price, &config, &clock_at(1_700_000_001)).unwrap();
+    assert_eq!((validated.price, validated.conf, validated.exponent), (PRICE, 50_0000_0000, -8));
+    // Microseconds round down to the second
+    assert_eq!(validated.publish_time, 1_700_000_000);
+}
+
+#[test]
+fn stale_lazer_price_is_rejected() {
+    let payload = LazerPayload::parse(&btc_payload()).unwrap();
+    let price = LazerPrice::from_payload(&payload, BTC).unwrap();
+    let config = PriceValidationConfig::default();
+
+    assert!(get_validated_price(&price, &config, &clock_at(1_700_000_000 + config.max_age_secs as i64)).is_ok());
+    assert!(get_validated_price(&price, &config, &clock_at(1_700_000_001 + config.max_age_secs as i64)).is_err());
+}
+
+#[test]
+fn confidence_falls_back_to_half_the_spread() {
+    let data = payload(
+        BTC,
+        &[
+            (PRICE_PROPERTY, PRICE.to_le_bytes().to_vec()),
+            (BID, (PRICE - 30_0000_0000).to_le_bytes().to_vec()),
+            (ASK, (PRICE + 45_0000_0001).to_le_bytes().to_vec()),
+            (EXPONENT, (-8i16).to_le_bytes().to_vec()),
+        ],
+    );
+    let price = LazerPrice::from_payload(&LazerPayload::parse(&data).unwrap(), BTC).unwrap();
+    // $75.00000001 spread, rounded up
+    assert_eq!(price.conf, 37_5000_0001);
+}
+
+#[test]
+fn feed_without_confidence_or_exponent_is_rejected() {
+    let no_confidence = payload(
+        BTC,
+        &[
+            (PRICE_PROPERTY, PRICE.to_le_bytes().to_vec()),
+            (EXPONENT, (-8i16).to_le_bytes().to_vec()),
+        ],
+    );
+    let no_exponent = payload(
+        BTC,
+        &[
+            (PRICE_PROPERTY, PRICE.to_le_bytes().to_vec()),
+            (CONFIDENCE, 50_0000_0000i64.to_le_bytes().to_vec()),
+        ],
+    );
+
+    for data in [no_confidence, no_exponent] {
+        assert!(LazerPrice::from_payload(&LazerPayload::parse(&data).unwrap(), BTC).is_err());
+    }
+}
+
+#[test]
+fn lazer_feed_ids_are_widened_big_endian() {
+    let id = lazer_feed_id(0x0102_0304);
+    assert_eq!(id[..28], [0; 28]);
+    assert_eq!(id[28..], [1, 2, 3, 4]);
+}
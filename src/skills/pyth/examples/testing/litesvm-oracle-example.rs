@@ -0,0 +1,3483 @@
+/**
+ * LiteSVM Integration Tests for the Oracle Example Program
+ *
+ * End-to-end tests for the `oracle_example` program in anchor-oracle.rs,
+ * running the compiled program under LiteSVM with mock price accounts from
+ * `test_utils::PriceUpdateBuilder`. Covers the verified-price, price pair, inline VAA, Lazer, price event,
+ * basket price, price cache, stale fallback, LST pricing, swap, AMM
+ * cross-check, collateral,
+ * health-factor, interest accrual, liquidation, trigger order, position snapshot, oracle config, feed
+ * binding, market, and guardian instructions; copy the harness to test your own oracle consumers.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml of the program crate:
+ *    [dev-dependencies]
+ *    litesvm = "0.1"
+ *    anchor-spl = "0.30.1"
+ *    base64 = "0.21"
+ *    solana-sdk = "1.18"
+ *    pyth-oracle = { path = ".", features = ["no-entrypoint", "test-utils"] }
+ *    pythnet-sdk = "2"
+ *    byteorder = "1"
+ *    libsecp256k1 = "0.7"
+ *
+ * 2. Copy this file to `tests/oracle_example.rs`
+ * 3. Build the program, then run the tests:
+ *    anchor build && cargo test --test oracle_example
+ *    (set ORACLE_EXAMPLE_SO to load the .so from another path)
+ */
+
+use anchor_lang::solana_program::keccak;
+use anchor_lang::{AccountSerialize, AnchorDeserialize, AnchorSerialize, Event, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use base64::Engine;
+use litesvm::types::TransactionResult;
+use litesvm::LiteSVM;
+use pyth_solana_receiver_sdk::price_update::VerificationLevel;
+use pyth_oracle::oracle::PriceSourceError;
+use pyth_oracle::post_update::WORMHOLE_RECEIVER_PROGRAM_ID;
+use pyth_oracle::test_utils::{PriceUpdateBuilder, DEFAULT_TEST_TIMESTAMP};
+use pyth_oracle::vaa_verification::{GuardianSet, GUARDIAN_SET_SEED, PYTHNET_CHAIN_ID, PYTH_ACCUMULATOR_EMITTER};
+use pyth_oracle::{LazerMessage, LazerSigners, TrustedSigner, LAZER_PAYLOAD_MAGIC, LAZER_SIGNERS_SEED, LAZER_SOLANA_MAGIC};
+use pyth_oracle::{
+    merkle_proof, merkle_root, parse_feed_id, price_feeds, BasketHolding, BasketPosition, ConfigChange, DepegAction,
+    DepegConfig, DepegGuard, DepegStatus, FallbackPolicy, FeedBinding, FeedOverride, InterestMarket, LastGoodPrice, LastObservedPrice, OracleConfig, OracleConfigParams, OracleError,
+    Position, PositionLeaf, PositionSnapshot, PriceCache, PriceConsumed, PriceRejected, PriceUse,
+    PriceValidationConfig, RejectionReason, RiskParams, RiskParamsConfig, DEFAULT_MAX_POOL_EPOCH_LAG, HEALTH_FACTOR_ONE, MAX_FEED_OVERRIDES, MAX_REMAINING_FEEDS, MIN_CONFIG_TIMELOCK_SECS, ORACLE_CONFIG_SEED,
+    FEED_BINDING_SEED, LAST_GOOD_PRICE_SEED, POSITION_SNAPSHOT_SEED, PRICE_CACHE_SEED, SPL_STAKE_POOL_PROGRAM_ID,
+    SWAP_POOL_SEED, SWAP_VAULT_SEED, AMM_REFERENCE_SEED, DEFAULT_MAX_AMM_DEVIATION_BPS, ORCA_WHIRLPOOL_PROGRAM_ID,
+    RAYDIUM_CLMM_PROGRAM_ID, INDEX_ONE, INTEREST_MARKET_SEED, RateModel, SECONDS_PER_YEAR, TriggerDirection, TriggerOrder,
+    TriggerOrderParams, TRIGGER_ORDER_SEED, ORACLE_ERROR_OFFSET, PRICE_SOURCE_ERROR_OFFSET, BreakerConfig,
+    BreakerState, BreakerStatus, Market, MarketConfig, TripAction, DEFAULT_SWAP_FEE, MARKET_SEED, Guardian,
+    PauseFlags, GUARDIAN_SEED, PairLimits,
+};
+use pythnet_sdk::accumulators::merkle::MerkleTree;
+use pythnet_sdk::accumulators::Accumulator;
+use pythnet_sdk::hashers::keccak256_160::Keccak160;
+use pythnet_sdk::messages::{Message, PriceFeedMessage};
+use pythnet_sdk::wire::to_vec;
+use pythnet_sdk::wire::v1::{MerklePriceUpdate, WormholeMerkleRoot, WormholeMessage, WormholePayload};
+use solana_sdk::account::Account;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+// ============================================================================
+// HARNESS
+// ============================================================================
+
+const DEFAULT_PROGRAM_PATH: &str = "target/deploy/pyth_oracle.so";
+
+struct OracleTestHarness {
+    svm: LiteSVM,
+    payer: Keypair,
+}
+
+impl OracleTestHarness {
+    fn new() -> Self {
+        let mut svm = LiteSVM::new();
+        let path = std::env::var("ORACLE_EXAMPLE_SO").unwrap_or(DEFAULT_PROGRAM_PATH.to_string());
+        svm.add_program_from_file(pyth_oracle::ID, &path)
+            .expect("build the program with `anchor build` first");
+
+        let payer = Keypair::new();
+        svm.airdrop(&payer.pubkey(), 10_000_000_000).unwrap();
+        Self { svm, payer }
+    }
+
+    /// Move the on-chain clock so mock publish times are interpreted correctly
+    fn set_unix_timestamp(&mut self, unix_timestamp: i64) {
+        let mut clock: Clock = self.svm.get_sysvar();
+        clock.unix_timestamp = unix_timestamp;
+        self.svm.set_sysvar(&clock);
+    }
+
+    /// Write a mock price update account and align the clock with it
+    fn seed_price(&mut self, builder: PriceUpdateBuilder) -> Pubkey {
+        let address = Pubkey::new_unique();
+        self.set_unix_timestamp(builder.reference_time());
+        self.svm.set_account(address, builder.to_account()).unwrap();
+        address
+    }
+
+    /// Write an Anchor account owned by the program
+    fn seed_program_account<T: AccountSerialize>(&mut self, value: &T, len: usize) -> Pubkey {
+        self.seed_program_account_at(Pubkey::new_unique(), value, len)
+    }
+
+    /// Write an Anchor account owned by the program at a fixed address (PDAs)
+    fn seed_program_account_at<T: AccountSerialize>(&mut self, address: Pubkey, value: &T, len: usize) -> Pubkey {
+        let mut data = Vec::with_capacity(len);
+        value.try_serialize(&mut data).unwrap();
+        data.resize(len, 0);
+
+        self.svm
+            .set_account(
+                address,
+                Account {
+                    lamports: self.svm.minimum_balance_for_rent_exemption(data.len()),
+                    data,
+                    owner: pyth_oracle::ID,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .unwrap();
+        address
+    }
+
+    fn seed_position(&mut self, collateral_amount: u64, debt_amount: u64) -> Pubkey {
+        self.seed_position_in(SOL_MINT, collateral_amount, debt_amount)
+    }
+
+    fn seed_position_in(&mut self, collateral_mint: Pubkey, collateral_amount: u64, debt_amount: u64) -> Pubkey {
+        self.seed_position_at_index(collateral_mint, collateral_amount, debt_amount, 0)
+    }
+
+    /// Position whose debt was last accrued at borrow index `debt_index`
+    fn seed_position_at_index(
+        &mut self,
+        collateral_mint: Pubkey,
+        collateral_amount: u64,
+        debt_amount: u64,
+        debt_index: u128,
+    ) -> Pubkey {
+        let position = Position {
+            owner: self.payer.pubkey(),
+            collateral_mint,
+            collateral_amount,
+            usd_value: 0,
+            debt_mint: USDC_MINT,
+            debt_amount,
+            debt_index,
+            debt_usd_value: 0,
+            health_factor: u64::MAX,
+            last_price_update: 0,
+            bump: 255,
+        };
+        self.seed_program_account(&position, Position::LEN)
+    }
+
+    /// SPL Token mint with no authorities
+    fn seed_mint(&mut self, decimals: u8) -> Pubkey {
+        let mint = spl_token::state::Mint {
+            decimals,
+            is_initialized: true,
+            ..Default::default()
+        };
+        let mut data = vec![0u8; spl_token::state::Mint::LEN];
+        spl_token::state::Mint::pack(mint, &mut data).unwrap();
+        let address = Pubkey::new_unique();
+        self.set_token_program_account(address, data);
+        address
+    }
+
+    fn seed_token_account(&mut self, mint: Pubkey, owner: Pubkey, amount: u64) -> Pubkey {
+        self.seed_token_account_at(Pubkey::new_unique(), mint, owner, amount)
+    }
+
+    /// SPL Token account holding `amount`; overwrites an existing one
+    fn seed_token_account_at(&mut self, address: Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) -> Pubkey {
+        let account = spl_token::state::Account {
+            mint,
+            owner,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        let mut data = vec![0u8; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(account, &mut data).unwrap();
+        self.set_token_program_account(address, data);
+        address
+    }
+
+    fn set_token_program_account(&mut self, address: Pubkey, data: Vec<u8>) {
+        self.svm
+            .set_account(
+                address,
+                Account {
+                    lamports: self.svm.minimum_balance_for_rent_exemption(data.len()),
+                    data,
+                    owner: spl_token::ID,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .unwrap();
+    }
+
+    fn token_balance(&self, address: Pubkey) -> u64 {
+        let account = self.svm.get_account(&address).unwrap();
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    /// Feed binding at its PDA
+    fn seed_feed_binding(&mut self, mint: Pubkey, feed_id: [u8; 32]) -> Pubkey {
+        let (address, bump) = Pubkey::find_program_address(&[FEED_BINDING_SEED, mint.as_ref()], &pyth_oracle::ID);
+        let binding = FeedBinding { mint, feed_id, bump };
+        self.seed_program_account_at(address, &binding, FeedBinding::LEN)
+    }
+
+    fn seed_risk_params(&mut self, mint: Pubkey, feed_id: [u8; 32], decimals: u8, threshold_bps: u16) -> Pubkey {
+        let params = RiskParams {
+            authority: self.payer.pubkey(),
+            mint,
+            feed_id,
+            decimals,
+            config: RiskParamsConfig {
+                max_ltv_bps: threshold_bps - 500,
+                liquidation_threshold_bps: threshold_bps,
+                liquidation_bonus_bps: 500,
+                close_factor_bps: 5_000,
+                borrow_cap: u64::MAX,
+            },
+            total_borrowed: 0,
+            has_interest_market: false,
+            bump: 255,
+        };
+        self.seed_program_account(&params, RiskParams::LEN)
+    }
+
+    /// Market at its PDA with the default validation limits and swap fee, a
+    /// 5% breaker, and the risk settings of `seed_risk_params`
+    fn seed_market(&mut self, mint: Pubkey, feed_id: [u8; 32], decimals: u8, threshold_bps: u16) -> Pubkey {
+        let (address, bump) = Pubkey::find_program_address(&[MARKET_SEED, mint.as_ref()], &pyth_oracle::ID);
+        let config = market_config(feed_id, threshold_bps);
+        let market = Market {
+            mint,
+            decimals,
+            feed_id,
+            oracle: config.oracle,
+            pair: config.pair,
+            risk: config.risk,
+            swap_fee: config.swap_fee,
+            breaker: BreakerState::new(config.breaker),
+            paused: PauseFlags::NONE,
+            total_borrowed: 0,
+            pending_change: None,
+            bump,
+        };
+        self.seed_program_account_at(address, &market, Market::LEN)
+    }
+
+    /// Interest market of `risk_params` with a flat `rate_bps` borrow rate,
+    /// last accrued one year ago at index 1.0
+    fn seed_interest_market(&mut self, risk_params: Pubkey, mint: Pubkey, rate_bps: u16) -> Pubkey {
+        let (address, bump) =
+            Pubkey::find_program_address(&[INTEREST_MARKET_SEED, risk_params.as_ref()], &pyth_oracle::ID);
+        let clock: Clock = self.svm.get_sysvar();
+        let market = InterestMarket {
+            authority: self.payer.pubkey(),
+            risk_params,
+            mint,
+            rate_model: RateModel {
+                base_rate_bps: rate_bps,
+                optimal_utilization_bps: 8_000,
+                slope1_bps: 0,
+                slope2_bps: 0,
+                reserve_factor_bps: 1_000,
+            },
+            total_deposits: 1_000_000_000,
+            total_borrows: 500_000_000,
+            total_reserves: 0,
+            borrow_index: INDEX_ONE,
+            supply_index: INDEX_ONE,
+            last_accrual: clock.unix_timestamp - SECONDS_PER_YEAR as i64,
+            bump,
+        };
+        self.seed_program_account_at(address, &market, InterestMarket::LEN)
+    }
+
+    /// `seed_interest_market` for `market`, flagged on the market as
+    /// `init_market_interest` does
+    fn seed_market_interest(&mut self, market: Pubkey, mint: Pubkey, rate_bps: u16) -> Pubkey {
+        let address = self.seed_interest_market(market, mint, rate_bps);
+        let account = self.svm.get_account(&market).unwrap();
+        let mut state = <Market as anchor_lang::AccountDeserialize>::try_deserialize(&mut account.data.as_slice()).unwrap();
+        state.has_interest_market = true;
+        self.seed_program_account_at(market, &state, Market::LEN);
+        address
+    }
+
+    /// Depeg guard with 50 bps tolerance, 10 minutes persistence, and a 20%
+    /// haircut, already `Deviating` since `deviating_since` if set
+    fn seed_depeg_guard(&mut self, feed_id: [u8; 32], deviating_since: Option<i64>) -> Pubkey {
+        let guard = DepegGuard {
+            authority: self.payer.pubkey(),
+            feed_id,
+            config: DepegConfig {
+                peg_price: 1,
+                peg_exponent: 0,
+                max_deviation_bps: 50,
+                min_duration_secs: 600,
+                action: DepegAction::Haircut { bps: 2_000 },
+            },
+            status: if deviating_since.is_some() { DepegStatus::Deviating } else { DepegStatus::Pegged },
+            pending_since: deviating_since.unwrap_or(0),
+            last_deviation_bps: 0,
+            last_publish_time: deviating_since.unwrap_or(0),
+            bump: 255,
+        };
+        self.seed_program_account(&guard, DepegGuard::LEN)
+    }
+
+    /// Publish-time reference at `publish_time`, accepted at `accepted_at`,
+    /// with a 5 minute rollback gap
+    fn seed_last_observed_price(&mut self, feed_id: [u8; 32], publish_time: i64, accepted_at: i64) -> Pubkey {
+        let last = LastObservedPrice {
+            authority: self.payer.pubkey(),
+            feed_id,
+            price: 150_0000_0000,
+            exponent: -8,
+            publish_time,
+            accepted_at,
+            max_gap_secs: 300,
+            bump: 255,
+        };
+        self.seed_program_account(&last, LastObservedPrice::LEN)
+    }
+
+    /// Price cache at its PDA holding $150 published at the default test
+    /// time, validated in `slot`
+    fn seed_price_cache(&mut self, feed_id: [u8; 32], slot: u64) -> Pubkey {
+        let (address, bump) = Pubkey::find_program_address(&[PRICE_CACHE_SEED, feed_id.as_ref()], &pyth_oracle::ID);
+        let cache = PriceCache {
+            feed_id,
+            price: 150_0000_0000,
+            conf: 0,
+            exponent: -8,
+            publish_time: DEFAULT_TEST_TIMESTAMP,
+            posted_slot: slot,
+            slot,
+            verification_level: VerificationLevel::Full,
+            bump,
+        };
+        self.seed_program_account_at(address, &cache, PriceCache::LEN)
+    }
+
+    /// Last good price at its PDA holding $150 published at `publish_time`
+    /// (0 for none yet)
+    fn seed_last_good_price(&mut self, feed_id: [u8; 32], publish_time: i64) -> Pubkey {
+        let (address, bump) = Pubkey::find_program_address(&[LAST_GOOD_PRICE_SEED, feed_id.as_ref()], &pyth_oracle::ID);
+        let last_good = LastGoodPrice {
+            feed_id,
+            price: 150_0000_0000,
+            conf: 0,
+            exponent: -8,
+            publish_time,
+            bump,
+        };
+        self.seed_program_account_at(address, &last_good, LastGoodPrice::LEN)
+    }
+
+    fn seed_basket_position(&mut self, holdings: Vec<BasketHolding>) -> Pubkey {
+        let position = BasketPosition {
+            owner: self.payer.pubkey(),
+            holdings,
+            total_usd_value: 0,
+            worst_case_usd_value: 0,
+            last_valued_at: 0,
+            bump: 255,
+        };
+        self.seed_program_account(&position, BasketPosition::LEN)
+    }
+
+    /// Payer-owned position snapshot at its PDA, committed from `source_slot`
+    fn seed_position_snapshot(&mut self, root: [u8; 32], leaf_count: u64, source_slot: u64) -> Pubkey {
+        let authority = self.payer.pubkey();
+        let (address, bump) =
+            Pubkey::find_program_address(&[POSITION_SNAPSHOT_SEED, authority.as_ref()], &pyth_oracle::ID);
+        let snapshot = PositionSnapshot {
+            authority,
+            root,
+            leaf_count,
+            source_slot,
+            committed_at: DEFAULT_TEST_TIMESTAMP,
+            sequence: 1,
+            bump,
+        };
+        self.seed_program_account_at(address, &snapshot, PositionSnapshot::LEN)
+    }
+
+    /// Oracle config at its PDA with a 60 s max age, 100 bps max
+    /// confidence, a one-day timelock and nothing pending
+    fn seed_oracle_config(&mut self, authority: Pubkey, nominated_authority: Pubkey, overrides: Vec<FeedOverride>) -> Pubkey {
+        let (address, bump) = Pubkey::find_program_address(&[ORACLE_CONFIG_SEED], &pyth_oracle::ID);
+        let config = OracleConfig {
+            authority,
+            nominated_authority,
+            params: OracleConfigParams {
+                max_age_secs: 60,
+                max_confidence_bps: 100,
+                verification_level: VerificationLevel::Full,
+            },
+            overrides,
+            timelock_secs: CONFIG_TIMELOCK_SECS,
+            pending_change: None,
+            bump,
+        };
+        self.seed_program_account_at(address, &config, OracleConfig::LEN)
+    }
+
+    fn send(&mut self, accounts: impl ToAccountMetas, data: impl InstructionData) -> Result<(), TransactionError> {
+        self.send_metered(accounts, data).map(|_| ())
+    }
+
+    /// Send and return the compute units the transaction consumed
+    fn send_metered(&mut self, accounts: impl ToAccountMetas, data: impl InstructionData) -> Result<u64, TransactionError> {
+        self.send_with_remaining(accounts, &[], data)
+    }
+
+    /// Send with read-only `remaining` accounts after the named ones
+    fn send_with_remaining(
+        &mut self,
+        accounts: impl ToAccountMetas,
+        remaining: &[Pubkey],
+        data: impl InstructionData,
+    ) -> Result<u64, TransactionError> {
+        self.execute(accounts, remaining, data)
+            .map(|meta| meta.compute_units_consumed)
+            .map_err(|failed| failed.err)
+    }
+
+    /// Send and return the program logs, which a failed transaction keeps
+    fn send_logged(
+        &mut self,
+        accounts: impl ToAccountMetas,
+        data: impl InstructionData,
+    ) -> (Result<(), TransactionError>, Vec<String>) {
+        match self.execute(accounts, &[], data) {
+            Ok(meta) => (Ok(()), meta.logs),
+            Err(failed) => (Err(failed.err), failed.meta.logs),
+        }
+    }
+
+    fn execute(
+        &mut self,
+        accounts: impl ToAccountMetas,
+        remaining: &[Pubkey],
+        data: impl InstructionData,
+    ) -> TransactionResult {
+        let mut metas = accounts.to_account_metas(None);
+        metas.extend(remaining.iter().map(|key| AccountMeta::new_readonly(*key, false)));
+        let instruction = Instruction {
+            program_id: pyth_oracle::ID,
+            accounts: metas,
+            data: data.data(),
+        };
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            self.svm.latest_blockhash(),
+        );
+        // Distinct blockhash per test transaction
+        self.svm.expire_blockhash();
+        self.svm.send_transaction(tx)
+    }
+
+    /// Send several instructions in one transaction
+    fn send_instructions(&mut self, instructions: &[Instruction]) -> Result<(), TransactionError> {
+        let tx = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            self.svm.latest_blockhash(),
+        );
+        self.svm.expire_blockhash();
+        self.svm.send_transaction(tx).map(|_| ()).map_err(|failed| failed.err)
+    }
+
+    fn get_price(&mut self, price_update: Pubkey) -> Result<(), TransactionError> {
+        self.send(
+            pyth_oracle::accounts::SinglePriceContext { price_update },
+            pyth_oracle::instruction::GetPrice {},
+        )
+    }
+}
+
+const SOL_MINT: Pubkey = solana_sdk::pubkey!("So11111111111111111111111111111111111111112");
+const USDC_MINT: Pubkey = solana_sdk::pubkey!("EPjFWdd5AufqSSqeM2qrxzJ6orvjbs5iMAvPRBEtzpdx");
+
+fn sol_feed() -> [u8; 32] {
+    parse_feed_id(price_feeds::SOL_USD).unwrap()
+}
+
+fn usdc_feed() -> [u8; 32] {
+    parse_feed_id(price_feeds::USDC_USD).unwrap()
+}
+
+/// Pair limits of `seed_market` and the exchange rate examples: 10 s apart,
+/// 300 bps together
+const PAIR_LIMITS: PairLimits = PairLimits { max_skew_secs: 10, max_combined_confidence_bps: 300 };
+
+/// Settings of `seed_market`: the default validation limits and swap fee,
+/// `PAIR_LIMITS`, a 5% breaker over a minute, and a max LTV 5% under
+/// `threshold_bps`
+fn market_config(feed_id: [u8; 32], threshold_bps: u16) -> MarketConfig {
+    let limits = PriceValidationConfig::default();
+    MarketConfig {
+        feed_id,
+        oracle: OracleConfigParams {
+            max_age_secs: limits.max_age_secs,
+            max_confidence_bps: limits.max_confidence_bps,
+            verification_level: limits.verification_level,
+        },
+        pair: PAIR_LIMITS,
+        risk: RiskParamsConfig {
+            max_ltv_bps: threshold_bps - 500,
+            liquidation_threshold_bps: threshold_bps,
+            liquidation_bonus_bps: 500,
+            close_factor_bps: 5_000,
+            borrow_cap: u64::MAX,
+        },
+        swap_fee: DEFAULT_SWAP_FEE,
+        breaker: BreakerConfig { max_deviation_bps: 500, window_secs: 60, on_trip: TripAction::Reject },
+    }
+}
+
+fn assert_oracle_error(result: Result<(), TransactionError>, expected: OracleError) {
+    assert_oracle_error_at(result, 0, expected);
+}
+
+/// `expected` from the transaction's instruction at `index`
+fn assert_oracle_error_at(result: Result<(), TransactionError>, index: u8, expected: OracleError) {
+    assert_eq!(
+        result,
+        Err(TransactionError::InstructionError(
+            index,
+            InstructionError::Custom(u32::from(expected))
+        ))
+    );
+}
+
+// ============================================================================
+// VERIFIED PRICE
+// ============================================================================
+
+#[test]
+fn fresh_price_is_accepted() {
+    let mut harness = OracleTestHarness::new();
+    let price = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+
+    assert_eq!(harness.get_price(price), Ok(()));
+}
+
+#[test]
+fn raw_price_read_uses_fewer_compute_units() {
+    let mut harness = OracleTestHarness::new();
+    let price_update = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+
+    let anchor = harness
+        .send_metered(
+            pyth_oracle::accounts::SinglePriceContext { price_update },
+            pyth_oracle::instruction::GetPrice {},
+        )
+        .unwrap();
+    let raw = harness
+        .send_metered(
+            pyth_oracle::accounts::SinglePriceContextRaw { price_update },
+            pyth_oracle::instruction::GetPriceRaw {},
+        )
+        .unwrap();
+
+    println!("get_price: {anchor} CU, get_price_raw: {raw} CU");
+    assert!(raw < anchor);
+}
+
+#[test]
+fn raw_price_read_checks_owner() {
+    let mut harness = OracleTestHarness::new();
+    let mut account = PriceUpdateBuilder::new(sol_feed()).to_account();
+    account.owner = Pubkey::new_unique();
+    let price_update = Pubkey::new_unique();
+    harness.svm.set_account(price_update, account).unwrap();
+
+    let result = harness.send(
+        pyth_oracle::accounts::SinglePriceContextRaw { price_update },
+        pyth_oracle::instruction::GetPriceRaw {},
+    );
+    assert_oracle_error(result, OracleError::InvalidPriceUpdateAccount);
+}
+
+#[test]
+fn stale_price_is_rejected() {
+    let mut harness = OracleTestHarness::new();
+    let price = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed())
+            .with_price(150_0000_0000, -8)
+            .stale_by(pyth_oracle::DEFAULT_MAX_PRICE_AGE as i64 + 1),
+    );
+
+    assert!(harness.get_price(price).is_err());
+}
+
+#[test]
+fn stale_price_fails_outside_the_host_error_range_with_its_age_logged() {
+    let mut harness = OracleTestHarness::new();
+    let price_update = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed())
+            .with_price(150_0000_0000, -8)
+            .with_confidence_bps(12)
+            .stale_by(74),
+    );
+    let (result, logs) = harness.send_logged(
+        pyth_oracle::accounts::SinglePriceContext { price_update },
+        pyth_oracle::instruction::GetPrice {},
+    );
+
+    // The receiver SDK's `PriceTooOld` (6000) comes back renumbered
+    let code = u32::from(PriceSourceError::PriceTooStale);
+    assert_eq!(result, Err(TransactionError::InstructionError(0, InstructionError::Custom(code))));
+    assert_eq!(code, PRICE_SOURCE_ERROR_OFFSET + 2);
+    assert_eq!(u32::from(OracleError::InvalidFeedId), ORACLE_ERROR_OFFSET);
+
+    assert!(logs.iter().any(|log| log.ends_with("Age: 74 s (max 60 s)")));
+    assert!(logs.iter().any(|log| log.ends_with("Confidence: 12 bps (max 200 bps)")));
+}
+
+#[test]
+fn wide_confidence_is_rejected() {
+    let mut harness = OracleTestHarness::new();
+    let price = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed())
+            .with_price(150_0000_0000, -8)
+            .with_confidence_bps(pyth_oracle::MAX_CONFIDENCE_BPS + 1),
+    );
+
+    assert_oracle_error(harness.get_price(price), OracleError::ConfidenceTooHigh);
+}
+
+#[test]
+fn verified_price_checks_feed_id() {
+    let mut harness = OracleTestHarness::new();
+    let price = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+
+    let result = harness.send(
+        pyth_oracle::accounts::SinglePriceContext { price_update: price },
+        pyth_oracle::instruction::GetVerifiedPrice {
+            feed_id_hex: price_feeds::SOL_USD.to_string(),
+        },
+    );
+
+    assert_oracle_error(result, OracleError::FeedIdMismatch);
+}
+
+fn get_price_pair(harness: &mut OracleTestHarness, price_update: Pubkey) -> Result<(), TransactionError> {
+    harness.send(
+        pyth_oracle::accounts::SinglePriceContext { price_update },
+        pyth_oracle::instruction::GetPricePair { max_divergence_bps: pyth_oracle::MAX_EMA_DIVERGENCE_BPS },
+    )
+}
+
+#[test]
+fn spot_near_ema_is_accepted() {
+    let mut harness = OracleTestHarness::new();
+    // $150 against a $148 EMA: 135 bps
+    let price = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8).with_ema(148_0000_0000, 0),
+    );
+
+    assert_eq!(get_price_pair(&mut harness, price), Ok(()));
+}
+
+#[test]
+fn spot_far_from_ema_is_rejected() {
+    let mut harness = OracleTestHarness::new();
+    // $150 against a $140 EMA: 714 bps, either direction
+    let above = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8).with_ema(140_0000_0000, 0),
+    );
+    assert_oracle_error(get_price_pair(&mut harness, above), OracleError::SpotEmaDivergence);
+
+    let below = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed()).with_price(130_0000_0000, -8).with_ema(140_0000_0000, 0),
+    );
+    assert_oracle_error(get_price_pair(&mut harness, below), OracleError::SpotEmaDivergence);
+}
+
+#[test]
+fn ema_confidence_is_checked() {
+    let mut harness = OracleTestHarness::new();
+    let conf = 150_0000_0000 / 10_000 * (pyth_oracle::MAX_CONFIDENCE_BPS + 1);
+    let price = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8).with_ema(150_0000_0000, conf),
+    );
+
+    assert_oracle_error(get_price_pair(&mut harness, price), OracleError::ConfidenceTooHigh);
+}
+
+fn get_exchange_rate(harness: &mut OracleTestHarness, input: Pubkey, output: Pubkey) -> Result<(), TransactionError> {
+    harness.send(
+        pyth_oracle::accounts::DualPriceContext {
+            input_price_update: input,
+            output_price_update: output,
+        },
+        pyth_oracle::instruction::GetExchangeRate { result_decimals: 6, limits: PAIR_LIMITS },
+    )
+}
+
+#[test]
+fn synchronized_pair_is_priced() {
+    let mut harness = OracleTestHarness::new();
+    let sol = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8).with_confidence_bps(100),
+    );
+    let usdc = harness.seed_price(
+        PriceUpdateBuilder::new(usdc_feed())
+            .with_price(1_0000_0000, -8)
+            .with_confidence_bps(100)
+            .stale_by(PAIR_LIMITS.max_skew_secs as i64),
+    );
+
+    assert_eq!(get_exchange_rate(&mut harness, sol, usdc), Ok(()));
+}
+
+#[test]
+fn pair_must_be_published_together() {
+    let mut harness = OracleTestHarness::new();
+    let sol = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+    let usdc = harness.seed_price(
+        PriceUpdateBuilder::new(usdc_feed())
+            .with_price(1_0000_0000, -8)
+            .stale_by(PAIR_LIMITS.max_skew_secs as i64 + 1),
+    );
+
+    assert_oracle_error(get_exchange_rate(&mut harness, sol, usdc), OracleError::PricesNotSynchronized);
+}
+
+#[test]
+fn pair_shares_one_confidence_budget() {
+    let mut harness = OracleTestHarness::new();
+    let sol = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8).with_confidence_bps(150),
+    );
+    let usdc = harness.seed_price(
+        PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8).with_confidence_bps(151),
+    );
+
+    assert_oracle_error(get_exchange_rate(&mut harness, sol, usdc), OracleError::ConfidenceTooHigh);
+}
+
+// ============================================================================
+// INLINE VAA VERIFICATION
+// ============================================================================
+
+const GUARDIAN_SET_INDEX: u32 = 4;
+
+/// Seven guardians: a quorum is five signatures
+fn guardian_keys() -> Vec<libsecp256k1::SecretKey> {
+    (1..=7u8).map(|i| libsecp256k1::SecretKey::parse(&[i; 32]).unwrap()).collect()
+}
+
+fn guardian_address(key: &libsecp256k1::SecretKey) -> [u8; 20] {
+    let public = libsecp256k1::PublicKey::from_secret_key(key).serialize();
+    keccak::hash(&public[1..]).to_bytes()[12..].try_into().unwrap()
+}
+
+/// The test guardians at the Wormhole receiver's guardian set PDA; 0 for
+/// `expiration_time` keeps the set current
+fn seed_guardian_set(harness: &mut OracleTestHarness, expiration_time: u32) -> Pubkey {
+    let set = GuardianSet {
+        index: GUARDIAN_SET_INDEX,
+        keys: guardian_keys().iter().map(guardian_address).collect(),
+        creation_time: 0,
+        expiration_time,
+    };
+    let (address, _) = Pubkey::find_program_address(
+        &[GUARDIAN_SET_SEED, &GUARDIAN_SET_INDEX.to_be_bytes()],
+        &WORMHOLE_RECEIVER_PROGRAM_ID,
+    );
+    let data = set.try_to_vec().unwrap();
+    harness
+        .svm
+        .set_account(
+            address,
+            Account {
+                lamports: harness.svm.minimum_balance_for_rent_exemption(data.len()),
+                data,
+                owner: WORMHOLE_RECEIVER_PROGRAM_ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+    address
+}
+
+/// VAA from `emitter` carrying `payload`, signed by the guardians at
+/// `signers`
+fn sign_vaa(payload: &[u8], emitter: [u8; 32], signers: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&(DEFAULT_TEST_TIMESTAMP as u32).to_be_bytes());
+    body.extend_from_slice(&0u32.to_be_bytes()); // nonce
+    body.extend_from_slice(&PYTHNET_CHAIN_ID.to_be_bytes());
+    body.extend_from_slice(&emitter);
+    body.extend_from_slice(&1u64.to_be_bytes()); // sequence
+    body.push(1); // consistency level
+    body.extend_from_slice(payload);
+
+    let digest = keccak::hash(&keccak::hash(&body).to_bytes()).to_bytes();
+    let keys = guardian_keys();
+    let mut vaa = vec![1];
+    vaa.extend_from_slice(&GUARDIAN_SET_INDEX.to_be_bytes());
+    vaa.push(signers.len() as u8);
+    for &guardian in signers {
+        let (signature, recovery_id) =
+            libsecp256k1::sign(&libsecp256k1::Message::parse(&digest), &keys[guardian as usize]);
+        vaa.push(guardian);
+        vaa.extend_from_slice(&signature.serialize());
+        vaa.push(recovery_id.serialize());
+    }
+    vaa.extend_from_slice(&body);
+    vaa
+}
+
+/// Accumulator VAA over SOL at $150 and USDC at $1, and the merkle update
+/// of SOL
+fn accumulator_update(emitter: [u8; 32], signers: &[u8]) -> (Vec<u8>, MerklePriceUpdate) {
+    let messages: Vec<Vec<u8>> = [(sol_feed(), 150_0000_0000), (usdc_feed(), 1_0000_0000)]
+        .into_iter()
+        .map(|(feed_id, price)| {
+            let message = PriceFeedMessage {
+                feed_id,
+                price,
+                conf: 0,
+                exponent: -8,
+                publish_time: DEFAULT_TEST_TIMESTAMP,
+                prev_publish_time: DEFAULT_TEST_TIMESTAMP - 1,
+                ema_price: price,
+                ema_conf: 0,
+            };
+            to_vec::<_, byteorder::BE>(&Message::PriceFeedMessage(message)).unwrap()
+        })
+        .collect();
+    let tree = MerkleTree::<Keccak160>::from_set(messages.iter().map(Vec::as_slice)).unwrap();
+    let root = WormholeMerkleRoot {
+        slot: 1,
+        ring_size: 1,
+        root: tree.root.as_bytes().try_into().unwrap(),
+    };
+    let payload = to_vec::<_, byteorder::BE>(&WormholeMessage::new(WormholePayload::Merkle(root))).unwrap();
+
+    let update = MerklePriceUpdate {
+        proof: tree.prove(&messages[0]).unwrap(),
+        message: messages[0].clone().into(),
+    };
+    (sign_vaa(&payload, emitter, signers), update)
+}
+
+fn get_price_inline(
+    harness: &mut OracleTestHarness,
+    guardian_set: Pubkey,
+    (vaa, merkle_price_update): (Vec<u8>, MerklePriceUpdate),
+) -> Result<(), TransactionError> {
+    harness.set_unix_timestamp(DEFAULT_TEST_TIMESTAMP);
+    harness.send(
+        pyth_oracle::accounts::InlinePriceContext { guardian_set },
+        pyth_oracle::instruction::GetPriceInline { vaa, merkle_price_update },
+    )
+}
+
+#[test]
+fn inline_update_is_verified_and_consumed() {
+    let mut harness = OracleTestHarness::new();
+    let guardian_set = seed_guardian_set(&mut harness, 0);
+    let update = accumulator_update(PYTH_ACCUMULATOR_EMITTER, &[0, 2, 3, 5, 6]);
+
+    assert_eq!(get_price_inline(&mut harness, guardian_set, update), Ok(()));
+}
+
+#[test]
+fn inline_update_rejects_a_forged_signature() {
+    let mut harness = OracleTestHarness::new();
+    let guardian_set = seed_guardian_set(&mut harness, 0);
+    let (mut vaa, update) = accumulator_update(PYTH_ACCUMULATOR_EMITTER, &[0, 1, 2, 3, 4]);
+    // Claim the last signature is guardian 6's
+    vaa[6 + 4 * 66] = 6;
+
+    let result = get_price_inline(&mut harness, guardian_set, (vaa, update));
+    assert_oracle_error(result, OracleError::InvalidGuardianSignature);
+}
+
+#[test]
+fn inline_update_rejects_a_repeated_guardian() {
+    let mut harness = OracleTestHarness::new();
+    let guardian_set = seed_guardian_set(&mut harness, 0);
+    let update = accumulator_update(PYTH_ACCUMULATOR_EMITTER, &[0, 1, 2, 2, 3]);
+
+    let result = get_price_inline(&mut harness, guardian_set, update);
+    assert_oracle_error(result, OracleError::InvalidGuardianSignature);
+}
+
+#[test]
+fn inline_update_rejects_another_emitter() {
+    let mut harness = OracleTestHarness::new();
+    let guardian_set = seed_guardian_set(&mut harness, 0);
+    let update = accumulator_update([7; 32], &[0, 1, 2, 3, 4]);
+
+    assert_oracle_error(get_price_inline(&mut harness, guardian_set, update), OracleError::InvalidVaa);
+}
+
+#[test]
+fn inline_update_rejects_a_tampered_price() {
+    let mut harness = OracleTestHarness::new();
+    let guardian_set = seed_guardian_set(&mut harness, 0);
+    let (vaa, mut update) = accumulator_update(PYTH_ACCUMULATOR_EMITTER, &[0, 1, 2, 3, 4]);
+    let mut message = update.message.as_ref().to_vec();
+    // Last byte of the price
+    message[40] ^= 1;
+    update.message = message.into();
+
+    let result = get_price_inline(&mut harness, guardian_set, (vaa, update));
+    assert_oracle_error(result, OracleError::PriceUpdateProofMismatch);
+}
+
+#[test]
+fn inline_update_rejects_an_expired_guardian_set() {
+    let mut harness = OracleTestHarness::new();
+    let guardian_set = seed_guardian_set(&mut harness, DEFAULT_TEST_TIMESTAMP as u32 - 1);
+    let update = accumulator_update(PYTH_ACCUMULATOR_EMITTER, &[0, 1, 2, 3, 4]);
+
+    assert_oracle_error(get_price_inline(&mut harness, guardian_set, update), OracleError::InvalidGuardianSet);
+}
+
+// ============================================================================
+// LAZER
+// ============================================================================
+
+const LAZER_BTC: u32 = 1;
+
+/// BTC at $100,000 ± $50 (exponent -8) at the default test time, signed by
+/// `signer` in the `solana` format
+fn lazer_message(signer: &Keypair) -> Vec<u8> {
+    let mut payload = LAZER_PAYLOAD_MAGIC.to_le_bytes().to_vec();
+    payload.extend_from_slice(&(DEFAULT_TEST_TIMESTAMP as u64 * 1_000_000).to_le_bytes());
+    payload.extend_from_slice(&[1, 1]); // channel, feed count
+    payload.extend_from_slice(&LAZER_BTC.to_le_bytes());
+    payload.push(3); // property count
+    payload.push(0);
+    payload.extend_from_slice(&100_000_0000_0000i64.to_le_bytes());
+    payload.push(4);
+    payload.extend_from_slice(&(-8i16).to_le_bytes());
+    payload.push(5);
+    payload.extend_from_slice(&50_0000_0000i64.to_le_bytes());
+
+    let mut message = LAZER_SOLANA_MAGIC.to_le_bytes().to_vec();
+    message.extend_from_slice(signer.sign_message(&payload).as_ref());
+    message.extend_from_slice(signer.pubkey().as_ref());
+    message.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+    message.extend_from_slice(&payload);
+    message
+}
+
+/// Ed25519 program instruction verifying `message` from its own data
+fn lazer_ed25519_instruction(message: &[u8]) -> Instruction {
+    let message = LazerMessage::parse(message).unwrap();
+    let mut data = vec![1, 0];
+    for field in [48, u16::MAX, 16, u16::MAX, 112, message.payload.len() as u16, u16::MAX] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(message.public_key);
+    data.extend_from_slice(message.signature);
+    data.extend_from_slice(message.payload);
+    Instruction { program_id: solana_sdk::ed25519_program::ID, accounts: vec![], data }
+}
+
+/// Trusted signers at their PDA
+fn seed_lazer_signers(harness: &mut OracleTestHarness, signer: Pubkey, expires_at: i64) -> Pubkey {
+    let (address, bump) = Pubkey::find_program_address(&[LAZER_SIGNERS_SEED], &pyth_oracle::ID);
+    let signers = LazerSigners { signers: vec![TrustedSigner { pubkey: signer, expires_at }], bump };
+    harness.seed_program_account_at(address, &signers, LazerSigners::LEN)
+}
+
+fn get_lazer_price_instruction(lazer_signers: Pubkey, message: Vec<u8>, ed25519_index: u16) -> Instruction {
+    Instruction {
+        program_id: pyth_oracle::ID,
+        accounts: pyth_oracle::accounts::LazerPriceContext {
+            lazer_signers,
+            instructions: solana_sdk::sysvar::instructions::ID,
+        }
+        .to_account_metas(None),
+        data: pyth_oracle::instruction::GetLazerPrice { message, ed25519_index, feed_id: LAZER_BTC }.data(),
+    }
+}
+
+/// The Ed25519 instruction of `signed`, then `get_lazer_price` with `sent`
+fn get_lazer_price(
+    harness: &mut OracleTestHarness,
+    lazer_signers: Pubkey,
+    signed: &[u8],
+    sent: Vec<u8>,
+) -> Result<(), TransactionError> {
+    harness.set_unix_timestamp(DEFAULT_TEST_TIMESTAMP);
+    harness.send_instructions(&[
+        lazer_ed25519_instruction(signed),
+        get_lazer_price_instruction(lazer_signers, sent, 0),
+    ])
+}
+
+#[test]
+fn lazer_price_from_trusted_signer_is_accepted() {
+    let mut harness = OracleTestHarness::new();
+    let signer = Keypair::new();
+    let lazer_signers = seed_lazer_signers(&mut harness, signer.pubkey(), i64::MAX);
+    let message = lazer_message(&signer);
+
+    assert_eq!(get_lazer_price(&mut harness, lazer_signers, &message, message.clone()), Ok(()));
+}
+
+#[test]
+fn lazer_price_from_unknown_signer_is_rejected() {
+    let mut harness = OracleTestHarness::new();
+    let lazer_signers = seed_lazer_signers(&mut harness, Pubkey::new_unique(), i64::MAX);
+    let message = lazer_message(&Keypair::new());
+
+    let result = get_lazer_price(&mut harness, lazer_signers, &message, message.clone());
+    assert_oracle_error_at(result, 1, OracleError::UntrustedLazerSigner);
+}
+
+#[test]
+fn lazer_price_from_expired_signer_is_rejected() {
+    let mut harness = OracleTestHarness::new();
+    let signer = Keypair::new();
+    let lazer_signers = seed_lazer_signers(&mut harness, signer.pubkey(), DEFAULT_TEST_TIMESTAMP);
+    let message = lazer_message(&signer);
+
+    let result = get_lazer_price(&mut harness, lazer_signers, &message, message.clone());
+    assert_oracle_error_at(result, 1, OracleError::UntrustedLazerSigner);
+}
+
+#[test]
+fn lazer_price_needs_the_ed25519_instruction() {
+    let mut harness = OracleTestHarness::new();
+    let signer = Keypair::new();
+    let lazer_signers = seed_lazer_signers(&mut harness, signer.pubkey(), i64::MAX);
+    harness.set_unix_timestamp(DEFAULT_TEST_TIMESTAMP);
+
+    // Index 0 is the program's own instruction
+    let result = harness.send_instructions(&[get_lazer_price_instruction(lazer_signers, lazer_message(&signer), 0)]);
+    assert_oracle_error(result, OracleError::LazerSignatureMismatch);
+}
+
+#[test]
+fn lazer_message_must_match_the_verified_one() {
+    let mut harness = OracleTestHarness::new();
+    let signer = Keypair::new();
+    let lazer_signers = seed_lazer_signers(&mut harness, signer.pubkey(), i64::MAX);
+    let signed = lazer_message(&signer);
+    let mut sent = signed.clone();
+    // First byte of the price
+    let price = sent.len() - 8 - 1 - 2 - 1 - 8;
+    sent[price] ^= 1;
+
+    let result = get_lazer_price(&mut harness, lazer_signers, &signed, sent);
+    assert_oracle_error_at(result, 1, OracleError::LazerSignatureMismatch);
+}
+
+// ============================================================================
+// PRICE EVENTS
+// ============================================================================
+
+/// Events of type `E` in `logs`, in order
+fn events<E: Event + AnchorDeserialize>(logs: &[String]) -> Vec<E> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix("Program data: "))
+        .filter_map(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+        .filter(|bytes| bytes.starts_with(&E::DISCRIMINATOR))
+        .map(|bytes| E::try_from_slice(&bytes[E::DISCRIMINATOR.len()..]).unwrap())
+        .collect()
+}
+
+#[test]
+fn consumed_price_is_logged() {
+    let mut harness = OracleTestHarness::new();
+    let price_update = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8).with_confidence_bps(10),
+    );
+
+    let (result, logs) = harness.send_logged(
+        pyth_oracle::accounts::SinglePriceContext { price_update },
+        pyth_oracle::instruction::GetPrice {},
+    );
+    assert_eq!(result, Ok(()));
+
+    let consumed = events::<PriceConsumed>(&logs);
+    assert_eq!(consumed.len(), 1);
+    assert_eq!(consumed[0].feed_id, sol_feed());
+    assert_eq!(consumed[0].price_update, price_update);
+    assert_eq!((consumed[0].price, consumed[0].conf, consumed[0].exponent), (150_0000_0000, 1500_0000, -8));
+    assert_eq!(consumed[0].used_for, PriceUse::Read);
+}
+
+#[test]
+fn rejected_price_is_logged_by_the_failed_transaction() {
+    let mut harness = OracleTestHarness::new();
+    let stale = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed())
+            .with_price(150_0000_0000, -8)
+            .stale_by(pyth_oracle::DEFAULT_MAX_PRICE_AGE as i64 + 1),
+    );
+    let (result, logs) = harness.send_logged(
+        pyth_oracle::accounts::SinglePriceContext { price_update: stale },
+        pyth_oracle::instruction::GetPrice {},
+    );
+    assert!(result.is_err());
+    let rejected = events::<PriceRejected>(&logs);
+    assert_eq!(rejected.len(), 1);
+    assert_eq!((rejected[0].feed_id, rejected[0].reason), (sol_feed(), RejectionReason::Stale));
+    assert!(events::<PriceConsumed>(&logs).is_empty());
+
+    let wrong_feed = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+    let (_, logs) = harness.send_logged(
+        pyth_oracle::accounts::SinglePriceContext { price_update: wrong_feed },
+        pyth_oracle::instruction::GetVerifiedPrice {
+            feed_id_hex: price_feeds::SOL_USD.to_string(),
+        },
+    );
+    let rejected = events::<PriceRejected>(&logs);
+    assert_eq!((rejected[0].feed_id, rejected[0].reason), (usdc_feed(), RejectionReason::FeedIdMismatch));
+    assert_eq!(rejected[0].error_code, u32::from(OracleError::FeedIdMismatch));
+}
+
+// ============================================================================
+// BASKET PRICES
+// ============================================================================
+
+fn get_basket_prices(
+    harness: &mut OracleTestHarness,
+    feed_ids: Vec<[u8; 32]>,
+    price_updates: &[Pubkey],
+) -> Result<(), TransactionError> {
+    harness
+        .send_with_remaining(
+            pyth_oracle::accounts::BasketPrices {},
+            price_updates,
+            pyth_oracle::instruction::GetBasketPrices { feed_ids, config: PriceValidationConfig::default() },
+        )
+        .map(|_| ())
+}
+
+#[test]
+fn basket_reads_updates_in_any_order() {
+    let mut harness = OracleTestHarness::new();
+    let sol = harness.seed_price(PriceUpdateBuilder::new(sol_feed()));
+    let usdc = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()));
+
+    assert_eq!(get_basket_prices(&mut harness, vec![sol_feed(), usdc_feed()], &[usdc, sol]), Ok(()));
+    // The same account twice is read once
+    assert_eq!(get_basket_prices(&mut harness, vec![sol_feed()], &[sol, sol]), Ok(()));
+}
+
+#[test]
+fn basket_rejects_missing_feed() {
+    let mut harness = OracleTestHarness::new();
+    let sol = harness.seed_price(PriceUpdateBuilder::new(sol_feed()));
+
+    let result = get_basket_prices(&mut harness, vec![sol_feed(), usdc_feed()], &[sol]);
+    assert_oracle_error(result, OracleError::MissingPriceFeed);
+}
+
+#[test]
+fn basket_rejects_two_updates_for_one_feed() {
+    let mut harness = OracleTestHarness::new();
+    let first = harness.seed_price(PriceUpdateBuilder::new(sol_feed()));
+    let second = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(151_0000_0000, -8));
+
+    let result = get_basket_prices(&mut harness, vec![sol_feed()], &[first, second]);
+    assert_oracle_error(result, OracleError::DuplicatePriceFeed);
+}
+
+#[test]
+fn basket_rejects_unrequested_feed() {
+    let mut harness = OracleTestHarness::new();
+    let sol = harness.seed_price(PriceUpdateBuilder::new(sol_feed()));
+    let usdc = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()));
+
+    let result = get_basket_prices(&mut harness, vec![sol_feed()], &[sol, usdc]);
+    assert_oracle_error(result, OracleError::FeedIdMismatch);
+}
+
+#[test]
+fn basket_rejects_more_feeds_than_capacity() {
+    let mut harness = OracleTestHarness::new();
+    let feed_ids: Vec<[u8; 32]> = (0..=MAX_REMAINING_FEEDS as u8).map(|i| [i; 32]).collect();
+
+    // Fails while collecting the feed IDs, before any account is read
+    let result = get_basket_prices(&mut harness, feed_ids, &[]);
+    assert_oracle_error(result, OracleError::TooManyPriceFeeds);
+}
+
+#[test]
+fn basket_counts_repeated_feed_ids_once() {
+    let mut harness = OracleTestHarness::new();
+    let sol = harness.seed_price(PriceUpdateBuilder::new(sol_feed()));
+
+    let feed_ids = vec![sol_feed(); MAX_REMAINING_FEEDS + 1];
+    assert_eq!(get_basket_prices(&mut harness, feed_ids, &[sol]), Ok(()));
+}
+
+/// 2 SOL and 100 USDC
+fn basket_holdings() -> Vec<BasketHolding> {
+    vec![
+        BasketHolding { mint: SOL_MINT, feed_id: sol_feed(), decimals: 9, amount: 2_000_000_000, borrowed: 0 },
+        BasketHolding { mint: USDC_MINT, feed_id: usdc_feed(), decimals: 6, amount: 100_000_000, borrowed: 0 },
+    ]
+}
+
+/// Seconds the basket's prices may be published apart
+const BASKET_MAX_SKEW_SECS: u64 = 5;
+
+fn value_basket(harness: &mut OracleTestHarness, basket_position: Pubkey, price_updates: &[Pubkey]) -> Result<(), TransactionError> {
+    harness
+        .send_with_remaining(
+            pyth_oracle::accounts::ValueBasket { basket_position },
+            price_updates,
+            pyth_oracle::instruction::ValueBasket {
+                config: PriceValidationConfig::default(),
+                max_skew_secs: BASKET_MAX_SKEW_SECS,
+            },
+        )
+        .map(|_| ())
+}
+
+#[test]
+fn basket_is_valued_at_price_and_lower_bound() {
+    let mut harness = OracleTestHarness::new();
+    // SOL at $150 ± $1.50, USDC at $1.00 ± 0
+    let sol = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8).with_confidence_bps(100),
+    );
+    let usdc = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+    let basket = harness.seed_basket_position(basket_holdings());
+
+    assert_eq!(value_basket(&mut harness, basket, &[usdc, sol]), Ok(()));
+
+    let account = harness.svm.get_account(&basket).unwrap();
+    let basket = <BasketPosition as anchor_lang::AccountDeserialize>::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(basket.total_usd_value, 400_000_000);
+    // 2 × $148.50 + $100
+    assert_eq!(basket.worst_case_usd_value, 397_000_000);
+    assert_eq!(basket.last_valued_at, DEFAULT_TEST_TIMESTAMP);
+}
+
+#[test]
+fn basket_valuation_needs_every_holding_feed() {
+    let mut harness = OracleTestHarness::new();
+    let sol = harness.seed_price(PriceUpdateBuilder::new(sol_feed()));
+    let basket = harness.seed_basket_position(basket_holdings());
+
+    assert_oracle_error(value_basket(&mut harness, basket, &[sol]), OracleError::MissingPriceFeed);
+}
+
+#[test]
+fn basket_valuation_needs_synchronized_prices() {
+    let mut harness = OracleTestHarness::new();
+    let sol = harness.seed_price(PriceUpdateBuilder::new(sol_feed()));
+    // Fresh enough on its own, but 30 s older than the SOL price
+    let usdc = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8).stale_by(30));
+    let basket = harness.seed_basket_position(basket_holdings());
+
+    assert_oracle_error(value_basket(&mut harness, basket, &[usdc, sol]), OracleError::PricesNotSynchronized);
+}
+
+// ============================================================================
+// PRICE CACHE
+// ============================================================================
+
+fn get_cached_price(
+    harness: &mut OracleTestHarness,
+    price_cache: Pubkey,
+    price_update: Option<Pubkey>,
+) -> Result<(), TransactionError> {
+    get_cached_price_with(harness, price_cache, price_update, PriceValidationConfig::default())
+}
+
+fn get_cached_price_with(
+    harness: &mut OracleTestHarness,
+    price_cache: Pubkey,
+    price_update: Option<Pubkey>,
+    config: PriceValidationConfig,
+) -> Result<(), TransactionError> {
+    harness.send(
+        pyth_oracle::accounts::ReadPriceCache { price_cache, price_update },
+        pyth_oracle::instruction::GetCachedPrice { config },
+    )
+}
+
+#[test]
+fn cache_from_this_slot_needs_no_update() {
+    let mut harness = OracleTestHarness::new();
+    harness.set_unix_timestamp(DEFAULT_TEST_TIMESTAMP);
+    harness.svm.warp_to_slot(100);
+    let price_cache = harness.seed_price_cache(sol_feed(), 100);
+
+    assert_eq!(get_cached_price(&mut harness, price_cache, None), Ok(()));
+}
+
+#[test]
+fn cache_from_earlier_slot_needs_update() {
+    let mut harness = OracleTestHarness::new();
+    harness.set_unix_timestamp(DEFAULT_TEST_TIMESTAMP);
+    harness.svm.warp_to_slot(100);
+    let price_cache = harness.seed_price_cache(sol_feed(), 99);
+
+    assert_oracle_error(get_cached_price(&mut harness, price_cache, None), OracleError::PriceCacheMiss);
+}
+
+#[test]
+fn cache_miss_refills_from_update() {
+    let mut harness = OracleTestHarness::new();
+    harness.svm.warp_to_slot(100);
+    let price_update = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed())
+            .with_price(151_0000_0000, -8)
+            .at_time(DEFAULT_TEST_TIMESTAMP + 1),
+    );
+    let price_cache = harness.seed_price_cache(sol_feed(), 99);
+
+    assert_eq!(get_cached_price(&mut harness, price_cache, Some(price_update)), Ok(()));
+
+    let account = harness.svm.get_account(&price_cache).unwrap();
+    let cache = <PriceCache as anchor_lang::AccountDeserialize>::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(cache.slot, 100);
+    assert_eq!(cache.price, 151_0000_0000);
+}
+
+#[test]
+fn update_posted_too_many_slots_ago_is_rejected() {
+    let mut harness = OracleTestHarness::new();
+    harness.svm.warp_to_slot(100);
+    let config = PriceValidationConfig::default().with_max_age_slots(25);
+    let price_cache = harness.seed_price_cache(sol_feed(), 99);
+
+    // Fresh by the clock, but posted 30 slots ago
+    let old = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).posted_at_slot(70));
+    assert_oracle_error(
+        get_cached_price_with(&mut harness, price_cache, Some(old), config),
+        OracleError::PriceTooStale,
+    );
+
+    let recent = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).posted_at_slot(75));
+    assert_eq!(get_cached_price_with(&mut harness, price_cache, Some(recent), config), Ok(()));
+
+    // The cache keeps the posted slot, so later hits are checked the same way
+    let account = harness.svm.get_account(&price_cache).unwrap();
+    let cache = <PriceCache as anchor_lang::AccountDeserialize>::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(cache.posted_slot, 75);
+}
+
+// ============================================================================
+// STALE FALLBACK
+// ============================================================================
+
+fn get_fallback_price(
+    harness: &mut OracleTestHarness,
+    price_update: Pubkey,
+    last_good_price: Pubkey,
+    policy: FallbackPolicy,
+) -> (Result<(), TransactionError>, Vec<String>) {
+    harness.send_logged(
+        pyth_oracle::accounts::FallbackPriceContext { price_update, last_good_price },
+        pyth_oracle::instruction::GetFallbackPrice { policy },
+    )
+}
+
+/// SOL price update 90 s past the default max age
+fn stale_sol_price(harness: &mut OracleTestHarness) -> Pubkey {
+    harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed())
+            .with_price(150_0000_0000, -8)
+            .stale_by(pyth_oracle::DEFAULT_MAX_PRICE_AGE as i64 + 90),
+    )
+}
+
+const HAIRCUT: FallbackPolicy = FallbackPolicy::LastGoodWithHaircut { bps_per_minute: 100 };
+
+const FREEZE: FallbackPolicy = FallbackPolicy::FreezeWithdrawalsOnly { max_stale_secs: 600 };
+
+#[test]
+fn fresh_price_is_recorded_as_last_good() {
+    let mut harness = OracleTestHarness::new();
+    let price_update = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed())
+            .with_price(151_0000_0000, -8)
+            .at_time(DEFAULT_TEST_TIMESTAMP + 1),
+    );
+    let last_good_price = harness.seed_last_good_price(sol_feed(), DEFAULT_TEST_TIMESTAMP);
+
+    let (result, logs) = get_fallback_price(&mut harness, price_update, last_good_price, HAIRCUT);
+    assert_eq!(result, Ok(()));
+    assert!(logs.iter().any(|line| line.contains("(Fresh)")));
+
+    let account = harness.svm.get_account(&last_good_price).unwrap();
+    let last_good =
+        <LastGoodPrice as anchor_lang::AccountDeserialize>::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(last_good.price, 151_0000_0000);
+    assert_eq!(last_good.publish_time, DEFAULT_TEST_TIMESTAMP + 1);
+}
+
+#[test]
+fn stale_price_is_haircut_per_started_minute() {
+    let mut harness = OracleTestHarness::new();
+    let price_update = stale_sol_price(&mut harness);
+    let publish_time = DEFAULT_TEST_TIMESTAMP - pyth_oracle::DEFAULT_MAX_PRICE_AGE as i64 - 90;
+    let last_good_price = harness.seed_last_good_price(sol_feed(), publish_time);
+
+    let (result, logs) = get_fallback_price(&mut harness, price_update, last_good_price, HAIRCUT);
+    assert_eq!(result, Ok(()));
+    // 90 s past the max age: two started minutes at 100 bps; $1.50 each side
+    assert!(logs.iter().any(|line| line.contains("Haircut { haircut_bps: 200 }")));
+    assert!(logs.iter().any(|line| line.contains("Bounds: [14700000000, 15300000000]")));
+}
+
+#[test]
+fn frozen_price_is_rejected_past_max_stale() {
+    let mut harness = OracleTestHarness::new();
+    let max_age = pyth_oracle::DEFAULT_MAX_PRICE_AGE as i64;
+
+    // Exactly 600 s past the max age is still served
+    let price_update = stale_sol_price(&mut harness);
+    let last_good_price = harness.seed_last_good_price(sol_feed(), DEFAULT_TEST_TIMESTAMP - max_age - 600);
+    let (result, logs) = get_fallback_price(&mut harness, price_update, last_good_price, FREEZE);
+    assert_eq!(result, Ok(()));
+    assert!(logs.iter().any(|line| line.contains("(WithdrawalsFrozen)")));
+
+    // One second more and the stale error comes back
+    let price_update = stale_sol_price(&mut harness);
+    let last_good_price = harness.seed_last_good_price(sol_feed(), DEFAULT_TEST_TIMESTAMP - max_age - 601);
+    let (result, _) = get_fallback_price(&mut harness, price_update, last_good_price, FREEZE);
+    let code = u32::from(PriceSourceError::PriceTooStale);
+    assert_eq!(result, Err(TransactionError::InstructionError(0, InstructionError::Custom(code))));
+}
+
+#[test]
+fn haircut_is_rejected_at_the_cap() {
+    let mut harness = OracleTestHarness::new();
+    let price_update = stale_sol_price(&mut harness);
+    let long_ago = DEFAULT_TEST_TIMESTAMP - pyth_oracle::DEFAULT_MAX_PRICE_AGE as i64 - 50 * 60;
+    let last_good_price = harness.seed_last_good_price(sol_feed(), long_ago);
+
+    let (result, _) = get_fallback_price(&mut harness, price_update, last_good_price, HAIRCUT);
+    assert!(result.is_err());
+}
+
+#[test]
+fn stale_price_is_rejected_without_fallback() {
+    let mut harness = OracleTestHarness::new();
+    let price_update = stale_sol_price(&mut harness);
+    let last_good_price = harness.seed_last_good_price(sol_feed(), DEFAULT_TEST_TIMESTAMP - 200);
+
+    let (result, _) = get_fallback_price(&mut harness, price_update, last_good_price, FallbackPolicy::Reject);
+    assert!(result.is_err());
+
+    // No last good price to fall back to
+    let price_update = stale_sol_price(&mut harness);
+    let never_recorded = harness.seed_last_good_price(sol_feed(), 0);
+    let (result, _) = get_fallback_price(&mut harness, price_update, never_recorded, HAIRCUT);
+    assert!(result.is_err());
+}
+
+#[test]
+fn stale_price_freezes_withdrawals() {
+    let mut harness = OracleTestHarness::new();
+    let price_update = stale_sol_price(&mut harness);
+    let last_good_price = harness.seed_last_good_price(sol_feed(), DEFAULT_TEST_TIMESTAMP - 200);
+
+    let (result, logs) = get_fallback_price(&mut harness, price_update, last_good_price, FREEZE);
+    assert_eq!(result, Ok(()));
+    assert!(logs.iter().any(|line| line.contains("(WithdrawalsFrozen)")));
+}
+
+#[test]
+fn wide_confidence_does_not_fall_back() {
+    let mut harness = OracleTestHarness::new();
+    let price_update = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed())
+            .with_price(150_0000_0000, -8)
+            .with_confidence_bps(300),
+    );
+    let last_good_price = harness.seed_last_good_price(sol_feed(), DEFAULT_TEST_TIMESTAMP - 10);
+
+    let (result, _) = get_fallback_price(&mut harness, price_update, last_good_price, HAIRCUT);
+    assert_oracle_error(result, OracleError::ConfidenceTooHigh);
+}
+
+#[test]
+fn haircut_rate_is_validated() {
+    let mut harness = OracleTestHarness::new();
+    let price_update = stale_sol_price(&mut harness);
+    let last_good_price = harness.seed_last_good_price(sol_feed(), DEFAULT_TEST_TIMESTAMP - 200);
+
+    let policy = FallbackPolicy::LastGoodWithHaircut { bps_per_minute: 0 };
+    let (result, _) = get_fallback_price(&mut harness, price_update, last_good_price, policy);
+    assert_oracle_error(result, OracleError::InvalidFallbackPolicy);
+
+    let price_update = stale_sol_price(&mut harness);
+    let last_good_price = harness.seed_last_good_price(sol_feed(), DEFAULT_TEST_TIMESTAMP - 200);
+    let policy = FallbackPolicy::FreezeWithdrawalsOnly { max_stale_secs: 0 };
+    let (result, _) = get_fallback_price(&mut harness, price_update, last_good_price, policy);
+    assert_oracle_error(result, OracleError::InvalidFallbackPolicy);
+}
+
+// ============================================================================
+// LST PRICING
+// ============================================================================
+
+const JITOSOL_MINT: Pubkey = solana_sdk::pubkey!("J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn");
+
+/// SPL stake pool for `pool_mint` holding 1.1 SOL per pool token as of
+/// `last_update_epoch`, with the clock at `current_epoch`
+fn seed_stake_pool(harness: &mut OracleTestHarness, pool_mint: Pubkey, last_update_epoch: u64, current_epoch: u64) -> Pubkey {
+    // Borsh layout of the SPL `StakePool` up to `last_update_epoch`
+    let mut data = vec![0u8; 611];
+    data[0] = 1; // AccountType::StakePool
+    data[162..194].copy_from_slice(pool_mint.as_ref());
+    data[258..266].copy_from_slice(&1_100_000_000_000u64.to_le_bytes()); // total_lamports
+    data[266..274].copy_from_slice(&1_000_000_000_000u64.to_le_bytes()); // pool_token_supply
+    data[274..282].copy_from_slice(&last_update_epoch.to_le_bytes());
+
+    let address = Pubkey::new_unique();
+    harness
+        .svm
+        .set_account(
+            address,
+            Account {
+                lamports: harness.svm.minimum_balance_for_rent_exemption(data.len()),
+                data,
+                owner: SPL_STAKE_POOL_PROGRAM_ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+
+    let mut clock: Clock = harness.svm.get_sysvar();
+    clock.epoch = current_epoch;
+    harness.svm.set_sysvar(&clock);
+    address
+}
+
+fn get_lst_price(
+    harness: &mut OracleTestHarness,
+    price_update: Pubkey,
+    stake_pool: Pubkey,
+) -> (Result<(), TransactionError>, Vec<String>) {
+    harness.send_logged(
+        pyth_oracle::accounts::LstPriceContext { price_update, stake_pool },
+        pyth_oracle::instruction::GetLstPrice {
+            lst_mint: JITOSOL_MINT,
+            max_epochs_behind: DEFAULT_MAX_POOL_EPOCH_LAG,
+        },
+    )
+}
+
+/// SOL at $150 ± 1%
+fn sol_price_for_lst(harness: &mut OracleTestHarness) -> Pubkey {
+    harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed())
+            .with_price(150_0000_0000, -8)
+            .with_confidence_bps(100),
+    )
+}
+
+#[test]
+fn lst_price_is_sol_price_times_pool_rate() {
+    let mut harness = OracleTestHarness::new();
+    let price_update = sol_price_for_lst(&mut harness);
+    let stake_pool = seed_stake_pool(&mut harness, JITOSOL_MINT, 600, 600);
+
+    let (result, logs) = get_lst_price(&mut harness, price_update, stake_pool);
+    assert_eq!(result, Ok(()));
+    // $150 × 1.1 = $165, and the $1.50 confidence scales to $1.65
+    assert!(logs.iter().any(|line| line.contains("Price: 16500000000 × 10^-8")));
+    assert!(logs.iter().any(|line| line.contains("Bounds: [16335000000, 16665000000]")));
+}
+
+#[test]
+fn pool_rate_from_an_old_epoch_is_rejected() {
+    let mut harness = OracleTestHarness::new();
+    let price_update = sol_price_for_lst(&mut harness);
+
+    // One epoch behind is the crank window
+    let stake_pool = seed_stake_pool(&mut harness, JITOSOL_MINT, 599, 600);
+    let (result, _) = get_lst_price(&mut harness, price_update, stake_pool);
+    assert_eq!(result, Ok(()));
+
+    let stake_pool = seed_stake_pool(&mut harness, JITOSOL_MINT, 598, 600);
+    let (result, _) = get_lst_price(&mut harness, price_update, stake_pool);
+    assert_oracle_error(result, OracleError::StakePoolStale);
+}
+
+#[test]
+fn stake_pool_of_another_mint_is_rejected() {
+    let mut harness = OracleTestHarness::new();
+    let price_update = sol_price_for_lst(&mut harness);
+    let stake_pool = seed_stake_pool(&mut harness, Pubkey::new_unique(), 600, 600);
+
+    let (result, _) = get_lst_price(&mut harness, price_update, stake_pool);
+    assert_oracle_error(result, OracleError::InvalidStakePool);
+}
+
+#[test]
+fn lst_price_requires_the_sol_feed() {
+    let mut harness = OracleTestHarness::new();
+    let price_update = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+    let stake_pool = seed_stake_pool(&mut harness, JITOSOL_MINT, 600, 600);
+
+    let (result, _) = get_lst_price(&mut harness, price_update, stake_pool);
+    assert_oracle_error(result, OracleError::FeedIdMismatch);
+}
+
+// ============================================================================
+// SWAP
+// ============================================================================
+
+/// A SOL (9 decimals) / USDC (6 decimals) pool created with
+/// `init_swap_pool`, holding 1,000 USDC, the markets of both mints, and a
+/// user holding 10 SOL
+struct SwapFixture {
+    pool: Pubkey,
+    sol_mint: Pubkey,
+    usdc_mint: Pubkey,
+    sol_vault: Pubkey,
+    usdc_vault: Pubkey,
+    user_sol: Pubkey,
+    user_usdc: Pubkey,
+}
+
+fn seed_swap_pool(harness: &mut OracleTestHarness) -> SwapFixture {
+    let sol_mint = harness.seed_mint(9);
+    let usdc_mint = harness.seed_mint(6);
+    let (pool, _) = Pubkey::find_program_address(&[SWAP_POOL_SEED, sol_mint.as_ref(), usdc_mint.as_ref()], &pyth_oracle::ID);
+    let vault = |mint: Pubkey| Pubkey::find_program_address(&[SWAP_VAULT_SEED, pool.as_ref(), mint.as_ref()], &pyth_oracle::ID).0;
+    let (sol_vault, usdc_vault) = (vault(sol_mint), vault(usdc_mint));
+
+    let result = harness.send(
+        pyth_oracle::accounts::InitSwapPool {
+            authority: harness.payer.pubkey(),
+            mint_a: sol_mint,
+            mint_b: usdc_mint,
+            pool,
+            vault_a: sol_vault,
+            vault_b: usdc_vault,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+        },
+        pyth_oracle::instruction::InitSwapPool {},
+    );
+    assert_eq!(result, Ok(()));
+
+    // Fund the USDC vault in place
+    harness.seed_token_account_at(usdc_vault, usdc_mint, pool, 1_000_000_000);
+    harness.seed_market(sol_mint, sol_feed(), 9, 8_000);
+    harness.seed_market(usdc_mint, usdc_feed(), 6, 9_000);
+
+    let payer = harness.payer.pubkey();
+    SwapFixture {
+        pool,
+        sol_mint,
+        usdc_mint,
+        sol_vault,
+        usdc_vault,
+        user_sol: harness.seed_token_account(sol_mint, payer, 10_000_000_000),
+        user_usdc: harness.seed_token_account(usdc_mint, payer, 0),
+    }
+}
+
+/// Sell 1 SOL for USDC
+fn sell_sol(
+    harness: &mut OracleTestHarness,
+    pool: &SwapFixture,
+    sol_price: Pubkey,
+    usdc_price: Pubkey,
+    min_amount_out: u64,
+) -> Result<(), TransactionError> {
+    let market = |mint: Pubkey| Pubkey::find_program_address(&[MARKET_SEED, mint.as_ref()], &pyth_oracle::ID).0;
+    harness.send(
+        pyth_oracle::accounts::SwapWithOracle {
+            user: harness.payer.pubkey(),
+            pool: pool.pool,
+            input_mint: pool.sol_mint,
+            output_mint: pool.usdc_mint,
+            input_vault: pool.sol_vault,
+            output_vault: pool.usdc_vault,
+            user_input: pool.user_sol,
+            user_output: pool.user_usdc,
+            input_price: sol_price,
+            output_price: usdc_price,
+            input_market: market(pool.sol_mint),
+            output_market: market(pool.usdc_mint),
+            token_program: spl_token::ID,
+            pyth_receiver: None,
+        },
+        pyth_oracle::instruction::SwapWithOracle {
+            amount_in: 1_000_000_000, // 1 SOL
+            min_amount_out,
+        },
+    )
+}
+
+fn swap(harness: &mut OracleTestHarness, min_amount_out: u64) -> Result<(), TransactionError> {
+    swap_with_confidence(harness, 0, min_amount_out)
+}
+
+fn swap_with_confidence(
+    harness: &mut OracleTestHarness,
+    input_confidence_bps: u64,
+    min_amount_out: u64,
+) -> Result<(), TransactionError> {
+    let pool = seed_swap_pool(harness);
+    let sol_price = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed())
+            .with_price(150_0000_0000, -8)
+            .with_confidence_bps(input_confidence_bps),
+    );
+    let usdc_price = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+    sell_sol(harness, &pool, sol_price, usdc_price, min_amount_out)
+}
+
+#[test]
+fn swap_within_slippage_succeeds() {
+    let mut harness = OracleTestHarness::new();
+    // 1 SOL at $150 buys ~150 USDC
+    assert_eq!(swap(&mut harness, 149_000_000), Ok(()));
+}
+
+#[test]
+fn swap_beyond_slippage_fails() {
+    let mut harness = OracleTestHarness::new();
+    assert_oracle_error(swap(&mut harness, 151_000_000), OracleError::SlippageExceeded);
+}
+
+#[test]
+fn swap_charges_base_fee() {
+    let mut harness = OracleTestHarness::new();
+    // 150 USDC less the 30 bps base fee is 149.55
+    assert_eq!(swap(&mut harness, 149_550_000), Ok(()));
+    assert_oracle_error(swap(&mut harness, 149_550_001), OracleError::SlippageExceeded);
+}
+
+#[test]
+fn wide_confidence_raises_swap_fee() {
+    let mut harness = OracleTestHarness::new();
+    // 50 bps confidence: sold at 149.25 with a 30 + 2 * 50 = 130 bps fee,
+    // leaving ~147.31
+    assert_eq!(swap_with_confidence(&mut harness, 50, 147_000_000), Ok(()));
+    assert_oracle_error(
+        swap_with_confidence(&mut harness, 50, 148_000_000),
+        OracleError::SlippageExceeded,
+    );
+}
+
+#[test]
+fn swap_moves_tokens_on_both_legs() {
+    let mut harness = OracleTestHarness::new();
+    let pool = seed_swap_pool(&mut harness);
+    let sol_price = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+    let usdc_price = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+
+    assert_eq!(sell_sol(&mut harness, &pool, sol_price, usdc_price, 0), Ok(()));
+    assert_eq!(harness.token_balance(pool.user_sol), 9_000_000_000);
+    assert_eq!(harness.token_balance(pool.sol_vault), 1_000_000_000);
+    // 149.55 USDC out; the 0.45 USDC fee stays in the vault
+    assert_eq!(harness.token_balance(pool.user_usdc), 149_550_000);
+    assert_eq!(harness.token_balance(pool.usdc_vault), 850_450_000);
+}
+
+#[test]
+fn swap_fails_when_vault_cannot_cover_output() {
+    let mut harness = OracleTestHarness::new();
+    let pool = seed_swap_pool(&mut harness);
+    harness.seed_token_account_at(pool.usdc_vault, pool.usdc_mint, pool.pool, 100_000_000); // 100 USDC
+    let sol_price = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+    let usdc_price = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+
+    let result = sell_sol(&mut harness, &pool, sol_price, usdc_price, 0);
+    assert_oracle_error(result, OracleError::InsufficientLiquidity);
+    assert_eq!(harness.token_balance(pool.user_sol), 10_000_000_000);
+}
+
+#[test]
+fn swap_rejects_price_of_another_feed() {
+    let mut harness = OracleTestHarness::new();
+    let pool = seed_swap_pool(&mut harness);
+    // SOL/USD passed as the price of the USDC output
+    let sol_price = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+
+    let result = sell_sol(&mut harness, &pool, sol_price, sol_price, 0);
+    assert_oracle_error(result, OracleError::FeedIdMismatch);
+}
+
+#[test]
+fn swap_rejects_prices_published_apart() {
+    let mut harness = OracleTestHarness::new();
+    let pool = seed_swap_pool(&mut harness);
+    let sol_price = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+    // Fresh on its own, but older than the SOL price by more than the pair allows
+    let usdc_price = harness.seed_price(
+        PriceUpdateBuilder::new(usdc_feed())
+            .with_price(1_0000_0000, -8)
+            .stale_by(PAIR_LIMITS.max_skew_secs as i64 + 1),
+    );
+
+    let result = sell_sol(&mut harness, &pool, sol_price, usdc_price, 0);
+    assert_oracle_error(result, OracleError::PricesNotSynchronized);
+    assert_eq!(harness.token_balance(pool.user_sol), 10_000_000_000);
+}
+
+#[test]
+fn swap_takes_the_tighter_markets_pair_limits() {
+    let mut harness = OracleTestHarness::new();
+    let oracle_config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+    let pool = seed_swap_pool(&mut harness);
+
+    // Tightening needs no timelock
+    let mut config = market_config(usdc_feed(), 9_000);
+    config.pair.max_skew_secs = 2;
+    assert_eq!(tighten_market(&mut harness, oracle_config, market_address(pool.usdc_mint), config), Ok(()));
+
+    // 3 s apart: within the SOL market's 10, not the USDC market's 2
+    let sol_price = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+    let usdc_price = harness.seed_price(
+        PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8).stale_by(3),
+    );
+    let result = sell_sol(&mut harness, &pool, sol_price, usdc_price, 0);
+    assert_oracle_error(result, OracleError::PricesNotSynchronized);
+}
+
+#[test]
+fn swap_rejects_combined_confidence_over_budget() {
+    let mut harness = OracleTestHarness::new();
+    let pool = seed_swap_pool(&mut harness);
+    // 150 + 160 bps: each within its market's 200, together over 300
+    let sol_price = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8).with_confidence_bps(150),
+    );
+    let usdc_price = harness.seed_price(
+        PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8).with_confidence_bps(160),
+    );
+
+    let result = sell_sol(&mut harness, &pool, sol_price, usdc_price, 0);
+    assert_oracle_error(result, OracleError::ConfidenceTooHigh);
+}
+
+// ============================================================================
+// AMM CROSS-CHECK
+// ============================================================================
+
+/// Concentrated liquidity pool of `owner` (Orca or Raydium) trading
+/// `mint_a` for `mint_b` at `raw_price` mint_b base units per mint_a base
+/// unit
+fn seed_clmm_pool(harness: &mut OracleTestHarness, owner: Pubkey, mint_a: Pubkey, mint_b: Pubkey, raw_price: f64) -> Pubkey {
+    // Discriminator, mint and sqrt price offsets, and account size
+    let (discriminator, mint_a_offset, mint_b_offset, sqrt_price_offset, len) = if owner == ORCA_WHIRLPOOL_PROGRAM_ID {
+        ([63, 149, 209, 12, 225, 128, 99, 9], 101, 181, 65, 653)
+    } else {
+        ([247, 237, 227, 245, 215, 195, 222, 70], 73, 105, 253, 1544)
+    };
+    let sqrt_price_x64 = (raw_price.sqrt() * 2f64.powi(64)) as u128;
+
+    let mut data = vec![0u8; len];
+    data[..8].copy_from_slice(&discriminator);
+    data[mint_a_offset..mint_a_offset + 32].copy_from_slice(mint_a.as_ref());
+    data[mint_b_offset..mint_b_offset + 32].copy_from_slice(mint_b.as_ref());
+    data[sqrt_price_offset..sqrt_price_offset + 16].copy_from_slice(&sqrt_price_x64.to_le_bytes());
+
+    let address = Pubkey::new_unique();
+    harness
+        .svm
+        .set_account(
+            address,
+            Account {
+                lamports: harness.svm.minimum_balance_for_rent_exemption(data.len()),
+                data,
+                owner,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+    address
+}
+
+/// SOL (9 decimals) and USDC (6 decimals) with feed bindings, and an
+/// oracle config owned by the payer
+struct AmmFixture {
+    oracle_config: Pubkey,
+    sol_mint: Pubkey,
+    usdc_mint: Pubkey,
+}
+
+fn seed_amm_fixture(harness: &mut OracleTestHarness) -> AmmFixture {
+    let fixture = AmmFixture {
+        oracle_config: harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]),
+        sol_mint: harness.seed_mint(9),
+        usdc_mint: harness.seed_mint(6),
+    };
+    harness.seed_feed_binding(fixture.sol_mint, sol_feed());
+    harness.seed_feed_binding(fixture.usdc_mint, usdc_feed());
+    fixture
+}
+
+fn init_amm_reference(
+    harness: &mut OracleTestHarness,
+    fixture: &AmmFixture,
+    pool: Pubkey,
+) -> (Pubkey, Result<(), TransactionError>) {
+    let (amm_reference, _) =
+        Pubkey::find_program_address(&[AMM_REFERENCE_SEED, fixture.sol_mint.as_ref()], &pyth_oracle::ID);
+    let result = harness.send(
+        pyth_oracle::accounts::InitAmmReference {
+            authority: harness.payer.pubkey(),
+            oracle_config: fixture.oracle_config,
+            pool,
+            amm_reference,
+            system_program: solana_sdk::system_program::ID,
+        },
+        pyth_oracle::instruction::InitAmmReference {
+            mint: fixture.sol_mint,
+            max_deviation_bps: DEFAULT_MAX_AMM_DEVIATION_BPS,
+        },
+    );
+    (amm_reference, result)
+}
+
+/// Check SOL at $150 against `pool`
+fn check_amm_price(
+    harness: &mut OracleTestHarness,
+    fixture: &AmmFixture,
+    amm_reference: Pubkey,
+    pool: Pubkey,
+) -> (Result<(), TransactionError>, Vec<String>) {
+    let base_price = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+    let quote_price = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+    let binding = |mint: Pubkey| Pubkey::find_program_address(&[FEED_BINDING_SEED, mint.as_ref()], &pyth_oracle::ID).0;
+    harness.send_logged(
+        pyth_oracle::accounts::CheckAmmPrice {
+            base_mint: fixture.sol_mint,
+            quote_mint: fixture.usdc_mint,
+            base_price,
+            quote_price,
+            base_binding: binding(fixture.sol_mint),
+            quote_binding: binding(fixture.usdc_mint),
+            amm_reference,
+            pool,
+        },
+        pyth_oracle::instruction::CheckAmmPrice {},
+    )
+}
+
+#[test]
+fn oracle_near_whirlpool_price_passes() {
+    let mut harness = OracleTestHarness::new();
+    let fixture = seed_amm_fixture(&mut harness);
+    // $150.50 per SOL: 0.1505 USDC base units per lamport
+    let pool = seed_clmm_pool(&mut harness, ORCA_WHIRLPOOL_PROGRAM_ID, fixture.sol_mint, fixture.usdc_mint, 0.1505);
+    let (amm_reference, result) = init_amm_reference(&mut harness, &fixture, pool);
+    assert_eq!(result, Ok(()));
+
+    let (result, logs) = check_amm_price(&mut harness, &fixture, amm_reference, pool);
+    assert_eq!(result, Ok(()));
+    // $0.50 off $150 is 33.3 bps, rounded up
+    assert!(logs.iter().any(|line| line.contains("Pool deviation: 34 bps")));
+}
+
+#[test]
+fn raydium_pool_with_reversed_mints_is_inverted() {
+    let mut harness = OracleTestHarness::new();
+    let fixture = seed_amm_fixture(&mut harness);
+    // Token 0 is USDC: lamports per USDC base unit at $150.50 per SOL
+    let pool = seed_clmm_pool(&mut harness, RAYDIUM_CLMM_PROGRAM_ID, fixture.usdc_mint, fixture.sol_mint, 1e3 / 150.5);
+    let (amm_reference, result) = init_amm_reference(&mut harness, &fixture, pool);
+    assert_eq!(result, Ok(()));
+
+    let (result, logs) = check_amm_price(&mut harness, &fixture, amm_reference, pool);
+    assert_eq!(result, Ok(()));
+    assert!(logs.iter().any(|line| line.contains("Pool deviation: 34 bps")));
+}
+
+#[test]
+fn oracle_far_from_pool_price_is_rejected() {
+    let mut harness = OracleTestHarness::new();
+    let fixture = seed_amm_fixture(&mut harness);
+    // $140 per SOL is 667 bps below the oracle
+    let pool = seed_clmm_pool(&mut harness, ORCA_WHIRLPOOL_PROGRAM_ID, fixture.sol_mint, fixture.usdc_mint, 0.14);
+    let (amm_reference, _) = init_amm_reference(&mut harness, &fixture, pool);
+
+    let (result, _) = check_amm_price(&mut harness, &fixture, amm_reference, pool);
+    assert_oracle_error(result, OracleError::AmmPriceDeviation);
+}
+
+#[test]
+fn pool_other_than_the_reference_is_rejected() {
+    let mut harness = OracleTestHarness::new();
+    let fixture = seed_amm_fixture(&mut harness);
+    let pool = seed_clmm_pool(&mut harness, ORCA_WHIRLPOOL_PROGRAM_ID, fixture.sol_mint, fixture.usdc_mint, 0.15);
+    let (amm_reference, _) = init_amm_reference(&mut harness, &fixture, pool);
+
+    // A thin pool the attacker controls, at the same mints
+    let other = seed_clmm_pool(&mut harness, ORCA_WHIRLPOOL_PROGRAM_ID, fixture.sol_mint, fixture.usdc_mint, 0.15);
+    let (result, _) = check_amm_price(&mut harness, &fixture, amm_reference, other);
+    assert_oracle_error(result, OracleError::InvalidAmmPool);
+}
+
+#[test]
+fn reference_requires_a_supported_pool_of_the_mint() {
+    let mut harness = OracleTestHarness::new();
+    let fixture = seed_amm_fixture(&mut harness);
+
+    let pool = seed_clmm_pool(&mut harness, ORCA_WHIRLPOOL_PROGRAM_ID, Pubkey::new_unique(), fixture.usdc_mint, 0.15);
+    let (_, result) = init_amm_reference(&mut harness, &fixture, pool);
+    assert_oracle_error(result, OracleError::InvalidAmmPool);
+
+    let pool = seed_clmm_pool(&mut harness, Pubkey::new_unique(), fixture.sol_mint, fixture.usdc_mint, 0.15);
+    let (_, result) = init_amm_reference(&mut harness, &fixture, pool);
+    assert_oracle_error(result, OracleError::InvalidAmmPool);
+}
+
+// ============================================================================
+// COLLATERAL
+// ============================================================================
+
+#[test]
+fn collateral_value_is_updated() {
+    let mut harness = OracleTestHarness::new();
+    let collateral_price = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+    let position = harness.seed_position(2_000_000_000, 0); // 2 SOL
+    let collateral_market = harness.seed_market(SOL_MINT, sol_feed(), 9, 8_000);
+
+    let result = harness.send(
+        pyth_oracle::accounts::ValueCollateral {
+            owner: harness.payer.pubkey(),
+            collateral_price,
+            position,
+            collateral_market,
+            last_observed_price: None,
+            depeg_guard: None,
+        },
+        pyth_oracle::instruction::UpdateCollateralValue {},
+    );
+    assert_eq!(result, Ok(()));
+
+    let account = harness.svm.get_account(&position).unwrap();
+    let position = <Position as anchor_lang::AccountDeserialize>::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(position.usd_value, 300_000_000); // $300, 6 decimals
+}
+
+#[test]
+fn collateral_priced_by_another_feed_is_rejected() {
+    let mut harness = OracleTestHarness::new();
+    // A $150 SOL price passed for a position holding USDC
+    let collateral_price = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+    let position = harness.seed_position_in(USDC_MINT, 100_000_000, 0);
+    let collateral_market = harness.seed_market(USDC_MINT, usdc_feed(), 6, 9_000);
+
+    let result = harness.send(
+        pyth_oracle::accounts::ValueCollateral {
+            owner: harness.payer.pubkey(),
+            collateral_price,
+            position,
+            collateral_market,
+            last_observed_price: None,
+            depeg_guard: None,
+        },
+        pyth_oracle::instruction::UpdateCollateralValue {},
+    );
+    assert_oracle_error(result, OracleError::FeedIdMismatch);
+}
+
+#[test]
+fn collateral_requires_the_market_of_its_mint() {
+    let mut harness = OracleTestHarness::new();
+    let collateral_price = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+    let position = harness.seed_position_in(USDC_MINT, 100_000_000, 0);
+    // The SOL market matches the price but not the position's mint
+    let collateral_market = harness.seed_market(SOL_MINT, sol_feed(), 9, 8_000);
+
+    let result = harness.send(
+        pyth_oracle::accounts::ValueCollateral {
+            owner: harness.payer.pubkey(),
+            collateral_price,
+            position,
+            collateral_market,
+            last_observed_price: None,
+            depeg_guard: None,
+        },
+        pyth_oracle::instruction::UpdateCollateralValue {},
+    );
+    assert!(result.is_err());
+}
+
+fn value_stablecoin_collateral(
+    harness: &mut OracleTestHarness,
+    deviating_for_secs: Option<i64>,
+) -> (u64, DepegGuard) {
+    let builder = PriceUpdateBuilder::new(usdc_feed()).with_price(95_000_000, -8); // $0.95
+    let now = builder.reference_time();
+    let collateral_price = harness.seed_price(builder);
+    let depeg_guard = harness.seed_depeg_guard(usdc_feed(), deviating_for_secs.map(|secs| now - secs));
+    let position = harness.seed_position_in(USDC_MINT, 100_000_000, 0); // 100 USDC
+    let collateral_market = harness.seed_market(USDC_MINT, usdc_feed(), 6, 9_000);
+
+    let result = harness.send(
+        pyth_oracle::accounts::ValueCollateral {
+            owner: harness.payer.pubkey(),
+            collateral_price,
+            position,
+            collateral_market,
+            last_observed_price: None,
+            depeg_guard: Some(depeg_guard),
+        },
+        pyth_oracle::instruction::UpdateCollateralValue {},
+    );
+    assert_eq!(result, Ok(()));
+
+    let account = harness.svm.get_account(&position).unwrap();
+    let position = <Position as anchor_lang::AccountDeserialize>::try_deserialize(&mut account.data.as_slice()).unwrap();
+    let account = harness.svm.get_account(&depeg_guard).unwrap();
+    let guard = <DepegGuard as anchor_lang::AccountDeserialize>::try_deserialize(&mut account.data.as_slice()).unwrap();
+    (position.usd_value, guard)
+}
+
+#[test]
+fn short_depeg_is_not_flagged() {
+    let mut harness = OracleTestHarness::new();
+    let (usd_value, guard) = value_stablecoin_collateral(&mut harness, None);
+
+    assert_eq!(guard.status, DepegStatus::Deviating);
+    assert_eq!(guard.last_deviation_bps, 500);
+    assert_eq!(usd_value, 95_000_000); // market value, no haircut yet
+}
+
+#[test]
+fn persistent_depeg_haircuts_collateral() {
+    let mut harness = OracleTestHarness::new();
+    let (usd_value, guard) = value_stablecoin_collateral(&mut harness, Some(600));
+
+    assert_eq!(guard.status, DepegStatus::Depegged);
+    assert_eq!(usd_value, 76_000_000); // $95 less the 20% haircut
+}
+
+fn value_after_observing(
+    harness: &mut OracleTestHarness,
+    last_publish_secs_ago: i64,
+    last_accepted_secs_ago: i64,
+) -> (Result<(), TransactionError>, LastObservedPrice) {
+    // Update published 10s before the clock
+    let builder = PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8).stale_by(10);
+    let now = builder.reference_time();
+    let collateral_price = harness.seed_price(builder);
+    let last_observed_price =
+        harness.seed_last_observed_price(sol_feed(), now - last_publish_secs_ago, now - last_accepted_secs_ago);
+    let position = harness.seed_position(2_000_000_000, 0);
+    let collateral_market = harness.seed_market(SOL_MINT, sol_feed(), 9, 8_000);
+
+    let result = harness.send(
+        pyth_oracle::accounts::ValueCollateral {
+            owner: harness.payer.pubkey(),
+            collateral_price,
+            position,
+            collateral_market,
+            last_observed_price: Some(last_observed_price),
+            depeg_guard: None,
+        },
+        pyth_oracle::instruction::UpdateCollateralValue {},
+    );
+
+    let account = harness.svm.get_account(&last_observed_price).unwrap();
+    let last = <LastObservedPrice as anchor_lang::AccountDeserialize>::try_deserialize(&mut account.data.as_slice()).unwrap();
+    (result, last)
+}
+
+#[test]
+fn newer_price_advances_reference() {
+    let mut harness = OracleTestHarness::new();
+    let (result, last) = value_after_observing(&mut harness, 20, 20);
+
+    assert_eq!(result, Ok(()));
+    assert_eq!(last.publish_time, DEFAULT_TEST_TIMESTAMP - 10);
+}
+
+#[test]
+fn older_price_is_rejected() {
+    let mut harness = OracleTestHarness::new();
+    let (result, last) = value_after_observing(&mut harness, 5, 5);
+
+    assert_oracle_error(result, OracleError::PriceRollback);
+    assert_eq!(last.publish_time, DEFAULT_TEST_TIMESTAMP - 5);
+}
+
+#[test]
+fn older_price_is_accepted_after_gap() {
+    let mut harness = OracleTestHarness::new();
+    let (result, last) = value_after_observing(&mut harness, 5, 300);
+
+    assert_eq!(result, Ok(()));
+    assert_eq!(last.publish_time, DEFAULT_TEST_TIMESTAMP - 10);
+}
+
+// ============================================================================
+// LENDING RISK
+// ============================================================================
+
+fn load_position(harness: &OracleTestHarness, address: Pubkey) -> Position {
+    let account = harness.svm.get_account(&address).unwrap();
+    <Position as anchor_lang::AccountDeserialize>::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+/// `refresh_position_health` at $150 SOL and $1 USDC, accruing the debt
+/// through a market with a flat `rate_bps` borrow rate if set
+fn refresh_position_health(
+    harness: &mut OracleTestHarness,
+    position: Pubkey,
+    rate_bps: Option<u16>,
+) -> Result<(), TransactionError> {
+    let collateral_price = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+    let debt_price = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+    let collateral_params = harness.seed_risk_params(SOL_MINT, sol_feed(), 9, 8_000);
+    let debt_params = harness.seed_risk_params(USDC_MINT, usdc_feed(), 6, 9_000);
+    let debt_market = rate_bps.map(|rate_bps| harness.seed_interest_market(debt_params, USDC_MINT, rate_bps));
+
+    harness.send(
+        pyth_oracle::accounts::RefreshPositionHealth {
+            collateral_price,
+            debt_price,
+            collateral_params,
+            debt_params,
+            position,
+            debt_market,
+        },
+        pyth_oracle::instruction::RefreshPositionHealth {},
+    )
+}
+
+#[test]
+fn position_health_is_refreshed() {
+    let mut harness = OracleTestHarness::new();
+    let position = harness.seed_position(2_000_000_000, 150_000_000); // 2 SOL against 150 USDC
+
+    assert_eq!(refresh_position_health(&mut harness, position, None), Ok(()));
+
+    let position = load_position(&harness, position);
+    assert_eq!(position.usd_value, 300_000_000);
+    assert_eq!(position.debt_usd_value, 150_000_000);
+    // $300 × 80% / $150
+    assert_eq!(position.health_factor, HEALTH_FACTOR_ONE * 16 / 10);
+}
+
+#[test]
+fn refresh_accrues_debt_interest() {
+    let mut harness = OracleTestHarness::new();
+    let position = harness.seed_position_at_index(SOL_MINT, 2_000_000_000, 150_000_000, INDEX_ONE);
+
+    // One year at 10%
+    assert_eq!(refresh_position_health(&mut harness, position, Some(1_000)), Ok(()));
+
+    let position = load_position(&harness, position);
+    assert_eq!(position.debt_amount, 165_000_000);
+    assert_eq!(position.debt_index, INDEX_ONE * 11 / 10);
+    assert_eq!(position.debt_usd_value, 165_000_000);
+    // $300 × 80% / $165
+    assert_eq!(position.health_factor, 1_454_545);
+}
+
+#[test]
+fn position_without_index_starts_accruing_now() {
+    let mut harness = OracleTestHarness::new();
+    let position = harness.seed_position(2_000_000_000, 150_000_000);
+
+    assert_eq!(refresh_position_health(&mut harness, position, Some(1_000)), Ok(()));
+
+    let position = load_position(&harness, position);
+    assert_eq!(position.debt_amount, 150_000_000);
+    assert_eq!(position.debt_index, INDEX_ONE * 11 / 10);
+}
+
+#[test]
+fn interest_market_of_another_asset_is_rejected() {
+    let mut harness = OracleTestHarness::new();
+    let collateral_price = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+    let debt_price = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+    let collateral_params = harness.seed_risk_params(SOL_MINT, sol_feed(), 9, 8_000);
+    let debt_params = harness.seed_risk_params(USDC_MINT, usdc_feed(), 6, 9_000);
+    let position = harness.seed_position(2_000_000_000, 150_000_000);
+
+    // A zero-rate market of the collateral would let the debt stop accruing
+    let debt_market = harness.seed_interest_market(collateral_params, SOL_MINT, 0);
+    let result = harness.send(
+        pyth_oracle::accounts::RefreshPositionHealth {
+            collateral_price,
+            debt_price,
+            collateral_params,
+            debt_params,
+            position,
+            debt_market: Some(debt_market),
+        },
+        pyth_oracle::instruction::RefreshPositionHealth {},
+    );
+    assert_oracle_error(result, OracleError::InterestMarketMismatch);
+}
+
+fn liquidate(harness: &mut OracleTestHarness, position: Pubkey) -> Result<(), TransactionError> {
+    liquidate_accruing(harness, position, None)
+}
+
+/// `liquidate` for as much as the close factor allows, accruing the debt
+/// through a market with a flat `rate_bps` borrow rate if set
+fn liquidate_accruing(
+    harness: &mut OracleTestHarness,
+    position: Pubkey,
+    rate_bps: Option<u16>,
+) -> Result<(), TransactionError> {
+    liquidate_with_interest(harness, position, rate_bps, true)
+}
+
+/// `liquidate_accruing`, leaving the interest market out unless
+/// `pass_interest`
+fn liquidate_with_interest(
+    harness: &mut OracleTestHarness,
+    position: Pubkey,
+    rate_bps: Option<u16>,
+    pass_interest: bool,
+) -> Result<(), TransactionError> {
+    let collateral_price = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+    let debt_price = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+    let collateral_market = harness.seed_market(SOL_MINT, sol_feed(), 9, 8_000);
+    let debt_market = harness.seed_market(USDC_MINT, usdc_feed(), 6, 9_000);
+    let debt_interest = rate_bps
+        .map(|rate_bps| harness.seed_market_interest(debt_market, USDC_MINT, rate_bps))
+        .filter(|_| pass_interest);
+
+    harness.send(
+        pyth_oracle::accounts::Liquidate {
+            liquidator: harness.payer.pubkey(),
+            collateral_price,
+            debt_price,
+            collateral_market,
+            debt_market,
+            position,
+            debt_interest,
+        },
+        pyth_oracle::instruction::Liquidate {
+            repay_amount: u64::MAX, // as much as the close factor allows
+            min_collateral_out: 0,
+        },
+    )
+}
+
+#[test]
+fn unhealthy_position_is_partially_liquidated() {
+    let mut harness = OracleTestHarness::new();
+    // $300 × 80% against $250 of debt: health 0.96
+    let position = harness.seed_position(2_000_000_000, 250_000_000);
+
+    assert_eq!(liquidate(&mut harness, position), Ok(()));
+
+    let position = load_position(&harness, position);
+    // 50% close factor repays $125; 5% bonus seizes $131.25 of SOL
+    assert_eq!(position.debt_amount, 125_000_000);
+    assert_eq!(position.collateral_amount, 2_000_000_000 - 875_000_000);
+    assert!(position.health_factor > HEALTH_FACTOR_ONE);
+}
+
+#[test]
+fn healthy_position_cannot_be_liquidated() {
+    let mut harness = OracleTestHarness::new();
+    let position = harness.seed_position(2_000_000_000, 150_000_000);
+
+    assert_oracle_error(liquidate(&mut harness, position), OracleError::NotLiquidatable);
+}
+
+#[test]
+fn accrued_interest_makes_position_liquidatable() {
+    let mut harness = OracleTestHarness::new();
+    // $300 × 80% against $230 of debt: health 1.04 before interest
+    let position = harness.seed_position_at_index(SOL_MINT, 2_000_000_000, 230_000_000, INDEX_ONE);
+    assert_oracle_error(liquidate(&mut harness, position), OracleError::NotLiquidatable);
+
+    // A year at 10% brings the debt to $253: health 0.95
+    assert_eq!(liquidate_accruing(&mut harness, position, Some(1_000)), Ok(()));
+
+    let position = load_position(&harness, position);
+    // 50% close factor repays $126.50 of the accrued debt
+    assert_eq!(position.debt_amount, 126_500_000);
+    assert!(position.health_factor > HEALTH_FACTOR_ONE);
+
+    // $500 borrowed plus a year at 10%, less the repayment
+    let address = Pubkey::find_program_address(&[INTEREST_MARKET_SEED, market_address(USDC_MINT).as_ref()], &pyth_oracle::ID).0;
+    let account = harness.svm.get_account(&address).unwrap();
+    let interest = <InterestMarket as anchor_lang::AccountDeserialize>::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(interest.total_borrows, 423_500_000);
+}
+
+#[test]
+fn liquidation_requires_the_debt_markets_interest_market() {
+    let mut harness = OracleTestHarness::new();
+    let position = harness.seed_position_at_index(SOL_MINT, 2_000_000_000, 230_000_000, INDEX_ONE);
+
+    // Left out, the debt would skip a year of interest and stay in the
+    // interest market's borrows after repayment
+    assert_oracle_error(
+        liquidate_with_interest(&mut harness, position, Some(1_000), false),
+        OracleError::InterestMarketRequired,
+    );
+    assert_eq!(load_position(&harness, position).debt_amount, 230_000_000);
+}
+
+#[test]
+fn liquidation_rejects_another_markets_interest_market() {
+    let mut harness = OracleTestHarness::new();
+    let position = harness.seed_position_at_index(SOL_MINT, 2_000_000_000, 250_000_000, INDEX_ONE);
+    let collateral_price = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+    let debt_price = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+    let collateral_market = harness.seed_market(SOL_MINT, sol_feed(), 9, 8_000);
+    let debt_market = harness.seed_market(USDC_MINT, usdc_feed(), 6, 9_000);
+    // A zero-rate market of the collateral would let the debt stop accruing
+    let other_interest = harness.seed_interest_market(collateral_market, SOL_MINT, 0);
+
+    let result = harness.send(
+        pyth_oracle::accounts::Liquidate {
+            liquidator: harness.payer.pubkey(),
+            collateral_price,
+            debt_price,
+            collateral_market,
+            debt_market,
+            position,
+            debt_interest: Some(other_interest),
+        },
+        pyth_oracle::instruction::Liquidate { repay_amount: u64::MAX, min_collateral_out: 0 },
+    );
+    assert_eq!(
+        result,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(anchor_lang::error::ErrorCode::ConstraintSeeds as u32)
+        ))
+    );
+}
+
+#[test]
+fn risk_params_reject_threshold_below_ltv() {
+    let config = RiskParamsConfig {
+        max_ltv_bps: 8_500,
+        liquidation_threshold_bps: 8_000,
+        liquidation_bonus_bps: 500,
+        close_factor_bps: 5_000,
+        borrow_cap: 0,
+    };
+    assert!(config.validate().is_err());
+}
+
+// ============================================================================
+// TRIGGER ORDERS
+// ============================================================================
+
+/// SOL order of 1 SOL expiring an hour after the default test time
+fn trigger_params(direction: TriggerDirection, trigger_price: i64) -> TriggerOrderParams {
+    TriggerOrderParams {
+        feed_id: sol_feed(),
+        direction,
+        trigger_price,
+        trigger_exponent: -8,
+        amount: 1_000_000_000,
+        expires_at: DEFAULT_TEST_TIMESTAMP + 3_600,
+    }
+}
+
+fn trigger_order_address(owner: Pubkey, order_id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[TRIGGER_ORDER_SEED, owner.as_ref(), &order_id.to_le_bytes()], &pyth_oracle::ID).0
+}
+
+fn create_trigger_order(
+    harness: &mut OracleTestHarness,
+    order_id: u64,
+    params: TriggerOrderParams,
+) -> Result<(), TransactionError> {
+    harness.set_unix_timestamp(DEFAULT_TEST_TIMESTAMP);
+    let owner = harness.payer.pubkey();
+    harness.send(
+        pyth_oracle::accounts::CreateTriggerOrder {
+            owner,
+            trigger_order: trigger_order_address(owner, order_id),
+            system_program: solana_sdk::system_program::ID,
+        },
+        pyth_oracle::instruction::CreateTriggerOrder { order_id, params },
+    )
+}
+
+/// Order of another owner at its PDA; the payer executes it as a keeper
+fn seed_trigger_order(harness: &mut OracleTestHarness, params: TriggerOrderParams) -> Pubkey {
+    let owner = Pubkey::new_unique();
+    let (address, bump) =
+        Pubkey::find_program_address(&[TRIGGER_ORDER_SEED, owner.as_ref(), &7u64.to_le_bytes()], &pyth_oracle::ID);
+    let order = TriggerOrder {
+        owner,
+        order_id: 7,
+        feed_id: params.feed_id,
+        direction: params.direction,
+        trigger_price: params.trigger_price,
+        trigger_exponent: params.trigger_exponent,
+        amount: params.amount,
+        expires_at: params.expires_at,
+        created_at: DEFAULT_TEST_TIMESTAMP,
+        bump,
+    };
+    harness.seed_program_account_at(address, &order, TriggerOrder::LEN)
+}
+
+fn execute_trigger(harness: &mut OracleTestHarness, trigger_order: Pubkey, price_update: Pubkey) -> Result<(), TransactionError> {
+    let owner = load_trigger_order(harness, trigger_order).owner;
+    harness.send(
+        pyth_oracle::accounts::ExecuteTrigger {
+            executor: harness.payer.pubkey(),
+            owner,
+            price_update,
+            trigger_order,
+        },
+        pyth_oracle::instruction::ExecuteTrigger {},
+    )
+}
+
+fn load_trigger_order(harness: &OracleTestHarness, address: Pubkey) -> TriggerOrder {
+    let account = harness.svm.get_account(&address).unwrap();
+    <TriggerOrder as anchor_lang::AccountDeserialize>::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+fn is_closed(harness: &OracleTestHarness, address: Pubkey) -> bool {
+    harness.svm.get_account(&address).map_or(0, |account| account.lamports) == 0
+}
+
+/// SOL at `price` ± `conf` (exponent -8)
+fn sol_price_with_conf(harness: &mut OracleTestHarness, price: i64, conf: u64) -> Pubkey {
+    harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(price, -8).with_conf(conf))
+}
+
+#[test]
+fn sell_stop_fires_at_the_lower_bound() {
+    let mut harness = OracleTestHarness::new();
+    let order = seed_trigger_order(&mut harness, trigger_params(TriggerDirection::Below, 145_0000_0000));
+    // $145.50 ± $0.60: the lower bound $144.90 is below the $145 trigger
+    let price = sol_price_with_conf(&mut harness, 145_5000_0000, 6000_0000);
+
+    assert_eq!(execute_trigger(&mut harness, order, price), Ok(()));
+    assert!(is_closed(&harness, order));
+}
+
+#[test]
+fn sell_stop_waits_while_the_band_is_above_the_trigger() {
+    let mut harness = OracleTestHarness::new();
+    let order = seed_trigger_order(&mut harness, trigger_params(TriggerDirection::Below, 145_0000_0000));
+    // $145.50 ± $0.40: the lower bound $145.10 has not crossed
+    let price = sol_price_with_conf(&mut harness, 145_5000_0000, 4000_0000);
+
+    assert_oracle_error(execute_trigger(&mut harness, order, price), OracleError::TriggerNotReached);
+    assert!(!is_closed(&harness, order));
+}
+
+#[test]
+fn buy_stop_fires_at_the_upper_bound() {
+    let mut harness = OracleTestHarness::new();
+    let order = seed_trigger_order(&mut harness, trigger_params(TriggerDirection::Above, 155_0000_0000));
+    // $154.50 ± $0.60: the upper bound $155.10 is above the $155 trigger
+    let price = sol_price_with_conf(&mut harness, 154_5000_0000, 6000_0000);
+
+    assert_eq!(execute_trigger(&mut harness, order, price), Ok(()));
+    assert!(is_closed(&harness, order));
+}
+
+#[test]
+fn expired_order_cannot_execute() {
+    let mut harness = OracleTestHarness::new();
+    let order = seed_trigger_order(&mut harness, trigger_params(TriggerDirection::Below, 145_0000_0000));
+    let price = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed())
+            .with_price(140_0000_0000, -8)
+            .at_time(DEFAULT_TEST_TIMESTAMP + 3_601),
+    );
+
+    assert_oracle_error(execute_trigger(&mut harness, order, price), OracleError::TriggerOrderExpired);
+}
+
+#[test]
+fn trigger_rejects_price_of_another_feed() {
+    let mut harness = OracleTestHarness::new();
+    let order = seed_trigger_order(&mut harness, trigger_params(TriggerDirection::Below, 145_0000_0000));
+    let price = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+
+    assert_oracle_error(execute_trigger(&mut harness, order, price), OracleError::FeedIdMismatch);
+}
+
+#[test]
+fn owner_creates_and_cancels_an_order() {
+    let mut harness = OracleTestHarness::new();
+    let owner = harness.payer.pubkey();
+    let order = trigger_order_address(owner, 1);
+    assert_eq!(
+        create_trigger_order(&mut harness, 1, trigger_params(TriggerDirection::Below, 145_0000_0000)),
+        Ok(())
+    );
+
+    let created = load_trigger_order(&harness, order);
+    assert_eq!((created.owner, created.order_id), (owner, 1));
+    assert_eq!(created.created_at, DEFAULT_TEST_TIMESTAMP);
+
+    let result = harness.send(
+        pyth_oracle::accounts::CancelTriggerOrder { owner, trigger_order: order },
+        pyth_oracle::instruction::CancelTriggerOrder {},
+    );
+    assert_eq!(result, Ok(()));
+    assert!(is_closed(&harness, order));
+}
+
+#[test]
+fn order_expiring_in_the_past_is_rejected() {
+    let mut harness = OracleTestHarness::new();
+    let params = TriggerOrderParams {
+        expires_at: DEFAULT_TEST_TIMESTAMP,
+        ..trigger_params(TriggerDirection::Below, 145_0000_0000)
+    };
+
+    assert_oracle_error(create_trigger_order(&mut harness, 1, params), OracleError::InvalidTriggerOrder);
+}
+
+// ============================================================================
+// POSITION SNAPSHOT
+// ============================================================================
+
+/// Five positions, so the tree has an odd node to carry up
+fn snapshot_leaves() -> Vec<PositionLeaf> {
+    (1..=5u64)
+        .map(|i| PositionLeaf {
+            position: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            collateral_mint: SOL_MINT,
+            collateral_amount: i * 1_000_000_000,
+            collateral_usd_value: i * 150_000_000,
+            debt_mint: USDC_MINT,
+            debt_amount: i * 50_000_000,
+            debt_usd_value: i * 50_000_000,
+            health_factor: 2 * HEALTH_FACTOR_ONE + i,
+            last_price_update: DEFAULT_TEST_TIMESTAMP,
+        })
+        .collect()
+}
+
+fn verify_position(
+    harness: &mut OracleTestHarness,
+    position_snapshot: Pubkey,
+    leaf: PositionLeaf,
+    proof: Vec<[u8; 32]>,
+) -> Result<(), TransactionError> {
+    harness.send(
+        pyth_oracle::accounts::VerifyPositionSnapshot { position_snapshot },
+        pyth_oracle::instruction::VerifyPositionSnapshot { leaf, proof },
+    )
+}
+
+fn commit_snapshot(harness: &mut OracleTestHarness, position_snapshot: Pubkey, source_slot: u64) -> Result<(), TransactionError> {
+    harness.send(
+        pyth_oracle::accounts::CommitPositionSnapshot {
+            authority: harness.payer.pubkey(),
+            position_snapshot,
+        },
+        pyth_oracle::instruction::CommitPositionSnapshot {
+            root: [1; 32],
+            leaf_count: 5,
+            source_slot,
+        },
+    )
+}
+
+#[test]
+fn every_position_proof_verifies() {
+    let mut harness = OracleTestHarness::new();
+    let leaves = snapshot_leaves();
+    let snapshot = harness.seed_position_snapshot(merkle_root(&leaves), leaves.len() as u64, 100);
+
+    for (index, leaf) in leaves.iter().enumerate() {
+        let proof = merkle_proof(&leaves, index).unwrap();
+        assert_eq!(verify_position(&mut harness, snapshot, *leaf, proof), Ok(()));
+    }
+}
+
+#[test]
+fn tampered_leaf_fails_verification() {
+    let mut harness = OracleTestHarness::new();
+    let leaves = snapshot_leaves();
+    let snapshot = harness.seed_position_snapshot(merkle_root(&leaves), leaves.len() as u64, 100);
+
+    let proof = merkle_proof(&leaves, 2).unwrap();
+    let inflated = PositionLeaf { health_factor: 10 * HEALTH_FACTOR_ONE, ..leaves[2] };
+    assert_oracle_error(verify_position(&mut harness, snapshot, inflated, proof), OracleError::InvalidPositionProof);
+}
+
+#[test]
+fn commit_advances_snapshot() {
+    let mut harness = OracleTestHarness::new();
+    harness.svm.warp_to_slot(200);
+    let snapshot = harness.seed_position_snapshot([0; 32], 0, 100);
+
+    assert_eq!(commit_snapshot(&mut harness, snapshot, 150), Ok(()));
+
+    let account = harness.svm.get_account(&snapshot).unwrap();
+    let snapshot =
+        <PositionSnapshot as anchor_lang::AccountDeserialize>::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(snapshot.root, [1; 32]);
+    assert_eq!(snapshot.source_slot, 150);
+    assert_eq!(snapshot.sequence, 2);
+}
+
+#[test]
+fn commit_rejects_older_or_future_slot() {
+    let mut harness = OracleTestHarness::new();
+    harness.svm.warp_to_slot(200);
+    let snapshot = harness.seed_position_snapshot([0; 32], 0, 100);
+
+    assert_oracle_error(commit_snapshot(&mut harness, snapshot, 100), OracleError::StaleSnapshot);
+    assert_oracle_error(commit_snapshot(&mut harness, snapshot, 201), OracleError::StaleSnapshot);
+}
+
+// ============================================================================
+// ORACLE CONFIG
+// ============================================================================
+
+const CONFIG_TIMELOCK_SECS: i64 = 24 * 60 * 60;
+
+/// SOL at $150 with a 150 bps confidence band: over the config's global
+/// 100 bps limit
+fn wide_sol_price(harness: &mut OracleTestHarness) -> Pubkey {
+    harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8).with_confidence_bps(150))
+}
+
+fn sol_override(max_confidence_bps: u64) -> FeedOverride {
+    FeedOverride { feed_id: sol_feed(), max_age_secs: 60, max_confidence_bps }
+}
+
+fn get_price_with_config(
+    harness: &mut OracleTestHarness,
+    price_update: Pubkey,
+    oracle_config: Pubkey,
+    feed_id: [u8; 32],
+) -> Result<(), TransactionError> {
+    harness.send(
+        pyth_oracle::accounts::ConfiguredPriceContext { price_update, oracle_config },
+        pyth_oracle::instruction::GetPriceWithConfig { feed_id },
+    )
+}
+
+fn update_config_accounts(harness: &OracleTestHarness, oracle_config: Pubkey) -> pyth_oracle::accounts::UpdateOracleConfig {
+    pyth_oracle::accounts::UpdateOracleConfig { authority: harness.payer.pubkey(), oracle_config }
+}
+
+fn load_oracle_config(harness: &OracleTestHarness, address: Pubkey) -> OracleConfig {
+    let account = harness.svm.get_account(&address).unwrap();
+    <OracleConfig as anchor_lang::AccountDeserialize>::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+#[test]
+fn feed_override_replaces_global_limits() {
+    let mut harness = OracleTestHarness::new();
+    let authority = harness.payer.pubkey();
+    let price = wide_sol_price(&mut harness);
+
+    let config = harness.seed_oracle_config(authority, Pubkey::default(), vec![]);
+    assert_oracle_error(get_price_with_config(&mut harness, price, config, sol_feed()), OracleError::ConfidenceTooHigh);
+
+    harness.seed_oracle_config(authority, Pubkey::default(), vec![sol_override(200)]);
+    assert_eq!(get_price_with_config(&mut harness, price, config, sol_feed()), Ok(()));
+}
+
+#[test]
+fn configured_price_checks_feed_id() {
+    let mut harness = OracleTestHarness::new();
+    let config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+    let price = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+
+    assert_oracle_error(get_price_with_config(&mut harness, price, config, sol_feed()), OracleError::FeedIdMismatch);
+}
+
+fn propose_config(harness: &mut OracleTestHarness, oracle_config: Pubkey, change: ConfigChange) -> Result<(), TransactionError> {
+    let accounts = update_config_accounts(harness, oracle_config);
+    harness.send(accounts, pyth_oracle::instruction::ProposeOracleConfig { change })
+}
+
+fn apply_config(harness: &mut OracleTestHarness, oracle_config: Pubkey) -> Result<(), TransactionError> {
+    harness.send(
+        pyth_oracle::accounts::ApplyOracleConfig { oracle_config },
+        pyth_oracle::instruction::ApplyOracleConfig {},
+    )
+}
+
+/// The seeded config with `sol_override(200)` added
+fn with_sol_override(harness: &OracleTestHarness, oracle_config: Pubkey) -> ConfigChange {
+    let mut change = load_oracle_config(harness, oracle_config).current();
+    change.set_override(sol_override(200)).unwrap();
+    change
+}
+
+#[test]
+fn proposed_change_applies_after_timelock() {
+    let mut harness = OracleTestHarness::new();
+    let config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+    let price = wide_sol_price(&mut harness);
+    let proposed_at = DEFAULT_TEST_TIMESTAMP;
+
+    let change = with_sol_override(&harness, config);
+    assert_eq!(propose_config(&mut harness, config, change), Ok(()));
+    assert_eq!(load_oracle_config(&harness, config).pending_change.unwrap().effective_at, proposed_at + CONFIG_TIMELOCK_SECS);
+
+    // Still the old limits while the change waits
+    assert_oracle_error(get_price_with_config(&mut harness, price, config, sol_feed()), OracleError::ConfidenceTooHigh);
+    harness.set_unix_timestamp(proposed_at + CONFIG_TIMELOCK_SECS - 1);
+    assert_oracle_error(apply_config(&mut harness, config), OracleError::ConfigChangeNotReady);
+
+    harness.set_unix_timestamp(proposed_at + CONFIG_TIMELOCK_SECS);
+    assert_eq!(apply_config(&mut harness, config), Ok(()));
+    let applied = load_oracle_config(&harness, config);
+    assert_eq!(applied.overrides, vec![sol_override(200)]);
+    assert!(applied.pending_change.is_none());
+
+    let price = wide_sol_price(&mut harness);
+    assert_eq!(get_price_with_config(&mut harness, price, config, sol_feed()), Ok(()));
+    assert_oracle_error(apply_config(&mut harness, config), OracleError::NoPendingConfigChange);
+}
+
+#[test]
+fn shorter_timelock_waits_out_the_current_one() {
+    let mut harness = OracleTestHarness::new();
+    let config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+    harness.set_unix_timestamp(DEFAULT_TEST_TIMESTAMP);
+
+    let mut change = load_oracle_config(&harness, config).current();
+    change.timelock_secs = MIN_CONFIG_TIMELOCK_SECS;
+    assert_eq!(propose_config(&mut harness, config, change), Ok(()));
+
+    harness.set_unix_timestamp(DEFAULT_TEST_TIMESTAMP + MIN_CONFIG_TIMELOCK_SECS);
+    assert_oracle_error(apply_config(&mut harness, config), OracleError::ConfigChangeNotReady);
+}
+
+#[test]
+fn one_change_pending_at_a_time() {
+    let mut harness = OracleTestHarness::new();
+    let config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+    harness.set_unix_timestamp(DEFAULT_TEST_TIMESTAMP);
+
+    let change = with_sol_override(&harness, config);
+    assert_eq!(propose_config(&mut harness, config, change.clone()), Ok(()));
+    assert_oracle_error(propose_config(&mut harness, config, change.clone()), OracleError::ConfigChangePending);
+
+    // Cancelling frees the slot and leaves the config as it was
+    let accounts = update_config_accounts(&harness, config);
+    assert_eq!(harness.send(accounts, pyth_oracle::instruction::CancelOracleConfig {}), Ok(()));
+    let cancelled = load_oracle_config(&harness, config);
+    assert!(cancelled.pending_change.is_none());
+    assert!(cancelled.overrides.is_empty());
+
+    let accounts = update_config_accounts(&harness, config);
+    let result = harness.send(accounts, pyth_oracle::instruction::CancelOracleConfig {});
+    assert_oracle_error(result, OracleError::NoPendingConfigChange);
+    assert_eq!(propose_config(&mut harness, config, change), Ok(()));
+}
+
+#[test]
+fn overrides_are_capped() {
+    let mut harness = OracleTestHarness::new();
+    let config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+    harness.set_unix_timestamp(DEFAULT_TEST_TIMESTAMP);
+
+    let mut change = load_oracle_config(&harness, config).current();
+    change.overrides = (0..=MAX_FEED_OVERRIDES as u8)
+        .map(|i| FeedOverride { feed_id: [i; 32], max_age_secs: 60, max_confidence_bps: 100 })
+        .collect();
+    assert_oracle_error(propose_config(&mut harness, config, change.clone()), OracleError::TooManyFeedOverrides);
+
+    change.overrides.pop();
+    assert_eq!(propose_config(&mut harness, config, change), Ok(()));
+}
+
+#[test]
+fn propose_rejects_invalid_changes_and_other_signers() {
+    let mut harness = OracleTestHarness::new();
+    let config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+    harness.set_unix_timestamp(DEFAULT_TEST_TIMESTAMP);
+    let current = load_oracle_config(&harness, config).current();
+
+    let mut zero_age = current.clone();
+    zero_age.params.max_age_secs = 0;
+    assert_oracle_error(propose_config(&mut harness, config, zero_age), OracleError::InvalidOracleConfig);
+
+    let mut instant = current.clone();
+    instant.timelock_secs = 0;
+    assert_oracle_error(propose_config(&mut harness, config, instant), OracleError::InvalidOracleConfig);
+
+    let mut duplicated = current.clone();
+    duplicated.overrides = vec![sol_override(200), sol_override(300)];
+    assert_oracle_error(propose_config(&mut harness, config, duplicated), OracleError::InvalidOracleConfig);
+
+    harness.seed_oracle_config(Pubkey::new_unique(), Pubkey::default(), vec![]);
+    assert_eq!(
+        propose_config(&mut harness, config, current),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(anchor_lang::error::ErrorCode::ConstraintHasOne as u32)
+        ))
+    );
+}
+
+#[test]
+fn authority_transfer_takes_effect_on_accept() {
+    let mut harness = OracleTestHarness::new();
+    let previous = Pubkey::new_unique();
+    let payer = harness.payer.pubkey();
+
+    // The payer is nominated, not yet the authority
+    let config = harness.seed_oracle_config(previous, payer, vec![]);
+    let accept = pyth_oracle::accounts::AcceptOracleConfigAuthority { nominated_authority: payer, oracle_config: config };
+    assert_eq!(harness.send(accept, pyth_oracle::instruction::AcceptOracleConfigAuthority {}), Ok(()));
+
+    let config = load_oracle_config(&harness, config);
+    assert_eq!(config.authority, payer);
+    assert_eq!(config.nominated_authority, Pubkey::default());
+}
+
+#[test]
+fn only_nominated_authority_can_accept() {
+    let mut harness = OracleTestHarness::new();
+    let payer = harness.payer.pubkey();
+    let config = harness.seed_oracle_config(payer, Pubkey::new_unique(), vec![]);
+
+    let accept = pyth_oracle::accounts::AcceptOracleConfigAuthority { nominated_authority: payer, oracle_config: config };
+    let result = harness.send(accept, pyth_oracle::instruction::AcceptOracleConfigAuthority {});
+    assert_oracle_error(result, OracleError::NotNominatedAuthority);
+
+    // Nominating does not hand over anything by itself
+    let accounts = update_config_accounts(&harness, config);
+    let nominated = Pubkey::new_unique();
+    let result = harness.send(accounts, pyth_oracle::instruction::NominateOracleConfigAuthority { new_authority: nominated });
+    assert_eq!(result, Ok(()));
+    let config = load_oracle_config(&harness, config);
+    assert_eq!(config.authority, payer);
+    assert_eq!(config.nominated_authority, nominated);
+}
+
+// ============================================================================
+// FEED BINDINGS
+// ============================================================================
+
+fn init_feed_binding(harness: &mut OracleTestHarness, oracle_config: Pubkey, mint: Pubkey) -> (Pubkey, Result<(), TransactionError>) {
+    let (feed_binding, _) = Pubkey::find_program_address(&[FEED_BINDING_SEED, mint.as_ref()], &pyth_oracle::ID);
+    let result = harness.send(
+        pyth_oracle::accounts::InitFeedBinding {
+            authority: harness.payer.pubkey(),
+            oracle_config,
+            feed_binding,
+            system_program: solana_sdk::system_program::ID,
+        },
+        pyth_oracle::instruction::InitFeedBinding { mint, feed_id: sol_feed() },
+    );
+    (feed_binding, result)
+}
+
+fn load_feed_binding(harness: &OracleTestHarness, address: Pubkey) -> FeedBinding {
+    let account = harness.svm.get_account(&address).unwrap();
+    <FeedBinding as anchor_lang::AccountDeserialize>::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+#[test]
+fn config_authority_binds_and_rebinds_a_mint() {
+    let mut harness = OracleTestHarness::new();
+    let oracle_config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+
+    let (feed_binding, result) = init_feed_binding(&mut harness, oracle_config, SOL_MINT);
+    assert_eq!(result, Ok(()));
+    assert_eq!(load_feed_binding(&harness, feed_binding).feed_id, sol_feed());
+
+    let result = harness.send(
+        pyth_oracle::accounts::UpdateFeedBinding { authority: harness.payer.pubkey(), oracle_config, feed_binding },
+        pyth_oracle::instruction::UpdateFeedBinding { feed_id: usdc_feed() },
+    );
+    assert_eq!(result, Ok(()));
+    let binding = load_feed_binding(&harness, feed_binding);
+    assert_eq!(binding.mint, SOL_MINT);
+    assert_eq!(binding.feed_id, usdc_feed());
+}
+
+#[test]
+fn only_config_authority_binds_a_mint() {
+    let mut harness = OracleTestHarness::new();
+    let oracle_config = harness.seed_oracle_config(Pubkey::new_unique(), Pubkey::default(), vec![]);
+
+    let (_, result) = init_feed_binding(&mut harness, oracle_config, SOL_MINT);
+    assert_eq!(
+        result,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(anchor_lang::error::ErrorCode::ConstraintHasOne as u32)
+        ))
+    );
+}
+
+// ============================================================================
+// MARKETS
+// ============================================================================
+
+fn market_address(mint: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[MARKET_SEED, mint.as_ref()], &pyth_oracle::ID).0
+}
+
+fn load_market(harness: &OracleTestHarness, address: Pubkey) -> Market {
+    let account = harness.svm.get_account(&address).unwrap();
+    <Market as anchor_lang::AccountDeserialize>::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+fn init_market(
+    harness: &mut OracleTestHarness,
+    oracle_config: Pubkey,
+    mint: Pubkey,
+    config: MarketConfig,
+) -> Result<(), TransactionError> {
+    harness.send(
+        pyth_oracle::accounts::InitMarket {
+            authority: harness.payer.pubkey(),
+            oracle_config,
+            mint,
+            market: market_address(mint),
+            system_program: solana_sdk::system_program::ID,
+        },
+        pyth_oracle::instruction::InitMarket { config },
+    )
+}
+
+fn update_market_accounts(harness: &OracleTestHarness, oracle_config: Pubkey, market: Pubkey) -> pyth_oracle::accounts::UpdateMarket {
+    pyth_oracle::accounts::UpdateMarket { authority: harness.payer.pubkey(), oracle_config, market }
+}
+
+fn tighten_market(
+    harness: &mut OracleTestHarness,
+    oracle_config: Pubkey,
+    market: Pubkey,
+    config: MarketConfig,
+) -> Result<(), TransactionError> {
+    let accounts = update_market_accounts(harness, oracle_config, market);
+    harness.send(accounts, pyth_oracle::instruction::TightenMarket { config })
+}
+
+fn propose_market_config(
+    harness: &mut OracleTestHarness,
+    oracle_config: Pubkey,
+    market: Pubkey,
+    config: MarketConfig,
+) -> Result<(), TransactionError> {
+    let accounts = update_market_accounts(harness, oracle_config, market);
+    harness.send(accounts, pyth_oracle::instruction::ProposeMarketConfig { config })
+}
+
+fn apply_market_config(harness: &mut OracleTestHarness, market: Pubkey) -> Result<(), TransactionError> {
+    harness.send(
+        pyth_oracle::accounts::ApplyMarketConfig { market },
+        pyth_oracle::instruction::ApplyMarketConfig {},
+    )
+}
+
+/// Propose `config` and apply it once the timelock has passed; seed prices
+/// afterwards, the clock has moved
+fn change_market(harness: &mut OracleTestHarness, oracle_config: Pubkey, market: Pubkey, config: MarketConfig) {
+    assert_eq!(propose_market_config(harness, oracle_config, market, config), Ok(()));
+    let clock: Clock = harness.svm.get_sysvar();
+    harness.set_unix_timestamp(clock.unix_timestamp + CONFIG_TIMELOCK_SECS);
+    assert_eq!(apply_market_config(harness, market), Ok(()));
+}
+
+/// `update_collateral_value` of 2 SOL at `price` with `confidence_bps`
+fn value_sol_collateral(
+    harness: &mut OracleTestHarness,
+    price: i64,
+    confidence_bps: u64,
+) -> (Result<(), TransactionError>, Position) {
+    let collateral_price = harness.seed_price(
+        PriceUpdateBuilder::new(sol_feed()).with_price(price, -8).with_confidence_bps(confidence_bps),
+    );
+    let position = harness.seed_position(2_000_000_000, 0);
+    let result = harness.send(
+        pyth_oracle::accounts::ValueCollateral {
+            owner: harness.payer.pubkey(),
+            collateral_price,
+            position,
+            collateral_market: market_address(SOL_MINT),
+            last_observed_price: None,
+            depeg_guard: None,
+        },
+        pyth_oracle::instruction::UpdateCollateralValue {},
+    );
+    (result, load_position(harness, position))
+}
+
+#[test]
+fn config_authority_creates_and_updates_a_market() {
+    let mut harness = OracleTestHarness::new();
+    let oracle_config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+    let mint = harness.seed_mint(6);
+
+    let mut config = market_config(usdc_feed(), 9_000);
+    assert_eq!(init_market(&mut harness, oracle_config, mint, config), Ok(()));
+    let market = load_market(&harness, market_address(mint));
+    assert_eq!(market.mint, mint);
+    assert_eq!(market.decimals, 6);
+    assert_eq!(market.config(), config);
+    assert_eq!(market.breaker.status, BreakerStatus::Normal);
+    assert_eq!(market.paused, PauseFlags::NONE);
+
+    config.oracle.max_confidence_bps = 50;
+    config.risk.max_ltv_bps -= 1_000;
+    assert_eq!(tighten_market(&mut harness, oracle_config, market_address(mint), config), Ok(()));
+    assert_eq!(load_market(&harness, market_address(mint)).config(), config);
+}
+
+#[test]
+fn loosening_a_market_waits_out_the_timelock() {
+    let mut harness = OracleTestHarness::new();
+    let oracle_config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+    let market = harness.seed_market(SOL_MINT, sol_feed(), 9, 8_000);
+    harness.set_unix_timestamp(DEFAULT_TEST_TIMESTAMP);
+    let current = load_market(&harness, market).config();
+
+    // Each of these hurts someone at once: the next feed, a lower
+    // threshold, a higher fee, a wider band, or a looser pair
+    let mut feed = current;
+    feed.feed_id = usdc_feed();
+    let mut threshold = current;
+    threshold.risk.liquidation_threshold_bps -= 100;
+    let mut fee = current;
+    fee.swap_fee.base_fee_bps += 10;
+    let mut band = current;
+    band.oracle.max_confidence_bps += 100;
+    let mut pair = current;
+    pair.pair.max_skew_secs += 5;
+    for config in [feed, threshold, fee, band, pair] {
+        assert_oracle_error(
+            tighten_market(&mut harness, oracle_config, market, config),
+            OracleError::MarketChangeLoosens,
+        );
+    }
+
+    assert_eq!(propose_market_config(&mut harness, oracle_config, market, threshold), Ok(()));
+    let pending = load_market(&harness, market).pending_change.unwrap();
+    assert_eq!(pending.effective_at, DEFAULT_TEST_TIMESTAMP + CONFIG_TIMELOCK_SECS);
+    assert_oracle_error(
+        propose_market_config(&mut harness, oracle_config, market, fee),
+        OracleError::ConfigChangePending,
+    );
+
+    harness.set_unix_timestamp(DEFAULT_TEST_TIMESTAMP + CONFIG_TIMELOCK_SECS - 1);
+    assert_oracle_error(apply_market_config(&mut harness, market), OracleError::ConfigChangeNotReady);
+    assert_eq!(load_market(&harness, market).config(), current);
+
+    harness.set_unix_timestamp(DEFAULT_TEST_TIMESTAMP + CONFIG_TIMELOCK_SECS);
+    assert_eq!(apply_market_config(&mut harness, market), Ok(()));
+    let applied = load_market(&harness, market);
+    assert_eq!(applied.config(), threshold);
+    assert!(applied.pending_change.is_none());
+    assert_oracle_error(apply_market_config(&mut harness, market), OracleError::NoPendingConfigChange);
+}
+
+#[test]
+fn cancelled_market_change_never_applies() {
+    let mut harness = OracleTestHarness::new();
+    let oracle_config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+    let market = harness.seed_market(SOL_MINT, sol_feed(), 9, 8_000);
+    harness.set_unix_timestamp(DEFAULT_TEST_TIMESTAMP);
+
+    let mut config = load_market(&harness, market).config();
+    config.swap_fee.base_fee_bps = 100;
+    assert_eq!(propose_market_config(&mut harness, oracle_config, market, config), Ok(()));
+    let accounts = update_market_accounts(&harness, oracle_config, market);
+    assert_eq!(harness.send(accounts, pyth_oracle::instruction::CancelMarketConfig {}), Ok(()));
+
+    harness.set_unix_timestamp(DEFAULT_TEST_TIMESTAMP + CONFIG_TIMELOCK_SECS);
+    assert_oracle_error(apply_market_config(&mut harness, market), OracleError::NoPendingConfigChange);
+    assert_eq!(load_market(&harness, market).config().swap_fee, DEFAULT_SWAP_FEE);
+}
+
+#[test]
+fn only_config_authority_creates_a_market() {
+    let mut harness = OracleTestHarness::new();
+    let oracle_config = harness.seed_oracle_config(Pubkey::new_unique(), Pubkey::default(), vec![]);
+    let mint = harness.seed_mint(6);
+
+    let result = init_market(&mut harness, oracle_config, mint, market_config(usdc_feed(), 9_000));
+    assert_eq!(
+        result,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(anchor_lang::error::ErrorCode::ConstraintHasOne as u32)
+        ))
+    );
+}
+
+#[test]
+fn market_config_is_validated() {
+    let mut harness = OracleTestHarness::new();
+    let oracle_config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+    let mint = harness.seed_mint(6);
+
+    let mut config = market_config(usdc_feed(), 9_000);
+    config.swap_fee.base_fee_bps = config.swap_fee.max_fee_bps + 1;
+    assert_oracle_error(init_market(&mut harness, oracle_config, mint, config), OracleError::InvalidFee);
+
+    let mut config = market_config(usdc_feed(), 9_000);
+    config.breaker.window_secs = 0;
+    assert_oracle_error(
+        init_market(&mut harness, oracle_config, mint, config),
+        OracleError::InvalidCircuitBreakerConfig,
+    );
+}
+
+#[test]
+fn output_market_sets_the_swap_fee() {
+    let mut harness = OracleTestHarness::new();
+    let oracle_config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+    let pool = seed_swap_pool(&mut harness);
+
+    let mut config = market_config(usdc_feed(), 9_000);
+    config.swap_fee.base_fee_bps = 100;
+    change_market(&mut harness, oracle_config, market_address(pool.usdc_mint), config);
+    let sol_price = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+    let usdc_price = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+
+    // 150 USDC less 1%
+    assert_eq!(sell_sol(&mut harness, &pool, sol_price, usdc_price, 0), Ok(()));
+    assert_eq!(harness.token_balance(pool.user_usdc), 148_500_000);
+}
+
+#[test]
+fn market_limits_apply_to_their_own_asset() {
+    let mut harness = OracleTestHarness::new();
+    let oracle_config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+    let market = harness.seed_market(SOL_MINT, sol_feed(), 9, 8_000);
+
+    // 150 bps is within the default 200
+    let (result, _) = value_sol_collateral(&mut harness, 150_0000_0000, 150);
+    assert_eq!(result, Ok(()));
+
+    let mut config = market_config(sol_feed(), 8_000);
+    config.oracle.max_confidence_bps = 100;
+    assert_eq!(tighten_market(&mut harness, oracle_config, market, config), Ok(()));
+    let (result, _) = value_sol_collateral(&mut harness, 150_0000_0000, 150);
+    assert_oracle_error(result, OracleError::ConfidenceTooHigh);
+}
+
+#[test]
+fn tripped_market_breaker_skips_valuation_until_reset() {
+    let mut harness = OracleTestHarness::new();
+    let oracle_config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+    let market = harness.seed_market(SOL_MINT, sol_feed(), 9, 8_000);
+
+    // Accepted $100 ten seconds ago; $150 now is a 50% jump
+    let mut state = load_market(&harness, market);
+    state.breaker.last_price = 100_0000_0000;
+    state.breaker.last_exponent = -8;
+    state.breaker.last_publish_time = DEFAULT_TEST_TIMESTAMP - 10;
+    harness.seed_program_account_at(market, &state, Market::LEN);
+
+    let (result, position) = value_sol_collateral(&mut harness, 150_0000_0000, 0);
+    assert_eq!(result, Ok(()));
+    assert_eq!(position.usd_value, 0);
+    let state = load_market(&harness, market);
+    assert_eq!(state.breaker.status, BreakerStatus::Tripped);
+    assert_eq!(state.breaker.trip_count, 1);
+
+    let price_update = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+    let result = harness.send(
+        pyth_oracle::accounts::ResetMarketBreaker {
+            authority: harness.payer.pubkey(),
+            oracle_config,
+            price_update,
+            market,
+        },
+        pyth_oracle::instruction::ResetMarketBreaker { reanchor: true },
+    );
+    assert_eq!(result, Ok(()));
+    assert_eq!(load_market(&harness, market).breaker.last_price, 150_0000_0000);
+
+    let (result, position) = value_sol_collateral(&mut harness, 150_0000_0000, 0);
+    assert_eq!(result, Ok(()));
+    assert_eq!(position.usd_value, 300_000_000);
+}
+
+// ============================================================================
+// GUARDIAN
+// ============================================================================
+
+fn guardian_address() -> Pubkey {
+    Pubkey::find_program_address(&[GUARDIAN_SEED], &pyth_oracle::ID).0
+}
+
+/// The guardian PDA naming `guardian`, as `init_guardian` would create it
+fn seed_guardian(harness: &mut OracleTestHarness, guardian: Pubkey) -> Pubkey {
+    let (address, bump) = Pubkey::find_program_address(&[GUARDIAN_SEED], &pyth_oracle::ID);
+    harness.seed_program_account_at(address, &Guardian { guardian, bump }, Guardian::LEN)
+}
+
+/// `pause_market` signed by the payer
+fn pause_market(
+    harness: &mut OracleTestHarness,
+    oracle_config: Pubkey,
+    market: Pubkey,
+    operations: PauseFlags,
+) -> Result<(), TransactionError> {
+    harness.send(
+        pyth_oracle::accounts::PauseMarket {
+            signer: harness.payer.pubkey(),
+            oracle_config,
+            guardian: guardian_address(),
+            market,
+        },
+        pyth_oracle::instruction::PauseMarket { operations },
+    )
+}
+
+/// `resume_market` signed by the payer
+fn resume_market(
+    harness: &mut OracleTestHarness,
+    oracle_config: Pubkey,
+    market: Pubkey,
+    operations: PauseFlags,
+) -> Result<(), TransactionError> {
+    harness.send(
+        pyth_oracle::accounts::ResumeMarket { authority: harness.payer.pubkey(), oracle_config, market },
+        pyth_oracle::instruction::ResumeMarket { operations },
+    )
+}
+
+#[test]
+fn config_authority_names_the_guardian() {
+    let mut harness = OracleTestHarness::new();
+    let oracle_config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+    let guardian = Pubkey::new_unique();
+
+    let result = harness.send(
+        pyth_oracle::accounts::InitGuardian {
+            authority: harness.payer.pubkey(),
+            oracle_config,
+            guardian: guardian_address(),
+            system_program: solana_sdk::system_program::ID,
+        },
+        pyth_oracle::instruction::InitGuardian { guardian },
+    );
+    assert_eq!(result, Ok(()));
+
+    let replacement = Pubkey::new_unique();
+    let result = harness.send(
+        pyth_oracle::accounts::SetGuardian {
+            authority: harness.payer.pubkey(),
+            oracle_config,
+            guardian: guardian_address(),
+        },
+        pyth_oracle::instruction::SetGuardian { guardian: replacement },
+    );
+    assert_eq!(result, Ok(()));
+    let account = harness.svm.get_account(&guardian_address()).unwrap();
+    let stored = <Guardian as anchor_lang::AccountDeserialize>::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(stored.guardian, replacement);
+}
+
+#[test]
+fn guardian_pauses_swaps_without_touching_deposits() {
+    let mut harness = OracleTestHarness::new();
+    // The payer is the guardian, not the config authority
+    let oracle_config = harness.seed_oracle_config(Pubkey::new_unique(), Pubkey::default(), vec![]);
+    let payer = harness.payer.pubkey();
+    seed_guardian(&mut harness, payer);
+    let pool = seed_swap_pool(&mut harness);
+    let sol_price = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+    let usdc_price = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+
+    let usdc_market = market_address(pool.usdc_mint);
+    assert_eq!(pause_market(&mut harness, oracle_config, usdc_market, PauseFlags::SWAPS), Ok(()));
+    assert_eq!(load_market(&harness, usdc_market).paused, PauseFlags::SWAPS);
+    assert_oracle_error(sell_sol(&mut harness, &pool, sol_price, usdc_price, 0), OracleError::MarketPaused);
+
+    let sol_market = harness.seed_market(SOL_MINT, sol_feed(), 9, 8_000);
+    assert_eq!(pause_market(&mut harness, oracle_config, sol_market, PauseFlags::SWAPS), Ok(()));
+    let (result, position) = value_sol_collateral(&mut harness, 150_0000_0000, 0);
+    assert_eq!(result, Ok(()));
+    assert_eq!(position.usd_value, 300_000_000);
+}
+
+#[test]
+fn paused_deposits_leave_swaps_running() {
+    let mut harness = OracleTestHarness::new();
+    let oracle_config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+    seed_guardian(&mut harness, Pubkey::new_unique());
+    let pool = seed_swap_pool(&mut harness);
+    let sol_price = harness.seed_price(PriceUpdateBuilder::new(sol_feed()).with_price(150_0000_0000, -8));
+    let usdc_price = harness.seed_price(PriceUpdateBuilder::new(usdc_feed()).with_price(1_0000_0000, -8));
+
+    // The config authority may pause as well
+    let collateral_market = harness.seed_market(SOL_MINT, sol_feed(), 9, 8_000);
+    assert_eq!(pause_market(&mut harness, oracle_config, collateral_market, PauseFlags::DEPOSITS), Ok(()));
+    let (result, _) = value_sol_collateral(&mut harness, 150_0000_0000, 0);
+    assert_oracle_error(result, OracleError::MarketPaused);
+
+    let swap_market = market_address(pool.sol_mint);
+    assert_eq!(pause_market(&mut harness, oracle_config, swap_market, PauseFlags::DEPOSITS), Ok(()));
+    assert_eq!(sell_sol(&mut harness, &pool, sol_price, usdc_price, 0), Ok(()));
+}
+
+#[test]
+fn only_config_authority_resumes_and_only_what_it_names() {
+    let mut harness = OracleTestHarness::new();
+    let oracle_config = harness.seed_oracle_config(harness.payer.pubkey(), Pubkey::default(), vec![]);
+    seed_guardian(&mut harness, Pubkey::new_unique());
+    let market = harness.seed_market(SOL_MINT, sol_feed(), 9, 8_000);
+
+    assert_eq!(pause_market(&mut harness, oracle_config, market, PauseFlags::ALL), Ok(()));
+    assert_eq!(resume_market(&mut harness, oracle_config, market, PauseFlags::DEPOSITS), Ok(()));
+    let paused = load_market(&harness, market).paused;
+    assert_eq!(paused, PauseFlags::ALL.difference(PauseFlags::DEPOSITS));
+    let (result, _) = value_sol_collateral(&mut harness, 150_0000_0000, 0);
+    assert_eq!(result, Ok(()));
+
+    // Hand the config to another authority: the payer, now only the
+    // guardian, cannot resume
+    let oracle_config = harness.seed_oracle_config(Pubkey::new_unique(), Pubkey::default(), vec![]);
+    let payer = harness.payer.pubkey();
+    seed_guardian(&mut harness, payer);
+    assert_eq!(
+        resume_market(&mut harness, oracle_config, market, PauseFlags::SWAPS),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(anchor_lang::error::ErrorCode::ConstraintHasOne as u32)
+        ))
+    );
+    assert_eq!(load_market(&harness, market).paused, paused);
+}
+
+#[test]
+fn pausing_requires_the_guardian_and_known_flags() {
+    let mut harness = OracleTestHarness::new();
+    let oracle_config = harness.seed_oracle_config(Pubkey::new_unique(), Pubkey::default(), vec![]);
+    let market = harness.seed_market(SOL_MINT, sol_feed(), 9, 8_000);
+
+    seed_guardian(&mut harness, Pubkey::new_unique());
+    assert_oracle_error(
+        pause_market(&mut harness, oracle_config, market, PauseFlags::SWAPS),
+        OracleError::NotGuardian,
+    );
+
+    let payer = harness.payer.pubkey();
+    seed_guardian(&mut harness, payer);
+    assert_oracle_error(
+        pause_market(&mut harness, oracle_config, market, PauseFlags::NONE),
+        OracleError::InvalidPauseFlags,
+    );
+    assert_oracle_error(
+        pause_market(&mut harness, oracle_config, market, PauseFlags(1 << 4)),
+        OracleError::InvalidPauseFlags,
+    );
+    assert_eq!(load_market(&harness, market).paused, PauseFlags::NONE);
+}
@@ -0,0 +1,250 @@
+/**
+ * Localnet Mock Feeds with Fault Injection
+ *
+ * `PriceUpdateBuilder::to_account_json` seeds a test validator with price
+ * accounts at genesis, but those never change: every price is frozen from
+ * the first slot, and a degraded feed cannot be switched on halfway
+ * through a test. This tool keeps mock feeds alive on a Surfpool localnet
+ * and injects faults into single feeds while it runs:
+ *
+ *   serve              refresh every feed each second at its sponsored
+ *                      push feed address (shard 0), as the Pyth push
+ *                      oracle does on mainnet
+ *   freeze <FEED>      stop refreshing the feed; it goes stale
+ *   skew <FEED> <BPS>  move the price by BPS (negative moves it down)
+ *   widen <FEED> <BPS> set the confidence to BPS of the price
+ *   restore <FEED|all> clear the feed's faults
+ *   status             print every feed and its faults
+ *
+ * FEED is a registry symbol (`SOL/USD`) or a 0x-prefixed feed ID. Consumer
+ * programs and the agent read the same addresses as on mainnet, so they
+ * run unchanged against degraded feeds.
+ *
+ * Accounts are written with Surfpool's `surfnet_setAccount` cheatcode (see
+ * the surfpool skill); solana-test-validator cannot change accounts after
+ * genesis. The fault commands only edit the state file, which `serve`
+ * re-reads on every refresh, so they take effect within a second.
+ *
+ * Add to Cargo.toml:
+ * [dependencies]
+ * base64 = "0.21"
+ * pyth-oracle = { path = "../..", features = ["no-entrypoint", "test-utils"] }
+ * serde = { version = "1", features = ["derive"] }
+ * serde_json = "1"
+ * solana-client = "1.18"
+ * solana-sdk = "1.18"
+ *
+ * Run:
+ * surfpool start
+ * cargo run -- serve SOL/USD=150 USDC/USD=1 &
+ * cargo run -- widen SOL/USD 300     # SOL confidence now 3%
+ * cargo run -- freeze USDC/USD       # USDC stale after the max age
+ * cargo run -- restore all
+ *
+ * LOCALNET_RPC selects the RPC (default http://127.0.0.1:8899) and
+ * LOCALNET_FEEDS the state file (default localnet-feeds.json).
+ */
+
+use base64::Engine;
+use pyth_oracle::test_utils::PriceUpdateBuilder;
+use pyth_oracle::{feed_id_for, parse_feed_id, symbol_for};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::clock::Clock;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+// Pyth push oracle program; owns the sponsored price feed accounts
+const PUSH_ORACLE_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("pythWSnswVUd12oZpeFP8e9CVaEqJg25g1Vtc2biRsT");
+
+// Shard of the sponsored feeds
+const PUSH_FEED_SHARD: u16 = 0;
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+// Exponent of every mock feed
+const EXPONENT: i32 = -8;
+
+// Confidence of a healthy mock feed
+const HEALTHY_CONF_BPS: u64 = 5;
+
+// ============================================================================
+// Feed State
+// ============================================================================
+
+/// One mock feed and its injected faults
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MockFeed {
+    /// Healthy price, with `EXPONENT`
+    pub price: i64,
+    pub frozen: bool,
+    pub skew_bps: i64,
+    /// Confidence override; `HEALTHY_CONF_BPS` when unset
+    pub conf_bps: Option<u64>,
+}
+
+impl MockFeed {
+    pub fn healthy(price: i64) -> Self {
+        Self { price, frozen: false, skew_bps: 0, conf_bps: None }
+    }
+
+    pub fn restore(&mut self) {
+        *self = Self::healthy(self.price);
+    }
+
+    /// Price update published at `clock`
+    pub fn update(&self, feed_id: [u8; 32], clock: &Clock) -> PriceUpdateBuilder {
+        let price = self.price as i128 * (10_000 + self.skew_bps as i128) / 10_000;
+        PriceUpdateBuilder::new(feed_id)
+            .with_price(price as i64, EXPONENT)
+            .with_confidence_bps(self.conf_bps.unwrap_or(HEALTHY_CONF_BPS))
+            .at_time(clock.unix_timestamp)
+            .posted_at_slot(clock.slot)
+    }
+}
+
+/// Feeds by 0x-prefixed feed ID
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct FeedState {
+    pub feeds: BTreeMap<String, MockFeed>,
+}
+
+fn state_path() -> String {
+    std::env::var("LOCALNET_FEEDS").unwrap_or_else(|_| "localnet-feeds.json".to_string())
+}
+
+fn load_state() -> Result<FeedState, Box<dyn std::error::Error>> {
+    match std::fs::read_to_string(state_path()) {
+        Ok(json) => Ok(serde_json::from_str(&json)?),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(FeedState::default()),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Write through a temporary file so `serve` never reads a partial state
+fn save_state(state: &FeedState) -> Result<(), Box<dyn std::error::Error>> {
+    let path = state_path();
+    let tmp = format!("{path}.tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(state)?)?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+/// `SOL/USD` or `0x...` to the feed ID and its state key
+fn resolve_feed(name: &str) -> Result<([u8; 32], String), Box<dyn std::error::Error>> {
+    let feed_id = match feed_id_for(name) {
+        Some(feed_id) => feed_id,
+        None => parse_feed_id(name).map_err(|_| format!("unknown feed {name}"))?,
+    };
+    Ok((feed_id, format!("0x{}", feed_id_hex(&feed_id))))
+}
+
+fn feed_id_hex(feed_id: &[u8; 32]) -> String {
+    feed_id.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Sponsored push feed account of a feed ID
+fn push_feed_account(feed_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[&PUSH_FEED_SHARD.to_le_bytes(), feed_id], &PUSH_ORACLE_PROGRAM_ID).0
+}
+
+// ============================================================================
+// Localnet
+// ============================================================================
+
+fn localnet_clock(rpc: &RpcClient) -> Result<Clock, Box<dyn std::error::Error>> {
+    let account = rpc.get_account(&solana_sdk::sysvar::clock::ID)?;
+    solana_sdk::account::from_account(&account).ok_or_else(|| "cannot decode the clock sysvar".into())
+}
+
+/// Overwrite an account through Surfpool's `surfnet_setAccount`
+fn set_account(rpc: &RpcClient, address: &Pubkey, update: &PriceUpdateBuilder) -> Result<(), Box<dyn std::error::Error>> {
+    let account = update.to_account();
+    let params = serde_json::json!([{
+        "pubkey": address.to_string(),
+        "lamports": account.lamports,
+        "data": base64::engine::general_purpose::STANDARD.encode(&account.data),
+        "owner": account.owner.to_string(),
+        "executable": false,
+    }]);
+    rpc.send::<serde_json::Value>(RpcRequest::Custom { method: "surfnet_setAccount" }, params)?;
+    Ok(())
+}
+
+fn serve(rpc: &RpcClient, seeds: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut state = load_state()?;
+    for seed in seeds {
+        let (name, price) = seed.split_once('=').ok_or("feeds are SYMBOL=PRICE")?;
+        let (_, key) = resolve_feed(name)?;
+        let price: f64 = price.parse()?;
+        let price = (price * 10f64.powi(-EXPONENT)).round() as i64;
+        state.feeds.insert(key, MockFeed::healthy(price));
+    }
+    save_state(&state)?;
+
+    loop {
+        let state = load_state()?;
+        let clock = localnet_clock(rpc)?;
+        for (key, feed) in &state.feeds {
+            if feed.frozen {
+                continue;
+            }
+            let (feed_id, _) = resolve_feed(key)?;
+            set_account(rpc, &push_feed_account(&feed_id), &feed.update(feed_id, &clock))?;
+        }
+        std::thread::sleep(REFRESH_INTERVAL);
+    }
+}
+
+fn print_status(state: &FeedState) -> Result<(), Box<dyn std::error::Error>> {
+    for (key, feed) in &state.feeds {
+        let (feed_id, _) = resolve_feed(key)?;
+        println!(
+            "{:<10} {} price {} × 10^{}{}{}{}",
+            symbol_for(&feed_id).unwrap_or("?"),
+            push_feed_account(&feed_id),
+            feed.price,
+            EXPONENT,
+            if feed.frozen { ", FROZEN" } else { "" },
+            if feed.skew_bps != 0 { format!(", skewed {} bps", feed.skew_bps) } else { String::new() },
+            feed.conf_bps.map(|bps| format!(", confidence {bps} bps")).unwrap_or_default(),
+        );
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let usage = "usage: localnet-feeds serve SYMBOL=PRICE... | freeze FEED | skew FEED BPS | widen FEED BPS \
+                 | restore FEED|all | status";
+    let command = args.first().ok_or(usage)?;
+
+    if command == "serve" {
+        let rpc_url = std::env::var("LOCALNET_RPC").unwrap_or_else(|_| "http://127.0.0.1:8899".to_string());
+        return serve(&RpcClient::new(rpc_url), &args[1..]);
+    }
+
+    let mut state = load_state()?;
+    if command == "status" {
+        return print_status(&state);
+    }
+    let target = args.get(1).ok_or(usage)?;
+    if command == "restore" && target == "all" {
+        state.feeds.values_mut().for_each(MockFeed::restore);
+        return save_state(&state);
+    }
+
+    let (_, key) = resolve_feed(target)?;
+    let feed = state.feeds.get_mut(&key).ok_or_else(|| format!("{target} is not served"))?;
+    match (command.as_str(), args.get(2)) {
+        ("freeze", None) => feed.frozen = true,
+        ("skew", Some(bps)) => feed.skew_bps = bps.parse()?,
+        ("widen", Some(bps)) => feed.conf_bps = Some(bps.parse()?),
+        ("restore", None) => feed.restore(),
+        _ => return Err(usage.into()),
+    }
+    save_state(&state)
+}
@@ -0,0 +1,669 @@
+/**
+ * Oracle Downtime Drill
+ *
+ * Replays a price stream against the `oracle_example` program under
+ * LiteSVM with faults injected, and checks that each guard fires where it
+ * is designed to, and only there:
+ *
+ *   Gap                 nothing is published for a while: staleness
+ *                       rejects once the last update is past the max age,
+ *                       and the fallback policy serves the last good price
+ *                       with a haircut per started minute
+ *   StaleRepeat         the last price is re-published with fresh publish
+ *                       times: staleness cannot see it, by design; the
+ *                       circuit breaker catches the jump when it thaws
+ *   ConfidenceBlowout   confidence widens past the limit: rejected, with
+ *                       no fallback
+ *   Rollback            an older update is replayed: the publish-time
+ *                       guard rejects it
+ *   Spike               one update moves the price: the circuit breaker
+ *                       trips and stays tripped
+ *
+ * A reader arrives every `TICK_SECS` and reads the newest update published
+ * by then with `get_price`, `check_circuit_breaker`,
+ * `update_collateral_value` behind the publish-time guard, and
+ * `get_fallback_price`. Each `Reading` keeps every result, and
+ * `expected_price` says what staleness and confidence validation should
+ * have made of the update.
+ *
+ * The stream is synthetic unless ORACLE_DRILL_STREAM names a recording of
+ * `automaton feed-analytics record` (JSON lines): the drill then replays
+ * the first `DRILL_SECS` of its SOL/USD updates. Faults are placed in
+ * seconds from the start of the stream, so one drill runs on either. The
+ * frozen-price drill needs the synthetic stream's steady rise to trip the
+ * breaker when the feed thaws; the others hold for any stream.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml of the program crate the dev-dependencies of
+ *    litesvm-oracle-example.rs, plus:
+ *    serde = { version = "1", features = ["derive"] }
+ *    serde_json = "1"
+ *
+ * 2. Copy this file to `tests/oracle_drill.rs`
+ * 3. Build the program, then run the drills:
+ *    anchor build && cargo test --test oracle_drill
+ *    ORACLE_DRILL_STREAM=prices.jsonl cargo test --test oracle_drill
+ *    (set ORACLE_EXAMPLE_SO to load the .so from another path)
+ */
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use litesvm::LiteSVM;
+use pyth_oracle::oracle::PriceSourceError;
+use pyth_oracle::test_utils::{PriceUpdateBuilder, DEFAULT_TEST_TIMESTAMP};
+use pyth_oracle::{
+    confidence_within, parse_feed_id, price_feeds, staleness_haircut_bps, BreakerConfig, BreakerState, BreakerStatus,
+    CircuitBreaker, FallbackPolicy, LastGoodPrice, LastObservedPrice, Market, OracleConfigParams, OracleError,
+    PairLimits, PauseFlags, Position, PriceValidationConfig, RiskParamsConfig, TripAction, CIRCUIT_BREAKER_SEED,
+    DEFAULT_MAX_PRICE_AGE, DEFAULT_SWAP_FEE, LAST_GOOD_PRICE_SEED, MARKET_SEED, MAX_CONFIDENCE_BPS,
+};
+use serde::Deserialize;
+use solana_sdk::account::Account;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+const DEFAULT_PROGRAM_PATH: &str = "target/deploy/pyth_oracle.so";
+
+/// Seconds of stream each drill replays
+const DRILL_SECS: i64 = 900;
+
+/// A reader arrives this often
+const TICK_SECS: i64 = 5;
+
+/// Trips on a 5% move within a minute and halts the feed
+const BREAKER: BreakerConfig = BreakerConfig {
+    max_deviation_bps: 500,
+    window_secs: 60,
+    on_trip: TripAction::Reject,
+};
+
+/// The collateral market's own breaker never trips on the drills' moves,
+/// so `check_circuit_breaker` is the only one that looks at them
+const MARKET_BREAKER: BreakerConfig = BreakerConfig {
+    max_deviation_bps: 9_999,
+    window_secs: 60,
+    on_trip: TripAction::Reject,
+};
+
+const FALLBACK_BPS_PER_MINUTE: u64 = 100;
+const FALLBACK: FallbackPolicy = FallbackPolicy::LastGoodWithHaircut {
+    bps_per_minute: FALLBACK_BPS_PER_MINUTE,
+};
+
+/// The publish-time guard accepts an older price after this long without one
+const ROLLBACK_GAP_SECS: i64 = 300;
+
+const SOL_MINT: Pubkey = solana_sdk::pubkey!("So11111111111111111111111111111111111111112");
+
+fn sol_feed() -> [u8; 32] {
+    parse_feed_id(price_feeds::SOL_USD).unwrap()
+}
+
+// ============================================================================
+// STREAMS
+// ============================================================================
+
+/// One update, as `feed-analytics record` writes it
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+struct Sample {
+    feed_id: [u8; 32],
+    publish_time: i64,
+    price: i64,
+    conf: u64,
+    exponent: i32,
+}
+
+/// SOL/USD published every second, rising $0.05 a second from $150, with
+/// confidence of 5 bps
+fn synthetic_stream() -> Vec<Sample> {
+    (0..=DRILL_SECS)
+        .map(|secs| {
+            let price = 150_0000_0000 + secs * 500_0000;
+            Sample {
+                feed_id: sol_feed(),
+                publish_time: DEFAULT_TEST_TIMESTAMP + secs,
+                price,
+                conf: price as u64 / 2_000,
+                exponent: -8,
+            }
+        })
+        .collect()
+}
+
+/// The first `DRILL_SECS` of SOL/USD updates in a recording, one per
+/// publish time
+fn recorded_stream(path: &str) -> Vec<Sample> {
+    let recording = std::fs::read_to_string(path).expect("read the recording");
+    let mut samples: Vec<Sample> = recording
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("a feed-analytics recording"))
+        .filter(|sample: &Sample| sample.feed_id == sol_feed())
+        .collect();
+    samples.sort_by_key(|sample| sample.publish_time);
+    samples.dedup_by_key(|sample| sample.publish_time);
+
+    let start = samples.first().expect("SOL/USD updates in the recording").publish_time;
+    samples.retain(|sample| sample.publish_time <= start + DRILL_SECS);
+    samples
+}
+
+fn stream() -> Vec<Sample> {
+    match std::env::var("ORACLE_DRILL_STREAM") {
+        Ok(path) => recorded_stream(&path),
+        Err(_) => synthetic_stream(),
+    }
+}
+
+// ============================================================================
+// FAULTS
+// ============================================================================
+
+/// A fault, placed in seconds from the start of the stream
+#[derive(Clone, Copy, Debug)]
+enum Fault {
+    /// Nothing is published for `secs`
+    Gap { from: i64, secs: i64 },
+    /// The last price before `from` is re-published for `secs`, each time
+    /// with a fresh publish time
+    StaleRepeat { from: i64, secs: i64 },
+    /// Confidence is `conf_bps` of the price for `secs`
+    ConfidenceBlowout { from: i64, secs: i64, conf_bps: u64 },
+    /// The reader at `at` gets the update it would have got `by_secs`
+    /// earlier
+    Rollback { at: i64, by_secs: i64 },
+    /// The reader at `at` gets the price moved by `bps`
+    Spike { at: i64, bps: i64 },
+}
+
+/// The update a reader gets at `now`
+#[derive(Clone, Copy, Debug)]
+struct Delivery {
+    now: i64,
+    sample: Sample,
+}
+
+/// A stream and the faults to inject into it
+struct Drill {
+    samples: Vec<Sample>,
+    faults: Vec<Fault>,
+}
+
+impl Drill {
+    fn new(samples: Vec<Sample>) -> Self {
+        assert!(!samples.is_empty(), "nothing to replay");
+        Self { samples, faults: Vec::new() }
+    }
+
+    fn inject(mut self, fault: Fault) -> Self {
+        self.faults.push(fault);
+        self
+    }
+
+    fn start(&self) -> i64 {
+        self.samples[0].publish_time
+    }
+
+    /// The stream as published: the faults that change what the feed
+    /// publishes applied, in order
+    fn published(&self) -> Vec<Sample> {
+        let start = self.start();
+        let within =
+            |sample: &Sample, from: i64, secs: i64| (start + from..start + from + secs).contains(&sample.publish_time);
+
+        let mut samples = self.samples.clone();
+        for fault in &self.faults {
+            match *fault {
+                Fault::Gap { from, secs } => samples.retain(|sample| !within(sample, from, secs)),
+                Fault::StaleRepeat { from, secs } => {
+                    let before = samples.iter().rev().find(|sample| sample.publish_time < start + from);
+                    let Some(last) = before.copied() else { continue };
+                    for sample in samples.iter_mut().filter(|sample| within(sample, from, secs)) {
+                        *sample = Sample { publish_time: sample.publish_time, ..last };
+                    }
+                }
+                Fault::ConfidenceBlowout { from, secs, conf_bps } => {
+                    for sample in samples.iter_mut().filter(|sample| within(sample, from, secs)) {
+                        sample.conf = (sample.price.unsigned_abs() as u128 * conf_bps as u128 / 10_000) as u64;
+                    }
+                }
+                Fault::Rollback { .. } | Fault::Spike { .. } => {}
+            }
+        }
+        samples
+    }
+
+    /// What the reader of each tick gets: the newest update published by
+    /// then, unless a fault replaces it
+    fn deliveries(&self) -> Vec<Delivery> {
+        let start = self.start();
+        let published = self.published();
+        let newest = |now: i64| published.iter().rev().find(|sample| sample.publish_time <= now).copied();
+
+        let mut deliveries: Vec<Delivery> = (0..=DRILL_SECS / TICK_SECS)
+            .map(|tick| start + tick * TICK_SECS)
+            .filter_map(|now| Some(Delivery { now, sample: newest(now)? }))
+            .collect();
+        for fault in &self.faults {
+            match *fault {
+                Fault::Rollback { at, by_secs } => {
+                    let delivery = deliveries.iter_mut().find(|delivery| delivery.now == start + at);
+                    if let (Some(delivery), Some(older)) = (delivery, newest(start + at - by_secs)) {
+                        delivery.sample = older;
+                    }
+                }
+                Fault::Spike { at, bps } => {
+                    if let Some(delivery) = deliveries.iter_mut().find(|delivery| delivery.now == start + at) {
+                        delivery.sample.price += delivery.sample.price * bps / 10_000;
+                    }
+                }
+                Fault::Gap { .. } | Fault::StaleRepeat { .. } | Fault::ConfidenceBlowout { .. } => {}
+            }
+        }
+        deliveries
+    }
+
+    /// Replay against a fresh program, one reading per tick
+    fn run(&self) -> Vec<Reading> {
+        let start = self.start();
+        let mut harness = DrillHarness::new();
+        self.deliveries()
+            .into_iter()
+            .map(|delivery| harness.read(start, delivery))
+            .collect()
+    }
+}
+
+// ============================================================================
+// HARNESS
+// ============================================================================
+
+/// What the guards made of one delivered update
+#[derive(Debug)]
+struct Reading {
+    /// Seconds from the start of the stream
+    offset: i64,
+    now: i64,
+    sample: Sample,
+    /// `get_price`: staleness and confidence
+    price: Result<(), TransactionError>,
+    /// Status after `check_circuit_breaker`
+    breaker: BreakerStatus,
+    /// `update_collateral_value` behind the publish-time guard
+    collateral: Result<(), TransactionError>,
+    /// `get_fallback_price`, and the `PriceFreshness` it logged
+    fallback: Result<(), TransactionError>,
+    freshness: Option<String>,
+}
+
+impl Reading {
+    fn age(&self) -> i64 {
+        self.now - self.sample.publish_time
+    }
+}
+
+/// The example program with one of each guard for SOL/USD, all owned by
+/// the payer
+struct DrillHarness {
+    svm: LiteSVM,
+    payer: Keypair,
+    circuit_breaker: Pubkey,
+    last_observed_price: Pubkey,
+    last_good_price: Pubkey,
+    position: Pubkey,
+    collateral_market: Pubkey,
+}
+
+impl DrillHarness {
+    fn new() -> Self {
+        let mut svm = LiteSVM::new();
+        let path = std::env::var("ORACLE_EXAMPLE_SO").unwrap_or(DEFAULT_PROGRAM_PATH.to_string());
+        svm.add_program_from_file(pyth_oracle::ID, &path)
+            .expect("build the program with `anchor build` first");
+        let payer = Keypair::new();
+        svm.airdrop(&payer.pubkey(), 10_000_000_000).unwrap();
+
+        let authority = payer.pubkey();
+        let feed_id = sol_feed();
+        let mut harness = Self {
+            svm,
+            payer,
+            circuit_breaker: Pubkey::default(),
+            last_observed_price: Pubkey::default(),
+            last_good_price: Pubkey::default(),
+            position: Pubkey::default(),
+            collateral_market: Pubkey::default(),
+        };
+
+        let (address, bump) =
+            Pubkey::find_program_address(&[CIRCUIT_BREAKER_SEED, authority.as_ref(), &feed_id], &pyth_oracle::ID);
+        let breaker = CircuitBreaker {
+            authority,
+            feed_id,
+            state: BreakerState::new(BREAKER),
+            bump,
+        };
+        harness.circuit_breaker = harness.seed_program_account(address, &breaker, CircuitBreaker::LEN);
+
+        let last_observed = LastObservedPrice {
+            authority,
+            feed_id,
+            price: 0,
+            exponent: 0,
+            publish_time: 0,
+            accepted_at: 0,
+            max_gap_secs: ROLLBACK_GAP_SECS,
+            bump: 255,
+        };
+        harness.last_observed_price =
+            harness.seed_program_account(Pubkey::new_unique(), &last_observed, LastObservedPrice::LEN);
+
+        let (address, bump) = Pubkey::find_program_address(&[LAST_GOOD_PRICE_SEED, &feed_id], &pyth_oracle::ID);
+        let last_good = LastGoodPrice { feed_id, price: 0, conf: 0, exponent: 0, publish_time: 0, bump };
+        harness.last_good_price = harness.seed_program_account(address, &last_good, LastGoodPrice::LEN);
+
+        let position = Position {
+            owner: authority,
+            collateral_mint: SOL_MINT,
+            collateral_amount: 2_000_000_000,
+            usd_value: 0,
+            debt_mint: Pubkey::new_unique(),
+            debt_amount: 0,
+            debt_index: 0,
+            debt_usd_value: 0,
+            health_factor: u64::MAX,
+            last_price_update: 0,
+            bump: 255,
+        };
+        harness.position = harness.seed_program_account(Pubkey::new_unique(), &position, Position::LEN);
+
+        let (address, bump) = Pubkey::find_program_address(&[MARKET_SEED, SOL_MINT.as_ref()], &pyth_oracle::ID);
+        let market = Market {
+            mint: SOL_MINT,
+            decimals: 9,
+            feed_id,
+            oracle: OracleConfigParams {
+                max_age_secs: DEFAULT_MAX_PRICE_AGE,
+                max_confidence_bps: MAX_CONFIDENCE_BPS,
+                verification_level: PriceValidationConfig::default().verification_level,
+            },
+            pair: PairLimits { max_skew_secs: 10, max_combined_confidence_bps: 300 },
+            risk: RiskParamsConfig {
+                max_ltv_bps: 7_500,
+                liquidation_threshold_bps: 8_000,
+                liquidation_bonus_bps: 500,
+                close_factor_bps: 5_000,
+                borrow_cap: u64::MAX,
+            },
+            swap_fee: DEFAULT_SWAP_FEE,
+            breaker: BreakerState::new(MARKET_BREAKER),
+            paused: PauseFlags::NONE,
+            total_borrowed: 0,
+            has_interest_market: false,
+            pending_change: None,
+            bump,
+        };
+        harness.collateral_market = harness.seed_program_account(address, &market, Market::LEN);
+        harness
+    }
+
+    fn seed_program_account<T: AccountSerialize>(&mut self, address: Pubkey, value: &T, len: usize) -> Pubkey {
+        let mut data = Vec::with_capacity(len);
+        value.try_serialize(&mut data).unwrap();
+        data.resize(len, 0);
+        let lamports = self.svm.minimum_balance_for_rent_exemption(data.len());
+        self.svm
+            .set_account(
+                address,
+                Account {
+                    lamports,
+                    data,
+                    owner: pyth_oracle::ID,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .unwrap();
+        address
+    }
+
+    /// Post `delivery` at its clock time and read it with every guard
+    fn read(&mut self, start: i64, delivery: Delivery) -> Reading {
+        let Sample { feed_id, publish_time, price, conf, exponent } = delivery.sample;
+        let builder = PriceUpdateBuilder::new(feed_id)
+            .with_price(price, exponent)
+            .with_conf(conf)
+            .at_time(delivery.now)
+            .published_at(publish_time);
+        let price_update = Pubkey::new_unique();
+        let mut clock: Clock = self.svm.get_sysvar();
+        clock.unix_timestamp = delivery.now;
+        self.svm.set_sysvar(&clock);
+        self.svm.set_account(price_update, builder.to_account()).unwrap();
+
+        let (price, _) = self.send(
+            pyth_oracle::accounts::SinglePriceContext { price_update },
+            pyth_oracle::instruction::GetPrice {},
+        );
+        // Fails only on a price it cannot validate; a trip is persisted
+        self.send(
+            pyth_oracle::accounts::CheckCircuitBreaker { price_update, circuit_breaker: self.circuit_breaker },
+            pyth_oracle::instruction::CheckCircuitBreaker { config: PriceValidationConfig::default() },
+        );
+        let (collateral, _) = self.send(
+            pyth_oracle::accounts::ValueCollateral {
+                owner: self.payer.pubkey(),
+                collateral_price: price_update,
+                position: self.position,
+                collateral_market: self.collateral_market,
+                last_observed_price: Some(self.last_observed_price),
+                depeg_guard: None,
+            },
+            pyth_oracle::instruction::UpdateCollateralValue {},
+        );
+        let (fallback, logs) = self.send(
+            pyth_oracle::accounts::FallbackPriceContext { price_update, last_good_price: self.last_good_price },
+            pyth_oracle::instruction::GetFallbackPrice { policy: FALLBACK },
+        );
+        // "Price: <price> × 10^<exponent> (<freshness>)"
+        let freshness = logs
+            .iter()
+            .filter_map(|line| line.strip_prefix("Program log: Price: ")?.strip_suffix(')'))
+            .find_map(|line| Some(line.split_once(" (")?.1.to_string()));
+
+        let account = self.svm.get_account(&self.circuit_breaker).unwrap();
+        let breaker = CircuitBreaker::try_deserialize(&mut account.data.as_slice()).unwrap();
+
+        Reading {
+            offset: delivery.now - start,
+            now: delivery.now,
+            sample: delivery.sample,
+            price,
+            breaker: breaker.state.status,
+            collateral,
+            fallback,
+            freshness,
+        }
+    }
+
+    /// Send one instruction; returns its result and the program logs
+    fn send(
+        &mut self,
+        accounts: impl ToAccountMetas,
+        data: impl InstructionData,
+    ) -> (Result<(), TransactionError>, Vec<String>) {
+        let instruction = Instruction {
+            program_id: pyth_oracle::ID,
+            accounts: accounts.to_account_metas(None),
+            data: data.data(),
+        };
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            self.svm.latest_blockhash(),
+        );
+        // Readers repeat transactions; each needs its own blockhash
+        self.svm.expire_blockhash();
+        match self.svm.send_transaction(tx) {
+            Ok(meta) => (Ok(()), meta.logs),
+            Err(failed) => (Err(failed.err), failed.meta.logs),
+        }
+    }
+}
+
+fn custom_error(code: u32) -> Result<(), TransactionError> {
+    Err(TransactionError::InstructionError(0, InstructionError::Custom(code)))
+}
+
+/// What `get_validated_price` should make of the reading's update under the
+/// default config: stale past `DEFAULT_MAX_PRICE_AGE`, too wide over
+/// `MAX_CONFIDENCE_BPS`
+fn expected_price(reading: &Reading) -> Result<(), TransactionError> {
+    if reading.age() > DEFAULT_MAX_PRICE_AGE as i64 {
+        return custom_error(u32::from(PriceSourceError::PriceTooStale));
+    }
+    if !confidence_within(reading.sample.price, reading.sample.conf, MAX_CONFIDENCE_BPS) {
+        return custom_error(u32::from(OracleError::ConfidenceTooHigh));
+    }
+    Ok(())
+}
+
+/// Readings from `from` seconds into the stream for `secs`
+fn during(readings: &[Reading], from: i64, secs: i64) -> impl Iterator<Item = &Reading> {
+    readings.iter().filter(move |reading| (from..from + secs).contains(&reading.offset))
+}
+
+// ============================================================================
+// DRILLS
+// ============================================================================
+
+#[test]
+fn every_reading_matches_the_validation_model() {
+    let readings = Drill::new(stream())
+        .inject(Fault::Gap { from: 100, secs: 180 })
+        .inject(Fault::StaleRepeat { from: 350, secs: 120 })
+        .inject(Fault::ConfidenceBlowout { from: 550, secs: 60, conf_bps: 300 })
+        .inject(Fault::Rollback { at: 700, by_secs: 30 })
+        .inject(Fault::Spike { at: 800, bps: 2_000 })
+        .run();
+
+    for reading in &readings {
+        let expected = expected_price(reading);
+        assert_eq!(reading.price, expected, "{reading:?}");
+        if expected == custom_error(u32::from(OracleError::ConfidenceTooHigh)) {
+            // A wide price is not stale; the fallback does not cover it
+            assert_eq!(reading.fallback, expected, "{reading:?}");
+        } else if expected.is_ok() {
+            assert_eq!(reading.freshness.as_deref(), Some("Fresh"), "{reading:?}");
+        }
+    }
+}
+
+#[test]
+fn gap_goes_stale_and_falls_back() {
+    let readings = Drill::new(stream()).inject(Fault::Gap { from: 100, secs: 180 }).run();
+
+    let stale = custom_error(u32::from(PriceSourceError::PriceTooStale));
+    let mut last_good = None;
+    let mut stale_readings = 0;
+    for reading in &readings {
+        assert_eq!(reading.price, expected_price(reading), "{reading:?}");
+        if reading.price.is_ok() {
+            assert_eq!(reading.collateral, Ok(()), "{reading:?}");
+            assert_eq!(reading.freshness.as_deref(), Some("Fresh"), "{reading:?}");
+            last_good = Some(reading.sample.publish_time);
+        }
+        if reading.price != stale {
+            continue;
+        }
+
+        // Collateral is not valued; the fallback serves the last good price
+        // haircut per started minute past the max age
+        stale_readings += 1;
+        assert_eq!(reading.collateral, stale, "{reading:?}");
+        let last_good_age = (reading.now - last_good.expect("a fresh reading before the gap")) as u64;
+        let haircut_bps =
+            staleness_haircut_bps(last_good_age.saturating_sub(DEFAULT_MAX_PRICE_AGE), FALLBACK_BPS_PER_MINUTE);
+        assert_eq!(reading.fallback, Ok(()), "{reading:?}");
+        assert_eq!(reading.freshness, Some(format!("Haircut {{ haircut_bps: {haircut_bps} }}")), "{reading:?}");
+    }
+    assert!(stale_readings > 0, "a 180 s gap outlasts the max age");
+
+    // Fresh again once the feed resumes
+    let resumed = readings.iter().find(|reading| reading.offset >= 280).unwrap();
+    assert_eq!(resumed.price, expected_price(resumed));
+}
+
+#[test]
+fn frozen_price_passes_staleness_until_the_breaker_catches_the_thaw() {
+    let readings = Drill::new(synthetic_stream()).inject(Fault::StaleRepeat { from: 300, secs: 240 }).run();
+
+    let frozen: Vec<&Reading> = during(&readings, 300, 240).collect();
+    for reading in &frozen {
+        assert_eq!(reading.price, Ok(()), "fresh publish times pass staleness: {reading:?}");
+        assert_eq!(reading.collateral, Ok(()));
+        assert_eq!(reading.sample.price, frozen[0].sample.price);
+        assert_eq!(reading.breaker, BreakerStatus::Normal);
+    }
+
+    // $164.95 frozen, $177 on thaw: over 5% within a tick
+    for reading in readings.iter().filter(|reading| reading.offset >= 540) {
+        assert_eq!(reading.breaker, BreakerStatus::Tripped, "{reading:?}");
+    }
+}
+
+#[test]
+fn confidence_blowout_is_rejected_without_fallback() {
+    let readings = Drill::new(stream())
+        .inject(Fault::ConfidenceBlowout { from: 300, secs: 60, conf_bps: 300 })
+        .run();
+
+    let blown: Vec<&Reading> = during(&readings, 300, 60).collect();
+    assert!(!blown.is_empty());
+    for reading in blown {
+        assert_eq!(reading.price, expected_price(reading), "{reading:?}");
+        if reading.price.is_ok() {
+            continue;
+        }
+        assert_eq!(reading.collateral, reading.price, "{reading:?}");
+        assert_eq!(reading.fallback, reading.price, "{reading:?}");
+        assert_eq!(reading.freshness, None);
+    }
+    let after = readings.iter().find(|reading| reading.offset == 360).unwrap();
+    assert_eq!(after.price, expected_price(after));
+}
+
+#[test]
+fn rollback_is_rejected_by_the_publish_time_guard() {
+    let readings = Drill::new(stream()).inject(Fault::Rollback { at: 300, by_secs: 30 }).run();
+
+    let rolled_back = readings.iter().find(|reading| reading.offset == 300).unwrap();
+    let previous = readings.iter().find(|reading| reading.offset == 300 - TICK_SECS).unwrap();
+    assert!(rolled_back.sample.publish_time < previous.sample.publish_time);
+    assert_eq!(rolled_back.price, expected_price(rolled_back), "older is not stale by itself");
+    if rolled_back.price.is_ok() {
+        assert_eq!(rolled_back.collateral, custom_error(u32::from(OracleError::PriceRollback)));
+    }
+
+    // The reference stays on the newer update, so the next reader passes
+    let next = readings.iter().find(|reading| reading.offset == 300 + TICK_SECS).unwrap();
+    if next.price.is_ok() {
+        assert_eq!(next.collateral, Ok(()));
+    }
+}
+
+#[test]
+fn spike_trips_the_breaker_and_it_stays_tripped() {
+    let readings = Drill::new(stream()).inject(Fault::Spike { at: 300, bps: 2_000 }).run();
+
+    let spike = readings.iter().find(|reading| reading.offset == 300).unwrap();
+    assert_eq!(spike.price, expected_price(spike), "a spike is a valid price");
+    if spike.price.is_ok() {
+        assert_eq!(spike.collateral, Ok(()), "only the breaker looks at the move");
+    }
+    for reading in readings.iter().filter(|reading| reading.offset >= 300) {
+        assert_eq!(reading.breaker, BreakerStatus::Tripped, "{reading:?}");
+    }
+}
@@ -0,0 +1,208 @@
+/**
+ * Byte-Offset Parity Tests for the Pinocchio Template
+ *
+ * pinocchio-oracle.rs reads `PriceUpdateV2` at hard-coded offsets. These
+ * tests serialize the receiver SDK's own structs, with Anchor's account
+ * serializer, and check that every offset and the discriminator land on
+ * the same bytes, for both one-byte (Full) and two-byte (Partial)
+ * verification levels. They also run `validate_price_update` through each
+ * rejection path.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml of the pinocchio program crate:
+ *    [dev-dependencies]
+ *    anchor-lang = "0.30.1"
+ *    pyth-solana-receiver-sdk = "0.3.0"
+ *    pythnet-sdk = "2"
+ *
+ * 2. Copy this file to `tests/offsets.rs`
+ * 3. Run: cargo test --test offsets
+ */
+
+use anchor_lang::{AccountSerialize, Discriminator};
+use pinocchio::program_error::ProgramError;
+use pyth_oracle_pinocchio::{
+    layout, validate_price_update, OracleError, PriceCheck, ERROR_CODE_OFFSET, INSUFFICIENT_VERIFICATION,
+    PRICE_UPDATE_V2_DISCRIMINATOR, PYTH_RECEIVER_PROGRAM_ID,
+};
+use pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, VerificationLevel};
+use pythnet_sdk::messages::PriceFeedMessage;
+
+const NOW: i64 = 1_700_000_000;
+const FEED_ID: [u8; 32] = [7; 32];
+
+// ============================================================================
+// FIXTURES
+// ============================================================================
+
+/// Every field distinct, so a read at the wrong offset cannot match
+fn sdk_update(verification_level: VerificationLevel) -> PriceUpdateV2 {
+    PriceUpdateV2 {
+        write_authority: anchor_lang::prelude::Pubkey::new_from_array([9; 32]),
+        verification_level,
+        price_message: PriceFeedMessage {
+            feed_id: FEED_ID,
+            price: 150_0000_0000,
+            conf: 7_500_000,
+            exponent: -8,
+            publish_time: NOW - 5,
+            prev_publish_time: NOW - 6,
+            ema_price: 149_0000_0000,
+            ema_conf: 8_500_000,
+        },
+        posted_slot: 123_456_789,
+    }
+}
+
+fn account_data(update: &PriceUpdateV2) -> Vec<u8> {
+    let mut data = Vec::new();
+    update.try_serialize(&mut data).unwrap();
+    data
+}
+
+fn check() -> PriceCheck {
+    PriceCheck {
+        feed_id: FEED_ID,
+        max_age_secs: 60,
+        max_confidence_bps: 100,
+        min_signatures: 0,
+    }
+}
+
+fn oracle_error(error: OracleError) -> ProgramError {
+    ProgramError::Custom(ERROR_CODE_OFFSET + error as u32)
+}
+
+fn read_i64(data: &[u8], offset: usize) -> i64 {
+    i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn assert_offsets_match(update: &PriceUpdateV2, message: usize) {
+    let data = account_data(update);
+    let expected = &update.price_message;
+
+    assert_eq!(data.len(), message + layout::POSTED_SLOT + 8);
+    assert_eq!(&data[message + layout::FEED_ID..message + layout::FEED_ID + 32], &expected.feed_id);
+    assert_eq!(read_i64(&data, message + layout::PRICE), expected.price);
+    assert_eq!(read_u64(&data, message + layout::CONF), expected.conf);
+    assert_eq!(
+        i32::from_le_bytes(data[message + layout::EXPONENT..message + layout::EXPONENT + 4].try_into().unwrap()),
+        expected.exponent
+    );
+    assert_eq!(read_i64(&data, message + layout::PUBLISH_TIME), expected.publish_time);
+    assert_eq!(read_i64(&data, message + layout::PREV_PUBLISH_TIME), expected.prev_publish_time);
+    assert_eq!(read_i64(&data, message + layout::EMA_PRICE), expected.ema_price);
+    assert_eq!(read_u64(&data, message + layout::EMA_CONF), expected.ema_conf);
+    assert_eq!(read_u64(&data, message + layout::POSTED_SLOT), update.posted_slot);
+}
+
+// ============================================================================
+// LAYOUT PARITY
+// ============================================================================
+
+#[test]
+fn discriminator_matches_sdk() {
+    assert_eq!(PRICE_UPDATE_V2_DISCRIMINATOR, PriceUpdateV2::DISCRIMINATOR);
+}
+
+#[test]
+fn receiver_program_id_matches_sdk() {
+    assert_eq!(PYTH_RECEIVER_PROGRAM_ID, pyth_solana_receiver_sdk::ID.to_bytes());
+}
+
+#[test]
+fn full_update_offsets_match_sdk() {
+    let update = sdk_update(VerificationLevel::Full);
+    let data = account_data(&update);
+
+    assert_eq!(data[layout::VERIFICATION_LEVEL], layout::FULL_TAG);
+    assert_eq!(data.len(), layout::MIN_LEN);
+    assert_offsets_match(&update, layout::MESSAGE_FULL);
+}
+
+#[test]
+fn partial_update_offsets_match_sdk() {
+    let update = sdk_update(VerificationLevel::Partial { num_signatures: 5 });
+    let data = account_data(&update);
+
+    assert_eq!(data[layout::VERIFICATION_LEVEL], layout::PARTIAL_TAG);
+    assert_eq!(data[layout::VERIFICATION_LEVEL + 1], 5);
+    assert_offsets_match(&update, layout::MESSAGE_PARTIAL);
+}
+
+#[test]
+fn validated_price_matches_sdk_fields() {
+    for level in [VerificationLevel::Full, VerificationLevel::Partial { num_signatures: 13 }] {
+        let update = sdk_update(level);
+        let check = PriceCheck { min_signatures: 13, ..check() };
+        let price = validate_price_update(&account_data(&update), &check, NOW).unwrap();
+
+        let expected = &update.price_message;
+        assert_eq!(price.price, expected.price);
+        assert_eq!(price.conf, expected.conf);
+        assert_eq!(price.exponent, expected.exponent);
+        assert_eq!(price.publish_time, expected.publish_time);
+        assert_eq!(price.lower_bound, expected.price - expected.conf as i64);
+        assert_eq!(price.upper_bound, expected.price + expected.conf as i64);
+    }
+}
+
+// ============================================================================
+// REJECTIONS
+// ============================================================================
+
+#[test]
+fn rejects_wrong_discriminator() {
+    let mut data = account_data(&sdk_update(VerificationLevel::Full));
+    data[0] ^= 1;
+    assert_eq!(
+        validate_price_update(&data, &check(), NOW),
+        Err(oracle_error(OracleError::InvalidPriceUpdateAccount))
+    );
+}
+
+#[test]
+fn rejects_truncated_account() {
+    let data = account_data(&sdk_update(VerificationLevel::Partial { num_signatures: 13 }));
+    let check = PriceCheck { min_signatures: 5, ..check() };
+    // Long enough for a Full update, one byte short for this Partial one
+    assert_eq!(
+        validate_price_update(&data[..data.len() - 1], &check, NOW),
+        Err(oracle_error(OracleError::InvalidPriceUpdateAccount))
+    );
+}
+
+#[test]
+fn rejects_other_feed() {
+    let data = account_data(&sdk_update(VerificationLevel::Full));
+    let check = PriceCheck { feed_id: [8; 32], ..check() };
+    assert_eq!(validate_price_update(&data, &check, NOW), Err(oracle_error(OracleError::FeedIdMismatch)));
+}
+
+#[test]
+fn rejects_stale_price() {
+    let data = account_data(&sdk_update(VerificationLevel::Full));
+    assert_eq!(validate_price_update(&data, &check(), NOW + 60), Err(oracle_error(OracleError::PriceTooStale)));
+    assert!(validate_price_update(&data, &check(), NOW + 55).is_ok());
+}
+
+#[test]
+fn rejects_wide_confidence() {
+    let data = account_data(&sdk_update(VerificationLevel::Full));
+    // 7_500_000 / 150_0000_0000 = 5 bps
+    let check = PriceCheck { max_confidence_bps: 4, ..check() };
+    assert_eq!(validate_price_update(&data, &check, NOW), Err(oracle_error(OracleError::ConfidenceTooHigh)));
+}
+
+#[test]
+fn rejects_insufficient_verification() {
+    let data = account_data(&sdk_update(VerificationLevel::Partial { num_signatures: 5 }));
+    assert_eq!(validate_price_update(&data, &check(), NOW), Err(INSUFFICIENT_VERIFICATION));
+
+    let check = PriceCheck { min_signatures: 6, ..check() };
+    assert_eq!(validate_price_update(&data, &check, NOW), Err(INSUFFICIENT_VERIFICATION));
+}
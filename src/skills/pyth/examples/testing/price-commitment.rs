@@ -0,0 +1,127 @@
+/**
+ * Tests for the zk Price Commitment Helpers
+ *
+ * Checks the field encoding a circuit has to replicate (feed ID halves,
+ * biased signed fields), that the witness generator and the on-chain
+ * helpers commit to the same inputs, and that every committed field
+ * changes the commitment. Off chain, `poseidon::hashv` runs the same
+ * circom-compatible implementation the syscall uses.
+ *
+ * Setup:
+ * 1. Copy this file to `tests/price_commitment.rs` of the program crate
+ * 2. Run: cargo test --test price_commitment
+ */
+
+use anchor_lang::solana_program::poseidon::{self, Endianness, Parameters};
+use pyth_oracle::{
+    bias_i32, bias_i64, keccak_commitment_as_field, keccak_price_commitment, poseidon_price_commitment,
+    split_feed_id, PriceWitness, ValidatedPrice, PRICE_COMMITMENT_DOMAIN,
+};
+
+fn sample_price() -> ValidatedPrice {
+    let mut feed_id = [0u8; 32];
+    feed_id[0] = 0xef;
+    feed_id[31] = 0x01;
+    ValidatedPrice {
+        feed_id,
+        price: 150_0000_0000,
+        conf: 7_500_000,
+        exponent: -8,
+        publish_time: 1_700_000_000,
+        lower_bound: 150_0000_0000 - 7_500_000,
+        upper_bound: 150_0000_0000 + 7_500_000,
+    }
+}
+
+// ============================================================================
+// ENCODING
+// ============================================================================
+
+#[test]
+fn signed_fields_are_biased() {
+    assert_eq!(bias_i64(0), 1 << 63);
+    assert_eq!(bias_i64(-1), (1 << 63) - 1);
+    assert_eq!(bias_i64(i64::MIN), 0);
+    assert_eq!(bias_i64(i64::MAX), u64::MAX);
+    assert_eq!(bias_i32(-8), (1 << 31) - 8);
+}
+
+#[test]
+fn bias_preserves_order() {
+    let values = [i64::MIN, -150_0000_0000, -1, 0, 1, 150_0000_0000, i64::MAX];
+    for pair in values.windows(2) {
+        assert!(bias_i64(pair[0]) < bias_i64(pair[1]));
+    }
+}
+
+#[test]
+fn feed_id_splits_big_endian() {
+    let (hi, lo) = split_feed_id(&sample_price().feed_id);
+    assert_eq!(hi, 0xef << 120);
+    assert_eq!(lo, 1);
+}
+
+// ============================================================================
+// COMMITMENTS
+// ============================================================================
+
+#[test]
+fn witness_hashes_to_on_chain_commitment() {
+    let price = sample_price();
+    let inputs: Vec<[u8; 32]> = PriceWitness::from_price(&price)
+        .field_elements()
+        .iter()
+        .map(|element| {
+            let mut bytes = [0u8; 32];
+            bytes[16..].copy_from_slice(&element.to_be_bytes());
+            bytes
+        })
+        .collect();
+    let refs: Vec<&[u8]> = inputs.iter().map(|input| input.as_slice()).collect();
+    let expected = poseidon::hashv(Parameters::Bn254X5, Endianness::BigEndian, &refs).unwrap();
+
+    assert_eq!(poseidon_price_commitment(&price).unwrap(), expected.to_bytes());
+    assert_eq!(PriceWitness::from_price(&price).field_elements()[0], PRICE_COMMITMENT_DOMAIN as u128);
+}
+
+#[test]
+fn every_field_changes_the_commitment() {
+    let base = sample_price();
+    let mut other_feed = base;
+    other_feed.feed_id[31] = 0x02;
+    let variants = [
+        other_feed,
+        ValidatedPrice { price: base.price + 1, ..base },
+        ValidatedPrice { exponent: base.exponent - 1, ..base },
+        ValidatedPrice { publish_time: base.publish_time + 1, ..base },
+    ];
+
+    for variant in variants {
+        assert_ne!(poseidon_price_commitment(&variant).unwrap(), poseidon_price_commitment(&base).unwrap());
+        assert_ne!(keccak_price_commitment(&variant), keccak_price_commitment(&base));
+    }
+}
+
+#[test]
+fn confidence_is_not_committed() {
+    let base = sample_price();
+    let wider = ValidatedPrice { conf: base.conf * 2, ..base };
+    assert_eq!(poseidon_price_commitment(&wider).unwrap(), poseidon_price_commitment(&base).unwrap());
+}
+
+#[test]
+fn keccak_field_fits_bn254() {
+    let field = keccak_commitment_as_field(&keccak_price_commitment(&sample_price()));
+    assert_eq!(field[0], 0);
+}
+
+#[test]
+fn circuit_input_json_has_decimal_inputs() {
+    let price = sample_price();
+    let witness = PriceWitness::from_price(&price);
+    let json = witness.to_circuit_input_json(&[0xab; 32]);
+
+    assert!(json.contains(&format!(r#""price":"{}""#, bias_i64(price.price))));
+    assert!(json.contains(r#""feed_id_lo":"1""#));
+    assert!(json.contains(&format!(r#""commitment":"0x{}""#, "ab".repeat(32))));
+}
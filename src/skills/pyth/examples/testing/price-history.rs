@@ -0,0 +1,147 @@
+/**
+ * Tests for the Local Price History Store
+ *
+ * Runs `PriceHistoryStore` against a scratch directory: appends skip
+ * updates already stored, range loads return only the range, and a torn
+ * append is cut back to whole rows. The `PriceSeries` queries are checked
+ * on hand-made series with known volatility, drawdown, and confidence.
+ * Nothing here calls Benchmarks or Hermes.
+ *
+ * Setup:
+ * 1. Copy this file to `tests/price_history.rs` of the program crate
+ * 2. Run: cargo test --test price_history --features hermes
+ */
+
+use pyth_oracle::hermes::{feed_id_to_hex, ParsedPriceUpdate, PriceUpdateMetadata};
+use pyth_oracle::price_history::{PriceHistoryStore, PriceSeries};
+use pyth_solana_receiver_sdk::price_update::Price;
+use std::io::Write;
+use std::path::PathBuf;
+
+const FEED: [u8; 32] = [7; 32];
+const HOUR: i64 = 3_600;
+
+/// Empty store in a directory of its own
+fn scratch_store(name: &str) -> (PriceHistoryStore, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("price-history-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    (PriceHistoryStore::open(&dir).unwrap(), dir)
+}
+
+/// $`dollars` ± 1% at `publish_time`
+fn update(publish_time: i64, dollars: i64) -> ParsedPriceUpdate {
+    let price = Price { price: dollars * 100_000_000, conf: dollars as u64 * 1_000_000, exponent: -8, publish_time };
+    ParsedPriceUpdate {
+        feed_id: FEED,
+        price,
+        ema_price: price,
+        metadata: PriceUpdateMetadata { slot: None, proof_available_time: None, prev_publish_time: None },
+    }
+}
+
+fn series(points: &[(i64, i64)]) -> PriceSeries {
+    PriceSeries {
+        feed_id: FEED,
+        publish_time: points.iter().map(|&(time, _)| time).collect(),
+        price: points.iter().map(|&(_, dollars)| dollars * 100_000_000).collect(),
+        conf: points.iter().map(|&(_, dollars)| dollars as u64 * 1_000_000).collect(),
+        exponent: vec![-8; points.len()],
+    }
+}
+
+// ============================================================================
+// STORE
+// ============================================================================
+
+#[test]
+fn append_skips_updates_already_stored() {
+    let (store, _) = scratch_store("append");
+    assert_eq!(store.last_publish_time(&FEED).unwrap(), None);
+
+    assert_eq!(store.append(&FEED, &[update(100, 150), update(101, 151), update(102, 152)]).unwrap(), 3);
+    // A rerun over an overlapping window only adds what is new
+    assert_eq!(store.append(&FEED, &[update(101, 151), update(102, 152), update(103, 153)]).unwrap(), 1);
+
+    assert_eq!(store.row_count(&FEED).unwrap(), 4);
+    assert_eq!(store.last_publish_time(&FEED).unwrap(), Some(103));
+}
+
+#[test]
+fn load_returns_only_the_range() {
+    let (store, _) = scratch_store("load");
+    let updates: Vec<_> = (0..10).map(|i| update(1_000 + i, 150 + i)).collect();
+    store.append(&FEED, &updates).unwrap();
+
+    let loaded = store.load(&FEED, 1_003, 1_006).unwrap();
+    assert_eq!(loaded.publish_time, vec![1_003, 1_004, 1_005]);
+    assert_eq!(loaded.price, vec![153_0000_0000, 154_0000_0000, 155_0000_0000]);
+    assert_eq!(loaded.exponent, vec![-8; 3]);
+
+    assert!(store.load(&FEED, 2_000, 3_000).unwrap().is_empty());
+    assert!(store.load(&[8; 32], 0, i64::MAX).unwrap().is_empty());
+}
+
+#[test]
+fn torn_append_is_cut_back_to_whole_rows() {
+    let (store, dir) = scratch_store("torn");
+    store.append(&FEED, &[update(100, 150), update(101, 151)]).unwrap();
+
+    // A crash after the price column of a third row was written
+    let price_column = dir.join(feed_id_to_hex(&FEED).trim_start_matches("0x")).join("price.i64");
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&price_column)
+        .unwrap()
+        .write_all(&152_0000_0000i64.to_le_bytes())
+        .unwrap();
+
+    assert_eq!(store.row_count(&FEED).unwrap(), 2);
+    assert_eq!(std::fs::metadata(&price_column).unwrap().len(), 16);
+    assert_eq!(store.append(&FEED, &[update(102, 152)]).unwrap(), 1);
+    assert_eq!(store.load(&FEED, 0, i64::MAX).unwrap().price[2], 152_0000_0000);
+}
+
+// ============================================================================
+// QUERIES
+// ============================================================================
+
+#[test]
+fn resample_repeats_the_last_price_over_gaps() {
+    let series = series(&[(0, 100), (HOUR / 2, 105), (2 * HOUR + 1, 110)]);
+    assert_eq!(series.resample(HOUR), vec![100.0, 105.0, 105.0]);
+}
+
+#[test]
+fn steady_growth_has_no_volatility() {
+    // Doubling every hour: every log return is ln(2)
+    let series = series(&[(0, 100), (HOUR, 200), (2 * HOUR, 400), (3 * HOUR, 800)]);
+    assert!(series.realized_volatility(HOUR).unwrap().abs() < 1e-12);
+    assert_eq!(series.realized_volatility(4 * HOUR), None);
+}
+
+#[test]
+fn volatility_is_annualized() {
+    // Log returns +r, -r, +r, -r: sample standard deviation r × sqrt(4/3)
+    let series = series(&[(0, 100), (HOUR, 110), (2 * HOUR, 100), (3 * HOUR, 110), (4 * HOUR, 100)]);
+    let per_hour = (1.1f64).ln() * (4.0f64 / 3.0).sqrt();
+    let expected = per_hour * (365.0f64 * 24.0).sqrt();
+    assert!((series.realized_volatility(HOUR).unwrap() - expected).abs() < 1e-9);
+}
+
+#[test]
+fn max_drawdown_is_measured_from_the_running_peak() {
+    let series = series(&[(0, 100), (10, 120), (20, 90), (30, 110), (40, 130), (50, 117)]);
+    let drawdown = series.max_drawdown().unwrap();
+    assert!((drawdown.fraction - 0.25).abs() < 1e-12);
+    assert_eq!((drawdown.peak_time, drawdown.trough_time), (10, 20));
+
+    assert_eq!(PriceSeries::default().max_drawdown(), None);
+}
+
+#[test]
+fn average_confidence_is_in_bps() {
+    // Every point is ± 1%
+    let series = series(&[(0, 100), (10, 120), (20, 90)]);
+    assert!((series.average_confidence_bps().unwrap() - 100.0).abs() < 1e-9);
+    assert_eq!(PriceSeries::default().average_confidence_bps(), None);
+}
@@ -0,0 +1,316 @@
+/**
+ * Tests for Numeric Conversions and Human-Readable Prices
+ *
+ * Pins the text format ("171.2345 ± 0.12"), checks that `Display` and
+ * `FromStr` round-trip exactly, and checks the accuracy bounds documented
+ * in price-numeric.rs: `Decimal` conversions exact for every i64 price with
+ * exponent -28 to 9 and rounded to nearest past 28 places, and `f64`
+ * conversions correctly rounded.
+ *
+ * Setup:
+ * 1. Copy this file to `tests/price_numeric.rs` of the program crate
+ * 2. Add to Cargo.toml:
+ *    [dev-dependencies]
+ *    rust_decimal = "1"
+ * 3. Run: cargo test --test price_numeric --features numeric
+ */
+
+use anchor_lang::error::Error;
+use pyth_oracle::numeric::{HumanPrice, ParsePriceError};
+use pyth_oracle::{parse_feed_id, price_feeds, OracleDecimal, OracleError, OraclePrice, ValidatedPrice};
+use rust_decimal::Decimal;
+
+fn price_at(price: i64, conf: u64, exponent: i32) -> ValidatedPrice {
+    ValidatedPrice {
+        feed_id: parse_feed_id(price_feeds::SOL_USD).unwrap(),
+        price,
+        conf,
+        exponent,
+        publish_time: 1_700_000_000,
+        lower_bound: price.saturating_sub(conf as i64),
+        upper_bound: price.saturating_add(conf as i64),
+    }
+}
+
+/// 171.2345 ± 0.12
+fn sample_price() -> ValidatedPrice {
+    price_at(171_2345_0000, 12_000_000, -8)
+}
+
+// ============================================================================
+// DISPLAY
+// ============================================================================
+
+#[test]
+fn validated_price_renders_in_human_units() {
+    assert_eq!(sample_price().to_string(), "171.2345 ± 0.12");
+    assert_eq!(format!("{:.2}", sample_price()), "171.23 ± 0.12");
+    assert_eq!(format!("{:.6}", sample_price()), "171.234500 ± 0.120000");
+    assert_eq!(price_at(-5_0000_0000, 0, -8).to_string(), "-5 ± 0");
+}
+
+#[test]
+fn positive_exponents_render_as_integers() {
+    let price = HumanPrice {
+        price: 5,
+        conf: 1,
+        exponent: 3,
+    };
+    assert_eq!(price.to_string(), "5000 ± 1000");
+    assert_eq!(format!("{price:.1}"), "5000.0 ± 1000.0");
+}
+
+#[test]
+fn precision_rounds_halves_away_from_zero() {
+    let price = HumanPrice {
+        price: -125,
+        conf: 5,
+        exponent: -2,
+    };
+    assert_eq!(format!("{price:.1}"), "-1.3 ± 0.1");
+
+    // No sign on a price that rounds to zero
+    let price = HumanPrice {
+        price: -4,
+        conf: 0,
+        exponent: -2,
+    };
+    assert_eq!(format!("{price:.1}"), "0.0 ± 0.0");
+}
+
+#[test]
+fn oracle_decimal_displays_at_its_scale() {
+    assert_eq!(OracleDecimal::from_bps(8_000).to_string(), "0.8000");
+    assert_eq!(OracleDecimal::new(5, 3).to_string(), "0.005");
+    assert_eq!(OracleDecimal::new(0, 3).to_string(), "0.000");
+    assert_eq!(OracleDecimal::new(5, -2).to_string(), "500");
+    assert_eq!(OracleDecimal::ZERO.to_string(), "0");
+    assert_eq!(
+        OracleDecimal::new(u128::MAX, 38).to_string(),
+        "3.40282366920938463463374607431768211455"
+    );
+
+    assert_eq!(format!("{:.2}", OracleDecimal::new(5, 3)), "0.01");
+    assert_eq!(format!("{:.6}", OracleDecimal::from_bps(8_000)), "0.800000");
+    assert_eq!(format!("{:.2}", OracleDecimal::new(5, -2)), "500.00");
+}
+
+// ============================================================================
+// PARSING
+// ============================================================================
+
+#[test]
+fn oracle_decimal_parses_exactly() {
+    assert_eq!("0.8000".parse::<OracleDecimal>().unwrap(), OracleDecimal::new(8_000, 4));
+    assert_eq!("500".parse::<OracleDecimal>().unwrap(), OracleDecimal::new(500, 0));
+    assert_eq!(".5".parse::<OracleDecimal>().unwrap(), OracleDecimal::new(5, 1));
+    assert_eq!(" 12.0 ".parse::<OracleDecimal>().unwrap(), OracleDecimal::new(120, 1));
+
+    for value in [
+        OracleDecimal::ZERO,
+        OracleDecimal::from_bps(8_000),
+        OracleDecimal::new(5, 3),
+        OracleDecimal::new(u128::MAX, 0),
+        OracleDecimal::new(u128::MAX, 38),
+        OracleDecimal::new(1, 60),
+    ] {
+        assert_eq!(value.to_string().parse::<OracleDecimal>().unwrap(), value);
+    }
+}
+
+#[test]
+fn malformed_decimals_are_rejected() {
+    assert_eq!("-1".parse::<OracleDecimal>().unwrap_err(), ParsePriceError::Negative);
+    assert_eq!("1".repeat(40).parse::<OracleDecimal>().unwrap_err(), ParsePriceError::Overflow);
+    for text in ["", ".", "1.2.3", "1e5", "abc", "--1", "1 000"] {
+        assert_eq!(
+            text.parse::<OracleDecimal>().unwrap_err(),
+            ParsePriceError::InvalidNumber(text.to_string())
+        );
+    }
+}
+
+#[test]
+fn human_price_parses_at_the_finer_scale() {
+    let expected = HumanPrice {
+        price: 1_712_345,
+        conf: 1_200,
+        exponent: -4,
+    };
+    assert_eq!("171.2345 ± 0.12".parse::<HumanPrice>().unwrap(), expected);
+    assert_eq!("171.2345 +/- 0.12".parse::<HumanPrice>().unwrap(), expected);
+    assert_eq!(
+        "-0.5±1".parse::<HumanPrice>().unwrap(),
+        HumanPrice {
+            price: -5,
+            conf: 10,
+            exponent: -1,
+        }
+    );
+    assert_eq!(
+        "150".parse::<HumanPrice>().unwrap(),
+        HumanPrice {
+            price: 150,
+            conf: 0,
+            exponent: 0,
+        }
+    );
+
+    let oracle_price = expected.to_oracle_price(1_700_000_000);
+    assert_eq!(
+        oracle_price,
+        OraclePrice {
+            price: 1_712_345,
+            conf: 1_200,
+            exponent: -4,
+            publish_time: 1_700_000_000,
+        }
+    );
+}
+
+#[test]
+fn human_price_round_trips_its_value() {
+    for price in [
+        sample_price(),
+        price_at(-5_0000_0000, 0, -8),
+        price_at(i64::MAX, u64::MAX / 2, -8),
+        price_at(1, 1, 0),
+    ] {
+        let parsed = price.to_string().parse::<HumanPrice>().unwrap();
+        assert_eq!(parsed.to_string(), price.to_string());
+
+        let value = |price: i64, exponent: i32| {
+            OracleDecimal::from_price(price.abs(), exponent)
+                .unwrap()
+                .normalize()
+        };
+        assert_eq!(value(parsed.price, parsed.exponent), value(price.price, price.exponent));
+        assert_eq!(parsed.price.signum(), price.price.signum());
+    }
+}
+
+#[test]
+fn malformed_prices_are_rejected() {
+    assert_eq!("1 ± -0.1".parse::<HumanPrice>().unwrap_err(), ParsePriceError::Negative);
+    // Past i64 as written, and past it once rescaled to the confidence's 19
+    // places
+    assert_eq!("99999999999999999999".parse::<HumanPrice>().unwrap_err(), ParsePriceError::Overflow);
+    assert_eq!(
+        "1 ± 0.0000000000000000001".parse::<HumanPrice>().unwrap_err(),
+        ParsePriceError::Overflow
+    );
+    assert_eq!(
+        "abc ± 1".parse::<HumanPrice>().unwrap_err(),
+        ParsePriceError::InvalidNumber("abc".to_string())
+    );
+}
+
+// ============================================================================
+// F64
+// ============================================================================
+
+#[test]
+fn f64_matches_the_literal() {
+    assert_eq!(sample_price().price_f64(), 171.2345);
+    assert_eq!(sample_price().conf_f64(), 0.12);
+    assert_eq!(price_at(-5_0000_0000, 0, -8).price_f64(), -5.0);
+    assert_eq!(OracleDecimal::from_bps(8_000).to_f64(), 0.8);
+    assert_eq!(OracleDecimal::new(5, -2).to_f64(), 500.0);
+}
+
+#[test]
+fn f64_is_correctly_rounded() {
+    // Exact up to 2^53; 2^53 + 1 is a tie and rounds to even
+    assert_eq!(OracleDecimal::new(1 << 53, 0).to_f64(), 9_007_199_254_740_992.0);
+    assert_eq!(OracleDecimal::new((1 << 53) + 1, 0).to_f64(), 9_007_199_254_740_992.0);
+    assert_eq!(OracleDecimal::new((1 << 53) + 3, 0).to_f64(), 9_007_199_254_740_996.0);
+    assert_eq!(OracleDecimal::new(u128::MAX, 0).to_f64(), u128::MAX as f64);
+
+    // Against the same value written in scientific notation, a separate
+    // parse path, over mantissas of every length and Pyth's exponents
+    let mut mantissa = 0x9e37_79b9_7f4a_7c15_u64;
+    for _ in 0..2_000 {
+        mantissa ^= mantissa << 13;
+        mantissa ^= mantissa >> 7;
+        mantissa ^= mantissa << 17;
+        let price = mantissa >> (mantissa % 64);
+        for scale in 0..=12 {
+            let expected = format!("{price}e-{scale}").parse::<f64>().unwrap();
+            assert_eq!(OracleDecimal::new(price as u128, scale).to_f64(), expected, "{price}e-{scale}");
+        }
+    }
+}
+
+#[test]
+fn f64_saturates_at_the_extremes() {
+    assert_eq!(OracleDecimal::new(5, 324).to_f64(), f64::from_bits(1));
+    assert_eq!(OracleDecimal::new(2, 324).to_f64(), 0.0);
+    assert_eq!(OracleDecimal::new(u128::MAX, 400).to_f64(), 0.0);
+    assert_eq!(OracleDecimal::new(1, -308).to_f64(), 1e308);
+    assert_eq!(OracleDecimal::new(1, -309).to_f64(), f64::INFINITY);
+    assert_eq!(OracleDecimal::new(1, i32::MIN).to_f64(), f64::INFINITY);
+    assert_eq!(OracleDecimal::new(0, i32::MIN).to_f64(), 0.0);
+}
+
+// ============================================================================
+// DECIMAL
+// ============================================================================
+
+#[test]
+fn decimal_is_exact_for_every_i64_price() {
+    for exponent in -28..=9 {
+        for price in [0, 1, -1, 171_2345_0000, i64::MAX, i64::MIN] {
+            let expected = if exponent <= 0 {
+                Decimal::from_i128_with_scale(price as i128, exponent.unsigned_abs())
+            } else {
+                Decimal::from_i128_with_scale(price as i128 * 10_i128.pow(exponent as u32), 0)
+            };
+            let validated = price_at(price, 0, exponent);
+            assert_eq!(validated.price_decimal().unwrap(), expected, "{price}e{exponent}");
+        }
+    }
+
+    assert_eq!(
+        sample_price().price_decimal().unwrap(),
+        Decimal::from_str_exact("171.2345").unwrap()
+    );
+    assert_eq!(sample_price().conf_decimal().unwrap(), Decimal::new(12, 2));
+}
+
+#[test]
+fn decimal_rounds_to_nearest_past_28_places() {
+    assert_eq!(price_at(51, 0, -30).price_decimal().unwrap(), Decimal::new(1, 28));
+    assert_eq!(price_at(50, 0, -30).price_decimal().unwrap(), Decimal::new(1, 28));
+    assert_eq!(price_at(49, 0, -30).price_decimal().unwrap(), Decimal::ZERO);
+    assert_eq!(price_at(-51, 0, -30).price_decimal().unwrap(), Decimal::new(-1, 28));
+    assert!(!price_at(-49, 0, -30).price_decimal().unwrap().is_sign_negative());
+}
+
+#[test]
+fn decimal_overflows_past_96_bits() {
+    assert_eq!(OracleDecimal::new((1 << 96) - 1, 0).to_decimal().unwrap(), Decimal::MAX);
+    assert_eq!(
+        OracleDecimal::new(1 << 96, 0).to_decimal().unwrap_err(),
+        Error::from(OracleError::MathOverflow)
+    );
+    assert_eq!(
+        price_at(i64::MAX, 0, 10).price_decimal().unwrap_err(),
+        Error::from(OracleError::MathOverflow)
+    );
+}
+
+#[test]
+fn decimal_converts_back_exactly() {
+    for decimal in [Decimal::ZERO, Decimal::new(12_345, 4), Decimal::MAX, Decimal::new(1, 28)] {
+        let value = OracleDecimal::try_from(decimal).unwrap();
+        assert_eq!(Decimal::try_from(value).unwrap(), decimal);
+    }
+    assert_eq!(
+        OracleDecimal::try_from(Decimal::new(12_345, 4)).unwrap(),
+        OracleDecimal::new(12_345, 4)
+    );
+    assert_eq!(
+        OracleDecimal::try_from(Decimal::new(-1, 2)).unwrap_err(),
+        Error::from(OracleError::NegativePrice)
+    );
+}
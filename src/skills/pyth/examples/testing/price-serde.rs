@@ -0,0 +1,134 @@
+/**
+ * Tests for the Serde and Borsh Encodings of Prices and Configs
+ *
+ * Round-trips `ValidatedPrice` and `PriceValidationConfig` through JSON and
+ * Borsh, and pins the JSON shape services depend on: hex feed IDs, the
+ * tagged verification level, and snake_case field names.
+ *
+ * Setup:
+ * 1. Copy this file to `tests/price_serde.rs` of the program crate
+ * 2. Add to Cargo.toml:
+ *    [dev-dependencies]
+ *    serde_json = "1"
+ * 3. Run: cargo test --test price_serde --features serde
+ */
+
+use anchor_lang::{AnchorDeserialize, AnchorSerialize};
+use pyth_oracle::{parse_feed_id, price_feeds, PriceValidationConfig, ValidatedPrice};
+use serde_json::json;
+
+fn sample_price() -> ValidatedPrice {
+    ValidatedPrice {
+        feed_id: parse_feed_id(price_feeds::SOL_USD).unwrap(),
+        price: 150_0000_0000,
+        conf: 7_500_000,
+        exponent: -8,
+        publish_time: 1_700_000_000,
+        lower_bound: 150_0000_0000 - 7_500_000,
+        upper_bound: 150_0000_0000 + 7_500_000,
+    }
+}
+
+// ============================================================================
+// JSON
+// ============================================================================
+
+#[test]
+fn validated_price_json_shape() {
+    let value = serde_json::to_value(sample_price()).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "feed_id": price_feeds::SOL_USD,
+            "price": 15_000_000_000i64,
+            "conf": 7_500_000u64,
+            "exponent": -8,
+            "publish_time": 1_700_000_000i64,
+            "lower_bound": 14_992_500_000i64,
+            "upper_bound": 15_007_500_000i64,
+        })
+    );
+}
+
+#[test]
+fn validated_price_json_round_trips() {
+    let price = sample_price();
+    let json = serde_json::to_string(&price).unwrap();
+    assert_eq!(serde_json::from_str::<ValidatedPrice>(&json).unwrap(), price);
+}
+
+#[test]
+fn feed_id_accepts_bare_hex() {
+    let mut value = serde_json::to_value(sample_price()).unwrap();
+    value["feed_id"] = json!(price_feeds::SOL_USD.trim_start_matches("0x"));
+    assert_eq!(serde_json::from_value::<ValidatedPrice>(value).unwrap(), sample_price());
+}
+
+#[test]
+fn malformed_feed_id_is_rejected() {
+    for feed_id in [json!("0xef0d"), json!("not hex"), json!([0xef, 0x0d])] {
+        let mut value = serde_json::to_value(sample_price()).unwrap();
+        value["feed_id"] = feed_id;
+        assert!(serde_json::from_value::<ValidatedPrice>(value).is_err());
+    }
+}
+
+#[test]
+fn verification_level_is_tagged() {
+    let full = serde_json::to_value(PriceValidationConfig::strict()).unwrap();
+    assert_eq!(full["verification_level"], json!({ "level": "full" }));
+
+    let partial = serde_json::to_value(PriceValidationConfig::lenient()).unwrap();
+    assert_eq!(
+        partial["verification_level"],
+        json!({ "level": "partial", "num_signatures": 5 })
+    );
+}
+
+#[test]
+fn config_json_round_trips() {
+    let configs = [
+        PriceValidationConfig::default(),
+        PriceValidationConfig::strict().with_feed_id(price_feeds::SOL_USD).unwrap(),
+        PriceValidationConfig::lenient().with_max_age_slots(25),
+    ];
+    for config in configs {
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(serde_json::from_str::<PriceValidationConfig>(&json).unwrap(), config);
+    }
+}
+
+#[test]
+fn config_optional_fields_are_null() {
+    let value = serde_json::to_value(PriceValidationConfig::default()).unwrap();
+    assert_eq!(value["expected_feed_id"], json!(null));
+    assert_eq!(value["max_age_slots"], json!(null));
+
+    let pinned = PriceValidationConfig::default().with_feed_id(price_feeds::SOL_USD).unwrap();
+    let value = serde_json::to_value(pinned).unwrap();
+    assert_eq!(value["expected_feed_id"], json!(price_feeds::SOL_USD));
+}
+
+// ============================================================================
+// BORSH
+// ============================================================================
+
+#[test]
+fn validated_price_borsh_round_trips() {
+    let price = sample_price();
+    let bytes = price.try_to_vec().unwrap();
+    // feed_id, price, conf, exponent, publish_time, lower_bound, upper_bound
+    assert_eq!(bytes.len(), 32 + 8 + 8 + 4 + 8 + 8 + 8);
+    assert_eq!(ValidatedPrice::try_from_slice(&bytes).unwrap(), price);
+}
+
+#[test]
+fn config_borsh_round_trips() {
+    for config in [
+        PriceValidationConfig::strict().with_feed_id(price_feeds::SOL_USD).unwrap(),
+        PriceValidationConfig::lenient(),
+    ] {
+        let bytes = config.try_to_vec().unwrap();
+        assert_eq!(PriceValidationConfig::try_from_slice(&bytes).unwrap(), config);
+    }
+}
@@ -0,0 +1,354 @@
+/**
+ * Tests for the Standalone Price Validation Example
+ *
+ * `examples/on-chain/price-validation.rs` carries its own copies of the
+ * decimal helpers for programs that use the receiver SDK's `Price`
+ * directly. Known values of the USD conversions, overflow on inputs that
+ * do not fit instead of a panic or a truncated result, and round trips
+ * that never gain tokens.
+ *
+ * `PriceWithConf` carries confidence through products and quotients; its
+ * interval must contain the exact result and never be relatively narrower
+ * than the operands' combined, down to mantissas at `i64::MAX` and
+ * `u64::MAX`.
+ *
+ * `rescale_to` moves a price between exponents for the ratio and the TWAP:
+ * exact when adding digits, widening when dropping them. The TWAP takes
+ * legs at different exponents, reproduces either leg at the weight bounds,
+ * and its weighted mean always fits back into `i64`/`u64`.
+ *
+ * `confidence_bps`, `dynamic_fee_bps`, and `deduct_fee` must match the
+ * anchor-oracle.rs helpers they copy for every input, so the two cannot
+ * drift apart: confidence and fees always round up. `validate_price`
+ * rejects a zero price even under a limit its saturated confidence meets.
+ *
+ * Setup:
+ * 1. Build `examples/on-chain/price-validation.rs` as the `lib.rs` of a
+ *    crate named `price_validation_example`
+ * 2. Add to its Cargo.toml:
+ *    [dev-dependencies]
+ *    proptest = "1"
+ *    pyth-oracle = { path = "../pyth-oracle", features = ["no-entrypoint"] }
+ * 3. Copy this file to `tests/price_validation.rs`
+ * 4. Run: cargo test --test price_validation
+ */
+
+use anchor_lang::error::Error;
+use anchor_lang::prelude::Clock;
+use price_validation_example::multi_price::{calculate_price_ratio, calculate_twap};
+use price_validation_example::price_validation::{validate_price, ValidationConfig};
+use price_validation_example::safe_math::{
+    self, calculate_tokens_for_usd, calculate_value_usd, DynamicFeeConfig, PriceWithConf, Rounding,
+};
+use price_validation_example::PriceValidationError;
+use proptest::prelude::*;
+use pyth_solana_receiver_sdk::price_update::Price;
+
+fn price(price: i64, exponent: i32) -> Price {
+    Price {
+        price,
+        conf: 0,
+        exponent,
+        publish_time: 1_700_000_000,
+    }
+}
+
+fn overflow() -> Error {
+    Error::from(PriceValidationError::MathOverflow)
+}
+
+fn with_conf(price: i64, conf: u64, exponent: i32) -> PriceWithConf {
+    PriceWithConf { price, conf, exponent }
+}
+
+/// (price, conf) as plain numbers
+fn as_f64(value: &PriceWithConf) -> (f64, f64) {
+    let scale = 10f64.powi(value.exponent);
+    (value.price as f64 * scale, value.conf as f64 * scale)
+}
+
+/// Confidence relative to the price
+fn relative(value: &PriceWithConf) -> f64 {
+    value.conf as f64 / value.price.unsigned_abs() as f64
+}
+
+/// `derived` contains `exact` and is at least as wide, relative to its
+/// price, as `min_relative`
+fn covers(derived: &PriceWithConf, exact: f64, min_relative: f64) -> bool {
+    let (price, conf) = as_f64(derived);
+    let contains = (price - exact).abs() <= conf * (1.0 + 1e-9) + exact.abs() * 1e-12;
+    contains && relative(derived) >= min_relative * (1.0 - 1e-9)
+}
+
+// ============================================================================
+// USD CONVERSIONS
+// ============================================================================
+
+#[test]
+fn one_sol_at_150_is_150_usd() {
+    let sol = price(150_0000_0000, -8);
+    assert_eq!(calculate_value_usd(1_000_000_000, 9, &sol, 6, Rounding::Floor).unwrap(), 150_000_000);
+    assert_eq!(calculate_tokens_for_usd(150_000_000, 6, 9, &sol, Rounding::Floor).unwrap(), 1_000_000_000);
+}
+
+#[test]
+fn results_that_do_not_fit_overflow() {
+    // Past u64 after scaling, past u128 while scaling, and past the POW10 table
+    let max = price(i64::MAX, 0);
+    assert_eq!(calculate_value_usd(u64::MAX, 0, &max, 0, Rounding::Floor).unwrap_err(), overflow());
+    assert_eq!(calculate_value_usd(u64::MAX, 0, &max, 6, Rounding::Floor).unwrap_err(), overflow());
+    assert_eq!(calculate_value_usd(1, 0, &price(1, 39), 0, Rounding::Floor).unwrap_err(), overflow());
+
+    assert_eq!(calculate_tokens_for_usd(u64::MAX, 0, 30, &price(1, 0), Rounding::Floor).unwrap_err(), overflow());
+    assert_eq!(calculate_tokens_for_usd(1, 0, 39, &price(1, 0), Rounding::Floor).unwrap_err(), overflow());
+}
+
+#[test]
+fn divisors_past_the_table_drop_everything() {
+    // 255 token decimals: 10^-257 USD per base unit
+    let sol = price(150_0000_0000, -8);
+    assert_eq!(calculate_value_usd(u64::MAX, 255, &sol, 6, Rounding::Floor).unwrap(), 0);
+    assert_eq!(calculate_value_usd(u64::MAX, 255, &sol, 6, Rounding::Nearest).unwrap(), 0);
+    assert_eq!(calculate_value_usd(u64::MAX, 255, &sol, 6, Rounding::Ceil).unwrap(), 1);
+
+    // Price × 10^30 exceeds u128
+    let huge = price(i64::MAX, 30);
+    assert_eq!(calculate_tokens_for_usd(u64::MAX, 0, 0, &huge, Rounding::Floor).unwrap(), 0);
+    assert_eq!(calculate_tokens_for_usd(u64::MAX, 0, 0, &huge, Rounding::Ceil).unwrap(), 1);
+}
+
+#[test]
+fn non_positive_prices_are_rejected() {
+    let negative = Error::from(PriceValidationError::NegativePrice);
+    for bad in [price(0, -8), price(-1, -8)] {
+        assert_eq!(calculate_value_usd(1, 9, &bad, 6, Rounding::Floor).unwrap_err(), negative);
+        assert_eq!(calculate_tokens_for_usd(1, 6, 9, &bad, Rounding::Floor).unwrap_err(), negative);
+    }
+}
+
+#[test]
+fn zero_price_fails_validation_under_any_confidence_limit() {
+    // A zero price's confidence saturates at u16::MAX bps, inside this limit
+    let config = ValidationConfig { max_confidence_bps: u64::MAX, ..ValidationConfig::default() };
+    let clock = Clock { unix_timestamp: 1_700_000_000, ..Clock::default() };
+    assert!(validate_price(&price(1, -8), &config, &clock).is_ok());
+    assert_eq!(
+        validate_price(&price(0, -8), &config, &clock).unwrap_err(),
+        Error::from(PriceValidationError::NegativePrice)
+    );
+}
+
+// ============================================================================
+// DERIVED PRICES
+// ============================================================================
+
+#[test]
+fn eth_btc_ratio() {
+    // $3,000 ± $1.50 over $60,000 ± $30: 0.05 ± 0.00005 (5 + 5 bps)
+    let eth = Price { price: 3000_0000_0000, conf: 1_5000_0000, exponent: -8, publish_time: 0 };
+    let btc = Price { price: 60000_0000_0000, conf: 30_0000_0000, exponent: -8, publish_time: 0 };
+
+    let ratio = calculate_price_ratio(&eth, &btc, 8).unwrap();
+    assert_eq!(ratio, with_conf(5_000_000, 5_000, -8));
+    assert_eq!((ratio.safe_price().lower, ratio.safe_price().upper), (4_995_000, 5_005_000));
+}
+
+#[test]
+fn zero_and_negative_divisors_are_errors() {
+    let one = with_conf(1, 0, 0);
+    assert_eq!(one.div(&with_conf(0, 5, -8)), None);
+
+    let eth = Price { price: 3000_0000_0000, conf: 0, exponent: -8, publish_time: 0 };
+    let negative = Error::from(PriceValidationError::NegativePrice);
+    for divisor in [0, -1, i64::MIN] {
+        let divisor = Price { price: divisor, ..eth };
+        assert_eq!(calculate_price_ratio(&eth, &divisor, 8).unwrap_err(), negative);
+    }
+    let below_zero = Price { price: -1, ..eth };
+    assert_eq!(calculate_price_ratio(&below_zero, &eth, 8).unwrap_err(), negative);
+}
+
+#[test]
+fn extreme_mantissas_do_not_wrap() {
+    let top = with_conf(i64::MAX, u64::MAX, 0);
+    let bottom = with_conf(i64::MIN, u64::MAX, 0);
+
+    // Unchanged at the same exponent; one digit dropped with the extra unit
+    assert_eq!(top.rescale_to(0).unwrap(), top);
+    assert_eq!(top.rescale_to(1).unwrap(), with_conf(i64::MAX / 10, u64::MAX / 10 + 2, 1));
+
+    // Products and quotients are truncated to fit, never wrapped
+    let exact = i64::MAX as f64;
+    assert!(covers(&top.mul(&top).unwrap(), exact * exact, 2.0 * relative(&top)));
+    assert!(covers(&bottom.mul(&bottom).unwrap(), exact * exact, 2.0 * relative(&top)));
+    assert!(covers(&top.div(&with_conf(1, 0, 0)).unwrap(), exact, relative(&top)));
+    assert!(covers(&bottom.div(&with_conf(-1, 0, 0)).unwrap(), exact, relative(&top)));
+}
+
+proptest! {
+    #[test]
+    fn quotient_interval_never_narrows(
+        a in any::<i64>().prop_filter("nonzero", |a| *a != 0),
+        a_conf in any::<u64>(),
+        a_exponent in -12i32..=0,
+        b in any::<i64>().prop_filter("nonzero", |b| *b != 0),
+        b_conf in any::<u64>(),
+        b_exponent in -12i32..=0,
+    ) {
+        let (a, b) = (with_conf(a, a_conf, a_exponent), with_conf(b, b_conf, b_exponent));
+        let quotient = a.div(&b).unwrap();
+        prop_assert!(covers(&quotient, as_f64(&a).0 / as_f64(&b).0, relative(&a) + relative(&b)));
+    }
+
+    #[test]
+    fn product_interval_never_narrows(
+        a in any::<i64>().prop_filter("nonzero", |a| *a != 0),
+        a_conf in any::<u64>(),
+        a_exponent in -12i32..=0,
+        b in any::<i64>().prop_filter("nonzero", |b| *b != 0),
+        b_conf in any::<u64>(),
+        b_exponent in -12i32..=0,
+    ) {
+        let (a, b) = (with_conf(a, a_conf, a_exponent), with_conf(b, b_conf, b_exponent));
+        let product = a.mul(&b).unwrap();
+        prop_assert!(covers(&product, as_f64(&a).0 * as_f64(&b).0, relative(&a) + relative(&b)));
+    }
+}
+
+// ============================================================================
+// RESCALING AND TWAP
+// ============================================================================
+
+fn pyth_price(price: i64, conf: u64, exponent: i32) -> Price {
+    Price { price, conf, exponent, publish_time: 0 }
+}
+
+#[test]
+fn rescaling_known_values() {
+    let price = with_conf(12345, 10, -2);
+    // Adding digits is exact; dropping one truncates and widens by a unit
+    assert_eq!(price.rescale_to(-4).unwrap(), with_conf(1_234_500, 1_000, -4));
+    assert_eq!(price.rescale_to(-1).unwrap(), with_conf(1_234, 2, -1));
+    assert_eq!(with_conf(-12345, 0, -2).rescale_to(-1).unwrap(), with_conf(-1_234, 1, -1));
+    // Past 10^38 everything is dropped
+    assert_eq!(with_conf(5, 3, 0).rescale_to(40).unwrap(), with_conf(0, 2, 40));
+
+    assert_eq!(with_conf(i64::MAX, 0, 0).rescale_to(-1).unwrap_err(), overflow());
+    assert_eq!(with_conf(1, u64::MAX, 0).rescale_to(-1).unwrap_err(), overflow());
+    assert_eq!(with_conf(1, 0, 0).rescale_to(-39).unwrap_err(), overflow());
+}
+
+#[test]
+fn twap_brings_legs_to_the_finer_exponent() {
+    // $150.00 ± $0.10 at 10^-2 and $149.50 ± $0.20 at 10^-8, weighted equally
+    let spot = pyth_price(15_000, 10, -2);
+    let ema = pyth_price(149_5000_0000, 2000_0000, -8);
+
+    assert_eq!(calculate_twap(&spot, &ema, 5_000).unwrap(), with_conf(149_7500_0000, 1500_0000, -8));
+    assert_eq!(calculate_twap(&ema, &spot, 5_000).unwrap(), with_conf(149_7500_0000, 1500_0000, -8));
+
+    // A leg that cannot take the finer exponent fails instead of wrapping
+    let huge = pyth_price(i64::MAX, 0, 0);
+    assert_eq!(calculate_twap(&huge, &pyth_price(1, 0, -1), 5_000).unwrap_err(), overflow());
+}
+
+#[test]
+fn twap_weight_bounds() {
+    let spot = pyth_price(15_000, 10, -2);
+    let ema = pyth_price(149_5000_0000, 2000_0000, -8);
+
+    assert_eq!(calculate_twap(&spot, &ema, 0).unwrap(), with_conf(149_5000_0000, 2000_0000, -8));
+    assert_eq!(calculate_twap(&spot, &ema, 10_000).unwrap(), with_conf(150_0000_0000, 1000_0000, -8));
+    assert_eq!(
+        calculate_twap(&spot, &ema, 10_001).unwrap_err(),
+        Error::from(PriceValidationError::InvalidWeight)
+    );
+}
+
+proptest! {
+    #[test]
+    fn twap_weighted_mean_fits(
+        spot in any::<i64>(),
+        spot_conf in any::<u64>(),
+        ema in any::<i64>(),
+        ema_conf in any::<u64>(),
+        weight in 0u16..=10_000,
+    ) {
+        let twap = calculate_twap(&pyth_price(spot, spot_conf, -8), &pyth_price(ema, ema_conf, -8), weight).unwrap();
+        let exact = (spot as i128 * weight as i128 + ema as i128 * (10_000 - weight) as i128) / 10_000;
+        prop_assert_eq!(twap.price as i128, exact);
+        prop_assert!(spot.min(ema) <= twap.price && twap.price <= spot.max(ema));
+        prop_assert!(spot_conf.min(ema_conf) <= twap.conf && twap.conf <= spot_conf.max(ema_conf));
+    }
+}
+
+// ============================================================================
+// PARITY WITH THE TEMPLATE
+// ============================================================================
+
+#[test]
+fn confidence_and_fees_round_up() {
+    // 1 over 3 is 3,333.3 bps; a 30 bps fee on 1,001 is 3.003
+    let third = Price { price: 3, conf: 1, exponent: 0, publish_time: 0 };
+    assert_eq!(safe_math::confidence_bps(&third).unwrap(), 3_334);
+    assert_eq!(pyth_oracle::confidence_bps(3, 1).unwrap(), 3_334);
+    assert_eq!(safe_math::deduct_fee(1_001, 30), Some((997, 4)));
+    assert_eq!(pyth_oracle::deduct_fee(1_001, 30).unwrap(), (997, 4));
+}
+
+proptest! {
+    #[test]
+    fn confidence_bps_matches_the_template(price in any::<i64>(), conf in any::<u64>()) {
+        let standalone = safe_math::confidence_bps(&Price { price, conf, exponent: -8, publish_time: 0 }).unwrap();
+        prop_assert_eq!(standalone, pyth_oracle::confidence_bps(price, conf).unwrap());
+    }
+
+    #[test]
+    fn dynamic_fee_matches_the_template(
+        conf_bps in any::<u16>(),
+        base_fee_bps in any::<u16>(),
+        confidence_multiplier_bps in any::<u32>(),
+        max_fee_bps in any::<u16>(),
+    ) {
+        let standalone = DynamicFeeConfig { base_fee_bps, confidence_multiplier_bps, max_fee_bps };
+        let template = pyth_oracle::DynamicFeeConfig { base_fee_bps, confidence_multiplier_bps, max_fee_bps };
+        prop_assert_eq!(
+            safe_math::dynamic_fee_bps(conf_bps, &standalone),
+            pyth_oracle::dynamic_fee_bps(conf_bps, &template)
+        );
+    }
+
+    #[test]
+    fn deduct_fee_matches_the_template(amount in any::<u64>(), fee_bps in any::<u16>()) {
+        prop_assert_eq!(safe_math::deduct_fee(amount, fee_bps), pyth_oracle::deduct_fee(amount, fee_bps).ok());
+    }
+}
+
+proptest! {
+    #[test]
+    fn conversions_never_panic(
+        amount in any::<u64>(),
+        decimals in any::<u8>(),
+        usd_decimals in any::<u8>(),
+        mantissa in any::<i64>(),
+        exponent in any::<i32>(),
+    ) {
+        let price = price(mantissa, exponent);
+        let _ = calculate_value_usd(amount, decimals, &price, usd_decimals, Rounding::Ceil);
+        let _ = calculate_tokens_for_usd(amount, usd_decimals, decimals, &price, Rounding::Ceil);
+    }
+
+    #[test]
+    fn token_round_trip_never_gains(
+        amount in any::<u64>(),
+        decimals in 0u8..=18,
+        mantissa in 1i64..=1_000_000_000_000,
+        exponent in -12i32..=4,
+    ) {
+        let price = price(mantissa, exponent);
+        if let Ok(usd) = calculate_value_usd(amount, decimals, &price, 6, Rounding::Floor) {
+            let back = calculate_tokens_for_usd(usd, 6, decimals, &price, Rounding::Floor).unwrap();
+            prop_assert!(back <= amount);
+        }
+    }
+}
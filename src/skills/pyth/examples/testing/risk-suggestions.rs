@@ -0,0 +1,119 @@
+/**
+ * Tests for the Volatility-Based Risk Parameter Suggestions
+ *
+ * Runs risk-suggestions.rs on hand-made series with known volatility:
+ * rolling windows pick up a volatile stretch, the margin and breaker
+ * bands follow from the peak volatility, and the confidence limit stays
+ * within half the margin. Nothing here reads a store or calls Benchmarks.
+ *
+ * Setup:
+ * 1. Copy this file to `tests/risk_suggestions.rs` of the program crate
+ * 2. Run: cargo test --test risk_suggestions --features hermes
+ */
+
+use pyth_oracle::price_history::PriceSeries;
+use pyth_oracle::risk_suggestions::{suggest_risk_parameters, SuggestionConfig};
+use pyth_oracle::TripAction;
+
+const FEED: [u8; 32] = [7; 32];
+const HOUR: i64 = 3_600;
+const HOURS_PER_YEAR: f64 = 365.0 * 24.0;
+
+/// Hourly prices from log returns `returns`, starting at $100 with
+/// confidence `conf_bps` of each price
+fn series(returns: &[f64], conf_bps: u64) -> PriceSeries {
+    let mut log_price = 100f64.ln();
+    let mut prices = vec![100_0000_0000i64];
+    for r in returns {
+        log_price += r;
+        prices.push((log_price.exp() * 1e8).round() as i64);
+    }
+    PriceSeries {
+        feed_id: FEED,
+        publish_time: (0..prices.len() as i64).map(|i| i * HOUR).collect(),
+        conf: prices.iter().map(|&price| price as u64 * conf_bps / 10_000).collect(),
+        exponent: vec![-8; prices.len()],
+        price: prices,
+    }
+}
+
+/// Alternating returns of ±`r`: sample standard deviation about r
+fn alternating(r: f64, count: usize) -> Vec<f64> {
+    (0..count).map(|i| if i % 2 == 0 { r } else { -r }).collect()
+}
+
+fn config(window_intervals: usize) -> SuggestionConfig {
+    SuggestionConfig { interval_secs: HOUR, window_intervals, ..SuggestionConfig::default() }
+}
+
+// ============================================================================
+// ROLLING VOLATILITY
+// ============================================================================
+
+#[test]
+fn rolling_windows_find_the_volatile_stretch() {
+    let mut returns = alternating(0.001, 24);
+    returns.extend(alternating(0.01, 24));
+    returns.extend(alternating(0.001, 24));
+    let series = series(&returns, 10);
+
+    let rolling = series.rolling_volatility(HOUR, 24);
+    assert_eq!(rolling.len(), 72 - 24 + 1);
+    assert_eq!(rolling[0].end_time, 24 * HOUR);
+
+    // The window covering exactly the volatile stretch is the peak
+    let peak = rolling.iter().max_by(|a, b| a.volatility.total_cmp(&b.volatility)).unwrap();
+    assert_eq!(peak.end_time, 48 * HOUR);
+    let expected = 0.01 * (24.0f64 / 23.0).sqrt() * HOURS_PER_YEAR.sqrt();
+    assert!((peak.volatility - expected).abs() < expected * 1e-3);
+}
+
+#[test]
+fn too_short_a_series_has_no_windows() {
+    let series = series(&alternating(0.01, 10), 10);
+    assert_eq!(series.rolling_volatility(HOUR, 10).len(), 1);
+    assert!(series.rolling_volatility(HOUR, 11).is_empty());
+    assert_eq!(suggest_risk_parameters(&series, &config(11)), None);
+    assert_eq!(suggest_risk_parameters(&PriceSeries::default(), &config(2)), None);
+}
+
+// ============================================================================
+// SUGGESTIONS
+// ============================================================================
+
+#[test]
+fn bands_follow_the_peak_volatility() {
+    // 1% hourly moves: 3 sigma over 4 hours is a 6% fall, 8 sigma over 5
+    // minutes a 2.3% rise
+    let series = series(&alternating(0.01, 48), 10);
+    let suggestion = suggest_risk_parameters(&series, &config(48)).unwrap();
+
+    let hourly = 0.01 * (48.0f64 / 47.0).sqrt();
+    let fall = 1.0 - (-3.0 * hourly * 4f64.sqrt()).exp();
+    let rise = (8.0 * hourly / 12f64.sqrt()).exp_m1();
+    assert_eq!(suggestion.maintenance_margin_bps, (fall * 10_000.0).ceil() as u16);
+    assert_eq!(suggestion.breaker.max_deviation_bps, (rise * 10_000.0).ceil() as u64);
+    assert_eq!(suggestion.breaker.window_secs, 300);
+    assert_eq!(suggestion.breaker.on_trip, TripAction::Restrict);
+    assert_eq!(suggestion.updates, 49);
+
+    // 10 bps confidence with 25% headroom
+    assert_eq!(suggestion.max_confidence_bps, 13);
+}
+
+#[test]
+fn confidence_limit_stays_within_half_the_margin() {
+    // Calm prices quoted with a wide band: the band is capped
+    let series = series(&alternating(0.0005, 48), 500);
+    let suggestion = suggest_risk_parameters(&series, &config(48)).unwrap();
+    assert!(suggestion.maintenance_margin_bps < 1_000);
+    assert_eq!(suggestion.max_confidence_bps, (suggestion.maintenance_margin_bps / 2) as u64);
+}
+
+#[test]
+fn extreme_volatility_keeps_bands_valid() {
+    let series = series(&alternating(1.5, 48), 10);
+    let suggestion = suggest_risk_parameters(&series, &config(48)).unwrap();
+    assert!(suggestion.maintenance_margin_bps <= 10_000);
+    assert_eq!(suggestion.breaker.max_deviation_bps, 9_999);
+}
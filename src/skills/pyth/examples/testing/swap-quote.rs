@@ -0,0 +1,237 @@
+/**
+ * Tests for the Swap Quote and `min_amount_out`
+ *
+ * Known values of `quote_swap` for 1 SOL into USDC, the worst case with
+ * widened confidence and the slippage on top of it, the fee of the output
+ * market, and the rejection of a price its market would refuse or of two
+ * prices too far apart or too uncertain together. Every expected amount
+ * is worked out by hand from the program's rounding: input value and
+ * output floored, fee rounded up.
+ *
+ * Setup:
+ * 1. Copy this file to `tests/swap_quote.rs` of the program crate
+ * 2. Run: cargo test --test swap_quote --features hermes
+ */
+
+use anchor_lang::error::Error;
+use anchor_lang::prelude::Pubkey;
+use pyth_oracle::hermes::{ParsedPriceUpdate, PriceUpdateMetadata};
+use pyth_oracle::swap_quote::{min_amount_out, SlippageTolerance};
+use pyth_oracle::{
+    quote_swap, BreakerConfig, BreakerState, Market, OracleConfigParams, OracleError, OraclePrice, PairLimits,
+    PauseFlags, RiskParamsConfig, SwapQuote, TripAction, ValidatedPrice, DEFAULT_SWAP_FEE,
+};
+use pyth_solana_receiver_sdk::price_update::{Price, VerificationLevel};
+
+const SOL_FEED: [u8; 32] = [1; 32];
+const USDC_FEED: [u8; 32] = [2; 32];
+const ONE_SOL: u64 = 1_000_000_000;
+
+/// Pair limits of every test market: 10 s apart, 300 bps together
+const PAIR_LIMITS: PairLimits = PairLimits { max_skew_secs: 10, max_combined_confidence_bps: 300 };
+
+fn update(feed_id: [u8; 32], price: i64, conf: u64) -> ParsedPriceUpdate {
+    let price = Price { price, conf, exponent: -8, publish_time: 1_700_000_000 };
+    ParsedPriceUpdate {
+        feed_id,
+        price,
+        ema_price: price,
+        metadata: PriceUpdateMetadata { slot: None, proof_available_time: None, prev_publish_time: None },
+    }
+}
+
+/// SOL at $150 ± $0.15 (10 bps)
+fn sol() -> ParsedPriceUpdate {
+    update(SOL_FEED, 150_0000_0000, 1500_0000)
+}
+
+/// USDC at $1 ± $0.0001 (1 bp)
+fn usdc() -> ParsedPriceUpdate {
+    update(USDC_FEED, 1_0000_0000, 1_0000)
+}
+
+/// Market of `feed_id` with a 1% confidence limit, `PAIR_LIMITS`, and the
+/// default fee
+fn market(feed_id: [u8; 32], decimals: u8) -> Market {
+    Market {
+        mint: Pubkey::default(),
+        decimals,
+        feed_id,
+        oracle: OracleConfigParams {
+            max_age_secs: 30,
+            max_confidence_bps: 100,
+            verification_level: VerificationLevel::Full,
+        },
+        pair: PAIR_LIMITS,
+        risk: RiskParamsConfig {
+            max_ltv_bps: 7_500,
+            liquidation_threshold_bps: 8_000,
+            liquidation_bonus_bps: 500,
+            close_factor_bps: 5_000,
+            borrow_cap: 0,
+        },
+        swap_fee: DEFAULT_SWAP_FEE,
+        breaker: BreakerState::new(BreakerConfig {
+            max_deviation_bps: 500,
+            window_secs: 60,
+            on_trip: TripAction::Reject,
+        }),
+        paused: PauseFlags::NONE,
+        total_borrowed: 0,
+        has_interest_market: false,
+        pending_change: None,
+        bump: 255,
+    }
+}
+
+fn sol_market() -> Market {
+    market(SOL_FEED, 9)
+}
+
+fn usdc_market() -> Market {
+    market(USDC_FEED, 6)
+}
+
+fn validated(update: &ParsedPriceUpdate) -> ValidatedPrice {
+    ValidatedPrice::from_price(update.feed_id, &OraclePrice::from(update.price))
+}
+
+// ============================================================================
+// QUOTES
+// ============================================================================
+
+#[test]
+fn expected_output_matches_the_program() {
+    let quote = min_amount_out(ONE_SOL, &sol(), &sol_market(), &usdc(), &usdc_market(), &SlippageTolerance::default())
+        .unwrap();
+
+    // $149.85 / $1.0001, less 30 + 2 × 10 bps
+    let expected = SwapQuote {
+        input_usd: 149_850_000,
+        gross_out: 149_835_016,
+        conf_bps: 10,
+        fee_bps: 50,
+        fee: 749_176,
+        amount_out: 149_085_840,
+    };
+    assert_eq!(quote.expected, expected);
+    assert_eq!(
+        quote_swap(ONE_SOL, &validated(&sol()), 9, &validated(&usdc()), 6, &DEFAULT_SWAP_FEE),
+        Ok(expected)
+    );
+}
+
+#[test]
+fn worst_case_widens_confidence_then_takes_slippage() {
+    let quote = min_amount_out(ONE_SOL, &sol(), &sol_market(), &usdc(), &usdc_market(), &SlippageTolerance::default())
+        .unwrap();
+
+    // SOL ± $0.30 and USDC ± $0.0011: $149.70 / $1.0011, less 30 + 2 × 20 bps
+    assert_eq!(quote.worst_case.input_usd, 149_700_000);
+    assert_eq!(quote.worst_case.gross_out, 149_535_510);
+    assert_eq!(quote.worst_case.fee_bps, 70);
+    assert_eq!(quote.worst_case.amount_out, 148_488_761);
+    // Less 0.5%, rounded down
+    assert_eq!(quote.min_amount_out, 147_746_317);
+}
+
+#[test]
+fn zero_tolerance_is_the_expected_output() {
+    let tolerance = SlippageTolerance { slippage_bps: 0, confidence_widening_bps: 0 };
+    let quote = min_amount_out(ONE_SOL, &sol(), &sol_market(), &usdc(), &usdc_market(), &tolerance).unwrap();
+    assert_eq!(quote.worst_case, quote.expected);
+    assert_eq!(quote.min_amount_out, quote.expected.amount_out);
+}
+
+#[test]
+fn output_market_fee_is_charged() {
+    let mut usdc_market = usdc_market();
+    usdc_market.swap_fee.base_fee_bps = 100;
+    let quote =
+        min_amount_out(ONE_SOL, &sol(), &sol_market(), &usdc(), &usdc_market, &SlippageTolerance::default()).unwrap();
+
+    // 100 + 2 × 10 bps of the same gross output
+    assert_eq!(quote.expected.gross_out, 149_835_016);
+    assert_eq!(quote.expected.fee_bps, 120);
+}
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[test]
+fn prices_the_swap_would_reject_fail_here() {
+    let tolerance = SlippageTolerance::default();
+
+    // 2% is over the market's 1%
+    let wide = update(SOL_FEED, 150_0000_0000, 3_0000_0000);
+    assert_eq!(
+        min_amount_out(ONE_SOL, &wide, &sol_market(), &usdc(), &usdc_market(), &tolerance).unwrap_err(),
+        Error::from(OracleError::ConfidenceTooHigh)
+    );
+
+    // Allowed by a market with a wider limit
+    let mut wide_market = sol_market();
+    wide_market.oracle.max_confidence_bps = 300;
+    assert!(min_amount_out(ONE_SOL, &wide, &wide_market, &usdc(), &usdc_market(), &tolerance).is_ok());
+
+    let zero = update(USDC_FEED, 0, 0);
+    assert_eq!(
+        min_amount_out(ONE_SOL, &sol(), &sol_market(), &zero, &usdc_market(), &tolerance).unwrap_err(),
+        Error::from(OracleError::ZeroPrice)
+    );
+
+    // The SOL update quoted as the USDC leg
+    assert_eq!(
+        min_amount_out(ONE_SOL, &sol(), &sol_market(), &sol(), &usdc_market(), &tolerance).unwrap_err(),
+        Error::from(OracleError::FeedIdMismatch)
+    );
+}
+
+#[test]
+fn prices_must_pass_as_a_pair() {
+    let tolerance = SlippageTolerance::default();
+
+    // Published one second more apart than the pair allows
+    let mut late = usdc();
+    late.price.publish_time += PAIR_LIMITS.max_skew_secs as i64 + 1;
+    assert_eq!(
+        min_amount_out(ONE_SOL, &sol(), &sol_market(), &late, &usdc_market(), &tolerance).unwrap_err(),
+        Error::from(OracleError::PricesNotSynchronized)
+    );
+    late.price.publish_time -= 1;
+    assert!(min_amount_out(ONE_SOL, &sol(), &sol_market(), &late, &usdc_market(), &tolerance).is_ok());
+
+    // 250 + 60 bps: each within its market's limit, together over 300
+    let mut wide_market = sol_market();
+    wide_market.oracle.max_confidence_bps = 300;
+    let wide_sol = update(SOL_FEED, 150_0000_0000, 3_7500_0000);
+    let wide_usdc = update(USDC_FEED, 1_0000_0000, 60_0000);
+    assert_eq!(
+        min_amount_out(ONE_SOL, &wide_sol, &wide_market, &wide_usdc, &usdc_market(), &tolerance).unwrap_err(),
+        Error::from(OracleError::ConfidenceTooHigh)
+    );
+    let narrower_usdc = update(USDC_FEED, 1_0000_0000, 50_0000);
+    assert!(min_amount_out(ONE_SOL, &wide_sol, &wide_market, &narrower_usdc, &usdc_market(), &tolerance).is_ok());
+}
+
+#[test]
+fn tighter_market_sets_the_pair_limits() {
+    let tolerance = SlippageTolerance::default();
+    let mut strict_usdc = usdc_market();
+    strict_usdc.pair.max_skew_secs = 2;
+
+    // 3 s apart is within the SOL market's 10 but not the USDC market's 2,
+    // whichever side of the swap it is on
+    let mut late = usdc();
+    late.price.publish_time += 3;
+    assert!(min_amount_out(ONE_SOL, &sol(), &sol_market(), &late, &usdc_market(), &tolerance).is_ok());
+    assert_eq!(
+        min_amount_out(ONE_SOL, &sol(), &sol_market(), &late, &strict_usdc, &tolerance).unwrap_err(),
+        Error::from(OracleError::PricesNotSynchronized)
+    );
+    assert_eq!(
+        min_amount_out(1_000_000, &late, &strict_usdc, &sol(), &sol_market(), &tolerance).unwrap_err(),
+        Error::from(OracleError::PricesNotSynchronized)
+    );
+}
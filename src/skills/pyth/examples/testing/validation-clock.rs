@@ -0,0 +1,110 @@
+/**
+ * Unit Tests for Validation with an Injected Clock
+ *
+ * Staleness checks read the time through `Now`, so `get_validated_price`
+ * and the helpers built on it run against a `MockClock` with no validator,
+ * LiteSVM, or sysvar. These cover the age boundary in seconds, slot age,
+ * the failure context of a stale price, and price cache freshness as the
+ * clock moves.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml of the program crate:
+ *    [dev-dependencies]
+ *    pyth-oracle = { path = ".", features = ["no-entrypoint", "test-utils"] }
+ *
+ * 2. Copy this file to `tests/validation-clock.rs`
+ * 3. Run: cargo test --test validation-clock
+ */
+
+use anchor_lang::error::Error;
+use pyth_oracle::test_utils::{PriceUpdateBuilder, DEFAULT_TEST_TIMESTAMP};
+use pyth_oracle::{
+    get_validated_price, get_validated_price_with_context, parse_feed_id, price_feeds, MockClock, OracleError,
+    PriceCache, PriceValidationConfig, DEFAULT_MAX_PRICE_AGE,
+};
+use pyth_solana_receiver_sdk::price_update::VerificationLevel;
+
+const SLOT: u64 = 250_000_000;
+
+fn sol_feed() -> [u8; 32] {
+    parse_feed_id(price_feeds::SOL_USD).unwrap()
+}
+
+fn clock() -> MockClock {
+    MockClock::new(DEFAULT_TEST_TIMESTAMP, SLOT)
+}
+
+fn sol_update() -> PriceUpdateBuilder {
+    PriceUpdateBuilder::new(sol_feed())
+        .with_price(150_0000_0000, -8)
+        .with_confidence_bps(10)
+        .posted_at_slot(SLOT)
+}
+
+// ============================================================================
+// STALENESS
+// ============================================================================
+
+#[test]
+fn price_expires_exactly_after_max_age() {
+    let update = sol_update().build();
+    let config = PriceValidationConfig::default();
+    let mut clock = clock();
+
+    clock.advance_secs(DEFAULT_MAX_PRICE_AGE);
+    assert!(get_validated_price(&update, &config, &clock).is_ok());
+
+    clock.advance_secs(1);
+    assert!(get_validated_price(&update, &config, &clock).is_err());
+}
+
+#[test]
+fn slot_age_is_checked_even_when_the_timestamp_lags() {
+    let update = sol_update().build();
+    let config = PriceValidationConfig::default().with_max_age_slots(25);
+    let mut clock = clock();
+
+    clock.advance_slots(25);
+    assert!(get_validated_price(&update, &config, &clock).is_ok());
+
+    clock.advance_slots(1);
+    let error = get_validated_price(&update, &config, &clock).unwrap_err();
+    assert_eq!(error, Error::from(OracleError::PriceTooStale));
+}
+
+#[test]
+fn stale_failure_carries_the_age_and_confidence() {
+    let update = sol_update().stale_by(74).build();
+    let config = PriceValidationConfig::default();
+
+    let failure = get_validated_price_with_context(&update, &config, &clock()).unwrap_err();
+    assert_eq!(failure.feed_id, sol_feed());
+    assert_eq!(failure.age, Some(74));
+    assert_eq!(failure.conf_bps, Some(10));
+    assert_eq!((failure.max_age_secs, failure.max_confidence_bps), (DEFAULT_MAX_PRICE_AGE, config.max_confidence_bps));
+}
+
+// ============================================================================
+// PRICE CACHE
+// ============================================================================
+
+#[test]
+fn price_cache_is_fresh_for_one_slot_only() {
+    let cache = PriceCache {
+        feed_id: sol_feed(),
+        price: 150_0000_0000,
+        conf: 1500_0000,
+        exponent: -8,
+        publish_time: DEFAULT_TEST_TIMESTAMP,
+        posted_slot: SLOT,
+        slot: SLOT,
+        verification_level: VerificationLevel::Full,
+        bump: 255,
+    };
+    let mut clock = clock();
+    assert!(cache.is_fresh(&clock));
+    assert!(get_validated_price(&cache, &PriceValidationConfig::default(), &clock).is_ok());
+
+    clock.advance_slots(1);
+    assert!(!cache.is_fresh(&clock));
+}
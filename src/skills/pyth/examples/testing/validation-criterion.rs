@@ -0,0 +1,159 @@
+/**
+ * Criterion Benchmarks for the Off-Chain Validation Path
+ *
+ * Keepers, indexers, and simulators run the same validation and decimal
+ * helpers as the program, thousands of times per second. These benchmarks
+ * cover the hot path: the confidence check, USD valuation and price ratios
+ * (both the u64 fast path and amounts that need u128 division), a full
+ * `get_validated_price` over a `PriceUpdateView`, and
+ * `load_remaining_prices` for 1, 4, and 8 feeds.
+ *
+ * Host timings do not predict compute units: SBF has no native u128
+ * division, so the gap between the fast and slow paths is wider on chain.
+ * Use compute-units-bench.rs for on-chain costs; use this to catch
+ * regressions in the helpers themselves.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml of the program crate:
+ *    [dev-dependencies]
+ *    criterion = "0.5"
+ *    pyth-oracle = { path = ".", features = ["no-entrypoint", "test-utils"] }
+ *
+ *    [[bench]]
+ *    name = "validation"
+ *    harness = false
+ *
+ * 2. Copy this file to `benches/validation.rs`
+ * 3. Run: cargo bench --bench validation
+ *    (save a baseline with `-- --save-baseline before`, then compare
+ *    after a change with `-- --baseline before`)
+ */
+
+use std::hint::black_box;
+
+use anchor_lang::prelude::{AccountInfo, Pubkey};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use pyth_oracle::test_utils::{PriceUpdateBuilder, DEFAULT_TEST_TIMESTAMP};
+use pyth_oracle::{
+    calculate_price_ratio, calculate_usd_value, get_validated_price, load_remaining_prices, validate_confidence,
+    MockClock, OraclePrice, PriceUpdateView, PriceValidationConfig, Rounding, PYTH_RECEIVER_PROGRAM_ID,
+};
+
+fn clock() -> MockClock {
+    MockClock::new(DEFAULT_TEST_TIMESTAMP, 0)
+}
+
+fn sol_price() -> OraclePrice {
+    OraclePrice {
+        price: 150_0000_0000,
+        conf: 7_500_000,
+        exponent: -8,
+        publish_time: DEFAULT_TEST_TIMESTAMP,
+    }
+}
+
+// ============================================================================
+// PRICE CHECKS
+// ============================================================================
+
+fn confidence(c: &mut Criterion) {
+    let price = sol_price();
+    c.bench_function("validate_confidence", |b| {
+        b.iter(|| validate_confidence(black_box(&price), black_box(100)))
+    });
+}
+
+fn validated_price(c: &mut Criterion) {
+    let data = PriceUpdateBuilder::new([1; 32]).with_price(150_0000_0000, -8).to_account_data();
+    let config = PriceValidationConfig::default();
+    let clock = clock();
+
+    c.bench_function("get_validated_price (PriceUpdateView)", |b| {
+        b.iter(|| {
+            let view = PriceUpdateView::parse(black_box(&data)).unwrap();
+            get_validated_price(&view, &config, &clock)
+        })
+    });
+}
+
+// ============================================================================
+// DECIMAL MATH
+// ============================================================================
+
+fn valuation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("calculate_usd_value");
+    // lamports × price mantissa: 0.1 SOL stays below u64::MAX, 2,000 SOL
+    // does not
+    for (name, amount) in [("u64 path", 100_000_000u64), ("u128 path", 2_000_000_000_000)] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                calculate_usd_value(black_box(amount), 9, black_box(150_0000_0000), -8, Rounding::Floor)
+            })
+        });
+    }
+    group.finish();
+
+    c.bench_function("calculate_price_ratio", |b| {
+        b.iter(|| {
+            calculate_price_ratio(
+                black_box(3_000_0000_0000),
+                -8,
+                black_box(60_000_0000_0000),
+                -8,
+                6,
+                Rounding::Floor,
+            )
+        })
+    });
+}
+
+// ============================================================================
+// MULTI-FEED
+// ============================================================================
+
+struct FeedAccount {
+    key: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+}
+
+fn multi_feed(c: &mut Criterion) {
+    let config = PriceValidationConfig::default();
+    let clock = clock();
+    let mut group = c.benchmark_group("load_remaining_prices");
+
+    for feeds in [1u8, 4, 8] {
+        let feed_ids: Vec<[u8; 32]> = (1..=feeds).map(|i| [i; 32]).collect();
+        let mut backing: Vec<FeedAccount> = feed_ids
+            .iter()
+            .map(|feed_id| FeedAccount {
+                key: Pubkey::new_unique(),
+                lamports: 1,
+                data: PriceUpdateBuilder::new(*feed_id).to_account_data(),
+            })
+            .collect();
+        let accounts: Vec<AccountInfo> = backing
+            .iter_mut()
+            .map(|account| {
+                AccountInfo::new(
+                    &account.key,
+                    false,
+                    false,
+                    &mut account.lamports,
+                    &mut account.data,
+                    &PYTH_RECEIVER_PROGRAM_ID,
+                    false,
+                    0,
+                )
+            })
+            .collect();
+
+        group.bench_with_input(BenchmarkId::from_parameter(feeds), &accounts, |b, accounts| {
+            b.iter(|| load_remaining_prices(black_box(accounts), &feed_ids, &config, &clock).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, confidence, validated_price, valuation, multi_feed);
+criterion_main!(benches);
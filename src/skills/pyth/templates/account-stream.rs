@@ -0,0 +1,285 @@
+/**
+ * On-Chain Price Account Subscriber (Off-Chain)
+ *
+ * Some operators would rather watch the receiver's price accounts than a
+ * Hermes endpoint: the accounts are what programs read, so a keeper
+ * watching the sponsored push feeds acts on exactly the price the program
+ * will see, without trailing it by the push delay.
+ *
+ * `AccountWatcher::subscribe_price_updates` subscribes to each account with
+ * WebSocket `accountSubscribe`, reads the price fields straight from the
+ * account data with `PriceUpdateView` (no Borsh decode of the whole
+ * account), and yields the same `StreamedUpdate` items as
+ * `HermesClient::subscribe_price_updates` in hermes-stream.rs, so a keeper
+ * switches sources by changing which one it calls. The differences:
+ *
+ *   - `update_data` is empty: the price is already on-chain, there is no
+ *     VAA to post. Keep the pusher on Hermes.
+ *   - `metadata.slot` is the slot the receiver wrote the update in, and
+ *     `proof_available_time` is unknown.
+ *   - Only fully verified updates are yielded; partially verified ones and
+ *     accounts not owned by the receiver are yielded as `Decode` errors.
+ *
+ * On every (re)connect the accounts are read once over RPC, after
+ * subscribing so no write falls in between. After a reconnect, what
+ * changed while disconnected is yielded as `backfilled`. An account holds
+ * only its latest update, so publish times it skipped during an outage are
+ * gone; `max_backfill_secs` does not apply. Reconnects follow the backoff
+ * and attempt limit of `StreamConfig`.
+ *
+ * Geyser: `price_updates_from_accounts` turns any stream of
+ * `AccountChange`s into the same items. Map the account messages of a
+ * Yellowstone gRPC subscription (filtered on the account keys, or on the
+ * receiver as owner) to `AccountChange` and it plugs in unchanged.
+ *
+ * Setup:
+ * 1. In addition to the hermes-stream.rs and price-accounts.rs dependencies:
+ *    [dependencies]
+ *    solana-account-decoder = { version = "1.18", optional = true }
+ *
+ *    [features]
+ *    client = [..., "dep:solana-account-decoder"]
+ *
+ * 2. Declared by anchor-oracle.rs behind the `client` and `hermes` features
+ */
+
+use futures::stream::{self, Stream, StreamExt};
+use pyth_solana_receiver_sdk::price_update::{Price, VerificationLevel};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::hermes::{HermesError, HermesResult, ParsedPriceUpdate, PriceUpdateMetadata, PriceUpdates};
+use crate::hermes_stream::{StreamConfig, StreamedUpdate, Watermarks};
+use crate::price_accounts::MAX_ACCOUNTS_PER_REQUEST;
+use crate::{PriceUpdateView, PYTH_RECEIVER_PROGRAM_ID};
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// One write to a watched account, from any subscription
+#[derive(Clone, Debug)]
+pub struct AccountChange {
+    pub address: Pubkey,
+    pub account: Account,
+    /// Slot of the notification or read
+    pub slot: u64,
+}
+
+/// Read one account change as a price update
+pub fn decode_account_change(change: &AccountChange) -> HermesResult<ParsedPriceUpdate> {
+    let invalid = || HermesError::Decode {
+        field: "price update account",
+        value: change.address.to_string(),
+    };
+    if change.account.owner != PYTH_RECEIVER_PROGRAM_ID {
+        return Err(invalid());
+    }
+    let view = PriceUpdateView::parse(&change.account.data).map_err(|_| invalid())?;
+    if !view.verification_level.gte(VerificationLevel::Full) {
+        return Err(invalid());
+    }
+
+    let price = |price, conf| Price {
+        price,
+        conf,
+        exponent: view.exponent,
+        publish_time: view.publish_time,
+    };
+    Ok(ParsedPriceUpdate {
+        feed_id: view.feed_id,
+        price: price(view.price, view.conf),
+        ema_price: price(view.ema_price, view.ema_conf),
+        metadata: PriceUpdateMetadata {
+            slot: Some(view.posted_slot),
+            proof_available_time: None,
+            prev_publish_time: Some(view.prev_publish_time),
+        },
+    })
+}
+
+/// The stream item for `change`, if it decodes to a newer update of its feed
+fn accept(
+    watermarks: &mut Watermarks,
+    change: &AccountChange,
+    backfilled: bool,
+) -> Option<HermesResult<StreamedUpdate>> {
+    let parsed = match decode_account_change(change) {
+        Ok(parsed) => parsed,
+        Err(e) => return Some(Err(e)),
+    };
+    let updates = PriceUpdates {
+        update_data: Vec::new(),
+        parsed: vec![parsed],
+    };
+    watermarks
+        .retain_new(updates)
+        .map(|updates| Ok(StreamedUpdate { updates, backfilled }))
+}
+
+/// Price updates from a stream of account changes (WebSocket, Geyser, or
+/// replayed), each feed's publish times strictly increasing
+pub fn price_updates_from_accounts(
+    changes: impl Stream<Item = AccountChange>,
+) -> impl Stream<Item = HermesResult<StreamedUpdate>> {
+    let mut watermarks = Watermarks::default();
+    changes.filter_map(move |change| {
+        let item = accept(&mut watermarks, &change, false);
+        async move { item }
+    })
+}
+
+fn subscription_error(error: impl std::fmt::Display) -> HermesError {
+    HermesError::Subscription(error.to_string())
+}
+
+// ============================================================================
+// SUBSCRIBER
+// ============================================================================
+
+/// Watches price update accounts over an RPC node's WebSocket endpoint
+#[derive(Clone, Debug)]
+pub struct AccountWatcher {
+    rpc_url: String,
+    ws_url: String,
+    commitment: CommitmentConfig,
+}
+
+impl AccountWatcher {
+    /// At `confirmed` commitment
+    pub fn new(rpc_url: impl Into<String>, ws_url: impl Into<String>) -> Self {
+        Self {
+            rpc_url: rpc_url.into(),
+            ws_url: ws_url.into(),
+            commitment: CommitmentConfig::confirmed(),
+        }
+    }
+
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    fn account_config(&self) -> RpcAccountInfoConfig {
+        RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(self.commitment),
+            ..RpcAccountInfoConfig::default()
+        }
+    }
+
+    /// One merged stream of changes to every account
+    async fn subscribe_all<'a>(
+        &self,
+        pubsub: &'a PubsubClient,
+        addresses: &[Pubkey],
+    ) -> HermesResult<impl Stream<Item = AccountChange> + Unpin + 'a> {
+        let mut subscriptions = Vec::with_capacity(addresses.len());
+        for &address in addresses {
+            let (notifications, _unsubscribe) = pubsub
+                .account_subscribe(&address, Some(self.account_config()))
+                .await
+                .map_err(subscription_error)?;
+            subscriptions.push(Box::pin(notifications.filter_map(move |response| {
+                let change = response.value.decode::<Account>().map(|account| AccountChange {
+                    address,
+                    account,
+                    slot: response.context.slot,
+                });
+                async move { change }
+            })));
+        }
+        Ok(stream::select_all(subscriptions))
+    }
+
+    /// Current state of every account; ones not created yet are skipped, as
+    /// their first write arrives through the subscription
+    async fn snapshot(&self, rpc: &RpcClient, addresses: &[Pubkey]) -> HermesResult<Vec<AccountChange>> {
+        let mut changes = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(MAX_ACCOUNTS_PER_REQUEST) {
+            let response = rpc
+                .get_multiple_accounts_with_config(chunk, self.account_config())
+                .await
+                .map_err(subscription_error)?;
+            for (address, account) in chunk.iter().zip(response.value) {
+                if let Some(account) = account {
+                    changes.push(AccountChange {
+                        address: *address,
+                        account,
+                        slot: response.context.slot,
+                    });
+                }
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Stream price updates written to `addresses` (e.g. the push feeds of
+    /// `push_feed_address`), reconnecting on disconnects. Decode errors are
+    /// yielded without ending the stream; the stream only ends after
+    /// `max_reconnect_attempts` consecutive failures.
+    pub fn subscribe_price_updates(
+        &self,
+        addresses: Vec<Pubkey>,
+        config: StreamConfig,
+    ) -> impl Stream<Item = HermesResult<StreamedUpdate>> {
+        let watcher = self.clone();
+
+        async_stream::stream! {
+            let rpc = RpcClient::new_with_commitment(watcher.rpc_url.clone(), watcher.commitment);
+            let mut watermarks = Watermarks::default();
+            let mut delay = config.reconnect_delay;
+            let mut failures: u32 = 0;
+            let mut reconnecting = false;
+
+            loop {
+                let error = match PubsubClient::new(&watcher.ws_url).await {
+                    Ok(pubsub) => match watcher.subscribe_all(&pubsub, &addresses).await {
+                        Ok(mut changes) => {
+                            failures = 0;
+                            delay = config.reconnect_delay;
+
+                            match watcher.snapshot(&rpc, &addresses).await {
+                                Ok(snapshot) => {
+                                    for change in &snapshot {
+                                        if let Some(item) = accept(&mut watermarks, change, reconnecting) {
+                                            yield item;
+                                        }
+                                    }
+                                }
+                                Err(e) => yield Err(e),
+                            }
+                            reconnecting = true;
+
+                            while let Some(change) = changes.next().await {
+                                if let Some(item) = accept(&mut watermarks, &change, false) {
+                                    yield item;
+                                }
+                            }
+                            None
+                        }
+                        Err(e) => Some(e),
+                    },
+                    Err(e) => Some(subscription_error(e)),
+                };
+
+                if let Some(e) = error {
+                    failures += 1;
+                    if config.max_reconnect_attempts.is_some_and(|max| failures >= max) {
+                        yield Err(e);
+                        yield Err(HermesError::ReconnectLimit(failures));
+                        break;
+                    }
+                }
+
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(config.max_reconnect_delay);
+            }
+        }
+    }
+}
@@ -0,0 +1,362 @@
+/**
+ * AMM Spot-Price Cross-Check
+ *
+ * A second defense for thinly traded assets: besides the usual validation,
+ * the Pyth price must agree with the spot price of a whitelisted
+ * concentrated liquidity pool. Moving the oracle is not enough to pass a
+ * manipulated price, and neither is moving the pool.
+ *
+ * A pool's spot price alone is not a price source: a flash loan can move it
+ * within the transaction that reads it. The check only rejects; it never
+ * prices anything from the pool. Whitelist deep pools, and set the band
+ * wider than the pool's fee tier so normal drift between swaps passes.
+ *
+ * `PoolSpotPrice` reads the square-root price and mints of:
+ * - Orca Whirlpools (`Whirlpool`)
+ * - Raydium CLMM (`PoolState`)
+ * Both store sqrt(token_b / token_a) in base units as Q64.64, converted
+ * with clmm-math.rs.
+ *
+ * An `AmmReference` PDA per mint, at [AMM_REFERENCE_SEED, mint], records
+ * the pool to compare against and the allowed deviation. References are
+ * managed by the `OracleConfig` authority, like feed bindings.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as
+ *    `amm_cross_check`); requires feed-binding.rs, oracle-decimal.rs, and
+ *    clmm-math.rs
+ * 2. Whitelist a pool for each mint with `init_amm_reference`; the pool's
+ *    other mint is the quote, and needs a feed binding of its own
+ * 3. Call `assert_near_pool_price` after validating both prices
+ */
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::{
+    assert_feed_matches_mint, get_validated_price, sqrt_price_x64_to_decimal, FeedBinding, OracleConfig, OracleDecimal,
+    OracleError, PriceValidationConfig, Rounding, ValidatedPrice, FEED_BINDING_SEED, ORACLE_CONFIG_SEED,
+};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for AMM references: [AMM_REFERENCE_SEED, mint]
+pub const AMM_REFERENCE_SEED: &[u8] = b"amm_reference";
+
+/// Orca Whirlpools program
+pub const ORCA_WHIRLPOOL_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+
+/// Raydium concentrated liquidity program
+pub const RAYDIUM_CLMM_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK");
+
+/// Allowed distance between the oracle and the pool
+pub const DEFAULT_MAX_AMM_DEVIATION_BPS: u16 = 200;
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Decimal places kept for quoted prices
+const PRICE_SCALE: i32 = 18;
+
+/// Where a pool layout keeps the fields the check reads
+struct ClmmLayout {
+    discriminator: [u8; 8],
+    mint_a_offset: usize,
+    mint_b_offset: usize,
+    sqrt_price_offset: usize,
+}
+
+/// Orca `Whirlpool`: token_mint_a, token_mint_b, sqrt_price
+const WHIRLPOOL_LAYOUT: ClmmLayout = ClmmLayout {
+    discriminator: [63, 149, 209, 12, 225, 128, 99, 9],
+    mint_a_offset: 101,
+    mint_b_offset: 181,
+    sqrt_price_offset: 65,
+};
+
+/// Raydium `PoolState`: token_mint_0, token_mint_1, sqrt_price_x64
+const RAYDIUM_CLMM_LAYOUT: ClmmLayout = ClmmLayout {
+    discriminator: [247, 237, 227, 245, 215, 195, 222, 70],
+    mint_a_offset: 73,
+    mint_b_offset: 105,
+    sqrt_price_offset: 253,
+};
+
+// ============================================================================
+// POOL ADAPTERS
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmmPoolKind {
+    OrcaWhirlpool,
+    RaydiumClmm,
+}
+
+/// Spot price of a concentrated liquidity pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolSpotPrice {
+    pub kind: AmmPoolKind,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    /// sqrt(token_b base units per token_a base unit), Q64.64
+    pub sqrt_price_x64: u128,
+}
+
+impl PoolSpotPrice {
+    /// Read an Orca Whirlpool or Raydium CLMM pool, by owner
+    pub fn read(pool: &AccountInfo) -> Result<Self> {
+        let data = pool.try_borrow_data()?;
+        if *pool.owner == ORCA_WHIRLPOOL_PROGRAM_ID {
+            Self::from_layout(&data, AmmPoolKind::OrcaWhirlpool, &WHIRLPOOL_LAYOUT)
+        } else if *pool.owner == RAYDIUM_CLMM_PROGRAM_ID {
+            Self::from_layout(&data, AmmPoolKind::RaydiumClmm, &RAYDIUM_CLMM_LAYOUT)
+        } else {
+            err!(OracleError::InvalidAmmPool)
+        }
+    }
+
+    fn from_layout(data: &[u8], kind: AmmPoolKind, layout: &ClmmLayout) -> Result<Self> {
+        let min_len = (layout.mint_a_offset.max(layout.mint_b_offset) + 32).max(layout.sqrt_price_offset + 16);
+        require!(
+            data.len() >= min_len && data[..8] == layout.discriminator,
+            OracleError::InvalidAmmPool
+        );
+
+        let read_key = |offset: usize| Pubkey::try_from(&data[offset..offset + 32]).unwrap();
+        let offset = layout.sqrt_price_offset;
+        let sqrt_price_x64 = u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap());
+        require!(sqrt_price_x64 > 0, OracleError::InvalidAmmPool);
+
+        Ok(Self {
+            kind,
+            mint_a: read_key(layout.mint_a_offset),
+            mint_b: read_key(layout.mint_b_offset),
+            sqrt_price_x64,
+        })
+    }
+
+    /// Whether the pool trades `base` against `quote`, in either order
+    pub fn trades(&self, base: &Pubkey, quote: &Pubkey) -> bool {
+        (self.mint_a, self.mint_b) == (*base, *quote) || (self.mint_b, self.mint_a) == (*base, *quote)
+    }
+
+    /// Price of one whole `base_mint` token in whole tokens of the other
+    /// mint
+    pub fn price_of(&self, base_mint: &Pubkey, base_decimals: u8, quote_decimals: u8) -> Result<OracleDecimal> {
+        let raw = sqrt_price_x64_to_decimal(self.sqrt_price_x64, PRICE_SCALE, Rounding::Floor)?;
+
+        if *base_mint == self.mint_a {
+            // token_b base units per token_a base unit, times 10^(decimals_a - decimals_b)
+            Ok(OracleDecimal::new(
+                raw.mantissa,
+                raw.scale + quote_decimals as i32 - base_decimals as i32,
+            ))
+        } else {
+            let inverse = OracleDecimal::new(raw.mantissa, raw.scale + base_decimals as i32 - quote_decimals as i32);
+            OracleDecimal::new(1, 0).checked_div(inverse, PRICE_SCALE, Rounding::Floor)
+        }
+    }
+}
+
+// ============================================================================
+// CROSS-CHECK
+// ============================================================================
+
+/// Distance of `pool_price` from `oracle_price`, in bps of the oracle price
+/// (rounded up)
+pub fn pool_deviation_bps(oracle_price: OracleDecimal, pool_price: OracleDecimal) -> Result<u64> {
+    require!(!oracle_price.is_zero(), OracleError::ZeroPrice);
+
+    let scale = oracle_price.scale.max(pool_price.scale);
+    let (oracle_at, pool_at) = (
+        oracle_price.rescale(scale, Rounding::Floor)?,
+        pool_price.rescale(scale, Rounding::Floor)?,
+    );
+    let diff = OracleDecimal::new(oracle_at.mantissa.abs_diff(pool_at.mantissa), scale);
+
+    diff.checked_mul(OracleDecimal::new(BPS_DENOMINATOR, 0))?
+        .checked_div(oracle_price, 0, Rounding::Ceil)?
+        .to_u64(0, Rounding::Ceil)
+}
+
+/// Fail with `AmmPriceDeviation` if the oracle rate of `base` in `quote`
+/// is more than `max_deviation_bps` from the pool's spot price; returns the
+/// deviation
+pub fn assert_near_pool_price(
+    pool: &PoolSpotPrice,
+    base_mint: &Pubkey,
+    base: (&ValidatedPrice, u8),
+    quote_mint: &Pubkey,
+    quote: (&ValidatedPrice, u8),
+    max_deviation_bps: u16,
+) -> Result<u64> {
+    require!(pool.trades(base_mint, quote_mint), OracleError::InvalidAmmPool);
+    let ((base_price, base_decimals), (quote_price, quote_decimals)) = (base, quote);
+
+    let oracle_price = OracleDecimal::from_price(base_price.price, base_price.exponent)?.checked_div(
+        OracleDecimal::from_price(quote_price.price, quote_price.exponent)?,
+        PRICE_SCALE,
+        Rounding::Floor,
+    )?;
+    let pool_price = pool.price_of(base_mint, base_decimals, quote_decimals)?;
+
+    let deviation = pool_deviation_bps(oracle_price, pool_price)?;
+    require!(
+        deviation <= max_deviation_bps as u64,
+        OracleError::AmmPriceDeviation
+    );
+    Ok(deviation)
+}
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// The pool a mint's oracle price is cross-checked against
+#[account]
+pub struct AmmReference {
+    pub mint: Pubkey,
+    pub pool: Pubkey,
+    pub max_deviation_bps: u16,
+    pub bump: u8,
+}
+
+impl AmmReference {
+    pub const LEN: usize = 8 + 32 + 32 + 2 + 1;
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct InitAmmReference<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [ORACLE_CONFIG_SEED], bump = oracle_config.bump, has_one = authority)]
+    pub oracle_config: Account<'info, OracleConfig>,
+
+    /// CHECK: owner and layout are checked by `PoolSpotPrice::read`
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = AmmReference::LEN,
+        seeds = [AMM_REFERENCE_SEED, mint.as_ref()],
+        bump
+    )]
+    pub amm_reference: Account<'info, AmmReference>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAmmReference<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [ORACLE_CONFIG_SEED], bump = oracle_config.bump, has_one = authority)]
+    pub oracle_config: Account<'info, OracleConfig>,
+
+    /// CHECK: owner and layout are checked by `PoolSpotPrice::read`
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [AMM_REFERENCE_SEED, amm_reference.mint.as_ref()],
+        bump = amm_reference.bump
+    )]
+    pub amm_reference: Account<'info, AmmReference>,
+}
+
+#[derive(Accounts)]
+pub struct CheckAmmPrice<'info> {
+    pub base_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub base_price: Account<'info, PriceUpdateV2>,
+    pub quote_price: Account<'info, PriceUpdateV2>,
+
+    #[account(seeds = [FEED_BINDING_SEED, base_mint.key().as_ref()], bump = base_binding.bump)]
+    pub base_binding: Account<'info, FeedBinding>,
+
+    #[account(seeds = [FEED_BINDING_SEED, quote_mint.key().as_ref()], bump = quote_binding.bump)]
+    pub quote_binding: Account<'info, FeedBinding>,
+
+    #[account(
+        seeds = [AMM_REFERENCE_SEED, base_mint.key().as_ref()],
+        bump = amm_reference.bump,
+        has_one = pool @ OracleError::InvalidAmmPool
+    )]
+    pub amm_reference: Account<'info, AmmReference>,
+
+    /// CHECK: the whitelisted pool (`has_one` above); layout is checked by
+    /// `PoolSpotPrice::read`
+    pub pool: UncheckedAccount<'info>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+/// Fail unless `pool` is a supported pool trading `mint`
+fn read_pool_for(pool: &AccountInfo, mint: &Pubkey) -> Result<PoolSpotPrice> {
+    let spot = PoolSpotPrice::read(pool)?;
+    require!(
+        spot.mint_a == *mint || spot.mint_b == *mint,
+        OracleError::InvalidAmmPool
+    );
+    Ok(spot)
+}
+
+pub fn init_amm_reference(ctx: Context<InitAmmReference>, mint: Pubkey, max_deviation_bps: u16) -> Result<()> {
+    read_pool_for(&ctx.accounts.pool, &mint)?;
+
+    let reference = &mut ctx.accounts.amm_reference;
+    reference.mint = mint;
+    reference.pool = ctx.accounts.pool.key();
+    reference.max_deviation_bps = max_deviation_bps;
+    reference.bump = ctx.bumps.amm_reference;
+    Ok(())
+}
+
+pub fn update_amm_reference(ctx: Context<UpdateAmmReference>, max_deviation_bps: u16) -> Result<()> {
+    let reference = &mut ctx.accounts.amm_reference;
+    read_pool_for(&ctx.accounts.pool, &reference.mint)?;
+
+    msg!("Cross-checking {} against pool {}", reference.mint, ctx.accounts.pool.key());
+    reference.pool = ctx.accounts.pool.key();
+    reference.max_deviation_bps = max_deviation_bps;
+    Ok(())
+}
+
+pub fn check_amm_price(ctx: Context<CheckAmmPrice>) -> Result<()> {
+    let clock = Clock::get()?;
+    let config = PriceValidationConfig::default();
+    let accounts = &ctx.accounts;
+    let (base_mint, quote_mint) = (accounts.base_mint.key(), accounts.quote_mint.key());
+
+    assert_feed_matches_mint(&base_mint, &accounts.base_price, &accounts.base_binding)?;
+    assert_feed_matches_mint(&quote_mint, &accounts.quote_price, &accounts.quote_binding)?;
+    let base_price = get_validated_price(&accounts.base_price, &config, &clock)?;
+    let quote_price = get_validated_price(&accounts.quote_price, &config, &clock)?;
+
+    let pool = PoolSpotPrice::read(&accounts.pool)?;
+    let deviation = assert_near_pool_price(
+        &pool,
+        &base_mint,
+        (&base_price, accounts.base_mint.decimals),
+        &quote_mint,
+        (&quote_price, accounts.quote_mint.decimals),
+        accounts.amm_reference.max_deviation_bps,
+    )?;
+
+    msg!("Pool deviation: {} bps", deviation);
+    Ok(())
+}
@@ -5,19 +5,233 @@
  * Copy this file and customize for your program.
  *
  * Setup:
- * 1. Add to Cargo.toml:
- *    pyth-solana-receiver-sdk = "0.3.0"
+ * 1. Cargo.toml of the template crate (the standard Anchor program layout;
+ *    the optional dependencies are listed in each module's header):
+ *    [package]
+ *    name = "pyth-oracle"
+ *
+ *    [lib]
+ *    crate-type = ["cdylib", "lib"]
+ *
+ *    [dependencies]
  *    anchor-lang = "0.30.1"
+ *    anchor-spl = "0.30.1"
+ *    pyth-solana-receiver-sdk = "0.3.0"
+ *    arrayvec = { version = "0.7", default-features = false }
+ *    token-utils = { path = "../token-utils" }   # tokens/templates/token-utils.rs
  *
- * 2. Import this module in your program
- * 3. Use the provided helpers and account structures
+ *    [features]
+ *    default = []
+ *    no-entrypoint = []
+ *    cpi = ["no-entrypoint"]
+ *    idl-build = ["anchor-lang/idl-build", "anchor-spl/idl-build"]
+ *    client = [...]       # price-accounts.rs, client.rs
+ *    hermes = [...]       # hermes-client.rs, hermes-stream.rs, price-history.rs,
+ *                         # risk-suggestions.rs, swap-quote.rs;
+ *                         # with client also account-stream.rs
+ *    serde = [...]        # price-serde.rs
+ *    numeric = [...]      # price-numeric.rs
+ *    test-utils = [...]   # test-utils.rs
+ *
+ * 2. Deploy it as a program, or depend on it from your own program crate
+ *    to reuse the validation helpers, account structs, and errors:
+ *    pyth-oracle = { path = "../pyth-oracle", features = ["no-entrypoint"] }
+ *
+ *    `no-entrypoint` drops the example program's entrypoint so it does not
+ *    collide with yours; its instruction handlers stay compiled but are
+ *    never dispatched. Use `cpi` instead to call a deployed copy of the
+ *    example program (e.g. `pyth_oracle::cpi::record_observation`), and
+ *    `idl-build` for `anchor build` to generate its IDL.
+ *
+ * Accounts defined here (`Position`, `PriceCache`, ...) are owned by the
+ * template's program ID, so a program using the crate as a library cannot
+ * load its own accounts as these types. Define your own state and call the
+ * helpers on it.
  */
 
 use anchor_lang::prelude::*;
 use pyth_solana_receiver_sdk::price_update::{
-    get_feed_id_from_hex, FeedId, Price, PriceUpdateV2, VerificationLevel,
+    get_feed_id_from_hex, FeedId, PriceUpdateV2, VerificationLevel,
+};
+
+#[path = "../../oracle/templates/price-source.rs"]
+pub mod oracle;
+pub use oracle::{
+    MockClock, Now, OraclePrice, PriceSource, PriceSourceError, StalenessMode, SysvarClock, PRICE_SOURCE_ERROR_OFFSET,
 };
 
+#[path = "observation-log.rs"]
+pub mod observation_log;
+pub use observation_log::*;
+
+#[path = "circuit-breaker.rs"]
+pub mod circuit_breaker;
+pub use circuit_breaker::*;
+
+#[path = "depeg.rs"]
+pub mod depeg;
+pub use depeg::*;
+
+#[path = "publish-time-guard.rs"]
+pub mod publish_time_guard;
+pub use publish_time_guard::*;
+
+#[path = "price-cache.rs"]
+pub mod price_cache;
+pub use price_cache::*;
+
+#[path = "stale-fallback.rs"]
+pub mod stale_fallback;
+pub use stale_fallback::*;
+
+#[path = "lst-pricing.rs"]
+pub mod lst_pricing;
+pub use lst_pricing::*;
+
+#[path = "oracle-config.rs"]
+pub mod oracle_config;
+pub use oracle_config::*;
+
+#[path = "feed-binding.rs"]
+pub mod feed_binding;
+pub use feed_binding::*;
+
+#[path = "market.rs"]
+pub mod market;
+pub use market::*;
+
+#[path = "guardian.rs"]
+pub mod guardian;
+pub use guardian::*;
+
+#[path = "oracle-swap.rs"]
+pub mod oracle_swap;
+pub use oracle_swap::*;
+
+#[path = "clmm-math.rs"]
+pub mod clmm;
+pub use clmm::*;
+
+#[path = "amm-cross-check.rs"]
+pub mod amm_cross_check;
+pub use amm_cross_check::*;
+
+#[path = "price-events.rs"]
+pub mod price_events;
+pub use price_events::*;
+
+#[path = "price-update-view.rs"]
+pub mod price_update_view;
+pub use price_update_view::PriceUpdateView;
+
+#[path = "remaining-prices.rs"]
+pub mod remaining_prices;
+pub use remaining_prices::{load_remaining_prices, RemainingPrices, MAX_REMAINING_FEEDS};
+
+#[path = "price-snapshot.rs"]
+pub mod price_snapshot;
+pub use price_snapshot::{load_price_snapshot, PriceSnapshot};
+
+#[path = "basket-valuation.rs"]
+pub mod basket;
+pub use basket::*;
+
+#[path = "cross-margin.rs"]
+pub mod margin;
+pub use margin::*;
+
+#[path = "price-commitment.rs"]
+pub mod price_commitment;
+pub use price_commitment::*;
+
+#[path = "lending-risk.rs"]
+pub mod lending;
+pub use lending::*;
+
+#[path = "liquidation.rs"]
+pub mod liquidation;
+pub use liquidation::*;
+
+#[path = "interest.rs"]
+pub mod interest;
+pub use interest::*;
+
+#[path = "funding.rs"]
+pub mod funding;
+pub use funding::*;
+
+#[path = "trigger-orders.rs"]
+pub mod trigger_orders;
+pub use trigger_orders::*;
+
+#[path = "position-snapshot.rs"]
+pub mod position_snapshot;
+pub use position_snapshot::*;
+
+#[path = "oracle-decimal.rs"]
+pub mod decimal;
+pub use decimal::{OracleDecimal, Rounding};
+
+#[path = "feed-registry.rs"]
+pub mod feed_registry;
+pub use feed_registry::{feed_id_for, feed_info, symbol_for, AssetClass, FeedInfo};
+
+#[path = "post-update.rs"]
+pub mod post_update;
+pub use post_update::PostAndConsumePrice;
+
+#[path = "vaa-verification.rs"]
+pub mod vaa_verification;
+pub use vaa_verification::InlinePriceContext;
+
+#[path = "lazer.rs"]
+pub mod lazer;
+pub use lazer::*;
+
+#[cfg(feature = "client")]
+#[path = "price-accounts.rs"]
+pub mod price_accounts;
+
+#[cfg(feature = "client")]
+#[path = "client.rs"]
+pub mod client;
+
+#[cfg(feature = "serde")]
+#[path = "price-serde.rs"]
+pub mod price_serde;
+
+#[cfg(feature = "numeric")]
+#[path = "price-numeric.rs"]
+pub mod numeric;
+
+#[cfg(feature = "hermes")]
+#[path = "hermes-client.rs"]
+pub mod hermes;
+
+#[cfg(feature = "hermes")]
+#[path = "hermes-stream.rs"]
+pub mod hermes_stream;
+
+#[cfg(feature = "hermes")]
+#[path = "price-history.rs"]
+pub mod price_history;
+
+#[cfg(feature = "hermes")]
+#[path = "risk-suggestions.rs"]
+pub mod risk_suggestions;
+
+#[cfg(feature = "hermes")]
+#[path = "swap-quote.rs"]
+pub mod swap_quote;
+
+#[cfg(all(feature = "client", feature = "hermes"))]
+#[path = "account-stream.rs"]
+pub mod account_stream;
+
+#[cfg(feature = "test-utils")]
+#[path = "test-utils.rs"]
+pub mod test_utils;
+
 // ============================================================================
 // CONSTANTS
 // ============================================================================
@@ -26,7 +240,7 @@ use pyth_solana_receiver_sdk::price_update::{
 pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey =
     solana_program::pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
 
-/// Common price feed IDs
+/// Common price feed IDs as hex; `feed_registry` covers the full catalog
 pub mod price_feeds {
     pub const BTC_USD: &str =
         "0xe62df6c8b4a85fe1a67db44dc12de5db330f7ac66b72dc658afedf0f4a415b43";
@@ -44,33 +258,59 @@ pub mod price_feeds {
         "0x0a0408d619e9380abad35060f9192039ed5042fa6f82301d0e48bb52be830996";
 }
 
+/// Decimals of USD values returned by `calculate_usd_value`
+pub const USD_DECIMALS: u8 = 6;
+
 /// Default maximum price age (60 seconds)
 pub const DEFAULT_MAX_PRICE_AGE: u64 = 60;
 
 /// Maximum acceptable confidence (200 basis points = 2%)
 pub const MAX_CONFIDENCE_BPS: u64 = 200;
 
+/// Guardian signatures required for partial verification in lenient mode
+pub const LENIENT_MIN_SIGNATURES: u8 = 5;
+
+/// Maximum distance of the spot price from the EMA (500 basis points = 5%)
+pub const MAX_EMA_DIVERGENCE_BPS: u64 = 500;
+
+/// First `OracleError` code, clear of the 6000 that the host program's
+/// errors and the receiver SDK's start at, so an error that bubbles up
+/// through a CPI still says which side raised it. `#[error_code]` takes a
+/// literal: change the `offset` on `OracleError` with this, and
+/// `ERROR_CODE_OFFSET` in native-oracle.rs and pinocchio-oracle.rs.
+pub const ORACLE_ERROR_OFFSET: u32 = 9000;
+
 // ============================================================================
 // PRICE VALIDATION
 // ============================================================================
 
 /// Configuration for price validation
-#[derive(Clone, Copy, Debug, AnchorSerialize, AnchorDeserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PriceValidationConfig {
     /// Maximum age of price in seconds
     pub max_age_secs: u64,
+    /// Maximum slots since the update was posted, checked in addition to
+    /// the seconds when set
+    pub max_age_slots: Option<u64>,
     /// Maximum confidence in basis points
     pub max_confidence_bps: u64,
     /// Expected feed ID (optional)
+    #[cfg_attr(feature = "serde", serde(with = "price_serde::optional_feed_id"))]
     pub expected_feed_id: Option<[u8; 32]>,
+    /// Minimum Wormhole verification level of the price update
+    #[cfg_attr(feature = "serde", serde(with = "price_serde::VerificationLevelDef"))]
+    pub verification_level: VerificationLevel,
 }
 
 impl Default for PriceValidationConfig {
     fn default() -> Self {
         Self {
             max_age_secs: DEFAULT_MAX_PRICE_AGE,
+            max_age_slots: None,
             max_confidence_bps: MAX_CONFIDENCE_BPS,
             expected_feed_id: None,
+            verification_level: VerificationLevel::Full,
         }
     }
 }
@@ -80,8 +320,10 @@ impl PriceValidationConfig {
     pub fn strict() -> Self {
         Self {
             max_age_secs: 30,
+            max_age_slots: None,
             max_confidence_bps: 100, // 1%
             expected_feed_id: None,
+            verification_level: VerificationLevel::Full,
         }
     }
 
@@ -89,8 +331,12 @@ impl PriceValidationConfig {
     pub fn lenient() -> Self {
         Self {
             max_age_secs: 120,
+            max_age_slots: None,
             max_confidence_bps: 500, // 5%
             expected_feed_id: None,
+            verification_level: VerificationLevel::Partial {
+                num_signatures: LENIENT_MIN_SIGNATURES,
+            },
         }
     }
 
@@ -101,11 +347,38 @@ impl PriceValidationConfig {
         self.expected_feed_id = Some(feed_id);
         Ok(self)
     }
+
+    /// Also reject updates posted more than `max_age_slots` slots ago
+    pub fn with_max_age_slots(mut self, max_age_slots: u64) -> Self {
+        self.max_age_slots = Some(max_age_slots);
+        self
+    }
+
+    /// Set the minimum verification level
+    pub fn with_verification_level(mut self, level: VerificationLevel) -> Self {
+        self.verification_level = level;
+        self
+    }
+
+    /// Require full Wormhole verification (high-value flows)
+    pub fn require_full_verification(self) -> Self {
+        self.with_verification_level(VerificationLevel::Full)
+    }
+
+    /// Accept partially verified updates with at least `num_signatures`
+    /// guardian signatures (cheap reads)
+    pub fn with_partial_verification(self, num_signatures: u8) -> Self {
+        self.with_verification_level(VerificationLevel::Partial { num_signatures })
+    }
 }
 
 /// Validated price with bounds
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ValidatedPrice {
+    /// Feed the price was read from
+    #[cfg_attr(feature = "serde", serde(with = "price_serde::feed_id"))]
+    pub feed_id: [u8; 32],
     /// Raw price value
     pub price: i64,
     /// Confidence interval
@@ -121,10 +394,11 @@ pub struct ValidatedPrice {
 }
 
 impl ValidatedPrice {
-    /// Create from Pyth Price
-    pub fn from_price(price: &Price) -> Self {
+    /// Create from a provider-neutral oracle price
+    pub fn from_price(feed_id: [u8; 32], price: &OraclePrice) -> Self {
         let conf_i64 = price.conf as i64;
         Self {
+            feed_id,
             price: price.price,
             conf: price.conf,
             exponent: price.exponent,
@@ -153,9 +427,26 @@ impl ValidatedPrice {
         )
     }
 
+    /// Confidence as basis points of the price; see `confidence_bps`
+    pub fn relative_confidence(&self) -> Result<u16> {
+        confidence_bps(self.price, self.conf)
+    }
+
     /// Convert to USD value (6 decimals)
-    pub fn to_usd_value(&self, token_amount: u64, token_decimals: u8) -> Result<u64> {
-        calculate_usd_value(token_amount, token_decimals, self.price, self.exponent)
+    pub fn to_usd_value(&self, token_amount: u64, token_decimals: u8, rounding: Rounding) -> Result<u64> {
+        calculate_usd_value(token_amount, token_decimals, self.price, self.exponent, rounding)
+    }
+
+    /// The same price at `exponent`, bounds included; see `rescale_price`
+    pub fn rescale_to(&self, exponent: i32) -> Result<Self> {
+        let (price, conf) = rescale_price(self.price, self.conf, self.exponent, exponent)?;
+        let rescaled = OraclePrice {
+            price,
+            conf,
+            exponent,
+            publish_time: self.publish_time,
+        };
+        Ok(Self::from_price(self.feed_id, &rescaled))
     }
 }
 
@@ -163,93 +454,403 @@ impl ValidatedPrice {
 // HELPER FUNCTIONS
 // ============================================================================
 
-/// Get and validate a price from a PriceUpdateV2 account
-pub fn get_validated_price(
-    price_update: &PriceUpdateV2,
+/// Get and validate a price from any oracle price source
+///
+/// On failure the feed, the price's age and its confidence are logged
+/// next to the error; see `ContextualError`.
+pub fn get_validated_price<S: PriceSource>(
+    source: &S,
+    config: &PriceValidationConfig,
+    clock: &impl Now,
+) -> Result<ValidatedPrice> {
+    get_validated_price_with_context(source, config, clock).map_err(Error::from)
+}
+
+/// `get_validated_price` returning the failure with the numbers behind it,
+/// for callers that report or branch on them before failing
+pub fn get_validated_price_with_context<S: PriceSource>(
+    source: &S,
+    config: &PriceValidationConfig,
+    clock: &impl Now,
+) -> std::result::Result<ValidatedPrice, ContextualError> {
+    validate_price(source, config, clock).map_err(|error| ContextualError::capture(error, source, config, clock))
+}
+
+fn validate_price<S: PriceSource>(
+    source: &S,
     config: &PriceValidationConfig,
-    clock: &Clock,
+    clock: &impl Now,
 ) -> Result<ValidatedPrice> {
+    // Enforce minimum verification level
+    source.check_verification(&config.verification_level)?;
+
+    // Verify feed identity
+    let feed_id = source.feed_id();
+    if let Some(expected) = config.expected_feed_id {
+        require!(feed_id == expected, OracleError::FeedIdMismatch);
+    }
+
     // Get price with staleness check
-    let price = if let Some(feed_id) = config.expected_feed_id {
-        price_update.get_price_no_older_than_with_custom_verification(
-            clock,
-            config.max_age_secs,
-            &feed_id,
-            &PYTH_RECEIVER_PROGRAM_ID,
-        )?
-    } else {
-        price_update.get_price_no_older_than(clock, config.max_age_secs)?
-    };
+    let max_age = source.staleness_mode().max_age_from_secs(config.max_age_secs);
+    let price = source.get_price(clock, max_age)?;
+
+    // Slot staleness, which does not depend on the validator clock
+    if let Some(max_age_slots) = config.max_age_slots {
+        validate_slot_age(source, clock, max_age_slots)?;
+    }
 
     // Validate confidence
     validate_confidence(&price, config.max_confidence_bps)?;
 
-    Ok(ValidatedPrice::from_price(&price))
+    Ok(ValidatedPrice::from_price(feed_id, &price))
+}
+
+/// A price validation error with the feed and the numbers that failed it
+///
+/// A bare error code says validation failed, not why: `PriceTooStale` from
+/// a price 61 seconds old and one from a price an hour old look the same.
+/// Converting into `Error` (as `?` does) logs the context with `msg!`, so
+/// it shows in the transaction logs next to the error.
+#[derive(Debug)]
+pub struct ContextualError {
+    pub error: Error,
+    /// Feed of the source, which may not be the expected one
+    pub feed_id: [u8; 32],
+    /// Seconds since the price was published; `None` if the source has no
+    /// price at all
+    pub age: Option<i64>,
+    /// Confidence in basis points of the price (see `confidence_bps`);
+    /// `None` without a price
+    pub conf_bps: Option<u16>,
+    pub max_age_secs: u64,
+    pub max_confidence_bps: u64,
+}
+
+impl ContextualError {
+    /// Attach the context of `source`'s latest price to `error`
+    pub fn capture<S: PriceSource>(
+        error: Error,
+        source: &S,
+        config: &PriceValidationConfig,
+        clock: &impl Now,
+    ) -> Self {
+        // Read again without the age limit so a stale price reports its age
+        let price = source.get_price(clock, i64::MAX as u64).ok();
+        Self {
+            error,
+            feed_id: source.feed_id(),
+            age: price.map(|price| clock.unix_timestamp().saturating_sub(price.publish_time)),
+            conf_bps: price.and_then(|price| confidence_bps(price.price, price.conf).ok()),
+            max_age_secs: config.max_age_secs,
+            max_confidence_bps: config.max_confidence_bps,
+        }
+    }
+
+    /// Log the context; the error itself is logged by Anchor when the
+    /// instruction returns it
+    pub fn log(&self) {
+        msg!("Price rejected for feed {:?}", &self.feed_id[..4]);
+        match self.age {
+            Some(age) => msg!("Age: {} s (max {} s)", age, self.max_age_secs),
+            None => msg!("Age: no price (max {} s)", self.max_age_secs),
+        }
+        match self.conf_bps {
+            Some(conf_bps) => msg!("Confidence: {} bps (max {} bps)", conf_bps, self.max_confidence_bps),
+            None => msg!("Confidence: unknown (max {} bps)", self.max_confidence_bps),
+        }
+    }
+}
+
+impl From<ContextualError> for Error {
+    fn from(error: ContextualError) -> Self {
+        error.log();
+        error.error
+    }
+}
+
+/// Reject a price posted more than `max_age_slots` slots before `clock.slot()`
+///
+/// `unix_timestamp` is the stake-weighted median of validator clocks and can
+/// drift from wall time; the slot cannot. A source that does not record its
+/// posted slot fails with `PostedSlotUnavailable`.
+pub fn validate_slot_age<S: PriceSource>(source: &S, clock: &impl Now, max_age_slots: u64) -> Result<()> {
+    let posted_slot = source
+        .posted_slot()
+        .ok_or(error!(OracleError::PostedSlotUnavailable))?;
+    require!(
+        clock.slot().saturating_sub(posted_slot) <= max_age_slots,
+        OracleError::PriceTooStale
+    );
+    Ok(())
+}
+
+/// Spot and EMA price of one feed, validated together
+#[derive(Clone, Copy, Debug)]
+pub struct ValidatedPricePair {
+    pub spot: ValidatedPrice,
+    pub ema: ValidatedPrice,
+}
+
+/// Get and validate the spot and EMA prices, rejecting a spot price more
+/// than `max_divergence_bps` away from the EMA
+///
+/// The EMA passes the same staleness and confidence checks as the spot price.
+pub fn get_validated_price_pair<S: PriceSource>(
+    source: &S,
+    config: &PriceValidationConfig,
+    max_divergence_bps: u64,
+    clock: &impl Now,
+) -> Result<ValidatedPricePair> {
+    let spot = get_validated_price(source, config, clock)?;
+
+    let max_age = source.staleness_mode().max_age_from_secs(config.max_age_secs);
+    let ema = source.get_ema_price(clock, max_age)?;
+    validate_confidence(&ema, config.max_confidence_bps)?;
+    let ema = ValidatedPrice::from_price(spot.feed_id, &ema);
+
+    validate_spot_ema_divergence(&spot, &ema, max_divergence_bps)?;
+    Ok(ValidatedPricePair { spot, ema })
+}
+
+/// Reject a spot price more than `max_bps` of the EMA away from it
+///
+/// A spike from a thin market or a bad print moves the spot price at once
+/// but the EMA (about an hour of history) barely, so a wide gap is a cheap
+/// manipulation signal before acting on the spot price.
+pub fn validate_spot_ema_divergence(
+    spot: &ValidatedPrice,
+    ema: &ValidatedPrice,
+    max_bps: u64,
+) -> Result<()> {
+    // Pyth publishes both with the feed's exponent
+    require!(spot.exponent == ema.exponent, OracleError::SpotEmaDivergence);
+    if ema.price == 0 {
+        return Err(error!(OracleError::ZeroPrice));
+    }
+
+    // Same comparison as a confidence interval, with the gap as the width
+    require!(
+        confidence_within(ema.price, spot.price.abs_diff(ema.price), max_bps),
+        OracleError::SpotEmaDivergence
+    );
+
+    Ok(())
+}
+
+/// Limits on two prices used together, as in a swap
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct PairLimits {
+    /// Maximum gap between the two publish times
+    pub max_skew_secs: u64,
+    /// Maximum of the two confidences in basis points added together
+    pub max_combined_confidence_bps: u64,
+}
+
+impl PairLimits {
+    pub const LEN: usize = 8 + 8;
+
+    /// A combined confidence of at most twice the whole price
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.max_combined_confidence_bps > 0 && self.max_combined_confidence_bps <= 20_000,
+            OracleError::InvalidOracleConfig
+        );
+        Ok(())
+    }
+
+    /// The stricter of each limit, for a pair of assets tuned apart
+    pub fn tighter(self, other: Self) -> Self {
+        Self {
+            max_skew_secs: self.max_skew_secs.min(other.max_skew_secs),
+            max_combined_confidence_bps: self.max_combined_confidence_bps.min(other.max_combined_confidence_bps),
+        }
+    }
+}
+
+/// Limits on the two prices of a swap, each on its own and together
+#[derive(Clone, Copy, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct PairValidationConfig {
+    /// Validation of the input price, with its feed ID
+    pub input: PriceValidationConfig,
+    /// Validation of the output price, with its feed ID
+    pub output: PriceValidationConfig,
+    pub limits: PairLimits,
+}
+
+/// Input and output prices of a swap, published close together and with
+/// confidence to spare between them
+///
+/// Each price passing on its own is not enough: a fresh input price next
+/// to an output price 50 seconds old prices the trade across two moments,
+/// and two prices each just inside the confidence limit price it with twice
+/// that uncertainty.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PricedPair {
+    pub input: ValidatedPrice,
+    pub output: ValidatedPrice,
+}
+
+impl PricedPair {
+    /// Pair two validated prices, failing with `PricesNotSynchronized` if
+    /// they were published more than `limits.max_skew_secs` apart and with
+    /// `ConfidenceTooHigh` if their confidences add up to more than
+    /// `limits.max_combined_confidence_bps`
+    pub fn try_new(input: ValidatedPrice, output: ValidatedPrice, limits: &PairLimits) -> Result<Self> {
+        require!(
+            input.publish_time.abs_diff(output.publish_time) <= limits.max_skew_secs,
+            OracleError::PricesNotSynchronized
+        );
+        let pair = Self { input, output };
+        require!(
+            pair.combined_confidence_bps()? <= limits.max_combined_confidence_bps,
+            OracleError::ConfidenceTooHigh
+        );
+        Ok(pair)
+    }
+
+    /// Confidence of both prices in basis points, added together
+    pub fn combined_confidence_bps(&self) -> Result<u64> {
+        Ok(self.input.relative_confidence()? as u64 + self.output.relative_confidence()? as u64)
+    }
+
+    /// Whole output tokens per whole input token, with `result_decimals`
+    /// decimals and rounded down: the input sold at its lower bound, the
+    /// output bought at its upper bound
+    pub fn exchange_rate(&self, result_decimals: u8) -> Result<u64> {
+        calculate_price_ratio(
+            self.input.sell_price(),
+            self.input.exponent,
+            self.output.buy_price(),
+            self.output.exponent,
+            result_decimals,
+            Rounding::Floor,
+        )
+    }
+}
+
+/// Get and validate the input and output prices of a swap as one
+/// `PricedPair`; see `PricedPair::try_new`
+pub fn get_validated_pair<I: PriceSource, O: PriceSource>(
+    input: &I,
+    output: &O,
+    config: &PairValidationConfig,
+    clock: &impl Now,
+) -> Result<PricedPair> {
+    let input = get_validated_price(input, &config.input, clock)?;
+    let output = get_validated_price(output, &config.output, clock)?;
+    PricedPair::try_new(input, output, &config.limits)
 }
 
 /// Validate that confidence is within acceptable bounds
-pub fn validate_confidence(price: &Price, max_bps: u64) -> Result<()> {
+pub fn validate_confidence(price: &OraclePrice, max_bps: u64) -> Result<()> {
     if price.price == 0 {
         return Err(error!(OracleError::ZeroPrice));
     }
 
-    let conf_bps = ((price.conf as u128) * 10000) / (price.price.unsigned_abs() as u128);
-
     require!(
-        conf_bps <= max_bps as u128,
+        confidence_within(price.price, price.conf, max_bps),
         OracleError::ConfidenceTooHigh
     );
 
     Ok(())
 }
 
-/// Calculate USD value from token amount and price
+/// `ceil(conf × 10_000 / |price|) <= max_bps`, as `confidence_bps` rounds,
+/// cross-multiplied: u128 division is a software routine on SBF and costs
+/// several times the multiplications (`price != 0`)
+#[inline]
+pub fn confidence_within(price: i64, conf: u64, max_bps: u64) -> bool {
+    (conf as u128) * 10_000 <= (max_bps as u128) * price.unsigned_abs() as u128
+}
+
+/// Calculate USD value (`USD_DECIMALS` decimals) from token amount and price
+///
+/// Round against the user: `Floor` for collateral and amounts credited,
+/// `Ceil` for debt and amounts owed.
 pub fn calculate_usd_value(
     token_amount: u64,
     token_decimals: u8,
     price: i64,
     price_exponent: i32,
+    rounding: Rounding,
 ) -> Result<u64> {
     require!(price > 0, OracleError::NegativePrice);
 
-    let amount = token_amount as u128;
-    let price_val = price as u128;
-
-    // Target: 6 decimal USD value
-    // Formula: amount * price * 10^(6 - token_decimals - price_exponent)
-    let exp_adjustment = 6i32 - (token_decimals as i32) - price_exponent;
-
-    let value = if exp_adjustment >= 0 {
-        amount * price_val * 10u128.pow(exp_adjustment as u32)
-    } else {
-        (amount * price_val) / 10u128.pow((-exp_adjustment) as u32)
-    };
-
-    Ok(value as u64)
+    OracleDecimal::from_u64(token_amount, token_decimals)
+        .checked_mul(OracleDecimal::from_price(price, price_exponent)?)?
+        .to_u64(USD_DECIMALS, rounding)
 }
 
 /// Calculate token amount from USD value and price
+///
+/// `Floor` for tokens paid out, `Ceil` for tokens required from the user.
 pub fn calculate_tokens_for_usd(
     usd_amount: u64,
     usd_decimals: u8,
     token_decimals: u8,
     price: i64,
     price_exponent: i32,
+    rounding: Rounding,
 ) -> Result<u64> {
     require!(price > 0, OracleError::NegativePrice);
 
-    let usd = usd_amount as u128;
-    let price_val = price as u128;
-
-    let exp_adjustment = (usd_decimals as i32) - price_exponent - (token_decimals as i32);
+    OracleDecimal::from_u64(usd_amount, usd_decimals)
+        .checked_div(
+            OracleDecimal::from_price(price, price_exponent)?,
+            token_decimals as i32,
+            rounding,
+        )?
+        .to_u64(token_decimals, rounding)
+}
 
-    let tokens = if exp_adjustment >= 0 {
-        (usd * 10u128.pow(exp_adjustment as u32)) / price_val
-    } else {
-        usd / (price_val * 10u128.pow((-exp_adjustment) as u32))
-    };
+/// Calculate the ratio of two prices (e.g. ETH/BTC from ETH/USD and BTC/USD)
+/// with `result_decimals` decimals
+pub fn calculate_price_ratio(
+    numerator_price: i64,
+    numerator_exponent: i32,
+    denominator_price: i64,
+    denominator_exponent: i32,
+    result_decimals: u8,
+    rounding: Rounding,
+) -> Result<u64> {
+    require!(numerator_price >= 0, OracleError::NegativePrice);
+    require!(denominator_price > 0, OracleError::NegativePrice);
+
+    OracleDecimal::from_price(numerator_price, numerator_exponent)?
+        .checked_div(
+            OracleDecimal::from_price(denominator_price, denominator_exponent)?,
+            result_decimals as i32,
+            rounding,
+        )?
+        .to_u64(result_decimals, rounding)
+}
 
-    Ok(tokens as u64)
+/// `price ± conf` moved from `from_exponent` to `to_exponent`: the one path
+/// by which prices of different feeds are brought to a common exponent
+///
+/// A smaller exponent adds digits and is exact, or fails with `MathOverflow`
+/// when the result does not fit. A larger exponent drops digits: the price
+/// is truncated toward zero and the confidence rounded up, plus one unit if
+/// the price lost digits, so the new interval covers the old one. Any
+/// exponent is accepted; none of the powers of ten can overflow.
+pub fn rescale_price(price: i64, conf: u64, from_exponent: i32, to_exponent: i32) -> Result<(i64, u64)> {
+    let scale = to_exponent
+        .checked_neg()
+        .ok_or_else(|| error!(OracleError::MathOverflow))?;
+    let magnitude = OracleDecimal::from_fixed_point(price.unsigned_abs() as u128, from_exponent)?;
+    let truncated = magnitude.rescale(scale, Rounding::Floor)?;
+    let lost_digits = magnitude.rescale(scale, Rounding::Ceil)? != truncated;
+    let conf = OracleDecimal::from_fixed_point(conf as u128, from_exponent)?
+        .rescale(scale, Rounding::Ceil)?
+        .mantissa
+        + lost_digits as u128;
+
+    let magnitude = i128::try_from(truncated.mantissa).map_err(|_| error!(OracleError::MathOverflow))?;
+    let price = if price < 0 { -magnitude } else { magnitude };
+    Ok((
+        i64::try_from(price).map_err(|_| error!(OracleError::MathOverflow))?,
+        u64::try_from(conf).map_err(|_| error!(OracleError::MathOverflow))?,
+    ))
 }
 
 /// Parse feed ID from hex string
@@ -257,6 +858,209 @@ pub fn parse_feed_id(feed_id_hex: &str) -> Result<FeedId> {
     get_feed_id_from_hex(feed_id_hex).map_err(|_| error!(OracleError::InvalidFeedId))
 }
 
+// ============================================================================
+// DYNAMIC FEES
+// ============================================================================
+
+/// Fee curve that charges more as oracle confidence widens, so wide prices
+/// cost the trader instead of failing the trade:
+/// `fee_bps = base_fee_bps + conf_bps * confidence_multiplier_bps / 10_000`,
+/// capped at `max_fee_bps`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct DynamicFeeConfig {
+    /// Fee charged at zero confidence
+    pub base_fee_bps: u16,
+    /// Weight of the confidence (10_000 adds the full confidence width)
+    pub confidence_multiplier_bps: u32,
+    /// Upper bound on the fee; at most 10_000
+    pub max_fee_bps: u16,
+}
+
+impl DynamicFeeConfig {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.base_fee_bps <= self.max_fee_bps && self.max_fee_bps <= 10_000,
+            OracleError::InvalidFee
+        );
+        Ok(())
+    }
+}
+
+/// 0.3% plus twice the confidence width, at most 3%
+pub const DEFAULT_SWAP_FEE: DynamicFeeConfig = DynamicFeeConfig {
+    base_fee_bps: 30,
+    confidence_multiplier_bps: 20_000,
+    max_fee_bps: 300,
+};
+
+/// Confidence as basis points of the price, rounded up so it never
+/// understates the uncertainty
+///
+/// Saturates at `u16::MAX` (655.35%), which is also the result for a zero
+/// price: a confidence of any width around zero is as uncertain as a price
+/// gets. `confidence_within` applies the same rounding without dividing.
+pub fn confidence_bps(price: i64, conf: u64) -> Result<u16> {
+    if price == 0 {
+        return Ok(u16::MAX);
+    }
+    let bps = ((conf as u128) * 10_000).div_ceil(price.unsigned_abs() as u128);
+    Ok(u16::try_from(bps).unwrap_or(u16::MAX))
+}
+
+/// Fee in basis points for a price with `conf_bps` confidence
+pub fn dynamic_fee_bps(conf_bps: u16, config: &DynamicFeeConfig) -> u16 {
+    let confidence_fee = (conf_bps as u128 * config.confidence_multiplier_bps as u128).div_ceil(10_000);
+    let fee = (config.base_fee_bps as u128).saturating_add(confidence_fee);
+    fee.min(config.max_fee_bps.min(10_000) as u128) as u16
+}
+
+/// Split `amount` into (amount after fee, fee). The fee is rounded up.
+pub fn deduct_fee(amount: u64, fee_bps: u16) -> Result<(u64, u64)> {
+    require!(fee_bps <= 10_000, OracleError::InvalidFee);
+
+    let fee = lending::apply_bps(amount, fee_bps, Rounding::Ceil)?;
+    Ok((amount - fee, fee))
+}
+
+// ============================================================================
+// MULTI-ORACLE AGGREGATION
+// ============================================================================
+
+/// Aggregate prices for the same asset from several oracles (e.g. Pyth and
+/// Switchboard) so a single compromised oracle cannot move the result
+pub mod multi_oracle {
+    use arrayvec::ArrayVec;
+
+    use super::*;
+
+    /// Minimum number of sources to aggregate
+    pub const MIN_SOURCES: usize = 2;
+
+    /// Maximum number of sources to aggregate
+    pub const MAX_SOURCES: usize = 5;
+
+    /// Default maximum pairwise deviation (100 basis points = 1%)
+    pub const DEFAULT_MAX_DEVIATION_BPS: u64 = 100;
+
+    /// How validated prices are combined
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+    pub enum AggregationMethod {
+        /// Middle price (mean of the two middle prices for even counts)
+        Median,
+        /// Mean weighted by inverse confidence (tighter prices count more)
+        ConfidenceWeightedMean,
+    }
+
+    /// Configuration for multi-oracle aggregation
+    #[derive(Clone, Copy, Debug, AnchorSerialize, AnchorDeserialize)]
+    pub struct AggregationConfig {
+        pub method: AggregationMethod,
+        /// Reject the set if any two prices differ by more than this
+        pub max_deviation_bps: u64,
+    }
+
+    impl Default for AggregationConfig {
+        fn default() -> Self {
+            Self {
+                method: AggregationMethod::Median,
+                max_deviation_bps: DEFAULT_MAX_DEVIATION_BPS,
+            }
+        }
+    }
+
+    /// Check that every pair of prices is within `max_deviation_bps`
+    pub fn validate_deviation(prices: &[i128], max_deviation_bps: u64) -> Result<()> {
+        let (Some(min), Some(max)) = (prices.iter().min(), prices.iter().max()) else {
+            return Ok(());
+        };
+        require!(*min > 0, OracleError::NegativePrice);
+
+        // The widest pair is always (min, max); floor((max - min) ×
+        // 10_000 / min) <= max_deviation_bps, cross-multiplied
+        require!(
+            (max - min) * 10_000 < (max_deviation_bps as i128 + 1) * min,
+            OracleError::OracleDeviationTooHigh
+        );
+        Ok(())
+    }
+
+    /// Aggregate 2-5 validated prices into one
+    ///
+    /// The result uses the smallest exponent among the inputs, the oldest
+    /// publish time, and the feed ID of the first (primary) input.
+    pub fn aggregate_prices(
+        prices: &[ValidatedPrice],
+        config: &AggregationConfig,
+    ) -> Result<ValidatedPrice> {
+        require!(
+            prices.len() >= MIN_SOURCES,
+            OracleError::InsufficientOracleSources
+        );
+        require!(
+            prices.len() <= MAX_SOURCES,
+            OracleError::TooManyOracleSources
+        );
+
+        let exponent = prices.iter().map(|p| p.exponent).min().unwrap();
+        // Bounded by MAX_SOURCES above, so nothing here allocates
+        let mut normalized: ArrayVec<(i128, i128), MAX_SOURCES> = prices
+            .iter()
+            .map(|p| {
+                // Moving to the smallest exponent only adds digits, so
+                // rounding never applies
+                let p = p.rescale_to(exponent)?;
+                Ok((p.price as i128, p.conf as i128))
+            })
+            .collect::<Result<_>>()?;
+
+        let values: ArrayVec<i128, MAX_SOURCES> = normalized.iter().map(|(price, _)| *price).collect();
+        validate_deviation(&values, config.max_deviation_bps)?;
+
+        let (price, conf) = match config.method {
+            AggregationMethod::Median => {
+                normalized.sort_by_key(|(price, _)| *price);
+                let mid = normalized.len() / 2;
+                if normalized.len() % 2 == 0 {
+                    let (a, b) = (normalized[mid - 1], normalized[mid]);
+                    ((a.0 + b.0) / 2, a.1.max(b.1))
+                } else {
+                    normalized[mid]
+                }
+            }
+            AggregationMethod::ConfidenceWeightedMean => {
+                // weight_i = SCALE / conf_i; zero confidence counts as 1 unit
+                const SCALE: i128 = 1_000_000_000_000_000_000;
+                let weights: ArrayVec<i128, MAX_SOURCES> = normalized
+                    .iter()
+                    .map(|(_, conf)| SCALE / (*conf).max(1))
+                    .collect();
+                let total_weight: i128 = weights.iter().sum();
+
+                let mut weighted_price: i128 = 0;
+                for ((price, _), weight) in normalized.iter().zip(&weights) {
+                    weighted_price = price
+                        .checked_mul(*weight)
+                        .and_then(|v| weighted_price.checked_add(v))
+                        .ok_or(error!(OracleError::MathOverflow))?;
+                }
+
+                // Combined confidence is the harmonic mean of the inputs
+                let conf = (normalized.len() as i128 * SCALE) / total_weight;
+                (weighted_price / total_weight, conf)
+            }
+        };
+
+        let aggregate = OraclePrice {
+            price: i64::try_from(price).map_err(|_| error!(OracleError::MathOverflow))?,
+            conf: u64::try_from(conf).map_err(|_| error!(OracleError::MathOverflow))?,
+            exponent,
+            publish_time: prices.iter().map(|p| p.publish_time).min().unwrap(),
+        };
+
+        Ok(ValidatedPrice::from_price(prices[0].feed_id, &aggregate))
+    }
+}
+
 // ============================================================================
 // ACCOUNT STRUCTURES
 // ============================================================================
@@ -268,6 +1072,18 @@ pub struct SinglePriceContext<'info> {
     pub price_update: Account<'info, PriceUpdateV2>,
 }
 
+/// `SinglePriceContext` without Anchor deserializing the price update;
+/// read it with `PriceUpdateView::load`
+#[derive(Accounts)]
+pub struct SinglePriceContextRaw<'info> {
+    /// CHECK: owner and discriminator are checked by `PriceUpdateView::load`
+    pub price_update: UncheckedAccount<'info>,
+}
+
+/// Accounts for a basket read; the price updates are remaining accounts
+#[derive(Accounts)]
+pub struct BasketPrices {}
+
 /// Accounts for dual price operation (e.g., swaps)
 #[derive(Accounts)]
 pub struct DualPriceContext<'info> {
@@ -277,32 +1093,58 @@ pub struct DualPriceContext<'info> {
     pub output_price_update: Account<'info, PriceUpdateV2>,
 }
 
-/// Accounts for a swap with price validation
+/// Accounts for collateral valuation
 #[derive(Accounts)]
-pub struct SwapWithOracle<'info> {
+pub struct ValueCollateral<'info> {
+    pub owner: Signer<'info>,
+
+    /// Price update for the collateral asset
+    pub collateral_price: Account<'info, PriceUpdateV2>,
+
+    /// State account to store valuation
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub position: Account<'info, Position>,
 
-    /// Price update for the input token
-    pub input_price: Account<'info, PriceUpdateV2>,
+    /// Market of the position's collateral mint: its feed, limits,
+    /// decimals, and breaker
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, position.collateral_mint.as_ref()],
+        bump = collateral_market.bump
+    )]
+    pub collateral_market: Account<'info, Market>,
 
-    /// Price update for the output token
-    pub output_price: Account<'info, PriceUpdateV2>,
+    /// Newest accepted collateral price; rejects an older update
+    #[account(mut)]
+    pub last_observed_price: Option<Account<'info, LastObservedPrice>>,
 
-    // Add your token accounts, pool accounts, etc.
+    /// Depeg guard for stablecoin collateral; haircuts or freezes the
+    /// valuation while the peg is lost. A real program should require it
+    /// for stablecoin mints rather than leave it optional.
+    #[account(mut)]
+    pub depeg_guard: Option<Account<'info, DepegGuard>>,
 }
 
-/// Accounts for collateral valuation
+/// Accounts for refreshing a position's health factor
 #[derive(Accounts)]
-pub struct ValueCollateral<'info> {
-    pub owner: Signer<'info>,
-
-    /// Price update for the collateral asset
+pub struct RefreshPositionHealth<'info> {
     pub collateral_price: Account<'info, PriceUpdateV2>,
+    pub debt_price: Account<'info, PriceUpdateV2>,
+
+    #[account(constraint = collateral_params.mint == position.collateral_mint)]
+    pub collateral_params: Account<'info, RiskParams>,
+
+    #[account(constraint = debt_params.mint == position.debt_mint)]
+    pub debt_params: Account<'info, RiskParams>,
 
-    /// State account to store valuation
     #[account(mut)]
     pub position: Account<'info, Position>,
+
+    /// Interest market of the debt asset; accrues the debt before it is
+    /// valued. A real program should require it for borrowable assets
+    /// rather than leave it optional.
+    #[account(mut)]
+    pub debt_market: Option<Account<'info, InterestMarket>>,
 }
 
 /// Example position state
@@ -312,19 +1154,27 @@ pub struct Position {
     pub collateral_mint: Pubkey,
     pub collateral_amount: u64,
     pub usd_value: u64,
+    pub debt_mint: Pubkey,
+    /// Including interest accrued up to `debt_index`
+    pub debt_amount: u64,
+    /// `InterestMarket::borrow_index` at the last accrual; 0 before the first
+    pub debt_index: u128,
+    pub debt_usd_value: u64,
+    /// `HEALTH_FACTOR_DECIMALS` decimals; `u64::MAX` without debt
+    pub health_factor: u64,
     pub last_price_update: i64,
     pub bump: u8,
 }
 
 impl Position {
-    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 1;
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 32 + 8 + 16 + 8 + 8 + 8 + 1;
 }
 
 // ============================================================================
 // ERROR CODES
 // ============================================================================
 
-#[error_code]
+#[error_code(offset = 9000)]
 pub enum OracleError {
     #[msg("Invalid feed ID format")]
     InvalidFeedId,
@@ -355,33 +1205,307 @@ pub enum OracleError {
 
     #[msg("Math overflow")]
     MathOverflow,
-}
 
-// ============================================================================
-// EXAMPLE PROGRAM
-// ============================================================================
+    #[msg("Not enough oracle sources to aggregate")]
+    InsufficientOracleSources,
 
-declare_id!("YourProgramId11111111111111111111111111111111");
+    #[msg("Too many oracle sources to aggregate")]
+    TooManyOracleSources,
 
-#[program]
-pub mod oracle_example {
-    use super::*;
+    #[msg("Oracle prices deviate too much from each other")]
+    OracleDeviationTooHigh,
 
-    /// Example: Get price with default validation
-    pub fn get_price(ctx: Context<SinglePriceContext>) -> Result<()> {
-        let config = PriceValidationConfig::default();
-        let clock = Clock::get()?;
+    #[msg("Account is not a Pyth receiver price update")]
+    InvalidPriceUpdateAccount,
 
-        let price = get_validated_price(&ctx.accounts.price_update, &config, &clock)?;
+    #[msg("Circuit breaker is tripped")]
+    CircuitBreakerTripped,
 
-        msg!("Price: {} × 10^{}", price.price, price.exponent);
-        msg!("Confidence: ±{}", price.conf);
-        msg!("Bounds: [{}, {}]", price.lower_bound, price.upper_bound);
+    #[msg("Invalid circuit breaker configuration")]
+    InvalidCircuitBreakerConfig,
 
-        Ok(())
-    }
+    #[msg("Invalid lending risk parameters")]
+    InvalidRiskParams,
 
-    /// Example: Get price with strict validation and feed ID check
+    #[msg("Borrow cap exceeded")]
+    BorrowCapExceeded,
+
+    #[msg("Position is not liquidatable")]
+    NotLiquidatable,
+
+    #[msg("Invalid liquidation amount")]
+    InvalidLiquidationAmount,
+
+    #[msg("Fee exceeds 100%")]
+    InvalidFee,
+
+    #[msg("Stablecoin is off its peg")]
+    Depegged,
+
+    #[msg("Invalid depeg guard configuration")]
+    InvalidDepegConfig,
+
+    #[msg("Price is older than the last accepted price")]
+    PriceRollback,
+
+    #[msg("Rollback gap must not be negative")]
+    InvalidRollbackGap,
+
+    #[msg("Price cache is from an earlier slot and no price update was passed")]
+    PriceCacheMiss,
+
+    #[msg("Snapshot slot must be after the last snapshot and not in the future")]
+    StaleSnapshot,
+
+    #[msg("Merkle proof does not match the position snapshot")]
+    InvalidPositionProof,
+
+    #[msg("No price update was passed for a required feed")]
+    MissingPriceFeed,
+
+    #[msg("More than one price update was passed for the same feed")]
+    DuplicatePriceFeed,
+
+    #[msg("Poseidon hash of the price commitment failed")]
+    CommitmentHashFailed,
+
+    #[msg("Basket position already holds the maximum number of assets")]
+    TooManyBasketAssets,
+
+    #[msg("Invalid cross-margin parameters")]
+    InvalidMarginParams,
+
+    #[msg("More distinct price feeds requested than MAX_REMAINING_FEEDS")]
+    TooManyPriceFeeds,
+
+    #[msg("Invalid oracle config")]
+    InvalidOracleConfig,
+
+    #[msg("More feed overrides than MAX_FEED_OVERRIDES")]
+    TooManyFeedOverrides,
+
+    #[msg("Signer is not the nominated oracle config authority")]
+    NotNominatedAuthority,
+
+    #[msg("An oracle or market config change is already pending")]
+    ConfigChangePending,
+
+    #[msg("Config change timelock has not passed")]
+    ConfigChangeNotReady,
+
+    #[msg("No oracle or market config change is pending")]
+    NoPendingConfigChange,
+
+    #[msg("Spot price diverges too far from the EMA price")]
+    SpotEmaDivergence,
+
+    #[msg("Invalid stale price fallback policy")]
+    InvalidFallbackPolicy,
+
+    #[msg("Withdrawals are frozen while the price is stale")]
+    WithdrawalsFrozen,
+
+    #[msg("Price source does not record the slot it was posted in")]
+    PostedSlotUnavailable,
+
+    #[msg("Account is not a stake pool for the expected LST mint")]
+    InvalidStakePool,
+
+    #[msg("Stake pool exchange rate is too many epochs old")]
+    StakePoolStale,
+
+    #[msg("Feed binding is for another mint")]
+    FeedBindingMismatch,
+
+    #[msg("Pool does not trade these mints")]
+    InvalidSwapPool,
+
+    #[msg("Pool vault cannot cover the output")]
+    InsufficientLiquidity,
+
+    #[msg("Account is not a supported AMM pool for these mints")]
+    InvalidAmmPool,
+
+    #[msg("Oracle price is too far from the AMM pool's spot price")]
+    AmmPriceDeviation,
+
+    #[msg("Invalid interest rate model")]
+    InvalidInterestRateModel,
+
+    #[msg("Interest market is for another asset")]
+    InterestMarketMismatch,
+
+    #[msg("Invalid funding parameters")]
+    InvalidFundingParams,
+
+    #[msg("Invalid trigger order")]
+    InvalidTriggerOrder,
+
+    #[msg("Price has not reached the order's trigger")]
+    TriggerNotReached,
+
+    #[msg("Trigger order has expired")]
+    TriggerOrderExpired,
+
+    #[msg("VAA is malformed or not a Pyth accumulator update")]
+    InvalidVaa,
+
+    #[msg("Guardian set account is not the VAA's active Wormhole guardian set")]
+    InvalidGuardianSet,
+
+    #[msg("Guardian signature does not match the guardian set")]
+    InvalidGuardianSignature,
+
+    #[msg("Merkle proof does not match the VAA's root")]
+    PriceUpdateProofMismatch,
+
+    #[msg("Lazer message or payload is malformed")]
+    InvalidLazerPayload,
+
+    #[msg("No Ed25519 instruction verifies the Lazer message")]
+    LazerSignatureMismatch,
+
+    #[msg("Lazer message is not signed by a trusted signer")]
+    UntrustedLazerSigner,
+
+    #[msg("Lazer payload lacks the feed or its price, exponent, or confidence")]
+    LazerFeedNotFound,
+
+    #[msg("Lazer signer set is empty or too large")]
+    InvalidLazerSigners,
+
+    #[msg("Tick spacing must be positive")]
+    InvalidTickSpacing,
+
+    #[msg("Price is outside the tick range of CLMM pools")]
+    TickOutOfRange,
+
+    #[msg("Market operation is paused")]
+    MarketPaused,
+
+    #[msg("Pause flags are empty or name no operation")]
+    InvalidPauseFlags,
+
+    #[msg("Signer is neither the guardian nor the oracle config authority")]
+    NotGuardian,
+
+    #[msg("Market change loosens a limit and must wait out the timelock")]
+    MarketChangeLoosens,
+
+    #[msg("Debt market has an interest market that was not passed")]
+    InterestMarketRequired,
+}
+
+// ============================================================================
+// EXAMPLE PROGRAM
+// ============================================================================
+
+declare_id!("YourProgramId11111111111111111111111111111111");
+
+#[program]
+pub mod oracle_example {
+    use super::*;
+
+    /// Example: Get price with default validation
+    pub fn get_price(ctx: Context<SinglePriceContext>) -> Result<()> {
+        let config = PriceValidationConfig::default();
+        let clock = Clock::get()?;
+
+        let price_update = &ctx.accounts.price_update;
+        let price = consume_price(price_update, price_update.key(), &config, &clock, PriceUse::Read, Pubkey::default())?;
+
+        msg!("Price: {} × 10^{}", price.price, price.exponent);
+        msg!("Confidence: ±{}", price.conf);
+        msg!("Bounds: [{}, {}]", price.lower_bound, price.upper_bound);
+
+        Ok(())
+    }
+
+    /// Example: `get_price` through the raw reader, for fewer compute units
+    pub fn get_price_raw(ctx: Context<SinglePriceContextRaw>) -> Result<()> {
+        let config = PriceValidationConfig::default();
+        let clock = Clock::get()?;
+
+        let price_update = PriceUpdateView::load(&ctx.accounts.price_update)?;
+        let key = ctx.accounts.price_update.key();
+        let price = consume_price(&price_update, key, &config, &clock, PriceUse::Read, Pubkey::default())?;
+
+        msg!("Price: {} × 10^{}", price.price, price.exponent);
+        msg!("Confidence: ±{}", price.conf);
+        msg!("Bounds: [{}, {}]", price.lower_bound, price.upper_bound);
+
+        Ok(())
+    }
+
+    /// Example: emit zk-friendly commitments of a validated price
+    pub fn commit_price(
+        ctx: Context<SinglePriceContextRaw>,
+        config: PriceValidationConfig,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let price_update = PriceUpdateView::load(&ctx.accounts.price_update)?;
+        let price = get_validated_price(&price_update, &config, &clock)?;
+
+        emit!(PriceCommitted {
+            feed_id: price.feed_id,
+            publish_time: price.publish_time,
+            poseidon: poseidon_price_commitment(&price)?,
+            keccak: keccak_price_commitment(&price),
+        });
+
+        Ok(())
+    }
+
+    /// Example: validate one price per feed from the remaining accounts
+    pub fn get_basket_prices<'info>(
+        ctx: Context<'_, '_, '_, 'info, BasketPrices>,
+        feed_ids: Vec<[u8; 32]>,
+        config: PriceValidationConfig,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let prices = load_remaining_prices(ctx.remaining_accounts, &feed_ids, &config, &clock)?;
+
+        for price in prices.iter() {
+            msg!("Feed {:?}: {} × 10^{}", &price.feed_id[..4], price.price, price.exponent);
+        }
+
+        Ok(())
+    }
+
+    /// Example: Read the spot price only while it tracks the EMA
+    pub fn get_price_pair(ctx: Context<SinglePriceContext>, max_divergence_bps: u64) -> Result<()> {
+        let config = PriceValidationConfig::default();
+        let clock = Clock::get()?;
+
+        let pair = get_validated_price_pair(&ctx.accounts.price_update, &config, max_divergence_bps, &clock)?;
+
+        msg!("Spot: {} × 10^{}", pair.spot.price, pair.spot.exponent);
+        msg!("EMA: {} × 10^{}", pair.ema.price, pair.ema.exponent);
+
+        Ok(())
+    }
+
+    /// Example: Rate of the input asset in the output asset, from both
+    /// prices validated as one pair within `limits`
+    pub fn get_exchange_rate(ctx: Context<DualPriceContext>, result_decimals: u8, limits: PairLimits) -> Result<()> {
+        limits.validate()?;
+        let config = PairValidationConfig {
+            input: PriceValidationConfig::default(),
+            output: PriceValidationConfig::default(),
+            limits,
+        };
+        let clock = Clock::get()?;
+
+        let accounts = &ctx.accounts;
+        let pair = get_validated_pair(&accounts.input_price_update, &accounts.output_price_update, &config, &clock)?;
+
+        msg!("Combined confidence: {} bps", pair.combined_confidence_bps()?);
+        msg!("Exchange rate: {} × 10^-{}", pair.exchange_rate(result_decimals)?, result_decimals);
+
+        Ok(())
+    }
+
+    /// Example: Get price with strict validation and feed ID check
     pub fn get_verified_price(
         ctx: Context<SinglePriceContext>,
         feed_id_hex: String,
@@ -390,89 +1514,71 @@ pub mod oracle_example {
             .with_feed_id(&feed_id_hex)?;
         let clock = Clock::get()?;
 
-        let price = get_validated_price(&ctx.accounts.price_update, &config, &clock)?;
+        let price_update = &ctx.accounts.price_update;
+        let price = consume_price(price_update, price_update.key(), &config, &clock, PriceUse::Read, Pubkey::default())?;
 
         msg!("Verified price: {} × 10^{}", price.price, price.exponent);
 
         Ok(())
     }
 
-    /// Example: Swap with price-based slippage protection
+    /// Create an oracle-priced pool trading `mint_a` against `mint_b`
+    pub fn init_swap_pool(ctx: Context<InitSwapPool>) -> Result<()> {
+        oracle_swap::init_swap_pool(ctx)
+    }
+
+    /// Example: Swap with price-based slippage protection and a
+    /// confidence-scaled fee, moving tokens through the pool's vaults
     pub fn swap_with_oracle(
         ctx: Context<SwapWithOracle>,
         amount_in: u64,
         min_amount_out: u64,
     ) -> Result<()> {
-        let clock = Clock::get()?;
-        let config = PriceValidationConfig::strict();
-
-        // Get input token price
-        let input_price = get_validated_price(
-            &ctx.accounts.input_price,
-            &config,
-            &clock,
-        )?;
-
-        // Get output token price
-        let output_price = get_validated_price(
-            &ctx.accounts.output_price,
-            &config,
-            &clock,
-        )?;
-
-        // Use conservative prices for safety
-        // Selling input: use lower bound
-        // Buying output: use upper bound
-        let input_usd = calculate_usd_value(
-            amount_in,
-            9, // Adjust for your token
-            input_price.sell_price(),
-            input_price.exponent,
-        )?;
-
-        let expected_out = calculate_tokens_for_usd(
-            input_usd,
-            6,
-            9, // Adjust for your token
-            output_price.buy_price(),
-            output_price.exponent,
-        )?;
-
-        msg!("Input value (USD): {}", input_usd);
-        msg!("Expected output: {}", expected_out);
-
-        // Slippage check
-        require!(
-            expected_out >= min_amount_out,
-            OracleError::SlippageExceeded
-        );
-
-        // Execute swap logic...
-
-        Ok(())
+        oracle_swap::swap_with_oracle(ctx, amount_in, min_amount_out)
     }
 
-    /// Example: Value collateral position
+    /// Example: Value collateral position with its market's settings
     pub fn update_collateral_value(ctx: Context<ValueCollateral>) -> Result<()> {
         let clock = Clock::get()?;
-        let config = PriceValidationConfig::default();
 
-        let price = get_validated_price(
+        let market = &mut ctx.accounts.collateral_market;
+        market.require_allowed(PauseFlags::DEPOSITS)?;
+        let Some(price) = market.guarded_price(
             &ctx.accounts.collateral_price,
-            &config,
             &clock,
-        )?;
+            PriceUse::CollateralValuation,
+            ctx.accounts.position.key(),
+            OperationKind::RiskIncreasing,
+        )?
+        else {
+            return Ok(());
+        };
+
+        // Never act on a price older than one already used
+        if let Some(last) = ctx.accounts.last_observed_price.as_mut() {
+            require_monotonic(last, &price, clock.unix_timestamp)?;
+        }
 
         // Use conservative valuation (2-sigma lower bound)
         let (lower_2sigma, _) = price.price_with_sigma(2);
 
         let usd_value = calculate_usd_value(
             ctx.accounts.position.collateral_amount,
-            9, // SOL decimals
+            market.decimals,
             lower_2sigma,
             price.exponent,
+            Rounding::Floor, // collateral is valued down
         )?;
 
+        // Stablecoin collateral: haircut or freeze during a depeg
+        let usd_value = match ctx.accounts.depeg_guard.as_mut() {
+            Some(guard) => {
+                guard.observe(&price)?;
+                guard.haircut_collateral(usd_value)?
+            }
+            None => usd_value,
+        };
+
         // Update position
         ctx.accounts.position.usd_value = usd_value;
         ctx.accounts.position.last_price_update = clock.unix_timestamp;
@@ -481,4 +1587,484 @@ pub mod oracle_example {
 
         Ok(())
     }
+
+    /// Example: Revalue collateral and debt and store the health factor
+    pub fn refresh_position_health(ctx: Context<RefreshPositionHealth>) -> Result<()> {
+        let clock = Clock::get()?;
+        let config = PriceValidationConfig::default();
+
+        let position_key = ctx.accounts.position.key();
+        let consume = |source: &Account<PriceUpdateV2>| {
+            consume_price(source, source.key(), &config, &clock, PriceUse::HealthRefresh, position_key)
+        };
+        let collateral_price = consume(&ctx.accounts.collateral_price)?;
+        let debt_price = consume(&ctx.accounts.debt_price)?;
+
+        let position = &mut ctx.accounts.position;
+        let interest = accrue_position_debt(
+            ctx.accounts.debt_market.as_mut(),
+            &ctx.accounts.debt_params.key(),
+            position,
+            clock.unix_timestamp,
+        )?;
+
+        // 2-sigma: collateral at its lower bound, debt at its upper bound
+        let mut risk = PositionRisk::default();
+        risk.add_collateral(
+            &ctx.accounts.collateral_params,
+            position.collateral_amount,
+            &collateral_price,
+            2,
+        )?;
+        risk.add_debt(&ctx.accounts.debt_params, position.debt_amount, &debt_price, 2)?;
+
+        position.usd_value = risk.collateral_usd;
+        position.debt_usd_value = risk.debt_usd;
+        position.health_factor = risk.health_factor()?;
+        position.last_price_update = clock.unix_timestamp;
+
+        msg!("Interest accrued: {}", interest);
+        msg!("LTV: {} bps", risk.loan_to_value_bps()?);
+        msg!("Health factor: {}", position.health_factor);
+        msg!("Max additional borrow (USD): {}", risk.max_borrow_usd());
+
+        Ok(())
+    }
+
+    /// Example: Post a fresh price update and consume it in one instruction
+    pub fn post_and_get_price(
+        ctx: Context<PostAndConsumePrice>,
+        params: pyth_solana_receiver_sdk::PostUpdateAtomicParams,
+    ) -> Result<()> {
+        // Inline VAAs carry a trimmed signature set
+        let config = PriceValidationConfig::lenient();
+        let clock = Clock::get()?;
+
+        let price_update = ctx.accounts.post_and_load(params)?;
+        let key = ctx.accounts.price_update.key();
+        let price = consume_price(&price_update, key, &config, &clock, PriceUse::Read, Pubkey::default())?;
+
+        msg!("Posted price: {} × 10^{}", price.price, price.exponent);
+
+        Ok(())
+    }
+
+    /// Example: Verify a price update carried in instruction data against
+    /// the guardian set and consume it, without posting an account
+    pub fn get_price_inline(
+        ctx: Context<InlinePriceContext>,
+        vaa: Vec<u8>,
+        merkle_price_update: pythnet_sdk::wire::v1::MerklePriceUpdate,
+    ) -> Result<()> {
+        // Inline VAAs carry a trimmed signature set
+        let config = PriceValidationConfig::lenient();
+        let clock = Clock::get()?;
+
+        let price_update = ctx.accounts.verify(&vaa, &merkle_price_update, &clock)?;
+        // No account holds the update
+        let price = consume_price(&price_update, Pubkey::default(), &config, &clock, PriceUse::Read, Pubkey::default())?;
+
+        msg!("Inline price: {} × 10^{}", price.price, price.exponent);
+
+        Ok(())
+    }
+
+    /// Register the Lazer signers whose messages are accepted
+    pub fn init_lazer_signers(ctx: Context<InitLazerSigners>, signers: Vec<TrustedSigner>) -> Result<()> {
+        lazer::init_lazer_signers(ctx, signers)
+    }
+
+    /// Replace the Lazer signers
+    pub fn update_lazer_signers(ctx: Context<UpdateLazerSigners>, signers: Vec<TrustedSigner>) -> Result<()> {
+        lazer::update_lazer_signers(ctx, signers)
+    }
+
+    /// Example: Verify a signed Lazer message and consume one of its feeds.
+    /// The Ed25519 instruction verifying it is at `ed25519_index`.
+    pub fn get_lazer_price(
+        ctx: Context<LazerPriceContext>,
+        message: Vec<u8>,
+        ed25519_index: u16,
+        feed_id: u32,
+    ) -> Result<()> {
+        let config = PriceValidationConfig {
+            expected_feed_id: Some(lazer_feed_id(feed_id)),
+            ..PriceValidationConfig::default()
+        };
+        let clock = Clock::get()?;
+
+        let lazer_price = ctx.accounts.verify(&message, ed25519_index, feed_id, &clock)?;
+        // No account holds the message
+        let price = consume_price(&lazer_price, Pubkey::default(), &config, &clock, PriceUse::Read, Pubkey::default())?;
+
+        msg!("Lazer price: {} × 10^{} at {} µs", price.price, price.exponent, lazer_price.timestamp_us);
+
+        Ok(())
+    }
+
+    /// Create a price observation log for the signer
+    pub fn init_observation_log(ctx: Context<InitObservationLog>) -> Result<()> {
+        observation_log::init_observation_log(ctx)
+    }
+
+    /// Validate a price and record it as consumed by `consumer`
+    pub fn record_observation(
+        ctx: Context<RecordObservation>,
+        config: PriceValidationConfig,
+    ) -> Result<()> {
+        observation_log::record_observation(ctx, config)
+    }
+
+    /// Create a circuit breaker for one feed
+    pub fn init_circuit_breaker(
+        ctx: Context<InitCircuitBreaker>,
+        feed_id: [u8; 32],
+        config: BreakerConfig,
+    ) -> Result<()> {
+        circuit_breaker::init_circuit_breaker(ctx, feed_id, config)
+    }
+
+    /// Push the latest price through a circuit breaker (permissionless)
+    pub fn check_circuit_breaker(
+        ctx: Context<CheckCircuitBreaker>,
+        config: PriceValidationConfig,
+    ) -> Result<()> {
+        circuit_breaker::check_circuit_breaker(ctx, config)
+    }
+
+    /// Return a tripped circuit breaker to normal (authority only)
+    pub fn reset_circuit_breaker(
+        ctx: Context<ResetCircuitBreaker>,
+        config: PriceValidationConfig,
+        reanchor: bool,
+    ) -> Result<()> {
+        circuit_breaker::reset_circuit_breaker(ctx, config, reanchor)
+    }
+
+    /// Create a depeg guard for a stablecoin feed
+    pub fn init_depeg_guard(
+        ctx: Context<InitDepegGuard>,
+        feed_id: [u8; 32],
+        config: DepegConfig,
+    ) -> Result<()> {
+        depeg::init_depeg_guard(ctx, feed_id, config)
+    }
+
+    /// Push the latest price through a depeg guard (permissionless)
+    pub fn check_depeg_guard(
+        ctx: Context<CheckDepegGuard>,
+        config: PriceValidationConfig,
+    ) -> Result<()> {
+        depeg::check_depeg_guard(ctx, config)
+    }
+
+    /// Create the publish-time reference for one feed
+    pub fn init_last_observed_price(
+        ctx: Context<InitLastObservedPrice>,
+        feed_id: [u8; 32],
+        max_gap_secs: i64,
+    ) -> Result<()> {
+        publish_time_guard::init_last_observed_price(ctx, feed_id, max_gap_secs)
+    }
+
+    /// Create the shared price cache for one feed
+    pub fn init_price_cache(ctx: Context<InitPriceCache>, feed_id: [u8; 32]) -> Result<()> {
+        price_cache::init_price_cache(ctx, feed_id)
+    }
+
+    /// Read a price through the cache, refilling it on the first read of a
+    /// slot
+    pub fn get_cached_price(
+        ctx: Context<ReadPriceCache>,
+        config: PriceValidationConfig,
+    ) -> Result<()> {
+        price_cache::get_cached_price(ctx, config)
+    }
+
+    /// Create the last good price of one feed, for stale price fallbacks
+    pub fn init_last_good_price(ctx: Context<InitLastGoodPrice>, feed_id: [u8; 32]) -> Result<()> {
+        stale_fallback::init_last_good_price(ctx, feed_id)
+    }
+
+    /// Read a price, degrading to the last good one under `policy` when it
+    /// is stale
+    pub fn get_fallback_price(ctx: Context<FallbackPriceContext>, policy: FallbackPolicy) -> Result<()> {
+        stale_fallback::get_fallback_price(ctx, policy)
+    }
+
+    /// Bind a mint to the feed that prices it (oracle config authority)
+    pub fn init_feed_binding(ctx: Context<InitFeedBinding>, mint: Pubkey, feed_id: [u8; 32]) -> Result<()> {
+        feed_binding::init_feed_binding(ctx, mint, feed_id)
+    }
+
+    /// Rebind a mint to another feed (oracle config authority)
+    pub fn update_feed_binding(ctx: Context<UpdateFeedBinding>, feed_id: [u8; 32]) -> Result<()> {
+        feed_binding::update_feed_binding(ctx, feed_id)
+    }
+
+    /// Create the market of a mint: its feed, limits, breaker, risk
+    /// parameters, and swap fee (oracle config authority)
+    pub fn init_market(ctx: Context<InitMarket>, config: MarketConfig) -> Result<()> {
+        market::init_market(ctx, config)
+    }
+
+    /// Apply a market change that loosens nothing, without the timelock
+    /// (oracle config authority)
+    pub fn tighten_market(ctx: Context<UpdateMarket>, config: MarketConfig) -> Result<()> {
+        market::tighten_market(ctx, config)
+    }
+
+    /// Queue new settings for a market (oracle config authority)
+    pub fn propose_market_config(ctx: Context<UpdateMarket>, config: MarketConfig) -> Result<()> {
+        market::propose_market_config(ctx, config)
+    }
+
+    /// Apply a market's pending settings once the timelock has passed
+    /// (anyone)
+    pub fn apply_market_config(ctx: Context<ApplyMarketConfig>) -> Result<()> {
+        market::apply_market_config(ctx)
+    }
+
+    /// Drop a market's pending settings (oracle config authority)
+    pub fn cancel_market_config(ctx: Context<UpdateMarket>) -> Result<()> {
+        market::cancel_market_config(ctx)
+    }
+
+    /// Name the key that may pause markets (oracle config authority)
+    pub fn init_guardian(ctx: Context<InitGuardian>, guardian: Pubkey) -> Result<()> {
+        guardian::init_guardian(ctx, guardian)
+    }
+
+    /// Replace the guardian (oracle config authority)
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        guardian::set_guardian(ctx, guardian)
+    }
+
+    /// Pause operations of a market (guardian or oracle config authority)
+    pub fn pause_market(ctx: Context<PauseMarket>, operations: PauseFlags) -> Result<()> {
+        guardian::pause_market(ctx, operations)
+    }
+
+    /// Resume operations of a market (oracle config authority)
+    pub fn resume_market(ctx: Context<ResumeMarket>, operations: PauseFlags) -> Result<()> {
+        guardian::resume_market(ctx, operations)
+    }
+
+    /// Clear a trip of a market's breaker (oracle config authority)
+    pub fn reset_market_breaker(ctx: Context<ResetMarketBreaker>, reanchor: bool) -> Result<()> {
+        market::reset_market_breaker(ctx, reanchor)
+    }
+
+    /// Create the interest market of a market's asset (oracle config
+    /// authority)
+    pub fn init_market_interest(ctx: Context<InitMarketInterest>, rate_model: RateModel) -> Result<()> {
+        market::init_market_interest(ctx, rate_model)
+    }
+
+    /// Whitelist the pool a mint's price is cross-checked against (oracle
+    /// config authority)
+    pub fn init_amm_reference(ctx: Context<InitAmmReference>, mint: Pubkey, max_deviation_bps: u16) -> Result<()> {
+        amm_cross_check::init_amm_reference(ctx, mint, max_deviation_bps)
+    }
+
+    /// Point a mint's cross-check at another pool (oracle config authority)
+    pub fn update_amm_reference(ctx: Context<UpdateAmmReference>, max_deviation_bps: u16) -> Result<()> {
+        amm_cross_check::update_amm_reference(ctx, max_deviation_bps)
+    }
+
+    /// Example: Require the oracle rate of a mint to agree with its
+    /// whitelisted pool
+    pub fn check_amm_price(ctx: Context<CheckAmmPrice>) -> Result<()> {
+        amm_cross_check::check_amm_price(ctx)
+    }
+
+    /// Price an LST at SOL/USD times its stake pool's exchange rate
+    pub fn get_lst_price(ctx: Context<LstPriceContext>, lst_mint: Pubkey, max_epochs_behind: u64) -> Result<()> {
+        lst_pricing::get_lst_price(ctx, lst_mint, max_epochs_behind)
+    }
+
+    /// Create risk parameters for one asset
+    pub fn init_risk_params(
+        ctx: Context<InitRiskParams>,
+        mint: Pubkey,
+        feed_id: [u8; 32],
+        decimals: u8,
+        config: RiskParamsConfig,
+    ) -> Result<()> {
+        lending::init_risk_params(ctx, mint, feed_id, decimals, config)
+    }
+
+    /// Replace an asset's risk parameters (authority only)
+    pub fn update_risk_params(
+        ctx: Context<UpdateRiskParams>,
+        config: RiskParamsConfig,
+    ) -> Result<()> {
+        lending::update_risk_params(ctx, config)
+    }
+
+    /// Partially liquidate an unhealthy position
+    pub fn liquidate(
+        ctx: Context<Liquidate>,
+        repay_amount: u64,
+        min_collateral_out: u64,
+    ) -> Result<()> {
+        liquidation::liquidate(ctx, repay_amount, min_collateral_out)
+    }
+
+    /// Create the interest market of an asset with risk parameters
+    pub fn init_interest_market(ctx: Context<InitInterestMarket>, rate_model: RateModel) -> Result<()> {
+        interest::init_interest_market(ctx, rate_model)
+    }
+
+    /// Replace an interest market's rate model (authority only)
+    pub fn update_rate_model(ctx: Context<UpdateRateModel>, rate_model: RateModel) -> Result<()> {
+        interest::update_rate_model(ctx, rate_model)
+    }
+
+    /// Accrue an interest market's indices to now
+    pub fn accrue_interest(ctx: Context<AccrueInterest>) -> Result<()> {
+        interest::accrue_interest(ctx)
+    }
+
+    /// Create the funding accumulator of a perpetual market
+    pub fn init_funding_state(
+        ctx: Context<InitFundingState>,
+        feed_id: [u8; 32],
+        params: FundingParams,
+    ) -> Result<()> {
+        funding::init_funding_state(ctx, feed_id, params)
+    }
+
+    /// Accrue funding and set the next rate from the mark price (authority
+    /// only)
+    pub fn update_funding(ctx: Context<UpdateFunding>, mark_price: i64, mark_exponent: i32) -> Result<()> {
+        funding::update_funding(ctx, mark_price, mark_exponent)
+    }
+
+    /// Place a stop order that executes once the oracle price crosses it
+    pub fn create_trigger_order(
+        ctx: Context<CreateTriggerOrder>,
+        order_id: u64,
+        params: TriggerOrderParams,
+    ) -> Result<()> {
+        trigger_orders::create_trigger_order(ctx, order_id, params)
+    }
+
+    /// Close a trigger order (owner only)
+    pub fn cancel_trigger_order(ctx: Context<CancelTriggerOrder>) -> Result<()> {
+        trigger_orders::cancel_trigger_order(ctx)
+    }
+
+    /// Execute a triggered order (permissionless)
+    pub fn execute_trigger(ctx: Context<ExecuteTrigger>) -> Result<()> {
+        trigger_orders::execute_trigger(ctx)
+    }
+
+    /// Create the position snapshot; the signer becomes its crank authority
+    pub fn init_position_snapshot(ctx: Context<InitPositionSnapshot>) -> Result<()> {
+        position_snapshot::init_position_snapshot(ctx)
+    }
+
+    /// Commit a Merkle root of position health computed by the indexer
+    pub fn commit_position_snapshot(
+        ctx: Context<CommitPositionSnapshot>,
+        root: [u8; 32],
+        leaf_count: u64,
+        source_slot: u64,
+    ) -> Result<()> {
+        position_snapshot::commit_position_snapshot(ctx, root, leaf_count, source_slot)
+    }
+
+    /// Check a position's health against the committed snapshot
+    pub fn verify_position_snapshot(
+        ctx: Context<VerifyPositionSnapshot>,
+        leaf: PositionLeaf,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        position_snapshot::verify_position_snapshot(ctx, leaf, proof)
+    }
+
+    /// Create a multi-asset position for the signer
+    pub fn init_basket_position(ctx: Context<InitBasketPosition>) -> Result<()> {
+        basket::init_basket_position(ctx)
+    }
+
+    /// Value every holding of a basket position; price updates are
+    /// remaining accounts, published at most `max_skew_secs` apart
+    pub fn value_basket<'info>(
+        ctx: Context<'_, '_, '_, 'info, ValueBasket<'info>>,
+        config: PriceValidationConfig,
+        max_skew_secs: u64,
+    ) -> Result<()> {
+        basket::value_basket(ctx, config, max_skew_secs)
+    }
+
+    /// Create the margin parameters of one mint
+    pub fn init_margin_params(
+        ctx: Context<InitMarginParams>,
+        mint: Pubkey,
+        config: AssetMarginConfig,
+    ) -> Result<()> {
+        margin::init_margin_params(ctx, mint, config)
+    }
+
+    /// Create the correlation group offsets
+    pub fn init_margin_groups(
+        ctx: Context<InitMarginGroups>,
+        offset_bps: [u16; MAX_CORRELATION_GROUPS],
+    ) -> Result<()> {
+        margin::init_margin_groups(ctx, offset_bps)
+    }
+
+    /// Compute a basket position's initial and maintenance margin; margin
+    /// parameters and price updates, published at most `max_skew_secs`
+    /// apart, are remaining accounts
+    pub fn refresh_margin<'info>(
+        ctx: Context<'_, '_, '_, 'info, RefreshMargin<'info>>,
+        config: PriceValidationConfig,
+        max_skew_secs: u64,
+    ) -> Result<()> {
+        margin::refresh_margin(ctx, config, max_skew_secs)
+    }
+
+    /// Create the program's oracle config; signed by the upgrade authority
+    pub fn init_oracle_config(ctx: Context<InitOracleConfig>, config: ConfigChange) -> Result<()> {
+        oracle_config::init_oracle_config(ctx, config)
+    }
+
+    /// Queue a new oracle config behind the timelock
+    pub fn propose_oracle_config(ctx: Context<UpdateOracleConfig>, change: ConfigChange) -> Result<()> {
+        oracle_config::propose_oracle_config(ctx, change)
+    }
+
+    /// Apply the queued config once its timelock has passed; anyone may call
+    pub fn apply_oracle_config(ctx: Context<ApplyOracleConfig>) -> Result<()> {
+        oracle_config::apply_oracle_config(ctx)
+    }
+
+    /// Drop the queued config
+    pub fn cancel_oracle_config(ctx: Context<UpdateOracleConfig>) -> Result<()> {
+        oracle_config::cancel_oracle_config(ctx)
+    }
+
+    /// Nominate a new config authority (e.g. a governance PDA)
+    pub fn nominate_oracle_config_authority(
+        ctx: Context<UpdateOracleConfig>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        oracle_config::nominate_oracle_config_authority(ctx, new_authority)
+    }
+
+    /// Accept a nomination; signed by the nominated authority
+    pub fn accept_oracle_config_authority(ctx: Context<AcceptOracleConfigAuthority>) -> Result<()> {
+        oracle_config::accept_oracle_config_authority(ctx)
+    }
+
+    /// Example: validate a price with the limits from the oracle config
+    pub fn get_price_with_config(ctx: Context<ConfiguredPriceContext>, feed_id: [u8; 32]) -> Result<()> {
+        let clock = Clock::get()?;
+        let price = get_configured_price(&ctx.accounts.price_update, &ctx.accounts.oracle_config, &feed_id, &clock)?;
+        msg!("Price: {} × 10^{}", price.price, price.exponent);
+        Ok(())
+    }
 }
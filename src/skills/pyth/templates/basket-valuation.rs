@@ -0,0 +1,303 @@
+/**
+ * Multi-Asset Portfolio Valuation
+ *
+ * Cross-margin protocols value a position holding several collateral
+ * mints, each priced by its own feed. A `BasketPosition` holds up to
+ * `MAX_BASKET_ASSETS` holdings, each recording its mint, feed ID, and
+ * decimals. `value_basket` loads the prices from the remaining accounts
+ * as one `PriceSnapshot` (price-snapshot.rs), so no holding is valued at a
+ * price from a different moment than the others, then returns a per-asset
+ * breakdown and two totals:
+ *
+ * - `total_usd_value` at the published price (for display)
+ * - `worst_case_usd_value` at the lower confidence bound (for margin),
+ *   where a bound at or below zero values the asset at zero
+ *
+ * Both round down. The instruction stores the totals on the position and
+ * emits the breakdown in `BasketValued`. Loading and looking up prices
+ * stays off the heap; the Borsh `Vec`s of `holdings` and the breakdown do
+ * not, and are bounded by `MAX_BASKET_ASSETS`.
+ *
+ * Valuation counts deposits only; cross-margin.rs nets them against
+ * `borrowed` amounts. Deposits, withdrawals, borrows, and repayments move
+ * tokens, so they belong to the protocol: call `BasketPosition::credit`,
+ * `debit`, `borrow`, and `repay` from those handlers after the transfer.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as `basket`);
+ *    requires remaining-prices.rs and price-snapshot.rs
+ * 2. Create one position per owner with `init_basket_position`
+ * 3. Clients pass one price update per distinct holding feed as remaining
+ *    accounts, in any order
+ */
+
+use anchor_lang::prelude::*;
+use arrayvec::ArrayVec;
+use pyth_solana_receiver_sdk::price_update::FeedId;
+
+use crate::{calculate_usd_value, load_price_snapshot, OracleError, PriceSnapshot, PriceValidationConfig, Rounding};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for basket positions: [BASKET_POSITION_SEED, owner]
+pub const BASKET_POSITION_SEED: &[u8] = b"basket_position";
+
+/// Most distinct mints one position may hold
+pub const MAX_BASKET_ASSETS: usize = 8;
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// One mint of a basket
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct BasketHolding {
+    pub mint: Pubkey,
+    pub feed_id: [u8; 32],
+    pub decimals: u8,
+    /// Deposited collateral
+    pub amount: u64,
+    /// Borrowed against the basket; only the margin engine counts it
+    pub borrowed: u64,
+}
+
+impl BasketHolding {
+    pub const LEN: usize = 32 + 32 + 1 + 8 + 8;
+}
+
+/// A position holding several collateral mints
+#[account]
+pub struct BasketPosition {
+    pub owner: Pubkey,
+    pub holdings: Vec<BasketHolding>,
+    /// From the last `value_basket`, with `USD_DECIMALS` decimals
+    pub total_usd_value: u64,
+    pub worst_case_usd_value: u64,
+    pub last_valued_at: i64,
+    pub bump: u8,
+}
+
+impl BasketPosition {
+    pub const LEN: usize = 8 + 32 + 4 + MAX_BASKET_ASSETS * BasketHolding::LEN + 8 + 8 + 8 + 1;
+
+    /// The holding of `mint`, opened empty if needed
+    fn holding_mut(&mut self, mint: Pubkey, feed_id: [u8; 32], decimals: u8) -> Result<&mut BasketHolding> {
+        match self.holdings.iter().position(|holding| holding.mint == mint) {
+            Some(index) => {
+                require!(self.holdings[index].feed_id == feed_id, OracleError::FeedIdMismatch);
+                Ok(&mut self.holdings[index])
+            }
+            None => {
+                require!(self.holdings.len() < MAX_BASKET_ASSETS, OracleError::TooManyBasketAssets);
+                self.holdings.push(BasketHolding { mint, feed_id, decimals, amount: 0, borrowed: 0 });
+                Ok(self.holdings.last_mut().unwrap())
+            }
+        }
+    }
+
+    /// Apply `update` to the holding of `mint`, closing it once empty
+    fn update_existing(&mut self, mint: Pubkey, update: impl FnOnce(&mut BasketHolding) -> Option<()>) -> Result<()> {
+        let index = self
+            .holdings
+            .iter()
+            .position(|holding| holding.mint == mint)
+            .ok_or_else(|| error!(OracleError::InsufficientCollateral))?;
+        let holding = &mut self.holdings[index];
+        update(holding).ok_or_else(|| error!(OracleError::InsufficientCollateral))?;
+        if holding.amount == 0 && holding.borrowed == 0 {
+            self.holdings.swap_remove(index);
+        }
+        Ok(())
+    }
+
+    /// Add `amount` of `mint`, opening a holding if needed
+    pub fn credit(&mut self, mint: Pubkey, feed_id: [u8; 32], decimals: u8, amount: u64) -> Result<()> {
+        let holding = self.holding_mut(mint, feed_id, decimals)?;
+        holding.amount = holding
+            .amount
+            .checked_add(amount)
+            .ok_or_else(|| error!(OracleError::MathOverflow))?;
+        Ok(())
+    }
+
+    /// Remove `amount` of `mint`, closing the holding when it is empty
+    pub fn debit(&mut self, mint: Pubkey, amount: u64) -> Result<()> {
+        self.update_existing(mint, |holding| {
+            holding.amount = holding.amount.checked_sub(amount)?;
+            Some(())
+        })
+    }
+
+    /// Record `amount` of `mint` borrowed against the basket
+    pub fn borrow(&mut self, mint: Pubkey, feed_id: [u8; 32], decimals: u8, amount: u64) -> Result<()> {
+        let holding = self.holding_mut(mint, feed_id, decimals)?;
+        holding.borrowed = holding
+            .borrowed
+            .checked_add(amount)
+            .ok_or_else(|| error!(OracleError::MathOverflow))?;
+        Ok(())
+    }
+
+    /// Record a repayment of `amount` of `mint`
+    pub fn repay(&mut self, mint: Pubkey, amount: u64) -> Result<()> {
+        self.update_existing(mint, |holding| {
+            holding.borrowed = holding.borrowed.checked_sub(amount)?;
+            Some(())
+        })
+    }
+
+    /// Feeds the holdings are priced by, one per holding (the loader
+    /// deduplicates)
+    pub fn feed_ids(&self) -> ArrayVec<FeedId, MAX_BASKET_ASSETS> {
+        self.holdings.iter().map(|holding| holding.feed_id).collect()
+    }
+}
+
+// ============================================================================
+// VALUATION
+// ============================================================================
+
+/// Value of one holding
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct AssetValuation {
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub usd_value: u64,
+    pub worst_case_usd_value: u64,
+    pub price: i64,
+    pub lower_bound: i64,
+    pub exponent: i32,
+}
+
+/// Value of a basket, asset by asset
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct BasketValuation {
+    pub assets: Vec<AssetValuation>,
+    pub total_usd_value: u64,
+    pub worst_case_usd_value: u64,
+}
+
+/// Value `holdings` with the prices of one snapshot (see `load_price_snapshot`)
+pub fn value_holdings(
+    holdings: &[BasketHolding],
+    prices: &PriceSnapshot,
+) -> Result<BasketValuation> {
+    let mut valuation = BasketValuation {
+        assets: Vec::with_capacity(holdings.len()),
+        total_usd_value: 0,
+        worst_case_usd_value: 0,
+    };
+
+    for holding in holdings {
+        let price = prices.get(&holding.feed_id)?;
+        let usd_value = calculate_usd_value(holding.amount, holding.decimals, price.price, price.exponent, Rounding::Floor)?;
+        let worst_case_usd_value = if price.lower_bound <= 0 {
+            0
+        } else {
+            calculate_usd_value(holding.amount, holding.decimals, price.lower_bound, price.exponent, Rounding::Floor)?
+        };
+
+        valuation.total_usd_value = valuation
+            .total_usd_value
+            .checked_add(usd_value)
+            .ok_or_else(|| error!(OracleError::MathOverflow))?;
+        valuation.worst_case_usd_value = valuation
+            .worst_case_usd_value
+            .checked_add(worst_case_usd_value)
+            .ok_or_else(|| error!(OracleError::MathOverflow))?;
+        valuation.assets.push(AssetValuation {
+            mint: holding.mint,
+            amount: holding.amount,
+            usd_value,
+            worst_case_usd_value,
+            price: price.price,
+            lower_bound: price.lower_bound,
+            exponent: price.exponent,
+        });
+    }
+
+    Ok(valuation)
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct BasketValued {
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub valuation: BasketValuation,
+    pub timestamp: i64,
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitBasketPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = BasketPosition::LEN,
+        seeds = [BASKET_POSITION_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub basket_position: Account<'info, BasketPosition>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Price updates for every holding feed follow as remaining accounts
+#[derive(Accounts)]
+pub struct ValueBasket<'info> {
+    #[account(mut)]
+    pub basket_position: Account<'info, BasketPosition>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn init_basket_position(ctx: Context<InitBasketPosition>) -> Result<()> {
+    let position = &mut ctx.accounts.basket_position;
+    position.owner = ctx.accounts.owner.key();
+    position.holdings = Vec::new();
+    position.total_usd_value = 0;
+    position.worst_case_usd_value = 0;
+    position.last_valued_at = 0;
+    position.bump = ctx.bumps.basket_position;
+    Ok(())
+}
+
+/// Value every holding and store the totals on the position; the prices
+/// must be published within `max_skew_secs` of each other
+pub fn value_basket<'info>(
+    ctx: Context<'_, '_, '_, 'info, ValueBasket<'info>>,
+    config: PriceValidationConfig,
+    max_skew_secs: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let position = &mut ctx.accounts.basket_position;
+
+    let prices = load_price_snapshot(ctx.remaining_accounts, &position.feed_ids(), &config, max_skew_secs, &clock)?;
+    let valuation = value_holdings(&position.holdings, &prices)?;
+
+    position.total_usd_value = valuation.total_usd_value;
+    position.worst_case_usd_value = valuation.worst_case_usd_value;
+    position.last_valued_at = clock.unix_timestamp;
+
+    emit!(BasketValued {
+        position: position.key(),
+        owner: position.owner,
+        valuation,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
@@ -0,0 +1,419 @@
+/**
+ * Price Circuit Breaker
+ *
+ * Guards a program against flash oracle spikes. A per-feed state account
+ * keeps the last accepted price; a new price that moves more than
+ * `max_deviation_bps` from it within `window_secs` trips the breaker
+ * instead of being used. Depending on configuration a tripped breaker
+ * either halts every price-dependent operation or drops the program into
+ * restricted mode, where only risk-reducing operations (repay, withdraw
+ * collateral surplus, close) continue. Only the authority can reset it.
+ * The breaker itself is a `BreakerState`, so other accounts can embed one
+ * (market.rs keeps one per `Market`).
+ *
+ * A failed instruction rolls back its account writes, so a trip recorded
+ * inside an instruction that then errors would be lost. Guards therefore
+ * report the trip instead of failing: callers return `Ok(())` without
+ * acting when `guard_price` says no, and the trip persists.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as
+ *    `circuit_breaker`)
+ * 2. Create one breaker per feed with `init_circuit_breaker`
+ * 3. Call `guard_price` after `get_validated_price` in every instruction
+ *    that acts on the price, or crank `check_circuit_breaker`
+ */
+
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::{
+    get_validated_price, OracleDecimal, OracleError, PriceValidationConfig, Rounding,
+    ValidatedPrice,
+};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for circuit breakers: [CIRCUIT_BREAKER_SEED, authority, feed_id]
+pub const CIRCUIT_BREAKER_SEED: &[u8] = b"circuit_breaker";
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// What a trip does to the program
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum TripAction {
+    /// Halt every price-dependent operation
+    Reject,
+    /// Allow only risk-reducing operations
+    Restrict,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum BreakerStatus {
+    Normal,
+    Restricted,
+    Tripped,
+}
+
+/// Whether an operation adds or removes risk
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OperationKind {
+    /// Borrow, open, increase, swap into the asset
+    RiskIncreasing,
+    /// Repay, reduce, close
+    RiskReducing,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct BreakerConfig {
+    /// Largest move from the last accepted price allowed within the window
+    pub max_deviation_bps: u64,
+    /// Moves over a longer interval re-anchor instead of tripping
+    pub window_secs: i64,
+    pub on_trip: TripAction,
+}
+
+impl BreakerConfig {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.max_deviation_bps > 0 && self.max_deviation_bps < BPS_DENOMINATOR as u64,
+            OracleError::InvalidCircuitBreakerConfig
+        );
+        require!(self.window_secs > 0, OracleError::InvalidCircuitBreakerConfig);
+        Ok(())
+    }
+}
+
+/// Reference price and trip record of one breaker; embedded in
+/// `CircuitBreaker` and in each `Market`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct BreakerState {
+    pub config: BreakerConfig,
+    pub status: BreakerStatus,
+    /// Last accepted price; publish_time 0 until the first price
+    pub last_price: i64,
+    pub last_exponent: i32,
+    pub last_publish_time: i64,
+    /// Price that tripped the breaker, kept for the reset decision
+    pub trip_price: i64,
+    pub trip_exponent: i32,
+    pub tripped_at: i64,
+    pub trip_count: u64,
+}
+
+impl BreakerState {
+    pub const LEN: usize = (8 + 8 + 1) + 1 + 8 + 4 + 8 + 8 + 4 + 8 + 8;
+
+    /// A normal breaker with no reference price yet
+    pub fn new(config: BreakerConfig) -> Self {
+        Self {
+            config,
+            status: BreakerStatus::Normal,
+            last_price: 0,
+            last_exponent: 0,
+            last_publish_time: 0,
+            trip_price: 0,
+            trip_exponent: 0,
+            tripped_at: 0,
+            trip_count: 0,
+        }
+    }
+
+    /// Whether an operation may proceed in the current status
+    pub fn allows(&self, operation: OperationKind) -> bool {
+        match self.status {
+            BreakerStatus::Normal => true,
+            BreakerStatus::Restricted => operation == OperationKind::RiskReducing,
+            BreakerStatus::Tripped => false,
+        }
+    }
+
+    /// Move of `price` from the last accepted price, in bps (rounded up)
+    pub fn deviation_bps(&self, price: i64, exponent: i32) -> Result<u64> {
+        let last = OracleDecimal::from_price(self.last_price, self.last_exponent)?;
+        require!(!last.is_zero(), OracleError::ZeroPrice);
+        let new = OracleDecimal::from_price(price, exponent)?;
+
+        // Common scale, so mantissas compare directly; growing scale is exact
+        let scale = last.scale.max(new.scale);
+        let (last_at, new_at) = (
+            last.rescale(scale, Rounding::Floor)?,
+            new.rescale(scale, Rounding::Floor)?,
+        );
+        let diff = OracleDecimal::new(last_at.mantissa.abs_diff(new_at.mantissa), scale);
+
+        diff.checked_mul(OracleDecimal::new(BPS_DENOMINATOR, 0))?
+            .checked_div(last, 0, Rounding::Ceil)?
+            .to_u64(0, Rounding::Ceil)
+    }
+
+    /// Feed a validated price of `feed_id` through the breaker and return
+    /// the resulting status. Accepted prices become the new reference; a
+    /// tripping price does not, so the breaker keeps comparing against the
+    /// last good one.
+    pub fn observe(&mut self, feed_id: &[u8; 32], price: &ValidatedPrice) -> Result<BreakerStatus> {
+        require!(price.feed_id == *feed_id, OracleError::FeedIdMismatch);
+
+        // Nothing to compare against yet, or nothing newer than the reference
+        if self.last_publish_time == 0 {
+            self.accept(price);
+            return Ok(self.status);
+        }
+        if price.publish_time <= self.last_publish_time || self.status == BreakerStatus::Tripped {
+            return Ok(self.status);
+        }
+
+        let elapsed = price.publish_time - self.last_publish_time;
+        let deviation = self.deviation_bps(price.price, price.exponent)?;
+        if elapsed <= self.config.window_secs && deviation > self.config.max_deviation_bps {
+            self.trip(feed_id, price, deviation, elapsed);
+        } else {
+            self.accept(price);
+        }
+        Ok(self.status)
+    }
+
+    /// Observe `price` and report whether `operation` may proceed
+    ///
+    /// When this returns `false`, return `Ok(())` from the instruction
+    /// without acting on the price; failing instead would roll back the
+    /// trip.
+    pub fn guard(&mut self, feed_id: &[u8; 32], price: &ValidatedPrice, operation: OperationKind) -> Result<bool> {
+        self.observe(feed_id, price)?;
+        let allowed = self.allows(operation);
+        if !allowed {
+            msg!("Circuit breaker {:?}: operation skipped", self.status);
+        }
+        Ok(allowed)
+    }
+
+    /// Return to normal; with `reanchor_to`, that price becomes the
+    /// reference
+    pub fn reset(&mut self, reanchor_to: Option<&ValidatedPrice>) {
+        self.status = BreakerStatus::Normal;
+        if let Some(price) = reanchor_to {
+            self.accept(price);
+        }
+    }
+
+    fn accept(&mut self, price: &ValidatedPrice) {
+        self.last_price = price.price;
+        self.last_exponent = price.exponent;
+        self.last_publish_time = price.publish_time;
+    }
+
+    fn trip(&mut self, feed_id: &[u8; 32], price: &ValidatedPrice, deviation_bps: u64, elapsed_secs: i64) {
+        self.status = match self.config.on_trip {
+            TripAction::Reject => BreakerStatus::Tripped,
+            TripAction::Restrict => BreakerStatus::Restricted,
+        };
+        self.trip_price = price.price;
+        self.trip_exponent = price.exponent;
+        self.tripped_at = price.publish_time;
+        self.trip_count = self.trip_count.saturating_add(1);
+
+        emit!(CircuitBreakerTripped {
+            breaker: *feed_id,
+            status: self.status,
+            reference_price: self.last_price,
+            reference_exponent: self.last_exponent,
+            price: price.price,
+            exponent: price.exponent,
+            deviation_bps,
+            elapsed_secs,
+        });
+    }
+}
+
+/// Per-feed breaker state
+#[account]
+pub struct CircuitBreaker {
+    /// May reconfigure and reset the breaker
+    pub authority: Pubkey,
+    pub feed_id: [u8; 32],
+    pub state: BreakerState,
+    pub bump: u8,
+}
+
+impl CircuitBreaker {
+    pub const LEN: usize = 8 + 32 + 32 + BreakerState::LEN + 1;
+
+    /// Whether an operation may proceed in the current status
+    pub fn allows(&self, operation: OperationKind) -> bool {
+        self.state.allows(operation)
+    }
+
+    /// `BreakerState::observe` for this breaker's feed
+    pub fn observe(&mut self, price: &ValidatedPrice) -> Result<BreakerStatus> {
+        self.state.observe(&self.feed_id, price)
+    }
+}
+
+// ============================================================================
+// GUARDS
+// ============================================================================
+
+/// Observe `price` and report whether `operation` may proceed
+///
+/// When this returns `false`, return `Ok(())` from the instruction without
+/// acting on the price; failing instead would roll back the trip.
+pub fn guard_price(
+    breaker: &mut CircuitBreaker,
+    price: &ValidatedPrice,
+    operation: OperationKind,
+) -> Result<bool> {
+    breaker.state.guard(&breaker.feed_id, price, operation)
+}
+
+/// Read-only check for instructions that cannot write the breaker
+pub fn require_operational(breaker: &CircuitBreaker, operation: OperationKind) -> Result<()> {
+    require!(breaker.allows(operation), OracleError::CircuitBreakerTripped);
+    Ok(())
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct CircuitBreakerTripped {
+    /// Feed ID of the breaker
+    pub breaker: [u8; 32],
+    pub status: BreakerStatus,
+    pub reference_price: i64,
+    pub reference_exponent: i32,
+    pub price: i64,
+    pub exponent: i32,
+    pub deviation_bps: u64,
+    pub elapsed_secs: i64,
+}
+
+#[event]
+pub struct CircuitBreakerReset {
+    pub breaker: [u8; 32],
+    pub authority: Pubkey,
+    /// Whether the reference was moved to the price at reset time
+    pub reanchored: bool,
+    pub price: i64,
+    pub exponent: i32,
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(feed_id: [u8; 32])]
+pub struct InitCircuitBreaker<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = CircuitBreaker::LEN,
+        seeds = [CIRCUIT_BREAKER_SEED, authority.key().as_ref(), feed_id.as_ref()],
+        bump
+    )]
+    pub circuit_breaker: Account<'info, CircuitBreaker>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Permissionless: anyone may push a fresh price through the breaker
+#[derive(Accounts)]
+pub struct CheckCircuitBreaker<'info> {
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    #[account(
+        mut,
+        seeds = [
+            CIRCUIT_BREAKER_SEED,
+            circuit_breaker.authority.as_ref(),
+            circuit_breaker.feed_id.as_ref(),
+        ],
+        bump = circuit_breaker.bump
+    )]
+    pub circuit_breaker: Account<'info, CircuitBreaker>,
+}
+
+#[derive(Accounts)]
+pub struct ResetCircuitBreaker<'info> {
+    pub authority: Signer<'info>,
+
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    #[account(
+        mut,
+        seeds = [
+            CIRCUIT_BREAKER_SEED,
+            authority.key().as_ref(),
+            circuit_breaker.feed_id.as_ref(),
+        ],
+        bump = circuit_breaker.bump,
+        has_one = authority
+    )]
+    pub circuit_breaker: Account<'info, CircuitBreaker>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn init_circuit_breaker(
+    ctx: Context<InitCircuitBreaker>,
+    feed_id: [u8; 32],
+    config: BreakerConfig,
+) -> Result<()> {
+    config.validate()?;
+
+    let breaker = &mut ctx.accounts.circuit_breaker;
+    breaker.authority = ctx.accounts.authority.key();
+    breaker.feed_id = feed_id;
+    breaker.state = BreakerState::new(config);
+    breaker.bump = ctx.bumps.circuit_breaker;
+    Ok(())
+}
+
+/// Observe the latest price; always succeeds so a trip is persisted
+pub fn check_circuit_breaker(
+    ctx: Context<CheckCircuitBreaker>,
+    config: PriceValidationConfig,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let price = get_validated_price(&ctx.accounts.price_update, &config, &clock)?;
+    let status = ctx.accounts.circuit_breaker.observe(&price)?;
+    msg!("Circuit breaker status: {:?}", status);
+    Ok(())
+}
+
+/// Return the breaker to normal. With `reanchor`, the current price becomes
+/// the reference; without it, the next price is compared against the last
+/// price accepted before the trip.
+pub fn reset_circuit_breaker(
+    ctx: Context<ResetCircuitBreaker>,
+    config: PriceValidationConfig,
+    reanchor: bool,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let price = get_validated_price(&ctx.accounts.price_update, &config, &clock)?;
+
+    let breaker = &mut ctx.accounts.circuit_breaker;
+    require!(price.feed_id == breaker.feed_id, OracleError::FeedIdMismatch);
+    breaker.state.reset(reanchor.then_some(&price));
+
+    emit!(CircuitBreakerReset {
+        breaker: breaker.feed_id,
+        authority: breaker.authority,
+        reanchored: reanchor,
+        price: price.price,
+        exponent: price.exponent,
+    });
+    Ok(())
+}
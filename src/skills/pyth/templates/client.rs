@@ -0,0 +1,511 @@
+/**
+ * Instruction Builders for the Example Program (Off-Chain)
+ *
+ * Typed builders for the example program's price-consuming instructions
+ * and the addresses of its PDAs. Off-chain code gets each instruction's
+ * discriminator and account order from the `accounts` and `instruction`
+ * modules Anchor generates, and each PDA from the seed constant its
+ * account struct checks, so neither is written out again in bots,
+ * scripts, or tests.
+ *
+ * The builders take the price update accounts to read. Those are accounts
+ * the caller posted (post-update.rs) or the sponsored push feed accounts
+ * (`push_feed_address`), which are fully verified and always present.
+ *
+ * `Position` is not a PDA: the integrating program creates positions (the
+ * tests seed them), so the builders take their address. `BasketPosition`
+ * is one, at [BASKET_POSITION_SEED, owner].
+ *
+ * Setup:
+ * 1. Declared by anchor-oracle.rs behind the `client` feature
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use pyth_solana_receiver_sdk::PostUpdateAtomicParams;
+
+use crate::post_update::client::{
+    get_guardian_set_address, guardian_set_index, parse_accumulator_update, post_and_consume_accounts,
+    trim_vaa_signatures, AccumulatorUpdate, PostUpdateError,
+};
+use crate::post_update::{DEFAULT_ATOMIC_SIGNATURES, DEFAULT_TREASURY_ID};
+
+use crate::{
+    ConfigChange, LazerMessage, PauseFlags, PriceValidationConfig, AMM_REFERENCE_SEED, BASKET_POSITION_SEED,
+    CIRCUIT_BREAKER_SEED, DEPEG_GUARD_SEED, FEED_BINDING_SEED, FUNDING_STATE_SEED, GUARDIAN_SEED, INTEREST_MARKET_SEED,
+    LAST_GOOD_PRICE_SEED, LAST_OBSERVED_PRICE_SEED, LAZER_SIGNERS_SEED, MARKET_SEED, OBSERVATION_LOG_SEED,
+    ORACLE_CONFIG_SEED, PRICE_CACHE_SEED, RISK_PARAMS_SEED, SWAP_POOL_SEED, SWAP_VAULT_SEED, TRIGGER_ORDER_SEED,
+};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Pyth push oracle program; owns the sponsored price feed accounts
+pub const PUSH_ORACLE_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("pythWSnswVUd12oZpeFP8e9CVaEqJg25g1Vtc2biRsT");
+
+/// Shard of the sponsored feeds
+pub const DEFAULT_PUSH_FEED_SHARD: u16 = 0;
+
+// ============================================================================
+// ADDRESSES
+// ============================================================================
+
+fn program_address(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &crate::ID).0
+}
+
+/// Sponsored push feed account of `feed_id` in the default shard
+pub fn push_feed_address(feed_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(
+        &[&DEFAULT_PUSH_FEED_SHARD.to_le_bytes(), feed_id],
+        &PUSH_ORACLE_PROGRAM_ID,
+    )
+    .0
+}
+
+pub fn oracle_config_address() -> Pubkey {
+    program_address(&[ORACLE_CONFIG_SEED])
+}
+
+pub fn lazer_signers_address() -> Pubkey {
+    program_address(&[LAZER_SIGNERS_SEED])
+}
+
+pub fn feed_binding_address(mint: &Pubkey) -> Pubkey {
+    program_address(&[FEED_BINDING_SEED, mint.as_ref()])
+}
+
+pub fn market_address(mint: &Pubkey) -> Pubkey {
+    program_address(&[MARKET_SEED, mint.as_ref()])
+}
+
+pub fn guardian_address() -> Pubkey {
+    program_address(&[GUARDIAN_SEED])
+}
+
+pub fn price_cache_address(feed_id: &[u8; 32]) -> Pubkey {
+    program_address(&[PRICE_CACHE_SEED, feed_id])
+}
+
+pub fn last_good_price_address(feed_id: &[u8; 32]) -> Pubkey {
+    program_address(&[LAST_GOOD_PRICE_SEED, feed_id])
+}
+
+pub fn last_observed_price_address(authority: &Pubkey, feed_id: &[u8; 32]) -> Pubkey {
+    program_address(&[LAST_OBSERVED_PRICE_SEED, authority.as_ref(), feed_id])
+}
+
+pub fn risk_params_address(authority: &Pubkey, mint: &Pubkey) -> Pubkey {
+    program_address(&[RISK_PARAMS_SEED, authority.as_ref(), mint.as_ref()])
+}
+
+pub fn interest_market_address(risk_params: &Pubkey) -> Pubkey {
+    program_address(&[INTEREST_MARKET_SEED, risk_params.as_ref()])
+}
+
+pub fn basket_position_address(owner: &Pubkey) -> Pubkey {
+    program_address(&[BASKET_POSITION_SEED, owner.as_ref()])
+}
+
+pub fn observation_log_address(authority: &Pubkey) -> Pubkey {
+    program_address(&[OBSERVATION_LOG_SEED, authority.as_ref()])
+}
+
+pub fn circuit_breaker_address(authority: &Pubkey, feed_id: &[u8; 32]) -> Pubkey {
+    program_address(&[CIRCUIT_BREAKER_SEED, authority.as_ref(), feed_id])
+}
+
+pub fn depeg_guard_address(authority: &Pubkey, feed_id: &[u8; 32]) -> Pubkey {
+    program_address(&[DEPEG_GUARD_SEED, authority.as_ref(), feed_id])
+}
+
+pub fn funding_state_address(authority: &Pubkey, feed_id: &[u8; 32]) -> Pubkey {
+    program_address(&[FUNDING_STATE_SEED, authority.as_ref(), feed_id])
+}
+
+pub fn trigger_order_address(owner: &Pubkey, order_id: u64) -> Pubkey {
+    program_address(&[TRIGGER_ORDER_SEED, owner.as_ref(), &order_id.to_le_bytes()])
+}
+
+pub fn amm_reference_address(mint: &Pubkey) -> Pubkey {
+    program_address(&[AMM_REFERENCE_SEED, mint.as_ref()])
+}
+
+/// Pool created with `mint_a` first; the swap trades both ways
+pub fn swap_pool_address(mint_a: &Pubkey, mint_b: &Pubkey) -> Pubkey {
+    program_address(&[SWAP_POOL_SEED, mint_a.as_ref(), mint_b.as_ref()])
+}
+
+pub fn swap_vault_address(pool: &Pubkey, mint: &Pubkey) -> Pubkey {
+    program_address(&[SWAP_VAULT_SEED, pool.as_ref(), mint.as_ref()])
+}
+
+// ============================================================================
+// PRICE READS
+// ============================================================================
+
+fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `get_price`: validate with the default config and log the price
+pub fn get_price(price_update: &Pubkey) -> Instruction {
+    instruction(
+        crate::accounts::SinglePriceContext {
+            price_update: *price_update,
+        },
+        crate::instruction::GetPrice {},
+    )
+}
+
+/// `get_verified_price`: strict validation against `feed_id`, emitting
+/// `PriceConsumed`
+pub fn get_verified_price(price_update: &Pubkey, feed_id: &[u8; 32]) -> Instruction {
+    let feed_id_hex: String = feed_id.iter().map(|b| format!("{:02x}", b)).collect();
+    instruction(
+        crate::accounts::SinglePriceContext {
+            price_update: *price_update,
+        },
+        crate::instruction::GetVerifiedPrice { feed_id_hex },
+    )
+}
+
+/// `post_and_get_price` carrying the first price update of one Hermes
+/// blob (`update_data`) inline; the program creates `price_update`, a fresh
+/// keypair that signs the transaction
+pub fn post_and_get_price(
+    payer: &Pubkey,
+    price_update: &Pubkey,
+    update_data: &[u8],
+) -> std::result::Result<Instruction, PostUpdateError> {
+    let AccumulatorUpdate { vaa, updates } = parse_accumulator_update(update_data)?;
+    let merkle_price_update = updates.into_iter().next().ok_or(PostUpdateError::InvalidUpdateData)?;
+    Ok(Instruction {
+        program_id: crate::ID,
+        accounts: post_and_consume_accounts(payer, update_data, price_update, DEFAULT_TREASURY_ID)?,
+        data: crate::instruction::PostAndGetPrice {
+            params: PostUpdateAtomicParams {
+                vaa: trim_vaa_signatures(&vaa, DEFAULT_ATOMIC_SIGNATURES)?,
+                merkle_price_update,
+                treasury_id: DEFAULT_TREASURY_ID,
+            },
+        }
+        .data(),
+    })
+}
+
+/// `get_price_inline` carrying the first price update of one Hermes blob
+/// as instruction data; the program verifies its signatures itself, so no
+/// account is posted. Each signature costs about 25,000 compute units.
+pub fn get_price_inline(update_data: &[u8]) -> std::result::Result<Instruction, PostUpdateError> {
+    let AccumulatorUpdate { vaa, updates } = parse_accumulator_update(update_data)?;
+    let merkle_price_update = updates.into_iter().next().ok_or(PostUpdateError::InvalidUpdateData)?;
+    Ok(instruction(
+        crate::accounts::InlinePriceContext {
+            guardian_set: get_guardian_set_address(guardian_set_index(&vaa)?),
+        },
+        crate::instruction::GetPriceInline {
+            vaa: trim_vaa_signatures(&vaa, DEFAULT_ATOMIC_SIGNATURES)?,
+            merkle_price_update,
+        },
+    ))
+}
+
+/// Ed25519 program instruction verifying a Lazer message (`solana`
+/// format), with the signature, public key, and payload in its own data
+pub fn lazer_ed25519_instruction(message: &[u8]) -> Result<Instruction> {
+    let message = LazerMessage::parse(message)?;
+    // Header, then public key, signature, and payload
+    let (public_key, signature, payload) = (16u16, 48u16, 112u16);
+    let mut data = vec![1, 0];
+    for field in [
+        signature,
+        u16::MAX,
+        public_key,
+        u16::MAX,
+        payload,
+        message.payload.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(message.public_key);
+    data.extend_from_slice(message.signature);
+    data.extend_from_slice(message.payload);
+    Ok(Instruction {
+        program_id: anchor_lang::solana_program::ed25519_program::ID,
+        accounts: vec![],
+        data,
+    })
+}
+
+/// The Ed25519 instruction and `get_lazer_price` for feed `feed_id` of a
+/// Lazer message, in that order; the transaction must start with them
+pub fn get_lazer_price(message: &[u8], feed_id: u32) -> Result<[Instruction; 2]> {
+    Ok([
+        lazer_ed25519_instruction(message)?,
+        instruction(
+            crate::accounts::LazerPriceContext {
+                lazer_signers: lazer_signers_address(),
+                instructions: anchor_lang::solana_program::sysvar::instructions::ID,
+            },
+            crate::instruction::GetLazerPrice {
+                message: message.to_vec(),
+                ed25519_index: 0,
+                feed_id,
+            },
+        ),
+    ])
+}
+
+/// `get_price_with_config`: validate with the limits of the oracle config
+pub fn get_price_with_config(price_update: &Pubkey, feed_id: &[u8; 32]) -> Instruction {
+    instruction(
+        crate::accounts::ConfiguredPriceContext {
+            price_update: *price_update,
+            oracle_config: oracle_config_address(),
+        },
+        crate::instruction::GetPriceWithConfig { feed_id: *feed_id },
+    )
+}
+
+/// `get_cached_price`; `price_update` is only read on a cache miss, so a
+/// caller that knows the cache is warm for the slot may leave it out
+pub fn get_cached_price(
+    feed_id: &[u8; 32],
+    price_update: Option<&Pubkey>,
+    config: PriceValidationConfig,
+) -> Instruction {
+    instruction(
+        crate::accounts::ReadPriceCache {
+            price_cache: price_cache_address(feed_id),
+            price_update: price_update.copied(),
+        },
+        crate::instruction::GetCachedPrice { config },
+    )
+}
+
+// ============================================================================
+// SWAPS AND POSITIONS
+// ============================================================================
+
+/// Accounts of a swap that the builder cannot derive
+pub struct SwapAccounts {
+    pub user: Pubkey,
+    /// From `swap_pool_address`, in the order the pool was created
+    pub pool: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    /// The user's token account of the input mint
+    pub user_input: Pubkey,
+    /// Any token account of the output mint
+    pub user_output: Pubkey,
+    pub input_price: Pubkey,
+    pub output_price: Pubkey,
+    /// Token program of both mints
+    pub token_program: Pubkey,
+}
+
+/// `swap_with_oracle`, with the vaults and markets derived. Pass
+/// `close_price_updates` to close price updates the user posted and refund
+/// their rent in the same instruction. `swap_quote::min_amount_out`
+/// (feature "hermes") computes `min_amount_out` from the posted prices.
+pub fn swap_with_oracle(
+    accounts: &SwapAccounts,
+    amount_in: u64,
+    min_amount_out: u64,
+    close_price_updates: bool,
+) -> Instruction {
+    instruction(
+        crate::accounts::SwapWithOracle {
+            user: accounts.user,
+            pool: accounts.pool,
+            input_mint: accounts.input_mint,
+            output_mint: accounts.output_mint,
+            input_vault: swap_vault_address(&accounts.pool, &accounts.input_mint),
+            output_vault: swap_vault_address(&accounts.pool, &accounts.output_mint),
+            user_input: accounts.user_input,
+            user_output: accounts.user_output,
+            input_price: accounts.input_price,
+            output_price: accounts.output_price,
+            input_market: market_address(&accounts.input_mint),
+            output_market: market_address(&accounts.output_mint),
+            token_program: accounts.token_program,
+            pyth_receiver: close_price_updates.then_some(crate::PYTH_RECEIVER_PROGRAM_ID),
+        },
+        crate::instruction::SwapWithOracle {
+            amount_in,
+            min_amount_out,
+        },
+    )
+}
+
+/// `update_collateral_value` of a position whose collateral is
+/// `collateral_mint`. `last_observed_price` and `depeg_guard` are the
+/// optional guards, by address.
+pub fn update_collateral_value(
+    owner: &Pubkey,
+    position: &Pubkey,
+    collateral_mint: &Pubkey,
+    collateral_price: &Pubkey,
+    last_observed_price: Option<Pubkey>,
+    depeg_guard: Option<Pubkey>,
+) -> Instruction {
+    instruction(
+        crate::accounts::ValueCollateral {
+            owner: *owner,
+            collateral_price: *collateral_price,
+            position: *position,
+            collateral_market: market_address(collateral_mint),
+            last_observed_price,
+            depeg_guard,
+        },
+        crate::instruction::UpdateCollateralValue {},
+    )
+}
+
+/// Price and risk parameter accounts of a position's two legs
+pub struct PositionPrices {
+    pub collateral_price: Pubkey,
+    pub debt_price: Pubkey,
+    pub collateral_params: Pubkey,
+    pub debt_params: Pubkey,
+    /// `interest_market_address(&debt_params)` if the debt asset has one;
+    /// the debt accrues before it is valued
+    pub debt_market: Option<Pubkey>,
+}
+
+/// `refresh_position_health`
+pub fn refresh_position_health(position: &Pubkey, prices: &PositionPrices) -> Instruction {
+    instruction(
+        crate::accounts::RefreshPositionHealth {
+            collateral_price: prices.collateral_price,
+            debt_price: prices.debt_price,
+            collateral_params: prices.collateral_params,
+            debt_params: prices.debt_params,
+            position: *position,
+            debt_market: prices.debt_market,
+        },
+        crate::instruction::RefreshPositionHealth {},
+    )
+}
+
+/// Price accounts and mints of a position's two legs, for `liquidate`;
+/// the markets are derived from the mints
+pub struct LiquidationPrices {
+    pub collateral_mint: Pubkey,
+    pub debt_mint: Pubkey,
+    pub collateral_price: Pubkey,
+    pub debt_price: Pubkey,
+    /// The debt market's `has_interest_market`: `liquidate` then requires
+    /// its interest market, and accrues the debt before the health check
+    pub accrue_debt: bool,
+}
+
+/// `liquidate`; fails with `SlippageExceeded` if less than
+/// `min_collateral_out` would be seized
+pub fn liquidate(
+    liquidator: &Pubkey,
+    position: &Pubkey,
+    prices: &LiquidationPrices,
+    repay_amount: u64,
+    min_collateral_out: u64,
+) -> Instruction {
+    let debt_market = market_address(&prices.debt_mint);
+    instruction(
+        crate::accounts::Liquidate {
+            liquidator: *liquidator,
+            collateral_price: prices.collateral_price,
+            debt_price: prices.debt_price,
+            collateral_market: market_address(&prices.collateral_mint),
+            debt_market,
+            position: *position,
+            debt_interest: prices.accrue_debt.then(|| interest_market_address(&debt_market)),
+        },
+        crate::instruction::Liquidate {
+            repay_amount,
+            min_collateral_out,
+        },
+    )
+}
+
+/// `accrue_interest` of the market of `risk_params`' asset
+pub fn accrue_interest(risk_params: &Pubkey) -> Instruction {
+    instruction(
+        crate::accounts::AccrueInterest {
+            interest_market: interest_market_address(risk_params),
+        },
+        crate::instruction::AccrueInterest {},
+    )
+}
+
+/// `update_funding`: accrue funding and set the next rate from the mark
+/// price `mark_price × 10^mark_exponent`
+pub fn update_funding(
+    authority: &Pubkey,
+    price_update: &Pubkey,
+    feed_id: &[u8; 32],
+    mark_price: i64,
+    mark_exponent: i32,
+) -> Instruction {
+    instruction(
+        crate::accounts::UpdateFunding {
+            authority: *authority,
+            price_update: *price_update,
+            funding_state: funding_state_address(authority, feed_id),
+        },
+        crate::instruction::UpdateFunding {
+            mark_price,
+            mark_exponent,
+        },
+    )
+}
+
+/// `execute_trigger` of `owner`'s order `order_id`, read at `price_update`
+pub fn execute_trigger(executor: &Pubkey, owner: &Pubkey, order_id: u64, price_update: &Pubkey) -> Instruction {
+    instruction(
+        crate::accounts::ExecuteTrigger {
+            executor: *executor,
+            owner: *owner,
+            price_update: *price_update,
+            trigger_order: trigger_order_address(owner, order_id),
+        },
+        crate::instruction::ExecuteTrigger {},
+    )
+}
+
+// ============================================================================
+// CONFIG
+// ============================================================================
+
+/// `propose_oracle_config`: start the timelock on `change`
+pub fn propose_oracle_config(authority: &Pubkey, change: ConfigChange) -> Instruction {
+    instruction(
+        crate::accounts::UpdateOracleConfig {
+            authority: *authority,
+            oracle_config: oracle_config_address(),
+        },
+        crate::instruction::ProposeOracleConfig { change },
+    )
+}
+
+/// `pause_market`: pause `operations` of the market of `mint`; `signer` is
+/// the guardian or the oracle config authority
+pub fn pause_market(signer: &Pubkey, mint: &Pubkey, operations: PauseFlags) -> Instruction {
+    instruction(
+        crate::accounts::PauseMarket {
+            signer: *signer,
+            oracle_config: oracle_config_address(),
+            guardian: guardian_address(),
+            market: market_address(mint),
+        },
+        crate::instruction::PauseMarket { operations },
+    )
+}
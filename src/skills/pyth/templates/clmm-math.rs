@@ -0,0 +1,278 @@
+/**
+ * Concentrated Liquidity Price Conversions
+ *
+ * CLMM pools (Orca Whirlpools, Raydium CLMM) do not store prices as
+ * `price × 10^exponent`. They store the square root of the price as a
+ * Q64.64 fixed-point number (`sqrt_price_x64`), and positions are bounded
+ * by tick indices, where tick `i` is the price 1.0001^i. A program that
+ * places or rebalances liquidity around the oracle price needs the Pyth
+ * price in those units.
+ *
+ * A pool price is in base units: token_b base units per token_a base unit.
+ * The `ValidatedPrice` methods take both mints' decimals and convert a
+ * price of token_a in token_b (for a USD-quoted pool, the Pyth price
+ * itself; otherwise the ratio of two prices) into:
+ * - `to_q64`: the price as Q64.64
+ * - `to_sqrt_price_x64`: its square root as Q64.64, what pools store
+ * - `to_tick_index`: the nearest tick that is a multiple of the pool's
+ *   tick spacing, for position bounds
+ * The `decimal_*` functions do the same for an `OracleDecimal`.
+ *
+ * Q64.64 conversions are exact, then rounded as requested. The square
+ * root is exact for prices below one and within 2^-63 relative above.
+ * Ticks go through `ln` and `exp_wad`, so they agree with the pools' own
+ * tick math to about 1e-16 relative: enough to place a range or check a
+ * price, but do not expect the last bit of a pool's `sqrt_price_x64` for a
+ * tick. Tick conversions cost several logarithm and exponential
+ * evaluations; convert once per instruction.
+ *
+ * Everything is checked: overflow fails with `MathOverflow`, and a tick
+ * or square-root price outside the range pools accept fails with
+ * `TickOutOfRange`.
+ *
+ * Setup:
+ * Declared by anchor-oracle.rs as `clmm`; requires oracle-decimal.rs.
+ * amm-cross-check.rs reads pool prices with `sqrt_price_x64_to_decimal`.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::decimal::{exp_wad, isqrt, pow10};
+use crate::{OracleDecimal, OracleError, Rounding, ValidatedPrice};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// 2^64, one in Q64.64
+pub const Q64: u128 = 1 << 64;
+
+/// Tick range of Orca Whirlpools and Raydium CLMM
+pub const MIN_TICK_INDEX: i32 = -443_636;
+pub const MAX_TICK_INDEX: i32 = 443_636;
+
+/// Square-root prices of `MIN_TICK_INDEX` and `MAX_TICK_INDEX`, Q64.64
+pub const MIN_SQRT_PRICE_X64: u128 = 4_295_048_016;
+pub const MAX_SQRT_PRICE_X64: u128 = 79_226_673_515_401_279_992_447_579_055;
+
+const WAD: u128 = 1_000_000_000_000_000_000;
+
+/// ln(sqrt(1.0001)) × 10^36: the exponent of one tick of square-root price
+const LN_SQRT_TICK_BASE_E36: i128 = 49_997_500_166_654_167_666_583_340_475_566;
+
+// ============================================================================
+// FIXED-POINT HELPERS
+// ============================================================================
+
+/// `numerator × 2^bits / denominator` (`denominator` in (0, 2^127)),
+/// rounded as requested; `None` if it does not fit a u128
+fn shl_div(numerator: u128, denominator: u128, bits: u32, rounding: Rounding) -> Option<u128> {
+    let whole = numerator / denominator;
+    if whole.leading_zeros() < bits {
+        return None;
+    }
+    let mut remainder = numerator % denominator;
+    let mut fraction = 0u128;
+    if denominator <= u64::MAX as u128 && bits <= 64 {
+        let shifted = remainder << bits;
+        (fraction, remainder) = (shifted / denominator, shifted % denominator);
+    } else {
+        // Long division, one bit at a time; remainder < denominator < 2^127,
+        // so doubling it cannot overflow
+        for _ in 0..bits {
+            remainder <<= 1;
+            fraction <<= 1;
+            if remainder >= denominator {
+                remainder -= denominator;
+                fraction |= 1;
+            }
+        }
+    }
+
+    let round_up = match rounding {
+        Rounding::Floor => false,
+        Rounding::Ceil => remainder > 0,
+        Rounding::Nearest => remainder >= denominator - remainder,
+    };
+    let high = if whole == 0 { 0 } else { whole << bits };
+    (high | fraction).checked_add(round_up as u128)
+}
+
+/// `value` as `numerator / denominator` with a power-of-ten denominator
+fn as_fraction(value: OracleDecimal, rounding: Rounding) -> Result<(u128, u128)> {
+    // 10^38 is the largest power of ten that fits; finer values are rounded
+    // to 38 decimal places first
+    let value = if value.scale > 38 { value.rescale(38, rounding)? } else { value };
+    let factor = pow10(value.scale.unsigned_abs()).ok_or_else(|| error!(OracleError::MathOverflow))?;
+    if value.scale >= 0 {
+        Ok((value.mantissa, factor))
+    } else {
+        let numerator = value
+            .mantissa
+            .checked_mul(factor)
+            .ok_or_else(|| error!(OracleError::MathOverflow))?;
+        Ok((numerator, 1))
+    }
+}
+
+// ============================================================================
+// Q64.64 AND SQUARE-ROOT PRICES
+// ============================================================================
+
+/// `value` as Q64.64
+pub fn decimal_to_q64(value: OracleDecimal, rounding: Rounding) -> Result<u128> {
+    let (numerator, denominator) = as_fraction(value, rounding)?;
+    shl_div(numerator, denominator, 64, rounding).ok_or_else(|| error!(OracleError::MathOverflow))
+}
+
+/// Q64.64 value as a decimal with `scale` decimal places
+pub fn q64_to_decimal(q64: u128, scale: i32, rounding: Rounding) -> Result<OracleDecimal> {
+    OracleDecimal::new(q64, 0).checked_div(OracleDecimal::new(Q64, 0), scale, rounding)
+}
+
+/// Square root of `value` as Q64.64: exact below one, within 2^-63
+/// relative above
+pub fn decimal_to_sqrt_price_x64(value: OracleDecimal, rounding: Rounding) -> Result<u128> {
+    let (numerator, denominator) = as_fraction(value, rounding)?;
+
+    // sqrt(value) × 2^64 = sqrt(value × 2^(128 - 2k)) × 2^k, with k just
+    // large enough that the radicand fits a u128
+    let whole = numerator / denominator;
+    let k = (128 - whole.leading_zeros()).div_ceil(2);
+    let radicand =
+        shl_div(numerator, denominator, 128 - 2 * k, rounding).ok_or_else(|| error!(OracleError::MathOverflow))?;
+
+    let root = isqrt(radicand);
+    let remainder = radicand - root * root;
+    let round_up = match rounding {
+        Rounding::Floor => false,
+        Rounding::Ceil => remainder > 0,
+        // (root + 1/2)^2 = root^2 + root + 1/4
+        Rounding::Nearest => remainder > root,
+    };
+    let root = root + round_up as u128;
+    require!(root.leading_zeros() >= k, OracleError::MathOverflow);
+    Ok(root << k)
+}
+
+/// Price of a Q64.64 square-root price, with `scale` decimal places
+pub fn sqrt_price_x64_to_decimal(sqrt_price_x64: u128, scale: i32, rounding: Rounding) -> Result<OracleDecimal> {
+    // As many decimal places for the root as keep its mantissa below 2^64,
+    // so the square fits: 10^root_scale <= 2^(128 - bits)
+    let bits = 128 - sqrt_price_x64.leading_zeros();
+    let root_scale = ((128 - bits) * 30_103 / 100_000) as i32;
+    let root = q64_to_decimal(sqrt_price_x64, root_scale, rounding)?;
+    root.checked_mul(root)?.rescale(scale, rounding)
+}
+
+// ============================================================================
+// TICKS
+// ============================================================================
+
+/// Square-root price of a tick, 1.0001^(tick / 2) as Q64.64, without the
+/// range check
+fn tick_sqrt_price_x64(tick_index: i32) -> Result<u128> {
+    // e^(|tick| × ln(sqrt(1.0001))), then inverted for negative ticks
+    let exponent = (tick_index.unsigned_abs() as i128 * LN_SQRT_TICK_BASE_E36 + WAD as i128 / 2) / WAD as i128;
+    let magnitude =
+        shl_div(exp_wad(exponent)?, WAD, 64, Rounding::Floor).ok_or_else(|| error!(OracleError::MathOverflow))?;
+    if tick_index >= 0 {
+        Ok(magnitude)
+    } else {
+        shl_div(Q64, magnitude, 64, Rounding::Floor).ok_or_else(|| error!(OracleError::MathOverflow))
+    }
+}
+
+/// Square-root price of a tick, Q64.64
+pub fn tick_index_to_sqrt_price_x64(tick_index: i32) -> Result<u128> {
+    require!(
+        (MIN_TICK_INDEX..=MAX_TICK_INDEX).contains(&tick_index),
+        OracleError::TickOutOfRange
+    );
+    tick_sqrt_price_x64(tick_index)
+}
+
+/// Highest tick whose square-root price is at most `sqrt_price_x64`, the
+/// tick a pool at that price is in
+pub fn sqrt_price_x64_to_tick_index(sqrt_price_x64: u128) -> Result<i32> {
+    require!(
+        (MIN_SQRT_PRICE_X64..=MAX_SQRT_PRICE_X64).contains(&sqrt_price_x64),
+        OracleError::TickOutOfRange
+    );
+
+    // tick = 2 ln(sqrt_price) / ln(1.0001), which lands on the tick or next
+    // to it; `tick_sqrt_price_x64` settles which
+    let ln_sqrt_price = OracleDecimal::new(sqrt_price_x64, 0).ln()? - OracleDecimal::new(Q64, 0).ln()?;
+    let estimate = (ln_sqrt_price * WAD as i128).div_euclid(LN_SQRT_TICK_BASE_E36);
+    let mut tick_index = i32::try_from(estimate).map_err(|_| error!(OracleError::MathOverflow))?;
+    if tick_sqrt_price_x64(tick_index + 1)? <= sqrt_price_x64 {
+        tick_index += 1;
+    } else if tick_sqrt_price_x64(tick_index)? > sqrt_price_x64 {
+        tick_index -= 1;
+    }
+    Ok(tick_index)
+}
+
+/// Multiple of `tick_spacing` nearest to log_1.0001(`value`), halves away
+/// from zero
+pub fn decimal_to_tick_index(value: OracleDecimal, tick_spacing: u16) -> Result<i32> {
+    require!(tick_spacing > 0, OracleError::InvalidTickSpacing);
+    require!(!value.is_zero(), OracleError::ZeroPrice);
+
+    // ticks = ln(value) / ln(1.0001), in units of tick_spacing
+    let numerator = value
+        .ln()?
+        .checked_mul(WAD as i128)
+        .ok_or_else(|| error!(OracleError::MathOverflow))?;
+    let denominator = 2 * LN_SQRT_TICK_BASE_E36 * tick_spacing as i128;
+    let (quotient, remainder) = (numerator / denominator, numerator % denominator);
+    let steps = quotient + (2 * remainder.abs() >= denominator) as i128 * numerator.signum();
+
+    let tick_index = steps
+        .checked_mul(tick_spacing as i128)
+        .and_then(|tick| i32::try_from(tick).ok())
+        .ok_or_else(|| error!(OracleError::TickOutOfRange))?;
+    require!(
+        (MIN_TICK_INDEX..=MAX_TICK_INDEX).contains(&tick_index),
+        OracleError::TickOutOfRange
+    );
+    Ok(tick_index)
+}
+
+// ============================================================================
+// ORACLE PRICES
+// ============================================================================
+
+impl ValidatedPrice {
+    /// Price in pool units: quote base units per base unit of the priced
+    /// token, given both mints' decimals
+    pub fn to_base_unit_price(&self, base_decimals: u8, quote_decimals: u8) -> Result<OracleDecimal> {
+        require!(self.price > 0, OracleError::NegativePrice);
+        let price = OracleDecimal::from_price(self.price, self.exponent)?;
+        // whole quote per whole base, times 10^(quote_decimals - base_decimals)
+        let scale = price.scale + base_decimals as i32 - quote_decimals as i32;
+        Ok(OracleDecimal::new(price.mantissa, scale))
+    }
+
+    /// Pool price as Q64.64
+    pub fn to_q64(&self, base_decimals: u8, quote_decimals: u8, rounding: Rounding) -> Result<u128> {
+        decimal_to_q64(self.to_base_unit_price(base_decimals, quote_decimals)?, rounding)
+    }
+
+    /// Pool square-root price as Q64.64, as CLMM pools store it; fails with
+    /// `TickOutOfRange` outside the range pools accept
+    pub fn to_sqrt_price_x64(&self, base_decimals: u8, quote_decimals: u8, rounding: Rounding) -> Result<u128> {
+        let price = self.to_base_unit_price(base_decimals, quote_decimals)?;
+        let sqrt_price_x64 = decimal_to_sqrt_price_x64(price, rounding)?;
+        require!(
+            (MIN_SQRT_PRICE_X64..=MAX_SQRT_PRICE_X64).contains(&sqrt_price_x64),
+            OracleError::TickOutOfRange
+        );
+        Ok(sqrt_price_x64)
+    }
+
+    /// Initializable tick nearest to the pool price
+    pub fn to_tick_index(&self, base_decimals: u8, quote_decimals: u8, tick_spacing: u16) -> Result<i32> {
+        decimal_to_tick_index(self.to_base_unit_price(base_decimals, quote_decimals)?, tick_spacing)
+    }
+}
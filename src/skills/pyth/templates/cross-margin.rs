@@ -0,0 +1,395 @@
+/**
+ * Cross-Margin Portfolio Risk
+ *
+ * A margin engine over `BasketPosition`: every holding may be deposited
+ * (long) and borrowed (short), and one margin requirement covers the whole
+ * portfolio. Each mint has an `AssetMarginConfig` with two haircuts, one
+ * for the initial and one for the maintenance requirement, and a
+ * correlation group:
+ *
+ * - A side's requirement is its USD value times the haircut. Longs are
+ *   valued at the lower confidence bound (rounded down), shorts at the
+ *   upper bound (rounded up).
+ * - Within a group, longs and shorts net: the larger side counts in full
+ *   and the smaller side counts at `10_000 - offset_bps`. Requirements are
+ *   summed per side before netting, so one dollar of long relieves at most
+ *   one dollar of short, however many correlated mints the group spans.
+ * - Group 0 never nets. Assets that hedge nothing belong there.
+ *
+ * `portfolio_margin` is a pure function of the exposures and group
+ * offsets, so keepers and front ends compute the same numbers off chain.
+ * `basket_exposures` prices a position's exposures from one
+ * `PriceSnapshot` (price-snapshot.rs), so a long and the short it hedges
+ * are never valued at different moments.
+ * A position may open risk while `meets_initial_margin` holds and may be
+ * liquidated once `is_liquidatable` does.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as `margin`);
+ *    requires basket-valuation.rs
+ * 2. Create the group offsets with `init_margin_groups` and one
+ *    `MarginParams` per mint with `init_margin_params`
+ * 3. `refresh_margin` takes the holdings' `MarginParams` (in holding order)
+ *    followed by one price update per distinct feed as remaining accounts,
+ *    published at most `max_skew_secs` apart
+ */
+
+use anchor_lang::prelude::*;
+use arrayvec::ArrayVec;
+
+use crate::lending::apply_bps;
+use crate::{
+    calculate_usd_value, load_price_snapshot, BasketPosition, OracleError, PriceSnapshot, PriceValidationConfig,
+    Rounding, ValidatedPrice, MAX_BASKET_ASSETS,
+};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for per-mint margin parameters: [MARGIN_PARAMS_SEED, authority, mint]
+pub const MARGIN_PARAMS_SEED: &[u8] = b"margin_params";
+
+/// PDA seed for group offsets: [MARGIN_GROUPS_SEED, authority]
+pub const MARGIN_GROUPS_SEED: &[u8] = b"margin_groups";
+
+/// Number of correlation groups, including the non-netting group 0
+pub const MAX_CORRELATION_GROUPS: usize = 16;
+
+const BPS_DENOMINATOR: u16 = 10_000;
+
+// ============================================================================
+// MARGIN PARAMETERS
+// ============================================================================
+
+/// Per-mint margin parameters, as passed to `init_margin_params`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct AssetMarginConfig {
+    /// Correlation group; 0 never nets
+    pub group: u8,
+    /// Share of each side's value required to open risk
+    pub initial_haircut_bps: u16,
+    /// Share of each side's value required to avoid liquidation
+    pub maintenance_haircut_bps: u16,
+}
+
+impl AssetMarginConfig {
+    pub fn validate(&self) -> Result<()> {
+        require!((self.group as usize) < MAX_CORRELATION_GROUPS, OracleError::InvalidMarginParams);
+        require!(
+            self.maintenance_haircut_bps <= self.initial_haircut_bps && self.initial_haircut_bps <= BPS_DENOMINATOR,
+            OracleError::InvalidMarginParams
+        );
+        Ok(())
+    }
+}
+
+/// Margin parameters of one mint
+#[account]
+pub struct MarginParams {
+    /// May update the parameters
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub config: AssetMarginConfig,
+    pub bump: u8,
+}
+
+impl MarginParams {
+    pub const LEN: usize = 8 + 32 + 32 + (1 + 2 + 2) + 1;
+}
+
+/// Netting offset of each correlation group
+#[account]
+pub struct MarginGroups {
+    /// May update the offsets
+    pub authority: Pubkey,
+    /// Share of the smaller side waived within the group; group 0 is 0
+    pub offset_bps: [u16; MAX_CORRELATION_GROUPS],
+    pub bump: u8,
+}
+
+impl MarginGroups {
+    pub const LEN: usize = 8 + 32 + 2 * MAX_CORRELATION_GROUPS + 1;
+
+    pub fn validate_offsets(offset_bps: &[u16; MAX_CORRELATION_GROUPS]) -> Result<()> {
+        require!(offset_bps[0] == 0, OracleError::InvalidMarginParams);
+        require!(
+            offset_bps.iter().all(|offset| *offset <= BPS_DENOMINATOR),
+            OracleError::InvalidMarginParams
+        );
+        Ok(())
+    }
+}
+
+// ============================================================================
+// MARGIN ENGINE
+// ============================================================================
+
+/// One mint of a portfolio, with its price
+#[derive(Clone, Copy, Debug)]
+pub struct AssetExposure {
+    pub config: AssetMarginConfig,
+    pub decimals: u8,
+    /// Deposited, in native units
+    pub long: u64,
+    /// Borrowed, in native units
+    pub short: u64,
+    pub price: ValidatedPrice,
+}
+
+/// USD totals of a portfolio (`USD_DECIMALS` decimals)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct PortfolioMargin {
+    /// Longs at the lower confidence bound
+    pub collateral_usd: u64,
+    /// Shorts at the upper confidence bound
+    pub liability_usd: u64,
+    pub initial_margin_usd: u64,
+    pub maintenance_margin_usd: u64,
+}
+
+impl PortfolioMargin {
+    /// Collateral minus liabilities; negative once the position is underwater
+    pub fn equity(&self) -> i128 {
+        self.collateral_usd as i128 - self.liability_usd as i128
+    }
+
+    /// Equity above the initial requirement; negative blocks new risk
+    pub fn free_collateral(&self) -> i128 {
+        self.equity() - self.initial_margin_usd as i128
+    }
+
+    pub fn meets_initial_margin(&self) -> bool {
+        self.free_collateral() >= 0
+    }
+
+    pub fn is_liquidatable(&self) -> bool {
+        self.equity() < self.maintenance_margin_usd as i128
+    }
+}
+
+/// Long and short requirements of one group
+#[derive(Clone, Copy, Default)]
+struct GroupRequirement {
+    long: u64,
+    short: u64,
+}
+
+impl GroupRequirement {
+    fn add(&mut self, long: u64, short: u64) -> Result<()> {
+        self.long = add_usd(self.long, long)?;
+        self.short = add_usd(self.short, short)?;
+        Ok(())
+    }
+
+    /// The larger side in full plus the smaller side less the offset
+    fn netted(&self, offset_bps: u16) -> Result<u64> {
+        let (larger, smaller) = (self.long.max(self.short), self.long.min(self.short));
+        add_usd(larger, apply_bps(smaller, BPS_DENOMINATOR.saturating_sub(offset_bps), Rounding::Ceil)?)
+    }
+}
+
+fn add_usd(a: u64, b: u64) -> Result<u64> {
+    a.checked_add(b).ok_or_else(|| error!(OracleError::MathOverflow))
+}
+
+/// Initial and maintenance margin of `exposures`, netted per correlation
+/// group with `group_offsets`
+pub fn portfolio_margin(
+    exposures: &[AssetExposure],
+    group_offsets: &[u16; MAX_CORRELATION_GROUPS],
+) -> Result<PortfolioMargin> {
+    let mut margin = PortfolioMargin::default();
+    let mut initial = [GroupRequirement::default(); MAX_CORRELATION_GROUPS];
+    let mut maintenance = [GroupRequirement::default(); MAX_CORRELATION_GROUPS];
+
+    for exposure in exposures {
+        let config = &exposure.config;
+        config.validate()?;
+        let price = &exposure.price;
+
+        let long_usd = if exposure.long == 0 || price.lower_bound <= 0 {
+            0
+        } else {
+            calculate_usd_value(exposure.long, exposure.decimals, price.lower_bound, price.exponent, Rounding::Floor)?
+        };
+        let short_usd = if exposure.short == 0 {
+            0
+        } else {
+            calculate_usd_value(exposure.short, exposure.decimals, price.upper_bound, price.exponent, Rounding::Ceil)?
+        };
+        margin.collateral_usd = add_usd(margin.collateral_usd, long_usd)?;
+        margin.liability_usd = add_usd(margin.liability_usd, short_usd)?;
+
+        let group = config.group as usize;
+        initial[group].add(
+            apply_bps(long_usd, config.initial_haircut_bps, Rounding::Ceil)?,
+            apply_bps(short_usd, config.initial_haircut_bps, Rounding::Ceil)?,
+        )?;
+        maintenance[group].add(
+            apply_bps(long_usd, config.maintenance_haircut_bps, Rounding::Ceil)?,
+            apply_bps(short_usd, config.maintenance_haircut_bps, Rounding::Ceil)?,
+        )?;
+    }
+
+    for group in 0..MAX_CORRELATION_GROUPS {
+        let offset_bps = if group == 0 { 0 } else { group_offsets[group] };
+        margin.initial_margin_usd = add_usd(margin.initial_margin_usd, initial[group].netted(offset_bps)?)?;
+        margin.maintenance_margin_usd =
+            add_usd(margin.maintenance_margin_usd, maintenance[group].netted(offset_bps)?)?;
+    }
+
+    Ok(margin)
+}
+
+/// Exposures of `position`, with one `MarginParams` per holding in order,
+/// priced from one snapshot
+pub fn basket_exposures(
+    position: &BasketPosition,
+    params: &[MarginParams],
+    prices: &PriceSnapshot,
+) -> Result<ArrayVec<AssetExposure, MAX_BASKET_ASSETS>> {
+    require!(params.len() == position.holdings.len(), OracleError::InvalidMarginParams);
+    position
+        .holdings
+        .iter()
+        .zip(params)
+        .map(|(holding, params)| {
+            require_keys_eq!(params.mint, holding.mint, OracleError::InvalidMarginParams);
+            Ok(AssetExposure {
+                config: params.config,
+                decimals: holding.decimals,
+                long: holding.amount,
+                short: holding.borrowed,
+                price: prices.get(&holding.feed_id)?,
+            })
+        })
+        .collect()
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct MarginRefreshed {
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub margin: PortfolioMargin,
+    pub liquidatable: bool,
+    pub timestamp: i64,
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct InitMarginParams<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = MarginParams::LEN,
+        seeds = [MARGIN_PARAMS_SEED, authority.key().as_ref(), mint.as_ref()],
+        bump
+    )]
+    pub margin_params: Account<'info, MarginParams>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitMarginGroups<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = MarginGroups::LEN,
+        seeds = [MARGIN_GROUPS_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub margin_groups: Account<'info, MarginGroups>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Margin parameters per holding, then price updates per feed, follow as
+/// remaining accounts
+#[derive(Accounts)]
+pub struct RefreshMargin<'info> {
+    pub basket_position: Account<'info, BasketPosition>,
+
+    #[account(
+        seeds = [MARGIN_GROUPS_SEED, margin_groups.authority.as_ref()],
+        bump = margin_groups.bump
+    )]
+    pub margin_groups: Account<'info, MarginGroups>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn init_margin_params(ctx: Context<InitMarginParams>, mint: Pubkey, config: AssetMarginConfig) -> Result<()> {
+    config.validate()?;
+
+    let params = &mut ctx.accounts.margin_params;
+    params.authority = ctx.accounts.authority.key();
+    params.mint = mint;
+    params.config = config;
+    params.bump = ctx.bumps.margin_params;
+    Ok(())
+}
+
+pub fn init_margin_groups(ctx: Context<InitMarginGroups>, offset_bps: [u16; MAX_CORRELATION_GROUPS]) -> Result<()> {
+    MarginGroups::validate_offsets(&offset_bps)?;
+
+    let groups = &mut ctx.accounts.margin_groups;
+    groups.authority = ctx.accounts.authority.key();
+    groups.offset_bps = offset_bps;
+    groups.bump = ctx.bumps.margin_groups;
+    Ok(())
+}
+
+/// Compute the position's margin and emit it; the prices must be
+/// published within `max_skew_secs` of each other
+pub fn refresh_margin<'info>(
+    ctx: Context<'_, '_, '_, 'info, RefreshMargin<'info>>,
+    config: PriceValidationConfig,
+    max_skew_secs: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let position = &ctx.accounts.basket_position;
+    let groups = &ctx.accounts.margin_groups;
+
+    let holding_count = position.holdings.len();
+    require!(ctx.remaining_accounts.len() >= holding_count, OracleError::InvalidMarginParams);
+    let (param_accounts, price_accounts) = ctx.remaining_accounts.split_at(holding_count);
+
+    let params = param_accounts
+        .iter()
+        .map(|info| {
+            let params = Account::<MarginParams>::try_from(info)?;
+            require_keys_eq!(params.authority, groups.authority, OracleError::InvalidMarginParams);
+            Ok(params.into_inner())
+        })
+        .collect::<Result<ArrayVec<_, MAX_BASKET_ASSETS>>>()?;
+
+    let prices = load_price_snapshot(price_accounts, &position.feed_ids(), &config, max_skew_secs, &clock)?;
+    let margin = portfolio_margin(&basket_exposures(position, &params, &prices)?, &groups.offset_bps)?;
+
+    emit!(MarginRefreshed {
+        position: position.key(),
+        owner: position.owner,
+        margin,
+        liquidatable: margin.is_liquidatable(),
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
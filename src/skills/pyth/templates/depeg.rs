@@ -0,0 +1,305 @@
+/**
+ * Stablecoin Depeg Guard
+ *
+ * Detects a stablecoin trading away from its peg. `assert_pegged` is the
+ * stateless check: it fails when the price is more than
+ * `max_deviation_bps` from the target. `DepegGuard` is the stateful
+ * version for collateral: the deviation must persist for
+ * `min_duration_secs` before the asset is flagged, so a single noisy
+ * print does not freeze a market, and the price must stay back within the
+ * band for as long before the flag clears. While flagged, collateral in
+ * the asset is either haircut or frozen (valued at zero).
+ *
+ * Like the circuit breaker, the guard reports instead of failing, so the
+ * state it records survives: `haircut_collateral` never errors on a depeg.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as `depeg`)
+ * 2. Create one guard per stablecoin feed with `init_depeg_guard`
+ * 3. Pass the guard to instructions that value the collateral, or crank
+ *    `check_depeg_guard`
+ */
+
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::{
+    get_validated_price, OracleDecimal, OracleError, PriceValidationConfig, Rounding,
+    ValidatedPrice,
+};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for depeg guards: [DEPEG_GUARD_SEED, authority, feed_id]
+pub const DEPEG_GUARD_SEED: &[u8] = b"depeg_guard";
+
+/// $1.00
+pub const USD_PEG: OracleDecimal = OracleDecimal::new(1, 0);
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+// ============================================================================
+// STATELESS CHECK
+// ============================================================================
+
+/// Distance of `price` from `peg_target`, in bps of the target (rounded up)
+pub fn peg_deviation_bps(price: &ValidatedPrice, peg_target: OracleDecimal) -> Result<u64> {
+    require!(!peg_target.is_zero(), OracleError::ZeroPrice);
+    let current = OracleDecimal::from_price(price.price, price.exponent)?;
+
+    // Common scale, so mantissas compare directly; growing scale is exact
+    let scale = current.scale.max(peg_target.scale);
+    let (current_at, target_at) = (
+        current.rescale(scale, Rounding::Floor)?,
+        peg_target.rescale(scale, Rounding::Floor)?,
+    );
+    let diff = OracleDecimal::new(current_at.mantissa.abs_diff(target_at.mantissa), scale);
+
+    diff.checked_mul(OracleDecimal::new(BPS_DENOMINATOR, 0))?
+        .checked_div(peg_target, 0, Rounding::Ceil)?
+        .to_u64(0, Rounding::Ceil)
+}
+
+/// Fail with `Depegged` if `price` is more than `max_deviation_bps` from
+/// `peg_target`
+pub fn assert_pegged(
+    price: &ValidatedPrice,
+    peg_target: OracleDecimal,
+    max_deviation_bps: u64,
+) -> Result<()> {
+    let deviation = peg_deviation_bps(price, peg_target)?;
+    require!(deviation <= max_deviation_bps, OracleError::Depegged);
+    Ok(())
+}
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// What a depeg does to collateral in the asset
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum DepegAction {
+    /// Value the collateral `bps` lower on top of the market price
+    Haircut { bps: u16 },
+    /// Value the collateral at zero
+    Freeze,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum DepegStatus {
+    Pegged,
+    /// Outside the band, but not for `min_duration_secs` yet
+    Deviating,
+    Depegged,
+    /// Back within the band, but not for `min_duration_secs` yet
+    Recovering,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct DepegConfig {
+    pub peg_price: i64,
+    pub peg_exponent: i32,
+    pub max_deviation_bps: u64,
+    /// How long a deviation (or a recovery) must last before it counts
+    pub min_duration_secs: i64,
+    pub action: DepegAction,
+}
+
+impl DepegConfig {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.peg_price > 0, OracleError::InvalidDepegConfig);
+        require!(
+            self.max_deviation_bps > 0 && self.max_deviation_bps < BPS_DENOMINATOR as u64,
+            OracleError::InvalidDepegConfig
+        );
+        require!(self.min_duration_secs >= 0, OracleError::InvalidDepegConfig);
+        if let DepegAction::Haircut { bps } = self.action {
+            require!(bps as u128 <= BPS_DENOMINATOR, OracleError::InvalidDepegConfig);
+        }
+        Ok(())
+    }
+
+    pub fn peg_target(&self) -> Result<OracleDecimal> {
+        OracleDecimal::from_price(self.peg_price, self.peg_exponent)
+    }
+}
+
+/// Per-feed depeg state
+#[account]
+pub struct DepegGuard {
+    pub authority: Pubkey,
+    pub feed_id: [u8; 32],
+    pub config: DepegConfig,
+    pub status: DepegStatus,
+    /// Publish time at which the current Deviating/Recovering run began
+    pub pending_since: i64,
+    pub last_deviation_bps: u64,
+    pub last_publish_time: i64,
+    pub bump: u8,
+}
+
+impl DepegGuard {
+    pub const LEN: usize = 8 + 32 + 32 + (8 + 4 + 8 + 8 + (1 + 2)) + 1 + 8 + 8 + 8 + 1;
+
+    pub fn is_depegged(&self) -> bool {
+        matches!(self.status, DepegStatus::Depegged | DepegStatus::Recovering)
+    }
+
+    /// Feed a validated price through the guard and return the new status.
+    /// Older or repeated prices leave the state unchanged.
+    pub fn observe(&mut self, price: &ValidatedPrice) -> Result<DepegStatus> {
+        require!(price.feed_id == self.feed_id, OracleError::FeedIdMismatch);
+        if price.publish_time <= self.last_publish_time {
+            return Ok(self.status);
+        }
+
+        let deviation = peg_deviation_bps(price, self.config.peg_target()?)?;
+        let outside = deviation > self.config.max_deviation_bps;
+        let now = price.publish_time;
+        let previous = self.status;
+
+        self.status = match (self.status, outside) {
+            (DepegStatus::Pegged, false) | (DepegStatus::Deviating, false) => DepegStatus::Pegged,
+            (DepegStatus::Depegged, true) | (DepegStatus::Recovering, true) => DepegStatus::Depegged,
+            (DepegStatus::Pegged, true) | (DepegStatus::Depegged, false) => {
+                self.pending_since = now;
+                self.settle(previous, now)
+            }
+            (DepegStatus::Deviating, true) | (DepegStatus::Recovering, false) => {
+                self.settle(previous, now)
+            }
+        };
+        self.last_deviation_bps = deviation;
+        self.last_publish_time = now;
+
+        if self.is_depegged() != matches!(previous, DepegStatus::Depegged | DepegStatus::Recovering) {
+            emit!(DepegStatusChanged {
+                guard: self.feed_id,
+                depegged: self.is_depegged(),
+                price: price.price,
+                exponent: price.exponent,
+                deviation_bps: deviation,
+                pending_secs: now - self.pending_since,
+            });
+        }
+        Ok(self.status)
+    }
+
+    /// Status after a condition opposite to the settled state, which began
+    /// at `pending_since`, is observed again at `now`
+    fn settle(&self, previous: DepegStatus, now: i64) -> DepegStatus {
+        let persisted = now - self.pending_since >= self.config.min_duration_secs;
+        match (previous, persisted) {
+            (DepegStatus::Pegged | DepegStatus::Deviating, true) => DepegStatus::Depegged,
+            (DepegStatus::Pegged | DepegStatus::Deviating, false) => DepegStatus::Deviating,
+            (DepegStatus::Depegged | DepegStatus::Recovering, true) => DepegStatus::Pegged,
+            (DepegStatus::Depegged | DepegStatus::Recovering, false) => DepegStatus::Recovering,
+        }
+    }
+
+    /// Collateral value after the configured depeg action. Unchanged while
+    /// pegged; never fails on a depeg, so the observed state is kept.
+    pub fn haircut_collateral(&self, usd_value: u64) -> Result<u64> {
+        if !self.is_depegged() {
+            return Ok(usd_value);
+        }
+        match self.config.action {
+            DepegAction::Freeze => Ok(0),
+            DepegAction::Haircut { bps } => OracleDecimal::from_u64(usd_value, 0)
+                .checked_mul(OracleDecimal::one_minus_bps(bps))?
+                .to_u64(0, Rounding::Floor),
+        }
+    }
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct DepegStatusChanged {
+    /// Feed ID of the guard
+    pub guard: [u8; 32],
+    pub depegged: bool,
+    pub price: i64,
+    pub exponent: i32,
+    pub deviation_bps: u64,
+    /// How long the condition persisted before the change
+    pub pending_secs: i64,
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(feed_id: [u8; 32])]
+pub struct InitDepegGuard<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = DepegGuard::LEN,
+        seeds = [DEPEG_GUARD_SEED, authority.key().as_ref(), feed_id.as_ref()],
+        bump
+    )]
+    pub depeg_guard: Account<'info, DepegGuard>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Permissionless: anyone may push a fresh price through the guard
+#[derive(Accounts)]
+pub struct CheckDepegGuard<'info> {
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    #[account(
+        mut,
+        seeds = [
+            DEPEG_GUARD_SEED,
+            depeg_guard.authority.as_ref(),
+            depeg_guard.feed_id.as_ref(),
+        ],
+        bump = depeg_guard.bump
+    )]
+    pub depeg_guard: Account<'info, DepegGuard>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn init_depeg_guard(
+    ctx: Context<InitDepegGuard>,
+    feed_id: [u8; 32],
+    config: DepegConfig,
+) -> Result<()> {
+    config.validate()?;
+
+    let guard = &mut ctx.accounts.depeg_guard;
+    guard.authority = ctx.accounts.authority.key();
+    guard.feed_id = feed_id;
+    guard.config = config;
+    guard.status = DepegStatus::Pegged;
+    guard.pending_since = 0;
+    guard.last_deviation_bps = 0;
+    guard.last_publish_time = 0;
+    guard.bump = ctx.bumps.depeg_guard;
+    Ok(())
+}
+
+/// Observe the latest price; always succeeds so the status is persisted
+pub fn check_depeg_guard(
+    ctx: Context<CheckDepegGuard>,
+    config: PriceValidationConfig,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let price = get_validated_price(&ctx.accounts.price_update, &config, &clock)?;
+    let status = ctx.accounts.depeg_guard.observe(&price)?;
+    msg!("Depeg guard status: {:?}", status);
+    Ok(())
+}
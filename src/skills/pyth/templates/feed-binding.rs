@@ -0,0 +1,117 @@
+/**
+ * Mint-to-Feed Bindings
+ *
+ * A price update account proves which feed it carries, not which asset the
+ * caller means it for. Without a binding, nothing stops a caller from
+ * passing the SOL/USD update while valuing a BONK position. A `FeedBinding`
+ * PDA per mint, at [FEED_BINDING_SEED, mint], records the one feed that
+ * prices the mint; `assert_feed_matches_mint` rejects any other.
+ *
+ * Bindings are managed by the `OracleConfig` authority. Rebinding a mint
+ * takes effect immediately, without the config's timelock: it is meant for
+ * a feed being retired, and a wrong binding fails closed (every price is
+ * rejected) rather than mispricing.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as
+ *    `feed_binding`); requires oracle-config.rs
+ * 2. Bind every mint the program prices with `init_feed_binding`
+ * 3. Derive the binding from the mint your state stores
+ *    (`seeds = [FEED_BINDING_SEED, position.collateral_mint.as_ref()]`)
+ *    and call `assert_feed_matches_mint` before using the price
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::oracle::PriceSource;
+use crate::{OracleConfig, OracleError, ORACLE_CONFIG_SEED};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for feed bindings: [FEED_BINDING_SEED, mint]
+pub const FEED_BINDING_SEED: &[u8] = b"feed_binding";
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// The feed that prices one mint
+#[account]
+pub struct FeedBinding {
+    pub mint: Pubkey,
+    pub feed_id: [u8; 32],
+    pub bump: u8,
+}
+
+impl FeedBinding {
+    pub const LEN: usize = 8 + 32 + 32 + 1;
+}
+
+/// Fail unless `binding` is the binding of `mint` and `source` carries its
+/// feed
+pub fn assert_feed_matches_mint<S: PriceSource>(mint: &Pubkey, source: &S, binding: &FeedBinding) -> Result<()> {
+    require_keys_eq!(binding.mint, *mint, OracleError::FeedBindingMismatch);
+    require!(source.feed_id() == binding.feed_id, OracleError::FeedIdMismatch);
+    Ok(())
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct InitFeedBinding<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [ORACLE_CONFIG_SEED], bump = oracle_config.bump, has_one = authority)]
+    pub oracle_config: Account<'info, OracleConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = FeedBinding::LEN,
+        seeds = [FEED_BINDING_SEED, mint.as_ref()],
+        bump
+    )]
+    pub feed_binding: Account<'info, FeedBinding>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeedBinding<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [ORACLE_CONFIG_SEED], bump = oracle_config.bump, has_one = authority)]
+    pub oracle_config: Account<'info, OracleConfig>,
+
+    #[account(
+        mut,
+        seeds = [FEED_BINDING_SEED, feed_binding.mint.as_ref()],
+        bump = feed_binding.bump
+    )]
+    pub feed_binding: Account<'info, FeedBinding>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn init_feed_binding(ctx: Context<InitFeedBinding>, mint: Pubkey, feed_id: [u8; 32]) -> Result<()> {
+    let binding = &mut ctx.accounts.feed_binding;
+    binding.mint = mint;
+    binding.feed_id = feed_id;
+    binding.bump = ctx.bumps.feed_binding;
+    Ok(())
+}
+
+pub fn update_feed_binding(ctx: Context<UpdateFeedBinding>, feed_id: [u8; 32]) -> Result<()> {
+    let binding = &mut ctx.accounts.feed_binding;
+    msg!("Rebinding {} to a new feed", binding.mint);
+    binding.feed_id = feed_id;
+    Ok(())
+}
@@ -0,0 +1,254 @@
+/**
+ * Pyth Feed Registry - Symbol to Feed ID Lookups
+ *
+ * Maps symbols such as "SOL/USD" (or the full Pyth symbol
+ * "Crypto.SOL/USD") to feed IDs and back, with per-feed metadata: asset
+ * class, base and quote currency, and the exponent the feed usually
+ * publishes with.
+ *
+ * The table lives in feed-registry-data.rs, which is generated from Hermes
+ * `/v2/price_feeds` by examples/off-chain/generate-feed-registry.rs. Every
+ * lookup is a `const fn` over `const` tables, so programs can resolve feed
+ * IDs at compile time and the table is not linked in unless it is used
+ * at runtime.
+ *
+ * Setup:
+ * 1. Regenerate the table for the full catalog:
+ *    cargo run --example generate-feed-registry -- templates/feed-registry-data.rs
+ *
+ * 2. Declared by anchor-oracle.rs as `feed_registry`
+ */
+
+use pyth_solana_receiver_sdk::price_update::FeedId;
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// Hermes `asset_type`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssetClass {
+    Crypto,
+    CryptoRedemptionRate,
+    Equity,
+    Fx,
+    Metal,
+    Rates,
+    Commodity,
+    Other,
+}
+
+/// One Pyth price feed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeedInfo {
+    /// Full Pyth symbol, unique per feed, e.g. "Crypto.SOL/USD"
+    pub pyth_symbol: &'static str,
+    /// Display symbol, e.g. "SOL/USD"
+    pub symbol: &'static str,
+    pub asset_class: AssetClass,
+    pub base: &'static str,
+    pub quote: &'static str,
+    /// Exponent observed when the table was generated; feeds may change it,
+    /// so always use the exponent on the price itself for math
+    pub typical_exponent: i32,
+    pub feed_id: FeedId,
+}
+
+impl FeedInfo {
+    pub const fn new(
+        pyth_symbol: &'static str,
+        symbol: &'static str,
+        asset_class: AssetClass,
+        base: &'static str,
+        quote: &'static str,
+        typical_exponent: i32,
+        feed_id_hex: &str,
+    ) -> Self {
+        Self {
+            pyth_symbol,
+            symbol,
+            asset_class,
+            base,
+            quote,
+            typical_exponent,
+            feed_id: parse_hex_feed_id(feed_id_hex),
+        }
+    }
+}
+
+// ============================================================================
+// TABLES
+// ============================================================================
+
+// Defines:
+// - `FEEDS`: every feed, sorted by `pyth_symbol`
+// - `BY_SYMBOL`: indices into `FEEDS` sorted by `symbol`, one per display
+//   symbol (ties go to the crypto feed)
+// - `BY_FEED_ID`: indices into `FEEDS` sorted by `feed_id`
+include!("feed-registry-data.rs");
+
+/// Every feed in the registry, sorted by Pyth symbol
+pub const fn all_feeds() -> &'static [FeedInfo] {
+    FEEDS
+}
+
+// ============================================================================
+// LOOKUPS
+// ============================================================================
+
+/// Feed metadata by display symbol ("SOL/USD") or Pyth symbol
+/// ("Crypto.SOL/USD"), ignoring ASCII case
+pub const fn feed_info(symbol: &str) -> Option<&'static FeedInfo> {
+    if contains_byte(symbol, b'.') {
+        // FEEDS is its own index by Pyth symbol
+        let (mut low, mut high) = (0, FEEDS.len());
+        while low < high {
+            let mid = (low + high) / 2;
+            match cmp_ignore_ascii_case(FEEDS[mid].pyth_symbol, symbol) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Some(&FEEDS[mid]),
+            }
+        }
+        return None;
+    }
+
+    let (mut low, mut high) = (0, BY_SYMBOL.len());
+    while low < high {
+        let mid = (low + high) / 2;
+        let info = &FEEDS[BY_SYMBOL[mid] as usize];
+        match cmp_ignore_ascii_case(info.symbol, symbol) {
+            Ordering::Less => low = mid + 1,
+            Ordering::Greater => high = mid,
+            Ordering::Equal => return Some(info),
+        }
+    }
+    None
+}
+
+/// Feed ID for a symbol, e.g. `feed_id_for("SOL/USD")`
+pub const fn feed_id_for(symbol: &str) -> Option<FeedId> {
+    match feed_info(symbol) {
+        Some(info) => Some(info.feed_id),
+        None => None,
+    }
+}
+
+/// Feed ID for a symbol that must exist. In a `const` this fails the
+/// build instead of the transaction:
+///
+/// `const SOL_USD: FeedId = feed_registry::feed_id("SOL/USD");`
+pub const fn feed_id(symbol: &str) -> FeedId {
+    match feed_id_for(symbol) {
+        Some(feed_id) => feed_id,
+        None => panic!("symbol is not in the Pyth feed registry"),
+    }
+}
+
+/// Feed metadata by feed ID
+pub const fn feed_info_by_id(feed_id: &FeedId) -> Option<&'static FeedInfo> {
+    let (mut low, mut high) = (0, BY_FEED_ID.len());
+    while low < high {
+        let mid = (low + high) / 2;
+        let info = &FEEDS[BY_FEED_ID[mid] as usize];
+        match cmp_feed_ids(&info.feed_id, feed_id) {
+            Ordering::Less => low = mid + 1,
+            Ordering::Greater => high = mid,
+            Ordering::Equal => return Some(info),
+        }
+    }
+    None
+}
+
+/// Display symbol for a feed ID
+pub const fn symbol_for(feed_id: &FeedId) -> Option<&'static str> {
+    match feed_info_by_id(feed_id) {
+        Some(info) => Some(info.symbol),
+        None => None,
+    }
+}
+
+// ============================================================================
+// CONST HELPERS
+// ============================================================================
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Ordering {
+    Less,
+    Equal,
+    Greater,
+}
+
+const fn compare_bytes(a: u8, b: u8) -> Ordering {
+    if a < b {
+        Ordering::Less
+    } else if a > b {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+const fn cmp_ignore_ascii_case(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let mut i = 0;
+    while i < a.len() && i < b.len() {
+        match compare_bytes(a[i].to_ascii_uppercase(), b[i].to_ascii_uppercase()) {
+            Ordering::Equal => i += 1,
+            unequal => return unequal,
+        }
+    }
+    // Equal up to the shorter length: the shorter string sorts first
+    if a.len() < b.len() {
+        Ordering::Less
+    } else if a.len() > b.len() {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+const fn cmp_feed_ids(a: &FeedId, b: &FeedId) -> Ordering {
+    let mut i = 0;
+    while i < a.len() {
+        match compare_bytes(a[i], b[i]) {
+            Ordering::Equal => i += 1,
+            unequal => return unequal,
+        }
+    }
+    Ordering::Equal
+}
+
+const fn contains_byte(s: &str, byte: u8) -> bool {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == byte {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+const fn hex_nibble(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        b'a'..=b'f' => c - b'a' + 10,
+        b'A'..=b'F' => c - b'A' + 10,
+        _ => panic!("invalid hex digit in feed ID"),
+    }
+}
+
+/// 64 hex digits, no 0x prefix
+const fn parse_hex_feed_id(hex: &str) -> FeedId {
+    let hex = hex.as_bytes();
+    assert!(hex.len() == 64, "feed ID must be 64 hex digits");
+    let mut feed_id = [0u8; 32];
+    let mut i = 0;
+    while i < 32 {
+        feed_id[i] = hex_nibble(hex[i * 2]) << 4 | hex_nibble(hex[i * 2 + 1]);
+        i += 1;
+    }
+    feed_id
+}
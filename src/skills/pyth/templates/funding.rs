@@ -0,0 +1,320 @@
+/**
+ * Perpetual Funding Rates from Mark/Index Divergence
+ *
+ * A perpetual has no expiry, so a periodic funding payment pulls its mark
+ * price (the perp's own AMM or order book) towards the index price (Pyth).
+ * When the mark trades above the index, longs pay shorts; below it,
+ * shorts pay longs.
+ *
+ * The premium is measured from the nearest edge of the index's confidence
+ * band, not from the index price itself: a mark inside the band pays no
+ * funding, so a wide oracle band during volatility does not charge traders
+ * for the oracle's own uncertainty. The rate per funding interval is the
+ * premium, clamped to `max_rate_bps`.
+ *
+ * A `FundingState` PDA per market accumulates funding as USD per whole
+ * base token (`FUNDING_INDEX_DECIMALS`). Each update first accrues the
+ * previous rate over the time since the last update, valued at the current
+ * index price, then sets the rate for the coming period. A rate therefore
+ * never applies to time before it was set, and a stale rate applies for at
+ * most one interval. Positions store the cumulative funding they last
+ * settled at; `funding_payment` is what they owe since.
+ *
+ * Sign convention: rates, cumulative funding, and payments are positive
+ * when longs pay. A position's size is positive for longs and negative for
+ * shorts, and a positive payment is owed by the position.
+ *
+ * The mark is whatever the perp engine trades at. A single pool's spot
+ * price can be moved within one transaction, so `update_funding` takes it
+ * from the market's authority (the engine or its keeper) rather than from
+ * an account anyone can pass. Engines that hold their book on chain call
+ * `FundingState::update` with it directly.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as `funding`)
+ * 2. Create one `FundingState` per market with `init_funding_state`
+ * 3. Call `update_funding` (or `FundingState::update`) at least once per
+ *    interval, and `FundingState::settle` before changing a position's size
+ */
+
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::decimal::pow10;
+use crate::{get_validated_price, OracleError, PriceValidationConfig, ValidatedPrice, USD_DECIMALS};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for funding state: [FUNDING_STATE_SEED, authority, feed_id]
+pub const FUNDING_STATE_SEED: &[u8] = b"funding_state";
+
+/// Decimals of funding rates and premiums; `FUNDING_RATE_ONE` is 100%
+pub const FUNDING_RATE_DECIMALS: u8 = 9;
+pub const FUNDING_RATE_ONE: i64 = 1_000_000_000;
+
+/// Decimals of cumulative funding, in USD per whole base token
+pub const FUNDING_INDEX_DECIMALS: u8 = 18;
+
+const BPS_DENOMINATOR: i64 = 10_000;
+
+// ============================================================================
+// RATE
+// ============================================================================
+
+/// Per-market funding parameters, as passed to `init_funding_state`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct FundingParams {
+    /// Period a rate refers to, e.g. 3600 for hourly funding
+    pub interval_secs: u32,
+    /// Largest rate per interval, either way
+    pub max_rate_bps: u16,
+}
+
+impl FundingParams {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.interval_secs > 0, OracleError::InvalidFundingParams);
+        require!(
+            self.max_rate_bps > 0 && self.max_rate_bps as i64 <= BPS_DENOMINATOR,
+            OracleError::InvalidFundingParams
+        );
+        Ok(())
+    }
+
+    /// Largest rate per interval with `FUNDING_RATE_DECIMALS` decimals
+    pub fn max_rate(&self) -> i64 {
+        self.max_rate_bps as i64 * (FUNDING_RATE_ONE / BPS_DENOMINATOR)
+    }
+}
+
+/// `value × 10^(to - from)`, truncated towards zero
+fn rescale(value: i128, from: i32, to: i32) -> Result<i128> {
+    let factor = |exp: i32| pow10(exp as u32).ok_or_else(|| error!(OracleError::MathOverflow));
+    if to >= from {
+        value
+            .checked_mul(factor(to - from)? as i128)
+            .ok_or_else(|| error!(OracleError::MathOverflow))
+    } else {
+        Ok(value / factor(from - to)? as i128)
+    }
+}
+
+/// Premium of the mark over the index, with `FUNDING_RATE_DECIMALS`
+/// decimals: the distance from the mark to the nearest edge of the index's
+/// confidence band, relative to the index price. Zero inside the band,
+/// positive above it, negative below it; truncated towards zero.
+pub fn funding_premium(mark_price: i64, mark_exponent: i32, index: &ValidatedPrice) -> Result<i64> {
+    require!(index.price > 0, OracleError::ZeroPrice);
+    require!(mark_price > 0, OracleError::ZeroPrice);
+
+    let mark = rescale(mark_price as i128, -mark_exponent, -index.exponent)?;
+    let edge = if mark > index.upper_bound as i128 {
+        index.upper_bound as i128
+    } else if mark < index.lower_bound as i128 {
+        index.lower_bound as i128
+    } else {
+        return Ok(0);
+    };
+    let premium = (mark - edge)
+        .checked_mul(FUNDING_RATE_ONE as i128)
+        .ok_or_else(|| error!(OracleError::MathOverflow))?
+        / index.price as i128;
+    i64::try_from(premium).map_err(|_| error!(OracleError::MathOverflow))
+}
+
+/// Rate per interval for `premium`, clamped to `params.max_rate_bps`
+pub fn funding_rate(premium: i64, params: &FundingParams) -> i64 {
+    let max = params.max_rate();
+    premium.clamp(-max, max)
+}
+
+/// What a position of `size` base units (`decimals` decimals; negative for
+/// shorts) owes in USD (`USD_DECIMALS`) for cumulative funding moving from
+/// `entry_funding` to `cumulative_funding`. Positive is owed by the
+/// position, negative is owed to it. Rounded up, so payers round against
+/// themselves and receivers get the truncated amount.
+pub fn funding_payment(size: i64, decimals: u8, entry_funding: i128, cumulative_funding: i128) -> Result<i64> {
+    let delta = cumulative_funding
+        .checked_sub(entry_funding)
+        .ok_or_else(|| error!(OracleError::MathOverflow))?;
+    let owed = (size as i128)
+        .checked_mul(delta)
+        .ok_or_else(|| error!(OracleError::MathOverflow))?;
+    let divisor = pow10(decimals as u32 + (FUNDING_INDEX_DECIMALS - USD_DECIMALS) as u32)
+        .ok_or_else(|| error!(OracleError::MathOverflow))? as i128;
+
+    let mut payment = owed / divisor;
+    if owed % divisor > 0 {
+        payment += 1;
+    }
+    i64::try_from(payment).map_err(|_| error!(OracleError::MathOverflow))
+}
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// Funding accumulator of one perpetual market
+#[account]
+pub struct FundingState {
+    /// Sets the mark price in `update_funding`
+    pub authority: Pubkey,
+    /// Index feed
+    pub feed_id: [u8; 32],
+    pub params: FundingParams,
+    /// USD per whole base token paid by longs since creation, with
+    /// `FUNDING_INDEX_DECIMALS` decimals; falls when shorts pay
+    pub cumulative_funding: i128,
+    /// Rate per interval applying since `last_update`, with
+    /// `FUNDING_RATE_DECIMALS` decimals
+    pub rate: i64,
+    pub last_update: i64,
+    pub bump: u8,
+}
+
+impl FundingState {
+    pub const LEN: usize = 8 + 32 + 32 + (4 + 2) + 16 + 8 + 8 + 1;
+
+    /// Accrue `rate` from `last_update` to `now` (at most one interval),
+    /// valued at `index`; returns the change in cumulative funding. A clock
+    /// behind `last_update` accrues nothing.
+    pub fn accrue(&mut self, index: &ValidatedPrice, now: i64) -> Result<i128> {
+        require!(index.feed_id == self.feed_id, OracleError::FeedIdMismatch);
+        let interval = self.params.interval_secs as i64;
+        let elapsed = now.saturating_sub(self.last_update).clamp(0, interval);
+        self.last_update = self.last_update.max(now);
+        if elapsed == 0 || self.rate == 0 {
+            return Ok(0);
+        }
+
+        // rate × index price × elapsed / interval, in USD per whole token
+        let price = rescale(index.price as i128, -index.exponent, FUNDING_INDEX_DECIMALS as i32)?;
+        let delta = (self.rate as i128)
+            .checked_mul(price)
+            .and_then(|value| value.checked_mul(elapsed as i128))
+            .ok_or_else(|| error!(OracleError::MathOverflow))?
+            / (interval as i128 * FUNDING_RATE_ONE as i128);
+        self.cumulative_funding = self
+            .cumulative_funding
+            .checked_add(delta)
+            .ok_or_else(|| error!(OracleError::MathOverflow))?;
+        Ok(delta)
+    }
+
+    /// Accrue the current rate to `now`, then set the rate for the coming
+    /// period from `mark` against `index`; returns the new rate
+    pub fn update(&mut self, mark_price: i64, mark_exponent: i32, index: &ValidatedPrice, now: i64) -> Result<i64> {
+        self.accrue(index, now)?;
+        self.rate = funding_rate(funding_premium(mark_price, mark_exponent, index)?, &self.params);
+        Ok(self.rate)
+    }
+
+    /// Payment of a position of `size` since `entry_funding` (see
+    /// `funding_payment`), moving `entry_funding` up to date. Accrue first.
+    pub fn settle(&self, size: i64, decimals: u8, entry_funding: &mut i128) -> Result<i64> {
+        let payment = funding_payment(size, decimals, *entry_funding, self.cumulative_funding)?;
+        *entry_funding = self.cumulative_funding;
+        Ok(payment)
+    }
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct FundingUpdated {
+    pub feed_id: [u8; 32],
+    pub mark_price: i64,
+    pub mark_exponent: i32,
+    pub index_price: i64,
+    pub index_conf: u64,
+    pub index_exponent: i32,
+    /// `FUNDING_RATE_DECIMALS` decimals, per interval
+    pub rate: i64,
+    pub cumulative_funding: i128,
+    pub timestamp: i64,
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(feed_id: [u8; 32])]
+pub struct InitFundingState<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = FundingState::LEN,
+        seeds = [FUNDING_STATE_SEED, authority.key().as_ref(), feed_id.as_ref()],
+        bump
+    )]
+    pub funding_state: Account<'info, FundingState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFunding<'info> {
+    pub authority: Signer<'info>,
+
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    #[account(
+        mut,
+        seeds = [FUNDING_STATE_SEED, authority.key().as_ref(), funding_state.feed_id.as_ref()],
+        bump = funding_state.bump,
+        has_one = authority
+    )]
+    pub funding_state: Account<'info, FundingState>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn init_funding_state(ctx: Context<InitFundingState>, feed_id: [u8; 32], params: FundingParams) -> Result<()> {
+    params.validate()?;
+
+    let state = &mut ctx.accounts.funding_state;
+    state.authority = ctx.accounts.authority.key();
+    state.feed_id = feed_id;
+    state.params = params;
+    state.cumulative_funding = 0;
+    state.rate = 0;
+    state.last_update = Clock::get()?.unix_timestamp;
+    state.bump = ctx.bumps.funding_state;
+    Ok(())
+}
+
+/// Accrue funding to now and set the next rate from the authority's mark
+pub fn update_funding(ctx: Context<UpdateFunding>, mark_price: i64, mark_exponent: i32) -> Result<()> {
+    let clock = Clock::get()?;
+    let state = &mut ctx.accounts.funding_state;
+    let config = PriceValidationConfig {
+        expected_feed_id: Some(state.feed_id),
+        ..PriceValidationConfig::default()
+    };
+    let index = get_validated_price(&ctx.accounts.price_update, &config, &clock)?;
+
+    let rate = state.update(mark_price, mark_exponent, &index, clock.unix_timestamp)?;
+    emit!(FundingUpdated {
+        feed_id: state.feed_id,
+        mark_price,
+        mark_exponent,
+        index_price: index.price,
+        index_conf: index.conf,
+        index_exponent: index.exponent,
+        rate,
+        cumulative_funding: state.cumulative_funding,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Funding rate: {} (1e-9 per interval)", rate);
+    Ok(())
+}
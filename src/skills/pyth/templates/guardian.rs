@@ -0,0 +1,234 @@
+/**
+ * Guardian: Pause Single Operations of a Market
+ *
+ * An oracle incident rarely calls for stopping everything. A feed that
+ * reads low makes collateral look cheap: new borrows against it must stop,
+ * but repaying and liquidating should go on. A feed that reads high is the
+ * reverse. Reverting every instruction that prices the asset is too blunt,
+ * and waiting for a program upgrade is too slow.
+ *
+ * Each `Market` (market.rs) carries `PauseFlags`, one bit per operation:
+ * `DEPOSITS`, `BORROWS`, `SWAPS`, and `LIQUIDATIONS`. Instructions call
+ * `market.require_allowed(PauseFlags::SWAPS)` (or their own flag) first,
+ * on every market they touch, and fail with `MarketPaused` while it is
+ * set. In the example program `update_collateral_value` checks `DEPOSITS`,
+ * `swap_with_oracle` checks `SWAPS`, and `liquidate` checks `LIQUIDATIONS`;
+ * check `BORROWS` in your borrow instruction.
+ *
+ * The `Guardian` PDA names one key, typically a hot key held by an
+ * on-call responder or a monitoring bot, that may set flags with
+ * `pause_market`. Only the `OracleConfig` authority, usually a multisig,
+ * clears them with `resume_market`: a leaked guardian key can halt a
+ * market but never reopen one the authority closed. The authority may
+ * also pause, and replaces the guardian with `set_guardian`. Every change
+ * emits `MarketPauseChanged` or `GuardianChanged`.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as `guardian`);
+ *    requires market.rs and oracle-config.rs
+ * 2. Create the guardian with `init_guardian`
+ * 3. Call `require_allowed` at the top of each instruction, before reading
+ *    prices
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{Market, OracleConfig, OracleError, MARKET_SEED, ORACLE_CONFIG_SEED};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for the guardian: [GUARDIAN_SEED]
+pub const GUARDIAN_SEED: &[u8] = b"guardian";
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// Operations of a market that are paused, one bit each
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct PauseFlags(pub u8);
+
+impl PauseFlags {
+    pub const NONE: Self = Self(0);
+    /// Adding or valuing collateral
+    pub const DEPOSITS: Self = Self(1 << 0);
+    pub const BORROWS: Self = Self(1 << 1);
+    /// Swaps paying out or taking in the asset
+    pub const SWAPS: Self = Self(1 << 2);
+    /// Liquidations seizing or repaying the asset
+    pub const LIQUIDATIONS: Self = Self(1 << 3);
+    pub const ALL: Self = Self(0b1111);
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// Non-empty and no bits beyond `ALL`
+    pub fn validate(self) -> Result<()> {
+        require!(self.0 != 0 && self.difference(Self::ALL).0 == 0, OracleError::InvalidPauseFlags);
+        Ok(())
+    }
+}
+
+/// The key allowed to pause markets besides the oracle config authority
+#[account]
+pub struct Guardian {
+    pub guardian: Pubkey,
+    pub bump: u8,
+}
+
+impl Guardian {
+    pub const LEN: usize = 8 + 32 + 1;
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct GuardianChanged {
+    pub previous: Pubkey,
+    pub guardian: Pubkey,
+}
+
+#[event]
+pub struct MarketPauseChanged {
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    /// The guardian or the oracle config authority
+    pub signer: Pubkey,
+    pub previous: PauseFlags,
+    pub paused: PauseFlags,
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitGuardian<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [ORACLE_CONFIG_SEED], bump = oracle_config.bump, has_one = authority)]
+    pub oracle_config: Account<'info, OracleConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Guardian::LEN,
+        seeds = [GUARDIAN_SEED],
+        bump
+    )]
+    pub guardian: Account<'info, Guardian>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [ORACLE_CONFIG_SEED], bump = oracle_config.bump, has_one = authority)]
+    pub oracle_config: Account<'info, OracleConfig>,
+
+    #[account(mut, seeds = [GUARDIAN_SEED], bump = guardian.bump)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct PauseMarket<'info> {
+    #[account(
+        constraint = signer.key() == guardian.guardian || signer.key() == oracle_config.authority
+            @ OracleError::NotGuardian
+    )]
+    pub signer: Signer<'info>,
+
+    #[account(seeds = [ORACLE_CONFIG_SEED], bump = oracle_config.bump)]
+    pub oracle_config: Account<'info, OracleConfig>,
+
+    #[account(seeds = [GUARDIAN_SEED], bump = guardian.bump)]
+    pub guardian: Account<'info, Guardian>,
+
+    #[account(mut, seeds = [MARKET_SEED, market.mint.as_ref()], bump = market.bump)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct ResumeMarket<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [ORACLE_CONFIG_SEED], bump = oracle_config.bump, has_one = authority)]
+    pub oracle_config: Account<'info, OracleConfig>,
+
+    #[account(mut, seeds = [MARKET_SEED, market.mint.as_ref()], bump = market.bump)]
+    pub market: Account<'info, Market>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn init_guardian(ctx: Context<InitGuardian>, guardian: Pubkey) -> Result<()> {
+    let account = &mut ctx.accounts.guardian;
+    account.guardian = guardian;
+    account.bump = ctx.bumps.guardian;
+
+    emit!(GuardianChanged {
+        previous: Pubkey::default(),
+        guardian,
+    });
+    Ok(())
+}
+
+pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+    let account = &mut ctx.accounts.guardian;
+    let previous = std::mem::replace(&mut account.guardian, guardian);
+
+    emit!(GuardianChanged { previous, guardian });
+    Ok(())
+}
+
+/// Pause `operations` of the market; flags already set stay set
+pub fn pause_market(ctx: Context<PauseMarket>, operations: PauseFlags) -> Result<()> {
+    operations.validate()?;
+    let market = &mut ctx.accounts.market;
+    let paused = market.paused.union(operations);
+    set_paused(market, ctx.accounts.signer.key(), paused)
+}
+
+/// Resume `operations` of the market; other paused flags stay set
+pub fn resume_market(ctx: Context<ResumeMarket>, operations: PauseFlags) -> Result<()> {
+    operations.validate()?;
+    let market = &mut ctx.accounts.market;
+    let paused = market.paused.difference(operations);
+    set_paused(market, ctx.accounts.authority.key(), paused)
+}
+
+fn set_paused(market: &mut Account<Market>, signer: Pubkey, paused: PauseFlags) -> Result<()> {
+    let previous = std::mem::replace(&mut market.paused, paused);
+    msg!("Market {} paused: {:#06b} -> {:#06b}", market.mint, previous.0, paused.0);
+
+    emit!(MarketPauseChanged {
+        market: market.key(),
+        mint: market.mint,
+        signer,
+        previous,
+        paused,
+    });
+    Ok(())
+}
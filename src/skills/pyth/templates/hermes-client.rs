@@ -0,0 +1,346 @@
+/**
+ * Hermes HTTP Client (Off-Chain)
+ *
+ * Typed async client for the Pyth Hermes API. Returns prices in the same
+ * `Price` shape the on-chain `PriceUpdateV2` exposes, plus the binary
+ * accumulator update data (VAA payloads) needed to build post_update
+ * transactions for the Pyth receiver.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    reqwest = { version = "0.11", features = ["json"], optional = true }
+ *    tokio = { version = "1", features = ["macros", "rt-multi-thread"], optional = true }
+ *    serde = { version = "1", features = ["derive"], optional = true }
+ *    serde_json = { version = "1", optional = true }
+ *    base64 = { version = "0.21", optional = true }
+ *    thiserror = "1"
+ *
+ *    [features]
+ *    hermes = ["dep:reqwest", "dep:tokio", "dep:serde", "dep:serde_json", "dep:base64"]
+ *
+ * 2. The module is declared by anchor-oracle.rs behind the `hermes` feature,
+ *    so on-chain builds never pull in the HTTP stack
+ */
+
+use base64::Engine;
+use pyth_solana_receiver_sdk::price_update::{FeedId, Price};
+use serde::Deserialize;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Public Hermes endpoint (use a dedicated provider in production)
+pub const DEFAULT_HERMES_ENDPOINT: &str = "https://hermes.pyth.network";
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum HermesError {
+    #[error("http request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("hermes returned {status}: {body}")]
+    Status { status: u16, body: String },
+
+    #[error("invalid feed id {0}")]
+    InvalidFeedId(String),
+
+    #[error("could not decode {field}: {value}")]
+    Decode { field: &'static str, value: String },
+
+    #[error("invalid response body: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("price stream gave up after {0} reconnect attempts")]
+    ReconnectLimit(u32),
+
+    #[error("account subscription failed: {0}")]
+    Subscription(String),
+}
+
+pub type HermesResult<T> = std::result::Result<T, HermesError>;
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// Slot and timing metadata attached to a price update
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceUpdateMetadata {
+    pub slot: Option<u64>,
+    pub proof_available_time: Option<i64>,
+    pub prev_publish_time: Option<i64>,
+}
+
+/// A parsed price update for one feed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParsedPriceUpdate {
+    pub feed_id: FeedId,
+    /// Spot price (same shape as on-chain `Price`)
+    pub price: Price,
+    /// EMA price (same shape as on-chain `Price`)
+    pub ema_price: Price,
+    pub metadata: PriceUpdateMetadata,
+}
+
+/// A Hermes price update response
+#[derive(Clone, Debug)]
+pub struct PriceUpdates {
+    /// Binary accumulator update data, one entry per VAA; pass these to the
+    /// receiver's post_update / post_update_atomic
+    pub update_data: Vec<Vec<u8>>,
+    pub parsed: Vec<ParsedPriceUpdate>,
+}
+
+impl PriceUpdates {
+    /// Parsed update for a feed, if present
+    pub fn get(&self, feed_id: &FeedId) -> Option<&ParsedPriceUpdate> {
+        self.parsed.iter().find(|update| &update.feed_id == feed_id)
+    }
+
+    /// Decode a Hermes price update JSON body (REST response or SSE event)
+    pub fn from_json(body: &str) -> HermesResult<Self> {
+        serde_json::from_str::<RawPriceUpdates>(body)?.try_into()
+    }
+
+    /// Decode a JSON array of price updates, as historical endpoints return
+    pub fn list_from_json(body: &str) -> HermesResult<Vec<Self>> {
+        serde_json::from_str::<Vec<RawPriceUpdates>>(body)?
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect()
+    }
+}
+
+// ============================================================================
+// WIRE FORMAT
+// ============================================================================
+
+#[derive(Deserialize)]
+struct RawPrice {
+    price: String,
+    conf: String,
+    expo: i32,
+    publish_time: i64,
+}
+
+#[derive(Deserialize)]
+struct RawMetadata {
+    slot: Option<u64>,
+    proof_available_time: Option<i64>,
+    prev_publish_time: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct RawParsedUpdate {
+    id: String,
+    price: RawPrice,
+    ema_price: RawPrice,
+    metadata: Option<RawMetadata>,
+}
+
+#[derive(Deserialize)]
+struct RawBinaryUpdate {
+    encoding: String,
+    data: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct RawPriceUpdates {
+    binary: RawBinaryUpdate,
+    #[serde(default)]
+    parsed: Vec<RawParsedUpdate>,
+}
+
+impl TryFrom<RawPrice> for Price {
+    type Error = HermesError;
+
+    fn try_from(raw: RawPrice) -> HermesResult<Self> {
+        Ok(Price {
+            price: raw.price.parse().map_err(|_| HermesError::Decode {
+                field: "price",
+                value: raw.price.clone(),
+            })?,
+            conf: raw.conf.parse().map_err(|_| HermesError::Decode {
+                field: "conf",
+                value: raw.conf.clone(),
+            })?,
+            exponent: raw.expo,
+            publish_time: raw.publish_time,
+        })
+    }
+}
+
+impl TryFrom<RawParsedUpdate> for ParsedPriceUpdate {
+    type Error = HermesError;
+
+    fn try_from(raw: RawParsedUpdate) -> HermesResult<Self> {
+        let metadata = raw.metadata.map_or(
+            PriceUpdateMetadata {
+                slot: None,
+                proof_available_time: None,
+                prev_publish_time: None,
+            },
+            |m| PriceUpdateMetadata {
+                slot: m.slot,
+                proof_available_time: m.proof_available_time,
+                prev_publish_time: m.prev_publish_time,
+            },
+        );
+
+        Ok(ParsedPriceUpdate {
+            feed_id: parse_feed_id(&raw.id)?,
+            price: raw.price.try_into()?,
+            ema_price: raw.ema_price.try_into()?,
+            metadata,
+        })
+    }
+}
+
+impl TryFrom<RawPriceUpdates> for PriceUpdates {
+    type Error = HermesError;
+
+    fn try_from(raw: RawPriceUpdates) -> HermesResult<Self> {
+        let update_data = raw
+            .binary
+            .data
+            .iter()
+            .map(|chunk| decode_binary(&raw.binary.encoding, chunk))
+            .collect::<HermesResult<_>>()?;
+
+        Ok(PriceUpdates {
+            update_data,
+            parsed: raw
+                .parsed
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<HermesResult<_>>()?,
+        })
+    }
+}
+
+fn decode_binary(encoding: &str, chunk: &str) -> HermesResult<Vec<u8>> {
+    let decoded = match encoding {
+        "base64" => base64::engine::general_purpose::STANDARD.decode(chunk).ok(),
+        "hex" => (0..chunk.len())
+            .step_by(2)
+            .map(|i| chunk.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+            .collect(),
+        _ => None,
+    };
+    decoded.ok_or_else(|| HermesError::Decode {
+        field: "binary.data",
+        value: encoding.to_string(),
+    })
+}
+
+/// Parse a feed ID with or without the 0x prefix
+pub fn parse_feed_id(feed_id_hex: &str) -> HermesResult<FeedId> {
+    let hex = feed_id_hex.trim_start_matches("0x");
+    if hex.len() != 64 {
+        return Err(HermesError::InvalidFeedId(feed_id_hex.to_string()));
+    }
+    let mut feed_id = [0u8; 32];
+    for (i, byte) in feed_id.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .map_err(|_| HermesError::InvalidFeedId(feed_id_hex.to_string()))?;
+    }
+    Ok(feed_id)
+}
+
+/// Format a feed ID as 0x-prefixed hex
+pub fn feed_id_to_hex(feed_id: &FeedId) -> String {
+    let hex: String = feed_id.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("0x{}", hex)
+}
+
+// ============================================================================
+// CLIENT
+// ============================================================================
+
+/// Async Hermes client
+#[derive(Clone, Debug)]
+pub struct HermesClient {
+    http: reqwest::Client,
+    endpoint: String,
+}
+
+impl Default for HermesClient {
+    fn default() -> Self {
+        Self::new(DEFAULT_HERMES_ENDPOINT)
+    }
+}
+
+impl HermesClient {
+    pub fn new(endpoint: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Use a preconfigured reqwest client (timeouts, API key headers, ...)
+    pub fn with_http_client(endpoint: &str, http: reqwest::Client) -> Self {
+        Self {
+            http,
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    pub fn http_client(&self) -> &reqwest::Client {
+        &self.http
+    }
+
+    async fn get_updates(&self, path: &str, feed_ids: &[FeedId]) -> HermesResult<PriceUpdates> {
+        let mut query: Vec<(&str, String)> = feed_ids
+            .iter()
+            .map(|id| ("ids[]", feed_id_to_hex(id)))
+            .collect();
+        query.push(("encoding", "base64".to_string()));
+        query.push(("parsed", "true".to_string()));
+
+        let response = self
+            .http
+            .get(format!("{}{}", self.endpoint, path))
+            .query(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(HermesError::Status {
+                status: response.status().as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            });
+        }
+
+        PriceUpdates::from_json(&response.text().await?)
+    }
+
+    /// Latest price updates for a set of feeds
+    pub async fn get_latest_price_updates(&self, feed_ids: &[FeedId]) -> HermesResult<PriceUpdates> {
+        self.get_updates("/v2/updates/price/latest", feed_ids).await
+    }
+
+    /// Price updates published at exactly `publish_time`
+    pub async fn get_price_updates_at(
+        &self,
+        feed_ids: &[FeedId],
+        publish_time: i64,
+    ) -> HermesResult<PriceUpdates> {
+        self.get_updates(&format!("/v2/updates/price/{}", publish_time), feed_ids)
+            .await
+    }
+
+    /// Binary update data only, ready for post_update instructions
+    pub async fn get_latest_update_data(&self, feed_ids: &[FeedId]) -> HermesResult<Vec<Vec<u8>>> {
+        Ok(self.get_latest_price_updates(feed_ids).await?.update_data)
+    }
+}
@@ -0,0 +1,228 @@
+/**
+ * Hermes Streaming Subscriber (Off-Chain)
+ *
+ * Push-based prices for keepers and liquidation bots. Subscribes to the
+ * Hermes server-sent events endpoint and yields an async `Stream` of price
+ * updates for a set of feeds. When the connection drops (Hermes also closes
+ * streams after 24 hours) the subscriber reconnects with exponential backoff
+ * and backfills the updates published while it was disconnected from the
+ * REST API, so consumers see a gap-free, duplicate-free sequence per feed.
+ *
+ * Setup:
+ * 1. In addition to the hermes-client.rs dependencies:
+ *    [dependencies]
+ *    reqwest = { version = "0.11", features = ["json", "stream"], optional = true }
+ *    tokio = { version = "1", features = ["macros", "rt-multi-thread", "time"], optional = true }
+ *    futures = { version = "0.3", optional = true }
+ *    async-stream = { version = "0.3", optional = true }
+ *
+ *    [features]
+ *    hermes = [..., "dep:futures", "dep:async-stream"]
+ *
+ * 2. Declared by anchor-oracle.rs behind the `hermes` feature
+ */
+
+use futures::{Stream, StreamExt};
+use pyth_solana_receiver_sdk::price_update::FeedId;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::hermes::{feed_id_to_hex, HermesClient, HermesError, HermesResult, PriceUpdates};
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Delay before the first reconnect attempt
+pub const DEFAULT_RECONNECT_DELAY: Duration = Duration::from_millis(500);
+
+/// Upper bound for the exponential reconnect backoff
+pub const DEFAULT_MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Longest outage that is backfilled; older gaps are skipped
+pub const DEFAULT_MAX_BACKFILL_SECS: i64 = 60;
+
+/// Subscriber behaviour on disconnects
+#[derive(Clone, Copy, Debug)]
+pub struct StreamConfig {
+    pub reconnect_delay: Duration,
+    pub max_reconnect_delay: Duration,
+    /// Consecutive failed reconnects before the stream ends with an error
+    /// (None = retry forever)
+    pub max_reconnect_attempts: Option<u32>,
+    /// Seconds of missed updates to backfill after a reconnect (0 disables)
+    pub max_backfill_secs: i64,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            reconnect_delay: DEFAULT_RECONNECT_DELAY,
+            max_reconnect_delay: DEFAULT_MAX_RECONNECT_DELAY,
+            max_reconnect_attempts: None,
+            max_backfill_secs: DEFAULT_MAX_BACKFILL_SECS,
+        }
+    }
+}
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// One item of the price stream
+#[derive(Clone, Debug)]
+pub struct StreamedUpdate {
+    /// Only feeds with a publish time newer than anything yielded before
+    pub updates: PriceUpdates,
+    /// Fetched from the REST API to fill a disconnect gap
+    pub backfilled: bool,
+}
+
+/// Latest publish time yielded per feed; drops duplicates and stale updates
+#[derive(Default)]
+pub(crate) struct Watermarks(HashMap<FeedId, i64>);
+
+impl Watermarks {
+    pub(crate) fn retain_new(&mut self, mut updates: PriceUpdates) -> Option<PriceUpdates> {
+        updates.parsed.retain(|update| {
+            let last = self.0.entry(update.feed_id).or_insert(i64::MIN);
+            if update.price.publish_time > *last {
+                *last = update.price.publish_time;
+                true
+            } else {
+                false
+            }
+        });
+        (!updates.parsed.is_empty()).then_some(updates)
+    }
+
+    /// Oldest watermark across feeds (where backfill has to start)
+    fn oldest(&self) -> Option<i64> {
+        self.0.values().copied().min()
+    }
+}
+
+/// Join the `data:` lines of one server-sent event
+fn sse_data(event: &str) -> Option<String> {
+    let data: Vec<&str> = event
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(str::trim_start)
+        .collect();
+    (!data.is_empty()).then(|| data.join("\n"))
+}
+
+// ============================================================================
+// SUBSCRIBER
+// ============================================================================
+
+impl HermesClient {
+    async fn open_price_stream(&self, feed_ids: &[FeedId]) -> HermesResult<reqwest::Response> {
+        let mut query: Vec<(&str, String)> = feed_ids
+            .iter()
+            .map(|id| ("ids[]", feed_id_to_hex(id)))
+            .collect();
+        query.push(("encoding", "base64".to_string()));
+        query.push(("parsed", "true".to_string()));
+
+        let response = self
+            .http_client()
+            .get(format!("{}/v2/updates/price/stream", self.endpoint()))
+            .query(&query)
+            .header("Accept", "text/event-stream")
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(HermesError::Status {
+                status: response.status().as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            });
+        }
+        Ok(response)
+    }
+
+    /// Stream price updates for `feed_ids`, reconnecting and backfilling on
+    /// disconnects. Decode errors are yielded without ending the stream; the
+    /// stream only ends after `max_reconnect_attempts` consecutive failures.
+    pub fn subscribe_price_updates(
+        &self,
+        feed_ids: Vec<FeedId>,
+        config: StreamConfig,
+    ) -> impl Stream<Item = HermesResult<StreamedUpdate>> {
+        let client = self.clone();
+
+        async_stream::stream! {
+            let mut watermarks = Watermarks::default();
+            let mut delay = config.reconnect_delay;
+            let mut failures: u32 = 0;
+
+            loop {
+                match client.open_price_stream(&feed_ids).await {
+                    Ok(response) => {
+                        // Backfill once per connection, before the first live event
+                        let mut backfill_from = watermarks.oldest().map(|t| t + 1);
+                        let mut body = response.bytes_stream();
+                        let mut buffer = String::new();
+
+                        while let Some(chunk) = body.next().await {
+                            let Ok(chunk) = chunk else { break };
+                            failures = 0;
+                            delay = config.reconnect_delay;
+                            buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+                            while let Some(end) = buffer.find("\n\n") {
+                                let event: String = buffer.drain(..end + 2).collect();
+                                let Some(data) = sse_data(&event) else { continue };
+                                let updates = match PriceUpdates::from_json(&data) {
+                                    Ok(updates) => updates,
+                                    Err(e) => {
+                                        yield Err(e);
+                                        continue;
+                                    }
+                                };
+
+                                if let Some(from) = backfill_from.take() {
+                                    let live = updates
+                                        .parsed
+                                        .iter()
+                                        .map(|u| u.price.publish_time)
+                                        .min()
+                                        .unwrap_or(from);
+                                    let from = from.max(live - config.max_backfill_secs);
+                                    for publish_time in from..live {
+                                        match client.get_price_updates_at(&feed_ids, publish_time).await {
+                                            Ok(missed) => {
+                                                if let Some(missed) = watermarks.retain_new(missed) {
+                                                    yield Ok(StreamedUpdate { updates: missed, backfilled: true });
+                                                }
+                                            }
+                                            // No update was published at this second
+                                            Err(HermesError::Status { status: 404, .. }) => {}
+                                            Err(e) => yield Err(e),
+                                        }
+                                    }
+                                }
+
+                                if let Some(updates) = watermarks.retain_new(updates) {
+                                    yield Ok(StreamedUpdate { updates, backfilled: false });
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        failures += 1;
+                        if config.max_reconnect_attempts.is_some_and(|max| failures >= max) {
+                            yield Err(e);
+                            yield Err(HermesError::ReconnectLimit(failures));
+                            break;
+                        }
+                    }
+                }
+
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(config.max_reconnect_delay);
+            }
+        }
+    }
+}
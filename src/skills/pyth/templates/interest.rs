@@ -0,0 +1,424 @@
+/**
+ * Interest Accrual: Kinked Rate Model and Borrow/Supply Indices
+ *
+ * Debt grows between price updates, and a position can cross its
+ * liquidation threshold through interest alone. Each borrowable asset has
+ * an `InterestMarket` account next to its `RiskParams`. The market holds
+ * the rate model, the asset's total deposits and borrows, and two indices
+ * that start at `INDEX_ONE`:
+ *
+ *   - `borrow_index` grows by the borrow rate; a debt recorded at index
+ *     `i0` is worth `amount × borrow_index / i0` now
+ *   - `supply_index` grows by the share of that interest paid to
+ *     depositors, after the reserve factor
+ *
+ * Rates are annual, in bps, and follow a kinked curve over utilization
+ * (borrows / deposits): `base + slope1` is reached at the optimal
+ * utilization, and `slope2` is added on top between there and 100%.
+ *
+ * Nothing accrues on its own. Every instruction that touches the market
+ * calls `accrue` first, which compounds the indices over the time since
+ * the last call (simple interest within the interval). `Position` keeps
+ * the borrow index its debt was last accrued at, so
+ * `refresh_position_health` and `liquidate` value current debt, not the
+ * amount borrowed.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as `interest`)
+ * 2. Create one `InterestMarket` per borrowable asset with
+ *    `init_interest_market`, after its `RiskParams` (or
+ *    `init_market_interest`, after its `Market`)
+ * 3. Call `accrue` before every deposit, withdrawal, borrow, and repay,
+ *    and `Position::accrue_debt` before reading a position's debt
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{OracleError, Position, RiskParams, RISK_PARAMS_SEED};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for interest markets: [INTEREST_MARKET_SEED, risk_params]
+pub const INTEREST_MARKET_SEED: &[u8] = b"interest_market";
+
+/// Indices are fixed point with 18 decimals; `INDEX_ONE` is exactly 1.0
+pub const INDEX_ONE: u128 = 1_000_000_000_000_000_000;
+
+/// Rates are annual; a year is 365 days
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+const BPS_DENOMINATOR: u64 = 10_000;
+
+// ============================================================================
+// RATE MODEL
+// ============================================================================
+
+/// Kinked utilization curve, as passed to `init_interest_market`. All rates
+/// are annual, in bps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct RateModel {
+    /// Borrow rate at zero utilization
+    pub base_rate_bps: u16,
+    /// Utilization at the kink
+    pub optimal_utilization_bps: u16,
+    /// Rate added between zero and optimal utilization
+    pub slope1_bps: u16,
+    /// Rate added between optimal and full utilization
+    pub slope2_bps: u16,
+    /// Share of borrow interest kept as protocol reserves
+    pub reserve_factor_bps: u16,
+}
+
+impl RateModel {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.optimal_utilization_bps > 0 && (self.optimal_utilization_bps as u64) < BPS_DENOMINATOR,
+            OracleError::InvalidInterestRateModel
+        );
+        require!(
+            self.reserve_factor_bps as u64 <= BPS_DENOMINATOR,
+            OracleError::InvalidInterestRateModel
+        );
+        Ok(())
+    }
+
+    /// Annual borrow rate at `utilization_bps`
+    pub fn borrow_rate_bps(&self, utilization_bps: u64) -> u64 {
+        let utilization = utilization_bps.min(BPS_DENOMINATOR);
+        let optimal = self.optimal_utilization_bps as u64;
+        let (base, slope1, slope2) = (
+            self.base_rate_bps as u64,
+            self.slope1_bps as u64,
+            self.slope2_bps as u64,
+        );
+        if utilization <= optimal {
+            base + slope1 * utilization / optimal
+        } else {
+            base + slope1 + slope2 * (utilization - optimal) / (BPS_DENOMINATOR - optimal)
+        }
+    }
+
+    /// Annual rate depositors earn at `utilization_bps`: the borrow rate
+    /// spread over all deposits, less the reserve factor
+    pub fn supply_rate_bps(&self, utilization_bps: u64) -> u64 {
+        let utilization = utilization_bps.min(BPS_DENOMINATOR);
+        let kept = BPS_DENOMINATOR - self.reserve_factor_bps as u64;
+        self.borrow_rate_bps(utilization) * utilization * kept / (BPS_DENOMINATOR * BPS_DENOMINATOR)
+    }
+}
+
+// ============================================================================
+// FIXED-POINT HELPERS
+// ============================================================================
+
+/// `a × b / denominator`, rounded up or down
+fn mul_div(a: u128, b: u128, denominator: u128, round_up: bool) -> Result<u128> {
+    let product = a.checked_mul(b).ok_or_else(|| error!(OracleError::MathOverflow))?;
+    let quotient = product / denominator;
+    if round_up && product % denominator != 0 {
+        return Ok(quotient + 1);
+    }
+    Ok(quotient)
+}
+
+fn to_u64(value: u128) -> Result<u64> {
+    u64::try_from(value).map_err(|_| error!(OracleError::MathOverflow))
+}
+
+// ============================================================================
+// INTEREST MARKET
+// ============================================================================
+
+/// Rates, totals, and indices of one borrowable asset
+#[account]
+pub struct InterestMarket {
+    /// May replace the rate model; the authority of `risk_params`
+    pub authority: Pubkey,
+    /// Risk parameters of the same asset: its `RiskParams` or `Market`
+    pub risk_params: Pubkey,
+    pub mint: Pubkey,
+    pub rate_model: RateModel,
+    /// Deposits in native units, including interest owed to depositors
+    pub total_deposits: u64,
+    /// Borrows in native units, including accrued interest
+    pub total_borrows: u64,
+    /// Interest kept by the protocol, in native units
+    pub total_reserves: u64,
+    /// 18 decimals; starts at `INDEX_ONE`
+    pub borrow_index: u128,
+    /// 18 decimals; starts at `INDEX_ONE`
+    pub supply_index: u128,
+    /// Unix timestamp of the last `accrue`
+    pub last_accrual: i64,
+    pub bump: u8,
+}
+
+impl InterestMarket {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + (2 + 2 + 2 + 2 + 2) + 8 + 8 + 8 + 16 + 16 + 8 + 1;
+
+    /// Initialize an empty market at index 1.0, last accrued at `now`
+    pub fn open(
+        &mut self,
+        authority: Pubkey,
+        risk_params: Pubkey,
+        mint: Pubkey,
+        rate_model: RateModel,
+        now: i64,
+        bump: u8,
+    ) -> Result<()> {
+        rate_model.validate()?;
+        self.authority = authority;
+        self.risk_params = risk_params;
+        self.mint = mint;
+        self.rate_model = rate_model;
+        self.total_deposits = 0;
+        self.total_borrows = 0;
+        self.total_reserves = 0;
+        self.borrow_index = INDEX_ONE;
+        self.supply_index = INDEX_ONE;
+        self.last_accrual = now;
+        self.bump = bump;
+        Ok(())
+    }
+
+    /// Borrows / deposits in bps, capped at 100%; 0 without deposits
+    pub fn utilization_bps(&self) -> u64 {
+        if self.total_deposits == 0 {
+            return 0;
+        }
+        let utilization = self.total_borrows as u128 * BPS_DENOMINATOR as u128 / self.total_deposits as u128;
+        utilization.min(BPS_DENOMINATOR as u128) as u64
+    }
+
+    pub fn borrow_rate_bps(&self) -> u64 {
+        self.rate_model.borrow_rate_bps(self.utilization_bps())
+    }
+
+    pub fn supply_rate_bps(&self) -> u64 {
+        self.rate_model.supply_rate_bps(self.utilization_bps())
+    }
+
+    /// Accrue interest from `last_accrual` to `now` at the current
+    /// utilization. Calling it again at the same timestamp is a no-op, and
+    /// a clock behind `last_accrual` accrues nothing.
+    pub fn accrue(&mut self, now: i64) -> Result<()> {
+        let elapsed = now.saturating_sub(self.last_accrual);
+        if elapsed <= 0 {
+            return Ok(());
+        }
+        self.last_accrual = now;
+
+        // Growth of the borrow index over the interval, with 18 decimals
+        let growth = mul_div(
+            self.borrow_rate_bps() as u128 * elapsed as u128,
+            INDEX_ONE,
+            BPS_DENOMINATOR as u128 * SECONDS_PER_YEAR as u128,
+            true,
+        )?;
+        if growth == 0 {
+            return Ok(());
+        }
+        self.borrow_index = self
+            .borrow_index
+            .checked_add(mul_div(self.borrow_index, growth, INDEX_ONE, true)?)
+            .ok_or_else(|| error!(OracleError::MathOverflow))?;
+
+        let interest = to_u64(mul_div(self.total_borrows as u128, growth, INDEX_ONE, true)?)?;
+        if interest == 0 {
+            return Ok(());
+        }
+        self.total_borrows = self
+            .total_borrows
+            .checked_add(interest)
+            .ok_or_else(|| error!(OracleError::MathOverflow))?;
+
+        let reserve = to_u64(mul_div(
+            interest as u128,
+            self.rate_model.reserve_factor_bps as u128,
+            BPS_DENOMINATOR as u128,
+            true,
+        )?)?;
+        self.total_reserves = self
+            .total_reserves
+            .checked_add(reserve)
+            .ok_or_else(|| error!(OracleError::MathOverflow))?;
+
+        // Depositors split the rest; rounded down so supply never outgrows borrows
+        let earned = interest - reserve;
+        if self.total_deposits > 0 && earned > 0 {
+            self.supply_index = self
+                .supply_index
+                .checked_add(mul_div(self.supply_index, earned as u128, self.total_deposits as u128, false)?)
+                .ok_or_else(|| error!(OracleError::MathOverflow))?;
+            self.total_deposits = self
+                .total_deposits
+                .checked_add(earned)
+                .ok_or_else(|| error!(OracleError::MathOverflow))?;
+        }
+        Ok(())
+    }
+
+    /// Current value of a debt of `amount` recorded at `index`, rounded up
+    pub fn debt_balance(&self, amount: u64, index: u128) -> Result<u64> {
+        if index == 0 || index >= self.borrow_index {
+            return Ok(amount);
+        }
+        to_u64(mul_div(amount as u128, self.borrow_index, index, true)?)
+    }
+
+    /// Current value of a deposit of `amount` recorded at `index`, rounded
+    /// down
+    pub fn supply_balance(&self, amount: u64, index: u128) -> Result<u64> {
+        if index == 0 || index >= self.supply_index {
+            return Ok(amount);
+        }
+        to_u64(mul_div(amount as u128, self.supply_index, index, false)?)
+    }
+
+    /// Deposits not lent out
+    pub fn available_liquidity(&self) -> u64 {
+        self.total_deposits.saturating_sub(self.total_borrows)
+    }
+
+    pub fn record_deposit(&mut self, amount: u64) -> Result<()> {
+        self.total_deposits = self
+            .total_deposits
+            .checked_add(amount)
+            .ok_or_else(|| error!(OracleError::MathOverflow))?;
+        Ok(())
+    }
+
+    pub fn record_withdraw(&mut self, amount: u64) -> Result<()> {
+        require!(amount <= self.available_liquidity(), OracleError::InsufficientLiquidity);
+        self.total_deposits -= amount;
+        Ok(())
+    }
+
+    pub fn record_borrow(&mut self, amount: u64) -> Result<()> {
+        require!(amount <= self.available_liquidity(), OracleError::InsufficientLiquidity);
+        self.total_borrows += amount;
+        Ok(())
+    }
+
+    pub fn record_repay(&mut self, amount: u64) {
+        self.total_borrows = self.total_borrows.saturating_sub(amount);
+    }
+}
+
+impl Position {
+    /// Bring `debt_amount` up to `market`'s borrow index and return the
+    /// interest added. A position that never accrued (`debt_index` 0) is
+    /// taken to be current.
+    pub fn accrue_debt(&mut self, market: &InterestMarket) -> Result<u64> {
+        require_keys_eq!(market.mint, self.debt_mint, OracleError::InterestMarketMismatch);
+        let debt = market.debt_balance(self.debt_amount, self.debt_index)?;
+        let interest = debt - self.debt_amount;
+        self.debt_amount = debt;
+        self.debt_index = market.borrow_index;
+        Ok(interest)
+    }
+}
+
+/// Accrue `market` to `now`, then `position`'s debt to the market, for
+/// handlers that take the market as an optional account. The market must
+/// belong to `debt_params`.
+pub fn accrue_position_debt(
+    market: Option<&mut Account<InterestMarket>>,
+    debt_params: &Pubkey,
+    position: &mut Position,
+    now: i64,
+) -> Result<u64> {
+    let Some(market) = market else {
+        return Ok(0);
+    };
+    require_keys_eq!(market.risk_params, *debt_params, OracleError::InterestMarketMismatch);
+    market.accrue(now)?;
+    position.accrue_debt(market)
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitInterestMarket<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [RISK_PARAMS_SEED, authority.key().as_ref(), risk_params.mint.as_ref()],
+        bump = risk_params.bump,
+        has_one = authority
+    )]
+    pub risk_params: Account<'info, RiskParams>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = InterestMarket::LEN,
+        seeds = [INTEREST_MARKET_SEED, risk_params.key().as_ref()],
+        bump
+    )]
+    pub interest_market: Account<'info, InterestMarket>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRateModel<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [INTEREST_MARKET_SEED, interest_market.risk_params.as_ref()],
+        bump = interest_market.bump,
+        has_one = authority
+    )]
+    pub interest_market: Account<'info, InterestMarket>,
+}
+
+/// Permissionless; lets keepers keep the indices fresh on idle markets
+#[derive(Accounts)]
+pub struct AccrueInterest<'info> {
+    #[account(
+        mut,
+        seeds = [INTEREST_MARKET_SEED, interest_market.risk_params.as_ref()],
+        bump = interest_market.bump
+    )]
+    pub interest_market: Account<'info, InterestMarket>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn init_interest_market(ctx: Context<InitInterestMarket>, rate_model: RateModel) -> Result<()> {
+    let risk_params = &ctx.accounts.risk_params;
+    ctx.accounts.interest_market.open(
+        ctx.accounts.authority.key(),
+        risk_params.key(),
+        risk_params.mint,
+        rate_model,
+        Clock::get()?.unix_timestamp,
+        ctx.bumps.interest_market,
+    )
+}
+
+/// Replace the rate model. Interest up to now accrues at the old rates.
+pub fn update_rate_model(ctx: Context<UpdateRateModel>, rate_model: RateModel) -> Result<()> {
+    rate_model.validate()?;
+    let market = &mut ctx.accounts.interest_market;
+    market.accrue(Clock::get()?.unix_timestamp)?;
+    market.rate_model = rate_model;
+    Ok(())
+}
+
+pub fn accrue_interest(ctx: Context<AccrueInterest>) -> Result<()> {
+    let market = &mut ctx.accounts.interest_market;
+    market.accrue(Clock::get()?.unix_timestamp)?;
+    msg!("Borrow rate: {} bps", market.borrow_rate_bps());
+    msg!("Supply rate: {} bps", market.supply_rate_bps());
+    Ok(())
+}
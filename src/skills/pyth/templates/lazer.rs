@@ -0,0 +1,435 @@
+/**
+ * Pyth Lazer: Low-Latency Signed Price Payloads
+ *
+ * Lazer streams prices every few milliseconds as payloads signed by a Lazer
+ * signer, not through Wormhole and the Pyth receiver. Nothing is posted on
+ * chain: the transaction carries the signed message as instruction data,
+ * and the program checks it itself.
+ *
+ * Verification:
+ * 1. An Ed25519 program instruction in the same transaction verifies the
+ *    message's signature over its payload; the runtime fails the whole
+ *    transaction if the signature is bad
+ * 2. `verify_lazer_message` finds that instruction through the
+ *    instructions sysvar and checks it verified exactly this message's
+ *    signature, public key, and payload
+ * 3. The public key must be an unexpired signer in `LazerSigners`, a PDA
+ *    the `OracleConfig` authority keeps in sync with Lazer's signer set
+ *
+ * `LazerPrice` is the adapter: one feed of a verified payload implementing
+ * `PriceSource`, so `get_validated_price` and the helpers built on it take
+ * Lazer prices unchanged. Lazer identifies feeds by a `u32`, which
+ * `lazer_feed_id` widens to the 32-byte ID the trait uses.
+ *
+ * Payload timestamps are microseconds, but the on-chain clock counts whole
+ * seconds, so staleness is checked in seconds (`publish_time` rounds the
+ * timestamp down). Perps that need tighter bounds compare `timestamp_us`
+ * between consecutive updates instead.
+ *
+ * Subscribe with the `solana` format and the `price`, `bestBidPrice`,
+ * `bestAskPrice`, `exponent`, and `confidence` properties (or a subset:
+ * price and exponent are required, and confidence falls back to half the
+ * bid-ask spread). Other properties are rejected.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as `lazer`);
+ *    requires oracle-config.rs
+ * 2. Register Lazer's current signer with `init_lazer_signers`
+ * 3. Put the Ed25519 instruction before yours; `client::get_lazer_price`
+ *    builds both
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::load_instruction_at_checked;
+use anchor_lang::solana_program::{ed25519_program, sysvar};
+
+use crate::oracle::{Now, OraclePrice, PriceSource, PriceSourceError, StalenessMode};
+use crate::{OracleConfig, OracleError, ORACLE_CONFIG_SEED};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for the trusted Lazer signers: [LAZER_SIGNERS_SEED]
+pub const LAZER_SIGNERS_SEED: &[u8] = b"lazer_signers";
+
+/// Signers kept at once, enough to overlap a rotation
+pub const MAX_LAZER_SIGNERS: usize = 4;
+
+/// Magic of a Lazer message in the `solana` format
+pub const LAZER_SOLANA_MAGIC: u32 = 0x821a_01b9;
+
+/// Magic of a Lazer price payload
+pub const LAZER_PAYLOAD_MAGIC: u32 = 0x93c7_d375;
+
+/// Magic (4) + signature (64) + public key (32) + payload length (2)
+const MESSAGE_HEADER_LEN: usize = 102;
+
+/// Signature count (1) + padding (1) + one set of signature offsets (14)
+const ED25519_HEADER_LEN: usize = 16;
+
+/// 32-byte feed ID of Lazer feed `feed_id`: zeros, then the ID big-endian
+pub fn lazer_feed_id(feed_id: u32) -> [u8; 32] {
+    let mut id = [0u8; 32];
+    id[28..].copy_from_slice(&feed_id.to_be_bytes());
+    id
+}
+
+// ============================================================================
+// PAYLOAD PARSING
+// ============================================================================
+
+/// A Lazer message in the `solana` format
+#[derive(Clone, Copy, Debug)]
+pub struct LazerMessage<'a> {
+    pub signature: &'a [u8],
+    pub public_key: &'a [u8],
+    /// The signed bytes
+    pub payload: &'a [u8],
+}
+
+impl<'a> LazerMessage<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        require!(data.len() >= MESSAGE_HEADER_LEN, OracleError::InvalidLazerPayload);
+        require!(
+            u32::from_le_bytes(data[0..4].try_into().unwrap()) == LAZER_SOLANA_MAGIC,
+            OracleError::InvalidLazerPayload
+        );
+        let len = u16::from_le_bytes(data[100..102].try_into().unwrap()) as usize;
+        require!(data.len() == MESSAGE_HEADER_LEN + len, OracleError::InvalidLazerPayload);
+        Ok(Self {
+            signature: &data[4..68],
+            public_key: &data[68..100],
+            payload: &data[MESSAGE_HEADER_LEN..],
+        })
+    }
+}
+
+/// One feed of a payload; properties the subscription did not request are
+/// `None`, as are prices the feed had none for
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LazerFeed {
+    pub feed_id: u32,
+    pub price: Option<i64>,
+    pub best_bid_price: Option<i64>,
+    pub best_ask_price: Option<i64>,
+    pub publisher_count: Option<u16>,
+    pub exponent: Option<i16>,
+    pub confidence: Option<i64>,
+}
+
+/// A decoded price payload
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LazerPayload {
+    pub timestamp_us: u64,
+    pub channel_id: u8,
+    pub feeds: Vec<LazerFeed>,
+}
+
+/// Little-endian reader over a payload
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N]> {
+        require!(self.0.len() >= N, OracleError::InvalidLazerPayload);
+        let (head, rest) = self.0.split_at(N);
+        self.0 = rest;
+        Ok(head.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    /// Prices are never zero; zero means the feed had no price
+    fn price(&mut self) -> Result<Option<i64>> {
+        Ok(Some(i64::from_le_bytes(self.take()?)).filter(|price| *price != 0))
+    }
+}
+
+impl LazerPayload {
+    pub fn parse(payload: &[u8]) -> Result<Self> {
+        let mut reader = Reader(payload);
+        require!(
+            u32::from_le_bytes(reader.take()?) == LAZER_PAYLOAD_MAGIC,
+            OracleError::InvalidLazerPayload
+        );
+        let timestamp_us = u64::from_le_bytes(reader.take()?);
+        let channel_id = reader.u8()?;
+
+        let feed_count = reader.u8()?;
+        let mut feeds = Vec::with_capacity(feed_count as usize);
+        for _ in 0..feed_count {
+            let mut feed = LazerFeed {
+                feed_id: u32::from_le_bytes(reader.take()?),
+                ..LazerFeed::default()
+            };
+            for _ in 0..reader.u8()? {
+                match reader.u8()? {
+                    0 => feed.price = reader.price()?,
+                    1 => feed.best_bid_price = reader.price()?,
+                    2 => feed.best_ask_price = reader.price()?,
+                    3 => feed.publisher_count = Some(u16::from_le_bytes(reader.take()?)),
+                    4 => feed.exponent = Some(i16::from_le_bytes(reader.take()?)),
+                    5 => feed.confidence = reader.price()?,
+                    _ => return err!(OracleError::InvalidLazerPayload),
+                }
+            }
+            feeds.push(feed);
+        }
+        require!(reader.0.is_empty(), OracleError::InvalidLazerPayload);
+
+        Ok(Self { timestamp_us, channel_id, feeds })
+    }
+
+    pub fn feed(&self, feed_id: u32) -> Option<&LazerFeed> {
+        self.feeds.iter().find(|feed| feed.feed_id == feed_id)
+    }
+}
+
+// ============================================================================
+// SIGNERS
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct TrustedSigner {
+    pub pubkey: Pubkey,
+    /// Unix timestamp after which the signer is no longer trusted
+    pub expires_at: i64,
+}
+
+/// Lazer signers the program accepts
+#[account]
+pub struct LazerSigners {
+    pub signers: Vec<TrustedSigner>,
+    pub bump: u8,
+}
+
+impl LazerSigners {
+    pub const LEN: usize = 8 + 4 + MAX_LAZER_SIGNERS * (32 + 8) + 1;
+
+    pub fn is_trusted(&self, pubkey: &[u8], now: i64) -> bool {
+        self.signers
+            .iter()
+            .any(|signer| signer.pubkey.as_ref() == pubkey && now < signer.expires_at)
+    }
+}
+
+fn validate_signers(signers: &[TrustedSigner]) -> Result<()> {
+    require!(
+        !signers.is_empty() && signers.len() <= MAX_LAZER_SIGNERS,
+        OracleError::InvalidLazerSigners
+    );
+    Ok(())
+}
+
+// ============================================================================
+// VERIFICATION
+// ============================================================================
+
+/// Check that instruction `ed25519_index` of this transaction is an Ed25519
+/// program instruction verifying `message`, with the signature, public key,
+/// and payload in its own data
+pub fn verify_ed25519_instruction(
+    instructions: &AccountInfo,
+    ed25519_index: u16,
+    message: &LazerMessage,
+) -> Result<()> {
+    require_keys_eq!(instructions.key(), sysvar::instructions::ID, OracleError::LazerSignatureMismatch);
+    let instruction = load_instruction_at_checked(ed25519_index as usize, instructions)?;
+    require_keys_eq!(instruction.program_id, ed25519_program::ID, OracleError::LazerSignatureMismatch);
+
+    let data = &instruction.data;
+    require!(
+        data.len() >= ED25519_HEADER_LEN && data[0] == 1,
+        OracleError::LazerSignatureMismatch
+    );
+    let field = |i: usize| u16::from_le_bytes([data[2 + 2 * i], data[3 + 2 * i]]) as usize;
+    // Offsets: signature, its instruction, public key, its instruction,
+    // message, message size, its instruction
+    let (signature, public_key, payload, payload_len) = (field(0), field(2), field(4), field(5));
+    for instruction_index in [field(1), field(3), field(6)] {
+        require!(instruction_index == u16::MAX as usize, OracleError::LazerSignatureMismatch);
+    }
+
+    let slice = |offset: usize, len: usize| data.get(offset..offset + len);
+    require!(slice(signature, 64) == Some(message.signature), OracleError::LazerSignatureMismatch);
+    require!(slice(public_key, 32) == Some(message.public_key), OracleError::LazerSignatureMismatch);
+    require!(
+        slice(payload, payload_len) == Some(message.payload),
+        OracleError::LazerSignatureMismatch
+    );
+    Ok(())
+}
+
+/// Verify a Lazer message against the Ed25519 instruction and the trusted
+/// signers, and decode its payload
+pub fn verify_lazer_message(
+    signers: &LazerSigners,
+    instructions: &AccountInfo,
+    ed25519_index: u16,
+    data: &[u8],
+    now: i64,
+) -> Result<LazerPayload> {
+    let message = LazerMessage::parse(data)?;
+    verify_ed25519_instruction(instructions, ed25519_index, &message)?;
+    require!(
+        signers.is_trusted(message.public_key, now),
+        OracleError::UntrustedLazerSigner
+    );
+    LazerPayload::parse(message.payload)
+}
+
+// ============================================================================
+// PRICE SOURCE
+// ============================================================================
+
+/// One feed of a verified Lazer payload, as a `PriceSource`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LazerPrice {
+    pub feed_id: u32,
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub best_bid_price: Option<i64>,
+    pub best_ask_price: Option<i64>,
+    pub timestamp_us: u64,
+}
+
+impl LazerPrice {
+    /// Feed `feed_id` of `payload`. Confidence is the payload's, or half the
+    /// bid-ask spread when the subscription has none; a feed with neither is
+    /// rejected rather than read with zero confidence.
+    pub fn from_payload(payload: &LazerPayload, feed_id: u32) -> Result<Self> {
+        let feed = payload.feed(feed_id).ok_or(OracleError::LazerFeedNotFound)?;
+        let price = feed.price.ok_or(OracleError::LazerFeedNotFound)?;
+        let exponent = feed.exponent.ok_or(OracleError::LazerFeedNotFound)?;
+        let conf = match (feed.confidence, feed.best_bid_price, feed.best_ask_price) {
+            (Some(confidence), _, _) => confidence.unsigned_abs(),
+            (None, Some(bid), Some(ask)) => ask.abs_diff(bid).div_ceil(2),
+            _ => return err!(OracleError::LazerFeedNotFound),
+        };
+        Ok(Self {
+            feed_id,
+            price,
+            conf,
+            exponent: exponent.into(),
+            best_bid_price: feed.best_bid_price,
+            best_ask_price: feed.best_ask_price,
+            timestamp_us: payload.timestamp_us,
+        })
+    }
+
+    pub fn publish_time(&self) -> i64 {
+        (self.timestamp_us / 1_000_000) as i64
+    }
+}
+
+impl PriceSource for LazerPrice {
+    fn feed_id(&self) -> [u8; 32] {
+        lazer_feed_id(self.feed_id)
+    }
+
+    fn staleness_mode(&self) -> StalenessMode {
+        StalenessMode::Seconds
+    }
+
+    fn get_price(&self, clock: &impl Now, max_age: u64) -> Result<OraclePrice> {
+        require!(
+            clock.unix_timestamp().saturating_sub(self.publish_time()) <= max_age as i64,
+            OracleError::PriceTooStale
+        );
+        Ok(OraclePrice {
+            price: self.price,
+            conf: self.conf,
+            exponent: self.exponent,
+            publish_time: self.publish_time(),
+        })
+    }
+
+    /// Lazer publishes no EMA
+    fn get_ema_price(&self, _clock: &impl Now, _max_age: u64) -> Result<OraclePrice> {
+        err!(PriceSourceError::PriceUnavailable)
+    }
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitLazerSigners<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [ORACLE_CONFIG_SEED], bump = oracle_config.bump, has_one = authority)]
+    pub oracle_config: Account<'info, OracleConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = LazerSigners::LEN,
+        seeds = [LAZER_SIGNERS_SEED],
+        bump
+    )]
+    pub lazer_signers: Account<'info, LazerSigners>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateLazerSigners<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [ORACLE_CONFIG_SEED], bump = oracle_config.bump, has_one = authority)]
+    pub oracle_config: Account<'info, OracleConfig>,
+
+    #[account(mut, seeds = [LAZER_SIGNERS_SEED], bump = lazer_signers.bump)]
+    pub lazer_signers: Account<'info, LazerSigners>,
+}
+
+/// Accounts for consuming a Lazer message carried in instruction data
+#[derive(Accounts)]
+pub struct LazerPriceContext<'info> {
+    #[account(seeds = [LAZER_SIGNERS_SEED], bump = lazer_signers.bump)]
+    pub lazer_signers: Account<'info, LazerSigners>,
+
+    /// CHECK: The instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+impl<'info> LazerPriceContext<'info> {
+    /// Verify `data` and return feed `feed_id` of its payload
+    pub fn verify(&self, data: &[u8], ed25519_index: u16, feed_id: u32, clock: &impl Now) -> Result<LazerPrice> {
+        let payload = verify_lazer_message(
+            &self.lazer_signers,
+            &self.instructions.to_account_info(),
+            ed25519_index,
+            data,
+            clock.unix_timestamp(),
+        )?;
+        LazerPrice::from_payload(&payload, feed_id)
+    }
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn init_lazer_signers(ctx: Context<InitLazerSigners>, signers: Vec<TrustedSigner>) -> Result<()> {
+    validate_signers(&signers)?;
+    let lazer_signers = &mut ctx.accounts.lazer_signers;
+    lazer_signers.signers = signers;
+    lazer_signers.bump = ctx.bumps.lazer_signers;
+    Ok(())
+}
+
+/// Replace the signer set, e.g. to add Lazer's next signer before a
+/// rotation and drop the old one after
+pub fn update_lazer_signers(ctx: Context<UpdateLazerSigners>, signers: Vec<TrustedSigner>) -> Result<()> {
+    validate_signers(&signers)?;
+    msg!("Lazer signers: {}", signers.len());
+    ctx.accounts.lazer_signers.signers = signers;
+    Ok(())
+}
@@ -0,0 +1,368 @@
+/**
+ * Lending Risk: LTV, Health Factor, and Borrow Limits
+ *
+ * Risk math for collateralized lending on top of validated oracle prices.
+ * Each asset has a `RiskParams` account holding its max LTV, liquidation
+ * threshold, liquidation bonus, and borrow cap. A `PositionRisk` sums a
+ * position's collateral and debt in USD, weighted by those parameters,
+ * and answers the usual questions: current loan-to-value, health factor,
+ * how much more may be borrowed, and whether the position is liquidatable.
+ *
+ * Valuation is conservative: collateral uses the lower edge of an N-sigma
+ * confidence band and rounds down; debt uses the upper edge and rounds up.
+ * All arithmetic goes through `OracleDecimal` and fails with
+ * `OracleError::MathOverflow` instead of wrapping.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as `lending`)
+ * 2. Create one `RiskParams` per asset with `init_risk_params`
+ * 3. Build a `PositionRisk` from validated prices in every instruction that
+ *    borrows, withdraws collateral, or liquidates
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    calculate_tokens_for_usd, calculate_usd_value, OracleDecimal, OracleError, Rounding,
+    ValidatedPrice, USD_DECIMALS,
+};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for risk parameters: [RISK_PARAMS_SEED, authority, mint]
+pub const RISK_PARAMS_SEED: &[u8] = b"risk_params";
+
+/// Decimals of health factors; `HEALTH_FACTOR_ONE` is exactly 1.0
+pub const HEALTH_FACTOR_DECIMALS: u8 = 6;
+pub const HEALTH_FACTOR_ONE: u64 = 1_000_000;
+
+const BPS_DENOMINATOR: u64 = 10_000;
+
+// ============================================================================
+// RISK PARAMETERS
+// ============================================================================
+
+/// Per-asset parameters, as passed to `init_risk_params`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct RiskParamsConfig {
+    /// Largest debt / collateral ratio at which new borrows are allowed
+    pub max_ltv_bps: u16,
+    /// Debt / collateral ratio at which the position becomes liquidatable
+    pub liquidation_threshold_bps: u16,
+    /// Extra collateral paid to liquidators, on top of the repaid debt
+    pub liquidation_bonus_bps: u16,
+    /// Largest share of this asset's debt one liquidation may repay
+    pub close_factor_bps: u16,
+    /// Largest total borrow of this asset in native units; 0 disables
+    /// borrowing (collateral-only asset)
+    pub borrow_cap: u64,
+}
+
+impl RiskParamsConfig {
+    pub fn validate(&self) -> Result<()> {
+        let (ltv, threshold, bonus) = (
+            self.max_ltv_bps as u64,
+            self.liquidation_threshold_bps as u64,
+            self.liquidation_bonus_bps as u64,
+        );
+        require!(
+            ltv > 0 && ltv <= threshold && threshold < BPS_DENOMINATOR,
+            OracleError::InvalidRiskParams
+        );
+        require!(
+            self.close_factor_bps > 0 && self.close_factor_bps as u64 <= BPS_DENOMINATOR,
+            OracleError::InvalidRiskParams
+        );
+        // A position liquidated at the threshold must still cover the bonus
+        require!(
+            threshold * (BPS_DENOMINATOR + bonus) <= BPS_DENOMINATOR * BPS_DENOMINATOR,
+            OracleError::InvalidRiskParams
+        );
+        Ok(())
+    }
+}
+
+/// Risk parameters and borrow usage of one asset
+#[account]
+pub struct RiskParams {
+    /// May update the parameters
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    /// Feed that prices this asset
+    pub feed_id: [u8; 32],
+    pub decimals: u8,
+    pub config: RiskParamsConfig,
+    /// Outstanding borrows in native units
+    pub total_borrowed: u64,
+    pub bump: u8,
+}
+
+impl RiskParams {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 1 + (2 + 2 + 2 + 2 + 8) + 8 + 1;
+
+    fn check_price(&self, price: &ValidatedPrice) -> Result<()> {
+        require!(price.feed_id == self.feed_id, OracleError::FeedIdMismatch);
+        Ok(())
+    }
+
+    /// Collateral value in USD at the lower edge of the `sigma` band,
+    /// rounded down. A band reaching zero values the collateral at zero.
+    pub fn collateral_value(&self, amount: u64, price: &ValidatedPrice, sigma: u8) -> Result<u64> {
+        self.check_price(price)?;
+        let (lower, _) = price.price_with_sigma(sigma);
+        if lower <= 0 {
+            return Ok(0);
+        }
+        calculate_usd_value(amount, self.decimals, lower, price.exponent, Rounding::Floor)
+    }
+
+    /// Debt value in USD at the upper edge of the `sigma` band, rounded up
+    pub fn debt_value(&self, amount: u64, price: &ValidatedPrice, sigma: u8) -> Result<u64> {
+        self.check_price(price)?;
+        let (_, upper) = price.price_with_sigma(sigma);
+        calculate_usd_value(amount, self.decimals, upper, price.exponent, Rounding::Ceil)
+    }
+
+    /// Native units that may still be borrowed before the cap
+    pub fn remaining_borrow_cap(&self) -> u64 {
+        self.config.borrow_cap.saturating_sub(self.total_borrowed)
+    }
+
+    pub fn record_borrow(&mut self, amount: u64) -> Result<()> {
+        let total = self
+            .total_borrowed
+            .checked_add(amount)
+            .ok_or_else(|| error!(OracleError::MathOverflow))?;
+        require!(total <= self.config.borrow_cap, OracleError::BorrowCapExceeded);
+        self.total_borrowed = total;
+        Ok(())
+    }
+
+    pub fn record_repay(&mut self, amount: u64) {
+        self.total_borrowed = self.total_borrowed.saturating_sub(amount);
+    }
+}
+
+// ============================================================================
+// POSITION RISK
+// ============================================================================
+
+/// `value × bps / 10_000`
+pub(crate) fn apply_bps(value: u64, bps: u16, rounding: Rounding) -> Result<u64> {
+    OracleDecimal::from_u64(value, 0)
+        .checked_mul(OracleDecimal::from_bps(bps))?
+        .to_u64(0, rounding)
+}
+
+/// `numerator / denominator` with `decimals` decimals
+fn ratio(numerator: u64, denominator: u64, decimals: u8, rounding: Rounding) -> Result<u64> {
+    OracleDecimal::from_u64(numerator, 0)
+        .checked_div(OracleDecimal::from_u64(denominator, 0), decimals as i32, rounding)?
+        .to_u64(decimals, rounding)
+}
+
+fn add_usd(a: u64, b: u64) -> Result<u64> {
+    a.checked_add(b).ok_or_else(|| error!(OracleError::MathOverflow))
+}
+
+/// USD totals of one position (`USD_DECIMALS` decimals), weighted by each
+/// asset's risk parameters
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PositionRisk {
+    /// Unweighted collateral value
+    pub collateral_usd: u64,
+    /// Collateral weighted by max LTV: the most debt new borrows may reach
+    pub borrow_limit_usd: u64,
+    /// Collateral weighted by liquidation threshold
+    pub liquidation_limit_usd: u64,
+    pub debt_usd: u64,
+}
+
+impl PositionRisk {
+    pub fn add_collateral(
+        &mut self,
+        params: &RiskParams,
+        amount: u64,
+        price: &ValidatedPrice,
+        sigma: u8,
+    ) -> Result<()> {
+        let value = params.collateral_value(amount, price, sigma)?;
+        self.collateral_usd = add_usd(self.collateral_usd, value)?;
+        self.borrow_limit_usd = add_usd(
+            self.borrow_limit_usd,
+            apply_bps(value, params.config.max_ltv_bps, Rounding::Floor)?,
+        )?;
+        self.liquidation_limit_usd = add_usd(
+            self.liquidation_limit_usd,
+            apply_bps(value, params.config.liquidation_threshold_bps, Rounding::Floor)?,
+        )?;
+        Ok(())
+    }
+
+    pub fn add_debt(
+        &mut self,
+        params: &RiskParams,
+        amount: u64,
+        price: &ValidatedPrice,
+        sigma: u8,
+    ) -> Result<()> {
+        let value = params.debt_value(amount, price, sigma)?;
+        self.debt_usd = add_usd(self.debt_usd, value)?;
+        Ok(())
+    }
+
+    /// Debt / collateral in bps, rounded up; `u64::MAX` for debt without
+    /// collateral
+    pub fn loan_to_value_bps(&self) -> Result<u64> {
+        match (self.debt_usd, self.collateral_usd) {
+            (0, _) => Ok(0),
+            (_, 0) => Ok(u64::MAX),
+            (debt, collateral) => OracleDecimal::from_u64(debt, 0)
+                .checked_mul(OracleDecimal::from_u64(BPS_DENOMINATOR, 0))?
+                .checked_div(OracleDecimal::from_u64(collateral, 0), 0, Rounding::Ceil)?
+                .to_u64(0, Rounding::Ceil),
+        }
+    }
+
+    /// Liquidation-weighted collateral / debt with `HEALTH_FACTOR_DECIMALS`
+    /// decimals, rounded down; `u64::MAX` without debt
+    pub fn health_factor(&self) -> Result<u64> {
+        if self.debt_usd == 0 {
+            return Ok(u64::MAX);
+        }
+        // Only a health factor too large for u64 can fail here
+        Ok(ratio(
+            self.liquidation_limit_usd,
+            self.debt_usd,
+            HEALTH_FACTOR_DECIMALS,
+            Rounding::Floor,
+        )
+        .unwrap_or(u64::MAX))
+    }
+
+    pub fn is_liquidatable(&self) -> Result<bool> {
+        Ok(self.health_factor()? < HEALTH_FACTOR_ONE)
+    }
+
+    /// Further debt, in USD, that keeps the position within its max LTV
+    pub fn max_borrow_usd(&self) -> u64 {
+        self.borrow_limit_usd.saturating_sub(self.debt_usd)
+    }
+
+    /// Further borrow of `params`' asset in native units, priced at the
+    /// upper edge of the `sigma` band and limited by the borrow cap
+    pub fn max_borrow_amount(&self, params: &RiskParams, price: &ValidatedPrice, sigma: u8) -> Result<u64> {
+        params.check_price(price)?;
+        let headroom = self.max_borrow_usd();
+        if headroom == 0 {
+            return Ok(0);
+        }
+        let (_, upper) = price.price_with_sigma(sigma);
+        let amount = calculate_tokens_for_usd(
+            headroom,
+            USD_DECIMALS,
+            params.decimals,
+            upper,
+            price.exponent,
+            Rounding::Floor,
+        )?;
+        Ok(amount.min(params.remaining_borrow_cap()))
+    }
+
+    /// Whether the position stays within its max LTV after borrowing
+    /// `amount` more of `params`' asset
+    pub fn can_borrow(&self, params: &RiskParams, amount: u64, price: &ValidatedPrice, sigma: u8) -> Result<bool> {
+        let added = params.debt_value(amount, price, sigma)?;
+        Ok(add_usd(self.debt_usd, added)? <= self.borrow_limit_usd
+            && amount <= params.remaining_borrow_cap())
+    }
+}
+
+/// Collateral price (in `exponent` fixed point) at which a single-collateral
+/// position with `debt_usd` of debt reaches a health factor of 1, rounded
+/// up. Ignores confidence and assumes the debt value stays fixed.
+pub fn liquidation_price(
+    params: &RiskParams,
+    collateral_amount: u64,
+    debt_usd: u64,
+    exponent: i32,
+) -> Result<i64> {
+    let weighted = OracleDecimal::from_u64(collateral_amount, params.decimals)
+        .checked_mul(OracleDecimal::from_bps(params.config.liquidation_threshold_bps))?;
+    require!(!weighted.is_zero(), OracleError::InsufficientCollateral);
+    let scale = exponent
+        .checked_neg()
+        .ok_or_else(|| error!(OracleError::MathOverflow))?;
+    OracleDecimal::from_u64(debt_usd, USD_DECIMALS)
+        .checked_div(weighted, scale, Rounding::Ceil)?
+        .to_i64(exponent, Rounding::Ceil)
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct InitRiskParams<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = RiskParams::LEN,
+        seeds = [RISK_PARAMS_SEED, authority.key().as_ref(), mint.as_ref()],
+        bump
+    )]
+    pub risk_params: Account<'info, RiskParams>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRiskParams<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [RISK_PARAMS_SEED, authority.key().as_ref(), risk_params.mint.as_ref()],
+        bump = risk_params.bump,
+        has_one = authority
+    )]
+    pub risk_params: Account<'info, RiskParams>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn init_risk_params(
+    ctx: Context<InitRiskParams>,
+    mint: Pubkey,
+    feed_id: [u8; 32],
+    decimals: u8,
+    config: RiskParamsConfig,
+) -> Result<()> {
+    config.validate()?;
+
+    let params = &mut ctx.accounts.risk_params;
+    params.authority = ctx.accounts.authority.key();
+    params.mint = mint;
+    params.feed_id = feed_id;
+    params.decimals = decimals;
+    params.config = config;
+    params.total_borrowed = 0;
+    params.bump = ctx.bumps.risk_params;
+    Ok(())
+}
+
+/// Replace the parameters. Lowering the borrow cap below current borrows
+/// only blocks new borrows.
+pub fn update_risk_params(ctx: Context<UpdateRiskParams>, config: RiskParamsConfig) -> Result<()> {
+    config.validate()?;
+    ctx.accounts.risk_params.config = config;
+    Ok(())
+}
@@ -0,0 +1,316 @@
+/**
+ * Liquidation Engine
+ *
+ * Partial liquidation of unhealthy positions, built on lending-risk.rs.
+ * The position must be liquidatable at 2-sigma conservative prices
+ * (collateral at the lower edge, debt at the upper edge). A liquidator
+ * may then repay at most the debt asset's close factor of the
+ * outstanding debt. In return they seize collateral worth the repaid
+ * debt plus the collateral asset's liquidation bonus, valued at spot
+ * prices and rounded in the protocol's favour. If the position does not
+ * hold that much collateral, the repayment shrinks to match what can be
+ * seized.
+ *
+ * Both assets' settings come from their `Market` accounts (market.rs):
+ * each price is read with its market's limits and breaker, and its risk
+ * parameters are the market's. A breaker in restricted mode still allows
+ * liquidations, which reduce risk; only pausing `LIQUIDATIONS` on either
+ * market (guardian.rs) halts them.
+ *
+ * A debt market with an `InterestMarket` (interest.rs) requires it: the
+ * debt accrues before the health check, so interest alone can make a
+ * position liquidatable, and the repayment leaves its borrows. Leaving it
+ * out fails with `InterestMarketRequired` rather than skipping both.
+ *
+ * `quote_liquidation` is the pure calculation, shared by the instruction
+ * and by off-chain liquidators deciding whether a liquidation is worth
+ * sending.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs and lending-risk.rs (it is declared
+ *    there as `liquidation`)
+ * 2. Set `close_factor_bps` in each borrowable asset's `Market`
+ * 3. Add the token transfers for your vaults where marked in `liquidate`
+ */
+
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::lending::apply_bps;
+use crate::{
+    accrue_position_debt, calculate_tokens_for_usd, calculate_usd_value, InterestMarket, Market, OperationKind,
+    OracleDecimal, OracleError, PauseFlags, Position, PositionRisk, PriceUse, RiskParams, Rounding, ValidatedPrice,
+    INTEREST_MARKET_SEED, MARKET_SEED, USD_DECIMALS,
+};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Confidence band used to decide whether a position is liquidatable
+pub const LIQUIDATION_SIGMA: u8 = 2;
+
+// ============================================================================
+// QUOTE
+// ============================================================================
+
+/// Amounts of one liquidation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LiquidationQuote {
+    /// Debt repaid by the liquidator, in native units
+    pub repay_amount: u64,
+    /// Value of the repaid debt at spot (`USD_DECIMALS`)
+    pub repay_usd: u64,
+    /// Collateral transferred to the liquidator, bonus included
+    pub seize_amount: u64,
+}
+
+/// Health of a single-collateral, single-debt position at `sigma`
+pub fn position_risk(
+    collateral_params: &RiskParams,
+    collateral_amount: u64,
+    collateral_price: &ValidatedPrice,
+    debt_params: &RiskParams,
+    debt_amount: u64,
+    debt_price: &ValidatedPrice,
+    sigma: u8,
+) -> Result<PositionRisk> {
+    let mut risk = PositionRisk::default();
+    risk.add_collateral(collateral_params, collateral_amount, collateral_price, sigma)?;
+    risk.add_debt(debt_params, debt_amount, debt_price, sigma)?;
+    Ok(risk)
+}
+
+/// Repayment and seizure for a liquidation of up to `requested_repay`.
+/// Does not check that the position is liquidatable.
+pub fn quote_liquidation(
+    collateral_params: &RiskParams,
+    collateral_amount: u64,
+    collateral_price: &ValidatedPrice,
+    debt_params: &RiskParams,
+    debt_amount: u64,
+    debt_price: &ValidatedPrice,
+    requested_repay: u64,
+) -> Result<LiquidationQuote> {
+    require!(collateral_price.feed_id == collateral_params.feed_id, OracleError::FeedIdMismatch);
+    require!(debt_price.feed_id == debt_params.feed_id, OracleError::FeedIdMismatch);
+
+    // Ceil, so dust debt can always be closed out
+    let max_repay = apply_bps(debt_amount, debt_params.config.close_factor_bps, Rounding::Ceil)?
+        .min(debt_amount);
+    let mut repay_amount = requested_repay.min(max_repay);
+    require!(repay_amount > 0, OracleError::InvalidLiquidationAmount);
+
+    let seize_for = |repay: u64| -> Result<(u64, u64)> {
+        let repay_usd = calculate_usd_value(
+            repay,
+            debt_params.decimals,
+            debt_price.price,
+            debt_price.exponent,
+            Rounding::Floor,
+        )?;
+        let bonus = OracleDecimal::one_plus_bps(collateral_params.config.liquidation_bonus_bps);
+        let seize_usd = OracleDecimal::from_u64(repay_usd, USD_DECIMALS)
+            .checked_mul(bonus)?
+            .to_u64(USD_DECIMALS, Rounding::Floor)?;
+        let seize_amount = calculate_tokens_for_usd(
+            seize_usd,
+            USD_DECIMALS,
+            collateral_params.decimals,
+            collateral_price.price,
+            collateral_price.exponent,
+            Rounding::Floor,
+        )?;
+        Ok((repay_usd, seize_amount))
+    };
+
+    let (mut repay_usd, mut seize_amount) = seize_for(repay_amount)?;
+    if seize_amount > collateral_amount {
+        // Not enough collateral: repay only the share that can be covered
+        repay_amount = OracleDecimal::from_u64(repay_amount, 0)
+            .checked_mul(OracleDecimal::from_u64(collateral_amount, 0))?
+            .checked_div(OracleDecimal::from_u64(seize_amount, 0), 0, Rounding::Ceil)?
+            .to_u64(0, Rounding::Ceil)?;
+        require!(repay_amount > 0, OracleError::InvalidLiquidationAmount);
+        (repay_usd, seize_amount) = seize_for(repay_amount)?;
+        seize_amount = seize_amount.min(collateral_amount);
+    }
+
+    Ok(LiquidationQuote {
+        repay_amount,
+        repay_usd,
+        seize_amount,
+    })
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct LiquidationExecuted {
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub liquidator: Pubkey,
+    pub repay_amount: u64,
+    pub repay_usd: u64,
+    pub seize_amount: u64,
+    /// At `LIQUIDATION_SIGMA`, with `HEALTH_FACTOR_DECIMALS` decimals
+    pub health_factor_before: u64,
+    pub health_factor_after: u64,
+    pub collateral_price: i64,
+    pub collateral_exponent: i32,
+    pub debt_price: i64,
+    pub debt_exponent: i32,
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct Liquidate<'info> {
+    pub liquidator: Signer<'info>,
+
+    pub collateral_price: Account<'info, PriceUpdateV2>,
+    pub debt_price: Account<'info, PriceUpdateV2>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, position.collateral_mint.as_ref()],
+        bump = collateral_market.bump
+    )]
+    pub collateral_market: Account<'info, Market>,
+
+    #[account(mut, seeds = [MARKET_SEED, position.debt_mint.as_ref()], bump = debt_market.bump)]
+    pub debt_market: Account<'info, Market>,
+
+    #[account(mut)]
+    pub position: Account<'info, Position>,
+
+    /// Interest market of the debt asset; required when `debt_market` has
+    /// one, and accrues the debt before the health check
+    #[account(
+        mut,
+        seeds = [INTEREST_MARKET_SEED, debt_market.key().as_ref()],
+        bump = debt_interest.bump
+    )]
+    pub debt_interest: Option<Account<'info, InterestMarket>>,
+
+    // Add the liquidator's token accounts, vaults, and token program
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+/// Repay up to `repay_amount` of an unhealthy position's debt and seize
+/// collateral. Fails with `SlippageExceeded` if less than
+/// `min_collateral_out` would be seized.
+pub fn liquidate(ctx: Context<Liquidate>, repay_amount: u64, min_collateral_out: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let position_key = ctx.accounts.position.key();
+    let accounts = &mut ctx.accounts;
+    accounts.collateral_market.require_allowed(PauseFlags::LIQUIDATIONS)?;
+    accounts.debt_market.require_allowed(PauseFlags::LIQUIDATIONS)?;
+    require!(
+        accounts.debt_interest.is_some() || !accounts.debt_market.has_interest_market,
+        OracleError::InterestMarketRequired
+    );
+
+    let collateral_price = accounts.collateral_market.guarded_price(
+        &accounts.collateral_price,
+        &clock,
+        PriceUse::Liquidation,
+        position_key,
+        OperationKind::RiskReducing,
+    )?;
+    let debt_price = accounts.debt_market.guarded_price(
+        &accounts.debt_price,
+        &clock,
+        PriceUse::Liquidation,
+        position_key,
+        OperationKind::RiskReducing,
+    )?;
+    let (Some(collateral_price), Some(debt_price)) = (collateral_price, debt_price) else {
+        return Ok(());
+    };
+
+    let collateral_params = &accounts.collateral_market.risk_params();
+    let debt_params = &accounts.debt_market.risk_params();
+    let position = &mut accounts.position;
+    accrue_position_debt(
+        accounts.debt_interest.as_mut(),
+        &accounts.debt_market.key(),
+        position,
+        clock.unix_timestamp,
+    )?;
+
+    let before = position_risk(
+        collateral_params,
+        position.collateral_amount,
+        &collateral_price,
+        debt_params,
+        position.debt_amount,
+        &debt_price,
+        LIQUIDATION_SIGMA,
+    )?;
+    require!(before.is_liquidatable()?, OracleError::NotLiquidatable);
+
+    let quote = quote_liquidation(
+        collateral_params,
+        position.collateral_amount,
+        &collateral_price,
+        debt_params,
+        position.debt_amount,
+        &debt_price,
+        repay_amount,
+    )?;
+    require!(quote.seize_amount >= min_collateral_out, OracleError::SlippageExceeded);
+
+    // Transfer quote.repay_amount of the debt asset from the liquidator to
+    // the vault, and quote.seize_amount of collateral to the liquidator...
+
+    position.debt_amount = position
+        .debt_amount
+        .checked_sub(quote.repay_amount)
+        .ok_or_else(|| error!(OracleError::MathOverflow))?;
+    position.collateral_amount = position
+        .collateral_amount
+        .checked_sub(quote.seize_amount)
+        .ok_or_else(|| error!(OracleError::MathOverflow))?;
+    accounts.debt_market.record_repay(quote.repay_amount);
+    if let Some(market) = accounts.debt_interest.as_mut() {
+        market.record_repay(quote.repay_amount);
+    }
+
+    let after = position_risk(
+        collateral_params,
+        position.collateral_amount,
+        &collateral_price,
+        debt_params,
+        position.debt_amount,
+        &debt_price,
+        LIQUIDATION_SIGMA,
+    )?;
+    position.usd_value = after.collateral_usd;
+    position.debt_usd_value = after.debt_usd;
+    position.health_factor = after.health_factor()?;
+    position.last_price_update = clock.unix_timestamp;
+
+    emit!(LiquidationExecuted {
+        position: position.key(),
+        owner: position.owner,
+        liquidator: accounts.liquidator.key(),
+        repay_amount: quote.repay_amount,
+        repay_usd: quote.repay_usd,
+        seize_amount: quote.seize_amount,
+        health_factor_before: before.health_factor()?,
+        health_factor_after: position.health_factor,
+        collateral_price: collateral_price.price,
+        collateral_exponent: collateral_price.exponent,
+        debt_price: debt_price.price,
+        debt_exponent: debt_price.exponent,
+    });
+    Ok(())
+}
@@ -0,0 +1,203 @@
+/**
+ * Liquid Staking Token Fair-Value Pricing
+ *
+ * LSTs are the most common Solana collateral without a reliable direct
+ * feed, but their fair value is known: each pool token redeems for
+ * `total_lamports / pool_token_supply` SOL, so
+ *
+ *   LST/USD = SOL/USD × total_lamports / pool_token_supply
+ *
+ * `get_lst_fair_value` reads that rate from the stake pool account,
+ * validates the SOL/USD price as usual, and returns the product as a
+ * `ValidatedPrice` keyed by the LST mint (no feed ID prices it). The rate
+ * carries no uncertainty of its own, so the confidence scales with the
+ * price. The price rounds down and the confidence up.
+ *
+ * The pool must be an SPL stake pool: jitoSOL and bSOL, and Sanctum LSTs
+ * through Sanctum's deployments of the same program. Marinade (mSOL) keeps
+ * its rate in its own `State` account; build a `StakePoolRate` from it with
+ * `StakePoolRate::new`.
+ *
+ * The rate is refreshed once per epoch by the pool's update crank. A rate
+ * from an older epoch misses that epoch's rewards, so `max_epochs_behind`
+ * bounds how old it may be; `DEFAULT_MAX_POOL_EPOCH_LAG` tolerates the
+ * window at the start of each epoch before the crank runs.
+ *
+ * Fair value is what the token redeems for, not what it sells for: during
+ * a run on the pool the market price can trade below it. Neither the pool's
+ * withdrawal fee nor that discount is included.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as `lst_pricing`)
+ * 2. Pass the stake pool account with the SOL/USD price update, and store
+ *    the expected pool address for each LST mint in your market config
+ */
+
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::oracle::{OraclePrice, PriceSource};
+use crate::{get_validated_price, parse_feed_id, price_feeds, OracleError, PriceValidationConfig, ValidatedPrice};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// SPL stake pool program (jitoSOL, bSOL)
+pub const SPL_STAKE_POOL_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
+
+/// Sanctum's deployments of the SPL stake pool program
+pub const SANCTUM_SPL_STAKE_POOL_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("SP12tWFxD9oJsVWNavTTBZvMbA6gkAmxtVgxdqvyvhY");
+pub const SANCTUM_SPL_MULTI_STAKE_POOL_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("SPMBzsVUuoHA4Jm6KunbsotaahvVikZs1JyTW6iJvbn");
+
+/// Epochs a pool's rate may lag the current epoch
+pub const DEFAULT_MAX_POOL_EPOCH_LAG: u64 = 1;
+
+/// `AccountType::StakePool` in the SPL stake pool layout
+const STAKE_POOL_ACCOUNT_TYPE: u8 = 1;
+
+// Field offsets in the Borsh layout of the SPL `StakePool` account
+const POOL_MINT_OFFSET: usize = 162;
+const TOTAL_LAMPORTS_OFFSET: usize = 258;
+const POOL_TOKEN_SUPPLY_OFFSET: usize = 266;
+const LAST_UPDATE_EPOCH_OFFSET: usize = 274;
+const STAKE_POOL_MIN_LEN: usize = 282;
+
+// ============================================================================
+// EXCHANGE RATE
+// ============================================================================
+
+/// SOL per pool token, as of `last_update_epoch`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StakePoolRate {
+    pub total_lamports: u64,
+    pub pool_token_supply: u64,
+    pub last_update_epoch: u64,
+}
+
+impl StakePoolRate {
+    pub fn new(total_lamports: u64, pool_token_supply: u64, last_update_epoch: u64) -> Result<Self> {
+        require!(total_lamports > 0 && pool_token_supply > 0, OracleError::InvalidStakePool);
+        Ok(Self {
+            total_lamports,
+            pool_token_supply,
+            last_update_epoch,
+        })
+    }
+
+    /// Read the rate of an SPL stake pool whose pool mint is `lst_mint`
+    pub fn from_spl_stake_pool(stake_pool: &AccountInfo, lst_mint: &Pubkey) -> Result<Self> {
+        require!(
+            [
+                SPL_STAKE_POOL_PROGRAM_ID,
+                SANCTUM_SPL_STAKE_POOL_PROGRAM_ID,
+                SANCTUM_SPL_MULTI_STAKE_POOL_PROGRAM_ID,
+            ]
+            .contains(stake_pool.owner),
+            OracleError::InvalidStakePool
+        );
+        let data = stake_pool.try_borrow_data()?;
+        require!(
+            data.len() >= STAKE_POOL_MIN_LEN && data[0] == STAKE_POOL_ACCOUNT_TYPE,
+            OracleError::InvalidStakePool
+        );
+        require!(
+            data[POOL_MINT_OFFSET..POOL_MINT_OFFSET + 32] == lst_mint.to_bytes(),
+            OracleError::InvalidStakePool
+        );
+
+        let read_u64 = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+        Self::new(
+            read_u64(TOTAL_LAMPORTS_OFFSET),
+            read_u64(POOL_TOKEN_SUPPLY_OFFSET),
+            read_u64(LAST_UPDATE_EPOCH_OFFSET),
+        )
+    }
+
+    /// Fail with `StakePoolStale` if the rate is more than
+    /// `max_epochs_behind` epochs old. Takes a `Clock` rather than `Now`,
+    /// which has no epoch.
+    pub fn require_current(&self, clock: &Clock, max_epochs_behind: u64) -> Result<()> {
+        require!(
+            clock.epoch.saturating_sub(self.last_update_epoch) <= max_epochs_behind,
+            OracleError::StakePoolStale
+        );
+        Ok(())
+    }
+
+    /// `sol_price` converted to a price per pool token
+    pub fn apply(&self, sol_price: &ValidatedPrice) -> Result<OraclePrice> {
+        let (lamports, supply) = (self.total_lamports as i128, self.pool_token_supply as i128);
+        let price = (sol_price.price as i128 * lamports).div_euclid(supply);
+        let conf = (sol_price.conf as i128 * lamports + supply - 1) / supply;
+        Ok(OraclePrice {
+            price: i64::try_from(price).map_err(|_| error!(OracleError::MathOverflow))?,
+            conf: u64::try_from(conf).map_err(|_| error!(OracleError::MathOverflow))?,
+            exponent: sol_price.exponent,
+            publish_time: sol_price.publish_time,
+        })
+    }
+}
+
+// ============================================================================
+// READ PATH
+// ============================================================================
+
+/// Fair value of `lst_mint` in USD: the validated SOL/USD price times the
+/// stake pool's rate
+///
+/// `config.expected_feed_id` is replaced by SOL/USD.
+pub fn get_lst_fair_value<S: PriceSource>(
+    sol_source: &S,
+    stake_pool: &AccountInfo,
+    lst_mint: &Pubkey,
+    config: &PriceValidationConfig,
+    max_epochs_behind: u64,
+    clock: &Clock,
+) -> Result<ValidatedPrice> {
+    let rate = StakePoolRate::from_spl_stake_pool(stake_pool, lst_mint)?;
+    rate.require_current(clock, max_epochs_behind)?;
+
+    let config = PriceValidationConfig {
+        expected_feed_id: Some(parse_feed_id(price_feeds::SOL_USD)?),
+        ..*config
+    };
+    let sol_price = get_validated_price(sol_source, &config, clock)?;
+    Ok(ValidatedPrice::from_price(lst_mint.to_bytes(), &rate.apply(&sol_price)?))
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct LstPriceContext<'info> {
+    /// SOL/USD
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    /// CHECK: owner, layout, and pool mint are checked by
+    /// `StakePoolRate::from_spl_stake_pool`
+    pub stake_pool: UncheckedAccount<'info>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn get_lst_price(ctx: Context<LstPriceContext>, lst_mint: Pubkey, max_epochs_behind: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let price = get_lst_fair_value(
+        &ctx.accounts.price_update,
+        &ctx.accounts.stake_pool,
+        &lst_mint,
+        &PriceValidationConfig::default(),
+        max_epochs_behind,
+        &clock,
+    )?;
+    msg!("Price: {} × 10^{}", price.price, price.exponent);
+    msg!("Bounds: [{}, {}]", price.lower_bound, price.upper_bound);
+    Ok(())
+}
@@ -0,0 +1,509 @@
+/**
+ * Per-Market Oracle Settings
+ *
+ * One `Market` PDA per mint, at [MARKET_SEED, mint], holds everything the
+ * program needs to price and risk that asset: its feed, the validation
+ * limits the feed is read with, the breaker that guards it, its lending
+ * parameters, its swap fee, and which of its operations are paused. The
+ * swap, collateral, and liquidation examples read these from the markets
+ * of the mints involved instead of from constants, so each asset is tuned
+ * on its own: a stablecoin can demand tight confidence while a long-tail
+ * token gets a wider band, a lower LTV, and a higher fee.
+ *
+ * `Market::guarded_price` is the one read path: it validates the update
+ * against the market's limits and feed, and passes the price through the
+ * market's breaker. Like `guard_price`, it reports a trip as `None`
+ * instead of failing so the trip persists; callers return `Ok(())`
+ * without acting. Check `require_allowed` before it: the guardian
+ * (guardian.rs) pauses operations of a market one by one.
+ * `guarded_pair` reads a swap's two prices that way and pairs them as a
+ * `PricedPair` within the tighter of the two markets' `pair` limits.
+ *
+ * Markets are managed by the `OracleConfig` authority, like feed bindings,
+ * and their settings change on the oracle config's timelock: the authority
+ * proposes a complete new `MarketConfig` (`propose_market_config`), which
+ * anyone may apply once `timelock_secs` have passed
+ * (`apply_market_config`); until then the authority may cancel it. Only a
+ * change that loosens nothing applies at once (`tighten_market`): the same
+ * feed, a shorter max age, a narrower confidence band, a stricter
+ * verification level, tighter pair limits, a lower max LTV or borrow cap,
+ * a tighter breaker, and the same liquidation terms and fee. Pausing goes
+ * through the guardian. Changing the feed re-arms the breaker, since the
+ * old feed's reference price means nothing for the new one;
+ * `reset_market_breaker` clears a trip.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as `market`);
+ *    requires circuit-breaker.rs, guardian.rs, lending-risk.rs,
+ *    oracle-config.rs, price-events.rs, and anchor-spl for the mint's
+ *    decimals
+ * 2. Create a market per mint with `init_market`, and the `InterestMarket`
+ *    of each borrowable one with `init_market_interest`
+ * 3. Derive the market from the mint your state stores
+ *    (`seeds = [MARKET_SEED, position.collateral_mint.as_ref()]`), check
+ *    `require_allowed`, and read prices through `guarded_price`
+ */
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::{
+    consume_price, get_validated_price, BreakerConfig, BreakerState, CircuitBreakerReset, DynamicFeeConfig,
+    InterestMarket, OperationKind, OracleConfig, OracleConfigParams, OracleError, PairLimits, PauseFlags,
+    PriceUse, PriceValidationConfig, PricedPair, RateModel, RiskParams, RiskParamsConfig, TripAction, ValidatedPrice,
+    INTEREST_MARKET_SEED, ORACLE_CONFIG_SEED,
+};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for markets: [MARKET_SEED, mint]
+pub const MARKET_SEED: &[u8] = b"market";
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// Settings of one market, as passed to `init_market` and
+/// `propose_market_config`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct MarketConfig {
+    /// The one feed that prices the mint
+    pub feed_id: [u8; 32],
+    /// Validation limits of the feed
+    pub oracle: OracleConfigParams,
+    /// Limits on this asset's price paired with another's; a pair takes
+    /// the tighter of its two markets'
+    pub pair: PairLimits,
+    pub risk: RiskParamsConfig,
+    /// Fee of swaps paying out this asset
+    pub swap_fee: DynamicFeeConfig,
+    pub breaker: BreakerConfig,
+}
+
+impl MarketConfig {
+    pub const LEN: usize =
+        32 + OracleConfigParams::LEN + PairLimits::LEN + (2 + 2 + 2 + 2 + 8) + (2 + 4 + 2) + (8 + 8 + 1);
+
+    pub fn validate(&self) -> Result<()> {
+        self.oracle.validate()?;
+        self.pair.validate()?;
+        self.risk.validate()?;
+        self.swap_fee.validate()?;
+        self.breaker.validate()
+    }
+
+    /// Whether this config is at least as strict as `current` everywhere,
+    /// so it may skip the timelock. Liquidation terms and the fee move
+    /// value between users either way and must stay unchanged.
+    pub fn only_tightens(&self, current: &MarketConfig) -> bool {
+        let (oracle, risk, breaker) = (&self.oracle, &self.risk, &self.breaker);
+        self.feed_id == current.feed_id
+            && oracle.max_age_secs <= current.oracle.max_age_secs
+            && oracle.max_confidence_bps <= current.oracle.max_confidence_bps
+            && oracle.verification_level.gte(current.oracle.verification_level)
+            && self.pair.tighter(current.pair) == self.pair
+            && risk.max_ltv_bps <= current.risk.max_ltv_bps
+            && risk.borrow_cap <= current.risk.borrow_cap
+            && risk.liquidation_threshold_bps == current.risk.liquidation_threshold_bps
+            && risk.liquidation_bonus_bps == current.risk.liquidation_bonus_bps
+            && risk.close_factor_bps == current.risk.close_factor_bps
+            && self.swap_fee == current.swap_fee
+            && breaker.max_deviation_bps <= current.breaker.max_deviation_bps
+            && breaker.window_secs >= current.breaker.window_secs
+            && (breaker.on_trip == current.breaker.on_trip || breaker.on_trip == TripAction::Reject)
+    }
+}
+
+/// A proposed market config and when it may be applied
+#[derive(Clone, Copy, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct PendingMarketChange {
+    pub config: MarketConfig,
+    pub proposed_at: i64,
+    pub effective_at: i64,
+}
+
+impl PendingMarketChange {
+    pub const LEN: usize = MarketConfig::LEN + 8 + 8;
+}
+
+/// Oracle and risk settings of one mint
+#[account]
+pub struct Market {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub feed_id: [u8; 32],
+    pub oracle: OracleConfigParams,
+    pub pair: PairLimits,
+    pub risk: RiskParamsConfig,
+    pub swap_fee: DynamicFeeConfig,
+    /// Breaker of `feed_id`; its config is the market's
+    pub breaker: BreakerState,
+    /// Operations the guardian or the authority paused
+    pub paused: PauseFlags,
+    /// Outstanding borrows in native units
+    pub total_borrowed: u64,
+    /// Set by `init_market_interest`: liquidations must then pass the
+    /// `InterestMarket` so the debt accrues and its repayment is recorded
+    pub has_interest_market: bool,
+    /// Change waiting out the oracle config's timelock
+    pub pending_change: Option<PendingMarketChange>,
+    pub bump: u8,
+}
+
+impl Market {
+    pub const LEN: usize = 8
+        + 32
+        + 1
+        + 32
+        + OracleConfigParams::LEN
+        + PairLimits::LEN
+        + (2 + 2 + 2 + 2 + 8)
+        + (2 + 4 + 2)
+        + BreakerState::LEN
+        + 1
+        + 8
+        + 1
+        + (1 + PendingMarketChange::LEN)
+        + 1;
+
+    pub fn config(&self) -> MarketConfig {
+        MarketConfig {
+            feed_id: self.feed_id,
+            oracle: self.oracle,
+            pair: self.pair,
+            risk: self.risk,
+            swap_fee: self.swap_fee,
+            breaker: self.breaker.config,
+        }
+    }
+
+    /// Limits of this market's feed, with the feed ID check
+    pub fn validation_config(&self) -> PriceValidationConfig {
+        PriceValidationConfig {
+            max_age_secs: self.oracle.max_age_secs,
+            max_age_slots: None,
+            max_confidence_bps: self.oracle.max_confidence_bps,
+            expected_feed_id: Some(self.feed_id),
+            verification_level: self.oracle.verification_level,
+        }
+    }
+
+    /// Fails with `MarketPaused` if any of `operations` is paused
+    pub fn require_allowed(&self, operations: PauseFlags) -> Result<()> {
+        require!(!self.paused.intersects(operations), OracleError::MarketPaused);
+        Ok(())
+    }
+
+    /// Validate `source` with this market's limits, emit its use, and pass
+    /// it through the breaker. `None` when the breaker does not allow
+    /// `operation`: return `Ok(())` without acting so the trip persists.
+    pub fn guarded_price(
+        &mut self,
+        source: &Account<PriceUpdateV2>,
+        clock: &Clock,
+        used_for: PriceUse,
+        subject: Pubkey,
+        operation: OperationKind,
+    ) -> Result<Option<ValidatedPrice>> {
+        let price = consume_price(source, source.key(), &self.validation_config(), clock, used_for, subject)?;
+        let allowed = self.breaker.guard(&self.feed_id, &price, operation)?;
+        Ok(allowed.then_some(price))
+    }
+
+    /// Read a swap's `input` through this market and its `output` through
+    /// `output_market`, as `guarded_price` does, and pair them within the
+    /// tighter of the two markets' `pair` limits. `None` when either
+    /// breaker does not allow `operation`; both observe their price first.
+    pub fn guarded_pair(
+        &mut self,
+        input: &Account<PriceUpdateV2>,
+        output_market: &mut Market,
+        output: &Account<PriceUpdateV2>,
+        clock: &Clock,
+        subject: Pubkey,
+        operation: OperationKind,
+    ) -> Result<Option<PricedPair>> {
+        let limits = self.pair.tighter(output_market.pair);
+        let input = self.guarded_price(input, clock, PriceUse::Swap, subject, operation)?;
+        let output = output_market.guarded_price(output, clock, PriceUse::Swap, subject, operation)?;
+        let (Some(input), Some(output)) = (input, output) else {
+            return Ok(None);
+        };
+        PricedPair::try_new(input, output, &limits).map(Some)
+    }
+
+    /// The market's risk settings as a `RiskParams`, for the lending-risk.rs
+    /// helpers. A value, not an account: record borrows on the market.
+    pub fn risk_params(&self) -> RiskParams {
+        RiskParams {
+            authority: Pubkey::default(),
+            mint: self.mint,
+            feed_id: self.feed_id,
+            decimals: self.decimals,
+            config: self.risk,
+            total_borrowed: self.total_borrowed,
+            bump: self.bump,
+        }
+    }
+
+    pub fn record_repay(&mut self, amount: u64) {
+        self.total_borrowed = self.total_borrowed.saturating_sub(amount);
+    }
+
+    fn apply(&mut self, config: MarketConfig) {
+        if config.feed_id != self.feed_id {
+            self.breaker = BreakerState::new(config.breaker);
+        } else {
+            self.breaker.config = config.breaker;
+        }
+        self.feed_id = config.feed_id;
+        self.oracle = config.oracle;
+        self.pair = config.pair;
+        self.risk = config.risk;
+        self.swap_fee = config.swap_fee;
+    }
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct MarketChangeProposed {
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    pub config: MarketConfig,
+    pub effective_at: i64,
+}
+
+#[event]
+pub struct MarketChangeCancelled {
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    pub effective_at: i64,
+}
+
+/// The settings now in force, after a tightening or an applied change
+#[event]
+pub struct MarketUpdated {
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    pub config: MarketConfig,
+    pub timestamp: i64,
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitMarket<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [ORACLE_CONFIG_SEED], bump = oracle_config.bump, has_one = authority)]
+    pub oracle_config: Account<'info, OracleConfig>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Market::LEN,
+        seeds = [MARKET_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Signed by the oracle config authority
+#[derive(Accounts)]
+pub struct UpdateMarket<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [ORACLE_CONFIG_SEED], bump = oracle_config.bump, has_one = authority)]
+    pub oracle_config: Account<'info, OracleConfig>,
+
+    #[account(mut, seeds = [MARKET_SEED, market.mint.as_ref()], bump = market.bump)]
+    pub market: Account<'info, Market>,
+}
+
+/// Permissionless once the timelock has passed
+#[derive(Accounts)]
+pub struct ApplyMarketConfig<'info> {
+    #[account(mut, seeds = [MARKET_SEED, market.mint.as_ref()], bump = market.bump)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct ResetMarketBreaker<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [ORACLE_CONFIG_SEED], bump = oracle_config.bump, has_one = authority)]
+    pub oracle_config: Account<'info, OracleConfig>,
+
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    #[account(mut, seeds = [MARKET_SEED, market.mint.as_ref()], bump = market.bump)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct InitMarketInterest<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [ORACLE_CONFIG_SEED], bump = oracle_config.bump, has_one = authority)]
+    pub oracle_config: Account<'info, OracleConfig>,
+
+    #[account(mut, seeds = [MARKET_SEED, market.mint.as_ref()], bump = market.bump)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = InterestMarket::LEN,
+        seeds = [INTEREST_MARKET_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub interest_market: Account<'info, InterestMarket>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn init_market(ctx: Context<InitMarket>, config: MarketConfig) -> Result<()> {
+    config.validate()?;
+
+    let market = &mut ctx.accounts.market;
+    market.mint = ctx.accounts.mint.key();
+    market.decimals = ctx.accounts.mint.decimals;
+    market.feed_id = config.feed_id;
+    market.breaker = BreakerState::new(config.breaker);
+    market.apply(config);
+    market.paused = PauseFlags::NONE;
+    market.total_borrowed = 0;
+    market.has_interest_market = false;
+    market.pending_change = None;
+    market.bump = ctx.bumps.market;
+    Ok(())
+}
+
+/// Apply `config` at once if it only tightens the market's settings (see
+/// `MarketConfig::only_tightens`), failing with `MarketChangeLoosens`
+/// otherwise. A pending change stays pending.
+pub fn tighten_market(ctx: Context<UpdateMarket>, config: MarketConfig) -> Result<()> {
+    config.validate()?;
+    let market = &mut ctx.accounts.market;
+    require!(config.only_tightens(&market.config()), OracleError::MarketChangeLoosens);
+    market.apply(config);
+
+    emit!(MarketUpdated {
+        market: market.key(),
+        mint: market.mint,
+        config,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Queue `config`, applicable after the oracle config's timelock. Borrows
+/// and the breaker's reference are kept unless the feed changes.
+pub fn propose_market_config(ctx: Context<UpdateMarket>, config: MarketConfig) -> Result<()> {
+    config.validate()?;
+    let now = Clock::get()?.unix_timestamp;
+
+    let market = &mut ctx.accounts.market;
+    require!(market.pending_change.is_none(), OracleError::ConfigChangePending);
+    let effective_at = now
+        .checked_add(ctx.accounts.oracle_config.timelock_secs)
+        .ok_or_else(|| error!(OracleError::MathOverflow))?;
+
+    emit!(MarketChangeProposed {
+        market: market.key(),
+        mint: market.mint,
+        config,
+        effective_at,
+    });
+    market.pending_change = Some(PendingMarketChange {
+        config,
+        proposed_at: now,
+        effective_at,
+    });
+    Ok(())
+}
+
+pub fn apply_market_config(ctx: Context<ApplyMarketConfig>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let market = &mut ctx.accounts.market;
+    let pending = market
+        .pending_change
+        .take()
+        .ok_or_else(|| error!(OracleError::NoPendingConfigChange))?;
+    require!(now >= pending.effective_at, OracleError::ConfigChangeNotReady);
+    market.apply(pending.config);
+
+    emit!(MarketUpdated {
+        market: market.key(),
+        mint: market.mint,
+        config: pending.config,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+pub fn cancel_market_config(ctx: Context<UpdateMarket>) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    let pending = market
+        .pending_change
+        .take()
+        .ok_or_else(|| error!(OracleError::NoPendingConfigChange))?;
+
+    emit!(MarketChangeCancelled {
+        market: market.key(),
+        mint: market.mint,
+        effective_at: pending.effective_at,
+    });
+    Ok(())
+}
+
+/// `reset_circuit_breaker` for the market's breaker, reading the price with
+/// the market's limits
+pub fn reset_market_breaker(ctx: Context<ResetMarketBreaker>, reanchor: bool) -> Result<()> {
+    let clock = Clock::get()?;
+    let market = &mut ctx.accounts.market;
+    let price = get_validated_price(&ctx.accounts.price_update, &market.validation_config(), &clock)?;
+    market.breaker.reset(reanchor.then_some(&price));
+
+    emit!(CircuitBreakerReset {
+        breaker: market.feed_id,
+        authority: ctx.accounts.authority.key(),
+        reanchored: reanchor,
+        price: price.price,
+        exponent: price.exponent,
+    });
+    Ok(())
+}
+
+/// `init_interest_market` for a market's asset; the interest market is keyed
+/// by the market, so `accrue_position_debt` takes the market's address
+pub fn init_market_interest(ctx: Context<InitMarketInterest>, rate_model: RateModel) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    market.has_interest_market = true;
+    ctx.accounts.interest_market.open(
+        ctx.accounts.authority.key(),
+        market.key(),
+        market.mint,
+        rate_model,
+        Clock::get()?.unix_timestamp,
+        ctx.bumps.interest_market,
+    )
+}
@@ -0,0 +1,624 @@
+/**
+ * Pyth Oracle Template for Native (non-Anchor) Programs
+ *
+ * The validation core of anchor-oracle.rs for programs written against
+ * `solana-program` directly: the same `PriceValidationConfig` (Borsh
+ * layout included, so clients serialize it the same way), `ValidatedPrice`,
+ * `get_validated_price`, and `OracleError` codes. The Anchor template
+ * numbers `OracleError` from 9000 and `PriceSourceError` from 9900, and so
+ * does this file, so clients decode failures from either program with the
+ * same table.
+ *
+ * Without Anchor there is no `Account<PriceUpdateV2>`: `PriceUpdate::load`
+ * does the owner and discriminator checks Anchor would and reads the price
+ * fields straight from the account data, as price-update-view.rs does.
+ * The receiver SDK types are not used because the SDK depends on Anchor.
+ *
+ * The decimal math helpers (`calculate_usd_value` and friends) are built on
+ * Anchor's error type and are not repeated here.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    solana-program = "1.18"
+ *    borsh = { version = "1", features = ["derive"] }
+ *    serde = { version = "1", features = ["derive"], optional = true }
+ *
+ *    [features]
+ *    no-entrypoint = []
+ *    serde = ["dep:serde"]   # same JSON as anchor-oracle.rs (price-serde.rs)
+ *
+ *    [lib]
+ *    crate-type = ["cdylib", "lib"]
+ *
+ * 2. Copy this file as the crate's `src/lib.rs` (or declare it as a module
+ *    and move the entrypoint to your own `lib.rs`)
+ * 3. Replace the example instructions with your program's
+ */
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::hash;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+solana_program::declare_id!("YourProgramId11111111111111111111111111111111");
+
+/// Pyth Receiver Program ID (mainnet/devnet)
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Common price feed IDs as hex
+pub mod price_feeds {
+    pub const BTC_USD: &str =
+        "0xe62df6c8b4a85fe1a67db44dc12de5db330f7ac66b72dc658afedf0f4a415b43";
+    pub const ETH_USD: &str =
+        "0xff61491a931112ddf1bd8147cd1b641375f79f5825126d665480874634fd0ace";
+    pub const SOL_USD: &str =
+        "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+    pub const USDC_USD: &str =
+        "0xeaa020c61cc479712813461ce153894a96a6c00b21ed0cfc2798d1f9a9e9c94a";
+}
+
+/// Default maximum price age (60 seconds)
+pub const DEFAULT_MAX_PRICE_AGE: u64 = 60;
+
+/// Maximum acceptable confidence (200 basis points = 2%)
+pub const MAX_CONFIDENCE_BPS: u64 = 200;
+
+/// Guardian signatures required for partial verification in lenient mode
+pub const LENIENT_MIN_SIGNATURES: u8 = 5;
+
+/// First `OracleError` code, `ORACLE_ERROR_OFFSET` in anchor-oracle.rs
+pub const ERROR_CODE_OFFSET: u32 = 9000;
+
+/// First `PriceSourceError` code, as in price-source.rs
+pub const PRICE_SOURCE_ERROR_OFFSET: u32 = 9900;
+
+// ============================================================================
+// ERROR CODES
+// ============================================================================
+
+/// Same variants, order, and codes as `OracleError` in anchor-oracle.rs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OracleError {
+    InvalidFeedId,
+    FeedIdMismatch,
+    PriceTooStale,
+    ConfidenceTooHigh,
+    ZeroPrice,
+    NegativePrice,
+    SlippageExceeded,
+    PricesNotSynchronized,
+    InsufficientCollateral,
+    MathOverflow,
+    InsufficientOracleSources,
+    TooManyOracleSources,
+    OracleDeviationTooHigh,
+    InvalidPriceUpdateAccount,
+    CircuitBreakerTripped,
+    InvalidCircuitBreakerConfig,
+    InvalidRiskParams,
+    BorrowCapExceeded,
+    NotLiquidatable,
+    InvalidLiquidationAmount,
+    InvalidFee,
+    Depegged,
+    InvalidDepegConfig,
+    PriceRollback,
+    InvalidRollbackGap,
+    PriceCacheMiss,
+    StaleSnapshot,
+    InvalidPositionProof,
+    MissingPriceFeed,
+    DuplicatePriceFeed,
+    CommitmentHashFailed,
+    TooManyBasketAssets,
+    InvalidMarginParams,
+    TooManyPriceFeeds,
+    InvalidOracleConfig,
+    TooManyFeedOverrides,
+    NotNominatedAuthority,
+    ConfigChangePending,
+    ConfigChangeNotReady,
+    NoPendingConfigChange,
+    SpotEmaDivergence,
+    InvalidFallbackPolicy,
+    WithdrawalsFrozen,
+    PostedSlotUnavailable,
+    InvalidStakePool,
+    StakePoolStale,
+    FeedBindingMismatch,
+    InvalidSwapPool,
+    InsufficientLiquidity,
+    InvalidAmmPool,
+    AmmPriceDeviation,
+    InvalidInterestRateModel,
+    InterestMarketMismatch,
+    InvalidFundingParams,
+    InvalidTriggerOrder,
+    TriggerNotReached,
+    TriggerOrderExpired,
+    InvalidVaa,
+    InvalidGuardianSet,
+    InvalidGuardianSignature,
+    PriceUpdateProofMismatch,
+    InvalidLazerPayload,
+    LazerSignatureMismatch,
+    UntrustedLazerSigner,
+    LazerFeedNotFound,
+    InvalidLazerSigners,
+    InvalidTickSpacing,
+    TickOutOfRange,
+    MarketPaused,
+    InvalidPauseFlags,
+    NotGuardian,
+    MarketChangeLoosens,
+    InterestMarketRequired,
+}
+
+impl From<OracleError> for ProgramError {
+    fn from(error: OracleError) -> Self {
+        ProgramError::Custom(ERROR_CODE_OFFSET + error as u32)
+    }
+}
+
+/// Same codes as `PriceSourceError` in price-source.rs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriceSourceError {
+    InsufficientVerification,
+    PriceUnavailable,
+    PriceTooStale,
+    FeedIdMismatch,
+    InvalidFeedId,
+}
+
+impl From<PriceSourceError> for ProgramError {
+    fn from(error: PriceSourceError) -> Self {
+        ProgramError::Custom(PRICE_SOURCE_ERROR_OFFSET + error as u32)
+    }
+}
+
+// ============================================================================
+// PRICE VALIDATION
+// ============================================================================
+
+/// Wormhole verification level of a price update; Borsh-compatible with
+/// the receiver SDK's `VerificationLevel`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "level", rename_all = "snake_case")
+)]
+pub enum VerificationLevel {
+    Partial { num_signatures: u8 },
+    Full,
+}
+
+impl VerificationLevel {
+    /// Whether this level is at least `other`
+    pub fn gte(&self, other: VerificationLevel) -> bool {
+        match (self, other) {
+            (VerificationLevel::Full, _) => true,
+            (VerificationLevel::Partial { .. }, VerificationLevel::Full) => false,
+            (VerificationLevel::Partial { num_signatures }, VerificationLevel::Partial { num_signatures: required }) => {
+                *num_signatures >= required
+            }
+        }
+    }
+}
+
+/// Configuration for price validation
+#[derive(Clone, Copy, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PriceValidationConfig {
+    /// Maximum age of price in seconds
+    pub max_age_secs: u64,
+    /// Maximum slots since the update was posted, checked in addition to
+    /// the seconds when set
+    pub max_age_slots: Option<u64>,
+    /// Maximum confidence in basis points
+    pub max_confidence_bps: u64,
+    /// Expected feed ID (optional)
+    #[cfg_attr(feature = "serde", serde(with = "feed_id_serde::optional"))]
+    pub expected_feed_id: Option<[u8; 32]>,
+    /// Minimum Wormhole verification level of the price update
+    pub verification_level: VerificationLevel,
+}
+
+impl Default for PriceValidationConfig {
+    fn default() -> Self {
+        Self {
+            max_age_secs: DEFAULT_MAX_PRICE_AGE,
+            max_age_slots: None,
+            max_confidence_bps: MAX_CONFIDENCE_BPS,
+            expected_feed_id: None,
+            verification_level: VerificationLevel::Full,
+        }
+    }
+}
+
+impl PriceValidationConfig {
+    /// Create strict config for high-value operations
+    pub fn strict() -> Self {
+        Self {
+            max_age_secs: 30,
+            max_age_slots: None,
+            max_confidence_bps: 100, // 1%
+            expected_feed_id: None,
+            verification_level: VerificationLevel::Full,
+        }
+    }
+
+    /// Create lenient config for less critical operations
+    pub fn lenient() -> Self {
+        Self {
+            max_age_secs: 120,
+            max_age_slots: None,
+            max_confidence_bps: 500, // 5%
+            expected_feed_id: None,
+            verification_level: VerificationLevel::Partial {
+                num_signatures: LENIENT_MIN_SIGNATURES,
+            },
+        }
+    }
+
+    /// Set expected feed ID from hex string
+    pub fn with_feed_id(mut self, feed_id_hex: &str) -> Result<Self, ProgramError> {
+        self.expected_feed_id = Some(parse_feed_id(feed_id_hex)?);
+        Ok(self)
+    }
+
+    /// Also reject updates posted more than `max_age_slots` slots ago
+    pub fn with_max_age_slots(mut self, max_age_slots: u64) -> Self {
+        self.max_age_slots = Some(max_age_slots);
+        self
+    }
+
+    /// Set the minimum verification level
+    pub fn with_verification_level(mut self, level: VerificationLevel) -> Self {
+        self.verification_level = level;
+        self
+    }
+
+    /// Require full Wormhole verification (high-value flows)
+    pub fn require_full_verification(self) -> Self {
+        self.with_verification_level(VerificationLevel::Full)
+    }
+
+    /// Accept partially verified updates with at least `num_signatures`
+    /// guardian signatures (cheap reads)
+    pub fn with_partial_verification(self, num_signatures: u8) -> Self {
+        self.with_verification_level(VerificationLevel::Partial { num_signatures })
+    }
+}
+
+/// Validated price with bounds
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidatedPrice {
+    /// Feed the price was read from
+    #[cfg_attr(feature = "serde", serde(with = "feed_id_serde"))]
+    pub feed_id: [u8; 32],
+    /// Raw price value
+    pub price: i64,
+    /// Confidence interval
+    pub conf: u64,
+    /// Price exponent
+    pub exponent: i32,
+    /// Publish timestamp
+    pub publish_time: i64,
+    /// Lower bound (price - conf)
+    pub lower_bound: i64,
+    /// Upper bound (price + conf)
+    pub upper_bound: i64,
+}
+
+impl ValidatedPrice {
+    /// Get conservative price for selling (lower bound)
+    pub fn sell_price(&self) -> i64 {
+        self.lower_bound
+    }
+
+    /// Get conservative price for buying (upper bound)
+    pub fn buy_price(&self) -> i64 {
+        self.upper_bound
+    }
+
+    /// Get price with N-sigma confidence interval
+    pub fn price_with_sigma(&self, sigma: u8) -> (i64, i64) {
+        let half_width = (self.conf as i64) * (sigma as i64);
+        (
+            self.price.saturating_sub(half_width),
+            self.price.saturating_add(half_width),
+        )
+    }
+
+    /// Confidence as basis points of the price; see `confidence_bps`
+    pub fn relative_confidence(&self) -> Result<u16, ProgramError> {
+        confidence_bps(self.price, self.conf)
+    }
+}
+
+// ============================================================================
+// PRICE UPDATE ACCOUNT
+// ============================================================================
+
+/// Discriminator, then `write_authority`
+const VERIFICATION_LEVEL_OFFSET: usize = 8 + 32;
+
+/// `PriceFeedMessage` fields, relative to its start
+const FEED_ID: usize = 0;
+const PRICE: usize = 32;
+const CONF: usize = 40;
+const EXPONENT: usize = 48;
+const PUBLISH_TIME: usize = 52;
+const PREV_PUBLISH_TIME: usize = 60;
+const EMA_PRICE: usize = 68;
+const EMA_CONF: usize = 76;
+/// Followed by `posted_slot`
+const MESSAGE_LEN: usize = 84;
+
+/// Anchor discriminator of `PriceUpdateV2`: sha256("account:PriceUpdateV2")
+fn price_update_discriminator() -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash(b"account:PriceUpdateV2").to_bytes()[..8]);
+    discriminator
+}
+
+/// The fields of a Pyth receiver `PriceUpdateV2` account
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceUpdate {
+    pub verification_level: VerificationLevel,
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+    pub posted_slot: u64,
+}
+
+impl PriceUpdate {
+    /// Check owner and discriminator, then read the price fields
+    pub fn load(account: &AccountInfo) -> Result<Self, ProgramError> {
+        if *account.owner != PYTH_RECEIVER_PROGRAM_ID {
+            return Err(OracleError::InvalidPriceUpdateAccount.into());
+        }
+        let data = account.try_borrow_data()?;
+        Self::parse(&data)
+    }
+
+    /// Read the price fields from raw account data (discriminator included)
+    pub fn parse(data: &[u8]) -> Result<Self, ProgramError> {
+        let invalid = || ProgramError::from(OracleError::InvalidPriceUpdateAccount);
+        if data.get(..8) != Some(price_update_discriminator().as_ref()) {
+            return Err(invalid());
+        }
+
+        // `VerificationLevel` is one byte (Full) or two (Partial), which
+        // shifts every later field
+        let mut level_bytes = data.get(VERIFICATION_LEVEL_OFFSET..).ok_or_else(invalid)?;
+        let verification_level = VerificationLevel::deserialize(&mut level_bytes).map_err(|_| invalid())?;
+        let message = data.len() - level_bytes.len();
+        if data.len() < message + MESSAGE_LEN + 8 {
+            return Err(invalid());
+        }
+
+        let bytes = |offset: usize, len: usize| &data[message + offset..message + offset + len];
+        let i64_at = |offset| i64::from_le_bytes(bytes(offset, 8).try_into().unwrap());
+        let u64_at = |offset| u64::from_le_bytes(bytes(offset, 8).try_into().unwrap());
+
+        Ok(Self {
+            verification_level,
+            feed_id: bytes(FEED_ID, 32).try_into().unwrap(),
+            price: i64_at(PRICE),
+            conf: u64_at(CONF),
+            exponent: i32::from_le_bytes(bytes(EXPONENT, 4).try_into().unwrap()),
+            publish_time: i64_at(PUBLISH_TIME),
+            prev_publish_time: i64_at(PREV_PUBLISH_TIME),
+            ema_price: i64_at(EMA_PRICE),
+            ema_conf: u64_at(EMA_CONF),
+            posted_slot: u64_at(MESSAGE_LEN),
+        })
+    }
+
+    /// Spot price, rejecting it if older than `max_age` seconds. Fails
+    /// with the code the Anchor template maps the receiver SDK's
+    /// `PriceTooOld` to.
+    pub fn get_price_no_older_than(&self, clock: &Clock, max_age: u64) -> Result<(i64, u64), ProgramError> {
+        if self.publish_time.saturating_add(max_age as i64) < clock.unix_timestamp {
+            return Err(PriceSourceError::PriceTooStale.into());
+        }
+        Ok((self.price, self.conf))
+    }
+}
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+/// Get and validate a price from a loaded price update
+pub fn get_validated_price(
+    price_update: &PriceUpdate,
+    config: &PriceValidationConfig,
+    clock: &Clock,
+) -> Result<ValidatedPrice, ProgramError> {
+    // Enforce minimum verification level
+    if !price_update.verification_level.gte(config.verification_level) {
+        return Err(PriceSourceError::InsufficientVerification.into());
+    }
+
+    // Verify feed identity
+    if let Some(expected) = config.expected_feed_id {
+        if price_update.feed_id != expected {
+            return Err(OracleError::FeedIdMismatch.into());
+        }
+    }
+
+    // Get price with staleness check
+    let (price, conf) = price_update.get_price_no_older_than(clock, config.max_age_secs)?;
+
+    // Slot staleness, which does not depend on the validator clock
+    if let Some(max_age_slots) = config.max_age_slots {
+        if clock.slot.saturating_sub(price_update.posted_slot) > max_age_slots {
+            return Err(OracleError::PriceTooStale.into());
+        }
+    }
+
+    // Validate confidence
+    validate_confidence(price, conf, config.max_confidence_bps)?;
+
+    let conf_i64 = conf as i64;
+    Ok(ValidatedPrice {
+        feed_id: price_update.feed_id,
+        price,
+        conf,
+        exponent: price_update.exponent,
+        publish_time: price_update.publish_time,
+        lower_bound: price.saturating_sub(conf_i64),
+        upper_bound: price.saturating_add(conf_i64),
+    })
+}
+
+/// Confidence as basis points of the price, rounded up, saturating at
+/// `u16::MAX` (also the result for a zero price), as in anchor-oracle.rs
+pub fn confidence_bps(price: i64, conf: u64) -> Result<u16, ProgramError> {
+    if price == 0 {
+        return Ok(u16::MAX);
+    }
+    let bps = ((conf as u128) * 10_000).div_ceil(price.unsigned_abs() as u128);
+    Ok(u16::try_from(bps).unwrap_or(u16::MAX))
+}
+
+/// Validate that confidence is within acceptable bounds
+pub fn validate_confidence(price: i64, conf: u64, max_bps: u64) -> Result<(), ProgramError> {
+    if price == 0 {
+        return Err(OracleError::ZeroPrice.into());
+    }
+
+    // ceil(conf_bps) <= max_bps, cross-multiplied to avoid a u128 division
+    if (conf as u128) * 10_000 > (max_bps as u128) * price.unsigned_abs() as u128 {
+        return Err(OracleError::ConfidenceTooHigh.into());
+    }
+    Ok(())
+}
+
+/// Parse feed ID from hex string (with or without `0x`)
+pub fn parse_feed_id(feed_id_hex: &str) -> Result<[u8; 32], ProgramError> {
+    let hex = feed_id_hex.strip_prefix("0x").unwrap_or(feed_id_hex).as_bytes();
+    if hex.len() != 64 {
+        return Err(OracleError::InvalidFeedId.into());
+    }
+
+    let nibble = |c: u8| match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => Err(ProgramError::from(OracleError::InvalidFeedId)),
+    };
+    let mut feed_id = [0u8; 32];
+    for (byte, pair) in feed_id.iter_mut().zip(hex.chunks_exact(2)) {
+        *byte = (nibble(pair[0])? << 4) | nibble(pair[1])?;
+    }
+    Ok(feed_id)
+}
+
+/// Feed IDs as `0x`-prefixed hex in serde formats
+#[cfg(feature = "serde")]
+mod feed_id_serde {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    fn to_hex(feed_id: &[u8; 32]) -> String {
+        let hex: String = feed_id.iter().map(|b| format!("{:02x}", b)).collect();
+        format!("0x{hex}")
+    }
+
+    fn from_hex<E: serde::de::Error>(hex: &str) -> Result<[u8; 32], E> {
+        super::parse_feed_id(hex).map_err(|_| E::custom(format!("invalid feed id {hex}")))
+    }
+
+    pub fn serialize<S: Serializer>(feed_id: &[u8; 32], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_hex(feed_id))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 32], D::Error> {
+        from_hex(&String::deserialize(deserializer)?)
+    }
+
+    pub mod optional {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(feed_id: &Option<[u8; 32]>, serializer: S) -> Result<S::Ok, S::Error> {
+            feed_id.as_ref().map(to_hex).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<[u8; 32]>, D::Error> {
+            Option::<String>::deserialize(deserializer)?.map(|hex| from_hex(&hex)).transpose()
+        }
+    }
+}
+
+// ============================================================================
+// EXAMPLE PROGRAM
+// ============================================================================
+
+/// Instructions, Borsh-encoded with a one-byte tag
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub enum OracleInstruction {
+    /// Get price with default validation
+    ///
+    /// Accounts: `[] price_update`
+    GetPrice,
+
+    /// Get price with strict validation and feed ID check
+    ///
+    /// Accounts: `[] price_update`
+    GetVerifiedPrice { feed_id_hex: String },
+
+    /// Get price with a caller-supplied validation config
+    ///
+    /// Accounts: `[] price_update`
+    GetPriceWithConfig { config: PriceValidationConfig },
+}
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    if *program_id != ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let instruction = OracleInstruction::try_from_slice(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    let config = match instruction {
+        OracleInstruction::GetPrice => PriceValidationConfig::default(),
+        OracleInstruction::GetVerifiedPrice { feed_id_hex } => {
+            PriceValidationConfig::strict().with_feed_id(&feed_id_hex)?
+        }
+        OracleInstruction::GetPriceWithConfig { config } => config,
+    };
+
+    let accounts = &mut accounts.iter();
+    let price_update = PriceUpdate::load(next_account_info(accounts)?)?;
+    let clock = Clock::get()?;
+
+    let price = get_validated_price(&price_update, &config, &clock)?;
+
+    msg!("Price: {} × 10^{}", price.price, price.exponent);
+    msg!("Confidence: ±{}", price.conf);
+    msg!("Bounds: [{}, {}]", price.lower_bound, price.upper_bound);
+
+    Ok(())
+}
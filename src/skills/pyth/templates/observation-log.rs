@@ -0,0 +1,234 @@
+/**
+ * Pyth Price Observation Log
+ *
+ * Records every oracle value a program actually consumed into a compact
+ * on-chain ring buffer, so auditors can later prove which (feed, price,
+ * conf, slot, consumer) tuples were used for each operation.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as
+ *    `observation_log`)
+ * 2. Initialize one log per authority with `init_observation_log`
+ * 3. Call `record_price_observation` from any instruction that consumes
+ *    a price, or use the standalone `record_observation` instruction
+ *
+ * Off-chain verification: see examples/off-chain/verify-observations.rs
+ */
+
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::{get_validated_price, Now, PriceValidationConfig, ValidatedPrice};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for observation logs
+pub const OBSERVATION_LOG_SEED: &[u8] = b"observation_log";
+
+/// Number of observations kept before the oldest is overwritten
+pub const OBSERVATION_LOG_CAPACITY: usize = 64;
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// A single consumed oracle value
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct PriceObservation {
+    /// Oracle feed ID the price was read from
+    pub feed_id: [u8; 32],
+    /// Raw price value
+    pub price: i64,
+    /// Confidence interval
+    pub conf: u64,
+    /// Price exponent
+    pub exponent: i32,
+    /// Publish timestamp of the price
+    pub publish_time: i64,
+    /// Slot in which the price was consumed
+    pub slot: u64,
+    /// Account that consumed the price (user, vault, or program PDA)
+    pub consumer: Pubkey,
+}
+
+impl PriceObservation {
+    pub const LEN: usize = 32 + 8 + 8 + 4 + 8 + 8 + 32;
+
+    /// Build an observation from a validated price
+    pub fn new(price: &ValidatedPrice, slot: u64, consumer: Pubkey) -> Self {
+        Self {
+            feed_id: price.feed_id,
+            price: price.price,
+            conf: price.conf,
+            exponent: price.exponent,
+            publish_time: price.publish_time,
+            slot,
+            consumer,
+        }
+    }
+}
+
+/// Ring buffer of consumed prices
+#[account]
+pub struct ObservationLog {
+    /// Authority allowed to record into this log
+    pub authority: Pubkey,
+    /// Index the next observation overwrites once the buffer is full
+    pub head: u32,
+    /// Total observations ever recorded (monotonic sequence number)
+    pub total_recorded: u64,
+    /// Stored observations (at most OBSERVATION_LOG_CAPACITY)
+    pub observations: Vec<PriceObservation>,
+    pub bump: u8,
+}
+
+impl ObservationLog {
+    pub const LEN: usize =
+        8 + 32 + 4 + 8 + (4 + PriceObservation::LEN * OBSERVATION_LOG_CAPACITY) + 1;
+
+    /// Append an observation, overwriting the oldest when full
+    pub fn push(&mut self, observation: PriceObservation) {
+        if self.observations.len() < OBSERVATION_LOG_CAPACITY {
+            self.observations.push(observation);
+        } else {
+            self.observations[self.head as usize] = observation;
+        }
+        self.head = ((self.head as usize + 1) % OBSERVATION_LOG_CAPACITY) as u32;
+        self.total_recorded = self.total_recorded.saturating_add(1);
+    }
+
+    /// Iterate observations from oldest to newest
+    pub fn iter_chronological(&self) -> impl Iterator<Item = &PriceObservation> {
+        let split = if self.observations.len() < OBSERVATION_LOG_CAPACITY {
+            0
+        } else {
+            self.head as usize
+        };
+        self.observations[split..]
+            .iter()
+            .chain(self.observations[..split].iter())
+    }
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+/// Record a price that was just consumed by `consumer`
+///
+/// Call this right after `get_validated_price` inside any instruction so the
+/// log reflects exactly the value the instruction used.
+pub fn record_price_observation(
+    log: &mut ObservationLog,
+    price: &ValidatedPrice,
+    consumer: Pubkey,
+    clock: &impl Now,
+) -> Result<()> {
+    let observation = PriceObservation::new(price, clock.slot(), consumer);
+    log.push(observation);
+
+    emit!(PriceObserved {
+        log: log.authority,
+        sequence: log.total_recorded,
+        observation,
+    });
+
+    Ok(())
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+/// Emitted for every recorded observation so indexers can keep a full history
+/// beyond the ring buffer capacity
+#[event]
+pub struct PriceObserved {
+    pub log: Pubkey,
+    pub sequence: u64,
+    pub observation: PriceObservation,
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+/// Accounts for creating an observation log
+#[derive(Accounts)]
+pub struct InitObservationLog<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = ObservationLog::LEN,
+        seeds = [OBSERVATION_LOG_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub observation_log: Account<'info, ObservationLog>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for recording a price observation
+#[derive(Accounts)]
+pub struct RecordObservation<'info> {
+    pub authority: Signer<'info>,
+
+    /// Account consuming the price
+    /// CHECK: only its key is recorded
+    pub consumer: UncheckedAccount<'info>,
+
+    /// The Pyth price update account
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    #[account(
+        mut,
+        seeds = [OBSERVATION_LOG_SEED, authority.key().as_ref()],
+        bump = observation_log.bump,
+        has_one = authority
+    )]
+    pub observation_log: Account<'info, ObservationLog>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn init_observation_log(ctx: Context<InitObservationLog>) -> Result<()> {
+    let log = &mut ctx.accounts.observation_log;
+    log.authority = ctx.accounts.authority.key();
+    log.head = 0;
+    log.total_recorded = 0;
+    log.observations = Vec::with_capacity(OBSERVATION_LOG_CAPACITY);
+    log.bump = ctx.bumps.observation_log;
+    Ok(())
+}
+
+pub fn record_observation(
+    ctx: Context<RecordObservation>,
+    config: PriceValidationConfig,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let price = get_validated_price(&ctx.accounts.price_update, &config, &clock)?;
+
+    record_price_observation(
+        &mut ctx.accounts.observation_log,
+        &price,
+        ctx.accounts.consumer.key(),
+        &clock,
+    )?;
+
+    msg!(
+        "Recorded observation #{}: {} × 10^{} at slot {}",
+        ctx.accounts.observation_log.total_recorded,
+        price.price,
+        price.exponent,
+        clock.slot
+    );
+
+    Ok(())
+}
@@ -0,0 +1,463 @@
+/**
+ * On-Chain Oracle Configuration
+ *
+ * `PriceValidationConfig` presets are compiled in, so tightening the max
+ * age or confidence after an incident means a program upgrade. An
+ * `OracleConfig` account holds the same parameters on chain instead: a
+ * global max age, max confidence, and minimum verification level, plus up
+ * to `MAX_FEED_OVERRIDES` per-feed overrides of the age and confidence
+ * limits (a stablecoin can require a tighter band than a volatile asset).
+ *
+ * There is one config per program, at the `[ORACLE_CONFIG_SEED]` PDA. Only
+ * the program's upgrade authority may create it, so nobody can front-run
+ * the deployment with their own parameters.
+ *
+ * No change takes effect immediately. The config authority proposes a
+ * complete new config (`propose_oracle_config`), which anyone may apply
+ * once `timelock_secs` have passed (`apply_oracle_config`); until then the
+ * authority may cancel it. Users and monitors see every change in
+ * `OracleConfigChangeProposed` a full delay before it binds. The delay is
+ * itself part of the config, and lowering it waits out the current one.
+ *
+ * The authority moves in two steps: the current authority nominates, and
+ * the nominee accepts, so a typo cannot lock the config. Hand it to a
+ * multisig or governance PDA this way after deployment.
+ *
+ * Handlers read prices through `get_configured_price`, which applies the
+ * feed's override (if any) and checks the feed ID.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as
+ *    `oracle_config`)
+ * 2. Call `init_oracle_config` once, signed by the upgrade authority, then
+ *    hand the config to governance with `nominate_oracle_config_authority`
+ * 3. Add the config PDA to every instruction that validates prices and call
+ *    `get_configured_price` in place of `get_validated_price`
+ */
+
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::{FeedId, PriceUpdateV2, VerificationLevel};
+
+use crate::program::OracleExample;
+use crate::{get_validated_price, Now, OracleError, PriceSource, PriceValidationConfig, ValidatedPrice};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for the program's oracle config: [ORACLE_CONFIG_SEED]
+pub const ORACLE_CONFIG_SEED: &[u8] = b"oracle_config";
+
+/// Most feeds with their own limits
+pub const MAX_FEED_OVERRIDES: usize = 16;
+
+/// Bounds of the change delay: long enough to react to a proposal, short
+/// enough that a mistaken delay cannot freeze the config
+pub const MIN_CONFIG_TIMELOCK_SECS: i64 = 60 * 60;
+pub const MAX_CONFIG_TIMELOCK_SECS: i64 = 30 * 24 * 60 * 60;
+
+const BPS_DENOMINATOR: u64 = 10_000;
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// Limits for every feed without an override
+#[derive(Clone, Copy, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct OracleConfigParams {
+    pub max_age_secs: u64,
+    pub max_confidence_bps: u64,
+    pub verification_level: VerificationLevel,
+}
+
+impl OracleConfigParams {
+    /// `VerificationLevel` takes at most 2 bytes
+    pub const LEN: usize = 8 + 8 + 2;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(self.max_age_secs > 0, OracleError::InvalidOracleConfig);
+        require!(
+            self.max_confidence_bps > 0 && self.max_confidence_bps <= BPS_DENOMINATOR,
+            OracleError::InvalidOracleConfig
+        );
+        if let VerificationLevel::Partial { num_signatures } = self.verification_level {
+            require!(num_signatures > 0, OracleError::InvalidOracleConfig);
+        }
+        Ok(())
+    }
+}
+
+/// Limits for one feed, replacing the global ones
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct FeedOverride {
+    pub feed_id: [u8; 32],
+    pub max_age_secs: u64,
+    pub max_confidence_bps: u64,
+}
+
+impl FeedOverride {
+    pub const LEN: usize = 32 + 8 + 8;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(self.max_age_secs > 0, OracleError::InvalidOracleConfig);
+        require!(
+            self.max_confidence_bps > 0 && self.max_confidence_bps <= BPS_DENOMINATOR,
+            OracleError::InvalidOracleConfig
+        );
+        Ok(())
+    }
+}
+
+/// A complete config, as proposed to `propose_oracle_config`
+#[derive(Clone, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct ConfigChange {
+    pub params: OracleConfigParams,
+    /// Sorted by feed ID, at most one per feed
+    pub overrides: Vec<FeedOverride>,
+    pub timelock_secs: i64,
+}
+
+impl ConfigChange {
+    pub const LEN: usize = OracleConfigParams::LEN + 4 + MAX_FEED_OVERRIDES * FeedOverride::LEN + 8;
+
+    pub fn validate(&self) -> Result<()> {
+        self.params.validate()?;
+        require!(
+            (MIN_CONFIG_TIMELOCK_SECS..=MAX_CONFIG_TIMELOCK_SECS).contains(&self.timelock_secs),
+            OracleError::InvalidOracleConfig
+        );
+        require!(self.overrides.len() <= MAX_FEED_OVERRIDES, OracleError::TooManyFeedOverrides);
+        for entry in &self.overrides {
+            entry.validate()?;
+        }
+        require!(
+            self.overrides.windows(2).all(|pair| pair[0].feed_id < pair[1].feed_id),
+            OracleError::InvalidOracleConfig
+        );
+        Ok(())
+    }
+}
+
+/// A proposed config and when it may be applied
+#[derive(Clone, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct PendingConfigChange {
+    pub change: ConfigChange,
+    pub proposed_at: i64,
+    pub effective_at: i64,
+}
+
+impl PendingConfigChange {
+    pub const LEN: usize = ConfigChange::LEN + 8 + 8;
+}
+
+/// Validation parameters of the program, tunable without an upgrade
+#[account]
+pub struct OracleConfig {
+    /// Proposes and cancels changes, nominates its successor
+    pub authority: Pubkey,
+    /// Nominated next authority; `Pubkey::default()` when none
+    pub nominated_authority: Pubkey,
+    pub params: OracleConfigParams,
+    /// Sorted by feed ID
+    pub overrides: Vec<FeedOverride>,
+    /// Delay between proposing and applying a change
+    pub timelock_secs: i64,
+    pub pending_change: Option<PendingConfigChange>,
+    pub bump: u8,
+}
+
+impl OracleConfig {
+    pub const LEN: usize = 8
+        + 32
+        + 32
+        + OracleConfigParams::LEN
+        + 4
+        + MAX_FEED_OVERRIDES * FeedOverride::LEN
+        + 8
+        + (1 + PendingConfigChange::LEN)
+        + 1;
+
+    pub fn feed_override(&self, feed_id: &FeedId) -> Option<&FeedOverride> {
+        self.overrides
+            .binary_search_by(|entry| entry.feed_id.cmp(feed_id))
+            .ok()
+            .map(|index| &self.overrides[index])
+    }
+
+    /// Validation config for `feed_id`: its override if set, the global
+    /// limits otherwise, and the feed ID check either way
+    pub fn validation_config(&self, feed_id: &FeedId) -> PriceValidationConfig {
+        let (max_age_secs, max_confidence_bps) = match self.feed_override(feed_id) {
+            Some(entry) => (entry.max_age_secs, entry.max_confidence_bps),
+            None => (self.params.max_age_secs, self.params.max_confidence_bps),
+        };
+        PriceValidationConfig {
+            max_age_secs,
+            max_age_slots: None,
+            max_confidence_bps,
+            expected_feed_id: Some(*feed_id),
+            verification_level: self.params.verification_level,
+        }
+    }
+
+    /// The current config as a `ConfigChange`, to edit into a proposal
+    pub fn current(&self) -> ConfigChange {
+        ConfigChange {
+            params: self.params,
+            overrides: self.overrides.clone(),
+            timelock_secs: self.timelock_secs,
+        }
+    }
+}
+
+impl ConfigChange {
+    /// Insert or replace the override of `entry.feed_id`
+    pub fn set_override(&mut self, entry: FeedOverride) -> Result<()> {
+        entry.validate()?;
+        match self.overrides.binary_search_by(|existing| existing.feed_id.cmp(&entry.feed_id)) {
+            Ok(index) => self.overrides[index] = entry,
+            Err(index) => {
+                require!(self.overrides.len() < MAX_FEED_OVERRIDES, OracleError::TooManyFeedOverrides);
+                self.overrides.insert(index, entry);
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop the override of `feed_id`; a feed without one is left alone
+    pub fn remove_override(&mut self, feed_id: &FeedId) {
+        if let Ok(index) = self.overrides.binary_search_by(|entry| entry.feed_id.cmp(feed_id)) {
+            self.overrides.remove(index);
+        }
+    }
+}
+
+/// `get_validated_price` with the limits `oracle_config` sets for `feed_id`
+pub fn get_configured_price<S: PriceSource>(
+    source: &S,
+    oracle_config: &OracleConfig,
+    feed_id: &FeedId,
+    clock: &impl Now,
+) -> Result<ValidatedPrice> {
+    get_validated_price(source, &oracle_config.validation_config(feed_id), clock)
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct OracleConfigChangeProposed {
+    pub authority: Pubkey,
+    pub change: ConfigChange,
+    pub effective_at: i64,
+}
+
+#[event]
+pub struct OracleConfigChangeCancelled {
+    pub authority: Pubkey,
+    pub effective_at: i64,
+}
+
+/// The config now in force, after `init_oracle_config` or a change
+#[event]
+pub struct OracleConfigUpdated {
+    pub config: ConfigChange,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OracleConfigAuthorityNominated {
+    pub authority: Pubkey,
+    pub nominated: Pubkey,
+}
+
+#[event]
+pub struct OracleConfigAuthorityChanged {
+    pub previous: Pubkey,
+    pub authority: Pubkey,
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitOracleConfig<'info> {
+    /// Must be the program's upgrade authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = OracleConfig::LEN,
+        seeds = [ORACLE_CONFIG_SEED],
+        bump
+    )]
+    pub oracle_config: Account<'info, OracleConfig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, OracleExample>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ OracleError::InvalidOracleConfig
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Signed by the config authority
+#[derive(Accounts)]
+pub struct UpdateOracleConfig<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ORACLE_CONFIG_SEED],
+        bump = oracle_config.bump,
+        has_one = authority
+    )]
+    pub oracle_config: Account<'info, OracleConfig>,
+}
+
+/// Permissionless once the timelock has passed
+#[derive(Accounts)]
+pub struct ApplyOracleConfig<'info> {
+    #[account(mut, seeds = [ORACLE_CONFIG_SEED], bump = oracle_config.bump)]
+    pub oracle_config: Account<'info, OracleConfig>,
+}
+
+/// A price validated with the program's oracle config
+#[derive(Accounts)]
+pub struct ConfiguredPriceContext<'info> {
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    #[account(seeds = [ORACLE_CONFIG_SEED], bump = oracle_config.bump)]
+    pub oracle_config: Account<'info, OracleConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptOracleConfigAuthority<'info> {
+    pub nominated_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ORACLE_CONFIG_SEED],
+        bump = oracle_config.bump,
+        constraint = oracle_config.nominated_authority == nominated_authority.key()
+            @ OracleError::NotNominatedAuthority
+    )]
+    pub oracle_config: Account<'info, OracleConfig>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+/// Create the config in force immediately: the upgrade authority could
+/// deploy any parameters anyway
+pub fn init_oracle_config(ctx: Context<InitOracleConfig>, config: ConfigChange) -> Result<()> {
+    config.validate()?;
+
+    let oracle_config = &mut ctx.accounts.oracle_config;
+    oracle_config.authority = ctx.accounts.authority.key();
+    oracle_config.nominated_authority = Pubkey::default();
+    oracle_config.params = config.params;
+    oracle_config.overrides = config.overrides.clone();
+    oracle_config.timelock_secs = config.timelock_secs;
+    oracle_config.pending_change = None;
+    oracle_config.bump = ctx.bumps.oracle_config;
+
+    emit!(OracleConfigUpdated {
+        config,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Queue `change`, applicable after the current timelock
+pub fn propose_oracle_config(ctx: Context<UpdateOracleConfig>, change: ConfigChange) -> Result<()> {
+    change.validate()?;
+    let now = Clock::get()?.unix_timestamp;
+
+    let oracle_config = &mut ctx.accounts.oracle_config;
+    require!(oracle_config.pending_change.is_none(), OracleError::ConfigChangePending);
+    let effective_at = now
+        .checked_add(oracle_config.timelock_secs)
+        .ok_or_else(|| error!(OracleError::MathOverflow))?;
+
+    emit!(OracleConfigChangeProposed {
+        authority: oracle_config.authority,
+        change: change.clone(),
+        effective_at,
+    });
+    oracle_config.pending_change = Some(PendingConfigChange {
+        change,
+        proposed_at: now,
+        effective_at,
+    });
+    Ok(())
+}
+
+pub fn apply_oracle_config(ctx: Context<ApplyOracleConfig>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let oracle_config = &mut ctx.accounts.oracle_config;
+    let pending = oracle_config
+        .pending_change
+        .take()
+        .ok_or_else(|| error!(OracleError::NoPendingConfigChange))?;
+    require!(now >= pending.effective_at, OracleError::ConfigChangeNotReady);
+
+    let change = pending.change;
+    oracle_config.params = change.params;
+    oracle_config.overrides = change.overrides.clone();
+    oracle_config.timelock_secs = change.timelock_secs;
+
+    emit!(OracleConfigUpdated {
+        config: change,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+pub fn cancel_oracle_config(ctx: Context<UpdateOracleConfig>) -> Result<()> {
+    let oracle_config = &mut ctx.accounts.oracle_config;
+    let pending = oracle_config
+        .pending_change
+        .take()
+        .ok_or_else(|| error!(OracleError::NoPendingConfigChange))?;
+
+    emit!(OracleConfigChangeCancelled {
+        authority: oracle_config.authority,
+        effective_at: pending.effective_at,
+    });
+    Ok(())
+}
+
+/// Nominate `new_authority`; `Pubkey::default()` withdraws a nomination
+pub fn nominate_oracle_config_authority(ctx: Context<UpdateOracleConfig>, new_authority: Pubkey) -> Result<()> {
+    let oracle_config = &mut ctx.accounts.oracle_config;
+    oracle_config.nominated_authority = new_authority;
+
+    emit!(OracleConfigAuthorityNominated {
+        authority: oracle_config.authority,
+        nominated: new_authority,
+    });
+    Ok(())
+}
+
+pub fn accept_oracle_config_authority(ctx: Context<AcceptOracleConfigAuthority>) -> Result<()> {
+    let oracle_config = &mut ctx.accounts.oracle_config;
+    let previous = oracle_config.authority;
+    oracle_config.authority = oracle_config.nominated_authority;
+    oracle_config.nominated_authority = Pubkey::default();
+
+    emit!(OracleConfigAuthorityChanged {
+        previous,
+        authority: oracle_config.authority,
+    });
+    Ok(())
+}
@@ -0,0 +1,435 @@
+/**
+ * OracleDecimal - Fixed-Point Decimal for Price Arithmetic
+ *
+ * One non-negative fixed-point type for everything the oracle helpers
+ * compute: token amounts, prices, USD values, and ratios. A value is
+ * `mantissa × 10^-scale`, so a Pyth price of 15_000_000_000 with exponent
+ * -8 is `{ mantissa: 15_000_000_000, scale: 8 }` and 2 SOL in lamports is
+ * `{ mantissa: 2_000_000_000, scale: 9 }`.
+ *
+ * Multiplication is exact; division and any scale reduction take an
+ * explicit `Rounding`, so callers decide which way dust goes instead of
+ * inheriting a silent floor. All operations are checked and fail with
+ * `OracleError::MathOverflow` rather than panicking or truncating.
+ *
+ * `isqrt`, `checked_sqrt`, `ln`, `exp_wad`, and `pow_frac` cover what LP
+ * pricing, volatility, and options code need beyond the four operations.
+ * They run a fixed algorithm on integers only, so every validator computes
+ * the same bits. The square roots are exact (then rounded as requested);
+ * `ln` is within 1e-16 and `exp_wad` within 1e-16 relative (or 1e-17
+ * absolute below one). `decimal-math-proptest.rs` checks these bounds
+ * against identities.
+ *
+ * Powers of ten come from `POW10`, a table built at compile time. The
+ * basis-point constructors (`from_bps`, `one_plus_bps`, `one_minus_bps`)
+ * are `const fn`, so fixed fees and thresholds bound to a `const` cost
+ * nothing at run time.
+ *
+ * Setup:
+ * Declared by anchor-oracle.rs as `decimal`; `calculate_usd_value`,
+ * `calculate_tokens_for_usd`, and `calculate_price_ratio` are built on it
+ */
+
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::Price;
+
+use crate::{OraclePrice, OracleError};
+
+// ============================================================================
+// ROUNDING
+// ============================================================================
+
+/// Direction to round when precision is dropped
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum Rounding {
+    /// Toward zero
+    Floor,
+    /// Away from zero
+    Ceil,
+    /// To the nearest value, halves away from zero
+    Nearest,
+}
+
+// ============================================================================
+// CONST TABLES
+// ============================================================================
+
+/// 10^0 through 10^38, every power of ten that fits a u128; built at
+/// compile time, so scaling is one load instead of a `10u128.pow` loop
+pub const POW10: [u128; 39] = {
+    let mut table = [1u128; 39];
+    let mut i = 1;
+    while i < 39 {
+        table[i] = table[i - 1] * 10;
+        i += 1;
+    }
+    table
+};
+
+/// 10^0 through 10^19, every power of ten that fits a u64
+pub const POW10_U64: [u64; 20] = {
+    let mut table = [1u64; 20];
+    let mut i = 1;
+    while i < 20 {
+        table[i] = table[i - 1] * 10;
+        i += 1;
+    }
+    table
+};
+
+/// 10^exp, or `None` past 10^38
+pub const fn pow10(exp: u32) -> Option<u128> {
+    if (exp as usize) < POW10.len() {
+        Some(POW10[exp as usize])
+    } else {
+        None
+    }
+}
+
+/// Decimal places of a basis-point value: 1 bps = 10^-4
+pub const BPS_SCALE: i32 = 4;
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// `numerator / denominator` rounded as requested (`denominator > 0`)
+fn div_rounded(numerator: u128, denominator: u128, rounding: Rounding) -> u128 {
+    // SBF divides u64s natively but u128s in software, so take the native
+    // path whenever both operands fit
+    let (quotient, remainder) = match (u64::try_from(numerator), u64::try_from(denominator)) {
+        (Ok(n), Ok(d)) => ((n / d) as u128, (n % d) as u128),
+        _ => (numerator / denominator, numerator % denominator),
+    };
+    let round_up = match rounding {
+        Rounding::Floor => false,
+        Rounding::Ceil => remainder > 0,
+        // remainder >= denominator / 2, without overflowing
+        Rounding::Nearest => remainder >= denominator - remainder,
+    };
+    quotient + round_up as u128
+}
+
+/// `value / 10^exp` rounded as requested
+fn div_pow10(value: u128, exp: u32, rounding: Rounding) -> u128 {
+    match pow10(exp) {
+        Some(factor) => div_rounded(value, factor, rounding),
+        // The divisor exceeds u128::MAX, so the quotient is below one
+        None if rounding == Rounding::Ceil && value > 0 => 1,
+        None => 0,
+    }
+}
+
+fn mul_pow10(value: u128, exp: u32) -> Result<u128> {
+    if value == 0 {
+        return Ok(0);
+    }
+    pow10(exp)
+        .and_then(|factor| value.checked_mul(factor))
+        .ok_or_else(|| error!(OracleError::MathOverflow))
+}
+
+// ============================================================================
+// DECIMAL
+// ============================================================================
+
+/// Non-negative fixed-point decimal: `mantissa × 10^-scale`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct OracleDecimal {
+    pub mantissa: u128,
+    /// Decimal places; negative for multiples of powers of ten
+    pub scale: i32,
+}
+
+impl OracleDecimal {
+    pub const ZERO: Self = Self {
+        mantissa: 0,
+        scale: 0,
+    };
+
+    pub const fn new(mantissa: u128, scale: i32) -> Self {
+        Self { mantissa, scale }
+    }
+
+    /// Token-native amount, e.g. lamports with 9 decimals
+    pub const fn from_u64(amount: u64, decimals: u8) -> Self {
+        Self::new(amount as u128, decimals as i32)
+    }
+
+    /// `bps / 10_000`, e.g. a 80% LTV as 0.8000
+    pub const fn from_bps(bps: u16) -> Self {
+        Self::new(bps as u128, BPS_SCALE)
+    }
+
+    /// `1 + bps / 10_000`, e.g. a 5% liquidation bonus as 1.0500
+    pub const fn one_plus_bps(bps: u16) -> Self {
+        Self::new(BPS_DENOMINATOR + bps as u128, BPS_SCALE)
+    }
+
+    /// `1 - bps / 10_000`, zero from 10_000 bps up, e.g. a 20% haircut as
+    /// 0.8000
+    pub const fn one_minus_bps(bps: u16) -> Self {
+        Self::new(BPS_DENOMINATOR.saturating_sub(bps as u128), BPS_SCALE)
+    }
+
+    /// Fixed-point value in the oracle convention `value × 10^exponent`
+    pub fn from_fixed_point(value: u128, exponent: i32) -> Result<Self> {
+        let scale = exponent
+            .checked_neg()
+            .ok_or_else(|| error!(OracleError::MathOverflow))?;
+        Ok(Self::new(value, scale))
+    }
+
+    /// Oracle price; negative prices are rejected
+    pub fn from_price(price: i64, exponent: i32) -> Result<Self> {
+        require!(price >= 0, OracleError::NegativePrice);
+        Self::from_fixed_point(price as u128, exponent)
+    }
+
+    pub fn from_pyth_price(price: &Price) -> Result<Self> {
+        Self::from_price(price.price, price.exponent)
+    }
+
+    pub fn from_oracle_price(price: &OraclePrice) -> Result<Self> {
+        Self::from_price(price.price, price.exponent)
+    }
+
+    /// Confidence interval of an oracle price, in the same units
+    pub fn from_oracle_conf(price: &OraclePrice) -> Result<Self> {
+        Self::from_fixed_point(price.conf as u128, price.exponent)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    /// Same value with `scale` decimal places, rounding if precision is lost
+    pub fn rescale(self, scale: i32, rounding: Rounding) -> Result<Self> {
+        let mantissa = if scale >= self.scale {
+            let shift = scale.abs_diff(self.scale);
+            mul_pow10(self.mantissa, shift)?
+        } else {
+            div_pow10(self.mantissa, scale.abs_diff(self.scale), rounding)
+        };
+        Ok(Self::new(mantissa, scale))
+    }
+
+    /// Same value with trailing zeros removed from the mantissa
+    pub fn normalize(self) -> Self {
+        if self.mantissa == 0 {
+            return Self::ZERO;
+        }
+        let mut normalized = self;
+        while normalized.mantissa % 10 == 0 && normalized.scale > i32::MIN {
+            normalized.mantissa /= 10;
+            normalized.scale -= 1;
+        }
+        normalized
+    }
+
+    /// Exact product
+    pub fn checked_mul(self, other: Self) -> Result<Self> {
+        let mantissa = self
+            .mantissa
+            .checked_mul(other.mantissa)
+            .ok_or_else(|| error!(OracleError::MathOverflow))?;
+        let scale = self
+            .scale
+            .checked_add(other.scale)
+            .ok_or_else(|| error!(OracleError::MathOverflow))?;
+        Ok(Self::new(mantissa, scale))
+    }
+
+    /// Quotient with `scale` decimal places
+    pub fn checked_div(self, other: Self, scale: i32, rounding: Rounding) -> Result<Self> {
+        require!(!other.is_zero(), OracleError::MathOverflow);
+
+        // mantissa = self.mantissa × 10^(scale + other.scale - self.scale) / other.mantissa
+        let shift = (scale as i64) + (other.scale as i64) - (self.scale as i64);
+        let shift_abs = u32::try_from(shift.unsigned_abs())
+            .map_err(|_| error!(OracleError::MathOverflow))?;
+        let mantissa = if shift >= 0 {
+            div_rounded(mul_pow10(self.mantissa, shift_abs)?, other.mantissa, rounding)
+        } else {
+            match mul_pow10(other.mantissa, shift_abs) {
+                Ok(denominator) => div_rounded(self.mantissa, denominator, rounding),
+                // The denominator exceeds u128::MAX, so the quotient is below one
+                Err(_) if rounding == Rounding::Ceil && self.mantissa > 0 => 1,
+                Err(_) => 0,
+            }
+        };
+        Ok(Self::new(mantissa, scale))
+    }
+
+    /// Exact sum
+    pub fn checked_add(self, other: Self) -> Result<Self> {
+        let scale = self.scale.max(other.scale);
+        let (a, b) = (self.rescale(scale, Rounding::Floor)?, other.rescale(scale, Rounding::Floor)?);
+        let mantissa = a
+            .mantissa
+            .checked_add(b.mantissa)
+            .ok_or_else(|| error!(OracleError::MathOverflow))?;
+        Ok(Self::new(mantissa, scale))
+    }
+
+    /// Exact difference; fails if `other` is larger
+    pub fn checked_sub(self, other: Self) -> Result<Self> {
+        let scale = self.scale.max(other.scale);
+        let (a, b) = (self.rescale(scale, Rounding::Floor)?, other.rescale(scale, Rounding::Floor)?);
+        let mantissa = a
+            .mantissa
+            .checked_sub(b.mantissa)
+            .ok_or_else(|| error!(OracleError::MathOverflow))?;
+        Ok(Self::new(mantissa, scale))
+    }
+
+    /// Token-native amount with `decimals` decimals
+    pub fn to_u64(self, decimals: u8, rounding: Rounding) -> Result<u64> {
+        let scaled = self.rescale(decimals as i32, rounding)?;
+        u64::try_from(scaled.mantissa).map_err(|_| error!(OracleError::MathOverflow))
+    }
+
+    /// Fixed-point value with the given oracle exponent
+    pub fn to_i64(self, exponent: i32, rounding: Rounding) -> Result<i64> {
+        let scale = exponent
+            .checked_neg()
+            .ok_or_else(|| error!(OracleError::MathOverflow))?;
+        let scaled = self.rescale(scale, rounding)?;
+        i64::try_from(scaled.mantissa).map_err(|_| error!(OracleError::MathOverflow))
+    }
+}
+
+// ============================================================================
+// ROOTS, LOGARITHMS, AND POWERS
+// ============================================================================
+
+/// Decimal places of `ln` and `exp_wad` values
+pub const WAD_SCALE: i32 = 18;
+const WAD: u128 = 1_000_000_000_000_000_000;
+/// ln(2) and ln(10) × 10^18, rounded to nearest
+const LN2_WAD: u128 = 693_147_180_559_945_309;
+const LN10_WAD: u128 = 2_302_585_092_994_045_684;
+
+/// Largest `n` with `n × n <= value`
+pub fn isqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    // Newton's method from a power of two at or above the root; the
+    // iterates decrease until they reach it
+    let mut root = 1u128 << (128 - value.leading_zeros()).div_ceil(2);
+    loop {
+        let next = (root + value / root) / 2;
+        if next >= root {
+            return root;
+        }
+        root = next;
+    }
+}
+
+/// ln(y) × 10^18 for y × 10^-18 in [1, 2), via
+/// ln(y) = 2 atanh((y - 1) / (y + 1))
+fn ln_unit_wad(y: u128) -> u128 {
+    let z = (y - WAD) * WAD / (y + WAD);
+    let z_squared = z * z / WAD;
+    let mut term = z;
+    let mut sum = z;
+    // z < 1/3, so each term shrinks by 9x; about 20 terms
+    let mut denominator = 3;
+    while term > 0 {
+        term = term * z_squared / WAD;
+        sum += term / denominator;
+        denominator += 2;
+    }
+    2 * sum
+}
+
+/// e^(x × 10^-18) × 10^18
+pub fn exp_wad(x: i128) -> Result<u128> {
+    // x = k ln(2) + r with r in [0, ln(2)), so e^x = 2^k e^r
+    let k = x.div_euclid(LN2_WAD as i128);
+    let r = x.rem_euclid(LN2_WAD as i128) as u128;
+
+    let mut term = WAD;
+    let mut sum = WAD;
+    let mut n = 1;
+    while term > 0 {
+        term = term * r / WAD / n;
+        sum += term;
+        n += 1;
+    }
+
+    if k >= 0 {
+        match u32::try_from(k) {
+            Ok(shift) if shift < sum.leading_zeros() => Ok(sum << shift),
+            _ => err!(OracleError::MathOverflow),
+        }
+    } else if k <= -128 {
+        Ok(0)
+    } else {
+        Ok(sum >> k.unsigned_abs())
+    }
+}
+
+impl OracleDecimal {
+    /// Square root with `scale` decimal places. Fails with `MathOverflow`
+    /// once the result mantissa reaches 2^64 (the radicand no longer fits
+    /// a u128).
+    pub fn checked_sqrt(self, scale: i32, rounding: Rounding) -> Result<Self> {
+        // root mantissa = sqrt(mantissa × 10^(2 scale - self.scale))
+        let shift = 2 * (scale as i64) - (self.scale as i64);
+        let shift_abs = u32::try_from(shift.unsigned_abs())
+            .map_err(|_| error!(OracleError::MathOverflow))?;
+        let (radicand, exact) = if shift >= 0 {
+            (mul_pow10(self.mantissa, shift_abs)?, true)
+        } else {
+            let floor = div_pow10(self.mantissa, shift_abs, Rounding::Floor);
+            (floor, div_pow10(self.mantissa, shift_abs, Rounding::Ceil) == floor)
+        };
+
+        let root = isqrt(radicand);
+        let remainder = radicand - root * root;
+        let round_up = match rounding {
+            Rounding::Floor => false,
+            Rounding::Ceil => remainder > 0 || !exact,
+            // (root + 1/2)^2 = root^2 + root + 1/4
+            Rounding::Nearest => remainder > root,
+        };
+        Ok(Self::new(root + round_up as u128, scale))
+    }
+
+    /// Natural logarithm × 10^18; fails on zero
+    pub fn ln(self) -> Result<i128> {
+        require!(!self.is_zero(), OracleError::MathOverflow);
+        // 1.00 becomes exactly 1, so ln(1) is exactly zero
+        let normalized = self.normalize();
+
+        // mantissa = 2^bits × y with y in [1, 2)
+        let bits = 127 - normalized.mantissa.leading_zeros();
+        let y = if bits > 64 {
+            ((normalized.mantissa >> (bits - 64)) * WAD) >> 64
+        } else {
+            (normalized.mantissa * WAD) >> bits
+        };
+        let ln_mantissa = bits as i128 * LN2_WAD as i128 + ln_unit_wad(y) as i128;
+        Ok(ln_mantissa - normalized.scale as i128 * LN10_WAD as i128)
+    }
+
+    /// `self^(numerator / denominator)` with `scale` decimal places (at most
+    /// 18), rounded down, as e^(ln(self) × numerator / denominator).
+    /// Accurate to about 1e-16 relative, times |numerator / denominator|.
+    pub fn pow_frac(self, numerator: i64, denominator: u64, scale: i32) -> Result<Self> {
+        require!(denominator > 0 && scale <= WAD_SCALE, OracleError::MathOverflow);
+        if numerator == 0 {
+            return Self::new(1, 0).rescale(scale, Rounding::Floor);
+        }
+        if self.is_zero() {
+            require!(numerator > 0, OracleError::MathOverflow);
+            return Ok(Self::new(0, scale));
+        }
+
+        let exponent = self
+            .ln()?
+            .checked_mul(numerator as i128)
+            .ok_or_else(|| error!(OracleError::MathOverflow))?
+            / denominator as i128;
+        Self::new(exp_wad(exponent)?, WAD_SCALE).rescale(scale, Rounding::Floor)
+    }
+}
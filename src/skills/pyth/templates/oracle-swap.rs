@@ -0,0 +1,360 @@
+/**
+ * Oracle-Priced Swap
+ *
+ * A complete swap against a two-token pool priced by Pyth instead of a
+ * curve. The pool holds one vault per mint, each a token account PDA at
+ * [SWAP_VAULT_SEED, pool, mint] owned by the pool PDA at
+ * [SWAP_POOL_SEED, mint_a, mint_b]; it trades in both directions.
+ *
+ * `swap_with_oracle`:
+ * 1. Fails if either market has `SWAPS` paused (guardian.rs), then reads
+ *    both prices as one `PricedPair` with `Market::guarded_pair`
+ *    (market.rs): each through the `Market` of its mint, with the
+ *    market's feed, validation limits, and breaker, then published close
+ *    enough together and with little enough combined confidence for the
+ *    tighter of the two markets' `pair` limits
+ * 2. Values the input at the lower confidence bound and the output at the
+ *    upper one, so the confidence interval is the spread
+ * 3. Deducts the confidence-scaled fee of the output market, which stays
+ *    in the output vault
+ * 4. Checks slippage and the output vault's balance
+ * 5. Moves the input from the user to its vault and the output from its
+ *    vault to the user with token-utils' `transfer_checked`, the second
+ *    signed by the pool PDA
+ *
+ * Amounts use each mint's decimals; every conversion rounds down. Mints
+ * with a transfer fee extension are not supported: the vault would receive
+ * less than `amount_in`.
+ *
+ * Steps 2 and 3 are `quote_swap`, a pure function of the two prices and
+ * the fee, so clients compute the output the program will pay from the
+ * same code (swap-quote.rs builds `min_amount_out` on it).
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as `oracle_swap`);
+ *    requires market.rs and price-events.rs, and:
+ *    anchor-spl = "0.30.1"
+ *    token-utils = { path = "../token-utils" }   # tokens/templates/token-utils.rs
+ * 2. Create the market of both mints with `init_market`, the pool with
+ *    `init_swap_pool`, and fund the vaults with plain token transfers
+ */
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use token_utils::{transfer_checked, transfer_checked_signed};
+
+use crate::{
+    calculate_tokens_for_usd, calculate_usd_value, deduct_fee, dynamic_fee_bps, post_update, DynamicFeeConfig,
+    Market, OperationKind, OracleError, PauseFlags, Rounding, ValidatedPrice, MARKET_SEED, PYTH_RECEIVER_PROGRAM_ID,
+    USD_DECIMALS,
+};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for swap pools: [SWAP_POOL_SEED, mint_a, mint_b]
+pub const SWAP_POOL_SEED: &[u8] = b"swap_pool";
+
+/// PDA seed for pool vaults: [SWAP_VAULT_SEED, pool, mint]
+pub const SWAP_VAULT_SEED: &[u8] = b"swap_vault";
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// A pool trading `mint_a` against `mint_b` at the oracle price
+#[account]
+pub struct SwapPool {
+    pub authority: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub bump: u8,
+}
+
+impl SwapPool {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 1;
+
+    /// Whether the pool trades `input` for `output`, in either direction
+    pub fn trades(&self, input: &Pubkey, output: &Pubkey) -> bool {
+        (self.mint_a, self.mint_b) == (*input, *output) || (self.mint_b, self.mint_a) == (*input, *output)
+    }
+}
+
+// ============================================================================
+// QUOTES
+// ============================================================================
+
+/// What `swap_with_oracle` pays for an input at two prices
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    /// Input valued at its lower bound, with `USD_DECIMALS` decimals
+    pub input_usd: u64,
+    /// Output bought at its upper bound, before the fee
+    pub gross_out: u64,
+    /// Wider confidence of the two prices
+    pub conf_bps: u16,
+    pub fee_bps: u16,
+    pub fee: u64,
+    /// Paid to the user; `swap_with_oracle` requires it to be at least
+    /// `min_amount_out`
+    pub amount_out: u64,
+}
+
+/// Output for `amount_in` of the input token, exactly as `swap_with_oracle`
+/// computes it; `fee` is the output market's `swap_fee`
+pub fn quote_swap(
+    amount_in: u64,
+    input_price: &ValidatedPrice,
+    input_decimals: u8,
+    output_price: &ValidatedPrice,
+    output_decimals: u8,
+    fee: &DynamicFeeConfig,
+) -> Result<SwapQuote> {
+    // Selling the input at its lower bound, buying the output at its upper
+    let input_usd = calculate_usd_value(
+        amount_in,
+        input_decimals,
+        input_price.sell_price(),
+        input_price.exponent,
+        Rounding::Floor, // value credited to the user
+    )?;
+    let gross_out = calculate_tokens_for_usd(
+        input_usd,
+        USD_DECIMALS,
+        output_decimals,
+        output_price.buy_price(),
+        output_price.exponent,
+        Rounding::Floor, // tokens paid out to the user
+    )?;
+
+    // Charge for the wider of the two confidence intervals
+    let conf_bps = input_price.relative_confidence()?.max(output_price.relative_confidence()?);
+    let fee_bps = dynamic_fee_bps(conf_bps, fee);
+    let (amount_out, fee) = deduct_fee(gross_out, fee_bps)?;
+
+    Ok(SwapQuote {
+        input_usd,
+        gross_out,
+        conf_bps,
+        fee_bps,
+        fee,
+        amount_out,
+    })
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitSwapPool<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(constraint = mint_b.key() != mint_a.key() @ OracleError::InvalidSwapPool)]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = SwapPool::LEN,
+        seeds = [SWAP_POOL_SEED, mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, SwapPool>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [SWAP_VAULT_SEED, pool.key().as_ref(), mint_a.key().as_ref()],
+        bump,
+        token::mint = mint_a,
+        token::authority = pool,
+        token::token_program = token_program
+    )]
+    pub vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [SWAP_VAULT_SEED, pool.key().as_ref(), mint_b.key().as_ref()],
+        bump,
+        token::mint = mint_b,
+        token::authority = pool,
+        token::token_program = token_program
+    )]
+    pub vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a swap with price validation
+#[derive(Accounts)]
+pub struct SwapWithOracle<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [SWAP_POOL_SEED, pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+        bump = pool.bump,
+        constraint = pool.trades(&input_mint.key(), &output_mint.key()) @ OracleError::InvalidSwapPool
+    )]
+    pub pool: Account<'info, SwapPool>,
+
+    pub input_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub output_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [SWAP_VAULT_SEED, pool.key().as_ref(), input_mint.key().as_ref()],
+        bump
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [SWAP_VAULT_SEED, pool.key().as_ref(), output_mint.key().as_ref()],
+        bump
+    )]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = input_mint,
+        token::authority = user,
+        token::token_program = token_program
+    )]
+    pub user_input: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Any account of the output mint; the user may pay out to another wallet
+    #[account(mut, token::mint = output_mint, token::token_program = token_program)]
+    pub user_output: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Price update for the input token (writable so it can be closed)
+    #[account(mut)]
+    pub input_price: Account<'info, PriceUpdateV2>,
+
+    /// Price update for the output token (writable so it can be closed)
+    #[account(mut)]
+    pub output_price: Account<'info, PriceUpdateV2>,
+
+    #[account(mut, seeds = [MARKET_SEED, input_mint.key().as_ref()], bump = input_market.bump)]
+    pub input_market: Account<'info, Market>,
+
+    /// Market of the output mint; its `swap_fee` is charged
+    #[account(mut, seeds = [MARKET_SEED, output_mint.key().as_ref()], bump = output_market.bump)]
+    pub output_market: Account<'info, Market>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: Pyth receiver; pass it to close price updates the user posted
+    /// and refund their rent in the same transaction
+    #[account(address = PYTH_RECEIVER_PROGRAM_ID)]
+    pub pyth_receiver: Option<UncheckedAccount<'info>>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn init_swap_pool(ctx: Context<InitSwapPool>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    pool.authority = ctx.accounts.authority.key();
+    pool.mint_a = ctx.accounts.mint_a.key();
+    pool.mint_b = ctx.accounts.mint_b.key();
+    pool.bump = ctx.bumps.pool;
+    Ok(())
+}
+
+pub fn swap_with_oracle(ctx: Context<SwapWithOracle>, amount_in: u64, min_amount_out: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let accounts = &mut ctx.accounts;
+    let user = accounts.user.key();
+    accounts.input_market.require_allowed(PauseFlags::SWAPS)?;
+    accounts.output_market.require_allowed(PauseFlags::SWAPS)?;
+
+    // Each price must be its market's feed, within that market's limits,
+    // and the two within the pair limits of both markets
+    let pair = accounts.input_market.guarded_pair(
+        &accounts.input_price,
+        &mut accounts.output_market,
+        &accounts.output_price,
+        &clock,
+        user,
+        OperationKind::RiskIncreasing,
+    )?;
+    let Some(pair) = pair else {
+        return Ok(());
+    };
+
+    let accounts = &ctx.accounts;
+    let quote = quote_swap(
+        amount_in,
+        &pair.input,
+        accounts.input_mint.decimals,
+        &pair.output,
+        accounts.output_mint.decimals,
+        &accounts.output_market.swap_fee,
+    )?;
+    let amount_out = quote.amount_out;
+
+    msg!("Input value (USD): {}", quote.input_usd);
+    msg!("Fee: {} bps ({} at {} bps confidence)", quote.fee_bps, quote.fee, quote.conf_bps);
+    msg!("Output: {}", amount_out);
+
+    require!(
+        amount_out > 0 && amount_out >= min_amount_out,
+        OracleError::SlippageExceeded
+    );
+    require!(
+        amount_out <= accounts.output_vault.amount,
+        OracleError::InsufficientLiquidity
+    );
+
+    // Input leg: user to pool
+    transfer_checked(
+        &accounts.token_program,
+        &accounts.user_input,
+        &accounts.input_mint,
+        &accounts.input_vault,
+        &accounts.user.to_account_info(),
+        amount_in,
+    )?;
+
+    // Output leg: pool to user, signed by the pool PDA
+    let pool = &accounts.pool;
+    let pool_seeds: &[&[u8]] = &[SWAP_POOL_SEED, pool.mint_a.as_ref(), pool.mint_b.as_ref(), &[pool.bump]];
+    transfer_checked_signed(
+        &accounts.token_program,
+        &accounts.output_vault,
+        &accounts.output_mint,
+        &accounts.user_output,
+        &pool.to_account_info(),
+        amount_out,
+        &[pool_seeds],
+    )?;
+
+    // Refund the rent of price updates the user posted for this swap
+    if let Some(pyth_receiver) = &accounts.pyth_receiver {
+        let same_account = accounts.input_price.key() == accounts.output_price.key();
+        let consumed = if same_account {
+            vec![&accounts.input_price]
+        } else {
+            vec![&accounts.input_price, &accounts.output_price]
+        };
+        for price_update in consumed {
+            post_update::reclaim_rent_if_owned(
+                pyth_receiver.to_account_info(),
+                accounts.user.to_account_info(),
+                price_update,
+            )?;
+        }
+    }
+
+    Ok(())
+}
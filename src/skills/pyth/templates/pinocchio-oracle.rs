@@ -0,0 +1,279 @@
+/**
+ * Pyth Oracle Template for Pinocchio Programs
+ *
+ * The cheapest way to consume a Pyth price: no Anchor, no Borsh, and no
+ * heap. `load_validated_price` checks the owner, discriminator, feed ID,
+ * verification level, staleness, and confidence of a `PriceUpdateV2` by
+ * slicing the raw account bytes at fixed offsets, and returns a `Copy`
+ * `ValidatedPrice`. The entrypoint installs `no_allocator!`, so any
+ * allocation that creeps in fails loudly instead of costing compute units.
+ *
+ * The offsets mirror the receiver SDK's Borsh layout, including the
+ * one-byte (Full) or two-byte (Partial) `verification_level` that shifts
+ * every later field. `examples/testing/pinocchio-offsets.rs` serializes
+ * SDK structs and checks every field read here against them; rerun it when
+ * bumping the SDK.
+ *
+ * Error codes match `OracleError` in anchor-oracle.rs (9000 + variant
+ * index), so clients decode them with the same table.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml (the tests import the crate as
+ *    `pyth_oracle_pinocchio`):
+ *    [package]
+ *    name = "pyth-oracle-pinocchio"
+ *
+ *    [dependencies]
+ *    pinocchio = "0.8"
+ *    pinocchio-pubkey = "0.2"
+ *
+ *    [features]
+ *    no-entrypoint = []
+ *
+ *    [lib]
+ *    crate-type = ["cdylib", "lib"]
+ *
+ * 2. Copy this file as the crate's `src/lib.rs`, or declare it as a module
+ *    and call `load_validated_price` from your own instructions
+ * 3. Add `pinocchio-log` if you need to log prices; `msg!` with format
+ *    arguments allocates
+ */
+
+use pinocchio::account_info::AccountInfo;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+pinocchio_pubkey::declare_id!("YourProgramId11111111111111111111111111111111");
+
+/// Pyth Receiver Program ID (mainnet/devnet)
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = pinocchio_pubkey::pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Anchor discriminator of `PriceUpdateV2` (sha256("account:PriceUpdateV2")[..8])
+pub const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+/// First `OracleError` code, `ORACLE_ERROR_OFFSET` in anchor-oracle.rs
+pub const ERROR_CODE_OFFSET: u32 = 9000;
+
+/// First `PriceSourceError` code, as in price-source.rs
+pub const PRICE_SOURCE_ERROR_OFFSET: u32 = 9900;
+
+// ============================================================================
+// LAYOUT
+// ============================================================================
+
+/// Offsets into `PriceUpdateV2` account data
+pub mod layout {
+    /// Discriminator, then `write_authority`
+    pub const VERIFICATION_LEVEL: usize = 8 + 32;
+
+    /// Borsh tags of `VerificationLevel`
+    pub const PARTIAL_TAG: u8 = 0;
+    pub const FULL_TAG: u8 = 1;
+
+    /// Start of `price_message` for each verification level
+    pub const MESSAGE_PARTIAL: usize = VERIFICATION_LEVEL + 2;
+    pub const MESSAGE_FULL: usize = VERIFICATION_LEVEL + 1;
+
+    /// `PriceFeedMessage` fields, relative to its start
+    pub const FEED_ID: usize = 0;
+    pub const PRICE: usize = 32;
+    pub const CONF: usize = 40;
+    pub const EXPONENT: usize = 48;
+    pub const PUBLISH_TIME: usize = 52;
+    pub const PREV_PUBLISH_TIME: usize = 60;
+    pub const EMA_PRICE: usize = 68;
+    pub const EMA_CONF: usize = 76;
+    /// `posted_slot` follows the message
+    pub const POSTED_SLOT: usize = 84;
+
+    /// Smallest valid account: a Full update
+    pub const MIN_LEN: usize = MESSAGE_FULL + POSTED_SLOT + 8;
+}
+
+// ============================================================================
+// ERROR CODES
+// ============================================================================
+
+/// The `OracleError` variants this template raises, at their anchor-oracle.rs
+/// indices
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum OracleError {
+    FeedIdMismatch = 1,
+    PriceTooStale = 2,
+    ConfidenceTooHigh = 3,
+    ZeroPrice = 4,
+    InvalidPriceUpdateAccount = 13,
+}
+
+impl From<OracleError> for ProgramError {
+    fn from(error: OracleError) -> Self {
+        ProgramError::Custom(ERROR_CODE_OFFSET + error as u32)
+    }
+}
+
+/// `PriceSourceError::InsufficientVerification` in price-source.rs
+pub const INSUFFICIENT_VERIFICATION: ProgramError = ProgramError::Custom(PRICE_SOURCE_ERROR_OFFSET);
+
+// ============================================================================
+// VALIDATION
+// ============================================================================
+
+/// What a price must satisfy
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceCheck {
+    pub feed_id: [u8; 32],
+    /// Maximum age of price in seconds
+    pub max_age_secs: u64,
+    /// Maximum confidence in basis points
+    pub max_confidence_bps: u64,
+    /// Guardian signatures a partially verified update needs; 0 requires
+    /// full verification
+    pub min_signatures: u8,
+}
+
+impl PriceCheck {
+    /// Instruction data length of `from_bytes`
+    pub const LEN: usize = 32 + 8 + 8 + 1;
+
+    /// `feed_id | max_age_secs (u64 LE) | max_confidence_bps (u64 LE) | min_signatures`
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(Self {
+            feed_id: data[..32].try_into().unwrap(),
+            max_age_secs: u64::from_le_bytes(data[32..40].try_into().unwrap()),
+            max_confidence_bps: u64::from_le_bytes(data[40..48].try_into().unwrap()),
+            min_signatures: data[48],
+        })
+    }
+}
+
+/// Validated price with bounds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidatedPrice {
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    /// Lower bound (price - conf)
+    pub lower_bound: i64,
+    /// Upper bound (price + conf)
+    pub upper_bound: i64,
+}
+
+fn read_i64(data: &[u8], offset: usize) -> i64 {
+    i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+/// Start of `price_message`, after checking the verification level
+/// (`data` is at least `layout::MIN_LEN` long)
+fn message_offset(data: &[u8], min_signatures: u8) -> Result<usize, ProgramError> {
+    match data[layout::VERIFICATION_LEVEL] {
+        layout::FULL_TAG => Ok(layout::MESSAGE_FULL),
+        layout::PARTIAL_TAG if min_signatures == 0 => Err(INSUFFICIENT_VERIFICATION),
+        layout::PARTIAL_TAG if data[layout::VERIFICATION_LEVEL + 1] < min_signatures => {
+            Err(INSUFFICIENT_VERIFICATION)
+        }
+        layout::PARTIAL_TAG => Ok(layout::MESSAGE_PARTIAL),
+        _ => Err(OracleError::InvalidPriceUpdateAccount.into()),
+    }
+}
+
+/// Check the bytes of a `PriceUpdateV2` account against `check` at unix
+/// time `now`. The caller checks the owner.
+pub fn validate_price_update(data: &[u8], check: &PriceCheck, now: i64) -> Result<ValidatedPrice, ProgramError> {
+    if data.len() < layout::MIN_LEN || data[..8] != PRICE_UPDATE_V2_DISCRIMINATOR {
+        return Err(OracleError::InvalidPriceUpdateAccount.into());
+    }
+
+    let message = message_offset(data, check.min_signatures)?;
+    // A Partial update is one byte longer than a Full one
+    if data.len() < message + layout::POSTED_SLOT + 8 {
+        return Err(OracleError::InvalidPriceUpdateAccount.into());
+    }
+
+    if data[message + layout::FEED_ID..message + layout::FEED_ID + 32] != check.feed_id {
+        return Err(OracleError::FeedIdMismatch.into());
+    }
+
+    let publish_time = read_i64(data, message + layout::PUBLISH_TIME);
+    if publish_time.saturating_add(check.max_age_secs as i64) < now {
+        return Err(OracleError::PriceTooStale.into());
+    }
+
+    let price = read_i64(data, message + layout::PRICE);
+    let conf = read_u64(data, message + layout::CONF);
+    if price == 0 {
+        return Err(OracleError::ZeroPrice.into());
+    }
+    // ceil(conf_bps) <= max_confidence_bps, cross-multiplied to avoid a
+    // u128 division
+    if conf as u128 * 10_000 > check.max_confidence_bps as u128 * price.unsigned_abs() as u128 {
+        return Err(OracleError::ConfidenceTooHigh.into());
+    }
+
+    let exponent = i32::from_le_bytes(
+        data[message + layout::EXPONENT..message + layout::EXPONENT + 4]
+            .try_into()
+            .unwrap(),
+    );
+    Ok(ValidatedPrice {
+        price,
+        conf,
+        exponent,
+        publish_time,
+        lower_bound: price.saturating_sub(conf as i64),
+        upper_bound: price.saturating_add(conf as i64),
+    })
+}
+
+/// Check the owner, then `validate_price_update` at the current clock
+pub fn load_validated_price(account: &AccountInfo, check: &PriceCheck) -> Result<ValidatedPrice, ProgramError> {
+    if !account.is_owned_by(&PYTH_RECEIVER_PROGRAM_ID) {
+        return Err(OracleError::InvalidPriceUpdateAccount.into());
+    }
+    let now = Clock::get()?.unix_timestamp;
+    let data = account.try_borrow_data()?;
+    validate_price_update(&data, check, now)
+}
+
+// ============================================================================
+// EXAMPLE PROGRAM
+// ============================================================================
+
+#[cfg(not(feature = "no-entrypoint"))]
+mod entrypoint {
+    use super::*;
+    use pinocchio::ProgramResult;
+
+    pinocchio::program_entrypoint!(process_instruction);
+    pinocchio::no_allocator!();
+    pinocchio::default_panic_handler!();
+
+    /// Validate a price. Accounts: `[] price_update`; data: `PriceCheck`
+    pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+        if *program_id != ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let [price_update, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        let check = PriceCheck::from_bytes(data)?;
+
+        let _price = load_validated_price(price_update, &check)?;
+        // Use the price here
+        Ok(())
+    }
+}
@@ -0,0 +1,280 @@
+/**
+ * Merkle Snapshots of Position Health
+ *
+ * A UI that wants to show one position's health with a proof would
+ * otherwise have to scan every position account. Instead, the indexer
+ * hashes each position's health data into a leaf, builds a Merkle tree off
+ * chain, and a crank commits the root to a `PositionSnapshot` PDA. A light
+ * client then needs only the root (one small account) and a proof from the
+ * indexer to check a position's state as of the snapshot's slot.
+ *
+ * The snapshot proves what the indexer saw at `source_slot`, not what the
+ * chain holds now: verify the root is recent enough for the UI, and never
+ * use a snapshot where the live account is available (liquidations read
+ * the position itself).
+ *
+ * Tree: leaves are `sha256(0x00 || borsh(PositionLeaf))` in any order;
+ * internal nodes are `sha256(0x01 || min(a, b) || max(a, b))`, so proofs
+ * need no left/right flags. The prefixes keep a leaf from being passed off
+ * as a node. An odd node is carried up unchanged.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as
+ *    `position_snapshot`)
+ * 2. Create the snapshot with `init_position_snapshot`; its authority is
+ *    the crank key
+ * 3. In the indexer, build leaves with `PositionLeaf::from_position`, then
+ *    `merkle_root` to commit and `merkle_proof` to serve proofs
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::{OracleError, Position};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed: [POSITION_SNAPSHOT_SEED, authority]
+pub const POSITION_SNAPSHOT_SEED: &[u8] = b"position_snapshot";
+
+/// Longest proof `verify_position_proof` accepts (2^32 positions)
+pub const MAX_PROOF_DEPTH: usize = 32;
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// Latest committed Merkle root of position health
+#[account]
+pub struct PositionSnapshot {
+    /// Crank allowed to commit roots
+    pub authority: Pubkey,
+    pub root: [u8; 32],
+    pub leaf_count: u64,
+    /// Slot the indexer read the positions at
+    pub source_slot: u64,
+    pub committed_at: i64,
+    /// Number of roots committed so far
+    pub sequence: u64,
+    pub bump: u8,
+}
+
+impl PositionSnapshot {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 1;
+}
+
+/// The health data of one position, as hashed into the tree
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct PositionLeaf {
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub collateral_amount: u64,
+    pub collateral_usd_value: u64,
+    pub debt_mint: Pubkey,
+    pub debt_amount: u64,
+    pub debt_usd_value: u64,
+    pub health_factor: u64,
+    pub last_price_update: i64,
+}
+
+impl PositionLeaf {
+    pub fn from_position(position_key: Pubkey, position: &Position) -> Self {
+        Self {
+            position: position_key,
+            owner: position.owner,
+            collateral_mint: position.collateral_mint,
+            collateral_amount: position.collateral_amount,
+            collateral_usd_value: position.usd_value,
+            debt_mint: position.debt_mint,
+            debt_amount: position.debt_amount,
+            debt_usd_value: position.debt_usd_value,
+            health_factor: position.health_factor,
+            last_price_update: position.last_price_update,
+        }
+    }
+
+    pub fn hash(&self) -> [u8; 32] {
+        let mut bytes = Vec::with_capacity(4 * 32 + 6 * 8);
+        self.serialize(&mut bytes).expect("writing to a Vec cannot fail");
+        hashv(&[LEAF_PREFIX, &bytes]).to_bytes()
+    }
+}
+
+// ============================================================================
+// MERKLE TREE
+// ============================================================================
+
+fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, low, high]).to_bytes()
+}
+
+/// Whether `leaf` is in the tree with `root`
+pub fn verify_position_proof(root: &[u8; 32], leaf: &PositionLeaf, proof: &[[u8; 32]]) -> bool {
+    proof.len() <= MAX_PROOF_DEPTH && proof.iter().fold(leaf.hash(), |node, sibling| hash_pair(&node, sibling)) == *root
+}
+
+/// Every level of the tree over `leaf_hashes`, leaves first
+fn merkle_levels(leaf_hashes: &[[u8; 32]]) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![leaf_hashes.to_vec()];
+    while levels.last().unwrap().len() > 1 {
+        let next = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => hash_pair(a, b),
+                [odd] => *odd,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// Root over `leaves` (off-chain); all zeros for no leaves
+pub fn merkle_root(leaves: &[PositionLeaf]) -> [u8; 32] {
+    let hashes: Vec<_> = leaves.iter().map(PositionLeaf::hash).collect();
+    merkle_levels(&hashes).last().unwrap().first().copied().unwrap_or([0; 32])
+}
+
+/// Proof for `leaves[index]` (off-chain)
+pub fn merkle_proof(leaves: &[PositionLeaf], index: usize) -> Option<Vec<[u8; 32]>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let hashes: Vec<_> = leaves.iter().map(PositionLeaf::hash).collect();
+    let levels = merkle_levels(&hashes);
+
+    let mut proof = Vec::new();
+    let mut index = index;
+    for level in &levels[..levels.len() - 1] {
+        // An odd node has no sibling and is carried up as is
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        index /= 2;
+    }
+    Some(proof)
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct PositionSnapshotCommitted {
+    pub snapshot: Pubkey,
+    pub root: [u8; 32],
+    pub leaf_count: u64,
+    pub source_slot: u64,
+    pub sequence: u64,
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitPositionSnapshot<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = PositionSnapshot::LEN,
+        seeds = [POSITION_SNAPSHOT_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub position_snapshot: Account<'info, PositionSnapshot>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CommitPositionSnapshot<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POSITION_SNAPSHOT_SEED, authority.key().as_ref()],
+        bump = position_snapshot.bump,
+        has_one = authority
+    )]
+    pub position_snapshot: Account<'info, PositionSnapshot>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyPositionSnapshot<'info> {
+    pub position_snapshot: Account<'info, PositionSnapshot>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn init_position_snapshot(ctx: Context<InitPositionSnapshot>) -> Result<()> {
+    let snapshot = &mut ctx.accounts.position_snapshot;
+    snapshot.authority = ctx.accounts.authority.key();
+    snapshot.root = [0; 32];
+    snapshot.leaf_count = 0;
+    snapshot.source_slot = 0;
+    snapshot.committed_at = 0;
+    snapshot.sequence = 0;
+    snapshot.bump = ctx.bumps.position_snapshot;
+    Ok(())
+}
+
+/// Commit the root the indexer computed from positions at `source_slot`
+pub fn commit_position_snapshot(
+    ctx: Context<CommitPositionSnapshot>,
+    root: [u8; 32],
+    leaf_count: u64,
+    source_slot: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let snapshot = &mut ctx.accounts.position_snapshot;
+    require!(
+        source_slot > snapshot.source_slot && source_slot <= clock.slot,
+        OracleError::StaleSnapshot
+    );
+
+    snapshot.root = root;
+    snapshot.leaf_count = leaf_count;
+    snapshot.source_slot = source_slot;
+    snapshot.committed_at = clock.unix_timestamp;
+    snapshot.sequence += 1;
+
+    emit!(PositionSnapshotCommitted {
+        snapshot: snapshot.key(),
+        root,
+        leaf_count,
+        source_slot,
+        sequence: snapshot.sequence,
+    });
+    Ok(())
+}
+
+/// Fail unless `leaf` is in the committed snapshot; for clients that
+/// verify by simulating a transaction
+pub fn verify_position_snapshot(
+    ctx: Context<VerifyPositionSnapshot>,
+    leaf: PositionLeaf,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let snapshot = &ctx.accounts.position_snapshot;
+    require!(
+        verify_position_proof(&snapshot.root, &leaf, &proof),
+        OracleError::InvalidPositionProof
+    );
+    msg!("Position {} health factor {} at slot {}", leaf.position, leaf.health_factor, snapshot.source_slot);
+    Ok(())
+}
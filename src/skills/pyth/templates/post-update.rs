@@ -0,0 +1,456 @@
+/**
+ * Post and Consume Pyth Price Updates in One Transaction
+ *
+ * CPI wrappers around the Pyth receiver's `post_update_atomic` /
+ * `post_update` instructions, plus off-chain instruction builders that turn
+ * Hermes update data into receiver instructions. Together they let a
+ * program write a fresh `PriceUpdateV2` account and read it in the same
+ * transaction instead of relying on a separately posted account.
+ *
+ * Write-account lifecycle:
+ * 1. The client generates an ephemeral keypair per price update; it signs
+ *    the transaction and becomes the `PriceUpdateV2` account
+ * 2. The receiver creates and writes the account (payer funds the rent)
+ * 3. The consuming instruction reads it via `load_posted_price_update`
+ * 4. The write authority closes it later with `reclaim_rent` to recover SOL
+ *
+ * `post_update_atomic` carries the VAA inline, so it only fits in a
+ * transaction with a trimmed signature set (partial verification). Use
+ * `post_update` with a pre-verified encoded VAA account when full
+ * verification is required.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    pyth-solana-receiver-sdk = "0.3.0"
+ *    pythnet-sdk = "2"
+ *    solana-sdk = { version = "1.18", optional = true }
+ *    solana-client = { version = "1.18", optional = true }
+ *    futures = { version = "0.3", optional = true }
+ *    thiserror = "1"
+ *
+ *    [features]
+ *    client = ["dep:solana-sdk", "dep:solana-client", "dep:futures"]
+ *
+ * 2. Declared by anchor-oracle.rs; the instruction builders are behind the
+ *    `client` feature
+ */
+
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use pyth_solana_receiver_sdk::{PostUpdateAtomicParams, PostUpdateParams};
+
+use crate::{OracleError, PYTH_RECEIVER_PROGRAM_ID};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Wormhole receiver program that owns the guardian set accounts used by
+/// the Pyth receiver
+pub const WORMHOLE_RECEIVER_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("HDwcJBJXjL9FpJ7UBsYBtaDjsBUhuLCUYoz3zr8SWWaQ");
+
+/// Default receiver treasury
+pub const DEFAULT_TREASURY_ID: u8 = 0;
+
+/// Guardian signatures kept in an inline VAA; matches the lenient
+/// validation preset and fits a single transaction
+pub const DEFAULT_ATOMIC_SIGNATURES: u8 = crate::LENIENT_MIN_SIGNATURES;
+
+// ============================================================================
+// CPI
+// ============================================================================
+
+/// Accounts for posting an update with the VAA inline
+pub struct PostUpdateAtomicAccounts<'info> {
+    pub pyth_receiver: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub guardian_set: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    pub treasury: AccountInfo<'info>,
+    /// Ephemeral keypair account (must sign)
+    pub price_update_account: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    /// May update or close the account later
+    pub write_authority: AccountInfo<'info>,
+}
+
+/// Accounts for posting an update against a verified encoded VAA account
+pub struct PostUpdateAccounts<'info> {
+    pub pyth_receiver: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub encoded_vaa: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    pub treasury: AccountInfo<'info>,
+    /// Ephemeral keypair account (must sign)
+    pub price_update_account: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub write_authority: AccountInfo<'info>,
+}
+
+/// CPI into `post_update_atomic`
+pub fn post_update_atomic<'info>(
+    accounts: PostUpdateAtomicAccounts<'info>,
+    params: PostUpdateAtomicParams,
+) -> Result<()> {
+    require_keys_eq!(
+        accounts.pyth_receiver.key(),
+        PYTH_RECEIVER_PROGRAM_ID,
+        OracleError::InvalidPriceUpdateAccount
+    );
+
+    let cpi_accounts = pyth_solana_receiver_sdk::cpi::accounts::PostUpdateAtomic {
+        payer: accounts.payer,
+        guardian_set: accounts.guardian_set,
+        config: accounts.config,
+        treasury: accounts.treasury,
+        price_update_account: accounts.price_update_account,
+        system_program: accounts.system_program,
+        write_authority: accounts.write_authority,
+    };
+    pyth_solana_receiver_sdk::cpi::post_update_atomic(
+        CpiContext::new(accounts.pyth_receiver, cpi_accounts),
+        params,
+    )
+}
+
+/// CPI into `post_update`
+pub fn post_update<'info>(
+    accounts: PostUpdateAccounts<'info>,
+    params: PostUpdateParams,
+) -> Result<()> {
+    require_keys_eq!(
+        accounts.pyth_receiver.key(),
+        PYTH_RECEIVER_PROGRAM_ID,
+        OracleError::InvalidPriceUpdateAccount
+    );
+
+    let cpi_accounts = pyth_solana_receiver_sdk::cpi::accounts::PostUpdate {
+        payer: accounts.payer,
+        encoded_vaa: accounts.encoded_vaa,
+        config: accounts.config,
+        treasury: accounts.treasury,
+        price_update_account: accounts.price_update_account,
+        system_program: accounts.system_program,
+        write_authority: accounts.write_authority,
+    };
+    pyth_solana_receiver_sdk::cpi::post_update(
+        CpiContext::new(accounts.pyth_receiver, cpi_accounts),
+        params,
+    )
+}
+
+/// CPI into `reclaim_rent`: close a consumed price update account and return
+/// its rent to `write_authority`, which must sign
+pub fn reclaim_rent<'info>(
+    pyth_receiver: AccountInfo<'info>,
+    write_authority: AccountInfo<'info>,
+    price_update_account: AccountInfo<'info>,
+) -> Result<()> {
+    require_keys_eq!(
+        pyth_receiver.key(),
+        PYTH_RECEIVER_PROGRAM_ID,
+        OracleError::InvalidPriceUpdateAccount
+    );
+
+    let cpi_accounts = pyth_solana_receiver_sdk::cpi::accounts::ReclaimRent {
+        payer: write_authority,
+        price_update_account,
+    };
+    pyth_solana_receiver_sdk::cpi::reclaim_rent(CpiContext::new(pyth_receiver, cpi_accounts))
+}
+
+/// Close `price_update` if `write_authority` is the account's write authority;
+/// accounts posted by someone else are left alone. Returns whether it closed.
+pub fn reclaim_rent_if_owned<'info>(
+    pyth_receiver: AccountInfo<'info>,
+    write_authority: AccountInfo<'info>,
+    price_update: &Account<'info, PriceUpdateV2>,
+) -> Result<bool> {
+    if price_update.write_authority != write_authority.key() || !write_authority.is_signer {
+        return Ok(false);
+    }
+    reclaim_rent(pyth_receiver, write_authority, price_update.to_account_info())?;
+    Ok(true)
+}
+
+/// Read a price update account written earlier in the same instruction or
+/// transaction. `Account<PriceUpdateV2>` cannot be used for it in the
+/// accounts struct because the account does not exist until the CPI runs.
+pub fn load_posted_price_update(account: &AccountInfo) -> Result<PriceUpdateV2> {
+    require_keys_eq!(
+        *account.owner,
+        PYTH_RECEIVER_PROGRAM_ID,
+        OracleError::InvalidPriceUpdateAccount
+    );
+    let data = account.try_borrow_data()?;
+    PriceUpdateV2::try_deserialize(&mut &data[..])
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+/// Accounts for posting a price update and consuming it in one instruction
+#[derive(Accounts)]
+pub struct PostAndConsumePrice<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Wormhole guardian set, validated by the receiver
+    pub guardian_set: UncheckedAccount<'info>,
+
+    /// CHECK: Receiver config PDA, validated by the receiver
+    pub receiver_config: UncheckedAccount<'info>,
+
+    /// CHECK: Receiver treasury PDA, validated by the receiver
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Ephemeral keypair the receiver initializes as a PriceUpdateV2
+    #[account(mut)]
+    pub price_update: Signer<'info>,
+
+    /// CHECK: Must be the Pyth receiver program
+    #[account(address = PYTH_RECEIVER_PROGRAM_ID)]
+    pub pyth_receiver: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PostAndConsumePrice<'info> {
+    /// Post the update with the payer as write authority and read it back
+    pub fn post_and_load(&self, params: PostUpdateAtomicParams) -> Result<PriceUpdateV2> {
+        post_update_atomic(
+            PostUpdateAtomicAccounts {
+                pyth_receiver: self.pyth_receiver.to_account_info(),
+                payer: self.payer.to_account_info(),
+                guardian_set: self.guardian_set.to_account_info(),
+                config: self.receiver_config.to_account_info(),
+                treasury: self.treasury.to_account_info(),
+                price_update_account: self.price_update.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                write_authority: self.payer.to_account_info(),
+            },
+            params,
+        )?;
+        load_posted_price_update(&self.price_update.to_account_info())
+    }
+}
+
+// ============================================================================
+// OFF-CHAIN INSTRUCTION BUILDERS
+// ============================================================================
+
+#[cfg(feature = "client")]
+pub mod client {
+    use super::*;
+    use anchor_lang::{InstructionData, ToAccountMetas};
+    use pythnet_sdk::wire::v1::{AccumulatorUpdateData, MerklePriceUpdate, Proof};
+    use solana_program::instruction::Instruction;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    /// VAA header: version (1) + guardian set index (4) + signature count (1)
+    const VAA_HEADER_LEN: usize = 6;
+    /// Guardian index (1) + secp256k1 signature (65)
+    const VAA_SIGNATURE_LEN: usize = 66;
+
+    #[derive(Debug, thiserror::Error)]
+    pub enum PostUpdateError {
+        #[error("update data is not a Pyth accumulator update")]
+        InvalidUpdateData,
+
+        #[error("VAA is truncated or malformed")]
+        InvalidVaa,
+    }
+
+    /// VAA and per-feed merkle updates contained in one Hermes update blob
+    pub struct AccumulatorUpdate {
+        pub vaa: Vec<u8>,
+        pub updates: Vec<MerklePriceUpdate>,
+    }
+
+    /// Decode one entry of Hermes `binary.data` (see `hermes::PriceUpdates`)
+    pub fn parse_accumulator_update(
+        update_data: &[u8],
+    ) -> std::result::Result<AccumulatorUpdate, PostUpdateError> {
+        let accumulator = AccumulatorUpdateData::try_from_slice(update_data)
+            .map_err(|_| PostUpdateError::InvalidUpdateData)?;
+        match accumulator.proof {
+            Proof::WormholeMerkle { vaa, updates } => Ok(AccumulatorUpdate {
+                vaa: Vec::from(vaa),
+                updates,
+            }),
+        }
+    }
+
+    /// Guardian set index from the VAA header
+    pub fn guardian_set_index(vaa: &[u8]) -> std::result::Result<u32, PostUpdateError> {
+        let bytes = vaa.get(1..5).ok_or(PostUpdateError::InvalidVaa)?;
+        Ok(u32::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Keep only the first `keep` guardian signatures so the VAA fits inline
+    pub fn trim_vaa_signatures(
+        vaa: &[u8],
+        keep: u8,
+    ) -> std::result::Result<Vec<u8>, PostUpdateError> {
+        let count = *vaa.get(5).ok_or(PostUpdateError::InvalidVaa)?;
+        let signatures_end = VAA_HEADER_LEN + count as usize * VAA_SIGNATURE_LEN;
+        if vaa.len() < signatures_end {
+            return Err(PostUpdateError::InvalidVaa);
+        }
+
+        let keep = keep.min(count);
+        let mut trimmed = vaa[..VAA_HEADER_LEN + keep as usize * VAA_SIGNATURE_LEN].to_vec();
+        trimmed[5] = keep;
+        trimmed.extend_from_slice(&vaa[signatures_end..]);
+        Ok(trimmed)
+    }
+
+    pub fn get_guardian_set_address(index: u32) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"GuardianSet", &index.to_be_bytes()],
+            &WORMHOLE_RECEIVER_PROGRAM_ID,
+        )
+        .0
+    }
+
+    pub fn get_config_address() -> Pubkey {
+        Pubkey::find_program_address(&[b"config"], &PYTH_RECEIVER_PROGRAM_ID).0
+    }
+
+    pub fn get_treasury_address(treasury_id: u8) -> Pubkey {
+        Pubkey::find_program_address(&[b"treasury", &[treasury_id]], &PYTH_RECEIVER_PROGRAM_ID).0
+    }
+
+    /// A receiver instruction plus the ephemeral account that must sign it
+    pub struct PostedPriceUpdate {
+        pub instruction: Instruction,
+        pub price_update_keypair: Keypair,
+    }
+
+    impl PostedPriceUpdate {
+        pub fn price_update_account(&self) -> Pubkey {
+            self.price_update_keypair.pubkey()
+        }
+    }
+
+    /// One `post_update_atomic` instruction per feed in the update blob.
+    /// The payer is also the write authority, so it can reclaim rent later.
+    pub fn build_post_update_atomic_instructions(
+        payer: &Pubkey,
+        update_data: &[u8],
+        num_signatures: u8,
+        treasury_id: u8,
+    ) -> std::result::Result<Vec<PostedPriceUpdate>, PostUpdateError> {
+        let AccumulatorUpdate { vaa, updates } = parse_accumulator_update(update_data)?;
+        let guardian_set = get_guardian_set_address(guardian_set_index(&vaa)?);
+        let vaa = trim_vaa_signatures(&vaa, num_signatures)?;
+
+        Ok(updates
+            .into_iter()
+            .map(|merkle_price_update| {
+                let price_update_keypair = Keypair::new();
+                let accounts = pyth_solana_receiver_sdk::accounts::PostUpdateAtomic {
+                    payer: *payer,
+                    guardian_set,
+                    config: get_config_address(),
+                    treasury: get_treasury_address(treasury_id),
+                    price_update_account: price_update_keypair.pubkey(),
+                    system_program: solana_program::system_program::ID,
+                    write_authority: *payer,
+                };
+                let data = pyth_solana_receiver_sdk::instruction::PostUpdateAtomic {
+                    params: PostUpdateAtomicParams {
+                        vaa: vaa.clone(),
+                        merkle_price_update,
+                        treasury_id,
+                    },
+                };
+                PostedPriceUpdate {
+                    instruction: Instruction {
+                        program_id: PYTH_RECEIVER_PROGRAM_ID,
+                        accounts: accounts.to_account_metas(None),
+                        data: data.data(),
+                    },
+                    price_update_keypair,
+                }
+            })
+            .collect())
+    }
+
+    /// `post_update` instruction for one merkle update against an encoded
+    /// VAA account already verified through the Wormhole receiver
+    pub fn build_post_update_instruction(
+        payer: &Pubkey,
+        encoded_vaa: &Pubkey,
+        merkle_price_update: MerklePriceUpdate,
+        treasury_id: u8,
+    ) -> PostedPriceUpdate {
+        let price_update_keypair = Keypair::new();
+        let accounts = pyth_solana_receiver_sdk::accounts::PostUpdate {
+            payer: *payer,
+            encoded_vaa: *encoded_vaa,
+            config: get_config_address(),
+            treasury: get_treasury_address(treasury_id),
+            price_update_account: price_update_keypair.pubkey(),
+            system_program: solana_program::system_program::ID,
+            write_authority: *payer,
+        };
+        let data = pyth_solana_receiver_sdk::instruction::PostUpdate {
+            params: PostUpdateParams {
+                merkle_price_update,
+                treasury_id,
+            },
+        };
+        PostedPriceUpdate {
+            instruction: Instruction {
+                program_id: PYTH_RECEIVER_PROGRAM_ID,
+                accounts: accounts.to_account_metas(None),
+                data: data.data(),
+            },
+            price_update_keypair,
+        }
+    }
+
+    /// `reclaim_rent` instruction closing a consumed price update account.
+    /// Append it after the consuming instruction, or batch it for accounts
+    /// posted earlier; `write_authority` signs and receives the rent.
+    pub fn build_reclaim_rent_instruction(
+        write_authority: &Pubkey,
+        price_update_account: &Pubkey,
+    ) -> Instruction {
+        let accounts = pyth_solana_receiver_sdk::accounts::ReclaimRent {
+            payer: *write_authority,
+            price_update_account: *price_update_account,
+        };
+        Instruction {
+            program_id: PYTH_RECEIVER_PROGRAM_ID,
+            accounts: accounts.to_account_metas(None),
+            data: pyth_solana_receiver_sdk::instruction::ReclaimRent {}.data(),
+        }
+    }
+
+    /// Accounts for `PostAndConsumePrice`, in declaration order
+    pub fn post_and_consume_accounts(
+        payer: &Pubkey,
+        update_data: &[u8],
+        price_update: &Pubkey,
+        treasury_id: u8,
+    ) -> std::result::Result<Vec<solana_program::instruction::AccountMeta>, PostUpdateError> {
+        use solana_program::instruction::AccountMeta;
+
+        let AccumulatorUpdate { vaa, .. } = parse_accumulator_update(update_data)?;
+        Ok(vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(get_guardian_set_address(guardian_set_index(&vaa)?), false),
+            AccountMeta::new_readonly(get_config_address(), false),
+            AccountMeta::new(get_treasury_address(treasury_id), false),
+            AccountMeta::new(*price_update, true),
+            AccountMeta::new_readonly(PYTH_RECEIVER_PROGRAM_ID, false),
+            AccountMeta::new_readonly(solana_program::system_program::ID, false),
+        ])
+    }
+}
@@ -0,0 +1,153 @@
+/**
+ * Batched PriceUpdateV2 Account Reads (Off-Chain)
+ *
+ * Fetches every configured `PriceUpdateV2` account with batched
+ * `getMultipleAccounts` calls instead of one `getAccountInfo` per feed,
+ * checks receiver ownership, decodes them, and returns them keyed by feed
+ * ID with the slot metadata needed to judge freshness.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    futures = { version = "0.3", optional = true }
+ *    solana-client = { version = "1.18", optional = true }
+ *    solana-sdk = { version = "1.18", optional = true }
+ *    thiserror = "1"
+ *
+ *    [features]
+ *    client = ["dep:solana-sdk", "dep:solana-client", "dep:futures"]
+ *
+ * 2. Declared by anchor-oracle.rs behind the `client` feature
+ */
+
+use anchor_lang::AccountDeserialize;
+use futures::future::try_join_all;
+use pyth_solana_receiver_sdk::price_update::{FeedId, PriceUpdateV2};
+use solana_client::client_error::ClientError;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+
+use crate::PYTH_RECEIVER_PROGRAM_ID;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Most accounts `getMultipleAccounts` returns per call
+pub const MAX_ACCOUNTS_PER_REQUEST: usize = 100;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum PriceAccountsError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] ClientError),
+
+    #[error("price update account {0} does not exist")]
+    Missing(Pubkey),
+
+    #[error("account {address} is owned by {owner}, not the Pyth receiver")]
+    WrongOwner { address: Pubkey, owner: Pubkey },
+
+    #[error("account {0} is not a PriceUpdateV2")]
+    InvalidData(Pubkey),
+}
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// A decoded price update and where it came from
+#[derive(Clone)]
+pub struct PriceAccount {
+    pub address: Pubkey,
+    pub price_update: PriceUpdateV2,
+    /// Slot the receiver wrote the update in
+    pub posted_slot: u64,
+    /// Slot the RPC node served the read from
+    pub context_slot: u64,
+}
+
+impl PriceAccount {
+    pub fn feed_id(&self) -> FeedId {
+        self.price_update.price_message.feed_id
+    }
+
+    pub fn publish_time(&self) -> i64 {
+        self.price_update.price_message.publish_time
+    }
+
+    /// Slots between the write and the read
+    pub fn slot_age(&self) -> u64 {
+        self.context_slot.saturating_sub(self.posted_slot)
+    }
+}
+
+/// Decode one receiver-owned account
+pub fn decode_price_account(
+    address: Pubkey,
+    account: &Account,
+    context_slot: u64,
+) -> Result<PriceAccount, PriceAccountsError> {
+    if account.owner != PYTH_RECEIVER_PROGRAM_ID {
+        return Err(PriceAccountsError::WrongOwner {
+            address,
+            owner: account.owner,
+        });
+    }
+    let price_update = PriceUpdateV2::try_deserialize(&mut account.data.as_slice())
+        .map_err(|_| PriceAccountsError::InvalidData(address))?;
+    Ok(PriceAccount {
+        address,
+        posted_slot: price_update.posted_slot,
+        price_update,
+        context_slot,
+    })
+}
+
+// ============================================================================
+// FETCHING
+// ============================================================================
+
+/// Fetch and decode price update accounts in parallel batches
+///
+/// When several accounts carry the same feed, the most recently published
+/// one wins. Any missing or foreign account fails the whole read, since a
+/// silently absent feed is worse than a retry.
+pub async fn fetch_price_accounts(
+    rpc: &RpcClient,
+    addresses: &[Pubkey],
+    commitment: CommitmentConfig,
+) -> Result<HashMap<FeedId, PriceAccount>, PriceAccountsError> {
+    let config = RpcAccountInfoConfig {
+        commitment: Some(commitment),
+        ..RpcAccountInfoConfig::default()
+    };
+    let batches = try_join_all(
+        addresses
+            .chunks(MAX_ACCOUNTS_PER_REQUEST)
+            .map(|chunk| rpc.get_multiple_accounts_with_config(chunk, config.clone())),
+    )
+    .await?;
+
+    let mut prices: HashMap<FeedId, PriceAccount> = HashMap::new();
+    for (chunk, response) in addresses.chunks(MAX_ACCOUNTS_PER_REQUEST).zip(batches) {
+        for (address, account) in chunk.iter().zip(response.value) {
+            let account = account.ok_or(PriceAccountsError::Missing(*address))?;
+            let decoded = decode_price_account(*address, &account, response.context.slot)?;
+            match prices.get(&decoded.feed_id()) {
+                Some(existing) if existing.publish_time() >= decoded.publish_time() => {}
+                _ => {
+                    prices.insert(decoded.feed_id(), decoded);
+                }
+            }
+        }
+    }
+    Ok(prices)
+}
@@ -0,0 +1,222 @@
+/**
+ * Per-Slot Shared Price Cache
+ *
+ * Programs that read the same feed many times per slot (keepers, market
+ * makers, batched liquidations) pay the full `PriceUpdateV2` load and
+ * verification in every instruction. A `PriceCache` PDA per feed holds the
+ * last validated price and the slot it was validated in. Within that slot,
+ * `read_cached_or_validate` reads the small cache account instead; the
+ * price update account is only deserialized and verified when the cache
+ * is from an earlier slot.
+ *
+ * The cache implements `PriceSource`, so a cache hit still goes through
+ * `get_validated_price` with the caller's own staleness, confidence, and
+ * verification requirements. The price update is passed as an unchecked
+ * account for the same reason: declaring it `Account<PriceUpdateV2>` would
+ * make Anchor deserialize it before the handler runs, hit or miss.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as `price_cache`)
+ * 2. Create one cache per feed with `init_price_cache`; anyone may refresh
+ *    it, since only validated prices are stored
+ * 3. Replace `get_validated_price` with `read_cached_or_validate` in
+ *    instructions that run many times per slot
+ */
+
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, VerificationLevel};
+
+use crate::oracle::{Now, OraclePrice, PriceSource, PriceSourceError, StalenessMode};
+use crate::{get_validated_price, OracleError, PriceValidationConfig, ValidatedPrice};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for price caches: [PRICE_CACHE_SEED, feed_id]
+pub const PRICE_CACHE_SEED: &[u8] = b"price_cache";
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// Last validated price of one feed, shared by every consumer
+#[account]
+pub struct PriceCache {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    /// 0 until the first price
+    pub publish_time: i64,
+    /// Slot the price update was posted in
+    pub posted_slot: u64,
+    /// Slot the price was validated in
+    pub slot: u64,
+    /// Verification level of the update the price came from
+    pub verification_level: VerificationLevel,
+    pub bump: u8,
+}
+
+impl PriceCache {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 4 + 8 + 8 + 8 + 2 + 1;
+
+    /// Whether the cache was filled in the current slot
+    pub fn is_fresh(&self, clock: &impl Now) -> bool {
+        self.publish_time > 0 && self.slot == clock.slot()
+    }
+
+    /// Store a validated price. An older price is not stored, so the cache
+    /// never moves backwards.
+    fn store(&mut self, price: &ValidatedPrice, update: &PriceUpdateV2, slot: u64) {
+        if price.publish_time < self.publish_time {
+            return;
+        }
+        self.price = price.price;
+        self.conf = price.conf;
+        self.exponent = price.exponent;
+        self.publish_time = price.publish_time;
+        self.posted_slot = update.posted_slot;
+        self.slot = slot;
+        self.verification_level = update.verification_level;
+    }
+}
+
+impl PriceSource for PriceCache {
+    fn feed_id(&self) -> [u8; 32] {
+        self.feed_id
+    }
+
+    fn staleness_mode(&self) -> StalenessMode {
+        StalenessMode::Seconds
+    }
+
+    fn get_price(&self, clock: &impl Now, max_age: u64) -> Result<OraclePrice> {
+        require!(self.publish_time > 0, PriceSourceError::PriceUnavailable);
+        require!(
+            clock.unix_timestamp().saturating_sub(self.publish_time) <= max_age as i64,
+            OracleError::PriceTooStale
+        );
+        Ok(OraclePrice {
+            price: self.price,
+            conf: self.conf,
+            exponent: self.exponent,
+            publish_time: self.publish_time,
+        })
+    }
+
+    /// The cache holds spot prices only
+    fn get_ema_price(&self, _clock: &impl Now, _max_age: u64) -> Result<OraclePrice> {
+        err!(PriceSourceError::PriceUnavailable)
+    }
+
+    fn posted_slot(&self) -> Option<u64> {
+        Some(self.posted_slot)
+    }
+
+    fn check_verification(&self, level: &VerificationLevel) -> Result<()> {
+        require!(
+            self.verification_level.gte(*level),
+            PriceSourceError::InsufficientVerification
+        );
+        Ok(())
+    }
+}
+
+// ============================================================================
+// READ PATH
+// ============================================================================
+
+/// Validate the cached price if it was filled in this slot; otherwise load
+/// and validate `price_update` and refill the cache from it
+///
+/// Fails with `PriceCacheMiss` when the cache is from an earlier slot and
+/// no price update was passed.
+pub fn read_cached_or_validate<'info>(
+    cache: &mut PriceCache,
+    price_update: Option<&AccountInfo<'info>>,
+    config: &PriceValidationConfig,
+    clock: &impl Now,
+) -> Result<ValidatedPrice> {
+    if cache.is_fresh(clock) {
+        return get_validated_price(&*cache, config, clock);
+    }
+
+    let price_update = price_update.ok_or(error!(OracleError::PriceCacheMiss))?;
+    let update = Account::<PriceUpdateV2>::try_from(price_update)?;
+    let config = PriceValidationConfig {
+        expected_feed_id: Some(cache.feed_id),
+        ..*config
+    };
+    let price = get_validated_price(&*update, &config, clock)?;
+
+    cache.store(&price, &update, clock.slot());
+    Ok(price)
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(feed_id: [u8; 32])]
+pub struct InitPriceCache<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = PriceCache::LEN,
+        seeds = [PRICE_CACHE_SEED, feed_id.as_ref()],
+        bump
+    )]
+    pub price_cache: Account<'info, PriceCache>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReadPriceCache<'info> {
+    #[account(
+        mut,
+        seeds = [PRICE_CACHE_SEED, price_cache.feed_id.as_ref()],
+        bump = price_cache.bump
+    )]
+    pub price_cache: Account<'info, PriceCache>,
+
+    /// CHECK: loaded as a `PriceUpdateV2` (owner and discriminator checked)
+    /// only on a cache miss
+    pub price_update: Option<UncheckedAccount<'info>>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn init_price_cache(ctx: Context<InitPriceCache>, feed_id: [u8; 32]) -> Result<()> {
+    let cache = &mut ctx.accounts.price_cache;
+    cache.feed_id = feed_id;
+    cache.price = 0;
+    cache.conf = 0;
+    cache.exponent = 0;
+    cache.publish_time = 0;
+    cache.posted_slot = 0;
+    cache.slot = 0;
+    cache.verification_level = VerificationLevel::Full;
+    cache.bump = ctx.bumps.price_cache;
+    Ok(())
+}
+
+pub fn get_cached_price(ctx: Context<ReadPriceCache>, config: PriceValidationConfig) -> Result<()> {
+    let clock = Clock::get()?;
+    let price_update = ctx.accounts.price_update.as_ref().map(|a| a.to_account_info());
+    let price = read_cached_or_validate(
+        &mut ctx.accounts.price_cache,
+        price_update.as_ref(),
+        &config,
+        &clock,
+    )?;
+    msg!("Price: {} × 10^{} (slot {})", price.price, price.exponent, ctx.accounts.price_cache.slot);
+    Ok(())
+}
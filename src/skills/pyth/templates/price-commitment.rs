@@ -0,0 +1,198 @@
+/**
+ * zk-Friendly Price Commitments
+ *
+ * Private trading protocols prove statements about a price inside a zk
+ * circuit ("this order filled within the oracle band") without revealing
+ * the order. The circuit takes a commitment to the price as a public input
+ * and the price itself as a private witness; the program checks that the
+ * public input equals the commitment of a price it validated.
+ *
+ * Two commitments of a `ValidatedPrice` (feed ID, price, exponent, publish
+ * time):
+ *
+ * - `poseidon_price_commitment`: Poseidon over BN254 (x^5, the parameters
+ *   of circomlib's `Poseidon(6)`), via the `sol_poseidon` syscall on chain.
+ *   Cheap inside circuits; use it for Groth16/PLONK over BN254.
+ * - `keccak_price_commitment`: keccak256 of the packed big-endian fields,
+ *   matching Solidity's `abi.encodePacked`. Use it when the verifier also
+ *   runs on an EVM chain; `keccak_commitment_as_field` fits it in a BN254
+ *   scalar.
+ *
+ * Field encoding, the part circuits must replicate exactly:
+ * - the feed ID is split into two 128-bit big-endian halves, since 256
+ *   bits do not fit the BN254 scalar field
+ * - signed fields are biased to unsigned: `price + 2^63`, `exponent + 2^31`,
+ *   `publish_time + 2^63`, so every input is a small non-negative integer
+ * - the first input is `PRICE_COMMITMENT_DOMAIN`, so a price commitment can
+ *   never equal a commitment the circuit computes for something else
+ *
+ * `PriceWitness` is the off-chain witness generator: it produces the same
+ * field elements, the commitment, and a circom/snarkjs input JSON.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as
+ *    `price_commitment`)
+ * 2. Requires `solana-program` >= 1.17 for the Poseidon syscall (included
+ *    with anchor-lang 0.30)
+ * 3. In the circuit, hash the witness inputs in the order of
+ *    `PriceWitness::field_elements` and constrain the result to the public
+ *    commitment
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::poseidon::{self, Endianness, Parameters};
+
+use crate::{OracleError, ValidatedPrice};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// First Poseidon input; bump it when the encoding changes
+pub const PRICE_COMMITMENT_DOMAIN: u64 = 1;
+
+/// Prefix of the keccak preimage
+pub const KECCAK_PRICE_PREFIX: &[u8] = b"pyth-price-v1";
+
+/// Bias applied to signed 64-bit fields
+const I64_BIAS: u64 = 1 << 63;
+
+/// Bias applied to the exponent
+const I32_BIAS: u32 = 1 << 31;
+
+// ============================================================================
+// ENCODING
+// ============================================================================
+
+/// `x + 2^63` as an unsigned integer
+pub fn bias_i64(x: i64) -> u64 {
+    (x as u64) ^ I64_BIAS
+}
+
+/// `x + 2^31` as an unsigned integer
+pub fn bias_i32(x: i32) -> u32 {
+    (x as u32) ^ I32_BIAS
+}
+
+/// Big-endian (high, low) halves of a feed ID
+pub fn split_feed_id(feed_id: &[u8; 32]) -> (u128, u128) {
+    (
+        u128::from_be_bytes(feed_id[..16].try_into().unwrap()),
+        u128::from_be_bytes(feed_id[16..].try_into().unwrap()),
+    )
+}
+
+/// A field element below 2^128, as 32 big-endian bytes
+fn field_bytes(value: u128) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[16..].copy_from_slice(&value.to_be_bytes());
+    bytes
+}
+
+// ============================================================================
+// COMMITMENTS
+// ============================================================================
+
+/// Poseidon commitment (big-endian BN254 scalar) of a validated price
+pub fn poseidon_price_commitment(price: &ValidatedPrice) -> Result<[u8; 32]> {
+    let inputs = PriceWitness::from_price(price).field_elements().map(field_bytes);
+    let refs: Vec<&[u8]> = inputs.iter().map(|input| input.as_slice()).collect();
+
+    poseidon::hashv(Parameters::Bn254X5, Endianness::BigEndian, &refs)
+        .map(|hash| hash.to_bytes())
+        .map_err(|_| error!(OracleError::CommitmentHashFailed))
+}
+
+/// keccak256(prefix || feed_id || price || exponent || publish_time), all
+/// big-endian, as `abi.encodePacked(bytes13, bytes32, int64, int32, int64)`
+pub fn keccak_price_commitment(price: &ValidatedPrice) -> [u8; 32] {
+    keccak::hashv(&[
+        KECCAK_PRICE_PREFIX,
+        &price.feed_id,
+        &price.price.to_be_bytes(),
+        &price.exponent.to_be_bytes(),
+        &price.publish_time.to_be_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// A keccak commitment with the top byte cleared, so it is below the BN254
+/// scalar modulus (circuits do the same to their own keccak output)
+pub fn keccak_commitment_as_field(commitment: &[u8; 32]) -> [u8; 32] {
+    let mut field = *commitment;
+    field[0] = 0;
+    field
+}
+
+// ============================================================================
+// WITNESS GENERATION (OFF-CHAIN)
+// ============================================================================
+
+/// Private inputs of a price commitment circuit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceWitness {
+    pub feed_id_hi: u128,
+    pub feed_id_lo: u128,
+    /// `price + 2^63`
+    pub price: u64,
+    /// `exponent + 2^31`
+    pub exponent: u32,
+    /// `publish_time + 2^63`
+    pub publish_time: u64,
+}
+
+impl PriceWitness {
+    pub fn from_price(price: &ValidatedPrice) -> Self {
+        let (feed_id_hi, feed_id_lo) = split_feed_id(&price.feed_id);
+        Self {
+            feed_id_hi,
+            feed_id_lo,
+            price: bias_i64(price.price),
+            exponent: bias_i32(price.exponent),
+            publish_time: bias_i64(price.publish_time),
+        }
+    }
+
+    /// Poseidon inputs, in order
+    pub fn field_elements(&self) -> [u128; 6] {
+        [
+            PRICE_COMMITMENT_DOMAIN as u128,
+            self.feed_id_hi,
+            self.feed_id_lo,
+            self.price as u128,
+            self.exponent as u128,
+            self.publish_time as u128,
+        ]
+    }
+
+    /// circom/snarkjs input: decimal private inputs and the public
+    /// `commitment` as a 0x-prefixed hex string
+    pub fn to_circuit_input_json(&self, commitment: &[u8; 32]) -> String {
+        let hex: String = commitment.iter().map(|byte| format!("{byte:02x}")).collect();
+        format!(
+            r#"{{"domain":"{}","feed_id_hi":"{}","feed_id_lo":"{}","price":"{}","exponent":"{}","publish_time":"{}","commitment":"0x{}"}}"#,
+            PRICE_COMMITMENT_DOMAIN,
+            self.feed_id_hi,
+            self.feed_id_lo,
+            self.price,
+            self.exponent,
+            self.publish_time,
+            hex
+        )
+    }
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+/// Both commitments of a price the program validated, for indexers that
+/// serve public inputs to provers
+#[event]
+pub struct PriceCommitted {
+    pub feed_id: [u8; 32],
+    pub publish_time: i64,
+    pub poseidon: [u8; 32],
+    pub keccak: [u8; 32],
+}
@@ -0,0 +1,168 @@
+/**
+ * Price Consumption Events
+ *
+ * A transaction's effects show what a protocol did, but not which price it
+ * did it at. `consume_price` validates a price like `get_validated_price`
+ * and emits `PriceConsumed` with the exact price, confidence, and publish
+ * time, what it was used for, and the account it was used on. An indexer
+ * can then reconstruct the oracle input of every swap, valuation, and
+ * liquidation.
+ *
+ * A price that fails validation emits `PriceRejected` with the reason
+ * before the instruction fails. The transaction is rolled back but its logs
+ * are kept, so the event still reaches log subscribers and
+ * `getTransaction`: a burst of rejections for one feed shows an outage
+ * before users report it.
+ *
+ * Together with `OracleConfigUpdated` (oracle-config.rs) and
+ * `CircuitBreakerTripped` (circuit-breaker.rs), the events cover each price
+ * the program used or refused and the limits it checked them against.
+ * examples/off-chain/price-events.rs subscribes to them.
+ *
+ * Each event costs a few hundred compute units and about 200 bytes of the
+ * 10 KB log limit. Instructions reading many prices (baskets, cross-margin)
+ * emit their own summary event instead of one per price.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as
+ *    `price_events`)
+ * 2. Replace `get_validated_price` with `consume_price` in instructions
+ *    that act on the price, passing what the price is used for and the
+ *    account it is used on
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{get_validated_price, Now, PriceSource, PriceValidationConfig, ValidatedPrice};
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// What a consumed price was used for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum PriceUse {
+    /// Read without an economic effect
+    Read,
+    Swap,
+    CollateralValuation,
+    HealthRefresh,
+    Liquidation,
+}
+
+/// Why a price failed validation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum RejectionReason {
+    InsufficientVerification,
+    FeedIdMismatch,
+    Stale,
+    ConfidenceTooHigh,
+    ZeroPrice,
+    NegativePrice,
+    /// Any other error; see `PriceRejected::error_code`
+    Other,
+}
+
+impl RejectionReason {
+    /// Classify a validation error. Errors are matched by name: the same
+    /// failure is an `OracleError` or a `PriceSourceError` depending on
+    /// which check caught it, and a source that returns the receiver SDK's
+    /// errors unconverted raises a `GetPriceError`.
+    pub fn from_error(error: &Error) -> Self {
+        let Error::AnchorError(error) = error else {
+            return Self::Other;
+        };
+        match error.error_name.as_str() {
+            "InsufficientVerification" | "InsufficientVerificationLevel" => Self::InsufficientVerification,
+            "FeedIdMismatch" | "MismatchedFeedId" => Self::FeedIdMismatch,
+            "PriceTooStale" | "PriceTooOld" => Self::Stale,
+            "ConfidenceTooHigh" => Self::ConfidenceTooHigh,
+            "ZeroPrice" => Self::ZeroPrice,
+            "NegativePrice" => Self::NegativePrice,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Numeric code of `error`, as the transaction reports it
+pub fn error_code(error: &Error) -> u32 {
+    match error {
+        Error::AnchorError(error) => error.error_code_number,
+        Error::ProgramError(error) => u64::from(error.program_error.clone()) as u32,
+    }
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct PriceConsumed {
+    pub feed_id: [u8; 32],
+    pub price_update: Pubkey,
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub used_for: PriceUse,
+    /// Account the price acted on (position, pool, ...); the default key
+    /// for plain reads
+    pub subject: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted before the instruction fails
+#[event]
+pub struct PriceRejected {
+    /// Feed of the price update, which may not be the expected one
+    pub feed_id: [u8; 32],
+    pub price_update: Pubkey,
+    pub reason: RejectionReason,
+    pub error_code: u32,
+    pub used_for: PriceUse,
+    pub subject: Pubkey,
+    pub timestamp: i64,
+}
+
+// ============================================================================
+// CONSUMPTION
+// ============================================================================
+
+/// `get_validated_price` that emits `PriceConsumed` or `PriceRejected`
+pub fn consume_price<S: PriceSource>(
+    source: &S,
+    price_update: Pubkey,
+    config: &PriceValidationConfig,
+    clock: &impl Now,
+    used_for: PriceUse,
+    subject: Pubkey,
+) -> Result<ValidatedPrice> {
+    match get_validated_price(source, config, clock) {
+        Ok(price) => {
+            emit!(PriceConsumed {
+                feed_id: price.feed_id,
+                price_update,
+                price: price.price,
+                conf: price.conf,
+                exponent: price.exponent,
+                publish_time: price.publish_time,
+                used_for,
+                subject,
+                timestamp: clock.unix_timestamp(),
+            });
+            Ok(price)
+        }
+        Err(error) => {
+            emit!(PriceRejected {
+                feed_id: source.feed_id(),
+                price_update,
+                reason: RejectionReason::from_error(&error),
+                error_code: error_code(&error),
+                used_for,
+                subject,
+                timestamp: clock.unix_timestamp(),
+            });
+            Err(error)
+        }
+    }
+}
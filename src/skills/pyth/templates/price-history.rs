@@ -0,0 +1,384 @@
+/**
+ * Historical Price Backfill and Local Store (Off-Chain)
+ *
+ * Risk parameters (LTV, liquidation thresholds, circuit breaker bands,
+ * confidence limits) should come from how a feed actually behaved, over
+ * months rather than the minutes a live recording covers. This module
+ * fetches that history and keeps it on disk:
+ *
+ * - `HermesClient::backfill_price_updates` pages through the updates of
+ *   a feed over a time range. Hermes only keeps recent updates, so pages
+ *   come from the Pyth Benchmarks API, which serves the same update
+ *   format for any past window of up to a minute.
+ * - `PriceHistoryStore` appends them to a columnar store: one directory
+ *   per feed, one file per column of fixed-width little-endian values.
+ *   Appends skip anything not newer than the last stored update, so a
+ *   backfill can be rerun or resumed with `last_publish_time`. A range
+ *   query binary-searches the `publish_time` column and reads only the
+ *   rows it needs from the others.
+ * - `PriceSeries` answers the questions tuning needs: realized
+ *   volatility, maximum drawdown, and average confidence.
+ *
+ * Setup:
+ * 1. Uses the hermes-client.rs and hermes-stream.rs dependencies
+ * 2. Declared by anchor-oracle.rs behind the `hermes` feature
+ */
+
+use futures::Stream;
+use pyth_solana_receiver_sdk::price_update::FeedId;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::hermes::{feed_id_to_hex, HermesClient, HermesError, HermesResult, ParsedPriceUpdate, PriceUpdates};
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Pyth Benchmarks API, which serves historical price updates
+pub const DEFAULT_BENCHMARKS_ENDPOINT: &str = "https://benchmarks.pyth.network";
+
+/// Longest window the Benchmarks API returns in one request
+pub const MAX_BACKFILL_PAGE_SECS: i64 = 60;
+
+/// Pause between page requests, to stay under public rate limits
+pub const DEFAULT_BACKFILL_REQUEST_DELAY: Duration = Duration::from_millis(350);
+
+const SECS_PER_YEAR: f64 = 365.0 * 24.0 * 3600.0;
+const BPS_DENOMINATOR: f64 = 10_000.0;
+
+/// Where and how fast a backfill pages
+#[derive(Clone, Debug)]
+pub struct BackfillConfig {
+    pub endpoint: String,
+    /// Seconds per request, at most `MAX_BACKFILL_PAGE_SECS`
+    pub page_secs: i64,
+    pub request_delay: Duration,
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: DEFAULT_BENCHMARKS_ENDPOINT.to_string(),
+            page_secs: MAX_BACKFILL_PAGE_SECS,
+            request_delay: DEFAULT_BACKFILL_REQUEST_DELAY,
+        }
+    }
+}
+
+// ============================================================================
+// BACKFILL
+// ============================================================================
+
+impl HermesClient {
+    /// Updates of `feed_id` published in [`start`, `start + secs`), oldest
+    /// first
+    pub async fn get_historical_price_updates(
+        &self,
+        endpoint: &str,
+        feed_id: &FeedId,
+        start: i64,
+        secs: i64,
+    ) -> HermesResult<Vec<ParsedPriceUpdate>> {
+        let response = self
+            .http_client()
+            .get(format!("{}/v1/updates/price/{}/{}", endpoint.trim_end_matches('/'), start, secs))
+            .query(&[
+                ("ids", feed_id_to_hex(feed_id)),
+                ("encoding", "base64".to_string()),
+                ("parsed", "true".to_string()),
+                ("unique", "true".to_string()),
+            ])
+            .send()
+            .await?;
+
+        match response.status().as_u16() {
+            // Nothing was published in the window
+            404 => return Ok(Vec::new()),
+            status if !response.status().is_success() => {
+                return Err(HermesError::Status {
+                    status,
+                    body: response.text().await.unwrap_or_default(),
+                })
+            }
+            _ => {}
+        }
+
+        let mut updates: Vec<ParsedPriceUpdate> = PriceUpdates::list_from_json(&response.text().await?)?
+            .into_iter()
+            .flat_map(|page| page.parsed)
+            .filter(|update| update.feed_id == *feed_id && (start..start + secs).contains(&update.price.publish_time))
+            .collect();
+        updates.sort_by_key(|update| update.price.publish_time);
+        updates.dedup_by_key(|update| update.price.publish_time);
+        Ok(updates)
+    }
+
+    /// Updates of `feed_id` published in [`from`, `to`), one page per item
+    /// in publish-time order, so a long backfill can be stored as it runs.
+    /// Empty windows yield nothing; an error is yielded and the backfill
+    /// moves on to the next page.
+    pub fn backfill_price_updates(
+        &self,
+        feed_id: FeedId,
+        from: i64,
+        to: i64,
+        config: BackfillConfig,
+    ) -> impl Stream<Item = HermesResult<Vec<ParsedPriceUpdate>>> {
+        let client = self.clone();
+        let page_secs = config.page_secs.clamp(1, MAX_BACKFILL_PAGE_SECS);
+
+        async_stream::stream! {
+            let mut start = from;
+            while start < to {
+                let secs = page_secs.min(to - start);
+                match client.get_historical_price_updates(&config.endpoint, &feed_id, start, secs).await {
+                    Ok(page) if page.is_empty() => {}
+                    Ok(page) => yield Ok(page),
+                    Err(e) => yield Err(e),
+                }
+                start += secs;
+                tokio::time::sleep(config.request_delay).await;
+            }
+        }
+    }
+}
+
+// ============================================================================
+// COLUMNAR STORE
+// ============================================================================
+
+/// Column files of a feed and the width of their values
+const PUBLISH_TIME_COLUMN: (&str, usize) = ("publish_time.i64", 8);
+const PRICE_COLUMN: (&str, usize) = ("price.i64", 8);
+const CONF_COLUMN: (&str, usize) = ("conf.u64", 8);
+const EXPONENT_COLUMN: (&str, usize) = ("exponent.i32", 4);
+const COLUMNS: [(&str, usize); 4] = [PUBLISH_TIME_COLUMN, PRICE_COLUMN, CONF_COLUMN, EXPONENT_COLUMN];
+
+/// Append-only price history of any number of feeds, under one directory
+#[derive(Clone, Debug)]
+pub struct PriceHistoryStore {
+    root: PathBuf,
+}
+
+impl PriceHistoryStore {
+    /// Open the store at `root`, creating the directory if needed
+    pub fn open(root: impl AsRef<Path>) -> io::Result<Self> {
+        std::fs::create_dir_all(root.as_ref())?;
+        Ok(Self { root: root.as_ref().to_path_buf() })
+    }
+
+    fn feed_dir(&self, feed_id: &FeedId) -> PathBuf {
+        self.root.join(feed_id_to_hex(feed_id).trim_start_matches("0x"))
+    }
+
+    /// Rows stored for `feed_id`. Columns of unequal length (an append cut
+    /// short) are truncated to the shortest, dropping the partial row.
+    pub fn row_count(&self, feed_id: &FeedId) -> io::Result<u64> {
+        let dir = self.feed_dir(feed_id);
+        let mut rows = u64::MAX;
+        let mut lengths = Vec::with_capacity(COLUMNS.len());
+        for (name, width) in COLUMNS {
+            let bytes = match std::fs::metadata(dir.join(name)) {
+                Ok(metadata) => metadata.len(),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+                Err(e) => return Err(e),
+            };
+            rows = rows.min(bytes / width as u64);
+            lengths.push(bytes);
+        }
+        for ((name, width), bytes) in COLUMNS.iter().zip(lengths) {
+            if bytes > rows * *width as u64 {
+                OpenOptions::new().write(true).open(dir.join(name))?.set_len(rows * *width as u64)?;
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Publish time of the newest stored update of `feed_id`
+    pub fn last_publish_time(&self, feed_id: &FeedId) -> io::Result<Option<i64>> {
+        let rows = self.row_count(feed_id)?;
+        if rows == 0 {
+            return Ok(None);
+        }
+        let times = read_column(&self.feed_dir(feed_id), PUBLISH_TIME_COLUMN, rows - 1, 1)?;
+        Ok(Some(i64::from_le_bytes(times[..8].try_into().unwrap())))
+    }
+
+    /// Append `updates` of `feed_id` newer than the last stored one, in
+    /// publish-time order; returns the rows written
+    pub fn append(&self, feed_id: &FeedId, updates: &[ParsedPriceUpdate]) -> io::Result<usize> {
+        let mut last = self.last_publish_time(feed_id)?.unwrap_or(i64::MIN);
+        let mut columns: [Vec<u8>; 4] = Default::default();
+        let mut rows = 0;
+        for update in updates.iter().filter(|update| update.feed_id == *feed_id) {
+            let price = &update.price;
+            if price.publish_time <= last {
+                continue;
+            }
+            last = price.publish_time;
+            columns[0].extend_from_slice(&price.publish_time.to_le_bytes());
+            columns[1].extend_from_slice(&price.price.to_le_bytes());
+            columns[2].extend_from_slice(&price.conf.to_le_bytes());
+            columns[3].extend_from_slice(&price.exponent.to_le_bytes());
+            rows += 1;
+        }
+        if rows == 0 {
+            return Ok(0);
+        }
+
+        let dir = self.feed_dir(feed_id);
+        std::fs::create_dir_all(&dir)?;
+        // publish_time last: a row only counts once every column has it
+        for ((name, _), bytes) in COLUMNS.iter().zip(&columns).rev() {
+            OpenOptions::new().create(true).append(true).open(dir.join(name))?.write_all(bytes)?;
+        }
+        Ok(rows)
+    }
+
+    /// Stored updates of `feed_id` published in [`from`, `to`)
+    pub fn load(&self, feed_id: &FeedId, from: i64, to: i64) -> io::Result<PriceSeries> {
+        let dir = self.feed_dir(feed_id);
+        let rows = self.row_count(feed_id)?;
+        let publish_time: Vec<i64> = read_column(&dir, PUBLISH_TIME_COLUMN, 0, rows)?
+            .chunks_exact(8)
+            .map(|value| i64::from_le_bytes(value.try_into().unwrap()))
+            .collect();
+
+        let start = publish_time.partition_point(|&time| time < from);
+        let end = publish_time.partition_point(|&time| time < to).max(start);
+        let (offset, count) = (start as u64, (end - start) as u64);
+        Ok(PriceSeries {
+            feed_id: *feed_id,
+            publish_time: publish_time[start..end].to_vec(),
+            price: read_column(&dir, PRICE_COLUMN, offset, count)?
+                .chunks_exact(8)
+                .map(|value| i64::from_le_bytes(value.try_into().unwrap()))
+                .collect(),
+            conf: read_column(&dir, CONF_COLUMN, offset, count)?
+                .chunks_exact(8)
+                .map(|value| u64::from_le_bytes(value.try_into().unwrap()))
+                .collect(),
+            exponent: read_column(&dir, EXPONENT_COLUMN, offset, count)?
+                .chunks_exact(4)
+                .map(|value| i32::from_le_bytes(value.try_into().unwrap()))
+                .collect(),
+        })
+    }
+}
+
+/// `count` values of a column from row `offset`
+fn read_column(dir: &Path, (name, width): (&str, usize), offset: u64, count: u64) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![0u8; count as usize * width];
+    if count > 0 {
+        let mut file = File::open(dir.join(name))?;
+        file.seek(SeekFrom::Start(offset * width as u64))?;
+        file.read_exact(&mut bytes)?;
+    }
+    Ok(bytes)
+}
+
+// ============================================================================
+// QUERIES
+// ============================================================================
+
+/// Price history of one feed, one entry per update, oldest first
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PriceSeries {
+    pub feed_id: FeedId,
+    pub publish_time: Vec<i64>,
+    pub price: Vec<i64>,
+    pub conf: Vec<u64>,
+    pub exponent: Vec<i32>,
+}
+
+/// Largest fall from a running peak
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Drawdown {
+    /// Fall as a fraction of the peak, in [0, 1]
+    pub fraction: f64,
+    pub peak_time: i64,
+    pub trough_time: i64,
+}
+
+impl PriceSeries {
+    pub fn len(&self) -> usize {
+        self.publish_time.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.publish_time.is_empty()
+    }
+
+    /// Price of row `i` as a float
+    pub fn price_f64(&self, i: usize) -> f64 {
+        self.price[i] as f64 * 10f64.powi(self.exponent[i])
+    }
+
+    /// Last price at or before every `interval_secs` boundary from the first
+    /// update on; intervals without an update repeat the previous price
+    pub fn resample(&self, interval_secs: i64) -> Vec<f64> {
+        let (Some(&first), Some(&last)) = (self.publish_time.first(), self.publish_time.last()) else {
+            return Vec::new();
+        };
+        let interval_secs = interval_secs.max(1);
+        let mut row = 0;
+        let mut samples = Vec::with_capacity(((last - first) / interval_secs + 1) as usize);
+        let mut boundary = first;
+        while boundary <= last {
+            while row + 1 < self.len() && self.publish_time[row + 1] <= boundary {
+                row += 1;
+            }
+            samples.push(self.price_f64(row));
+            boundary += interval_secs;
+        }
+        samples
+    }
+
+    /// Annualized standard deviation of log returns over `interval_secs`
+    /// steps; `None` with fewer than two returns or a non-positive price
+    pub fn realized_volatility(&self, interval_secs: i64) -> Option<f64> {
+        let samples = self.resample(interval_secs);
+        if samples.len() < 3 || samples.iter().any(|&price| price <= 0.0) {
+            return None;
+        }
+        let returns: Vec<f64> = samples.windows(2).map(|pair| (pair[1] / pair[0]).ln()).collect();
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+        Some((variance * SECS_PER_YEAR / interval_secs.max(1) as f64).sqrt())
+    }
+
+    /// Largest fall from a running peak across all updates
+    pub fn max_drawdown(&self) -> Option<Drawdown> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut peak = (self.price_f64(0), self.publish_time[0]);
+        let mut worst = Drawdown { fraction: 0.0, peak_time: peak.1, trough_time: peak.1 };
+        for i in 1..self.len() {
+            let price = self.price_f64(i);
+            if price > peak.0 {
+                peak = (price, self.publish_time[i]);
+            } else if peak.0 > 0.0 && (peak.0 - price) / peak.0 > worst.fraction {
+                worst = Drawdown {
+                    fraction: (peak.0 - price) / peak.0,
+                    peak_time: peak.1,
+                    trough_time: self.publish_time[i],
+                };
+            }
+        }
+        Some(worst)
+    }
+
+    /// Mean confidence in basis points of the price, over updates with a
+    /// non-zero price
+    pub fn average_confidence_bps(&self) -> Option<f64> {
+        let bps: Vec<f64> = (0..self.len())
+            .filter(|&i| self.price[i] != 0)
+            .map(|i| self.conf[i] as f64 * BPS_DENOMINATOR / self.price[i].unsigned_abs() as f64)
+            .collect();
+        (!bps.is_empty()).then(|| bps.iter().sum::<f64>() / bps.len() as f64)
+    }
+}
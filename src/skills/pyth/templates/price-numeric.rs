@@ -0,0 +1,351 @@
+/**
+ * Numeric Conversions and Human-Readable Prices (Off-Chain)
+ *
+ * Dashboards and risk scripts want `rust_decimal::Decimal`, `f64`, and
+ * text, not mantissas and exponents. With the `numeric` feature:
+ *
+ *   - `OracleDecimal::to_decimal` and `ValidatedPrice::price_decimal` /
+ *     `conf_decimal` convert to `Decimal`, with `TryFrom` both ways
+ *   - `OracleDecimal::to_f64` and `ValidatedPrice::price_f64` / `conf_f64`
+ *     convert to `f64`
+ *   - `Display` renders a `ValidatedPrice` in human units,
+ *     "171.2345 ± 0.12", and an `OracleDecimal` at its own scale, "0.8000";
+ *     a precision (`{:.2}`) rounds to that many places, halves away from
+ *     zero
+ *   - `FromStr` parses both back: `OracleDecimal` exactly, with as many
+ *     decimal places as the text has, and "171.2345 ± 0.12" (or "+/-", or
+ *     a bare price) as a `HumanPrice`, the price and confidence at the
+ *     finer of their two scales
+ *
+ * Accuracy:
+ *
+ *   - `Decimal` holds a 96-bit mantissa with 0 to 28 decimal places. Every
+ *     i64 price converts exactly for exponents from -28 to 9, which covers
+ *     every Pyth feed. Past 28 places the value is rounded to nearest at 28
+ *     places (error at most 5 × 10^-29); a value of 2^96 or more fails with
+ *     `MathOverflow`. `Decimal` to `OracleDecimal` is always exact.
+ *   - `f64` results are correctly rounded: the nearest `f64` to the exact
+ *     value, so the relative error is at most 2^-53 (about 1.1 × 10^-16),
+ *     and values past `f64::MAX` become infinity. Prices up to 2^53 with
+ *     exponent 0 convert exactly; a price like 171.2345 is the same `f64`
+ *     as the literal `171.2345`.
+ *   - Display and `FromStr` are exact without a precision: parsing the
+ *     text gives back the same value.
+ *
+ * None of this belongs in a program: `f64` is not deterministic across
+ * validators' compilers and `Decimal` costs compute. On-chain math stays
+ * on `OracleDecimal`.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    rust_decimal = { version = "1", optional = true }
+ *    thiserror = "1"
+ *
+ *    [features]
+ *    numeric = ["dep:rust_decimal"]
+ *
+ * 2. Declared by anchor-oracle.rs behind the `numeric` feature
+ */
+
+use std::fmt;
+use std::str::FromStr;
+
+use anchor_lang::prelude::*;
+use rust_decimal::Decimal;
+
+use crate::{OracleDecimal, OracleError, OraclePrice, Rounding, ValidatedPrice};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Most decimal places a `Decimal` holds
+pub const MAX_DECIMAL_SCALE: i32 = 28;
+
+/// Below 10^-364 even a 39-digit mantissa rounds to `0.0`
+const F64_ZERO_SCALE: i32 = 364;
+
+/// At 10^309 and above any non-zero mantissa is past `f64::MAX`
+const F64_INFINITE_SCALE: i32 = -309;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParsePriceError {
+    #[error("not a decimal number: {0:?}")]
+    InvalidNumber(String),
+
+    #[error("value must not be negative")]
+    Negative,
+
+    #[error("value does not fit the price type")]
+    Overflow,
+}
+
+// ============================================================================
+// FORMATTING
+// ============================================================================
+
+/// `mantissa × 10^-scale` as decimal digits, at `precision` places if set
+/// (rounded to nearest), else at `scale` places
+fn format_fixed(mantissa: u128, scale: i32, precision: Option<usize>) -> String {
+    let (mantissa, scale) = match precision {
+        Some(places) if (places as i64) < scale as i64 => {
+            let rounded = OracleDecimal::new(mantissa, scale)
+                .rescale(places as i32, Rounding::Nearest)
+                .expect("reducing the scale cannot overflow");
+            (rounded.mantissa, rounded.scale)
+        }
+        _ => (mantissa, scale),
+    };
+
+    let mut text = mantissa.to_string();
+    if scale <= 0 {
+        if mantissa != 0 {
+            text.push_str(&"0".repeat(scale.unsigned_abs() as usize));
+        }
+    } else {
+        let scale = scale as usize;
+        if text.len() <= scale {
+            text.insert_str(0, &"0".repeat(scale + 1 - text.len()));
+        }
+        text.insert(text.len() - scale, '.');
+    }
+
+    // Pad up to the requested places
+    let places = scale.max(0) as usize;
+    if let Some(precision) = precision.filter(|&precision| precision > places) {
+        if places == 0 {
+            text.push('.');
+        }
+        text.push_str(&"0".repeat(precision - places));
+    }
+    text
+}
+
+/// The nearest `f64` to `±mantissa × 10^-scale`
+fn fixed_to_f64(negative: bool, mantissa: u128, scale: i32) -> f64 {
+    let magnitude = if mantissa == 0 || scale > F64_ZERO_SCALE {
+        0.0
+    } else if scale < F64_INFINITE_SCALE {
+        f64::INFINITY
+    } else {
+        // Rust parses decimal text correctly rounded
+        format_fixed(mantissa, scale, None)
+            .parse::<f64>()
+            .expect("decimal digits parse as f64")
+    };
+    if negative {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// `±mantissa × 10^-scale` as a `Decimal`, rounded to nearest past 28
+/// places
+fn fixed_to_decimal(negative: bool, mantissa: u128, scale: i32) -> Result<Decimal> {
+    let fitted = OracleDecimal::new(mantissa, scale).rescale(scale.clamp(0, MAX_DECIMAL_SCALE), Rounding::Nearest)?;
+    let value = i128::try_from(fitted.mantissa).map_err(|_| error!(OracleError::MathOverflow))?;
+    let mut decimal = Decimal::try_from_i128_with_scale(value, fitted.scale as u32)
+        .map_err(|_| error!(OracleError::MathOverflow))?;
+    decimal.set_sign_negative(negative && value != 0);
+    Ok(decimal)
+}
+
+// ============================================================================
+// PARSING
+// ============================================================================
+
+/// Sign, mantissa, and decimal places of "-171.2345"-style text
+fn parse_fixed(text: &str) -> std::result::Result<(bool, u128, i32), ParsePriceError> {
+    let invalid = || ParsePriceError::InvalidNumber(text.to_string());
+    let (negative, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let all_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+    if integer.len() + fraction.len() == 0 || !all_digits(integer) || !all_digits(fraction) {
+        return Err(invalid());
+    }
+
+    let scale = i32::try_from(fraction.len()).map_err(|_| ParsePriceError::Overflow)?;
+    let mantissa = format!("{integer}{fraction}")
+        .parse::<u128>()
+        .map_err(|_| ParsePriceError::Overflow)?;
+    Ok((negative && mantissa != 0, mantissa, scale))
+}
+
+// ============================================================================
+// ORACLE DECIMAL
+// ============================================================================
+
+impl OracleDecimal {
+    /// The nearest `f64`
+    pub fn to_f64(&self) -> f64 {
+        fixed_to_f64(false, self.mantissa, self.scale)
+    }
+
+    /// Exact up to 28 decimal places, rounded to nearest past them
+    pub fn to_decimal(&self) -> Result<Decimal> {
+        fixed_to_decimal(false, self.mantissa, self.scale)
+    }
+}
+
+impl TryFrom<OracleDecimal> for Decimal {
+    type Error = anchor_lang::error::Error;
+
+    fn try_from(value: OracleDecimal) -> Result<Self> {
+        value.to_decimal()
+    }
+}
+
+impl TryFrom<Decimal> for OracleDecimal {
+    type Error = anchor_lang::error::Error;
+
+    /// Exact; negative values are rejected
+    fn try_from(value: Decimal) -> Result<Self> {
+        require!(!value.is_sign_negative() || value.is_zero(), OracleError::NegativePrice);
+        Ok(OracleDecimal::new(value.mantissa().unsigned_abs(), value.scale() as i32))
+    }
+}
+
+impl fmt::Display for OracleDecimal {
+    /// At its own scale: `OracleDecimal::from_bps(8_000)` is "0.8000"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_fixed(self.mantissa, self.scale, f.precision()))
+    }
+}
+
+impl FromStr for OracleDecimal {
+    type Err = ParsePriceError;
+
+    /// "0.8000" is `OracleDecimal::new(8_000, 4)`
+    fn from_str(text: &str) -> std::result::Result<Self, Self::Err> {
+        match parse_fixed(text.trim())? {
+            (true, _, _) => Err(ParsePriceError::Negative),
+            (false, mantissa, scale) => Ok(OracleDecimal::new(mantissa, scale)),
+        }
+    }
+}
+
+// ============================================================================
+// PRICES
+// ============================================================================
+
+impl ValidatedPrice {
+    pub fn price_f64(&self) -> f64 {
+        fixed_to_f64(self.price < 0, self.price.unsigned_abs() as u128, -self.exponent)
+    }
+
+    pub fn conf_f64(&self) -> f64 {
+        fixed_to_f64(false, self.conf as u128, -self.exponent)
+    }
+
+    /// Exact for exponents from -28 to 9
+    pub fn price_decimal(&self) -> Result<Decimal> {
+        fixed_to_decimal(self.price < 0, self.price.unsigned_abs() as u128, -self.exponent)
+    }
+
+    pub fn conf_decimal(&self) -> Result<Decimal> {
+        fixed_to_decimal(false, self.conf as u128, -self.exponent)
+    }
+}
+
+impl fmt::Display for ValidatedPrice {
+    /// "171.2345 ± 0.12"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&HumanPrice::from(self), f)
+    }
+}
+
+/// A price and its confidence as written for people, "171.2345 ± 0.12",
+/// in the oracle's fixed-point convention
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HumanPrice {
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+}
+
+impl HumanPrice {
+    /// With the publish time the text does not carry
+    pub fn to_oracle_price(&self, publish_time: i64) -> OraclePrice {
+        OraclePrice {
+            price: self.price,
+            conf: self.conf,
+            exponent: self.exponent,
+            publish_time,
+        }
+    }
+}
+
+impl From<&ValidatedPrice> for HumanPrice {
+    fn from(price: &ValidatedPrice) -> Self {
+        Self {
+            price: price.price,
+            conf: price.conf,
+            exponent: price.exponent,
+        }
+    }
+}
+
+impl fmt::Display for HumanPrice {
+    /// Each number without trailing zeros, or at the formatter's precision
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scale = -self.exponent;
+        let precision = f.precision();
+        let text = |mantissa: u128| match precision {
+            Some(places) => format_fixed(mantissa, scale, Some(places)),
+            None => {
+                let value = OracleDecimal::new(mantissa, scale).normalize();
+                format_fixed(value.mantissa, value.scale, None)
+            }
+        };
+        let price = text(self.price.unsigned_abs() as u128);
+        let sign = if self.price < 0 && price.bytes().any(|byte| matches!(byte, b'1'..=b'9')) {
+            "-"
+        } else {
+            ""
+        };
+        write!(f, "{sign}{price} ± {}", text(self.conf as u128))
+    }
+}
+
+impl FromStr for HumanPrice {
+    type Err = ParsePriceError;
+
+    /// "171.2345 ± 0.12", "171.2345 +/- 0.12", or "171.2345" with no
+    /// confidence
+    fn from_str(text: &str) -> std::result::Result<Self, Self::Err> {
+        let (price, conf) = match text.split_once('±').or_else(|| text.split_once("+/-")) {
+            Some((price, conf)) => (price.trim(), Some(conf.trim())),
+            None => (text.trim(), None),
+        };
+        let (negative, price_mantissa, price_scale) = parse_fixed(price)?;
+        let (conf_mantissa, conf_scale) = match conf.map(parse_fixed).transpose()? {
+            Some((true, _, _)) => return Err(ParsePriceError::Negative),
+            Some((false, mantissa, scale)) => (mantissa, scale),
+            None => (0, 0),
+        };
+
+        // Both at the finer scale, so neither loses a digit
+        let scale = price_scale.max(conf_scale);
+        let rescale = |mantissa: u128, from: i32| {
+            OracleDecimal::new(mantissa, from)
+                .rescale(scale, Rounding::Floor)
+                .map(|value| value.mantissa)
+                .map_err(|_| ParsePriceError::Overflow)
+        };
+        let magnitude = i64::try_from(rescale(price_mantissa, price_scale)?).map_err(|_| ParsePriceError::Overflow)?;
+        Ok(Self {
+            price: if negative { -magnitude } else { magnitude },
+            conf: u64::try_from(rescale(conf_mantissa, conf_scale)?).map_err(|_| ParsePriceError::Overflow)?,
+            exponent: -scale,
+        })
+    }
+}
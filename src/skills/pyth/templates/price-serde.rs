@@ -0,0 +1,92 @@
+/**
+ * Serde Support for Prices and Validation Configs
+ *
+ * With the `serde` feature, `ValidatedPrice` and `PriceValidationConfig`
+ * implement `Serialize` and `Deserialize`, so off-chain services can log
+ * them as JSON, persist them, and pass them between processes. Both also
+ * derive Borsh (`AnchorSerialize`), which is the on-chain and instruction
+ * data encoding.
+ *
+ * The JSON format is part of the interface and does not follow the Rust
+ * types where they would make poor JSON:
+ *
+ *   - feed IDs are `0x`-prefixed lowercase hex strings, not arrays of 32
+ *     numbers; both the prefixed and bare forms are accepted on input
+ *   - the verification level is `{"level": "full"}` or
+ *     `{"level": "partial", "num_signatures": 5}`
+ *   - field names are the Rust field names, in snake_case
+ *
+ *   {"feed_id": "0xef0d8b6f...", "price": 15000000000, "conf": 7500000,
+ *    "exponent": -8, "publish_time": 1700000000,
+ *    "lower_bound": 14992500000, "upper_bound": 15007500000}
+ *
+ * Prices and bounds are JSON integers. JavaScript readers lose precision
+ * above 2^53; parse them as big integers there.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    serde = { version = "1", features = ["derive"], optional = true }
+ *
+ *    [features]
+ *    serde = ["dep:serde"]
+ *
+ * 2. Declared by anchor-oracle.rs behind the `serde` feature
+ */
+
+use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, VerificationLevel};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// ============================================================================
+// FEED IDS
+// ============================================================================
+
+/// `0x`-prefixed hex of a feed ID
+pub fn feed_id_to_hex(feed_id: &[u8; 32]) -> String {
+    let hex: String = feed_id.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("0x{hex}")
+}
+
+/// `#[serde(with = "feed_id")]` for `[u8; 32]` feed IDs
+pub mod feed_id {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(feed_id: &[u8; 32], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&feed_id_to_hex(feed_id))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 32], D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        get_feed_id_from_hex(&hex).map_err(|_| serde::de::Error::custom(format!("invalid feed id {hex}")))
+    }
+}
+
+/// `#[serde(with = "optional_feed_id")]` for `Option<[u8; 32]>`
+pub mod optional_feed_id {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(feed_id: &Option<[u8; 32]>, serializer: S) -> Result<S::Ok, S::Error> {
+        feed_id.as_ref().map(feed_id_to_hex).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<[u8; 32]>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|hex| {
+                get_feed_id_from_hex(&hex).map_err(|_| serde::de::Error::custom(format!("invalid feed id {hex}")))
+            })
+            .transpose()
+    }
+}
+
+// ============================================================================
+// VERIFICATION LEVEL
+// ============================================================================
+
+/// Mirror of the receiver SDK's `VerificationLevel`, which has no serde
+/// support; use as `#[serde(with = "VerificationLevelDef")]`
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "VerificationLevel", tag = "level", rename_all = "snake_case")]
+pub enum VerificationLevelDef {
+    Partial { num_signatures: u8 },
+    Full,
+}
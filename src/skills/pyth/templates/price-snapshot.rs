@@ -0,0 +1,108 @@
+/**
+ * Synchronized Multi-Feed Price Snapshots
+ *
+ * Each price that `load_remaining_prices` returns passes the staleness
+ * check on its own, so one basket valuation may combine a SOL price from
+ * just now with a USDC price from 50 seconds ago. Over a sharp move that
+ * values a hedged position as if one leg had moved and the other had not.
+ * `validate_price_sync` (examples/on-chain/price-validation.rs) catches
+ * this for two prices; a `PriceSnapshot` does it for any number:
+ *
+ * - `PriceSnapshot::try_new` takes prices from one moment and fails with
+ *   `PricesNotSynchronized` unless every publish time lies within
+ *   `max_skew_secs` of the others
+ * - `timestamp` is the oldest publish time: every price is at least that
+ *   recent and none is more than `skew_secs` newer
+ *
+ * Basket valuation (`value_holdings`) and the margin engine
+ * (`basket_exposures`) take a `PriceSnapshot`, not loose prices, so every
+ * number they produce comes from one moment. `load_price_snapshot` loads
+ * and validates the remaining accounts and builds the snapshot in one
+ * call. The skew check is in addition to the staleness check, not a
+ * replacement: a snapshot of prices that are all an hour old is
+ * synchronized, and `get_validated_price` still rejects it.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as
+ *    `price_snapshot`); requires remaining-prices.rs
+ * 2. Pass `max_skew_secs` of a few seconds: Pyth feeds publish every
+ *    400 ms, so updates posted in one transaction are usually within 1-2 s
+ */
+
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::FeedId;
+
+use crate::{load_remaining_prices, Now, OracleError, PriceValidationConfig, RemainingPrices, ValidatedPrice};
+
+// ============================================================================
+// SNAPSHOT
+// ============================================================================
+
+/// Prices of several feeds whose publish times are at most `max_skew_secs`
+/// apart
+#[derive(Clone, Debug)]
+pub struct PriceSnapshot {
+    prices: RemainingPrices,
+    timestamp: i64,
+    skew_secs: u64,
+}
+
+impl PriceSnapshot {
+    /// Snapshot of `prices`, one per feed, validated elsewhere
+    pub fn try_new(prices: &[ValidatedPrice], max_skew_secs: u64) -> Result<Self> {
+        Self::from_prices(RemainingPrices::from_prices(prices)?, max_skew_secs)
+    }
+
+    /// Snapshot of prices loaded with `load_remaining_prices`
+    pub fn from_prices(prices: RemainingPrices, max_skew_secs: u64) -> Result<Self> {
+        let oldest = prices.iter().map(|price| price.publish_time).min().unwrap_or_default();
+        let newest = prices.iter().map(|price| price.publish_time).max().unwrap_or_default();
+        let skew_secs = newest.abs_diff(oldest);
+        require!(skew_secs <= max_skew_secs, OracleError::PricesNotSynchronized);
+        Ok(Self {
+            prices,
+            timestamp: oldest,
+            skew_secs,
+        })
+    }
+
+    /// Oldest publish time in the snapshot; 0 when it is empty
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    /// Seconds between the oldest and newest publish times
+    pub fn skew_secs(&self) -> u64 {
+        self.skew_secs
+    }
+
+    /// The price of `feed_id`; fails with `MissingPriceFeed` if the
+    /// snapshot does not hold it
+    pub fn get(&self, feed_id: &FeedId) -> Result<ValidatedPrice> {
+        self.prices.get(feed_id)
+    }
+
+    /// Prices sorted by feed ID
+    pub fn iter(&self) -> impl Iterator<Item = &ValidatedPrice> {
+        self.prices.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.prices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+}
+
+/// `load_remaining_prices`, then a snapshot of the result
+pub fn load_price_snapshot(
+    accounts: &[AccountInfo],
+    feed_ids: &[FeedId],
+    config: &PriceValidationConfig,
+    max_skew_secs: u64,
+    clock: &impl Now,
+) -> Result<PriceSnapshot> {
+    PriceSnapshot::from_prices(load_remaining_prices(accounts, feed_ids, config, clock)?, max_skew_secs)
+}
@@ -0,0 +1,178 @@
+/**
+ * Raw PriceUpdateV2 Reader
+ *
+ * `Account<'info, PriceUpdateV2>` Borsh-deserializes the whole update
+ * during account validation, and again wherever the account is reloaded.
+ * `PriceUpdateView` reads only the fields price validation needs, straight
+ * from the account data, after the same owner and discriminator checks
+ * Anchor performs. It implements `PriceSource`, so `get_validated_price`
+ * and everything built on it accept it unchanged.
+ *
+ * Anchor's `AccountLoader` (zero-copy) cannot be used here: `PriceUpdateV2`
+ * is a Borsh account owned by the Pyth receiver, and its
+ * `verification_level` enum is one byte (Full) or two (Partial), so the
+ * offsets of every later field depend on it. The reader handles that
+ * shift.
+ *
+ * Use the `*Raw` context structs in anchor-oracle.rs, which take the price
+ * update as an `UncheckedAccount`. The LiteSVM example
+ * `raw_price_read_uses_fewer_compute_units` measures the saving.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as
+ *    `price_update_view`)
+ * 2. Declare price updates as `UncheckedAccount<'info>` and load them with
+ *    `PriceUpdateView::load`
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, VerificationLevel};
+
+use crate::oracle::{Now, OraclePrice, PriceSource, PriceSourceError, StalenessMode};
+use crate::{OracleError, PYTH_RECEIVER_PROGRAM_ID};
+
+// ============================================================================
+// LAYOUT
+// ============================================================================
+
+/// Discriminator, then `write_authority`
+const VERIFICATION_LEVEL_OFFSET: usize = 8 + 32;
+
+/// Borsh tags of `VerificationLevel`
+const PARTIAL_TAG: u8 = 0;
+const FULL_TAG: u8 = 1;
+
+/// `PriceFeedMessage` fields, relative to its start
+const FEED_ID: usize = 0;
+const PRICE: usize = 32;
+const CONF: usize = 40;
+const EXPONENT: usize = 48;
+const PUBLISH_TIME: usize = 52;
+const PREV_PUBLISH_TIME: usize = 60;
+const EMA_PRICE: usize = 68;
+const EMA_CONF: usize = 76;
+/// Followed by `posted_slot`
+const MESSAGE_LEN: usize = 84;
+
+// ============================================================================
+// VIEW
+// ============================================================================
+
+/// The fields of a `PriceUpdateV2`, read without deserializing the account
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceUpdateView {
+    pub verification_level: VerificationLevel,
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+    pub posted_slot: u64,
+}
+
+impl PriceUpdateView {
+    /// Check owner and discriminator, then read the price fields
+    pub fn load(account: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(
+            *account.owner,
+            PYTH_RECEIVER_PROGRAM_ID,
+            OracleError::InvalidPriceUpdateAccount
+        );
+        let data = account.try_borrow_data()?;
+        Self::parse(&data)
+    }
+
+    /// Read the price fields from raw account data (discriminator included)
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        require!(
+            data.get(..8) == Some(PriceUpdateV2::DISCRIMINATOR.as_ref()),
+            OracleError::InvalidPriceUpdateAccount
+        );
+
+        let (verification_level, message) = match data.get(VERIFICATION_LEVEL_OFFSET) {
+            Some(&PARTIAL_TAG) => {
+                let num_signatures = *data
+                    .get(VERIFICATION_LEVEL_OFFSET + 1)
+                    .ok_or(OracleError::InvalidPriceUpdateAccount)?;
+                (
+                    VerificationLevel::Partial { num_signatures },
+                    VERIFICATION_LEVEL_OFFSET + 2,
+                )
+            }
+            Some(&FULL_TAG) => (VerificationLevel::Full, VERIFICATION_LEVEL_OFFSET + 1),
+            _ => return err!(OracleError::InvalidPriceUpdateAccount),
+        };
+        require!(data.len() >= message + MESSAGE_LEN + 8, OracleError::InvalidPriceUpdateAccount);
+
+        let bytes = |offset: usize, len: usize| &data[message + offset..message + offset + len];
+        let i64_at = |offset| i64::from_le_bytes(bytes(offset, 8).try_into().unwrap());
+        let u64_at = |offset| u64::from_le_bytes(bytes(offset, 8).try_into().unwrap());
+
+        Ok(Self {
+            verification_level,
+            feed_id: bytes(FEED_ID, 32).try_into().unwrap(),
+            price: i64_at(PRICE),
+            conf: u64_at(CONF),
+            exponent: i32::from_le_bytes(bytes(EXPONENT, 4).try_into().unwrap()),
+            publish_time: i64_at(PUBLISH_TIME),
+            prev_publish_time: i64_at(PREV_PUBLISH_TIME),
+            ema_price: i64_at(EMA_PRICE),
+            ema_conf: u64_at(EMA_CONF),
+            posted_slot: u64_at(MESSAGE_LEN),
+        })
+    }
+
+    fn check_age(&self, clock: &impl Now, max_age: u64) -> Result<()> {
+        require!(
+            self.publish_time.saturating_add(max_age as i64) >= clock.unix_timestamp(),
+            OracleError::PriceTooStale
+        );
+        Ok(())
+    }
+}
+
+impl PriceSource for PriceUpdateView {
+    fn feed_id(&self) -> [u8; 32] {
+        self.feed_id
+    }
+
+    fn staleness_mode(&self) -> StalenessMode {
+        StalenessMode::Seconds
+    }
+
+    fn get_price(&self, clock: &impl Now, max_age: u64) -> Result<OraclePrice> {
+        self.check_age(clock, max_age)?;
+        Ok(OraclePrice {
+            price: self.price,
+            conf: self.conf,
+            exponent: self.exponent,
+            publish_time: self.publish_time,
+        })
+    }
+
+    fn get_ema_price(&self, clock: &impl Now, max_age: u64) -> Result<OraclePrice> {
+        self.check_age(clock, max_age)?;
+        Ok(OraclePrice {
+            price: self.ema_price,
+            conf: self.ema_conf,
+            exponent: self.exponent,
+            publish_time: self.publish_time,
+        })
+    }
+
+    fn posted_slot(&self) -> Option<u64> {
+        Some(self.posted_slot)
+    }
+
+    fn check_verification(&self, level: &VerificationLevel) -> Result<()> {
+        require!(
+            self.verification_level.gte(*level),
+            PriceSourceError::InsufficientVerification
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+/**
+ * Publish-Time Monotonicity Guard
+ *
+ * Any valid price update for a feed passes `get_validated_price`, including
+ * one older than an update the program has already acted on. Two
+ * transactions that pass different price update accounts can therefore see
+ * the price move backwards: a liquidation at the new price followed by a
+ * borrow at the old one. `LastObservedPrice` records the newest publish
+ * time accepted per feed, and `require_monotonic` rejects anything older.
+ *
+ * A rejected price fails the instruction, which is what a rollback should
+ * do; unlike the circuit breaker there is no state to persist on rejection.
+ *
+ * If the recorded publish time can never be matched again (a bad update,
+ * or a feed that was reset), the guard would block the feed forever. When
+ * `max_gap_secs` is set, an older price is accepted once no price has been
+ * accepted for that long, and becomes the new reference.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as
+ *    `publish_time_guard`)
+ * 2. Create one account per feed with `init_last_observed_price`
+ * 3. Call `require_monotonic` after `get_validated_price` in every
+ *    instruction that acts on the price
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{OracleError, ValidatedPrice};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed: [LAST_OBSERVED_PRICE_SEED, authority, feed_id]
+pub const LAST_OBSERVED_PRICE_SEED: &[u8] = b"last_observed_price";
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// Newest price a program has accepted for one feed
+#[account]
+pub struct LastObservedPrice {
+    pub authority: Pubkey,
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub exponent: i32,
+    /// 0 until the first price
+    pub publish_time: i64,
+    /// Clock time the reference was last accepted
+    pub accepted_at: i64,
+    /// After this long without an accepted price, older prices are
+    /// accepted again; 0 never allows a rollback
+    pub max_gap_secs: i64,
+    pub bump: u8,
+}
+
+impl LastObservedPrice {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 4 + 8 + 8 + 8 + 1;
+
+    /// Whether a price older than the reference may be accepted at `now`
+    pub fn rollback_allowed(&self, now: i64) -> bool {
+        self.max_gap_secs > 0 && now - self.accepted_at >= self.max_gap_secs
+    }
+}
+
+// ============================================================================
+// GUARD
+// ============================================================================
+
+/// Fail with `PriceRollback` if `price` was published before the last
+/// accepted price for its feed; otherwise record it as the reference
+pub fn require_monotonic(
+    last: &mut LastObservedPrice,
+    price: &ValidatedPrice,
+    now: i64,
+) -> Result<()> {
+    require!(price.feed_id == last.feed_id, OracleError::FeedIdMismatch);
+
+    if price.publish_time < last.publish_time {
+        require!(last.rollback_allowed(now), OracleError::PriceRollback);
+        emit!(PriceRollbackAccepted {
+            feed_id: last.feed_id,
+            previous_publish_time: last.publish_time,
+            publish_time: price.publish_time,
+            gap_secs: now - last.accepted_at,
+        });
+    }
+
+    last.price = price.price;
+    last.exponent = price.exponent;
+    last.publish_time = price.publish_time;
+    last.accepted_at = now;
+    Ok(())
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+/// An older price was accepted through the `max_gap_secs` escape hatch
+#[event]
+pub struct PriceRollbackAccepted {
+    pub feed_id: [u8; 32],
+    pub previous_publish_time: i64,
+    pub publish_time: i64,
+    /// Time since the previous accepted price
+    pub gap_secs: i64,
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(feed_id: [u8; 32])]
+pub struct InitLastObservedPrice<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = LastObservedPrice::LEN,
+        seeds = [LAST_OBSERVED_PRICE_SEED, authority.key().as_ref(), feed_id.as_ref()],
+        bump
+    )]
+    pub last_observed_price: Account<'info, LastObservedPrice>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn init_last_observed_price(
+    ctx: Context<InitLastObservedPrice>,
+    feed_id: [u8; 32],
+    max_gap_secs: i64,
+) -> Result<()> {
+    require!(max_gap_secs >= 0, OracleError::InvalidRollbackGap);
+
+    let last = &mut ctx.accounts.last_observed_price;
+    last.authority = ctx.accounts.authority.key();
+    last.feed_id = feed_id;
+    last.price = 0;
+    last.exponent = 0;
+    last.publish_time = 0;
+    last.accepted_at = Clock::get()?.unix_timestamp;
+    last.max_gap_secs = max_gap_secs;
+    last.bump = ctx.bumps.last_observed_price;
+    Ok(())
+}
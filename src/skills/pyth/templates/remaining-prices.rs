@@ -0,0 +1,147 @@
+/**
+ * Multi-Feed Prices from Remaining Accounts
+ *
+ * Basket operations (index rebalances, portfolio margin, multi-asset
+ * liquidations) need a price per asset, and the number of assets is not
+ * known when the `Accounts` struct is written. Pass the price updates as
+ * remaining accounts instead and load them with `load_remaining_prices`:
+ * it checks each account's owner and discriminator, validates it with the
+ * caller's `PriceValidationConfig`, and returns the prices keyed by feed ID.
+ *
+ * The caller names the feeds it needs. A feed without an update fails with
+ * `MissingPriceFeed`; two different accounts for one feed fail with
+ * `DuplicatePriceFeed`, since which one wins would be the caller's choice,
+ * not the program's. The same account passed twice is read once. An update
+ * for a feed that was not asked for fails with `FeedIdMismatch`, so a
+ * client cannot pad the transaction with accounts the program ignores.
+ *
+ * Updates are read through `PriceUpdateView`, so no account is
+ * Borsh-deserialized. Nothing touches the heap either: the feeds and
+ * prices live in fixed-capacity `ArrayVec`s of `MAX_REMAINING_FEEDS`, so
+ * the worst-case cost of a call depends only on that constant. Asking for
+ * more feeds fails with `TooManyPriceFeeds`. `RemainingPrices` is about
+ * 1.3 KB; keep it out of handlers whose frames are already near the 4 KB
+ * SBF stack frame limit.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as
+ *    `remaining_prices`)
+ * 2. Take the handler's context as
+ *    `Context<'_, '_, '_, 'info, YourAccounts<'info>>` and call
+ *    `load_remaining_prices(ctx.remaining_accounts, &feed_ids, &config, &clock)`
+ * 3. Clients append one price update per feed, in any order, after the
+ *    instruction's named accounts
+ */
+
+use anchor_lang::prelude::*;
+use arrayvec::ArrayVec;
+use pyth_solana_receiver_sdk::price_update::FeedId;
+
+use crate::{get_validated_price, Now, OracleError, PriceUpdateView, PriceValidationConfig, ValidatedPrice};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Most distinct feeds one `load_remaining_prices` call may load
+pub const MAX_REMAINING_FEEDS: usize = 16;
+
+// ============================================================================
+// PRICES
+// ============================================================================
+
+/// Validated prices returned by `load_remaining_prices`, sorted by feed ID
+#[derive(Clone, Debug, Default)]
+pub struct RemainingPrices {
+    prices: ArrayVec<ValidatedPrice, MAX_REMAINING_FEEDS>,
+}
+
+impl RemainingPrices {
+    /// Prices validated elsewhere (off-chain simulation, tests), one per feed
+    pub fn from_prices(prices: &[ValidatedPrice]) -> Result<Self> {
+        let mut sorted = Self::default();
+        for price in prices {
+            let index = sorted
+                .prices
+                .binary_search_by(|existing| existing.feed_id.cmp(&price.feed_id))
+                .err()
+                .ok_or_else(|| error!(OracleError::DuplicatePriceFeed))?;
+            sorted
+                .prices
+                .try_insert(index, *price)
+                .map_err(|_| error!(OracleError::TooManyPriceFeeds))?;
+        }
+        Ok(sorted)
+    }
+
+    /// The price of `feed_id`; fails with `MissingPriceFeed` if it was not
+    /// loaded
+    pub fn get(&self, feed_id: &FeedId) -> Result<ValidatedPrice> {
+        self.prices
+            .binary_search_by(|price| price.feed_id.cmp(feed_id))
+            .map(|index| self.prices[index])
+            .map_err(|_| error!(OracleError::MissingPriceFeed))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ValidatedPrice> {
+        self.prices.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.prices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+}
+
+// ============================================================================
+// LOADING
+// ============================================================================
+
+/// Validated prices for exactly `feed_ids`, read from `accounts`
+///
+/// `config.expected_feed_id` is ignored: every update is matched against
+/// `feed_ids` instead.
+pub fn load_remaining_prices(
+    accounts: &[AccountInfo],
+    feed_ids: &[FeedId],
+    config: &PriceValidationConfig,
+    clock: &impl Now,
+) -> Result<RemainingPrices> {
+    // Sorted and deduplicated by insertion: at most 16 × 16 comparisons
+    let mut wanted: ArrayVec<FeedId, MAX_REMAINING_FEEDS> = ArrayVec::new();
+    for feed_id in feed_ids {
+        if let Err(index) = wanted.binary_search(feed_id) {
+            wanted
+                .try_insert(index, *feed_id)
+                .map_err(|_| error!(OracleError::TooManyPriceFeeds))?;
+        }
+    }
+    // The account each wanted feed was read from
+    let mut sources: [Option<Pubkey>; MAX_REMAINING_FEEDS] = [None; MAX_REMAINING_FEEDS];
+    let config = PriceValidationConfig { expected_feed_id: None, ..*config };
+
+    let mut prices = RemainingPrices::default();
+    for account in accounts {
+        let view = PriceUpdateView::load(account)?;
+        let index = wanted
+            .binary_search(&view.feed_id)
+            .map_err(|_| error!(OracleError::FeedIdMismatch))?;
+
+        match sources[index] {
+            Some(previous) => {
+                require_keys_eq!(previous, *account.key, OracleError::DuplicatePriceFeed);
+                continue;
+            }
+            None => sources[index] = Some(*account.key),
+        }
+        // Never full: at most one price per wanted feed
+        let position = prices.prices.partition_point(|price| price.feed_id < view.feed_id);
+        prices.prices.insert(position, get_validated_price(&view, &config, clock)?);
+    }
+
+    require!(prices.len() == wanted.len(), OracleError::MissingPriceFeed);
+    Ok(prices)
+}
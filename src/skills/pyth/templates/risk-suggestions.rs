@@ -0,0 +1,197 @@
+/**
+ * Volatility-Based Risk Parameter Suggestions (Off-Chain)
+ *
+ * Turns a feed's stored history (price-history.rs) into starting values
+ * for the parameters that depend on how far its price can move:
+ *
+ * - `max_confidence_bps`: the confidence quantile of the history with
+ *   headroom, as `recommend` in feed-analytics.rs does, but capped at half
+ *   the maintenance margin so an accepted price cannot be uncertain by
+ *   more than the buffer it is meant to protect
+ * - the maintenance margin: the fall a position must survive while it
+ *   waits to be liquidated, `margin_sigmas` standard deviations over
+ *   `liquidation_horizon_secs`, as the `maintenance_haircut_bps` of
+ *   cross-margin.rs or the gap between 100% and a liquidation threshold
+ * - the circuit breaker band: a move of `breaker_sigmas` standard
+ *   deviations within `breaker_window_secs`, as a `BreakerConfig`
+ *   (circuit-breaker.rs) for `init_circuit_breaker`
+ *
+ * Every band uses the highest rolling volatility of the history, not its
+ * average: parameters set from a calm month fail in the next volatile
+ * one. Moves are scaled from the return interval with the square root of
+ * time, so they assume independent returns; fat tails are what the sigma
+ * multiples are for. Review the output before proposing it.
+ *
+ * Setup:
+ * 1. Declared by anchor-oracle.rs behind the `hermes` feature, next to
+ *    price-history.rs
+ */
+
+use pyth_solana_receiver_sdk::price_update::FeedId;
+
+use crate::price_history::PriceSeries;
+use crate::{BreakerConfig, TripAction};
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+const SECS_PER_YEAR: f64 = 365.0 * 24.0 * 3600.0;
+const BPS_DENOMINATOR: f64 = 10_000.0;
+
+/// How the suggestions are derived from a history
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SuggestionConfig {
+    /// Return interval of the volatility estimate
+    pub interval_secs: i64,
+    /// Returns per rolling volatility window
+    pub window_intervals: usize,
+    /// Time from a position turning unhealthy to its liquidation landing
+    pub liquidation_horizon_secs: i64,
+    pub margin_sigmas: f64,
+    /// Window of the suggested circuit breaker
+    pub breaker_window_secs: i64,
+    /// Wider than the margin: a breaker should only trip on moves the
+    /// history makes implausible
+    pub breaker_sigmas: f64,
+    pub confidence_quantile: f64,
+    /// Added to the confidence quantile, as a fraction of it
+    pub confidence_headroom: f64,
+}
+
+impl Default for SuggestionConfig {
+    /// Hourly returns over rolling weeks; a four hour liquidation horizon
+    fn default() -> Self {
+        Self {
+            interval_secs: 3_600,
+            window_intervals: 7 * 24,
+            liquidation_horizon_secs: 4 * 3_600,
+            margin_sigmas: 3.0,
+            breaker_window_secs: 300,
+            breaker_sigmas: 8.0,
+            confidence_quantile: 0.99,
+            confidence_headroom: 0.25,
+        }
+    }
+}
+
+// ============================================================================
+// ROLLING VOLATILITY
+// ============================================================================
+
+/// Realized volatility of the window ending at `end_time`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VolatilityPoint {
+    pub end_time: i64,
+    /// Annualized
+    pub volatility: f64,
+}
+
+impl PriceSeries {
+    /// Annualized volatility of every run of `window` consecutive
+    /// `interval_secs` log returns, in order; empty if the series is
+    /// shorter than one window or has a non-positive price
+    pub fn rolling_volatility(&self, interval_secs: i64, window: usize) -> Vec<VolatilityPoint> {
+        let interval_secs = interval_secs.max(1);
+        let samples = self.resample(interval_secs);
+        if window < 2 || samples.len() <= window || samples.iter().any(|&price| price <= 0.0) {
+            return Vec::new();
+        }
+        let returns: Vec<f64> = samples.windows(2).map(|pair| (pair[1] / pair[0]).ln()).collect();
+        let annualize = SECS_PER_YEAR / interval_secs as f64;
+
+        // Running sums, so each window costs O(1)
+        let (mut sum, mut sum_sq) = (0.0, 0.0);
+        let mut points = Vec::with_capacity(returns.len() + 1 - window);
+        for (i, &r) in returns.iter().enumerate() {
+            sum += r;
+            sum_sq += r * r;
+            if i >= window {
+                let old = returns[i - window];
+                sum -= old;
+                sum_sq -= old * old;
+            }
+            if i + 1 >= window {
+                let n = window as f64;
+                let variance = ((sum_sq - sum * sum / n) / (n - 1.0)).max(0.0);
+                points.push(VolatilityPoint {
+                    end_time: self.publish_time[0] + (i as i64 + 1) * interval_secs,
+                    volatility: (variance * annualize).sqrt(),
+                });
+            }
+        }
+        points
+    }
+}
+
+// ============================================================================
+// SUGGESTIONS
+// ============================================================================
+
+/// Suggested parameters of one feed and the statistics behind them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RiskSuggestion {
+    pub feed_id: FeedId,
+    pub updates: usize,
+    /// Over the whole history, annualized
+    pub volatility: f64,
+    /// Highest rolling window, annualized; the bands are sized from it
+    pub peak_volatility: f64,
+    pub peak_volatility_end_time: i64,
+    pub max_confidence_bps: u64,
+    pub maintenance_margin_bps: u16,
+    pub breaker: BreakerConfig,
+}
+
+/// A move of `sigmas` standard deviations over `secs`, in log terms
+fn log_move(volatility: f64, secs: i64, sigmas: f64) -> f64 {
+    sigmas * volatility * (secs.max(0) as f64 / SECS_PER_YEAR).sqrt()
+}
+
+/// Confidence of each update in basis points, sorted
+fn confidence_bps_sorted(series: &PriceSeries) -> Vec<f64> {
+    let mut bps: Vec<f64> = (0..series.len())
+        .filter(|&i| series.price[i] != 0)
+        .map(|i| series.conf[i] as f64 * BPS_DENOMINATOR / series.price[i].unsigned_abs() as f64)
+        .collect();
+    bps.sort_by(f64::total_cmp);
+    bps
+}
+
+/// Parameters for `series`; `None` without a full volatility window
+pub fn suggest_risk_parameters(series: &PriceSeries, config: &SuggestionConfig) -> Option<RiskSuggestion> {
+    let rolling = series.rolling_volatility(config.interval_secs, config.window_intervals);
+    let peak = rolling.iter().copied().max_by(|a, b| a.volatility.total_cmp(&b.volatility))?;
+    let volatility = series.realized_volatility(config.interval_secs)?;
+
+    // A fall of the margin move, from the price the position was valued at
+    let fall = 1.0 - (-log_move(peak.volatility, config.liquidation_horizon_secs, config.margin_sigmas)).exp();
+    let maintenance_margin_bps = (fall * BPS_DENOMINATOR).ceil().clamp(1.0, BPS_DENOMINATOR) as u16;
+
+    // A rise of the breaker move is the larger of the two directions
+    let rise = log_move(peak.volatility, config.breaker_window_secs, config.breaker_sigmas).exp_m1();
+    let max_deviation_bps = (rise * BPS_DENOMINATOR).ceil().clamp(1.0, BPS_DENOMINATOR - 1.0) as u64;
+
+    let confidence = confidence_bps_sorted(series);
+    let rank = (config.confidence_quantile * confidence.len() as f64).ceil() as usize;
+    let quantile = confidence[rank.clamp(1, confidence.len()) - 1];
+    let max_confidence_bps = (quantile * (1.0 + config.confidence_headroom))
+        .ceil()
+        .min((maintenance_margin_bps / 2) as f64)
+        .max(1.0) as u64;
+
+    Some(RiskSuggestion {
+        feed_id: series.feed_id,
+        updates: series.len(),
+        volatility,
+        peak_volatility: peak.volatility,
+        peak_volatility_end_time: peak.end_time,
+        max_confidence_bps,
+        maintenance_margin_bps,
+        breaker: BreakerConfig {
+            max_deviation_bps,
+            window_secs: config.breaker_window_secs,
+            on_trip: TripAction::Restrict,
+        },
+    })
+}
@@ -0,0 +1,390 @@
+/**
+ * Shadow Pricing Against Exchange APIs
+ *
+ * Off-chain module that pulls reference prices from centralized exchanges
+ * and compares them with Pyth. A single divergent sample is noise; a
+ * divergence that persists for several consecutive samples raises an alert
+ * that may indicate a feed issue.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    thiserror = "1"
+ *    reqwest = { version = "0.11", features = ["blocking", "json"], optional = true }
+ *    serde = { version = "1", features = ["derive"], optional = true }
+ *
+ *    [features]
+ *    cex-reference = ["dep:reqwest", "dep:serde"]
+ *
+ * 2. Build with `--features cex-reference` to enable the Binance/Coinbase
+ *    sources. Without the feature only the comparison logic is compiled, so
+ *    you can plug in your own ReferencePriceSource.
+ */
+
+use std::collections::HashMap;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Default divergence that counts as an anomalous sample (1%)
+pub const DEFAULT_MAX_DIVERGENCE_BPS: u64 = 100;
+
+/// Default number of consecutive anomalous samples before alerting
+pub const DEFAULT_SUSTAINED_SAMPLES: u32 = 3;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum ShadowPricingError {
+    #[error("reference source {source_name} failed: {reason}")]
+    SourceFailed {
+        source_name: &'static str,
+        reason: String,
+    },
+
+    #[error("reference source {0} returned an unparseable price")]
+    InvalidPrice(&'static str),
+
+    #[error("no reference prices available for {0}")]
+    NoReferencePrices(String),
+}
+
+// ============================================================================
+// SYMBOL MAPPING
+// ============================================================================
+
+/// How one asset is named on Pyth and on each exchange
+#[derive(Clone, Debug)]
+pub struct SymbolMapping {
+    /// Pyth feed ID (hex with 0x prefix)
+    pub pyth_feed_id: &'static str,
+    /// Binance spot symbol (e.g. "SOLUSDT")
+    pub binance_symbol: &'static str,
+    /// Coinbase product ID (e.g. "SOL-USD")
+    pub coinbase_product: &'static str,
+}
+
+/// Mappings for commonly shadowed assets
+pub mod symbols {
+    use super::SymbolMapping;
+
+    pub const BTC_USD: SymbolMapping = SymbolMapping {
+        pyth_feed_id: "0xe62df6c8b4a85fe1a67db44dc12de5db330f7ac66b72dc658afedf0f4a415b43",
+        binance_symbol: "BTCUSDT",
+        coinbase_product: "BTC-USD",
+    };
+    pub const ETH_USD: SymbolMapping = SymbolMapping {
+        pyth_feed_id: "0xff61491a931112ddf1bd8147cd1b641375f79f5825126d665480874634fd0ace",
+        binance_symbol: "ETHUSDT",
+        coinbase_product: "ETH-USD",
+    };
+    pub const SOL_USD: SymbolMapping = SymbolMapping {
+        pyth_feed_id: "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d",
+        binance_symbol: "SOLUSDT",
+        coinbase_product: "SOL-USD",
+    };
+}
+
+// ============================================================================
+// REFERENCE SOURCES
+// ============================================================================
+
+/// A source of reference prices to shadow Pyth against
+pub trait ReferencePriceSource {
+    /// Short name used in alerts
+    fn name(&self) -> &'static str;
+
+    /// Fetch the current price for an asset in quote currency units
+    fn fetch_price(&self, mapping: &SymbolMapping) -> Result<f64, ShadowPricingError>;
+}
+
+#[cfg(feature = "cex-reference")]
+pub mod cex {
+    use super::*;
+    use serde::Deserialize;
+
+    const BINANCE_ENDPOINT: &str = "https://api.binance.com";
+    const COINBASE_ENDPOINT: &str = "https://api.coinbase.com";
+
+    fn source_failed(source_name: &'static str, err: reqwest::Error) -> ShadowPricingError {
+        ShadowPricingError::SourceFailed {
+            source_name,
+            reason: err.to_string(),
+        }
+    }
+
+    /// Binance spot ticker
+    pub struct BinanceSource {
+        http: reqwest::blocking::Client,
+        endpoint: String,
+    }
+
+    impl BinanceSource {
+        pub fn new() -> Self {
+            Self::with_endpoint(BINANCE_ENDPOINT)
+        }
+
+        pub fn with_endpoint(endpoint: &str) -> Self {
+            Self {
+                http: reqwest::blocking::Client::new(),
+                endpoint: endpoint.to_string(),
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct BinanceTicker {
+        price: String,
+    }
+
+    impl ReferencePriceSource for BinanceSource {
+        fn name(&self) -> &'static str {
+            "binance"
+        }
+
+        fn fetch_price(&self, mapping: &SymbolMapping) -> Result<f64, ShadowPricingError> {
+            let url = format!(
+                "{}/api/v3/ticker/price?symbol={}",
+                self.endpoint, mapping.binance_symbol
+            );
+            let ticker: BinanceTicker = self
+                .http
+                .get(url)
+                .send()
+                .and_then(|r| r.error_for_status())
+                .and_then(|r| r.json())
+                .map_err(|e| source_failed(self.name(), e))?;
+
+            ticker
+                .price
+                .parse()
+                .map_err(|_| ShadowPricingError::InvalidPrice(self.name()))
+        }
+    }
+
+    /// Coinbase spot price
+    pub struct CoinbaseSource {
+        http: reqwest::blocking::Client,
+        endpoint: String,
+    }
+
+    impl CoinbaseSource {
+        pub fn new() -> Self {
+            Self::with_endpoint(COINBASE_ENDPOINT)
+        }
+
+        pub fn with_endpoint(endpoint: &str) -> Self {
+            Self {
+                http: reqwest::blocking::Client::new(),
+                endpoint: endpoint.to_string(),
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct CoinbaseSpot {
+        data: CoinbaseAmount,
+    }
+
+    #[derive(Deserialize)]
+    struct CoinbaseAmount {
+        amount: String,
+    }
+
+    impl ReferencePriceSource for CoinbaseSource {
+        fn name(&self) -> &'static str {
+            "coinbase"
+        }
+
+        fn fetch_price(&self, mapping: &SymbolMapping) -> Result<f64, ShadowPricingError> {
+            let url = format!(
+                "{}/v2/prices/{}/spot",
+                self.endpoint, mapping.coinbase_product
+            );
+            let spot: CoinbaseSpot = self
+                .http
+                .get(url)
+                .send()
+                .and_then(|r| r.error_for_status())
+                .and_then(|r| r.json())
+                .map_err(|e| source_failed(self.name(), e))?;
+
+            spot.data
+                .amount
+                .parse()
+                .map_err(|_| ShadowPricingError::InvalidPrice(self.name()))
+        }
+    }
+}
+
+// ============================================================================
+// DIVERGENCE DETECTION
+// ============================================================================
+
+/// Configuration for shadow price comparison
+#[derive(Clone, Copy, Debug)]
+pub struct DivergenceConfig {
+    /// Divergence from the reference median that counts as anomalous
+    pub max_divergence_bps: u64,
+    /// Consecutive anomalous samples required before alerting
+    pub sustained_samples: u32,
+}
+
+impl Default for DivergenceConfig {
+    fn default() -> Self {
+        Self {
+            max_divergence_bps: DEFAULT_MAX_DIVERGENCE_BPS,
+            sustained_samples: DEFAULT_SUSTAINED_SAMPLES,
+        }
+    }
+}
+
+/// A reference price from one source
+#[derive(Clone, Debug)]
+pub struct ReferenceQuote {
+    pub source: &'static str,
+    pub price: f64,
+}
+
+/// Result of comparing one Pyth sample against the references
+#[derive(Clone, Debug)]
+pub struct ShadowCheck {
+    pub feed_id: String,
+    pub pyth_price: f64,
+    pub reference_median: f64,
+    pub divergence_bps: u64,
+    /// Consecutive anomalous samples including this one
+    pub streak: u32,
+    pub references: Vec<ReferenceQuote>,
+}
+
+impl ShadowCheck {
+    pub fn is_anomalous(&self, config: &DivergenceConfig) -> bool {
+        self.divergence_bps > config.max_divergence_bps
+    }
+}
+
+/// Raised once a divergence has persisted for `sustained_samples`
+#[derive(Clone, Debug)]
+pub struct DivergenceAlert {
+    pub feed_id: String,
+    pub pyth_price: f64,
+    pub reference_median: f64,
+    pub divergence_bps: u64,
+    pub consecutive_samples: u32,
+}
+
+/// Tracks divergence streaks per feed
+pub struct ShadowPriceMonitor {
+    config: DivergenceConfig,
+    streaks: HashMap<String, u32>,
+}
+
+impl ShadowPriceMonitor {
+    pub fn new(config: DivergenceConfig) -> Self {
+        Self {
+            config,
+            streaks: HashMap::new(),
+        }
+    }
+
+    /// Fetch references from every source and compare with a Pyth price
+    ///
+    /// Sources that fail are skipped; the check only errors when no source
+    /// returned a price.
+    pub fn check(
+        &mut self,
+        mapping: &SymbolMapping,
+        pyth_price: f64,
+        sources: &[&dyn ReferencePriceSource],
+    ) -> Result<(ShadowCheck, Option<DivergenceAlert>), ShadowPricingError> {
+        let references: Vec<ReferenceQuote> = sources
+            .iter()
+            .filter_map(|source| {
+                source.fetch_price(mapping).ok().map(|price| ReferenceQuote {
+                    source: source.name(),
+                    price,
+                })
+            })
+            .collect();
+
+        self.observe(mapping.pyth_feed_id, pyth_price, references)
+    }
+
+    /// Compare a Pyth price with already-fetched references
+    pub fn observe(
+        &mut self,
+        feed_id: &str,
+        pyth_price: f64,
+        references: Vec<ReferenceQuote>,
+    ) -> Result<(ShadowCheck, Option<DivergenceAlert>), ShadowPricingError> {
+        let reference_median = median(references.iter().map(|r| r.price).collect())
+            .ok_or_else(|| ShadowPricingError::NoReferencePrices(feed_id.to_string()))?;
+
+        let divergence_bps = divergence_bps(pyth_price, reference_median);
+        let streak = self.streaks.entry(feed_id.to_string()).or_insert(0);
+        if divergence_bps > self.config.max_divergence_bps {
+            *streak += 1;
+        } else {
+            *streak = 0;
+        }
+
+        let check = ShadowCheck {
+            feed_id: feed_id.to_string(),
+            pyth_price,
+            reference_median,
+            divergence_bps,
+            streak: *streak,
+            references,
+        };
+
+        let alert = (*streak >= self.config.sustained_samples).then(|| DivergenceAlert {
+            feed_id: feed_id.to_string(),
+            pyth_price,
+            reference_median,
+            divergence_bps,
+            consecutive_samples: *streak,
+        });
+
+        Ok((check, alert))
+    }
+
+    /// Forget the streak for a feed (e.g. after an operator acknowledges it)
+    pub fn reset(&mut self, feed_id: &str) {
+        self.streaks.remove(feed_id);
+    }
+}
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+/// Convert a Pyth fixed-point price to f64 for off-chain comparison
+pub fn pyth_price_to_f64(price: i64, exponent: i32) -> f64 {
+    price as f64 * 10f64.powi(exponent)
+}
+
+/// Absolute divergence between two prices in basis points of the reference
+pub fn divergence_bps(price: f64, reference: f64) -> u64 {
+    if reference == 0.0 {
+        return u64::MAX;
+    }
+    (((price - reference).abs() / reference.abs()) * 10_000.0).round() as u64
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    values.retain(|v| v.is_finite());
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mid = values.len() / 2;
+    Some(if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    })
+}
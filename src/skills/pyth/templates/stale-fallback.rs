@@ -0,0 +1,308 @@
+/**
+ * Stale Price Fallback Policy
+ *
+ * `get_validated_price` reverts once the freshest price is older than
+ * `max_age_secs`. During a feed outage that blocks every instruction that
+ * reads the feed, including the ones that only reduce risk (repaying,
+ * depositing), so most protocols prefer to degrade rather than halt. A
+ * `FallbackPolicy` decides what a stale price turns into:
+ *
+ * - `Reject`: the plain revert
+ * - `LastGoodWithHaircut { bps_per_minute }`: the last price that passed
+ *   validation, with its confidence widened by `bps_per_minute` for every
+ *   started minute past `max_age_secs`. `sell_price` (collateral) falls and
+ *   `buy_price` (debt) rises the longer the outage lasts; once the haircut
+ *   reaches `MAX_FALLBACK_HAIRCUT_BPS` the price is rejected after all.
+ * - `FreezeWithdrawalsOnly { max_stale_secs }`: the last good price
+ *   unchanged, flagged so that `require_outflows_allowed` rejects
+ *   withdrawals and borrows while deposits and repayments go through; once
+ *   the price is `max_stale_secs` past `max_age_secs` it is rejected after
+ *   all. Liquidations move collateral out too: call
+ *   `require_outflows_allowed` before seizing anything
+ *
+ * The last good price lives in a `LastGoodPrice` PDA per feed, written by
+ * `get_price_with_fallback` on every fresh price. Only staleness falls back:
+ * a wide confidence, a wrong feed, or a missing verification still reverts,
+ * since those say the price is wrong rather than late.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as `stale_fallback`)
+ * 2. Create one `LastGoodPrice` per feed with `init_last_good_price`
+ * 3. Replace `get_validated_price` with `get_price_with_fallback` where a
+ *    degraded price is acceptable, and call `require_outflows_allowed`
+ *    before releasing funds, liquidations included
+ */
+
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::oracle::{Now, OraclePrice, PriceSource};
+use crate::{get_validated_price, OracleError, PriceValidationConfig, RejectionReason, ValidatedPrice};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for last good prices: [LAST_GOOD_PRICE_SEED, feed_id]
+pub const LAST_GOOD_PRICE_SEED: &[u8] = b"last_good_price";
+
+/// Haircut at which a stale price is rejected after all (50%)
+pub const MAX_FALLBACK_HAIRCUT_BPS: u64 = 5_000;
+
+/// Longest a frozen price may be served past its max age (1 day)
+pub const MAX_FROZEN_STALE_SECS: u64 = 24 * 60 * 60;
+
+const BPS_DENOMINATOR: u64 = 10_000;
+
+const SECONDS_PER_MINUTE: u64 = 60;
+
+// ============================================================================
+// POLICY
+// ============================================================================
+
+/// What a price older than `max_age_secs` turns into
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum FallbackPolicy {
+    /// Revert, as `get_validated_price` does
+    Reject,
+    /// Serve the last good price, its confidence widened by `bps_per_minute`
+    /// of the price per started minute of staleness
+    LastGoodWithHaircut { bps_per_minute: u64 },
+    /// Serve the last good price, but reject withdrawals and borrows, for
+    /// at most `max_stale_secs` past the max age
+    FreezeWithdrawalsOnly { max_stale_secs: u64 },
+}
+
+impl FallbackPolicy {
+    pub fn validate(&self) -> Result<()> {
+        match *self {
+            FallbackPolicy::Reject => {}
+            FallbackPolicy::LastGoodWithHaircut { bps_per_minute } => require!(
+                bps_per_minute > 0 && bps_per_minute <= MAX_FALLBACK_HAIRCUT_BPS,
+                OracleError::InvalidFallbackPolicy
+            ),
+            FallbackPolicy::FreezeWithdrawalsOnly { max_stale_secs } => require!(
+                max_stale_secs > 0 && max_stale_secs <= MAX_FROZEN_STALE_SECS,
+                OracleError::InvalidFallbackPolicy
+            ),
+        }
+        Ok(())
+    }
+}
+
+/// How the returned price was obtained
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum PriceFreshness {
+    Fresh,
+    /// Last good price, confidence widened by `haircut_bps`
+    Haircut { haircut_bps: u64 },
+    /// Last good price; outflows are frozen
+    WithdrawalsFrozen,
+}
+
+/// A price and whether it came from the fallback
+#[derive(Clone, Copy, Debug)]
+pub struct FallbackPrice {
+    pub price: ValidatedPrice,
+    pub freshness: PriceFreshness,
+}
+
+impl FallbackPrice {
+    pub fn is_fresh(&self) -> bool {
+        self.freshness == PriceFreshness::Fresh
+    }
+
+    /// Fail with `WithdrawalsFrozen` under `FreezeWithdrawalsOnly`; call
+    /// before any withdrawal, borrow or liquidation priced by this price
+    pub fn require_outflows_allowed(&self) -> Result<()> {
+        require!(
+            self.freshness != PriceFreshness::WithdrawalsFrozen,
+            OracleError::WithdrawalsFrozen
+        );
+        Ok(())
+    }
+}
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// Last price of one feed that passed full validation
+#[account]
+pub struct LastGoodPrice {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    /// 0 until the first price
+    pub publish_time: i64,
+    pub bump: u8,
+}
+
+impl LastGoodPrice {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 4 + 8 + 1;
+
+    /// Store a validated price; an older one is ignored
+    pub fn record(&mut self, price: &ValidatedPrice) -> Result<()> {
+        require!(price.feed_id == self.feed_id, OracleError::FeedIdMismatch);
+        if price.publish_time > self.publish_time {
+            self.price = price.price;
+            self.conf = price.conf;
+            self.exponent = price.exponent;
+            self.publish_time = price.publish_time;
+        }
+        Ok(())
+    }
+
+    fn oracle_price(&self) -> OraclePrice {
+        OraclePrice {
+            price: self.price,
+            conf: self.conf,
+            exponent: self.exponent,
+            publish_time: self.publish_time,
+        }
+    }
+}
+
+/// Haircut of a price `stale_secs` past its max age: `bps_per_minute` per
+/// started minute
+pub fn staleness_haircut_bps(stale_secs: u64, bps_per_minute: u64) -> u64 {
+    stale_secs
+        .div_ceil(SECONDS_PER_MINUTE)
+        .saturating_mul(bps_per_minute)
+}
+
+// ============================================================================
+// READ PATH
+// ============================================================================
+
+/// `get_validated_price`, falling back to `last_good` under `policy` when
+/// the price is only too old
+///
+/// A fresh price is recorded in `last_good`. The stale error is returned
+/// when the policy is `Reject`, no price was recorded yet, the haircut
+/// reached `MAX_FALLBACK_HAIRCUT_BPS`, or a frozen price is more than
+/// `max_stale_secs` past the max age.
+pub fn get_price_with_fallback<S: PriceSource>(
+    source: &S,
+    config: &PriceValidationConfig,
+    policy: FallbackPolicy,
+    last_good: &mut LastGoodPrice,
+    clock: &impl Now,
+) -> Result<FallbackPrice> {
+    let config = PriceValidationConfig {
+        expected_feed_id: Some(last_good.feed_id),
+        ..*config
+    };
+    let stale = match get_validated_price(source, &config, clock) {
+        Ok(price) => {
+            last_good.record(&price)?;
+            return Ok(FallbackPrice { price, freshness: PriceFreshness::Fresh });
+        }
+        Err(error) if RejectionReason::from_error(&error) == RejectionReason::Stale => error,
+        Err(error) => return Err(error),
+    };
+    if last_good.publish_time == 0 {
+        return Err(stale);
+    }
+
+    let last = last_good.oracle_price();
+    let age = clock.unix_timestamp().saturating_sub(last.publish_time).max(0) as u64;
+    let stale_secs = age.saturating_sub(config.max_age_secs);
+    match policy {
+        FallbackPolicy::Reject => Err(stale),
+        FallbackPolicy::FreezeWithdrawalsOnly { max_stale_secs } => {
+            if stale_secs > max_stale_secs {
+                return Err(stale);
+            }
+            Ok(FallbackPrice {
+                price: ValidatedPrice::from_price(last_good.feed_id, &last),
+                freshness: PriceFreshness::WithdrawalsFrozen,
+            })
+        }
+        FallbackPolicy::LastGoodWithHaircut { bps_per_minute } => {
+            let haircut_bps = staleness_haircut_bps(stale_secs, bps_per_minute);
+            if haircut_bps >= MAX_FALLBACK_HAIRCUT_BPS {
+                return Err(stale);
+            }
+
+            // Rounded up, against the user on both sides
+            let widening = (last.price.unsigned_abs() as u128 * haircut_bps as u128)
+                .div_ceil(BPS_DENOMINATOR as u128) as u64;
+            let widened = OraclePrice {
+                conf: last.conf.saturating_add(widening),
+                ..last
+            };
+            Ok(FallbackPrice {
+                price: ValidatedPrice::from_price(last_good.feed_id, &widened),
+                freshness: PriceFreshness::Haircut { haircut_bps },
+            })
+        }
+    }
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(feed_id: [u8; 32])]
+pub struct InitLastGoodPrice<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = LastGoodPrice::LEN,
+        seeds = [LAST_GOOD_PRICE_SEED, feed_id.as_ref()],
+        bump
+    )]
+    pub last_good_price: Account<'info, LastGoodPrice>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Anyone may refresh a last good price, since only validated prices are
+/// stored
+#[derive(Accounts)]
+pub struct FallbackPriceContext<'info> {
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    #[account(
+        mut,
+        seeds = [LAST_GOOD_PRICE_SEED, last_good_price.feed_id.as_ref()],
+        bump = last_good_price.bump
+    )]
+    pub last_good_price: Account<'info, LastGoodPrice>,
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn init_last_good_price(ctx: Context<InitLastGoodPrice>, feed_id: [u8; 32]) -> Result<()> {
+    let last_good = &mut ctx.accounts.last_good_price;
+    last_good.feed_id = feed_id;
+    last_good.price = 0;
+    last_good.conf = 0;
+    last_good.exponent = 0;
+    last_good.publish_time = 0;
+    last_good.bump = ctx.bumps.last_good_price;
+    Ok(())
+}
+
+pub fn get_fallback_price(ctx: Context<FallbackPriceContext>, policy: FallbackPolicy) -> Result<()> {
+    policy.validate()?;
+    let clock = Clock::get()?;
+    let price = get_price_with_fallback(
+        &ctx.accounts.price_update,
+        &PriceValidationConfig::default(),
+        policy,
+        &mut ctx.accounts.last_good_price,
+        &clock,
+    )?;
+    msg!("Price: {} × 10^{} ({:?})", price.price.price, price.price.exponent, price.freshness);
+    msg!("Bounds: [{}, {}]", price.price.lower_bound, price.price.upper_bound);
+    Ok(())
+}
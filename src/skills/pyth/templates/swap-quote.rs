@@ -0,0 +1,145 @@
+/**
+ * Conservative `min_amount_out` for Oracle Swaps (Off-Chain)
+ *
+ * `swap_with_oracle` fails with `SlippageExceeded` when it would pay less
+ * than the caller's `min_amount_out`. Set it too high and the swap fails
+ * on ordinary price movement; set it by hand and it is either that or no
+ * protection at all. `min_amount_out` derives it from the Hermes prices
+ * the client is about to post and the `Market` accounts of both mints
+ * (market.rs), fetched from the cluster:
+ *
+ * 1. `expected`: the output at those prices, from `quote_swap`
+ *    (oracle-swap.rs), the function the program itself calls, so the
+ *    quote matches a swap landing at the same prices to the base unit
+ * 2. `worst_case`: the output if each leg's confidence has widened by
+ *    `confidence_widening_bps` of its price by the time the swap lands; a
+ *    wider interval lowers the input's value, raises the output's price,
+ *    and raises the fee
+ * 3. `min_amount_out`: `worst_case` less `slippage_bps`, for the prices
+ *    themselves moving
+ *
+ * The quote charges the output market's `swap_fee`, as the program does.
+ * Prices the program would reject (another feed than its market's,
+ * confidence above the market's limit, or a pair outside the tighter of
+ * the two markets' `pair` limits) fail here with `FeedIdMismatch`, `ConfidenceTooHigh`, or
+ * `PricesNotSynchronized` instead of in the transaction. Staleness,
+ * pauses, and breakers are not checked: quote from updates fetched just
+ * before posting them.
+ *
+ * Setup:
+ * 1. Declared by anchor-oracle.rs behind the `hermes` feature
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::hermes::ParsedPriceUpdate;
+use crate::{
+    deduct_fee, quote_swap, validate_confidence, DynamicFeeConfig, Market, OracleError, OraclePrice, PricedPair,
+    SwapQuote, ValidatedPrice,
+};
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// How far below the quote a swap may still land
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlippageTolerance {
+    /// Price movement between quoting and landing
+    pub slippage_bps: u16,
+    /// Confidence each leg may gain by then, in basis points of its price
+    pub confidence_widening_bps: u16,
+}
+
+impl Default for SlippageTolerance {
+    /// 0.5% of price movement; confidence up to 0.1% wider
+    fn default() -> Self {
+        Self {
+            slippage_bps: 50,
+            confidence_widening_bps: 10,
+        }
+    }
+}
+
+/// A quote and the `min_amount_out` to send with it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MinAmountOut {
+    /// At the quoted prices
+    pub expected: SwapQuote,
+    /// At the quoted prices with widened confidence
+    pub worst_case: SwapQuote,
+    pub min_amount_out: u64,
+}
+
+/// `price` with its confidence widened by `widening_bps` of the price,
+/// rounded up
+fn widen(price: &ValidatedPrice, widening_bps: u16) -> ValidatedPrice {
+    let extra = (price.price.unsigned_abs() as u128 * widening_bps as u128).div_ceil(BPS_DENOMINATOR);
+    let conf = (price.conf as u128 + extra).min(u64::MAX as u128) as u64;
+    let oracle_price = OraclePrice {
+        price: price.price,
+        conf,
+        exponent: price.exponent,
+        publish_time: price.publish_time,
+    };
+    ValidatedPrice::from_price(price.feed_id, &oracle_price)
+}
+
+/// `min_amount_out` for prices already validated or converted
+pub fn min_amount_out_for_prices(
+    amount_in: u64,
+    input_price: &ValidatedPrice,
+    input_decimals: u8,
+    output_price: &ValidatedPrice,
+    output_decimals: u8,
+    fee: &DynamicFeeConfig,
+    tolerance: &SlippageTolerance,
+) -> Result<MinAmountOut> {
+    let expected = quote_swap(amount_in, input_price, input_decimals, output_price, output_decimals, fee)?;
+    let worst_case = quote_swap(
+        amount_in,
+        &widen(input_price, tolerance.confidence_widening_bps),
+        input_decimals,
+        &widen(output_price, tolerance.confidence_widening_bps),
+        output_decimals,
+        fee,
+    )?;
+    let (min_amount_out, _) = deduct_fee(worst_case.amount_out, tolerance.slippage_bps.min(10_000))?;
+    Ok(MinAmountOut {
+        expected,
+        worst_case,
+        min_amount_out,
+    })
+}
+
+/// `update` checked against what `swap_with_oracle` requires of it
+fn market_price(update: &ParsedPriceUpdate, market: &Market) -> Result<ValidatedPrice> {
+    require!(update.feed_id == market.feed_id, OracleError::FeedIdMismatch);
+    let price = OraclePrice::from(update.price);
+    validate_confidence(&price, market.oracle.max_confidence_bps)?;
+    Ok(ValidatedPrice::from_price(update.feed_id, &price))
+}
+
+/// `min_amount_out` for swapping `amount_in` at the prices of two Hermes
+/// updates, the ones the client will post for the swap
+pub fn min_amount_out(
+    amount_in: u64,
+    input: &ParsedPriceUpdate,
+    input_market: &Market,
+    output: &ParsedPriceUpdate,
+    output_market: &Market,
+    tolerance: &SlippageTolerance,
+) -> Result<MinAmountOut> {
+    let pair = PricedPair::try_new(
+        market_price(input, input_market)?,
+        market_price(output, output_market)?,
+        &input_market.pair.tighter(output_market.pair),
+    )?;
+    min_amount_out_for_prices(
+        amount_in,
+        &pair.input,
+        input_market.decimals,
+        &pair.output,
+        output_market.decimals,
+        &output_market.swap_fee,
+        tolerance,
+    )
+}
@@ -0,0 +1,222 @@
+/**
+ * Test Utilities - Mock PriceUpdateV2 Accounts
+ *
+ * Builds realistic `PriceUpdateV2` account data (discriminator, write
+ * authority, verification level, price message, posted slot) owned by the
+ * Pyth receiver, for testing programs that call `get_validated_price`
+ * under solana-program-test, LiteSVM, or bankrun.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    pythnet-sdk = "2"
+ *    solana-sdk = { version = "1.18", optional = true }
+ *    base64 = { version = "0.21", optional = true }
+ *    serde_json = { version = "1", optional = true }
+ *
+ *    [features]
+ *    test-utils = ["dep:solana-sdk", "dep:base64", "dep:serde_json"]
+ *
+ *    # In the consuming program
+ *    [dev-dependencies]
+ *    pyth-oracle = { path = "../pyth-oracle", features = ["no-entrypoint", "test-utils"] }
+ *
+ * 2. Declared by anchor-oracle.rs behind the `test-utils` feature
+ */
+
+use anchor_lang::prelude::*;
+use base64::Engine;
+use pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, VerificationLevel};
+use pythnet_sdk::messages::PriceFeedMessage;
+
+use crate::PYTH_RECEIVER_PROGRAM_ID;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Default clock time the builder publishes at; set the test clock to
+/// `PriceUpdateBuilder::reference_time()` (this value unless overridden)
+pub const DEFAULT_TEST_TIMESTAMP: i64 = 1_700_000_000;
+
+/// Default mock price: 100.00000000 (exponent -8)
+pub const DEFAULT_TEST_PRICE: i64 = 100_0000_0000;
+
+pub const DEFAULT_TEST_EXPONENT: i32 = -8;
+
+// ============================================================================
+// BUILDER
+// ============================================================================
+
+/// Builder for mock `PriceUpdateV2` accounts
+///
+/// ```ignore
+/// let account = PriceUpdateBuilder::new(parse_feed_id(price_feeds::SOL_USD)?)
+///     .with_price(150_0000_0000, -8)
+///     .with_confidence_bps(50)
+///     .stale_by(90)
+///     .to_account();
+/// svm.set_account(price_update_key, account)?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct PriceUpdateBuilder {
+    feed_id: [u8; 32],
+    price: i64,
+    conf: u64,
+    exponent: i32,
+    ema_price: Option<i64>,
+    ema_conf: Option<u64>,
+    reference_time: i64,
+    publish_time: i64,
+    prev_publish_time: Option<i64>,
+    verification_level: VerificationLevel,
+    write_authority: Pubkey,
+    posted_slot: u64,
+}
+
+impl PriceUpdateBuilder {
+    /// Fully verified, fresh update at `DEFAULT_TEST_PRICE` with zero confidence
+    pub fn new(feed_id: [u8; 32]) -> Self {
+        Self {
+            feed_id,
+            price: DEFAULT_TEST_PRICE,
+            conf: 0,
+            exponent: DEFAULT_TEST_EXPONENT,
+            ema_price: None,
+            ema_conf: None,
+            reference_time: DEFAULT_TEST_TIMESTAMP,
+            publish_time: DEFAULT_TEST_TIMESTAMP,
+            prev_publish_time: None,
+            verification_level: VerificationLevel::Full,
+            write_authority: Pubkey::default(),
+            posted_slot: 0,
+        }
+    }
+
+    pub fn with_price(mut self, price: i64, exponent: i32) -> Self {
+        self.price = price;
+        self.exponent = exponent;
+        self
+    }
+
+    pub fn with_conf(mut self, conf: u64) -> Self {
+        self.conf = conf;
+        self
+    }
+
+    /// Confidence as basis points of the price (call after `with_price`)
+    pub fn with_confidence_bps(mut self, bps: u64) -> Self {
+        self.conf = (self.price.unsigned_abs() as u128 * bps as u128 / 10_000) as u64;
+        self
+    }
+
+    /// EMA price and confidence (defaults to the spot values)
+    pub fn with_ema(mut self, ema_price: i64, ema_conf: u64) -> Self {
+        self.ema_price = Some(ema_price);
+        self.ema_conf = Some(ema_conf);
+        self
+    }
+
+    /// Clock time the test runs at; publish time moves with it
+    pub fn at_time(mut self, unix_timestamp: i64) -> Self {
+        let age = self.reference_time - self.publish_time;
+        self.reference_time = unix_timestamp;
+        self.publish_time = unix_timestamp - age;
+        self
+    }
+
+    /// Publish `secs` before the reference time
+    pub fn stale_by(mut self, secs: i64) -> Self {
+        self.publish_time = self.reference_time - secs;
+        self
+    }
+
+    pub fn published_at(mut self, publish_time: i64) -> Self {
+        self.publish_time = publish_time;
+        self
+    }
+
+    pub fn with_verification_level(mut self, level: VerificationLevel) -> Self {
+        self.verification_level = level;
+        self
+    }
+
+    pub fn partially_verified(self, num_signatures: u8) -> Self {
+        self.with_verification_level(VerificationLevel::Partial { num_signatures })
+    }
+
+    pub fn with_write_authority(mut self, write_authority: Pubkey) -> Self {
+        self.write_authority = write_authority;
+        self
+    }
+
+    pub fn posted_at_slot(mut self, slot: u64) -> Self {
+        self.posted_slot = slot;
+        self
+    }
+
+    /// Time the test clock should be set to
+    pub fn reference_time(&self) -> i64 {
+        self.reference_time
+    }
+
+    pub fn build(&self) -> PriceUpdateV2 {
+        PriceUpdateV2 {
+            write_authority: self.write_authority,
+            verification_level: self.verification_level,
+            price_message: PriceFeedMessage {
+                feed_id: self.feed_id,
+                price: self.price,
+                conf: self.conf,
+                exponent: self.exponent,
+                publish_time: self.publish_time,
+                prev_publish_time: self.prev_publish_time.unwrap_or(self.publish_time - 1),
+                ema_price: self.ema_price.unwrap_or(self.price),
+                ema_conf: self.ema_conf.unwrap_or(self.conf),
+            },
+            posted_slot: self.posted_slot,
+        }
+    }
+
+    /// Serialized account data: discriminator + borsh body, padded to
+    /// `PriceUpdateV2::LEN` like accounts created by the receiver
+    pub fn to_account_data(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(PriceUpdateV2::LEN);
+        self.build()
+            .try_serialize(&mut data)
+            .expect("PriceUpdateV2 serializes");
+        data.resize(PriceUpdateV2::LEN.max(data.len()), 0);
+        data
+    }
+
+    /// Rent-exempt account owned by the Pyth receiver (solana-program-test,
+    /// LiteSVM `set_account`)
+    pub fn to_account(&self) -> solana_sdk::account::Account {
+        let data = self.to_account_data();
+        solana_sdk::account::Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: PYTH_RECEIVER_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    /// Account in the JSON format of `solana account --output json`, loadable
+    /// by `solana-test-validator --account` and bankrun fixtures
+    pub fn to_account_json(&self, address: &Pubkey) -> String {
+        let account = self.to_account();
+        serde_json::json!({
+            "pubkey": address.to_string(),
+            "account": {
+                "lamports": account.lamports,
+                "data": [base64::engine::general_purpose::STANDARD.encode(&account.data), "base64"],
+                "owner": account.owner.to_string(),
+                "executable": false,
+                "rentEpoch": 0,
+                "space": account.data.len(),
+            }
+        })
+        .to_string()
+    }
+}
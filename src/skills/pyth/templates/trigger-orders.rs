@@ -0,0 +1,268 @@
+/**
+ * Trigger Orders: Stop-Losses and Buy-Stops on Oracle Prices
+ *
+ * A `TriggerOrder` PDA records a price, a direction, and an expiry. Once
+ * the oracle price crosses the trigger, anyone may call `execute_trigger`:
+ * keepers race to execute, and the order's owner does not need to be
+ * online when the market moves.
+ *
+ * The trigger is checked against the conservative edge of the confidence
+ * band, not the price itself:
+ *
+ *   - `Below` (sell-stop, stop-loss on a long) fires once the band's lower
+ *     bound reaches the trigger
+ *   - `Above` (buy-stop, stop-loss on a short) fires once the band's upper
+ *     bound reaches the trigger
+ *
+ * A stop exists to limit losses, so it fires as soon as the oracle cannot
+ * rule out that the price has crossed. A wide band during volatility fires
+ * stops earlier, never later. The trigger is rounded away from the price
+ * when rescaled to the update's exponent, so rounding alone never fires an
+ * order.
+ *
+ * Executing closes the order to its owner and emits `TriggerExecuted` with
+ * the price that fired it. The action itself (a swap through
+ * oracle-swap.rs, closing a position) is the integrating program's; add it
+ * where marked in `execute_trigger`. Expired orders cannot execute; their
+ * owners close them with `cancel_trigger_order`.
+ *
+ * Setup:
+ * 1. Copy next to anchor-oracle.rs (it is declared there as
+ *    `trigger_orders`)
+ * 2. Add the order's action and its accounts to `execute_trigger`
+ * 3. Run a watcher such as examples/off-chain/trigger-watcher.rs, or
+ *    reward executors so third-party keepers do
+ */
+
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::{get_validated_price, OracleDecimal, OracleError, PriceValidationConfig, Rounding, ValidatedPrice};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed for trigger orders: [TRIGGER_ORDER_SEED, owner, order_id (LE)]
+pub const TRIGGER_ORDER_SEED: &[u8] = b"trigger_order";
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// Which crossing fires the order
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum TriggerDirection {
+    /// Sell-stop: fires when the lower bound is at or below the trigger
+    Below,
+    /// Buy-stop: fires when the upper bound is at or above the trigger
+    Above,
+}
+
+/// Order terms, as passed to `create_trigger_order`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct TriggerOrderParams {
+    pub feed_id: [u8; 32],
+    pub direction: TriggerDirection,
+    /// Trigger price, `trigger_price × 10^trigger_exponent`
+    pub trigger_price: i64,
+    pub trigger_exponent: i32,
+    /// What to act on when triggered, in the integrating program's units
+    pub amount: u64,
+    /// Unix timestamp after which the order can no longer execute
+    pub expires_at: i64,
+}
+
+impl TriggerOrderParams {
+    pub fn validate(&self, now: i64) -> Result<()> {
+        require!(self.trigger_price > 0, OracleError::InvalidTriggerOrder);
+        require!(self.amount > 0, OracleError::InvalidTriggerOrder);
+        require!(self.expires_at > now, OracleError::InvalidTriggerOrder);
+        Ok(())
+    }
+}
+
+#[account]
+pub struct TriggerOrder {
+    pub owner: Pubkey,
+    pub order_id: u64,
+    pub feed_id: [u8; 32],
+    pub direction: TriggerDirection,
+    pub trigger_price: i64,
+    pub trigger_exponent: i32,
+    pub amount: u64,
+    pub expires_at: i64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl TriggerOrder {
+    pub const LEN: usize = 8 + 32 + 8 + 32 + 1 + 8 + 4 + 8 + 8 + 8 + 1;
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now > self.expires_at
+    }
+
+    /// Trigger price at `exponent`, rounded away from the price so that
+    /// rounding never fires the order
+    pub fn trigger_at(&self, exponent: i32) -> Result<i64> {
+        let rounding = match self.direction {
+            TriggerDirection::Below => Rounding::Floor,
+            TriggerDirection::Above => Rounding::Ceil,
+        };
+        OracleDecimal::from_price(self.trigger_price, self.trigger_exponent)?.to_i64(exponent, rounding)
+    }
+
+    /// Whether `price` has crossed the trigger at its conservative bound
+    pub fn is_triggered(&self, price: &ValidatedPrice) -> Result<bool> {
+        require!(price.feed_id == self.feed_id, OracleError::FeedIdMismatch);
+        let trigger = self.trigger_at(price.exponent)?;
+        Ok(match self.direction {
+            TriggerDirection::Below => price.lower_bound <= trigger,
+            TriggerDirection::Above => price.upper_bound >= trigger,
+        })
+    }
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct TriggerExecuted {
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub executor: Pubkey,
+    pub feed_id: [u8; 32],
+    pub direction: TriggerDirection,
+    pub trigger_price: i64,
+    pub trigger_exponent: i32,
+    pub amount: u64,
+    /// The price that fired the order
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct CreateTriggerOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = TriggerOrder::LEN,
+        seeds = [TRIGGER_ORDER_SEED, owner.key().as_ref(), &order_id.to_le_bytes()],
+        bump
+    )]
+    pub trigger_order: Account<'info, TriggerOrder>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTriggerOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [TRIGGER_ORDER_SEED, owner.key().as_ref(), &trigger_order.order_id.to_le_bytes()],
+        bump = trigger_order.bump,
+        has_one = owner
+    )]
+    pub trigger_order: Account<'info, TriggerOrder>,
+}
+
+/// Permissionless: any keeper may execute a triggered order
+#[derive(Accounts)]
+pub struct ExecuteTrigger<'info> {
+    pub executor: Signer<'info>,
+
+    /// Receives the order's rent
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [TRIGGER_ORDER_SEED, owner.key().as_ref(), &trigger_order.order_id.to_le_bytes()],
+        bump = trigger_order.bump,
+        has_one = owner
+    )]
+    pub trigger_order: Account<'info, TriggerOrder>,
+
+    // Add the accounts of the order's action
+}
+
+// ============================================================================
+// INSTRUCTION HANDLERS
+// ============================================================================
+
+pub fn create_trigger_order(ctx: Context<CreateTriggerOrder>, order_id: u64, params: TriggerOrderParams) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    params.validate(now)?;
+
+    let order = &mut ctx.accounts.trigger_order;
+    order.owner = ctx.accounts.owner.key();
+    order.order_id = order_id;
+    order.feed_id = params.feed_id;
+    order.direction = params.direction;
+    order.trigger_price = params.trigger_price;
+    order.trigger_exponent = params.trigger_exponent;
+    order.amount = params.amount;
+    order.expires_at = params.expires_at;
+    order.created_at = now;
+    order.bump = ctx.bumps.trigger_order;
+    Ok(())
+}
+
+/// Close an open or expired order; the rent returns to the owner
+pub fn cancel_trigger_order(_ctx: Context<CancelTriggerOrder>) -> Result<()> {
+    Ok(())
+}
+
+/// Execute an order whose trigger the current price has crossed. Fails
+/// with `TriggerOrderExpired` after expiry and `TriggerNotReached` before
+/// the crossing.
+pub fn execute_trigger(ctx: Context<ExecuteTrigger>) -> Result<()> {
+    let clock = Clock::get()?;
+    let order = &ctx.accounts.trigger_order;
+    require!(!order.is_expired(clock.unix_timestamp), OracleError::TriggerOrderExpired);
+
+    let config = PriceValidationConfig {
+        expected_feed_id: Some(order.feed_id),
+        ..PriceValidationConfig::default()
+    };
+    let price = get_validated_price(&ctx.accounts.price_update, &config, &clock)?;
+    require!(order.is_triggered(&price)?, OracleError::TriggerNotReached);
+
+    // Perform the order's action for order.amount here: swap, reduce or
+    // close the position...
+
+    emit!(TriggerExecuted {
+        order: order.key(),
+        owner: order.owner,
+        executor: ctx.accounts.executor.key(),
+        feed_id: order.feed_id,
+        direction: order.direction,
+        trigger_price: order.trigger_price,
+        trigger_exponent: order.trigger_exponent,
+        amount: order.amount,
+        price: price.price,
+        conf: price.conf,
+        exponent: price.exponent,
+        publish_time: price.publish_time,
+    });
+    Ok(())
+}
@@ -0,0 +1,230 @@
+/**
+ * Inline VAA Verification: Prices Without a Posted Account
+ *
+ * `PostAndConsumePrice` (post-update.rs) needs the Pyth receiver to write
+ * a `PriceUpdateV2` account first: an ephemeral signer, rent, and a CPI
+ * into the receiver. This path skips the account. The instruction carries
+ * the Hermes accumulator update (the VAA and one merkle price update) as
+ * data, and the program checks it itself, the way the receiver would:
+ *
+ * 1. The guardian set account is the Wormhole receiver's PDA for the VAA's
+ *    guardian set index, and has not expired
+ * 2. Each guardian signature recovers (secp256k1) to that guardian's key,
+ *    with guardian indices strictly increasing so none counts twice
+ * 3. The VAA was emitted by the Pyth accumulator on Pythnet
+ * 4. The merkle proof places the price message under the VAA's root
+ *
+ * The result is an in-memory `PriceUpdateV2`, so it goes through the same
+ * `get_validated_price` checks as a posted account. Its verification level
+ * is `Full` when the signatures reach the guardian quorum and
+ * `Partial { num_signatures }` otherwise; the validation config decides
+ * whether that is enough.
+ *
+ * The Wormhole receiver verifies VAAs only in `EncodedVaa` accounts, which
+ * take several transactions to write, so there is no CPI that checks an
+ * inline VAA and returns. The guardian set is still Wormhole's: it is read
+ * from the account the Wormhole receiver owns, at its PDA.
+ *
+ * Cost: one `secp256k1_recover` (about 25,000 CU) per signature. Trim the
+ * VAA to what the config requires (`trim_vaa_signatures`) and raise the
+ * compute limit; 5 signatures fit a single transaction.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    pythnet-sdk = "2"
+ *    byteorder = "1"
+ *
+ * 2. Declared by anchor-oracle.rs; consume with `InlinePriceContext`
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::secp256k1_recover::secp256k1_recover;
+use pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, VerificationLevel};
+use pythnet_sdk::accumulators::merkle::MerkleRoot;
+use pythnet_sdk::hashers::keccak256_160::Keccak160;
+use pythnet_sdk::messages::Message;
+use pythnet_sdk::wire::from_slice;
+use pythnet_sdk::wire::v1::{MerklePriceUpdate, WormholeMessage, WormholePayload};
+
+use crate::oracle::Now;
+use crate::post_update::WORMHOLE_RECEIVER_PROGRAM_ID;
+use crate::OracleError;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// PDA seed of guardian sets under the Wormhole receiver:
+/// [GUARDIAN_SET_SEED, index (BE)]
+pub const GUARDIAN_SET_SEED: &[u8] = b"GuardianSet";
+
+/// Wormhole chain ID of Pythnet
+pub const PYTHNET_CHAIN_ID: u16 = 26;
+
+/// Wormhole emitter of the Pyth accumulator on Pythnet
+pub const PYTH_ACCUMULATOR_EMITTER: [u8; 32] = [
+    0xe1, 0x01, 0xfa, 0xed, 0xac, 0x58, 0x51, 0xe3, 0x2b, 0x9b, 0x23, 0xb5, 0xf9, 0x41, 0x1a, 0x8c, 0x2b, 0xac, 0x4a,
+    0xae, 0x3e, 0xd4, 0xdd, 0x7b, 0x81, 0x1d, 0xd1, 0xa7, 0x2e, 0xa4, 0xaa, 0x71,
+];
+
+/// VAA header: version (1) + guardian set index (4) + signature count (1)
+const VAA_HEADER_LEN: usize = 6;
+/// Guardian index (1) + secp256k1 signature (64) + recovery ID (1)
+const VAA_SIGNATURE_LEN: usize = 66;
+/// Body before the payload: timestamp (4) + nonce (4) + emitter chain (2) +
+/// emitter address (32) + sequence (8) + consistency level (1)
+const VAA_BODY_HEADER_LEN: usize = 51;
+
+// ============================================================================
+// GUARDIAN SET
+// ============================================================================
+
+/// Guardian set as the Wormhole receiver stores it (legacy layout, no
+/// discriminator)
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct GuardianSet {
+    pub index: u32,
+    /// Ethereum addresses of the guardians' secp256k1 keys
+    pub keys: Vec<[u8; 20]>,
+    pub creation_time: u32,
+    /// 0 while the set is current
+    pub expiration_time: u32,
+}
+
+impl GuardianSet {
+    /// Load and check the guardian set account for `index`
+    pub fn load(account: &AccountInfo, index: u32, now: i64) -> Result<Self> {
+        require_keys_eq!(*account.owner, WORMHOLE_RECEIVER_PROGRAM_ID, OracleError::InvalidGuardianSet);
+        let (address, _) =
+            Pubkey::find_program_address(&[GUARDIAN_SET_SEED, &index.to_be_bytes()], &WORMHOLE_RECEIVER_PROGRAM_ID);
+        require_keys_eq!(account.key(), address, OracleError::InvalidGuardianSet);
+
+        let data = account.try_borrow_data()?;
+        let set = GuardianSet::deserialize(&mut &data[..]).map_err(|_| OracleError::InvalidGuardianSet)?;
+        require!(set.index == index, OracleError::InvalidGuardianSet);
+        require!(set.is_active(now), OracleError::InvalidGuardianSet);
+        Ok(set)
+    }
+
+    pub fn is_active(&self, now: i64) -> bool {
+        self.expiration_time == 0 || i64::from(self.expiration_time) > now
+    }
+
+    /// Signatures needed for a full verification: more than two thirds
+    pub fn quorum(&self) -> usize {
+        self.keys.len() * 2 / 3 + 1
+    }
+}
+
+// ============================================================================
+// VERIFICATION
+// ============================================================================
+
+/// A VAA whose guardian signatures have been checked
+pub struct VerifiedVaa<'a> {
+    pub emitter_chain: u16,
+    pub emitter_address: [u8; 32],
+    pub payload: &'a [u8],
+    pub verification_level: VerificationLevel,
+}
+
+/// Guardian set index from the VAA header
+pub fn vaa_guardian_set_index(vaa: &[u8]) -> Result<u32> {
+    let bytes = vaa.get(1..5).ok_or(OracleError::InvalidVaa)?;
+    Ok(u32::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+/// Check every signature of `vaa` against the guardian set account
+pub fn verify_vaa<'a>(guardian_set: &AccountInfo, vaa: &'a [u8], now: i64) -> Result<VerifiedVaa<'a>> {
+    require!(vaa.first() == Some(&1), OracleError::InvalidVaa);
+    let set = GuardianSet::load(guardian_set, vaa_guardian_set_index(vaa)?, now)?;
+
+    let count = *vaa.get(5).ok_or(OracleError::InvalidVaa)? as usize;
+    require!(count > 0, OracleError::InvalidGuardianSignature);
+    let body_start = VAA_HEADER_LEN + count * VAA_SIGNATURE_LEN;
+    let body = vaa.get(body_start..).ok_or(OracleError::InvalidVaa)?;
+    require!(body.len() >= VAA_BODY_HEADER_LEN, OracleError::InvalidVaa);
+
+    // Guardians sign the double hash of the body
+    let digest = keccak::hash(&keccak::hash(body).to_bytes()).to_bytes();
+    let mut previous: Option<u8> = None;
+    for signature in vaa[VAA_HEADER_LEN..body_start].chunks_exact(VAA_SIGNATURE_LEN) {
+        let guardian = signature[0];
+        require!(previous.map_or(true, |p| guardian > p), OracleError::InvalidGuardianSignature);
+        previous = Some(guardian);
+
+        let key = set.keys.get(guardian as usize).ok_or(OracleError::InvalidGuardianSignature)?;
+        let recovered = secp256k1_recover(&digest, signature[65], &signature[1..65])
+            .map_err(|_| OracleError::InvalidGuardianSignature)?;
+        let address = keccak::hash(&recovered.to_bytes()).to_bytes();
+        require!(address[12..] == key[..], OracleError::InvalidGuardianSignature);
+    }
+
+    let verification_level = if count >= set.quorum() {
+        VerificationLevel::Full
+    } else {
+        VerificationLevel::Partial { num_signatures: count as u8 }
+    };
+    Ok(VerifiedVaa {
+        emitter_chain: u16::from_be_bytes(body[8..10].try_into().unwrap()),
+        emitter_address: body[10..42].try_into().unwrap(),
+        payload: &body[VAA_BODY_HEADER_LEN..],
+        verification_level,
+    })
+}
+
+/// Verify an accumulator update carried in instruction data and return its
+/// price as a `PriceUpdateV2` that exists only in memory. `posted_slot` is
+/// the current slot and there is no write authority.
+pub fn verify_price_update_inline(
+    guardian_set: &AccountInfo,
+    vaa: &[u8],
+    merkle_price_update: &MerklePriceUpdate,
+    clock: &impl Now,
+) -> Result<PriceUpdateV2> {
+    let verified = verify_vaa(guardian_set, vaa, clock.unix_timestamp())?;
+    require!(verified.emitter_chain == PYTHNET_CHAIN_ID, OracleError::InvalidVaa);
+    require!(verified.emitter_address == PYTH_ACCUMULATOR_EMITTER, OracleError::InvalidVaa);
+
+    let root = match WormholeMessage::try_from_bytes(verified.payload)
+        .map_err(|_| OracleError::InvalidVaa)?
+        .payload
+    {
+        WormholePayload::Merkle(merkle_root) => MerkleRoot::<Keccak160>::new(merkle_root.root),
+    };
+    let message = merkle_price_update.message.as_ref();
+    require!(
+        root.check(merkle_price_update.proof.clone(), message),
+        OracleError::PriceUpdateProofMismatch
+    );
+
+    let price_message = match from_slice::<byteorder::BE, Message>(message) {
+        Ok(Message::PriceFeedMessage(price_message)) => price_message,
+        _ => return err!(OracleError::InvalidVaa),
+    };
+    Ok(PriceUpdateV2 {
+        write_authority: Pubkey::default(),
+        verification_level: verified.verification_level,
+        price_message,
+        posted_slot: clock.slot(),
+    })
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+/// Accounts for consuming a price update carried in instruction data
+#[derive(Accounts)]
+pub struct InlinePriceContext<'info> {
+    /// CHECK: owner, address, and expiry checked by `GuardianSet::load`
+    pub guardian_set: UncheckedAccount<'info>,
+}
+
+impl<'info> InlinePriceContext<'info> {
+    pub fn verify(&self, vaa: &[u8], merkle_price_update: &MerklePriceUpdate, clock: &impl Now) -> Result<PriceUpdateV2> {
+        verify_price_update_inline(&self.guardian_set.to_account_info(), vaa, merkle_price_update, clock)
+    }
+}
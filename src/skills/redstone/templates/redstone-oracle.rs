@@ -0,0 +1,376 @@
+/**
+ * RedStone Oracle Template for Anchor Programs
+ *
+ * RedStone is a pull oracle: instead of reading a feed account, the caller
+ * fetches signed data packages from RedStone's gateways and attaches them
+ * to the instruction, in the same calldata layout RedStone uses on EVM
+ * chains. This module parses that payload, recovers each package's signer
+ * (secp256k1, as on Ethereum), requires a threshold of distinct authorized
+ * signers per feed, and aggregates their values into one price that goes
+ * through the same `get_validated_price` pipeline as Pyth, returning a
+ * `ValidatedPrice`.
+ *
+ * Payload layout, read from the end:
+ *   [data packages][unsigned metadata][metadata size: 3][package count: 2]
+ *   [marker: 0x000002ed57011e0000]
+ * Each data package:
+ *   [data points][timestamp ms: 6][value size: 4][point count: 3]
+ *   [signature: r 32, s 32, v 1]
+ * Each data point: [feed ID: 32][value: value size, big-endian]
+ * The signature covers the package's bytes before it, hashed with keccak256.
+ *
+ * Aggregation: the median of the authorized signers' values, with the
+ * confidence set to half their range. The publish time is the oldest
+ * package used, so staleness is measured from the stalest signer.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    anchor-lang = "0.30.1"
+ *    pyth-oracle = { path = "../pyth-oracle", features = ["no-entrypoint"] }
+ *    (pyth-oracle is pyth/templates/anchor-oracle.rs built as a library)
+ *
+ * 2. Import this module in your program
+ * 3. Fill `RedstoneSigners` with the signer addresses of the RedStone data
+ *    service you fetch from (e.g. `redstone-primary-prod`), as published
+ *    by RedStone, and a threshold
+ * 4. Take the payload as instruction data and call
+ *    `get_validated_redstone_price` with one of the presets below
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::secp256k1_recover::secp256k1_recover;
+use pyth_oracle::oracle::{Now, OraclePrice, PriceSource, PriceSourceError, StalenessMode};
+use pyth_oracle::{get_validated_price, PriceValidationConfig, ValidatedPrice};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Trailing marker of a RedStone payload
+pub const REDSTONE_MARKER: [u8; 9] = [0x00, 0x00, 0x02, 0xed, 0x57, 0x01, 0x1e, 0x00, 0x00];
+
+/// RedStone values are scaled by 10^8
+pub const REDSTONE_DECIMALS: u8 = 8;
+
+/// RedStone gateways refresh packages every few seconds, and its EVM
+/// consumers accept them for 3 minutes
+pub const DEFAULT_MAX_PACKAGE_AGE_SECS: u64 = 180;
+
+/// Packages timestamped further ahead of the clock are rejected
+pub const MAX_TIMESTAMP_AHEAD_SECS: i64 = 60;
+
+const SIGNATURE_LEN: usize = 65;
+const FEED_ID_LEN: usize = 32;
+
+/// 32-byte RedStone feed ID of `symbol`: the ASCII bytes, zero-padded
+pub const fn feed_id(symbol: &str) -> [u8; 32] {
+    let bytes = symbol.as_bytes();
+    let mut id = [0u8; 32];
+    let mut i = 0;
+    while i < bytes.len() && i < 32 {
+        id[i] = bytes[i];
+        i += 1;
+    }
+    id
+}
+
+/// Common feed IDs
+pub mod feeds {
+    use super::feed_id;
+
+    pub const SOL: [u8; 32] = feed_id("SOL");
+    pub const BTC: [u8; 32] = feed_id("BTC");
+    pub const ETH: [u8; 32] = feed_id("ETH");
+    pub const USDC: [u8; 32] = feed_id("USDC");
+}
+
+// ============================================================================
+// CONFIG PRESETS
+// ============================================================================
+
+/// RedStone equivalents of the Pyth `PriceValidationConfig` presets
+///
+/// The confidence limits apply to the spread between signers.
+pub mod presets {
+    use super::*;
+
+    /// Default config (3 minute package age)
+    pub fn default() -> PriceValidationConfig {
+        PriceValidationConfig {
+            max_age_secs: DEFAULT_MAX_PACKAGE_AGE_SECS,
+            ..PriceValidationConfig::default()
+        }
+    }
+
+    /// Strict config for high-value operations (1 minute package age)
+    pub fn strict() -> PriceValidationConfig {
+        PriceValidationConfig {
+            max_age_secs: 60,
+            ..PriceValidationConfig::strict()
+        }
+    }
+
+    /// Lenient config for less critical operations (10 minute package age)
+    pub fn lenient() -> PriceValidationConfig {
+        PriceValidationConfig {
+            max_age_secs: 600,
+            ..PriceValidationConfig::lenient()
+        }
+    }
+
+    /// Pin the config to a specific feed
+    pub fn with_feed(mut config: PriceValidationConfig, feed_id: &[u8; 32]) -> PriceValidationConfig {
+        config.expected_feed_id = Some(*feed_id);
+        config
+    }
+}
+
+// ============================================================================
+// SIGNERS
+// ============================================================================
+
+/// The signers a program trusts, and how many must agree
+#[derive(Clone, Copy, Debug)]
+pub struct RedstoneSigners<'a> {
+    /// Ethereum addresses of the data service's signers
+    pub authorized: &'a [[u8; 20]],
+    /// Distinct authorized signers required per feed
+    pub threshold: u8,
+}
+
+impl RedstoneSigners<'_> {
+    pub fn is_authorized(&self, signer: &[u8; 20]) -> bool {
+        self.authorized.contains(signer)
+    }
+}
+
+// ============================================================================
+// PAYLOAD PARSING
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DataPoint {
+    pub feed_id: [u8; 32],
+    /// Value scaled by 10^REDSTONE_DECIMALS
+    pub value: u64,
+}
+
+/// A data package and the address that signed it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataPackage {
+    pub signer: [u8; 20],
+    pub timestamp_ms: u64,
+    pub data_points: Vec<DataPoint>,
+}
+
+/// Big-endian integer of `len` bytes ending at `end`
+fn read_be(payload: &[u8], end: usize, len: usize) -> Result<(u64, usize)> {
+    let start = end.checked_sub(len).ok_or(RedstoneOracleError::InvalidPayload)?;
+    let bytes = &payload[start..end];
+    require!(
+        bytes.iter().rev().skip(8).all(|byte| *byte == 0),
+        RedstoneOracleError::ValueOutOfRange
+    );
+    let value = bytes.iter().fold(0u64, |value, byte| value << 8 | u64::from(*byte));
+    Ok((value, start))
+}
+
+/// Ethereum address that produced `signature` over `message`
+fn recover_signer(message: &[u8], signature: &[u8]) -> Result<[u8; 20]> {
+    let digest = keccak::hash(message).to_bytes();
+    let recovery_id = signature[64]
+        .checked_sub(27)
+        .ok_or(RedstoneOracleError::InvalidSignature)?;
+    let public_key = secp256k1_recover(&digest, recovery_id, &signature[..64])
+        .map_err(|_| RedstoneOracleError::InvalidSignature)?;
+    Ok(keccak::hash(&public_key.to_bytes()).to_bytes()[12..].try_into().unwrap())
+}
+
+/// The data package ending at `end`, and where it starts
+fn parse_package(payload: &[u8], end: usize) -> Result<(DataPackage, usize)> {
+    let signature_start = end.checked_sub(SIGNATURE_LEN).ok_or(RedstoneOracleError::InvalidPayload)?;
+    let (point_count, end) = read_be(payload, signature_start, 3)?;
+    let (value_size, end) = read_be(payload, end, 4)?;
+    let (timestamp_ms, points_end) = read_be(payload, end, 6)?;
+
+    let point_len = FEED_ID_LEN + value_size as usize;
+    let points_start = point_len
+        .checked_mul(point_count as usize)
+        .and_then(|points_len| points_end.checked_sub(points_len))
+        .ok_or(RedstoneOracleError::InvalidPayload)?;
+    let mut data_points = Vec::with_capacity(point_count as usize);
+    for point in payload[points_start..points_end].chunks_exact(point_len) {
+        let (value, _) = read_be(point, point_len, value_size as usize)?;
+        data_points.push(DataPoint {
+            feed_id: point[..FEED_ID_LEN].try_into().unwrap(),
+            value,
+        });
+    }
+
+    let signature = &payload[signature_start..signature_start + SIGNATURE_LEN];
+    let signer = recover_signer(&payload[points_start..signature_start], signature)?;
+    Ok((DataPackage { signer, timestamp_ms, data_points }, points_start))
+}
+
+/// Parse a RedStone payload and recover the signer of every package
+pub fn parse_payload(payload: &[u8]) -> Result<Vec<DataPackage>> {
+    let end = payload
+        .len()
+        .checked_sub(REDSTONE_MARKER.len())
+        .ok_or(RedstoneOracleError::InvalidPayload)?;
+    require!(payload[end..] == REDSTONE_MARKER, RedstoneOracleError::InvalidPayload);
+
+    let (metadata_size, end) = read_be(payload, end, 3)?;
+    let end = end
+        .checked_sub(metadata_size as usize)
+        .ok_or(RedstoneOracleError::InvalidPayload)?;
+    let (package_count, mut end) = read_be(payload, end, 2)?;
+
+    let mut packages = Vec::with_capacity(package_count as usize);
+    for _ in 0..package_count {
+        let (package, start) = parse_package(payload, end)?;
+        packages.push(package);
+        end = start;
+    }
+    require!(end == 0, RedstoneOracleError::InvalidPayload);
+    Ok(packages)
+}
+
+// ============================================================================
+// AGGREGATION
+// ============================================================================
+
+/// One feed aggregated over the authorized signers of a payload
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RedstonePrice {
+    pub feed_id: [u8; 32],
+    /// Median of the signers' values
+    pub value: u64,
+    /// Half the range of the signers' values
+    pub spread: u64,
+    /// Oldest package used
+    pub timestamp_ms: u64,
+    pub signer_count: u8,
+}
+
+impl RedstonePrice {
+    /// Aggregate `feed_id` over the packages of distinct authorized signers.
+    /// Packages of unknown signers are ignored; fewer than the threshold
+    /// fails.
+    pub fn aggregate(packages: &[DataPackage], feed_id: &[u8; 32], signers: &RedstoneSigners) -> Result<Self> {
+        require!(signers.threshold > 0, RedstoneOracleError::InsufficientSigners);
+
+        let mut seen: Vec<[u8; 20]> = Vec::new();
+        let mut values: Vec<u64> = Vec::new();
+        let mut timestamp_ms = u64::MAX;
+        for package in packages {
+            if !signers.is_authorized(&package.signer) || seen.contains(&package.signer) {
+                continue;
+            }
+            let Some(point) = package.data_points.iter().find(|point| point.feed_id == *feed_id) else {
+                continue;
+            };
+            seen.push(package.signer);
+            values.push(point.value);
+            timestamp_ms = timestamp_ms.min(package.timestamp_ms);
+        }
+        require!(
+            values.len() >= signers.threshold as usize,
+            RedstoneOracleError::InsufficientSigners
+        );
+
+        values.sort_unstable();
+        let mid = values.len() / 2;
+        let value = if values.len() % 2 == 0 {
+            ((values[mid - 1] as u128 + values[mid] as u128) / 2) as u64
+        } else {
+            values[mid]
+        };
+        let spread = (values[values.len() - 1] - values[0]).div_ceil(2);
+
+        Ok(Self {
+            feed_id: *feed_id,
+            value,
+            spread,
+            timestamp_ms,
+            signer_count: values.len() as u8,
+        })
+    }
+
+    pub fn publish_time(&self) -> i64 {
+        (self.timestamp_ms / 1_000) as i64
+    }
+}
+
+impl PriceSource for RedstonePrice {
+    fn feed_id(&self) -> [u8; 32] {
+        self.feed_id
+    }
+
+    fn staleness_mode(&self) -> StalenessMode {
+        StalenessMode::Seconds
+    }
+
+    fn get_price(&self, clock: &impl Now, max_age: u64) -> Result<OraclePrice> {
+        let publish_time = self.publish_time();
+        let age = clock.unix_timestamp() - publish_time;
+        require!(age >= -MAX_TIMESTAMP_AHEAD_SECS, RedstoneOracleError::TimestampTooFarAhead);
+        require!(age <= max_age as i64, RedstoneOracleError::PackageTooStale);
+
+        Ok(OraclePrice {
+            price: i64::try_from(self.value).map_err(|_| RedstoneOracleError::ValueOutOfRange)?,
+            conf: self.spread,
+            exponent: -(REDSTONE_DECIMALS as i32),
+            publish_time,
+        })
+    }
+
+    fn get_ema_price(&self, _clock: &impl Now, _max_age: u64) -> Result<OraclePrice> {
+        // Packages carry spot values only
+        Err(error!(PriceSourceError::PriceUnavailable))
+    }
+}
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+/// Parse a payload, aggregate `feed_id` over the authorized signers, and
+/// validate the result
+pub fn get_validated_redstone_price(
+    payload: &[u8],
+    feed_id: &[u8; 32],
+    signers: &RedstoneSigners,
+    config: &PriceValidationConfig,
+    clock: &impl Now,
+) -> Result<ValidatedPrice> {
+    let packages = parse_payload(payload)?;
+    let price = RedstonePrice::aggregate(&packages, feed_id, signers)?;
+    get_validated_price(&price, config, clock)
+}
+
+// ============================================================================
+// ERROR CODES
+// ============================================================================
+
+#[error_code]
+pub enum RedstoneOracleError {
+    #[msg("RedStone payload is malformed")]
+    InvalidPayload,
+
+    #[msg("Data package signature could not be recovered")]
+    InvalidSignature,
+
+    #[msg("Too few authorized signers for the feed")]
+    InsufficientSigners,
+
+    #[msg("Data package is too stale")]
+    PackageTooStale,
+
+    #[msg("Data package timestamp is ahead of the clock")]
+    TimestampTooFarAhead,
+
+    #[msg("Value does not fit the target price format")]
+    ValueOutOfRange,
+}
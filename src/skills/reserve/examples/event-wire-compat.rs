@@ -0,0 +1,175 @@
+/**
+ * Wire Format Compatibility Tests
+ *
+ * Pins the JSON produced and accepted by templates/event-wire.rs. The
+ * fixtures below are what external consumers parse; a change that breaks
+ * one of these tests breaks them too, and needs a `WIRE_VERSION` bump
+ * instead of a fixture edit. When adding an optional field or a new kind,
+ * add a fixture rather than changing an existing one.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dev-dependencies]
+ *    pyth-oracle = { path = "../pyth-oracle", features = ["no-entrypoint"] }
+ *    serde = { version = "1", features = ["derive"] }
+ *    serde_json = "1"
+ *    serde_with = "3"
+ *    thiserror = "1"
+ *
+ * 2. Copy this file to `tests/event_wire_compat.rs`, next to a `tests/`
+ *    copy of event-wire.rs or with the #[path] below adjusted
+ * 3. Run:
+ *    cargo test --test event_wire_compat
+ */
+
+#[path = "../templates/event-wire.rs"]
+mod event_wire;
+
+use event_wire::{
+    decode, encode, ActionEvent, EventBody, FillEvent, FillOutcome, PriceEvent, WireError,
+    WireEvent, WIRE_VERSION,
+};
+use pyth_oracle::OraclePrice;
+use serde_json::{json, Value};
+
+// ============================================================================
+// FIXTURES (schema v1)
+// ============================================================================
+
+const PRICE_V1: &str = r#"{"v":1,"id":"01J0000000000000000000PRICE","ts_ms":1700000000000,"source":"price-stream","kind":"price","data":{"feed_id":"ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d","price":"15000000000","conf":"7500000","exponent":-8,"publish_time":1700000000}}"#;
+
+const ACTION_V1: &str = r#"{"v":1,"id":"01J000000000000000000ACTION","ts_ms":1700000001000,"source":"executor","kind":"action","data":{"action":"swap","params":{"amount_in":"1000000000"},"reason":"rebalance to target","signature":null}}"#;
+
+const FILL_V1: &str = r#"{"v":1,"id":"01J00000000000000000000FILL","ts_ms":1700000002000,"source":"reconciler","kind":"fill","data":{"signature":"5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW","venue":"jupiter","outcome":"filled","input_mint":"So11111111111111111111111111111111111111112","output_mint":"EPjFWdd5AufqSSqeM2qrxzJ6orvjbs5iMAvPRBEtzpdx","input_amount":"1000000000","expected_output":"150000000","realized_output":"149700000","slippage_bps":20}}"#;
+
+fn as_json(bytes: &[u8]) -> Value {
+    serde_json::from_slice(bytes).unwrap()
+}
+
+fn sol_feed() -> [u8; 32] {
+    let hex = "ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+    let mut id = [0u8; 32];
+    for (i, byte) in id.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+    }
+    id
+}
+
+// ============================================================================
+// ENCODING IS STABLE
+// ============================================================================
+
+#[test]
+fn price_event_encodes_to_fixture() {
+    let price = OraclePrice {
+        price: 15_000_000_000,
+        conf: 7_500_000,
+        exponent: -8,
+        publish_time: 1_700_000_000,
+    };
+    let event = WireEvent::new(
+        "01J0000000000000000000PRICE",
+        1_700_000_000_000,
+        "price-stream",
+        EventBody::Price(PriceEvent::from_oracle(&sol_feed(), &price)),
+    );
+
+    assert_eq!(as_json(&encode(&event).unwrap()), as_json(PRICE_V1.as_bytes()));
+    assert_eq!(event.topic(), "automaton.v1.price");
+}
+
+#[test]
+fn fixtures_round_trip() {
+    for fixture in [PRICE_V1, ACTION_V1, FILL_V1] {
+        let event = decode(fixture.as_bytes()).unwrap();
+        assert_eq!(as_json(&encode(&event).unwrap()), as_json(fixture.as_bytes()));
+    }
+}
+
+// ============================================================================
+// DECODING OLD AND NEW PRODUCERS
+// ============================================================================
+
+#[test]
+fn fill_fixture_decodes() {
+    let event = decode(FILL_V1.as_bytes()).unwrap();
+    let EventBody::Fill(fill) = event.body else {
+        panic!("expected a fill");
+    };
+    assert_eq!(
+        fill,
+        FillEvent {
+            signature: "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW"
+                .to_string(),
+            venue: "jupiter".to_string(),
+            outcome: FillOutcome::Filled,
+            input_mint: "So11111111111111111111111111111111111111112".to_string(),
+            output_mint: "EPjFWdd5AufqSSqeM2qrxzJ6orvjbs5iMAvPRBEtzpdx".to_string(),
+            input_amount: 1_000_000_000,
+            expected_output: 150_000_000,
+            realized_output: Some(149_700_000),
+            slippage_bps: Some(20),
+        }
+    );
+}
+
+#[test]
+fn optional_fields_may_be_absent() {
+    let minimal = json!({
+        "v": 1, "id": "a", "ts_ms": 0, "source": "executor",
+        "kind": "action", "data": { "action": "flatten" }
+    });
+    let event = decode(minimal.to_string().as_bytes()).unwrap();
+
+    assert_eq!(
+        event.body,
+        EventBody::Action(ActionEvent {
+            action: "flatten".to_string(),
+            params: Value::Null,
+            reason: None,
+            signature: None,
+        })
+    );
+}
+
+#[test]
+fn unknown_fields_are_ignored() {
+    let mut newer: Value = serde_json::from_str(PRICE_V1).unwrap();
+    newer["trace_id"] = json!("abc");
+    newer["data"]["ema_price"] = json!("14990000000");
+
+    let event = decode(newer.to_string().as_bytes()).unwrap();
+    assert_eq!(event, decode(PRICE_V1.as_bytes()).unwrap());
+}
+
+#[test]
+fn unknown_kinds_pass_through() {
+    let newer = json!({
+        "v": 1, "id": "b", "ts_ms": 0, "source": "risk",
+        "kind": "margin_call", "data": { "account": "x", "health": "0.97" }
+    });
+    let event = decode(newer.to_string().as_bytes()).unwrap();
+
+    assert!(matches!(&event.body, EventBody::Unknown { kind, .. } if kind == "margin_call"));
+    assert_eq!(as_json(&encode(&event).unwrap()), newer);
+}
+
+#[test]
+fn unsupported_versions_are_rejected() {
+    let mut future: Value = serde_json::from_str(PRICE_V1).unwrap();
+    future["v"] = json!(WIRE_VERSION + 1);
+
+    assert!(matches!(
+        decode(future.to_string().as_bytes()),
+        Err(WireError::UnsupportedVersion(v)) if v == WIRE_VERSION + 1
+    ));
+}
+
+#[test]
+fn large_amounts_are_strings() {
+    let event = decode(FILL_V1.as_bytes()).unwrap();
+    let encoded = as_json(&encode(&event).unwrap());
+
+    assert!(encoded["data"]["input_amount"].is_string());
+    assert!(encoded["data"]["slippage_bps"].is_number());
+}
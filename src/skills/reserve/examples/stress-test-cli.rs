@@ -0,0 +1,92 @@
+/**
+ * Reserve Stress Test - Command Line
+ *
+ * Runs shock scenarios against a portfolio described in a JSON file and
+ * prints post-shock health factors, liquidation cascades and reserve
+ * shortfalls. Exits non-zero when any scenario leaves a shortfall or bad
+ * debt, so it can gate deployments or scheduled checks.
+ *
+ * Add to Cargo.toml:
+ * [dependencies]
+ * clap = { version = "4", features = ["derive"] }
+ * serde = { version = "1", features = ["derive"] }
+ * serde_json = "1"
+ *
+ * Run:
+ * cargo run -- --portfolio portfolio.json
+ * cargo run -- --portfolio portfolio.json --scenarios scenarios.json --json
+ */
+
+use clap::Parser;
+use std::path::PathBuf;
+
+#[path = "../templates/stress-test.rs"]
+mod stress_test;
+use stress_test::{run_scenarios, Portfolio, Scenario, StressReport};
+
+#[derive(Parser)]
+#[command(about = "Stress-test the reserve against price shock scenarios")]
+struct Args {
+    /// Portfolio JSON (markets, holdings, positions, required_reserve_usd)
+    #[arg(long)]
+    portfolio: PathBuf,
+
+    /// Scenario JSON array; defaults to the built-in presets
+    #[arg(long)]
+    scenarios: Option<PathBuf>,
+
+    /// Print reports as JSON instead of text
+    #[arg(long)]
+    json: bool,
+}
+
+fn print_report(report: &StressReport) {
+    println!("=== Scenario: {} ===", report.scenario);
+    println!(
+        "Reserve: ${:.2} -> ${:.2} (shortfall ${:.2})",
+        report.reserve_value_before, report.reserve_value_after, report.reserve_shortfall_usd
+    );
+    println!(
+        "Liquidations: {} over {} cascade round(s)",
+        report.liquidations.len(),
+        report.cascade_rounds
+    );
+    for position in &report.positions {
+        println!(
+            "  {}: HF {:.3} -> {:.3}, {} liquidation(s), bad debt ${:.2}",
+            position.position_id,
+            position.health_factor_before,
+            position.health_factor_after,
+            position.liquidations,
+            position.bad_debt_usd
+        );
+    }
+    for (asset, price) in &report.final_prices {
+        println!("  {} final price: ${:.4}", asset, price);
+    }
+    println!("Result: {}", if report.passes() { "PASS" } else { "FAIL" });
+    println!();
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
+    let portfolio: Portfolio = serde_json::from_str(&std::fs::read_to_string(&args.portfolio)?)?;
+    let scenarios: Vec<Scenario> = match &args.scenarios {
+        Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        None => Scenario::presets(),
+    };
+
+    let reports = run_scenarios(&portfolio, &scenarios);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    } else {
+        reports.iter().for_each(print_report);
+    }
+
+    if reports.iter().any(|r| !r.passes()) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,285 @@
+/**
+ * Account Decoder Registry and Typed Subscriptions
+ *
+ * Skills register the accounts they understand: owner program,
+ * discriminator, and a decode function (Anchor accounts register with one
+ * call). The subscription manager looks up the decoder for every account
+ * update it receives and hands consumers a typed value (pool state,
+ * reserve state, price update, position) instead of raw bytes, so each
+ * consumer stops carrying its own layout code.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    anchor-lang = "0.30.1"
+ *    futures = "0.3"
+ *    solana-account-decoder = "1.18"
+ *    solana-client = "1.18"
+ *    solana-sdk = "1.18"
+ *    thiserror = "1"
+ *    tokio = { version = "1", features = ["sync"] }
+ *
+ * 2. Copy this file as `account_decoders.rs` and declare `mod account_decoders;`
+ */
+
+use anchor_lang::{AccountDeserialize, Discriminator};
+use futures::stream::{select_all, StreamExt};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::pubsub_client::{PubsubClient, PubsubClientError};
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+    #[error("no decoders registered for owner {0}")]
+    UnknownOwner(Pubkey),
+
+    #[error("no decoder for this discriminator under owner {0}")]
+    UnknownDiscriminator(Pubkey),
+
+    #[error("{kind} is already registered for owner {owner}")]
+    Duplicate { owner: Pubkey, kind: &'static str },
+
+    #[error("could not decode {kind}: {reason}")]
+    Failed { kind: &'static str, reason: String },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SubscriptionError {
+    #[error("pubsub error: {0}")]
+    Pubsub(#[from] PubsubClientError),
+
+    #[error("update for {0} could not be read as binary account data")]
+    UndecodableUpdate(Pubkey),
+}
+
+// ============================================================================
+// DECODED VALUES
+// ============================================================================
+
+/// A decoded account, downcast by consumers to the concrete type
+#[derive(Clone)]
+pub struct DecodedAccount {
+    /// Skill that registered the decoder, e.g. "pyth"
+    pub skill: &'static str,
+    /// Account type name, e.g. "PriceUpdateV2"
+    pub kind: &'static str,
+    value: Arc<dyn Any + Send + Sync>,
+}
+
+impl DecodedAccount {
+    pub fn downcast<T: 'static>(&self) -> Option<&T> {
+        self.value.downcast_ref::<T>()
+    }
+
+    pub fn is<T: 'static>(&self) -> bool {
+        self.value.is::<T>()
+    }
+}
+
+impl std::fmt::Debug for DecodedAccount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DecodedAccount")
+            .field("skill", &self.skill)
+            .field("kind", &self.kind)
+            .finish_non_exhaustive()
+    }
+}
+
+type DecodeFn = Arc<dyn Fn(&[u8]) -> Result<Arc<dyn Any + Send + Sync>, String> + Send + Sync>;
+
+#[derive(Clone)]
+struct Decoder {
+    skill: &'static str,
+    kind: &'static str,
+    discriminator: Vec<u8>,
+    decode: DecodeFn,
+}
+
+// ============================================================================
+// REGISTRY
+// ============================================================================
+
+/// Implemented by each skill to register the accounts it knows
+pub trait RegisterDecoders {
+    fn register_decoders(registry: &mut DecoderRegistry) -> Result<(), DecodeError>;
+}
+
+/// Decoders keyed by owner program, matched by discriminator prefix
+#[derive(Clone, Default)]
+pub struct DecoderRegistry {
+    decoders: BTreeMap<Pubkey, Vec<Decoder>>,
+}
+
+impl DecoderRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an Anchor account type by its 8-byte discriminator
+    pub fn register_anchor<T>(
+        &mut self,
+        skill: &'static str,
+        kind: &'static str,
+        owner: Pubkey,
+    ) -> Result<(), DecodeError>
+    where
+        T: AccountDeserialize + Discriminator + Send + Sync + 'static,
+    {
+        self.register(skill, kind, owner, T::DISCRIMINATOR.to_vec(), |data| {
+            // try_deserialize re-checks the discriminator
+            T::try_deserialize(&mut &data[..]).map_err(|e| e.to_string())
+        })
+    }
+
+    /// Register a non-Anchor (plain Borsh or custom) layout. `discriminator`
+    /// is matched as a prefix of the account data and may be empty for
+    /// programs with a single account type.
+    pub fn register<T, F>(
+        &mut self,
+        skill: &'static str,
+        kind: &'static str,
+        owner: Pubkey,
+        discriminator: Vec<u8>,
+        decode: F,
+    ) -> Result<(), DecodeError>
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&[u8]) -> Result<T, String> + Send + Sync + 'static,
+    {
+        let decoders = self.decoders.entry(owner).or_default();
+        if decoders.iter().any(|d| d.discriminator == discriminator) {
+            return Err(DecodeError::Duplicate { owner, kind });
+        }
+        decoders.push(Decoder {
+            skill,
+            kind,
+            discriminator,
+            decode: Arc::new(move |data| {
+                decode(data).map(|value| Arc::new(value) as Arc<dyn Any + Send + Sync>)
+            }),
+        });
+        // Longest discriminator first so specific layouts win over catch-alls
+        decoders.sort_by(|a, b| b.discriminator.len().cmp(&a.discriminator.len()));
+        Ok(())
+    }
+
+    /// Register everything a skill provides
+    pub fn register_skill<S: RegisterDecoders>(&mut self) -> Result<(), DecodeError> {
+        S::register_decoders(self)
+    }
+
+    pub fn decode(&self, owner: &Pubkey, data: &[u8]) -> Result<DecodedAccount, DecodeError> {
+        let decoders = self
+            .decoders
+            .get(owner)
+            .ok_or(DecodeError::UnknownOwner(*owner))?;
+        let decoder = decoders
+            .iter()
+            .find(|d| data.starts_with(&d.discriminator))
+            .ok_or(DecodeError::UnknownDiscriminator(*owner))?;
+        let value = (decoder.decode)(data).map_err(|reason| DecodeError::Failed {
+            kind: decoder.kind,
+            reason,
+        })?;
+        Ok(DecodedAccount {
+            skill: decoder.skill,
+            kind: decoder.kind,
+            value,
+        })
+    }
+
+    pub fn decode_account(&self, account: &Account) -> Result<DecodedAccount, DecodeError> {
+        self.decode(&account.owner, &account.data)
+    }
+}
+
+// ============================================================================
+// SUBSCRIPTIONS
+// ============================================================================
+
+/// One typed account change
+#[derive(Clone, Debug)]
+pub struct AccountUpdate {
+    pub pubkey: Pubkey,
+    pub slot: u64,
+    pub owner: Pubkey,
+    /// `Err` when the owner or layout is not registered, so consumers can
+    /// still see that the account changed
+    pub decoded: Result<DecodedAccount, Arc<DecodeError>>,
+}
+
+/// Delivers typed updates for a set of accounts over one websocket
+pub struct SubscriptionManager {
+    registry: Arc<DecoderRegistry>,
+    commitment: CommitmentConfig,
+}
+
+impl SubscriptionManager {
+    pub fn new(registry: Arc<DecoderRegistry>, commitment: CommitmentConfig) -> Self {
+        Self {
+            registry,
+            commitment,
+        }
+    }
+
+    /// Subscribe to `accounts` and forward decoded updates to `updates`
+    /// until the receiver is dropped or the connection fails. Callers
+    /// reconnect by calling again with a fresh client.
+    pub async fn run(
+        &self,
+        client: &PubsubClient,
+        accounts: &[Pubkey],
+        updates: mpsc::Sender<AccountUpdate>,
+    ) -> Result<(), SubscriptionError> {
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(self.commitment),
+            ..RpcAccountInfoConfig::default()
+        };
+
+        let mut streams = Vec::with_capacity(accounts.len());
+        let mut unsubscribes = Vec::with_capacity(accounts.len());
+        for pubkey in accounts {
+            let (stream, unsubscribe) = client.account_subscribe(pubkey, Some(config.clone())).await?;
+            let pubkey = *pubkey;
+            streams.push(stream.map(move |response| (pubkey, response)));
+            unsubscribes.push(unsubscribe);
+        }
+
+        let mut merged = select_all(streams);
+        let result = loop {
+            let Some((pubkey, response)) = merged.next().await else {
+                break Ok(());
+            };
+            let Some(account) = response.value.decode::<Account>() else {
+                break Err(SubscriptionError::UndecodableUpdate(pubkey));
+            };
+            let update = AccountUpdate {
+                pubkey,
+                slot: response.context.slot,
+                owner: account.owner,
+                decoded: self.registry.decode_account(&account).map_err(Arc::new),
+            };
+            if updates.send(update).await.is_err() {
+                break Ok(());
+            }
+        };
+
+        drop(merged);
+        for unsubscribe in unsubscribes {
+            unsubscribe().await;
+        }
+        result
+    }
+}
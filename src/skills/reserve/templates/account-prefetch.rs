@@ -0,0 +1,237 @@
+/**
+ * Account Prefetch and Cache Warm-Up
+ *
+ * Strategies declare the accounts they read (price updates, pools, token
+ * accounts, obligations). Before evaluation, the prefetcher collects the
+ * declared accounts of every strategy, deduplicates them, and loads them
+ * with batched `getMultipleAccounts` calls at one consistent slot, so a
+ * multi-asset strategy pays one round trip instead of one per account.
+ *
+ * Strategies then read through an `AccountView` that only serves declared
+ * accounts, so a missing declaration fails loudly instead of silently
+ * falling back to a slow per-account fetch.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    futures = "0.3"
+ *    solana-account-decoder = "1.18"
+ *    solana-client = "1.18"
+ *    solana-sdk = "1.18"
+ *    thiserror = "1"
+ *
+ * 2. Copy this file as `account_prefetch.rs` and declare `mod account_prefetch;`
+ */
+
+use futures::future::try_join_all;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::client_error::ClientError;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeSet, HashMap};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Most accounts `getMultipleAccounts` returns per call
+pub const MAX_ACCOUNTS_PER_REQUEST: usize = 100;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum PrefetchError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] ClientError),
+
+    #[error("batches were served from slots {min}..={max}, more than {max_spread} apart")]
+    InconsistentSlots { min: u64, max: u64, max_spread: u64 },
+
+    #[error("account {0} was read but not declared as a dependency")]
+    Undeclared(Pubkey),
+
+    #[error("account {0} does not exist")]
+    Missing(Pubkey),
+}
+
+// ============================================================================
+// DEPENDENCIES
+// ============================================================================
+
+/// Implemented by strategies to declare every account they read
+pub trait AccountDependencies {
+    fn account_dependencies(&self) -> Vec<Pubkey>;
+}
+
+/// Deduplicated union of the dependencies of `strategies`
+pub fn collect_dependencies<S: AccountDependencies + ?Sized>(strategies: &[&S]) -> BTreeSet<Pubkey> {
+    strategies
+        .iter()
+        .flat_map(|strategy| strategy.account_dependencies())
+        .collect()
+}
+
+// ============================================================================
+// CACHE
+// ============================================================================
+
+/// Accounts loaded by one prefetch
+#[derive(Clone, Debug, Default)]
+pub struct AccountCache {
+    accounts: HashMap<Pubkey, Option<Account>>,
+    /// Oldest slot any batch was served from
+    min_context_slot: u64,
+}
+
+impl AccountCache {
+    /// `None` if the key was not prefetched, `Some(None)` if it does not exist
+    pub fn get(&self, key: &Pubkey) -> Option<Option<&Account>> {
+        self.accounts.get(key).map(Option::as_ref)
+    }
+
+    pub fn contains(&self, key: &Pubkey) -> bool {
+        self.accounts.contains_key(key)
+    }
+
+    /// Slot the cache is at least as recent as
+    pub fn context_slot(&self) -> u64 {
+        self.min_context_slot
+    }
+
+    /// Prefetched keys that do not exist on chain
+    pub fn missing(&self) -> Vec<Pubkey> {
+        self.accounts
+            .iter()
+            .filter(|(_, account)| account.is_none())
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Read-only view for one strategy, limited to its declared accounts
+    pub fn view<S: AccountDependencies + ?Sized>(&self, strategy: &S) -> AccountView<'_> {
+        AccountView {
+            cache: self,
+            declared: strategy.account_dependencies().into_iter().collect(),
+        }
+    }
+}
+
+/// A strategy's window into the cache
+pub struct AccountView<'a> {
+    cache: &'a AccountCache,
+    declared: BTreeSet<Pubkey>,
+}
+
+impl<'a> AccountView<'a> {
+    /// Declared account, `None` if it does not exist on chain
+    pub fn get(&self, key: &Pubkey) -> Result<Option<&'a Account>, PrefetchError> {
+        if !self.declared.contains(key) {
+            return Err(PrefetchError::Undeclared(*key));
+        }
+        self.cache
+            .get(key)
+            .ok_or(PrefetchError::Undeclared(*key))
+    }
+
+    /// Declared account that must exist
+    pub fn require(&self, key: &Pubkey) -> Result<&'a Account, PrefetchError> {
+        self.get(key)?.ok_or(PrefetchError::Missing(*key))
+    }
+
+    pub fn context_slot(&self) -> u64 {
+        self.cache.context_slot()
+    }
+}
+
+// ============================================================================
+// PREFETCHER
+// ============================================================================
+
+#[derive(Clone, Debug)]
+pub struct PrefetchConfig {
+    pub commitment: CommitmentConfig,
+    /// Largest allowed gap between the slots batches were served from;
+    /// 0 requires every batch to come from the same slot
+    pub max_slot_spread: u64,
+}
+
+impl Default for PrefetchConfig {
+    fn default() -> Self {
+        Self {
+            commitment: CommitmentConfig::confirmed(),
+            max_slot_spread: 2,
+        }
+    }
+}
+
+pub struct AccountPrefetcher<'a> {
+    rpc: &'a RpcClient,
+    config: PrefetchConfig,
+}
+
+impl<'a> AccountPrefetcher<'a> {
+    pub fn new(rpc: &'a RpcClient, config: PrefetchConfig) -> Self {
+        Self { rpc, config }
+    }
+
+    /// Load every account the strategies declare, in parallel batches of
+    /// `MAX_ACCOUNTS_PER_REQUEST`
+    pub async fn warm<S: AccountDependencies + ?Sized>(
+        &self,
+        strategies: &[&S],
+    ) -> Result<AccountCache, PrefetchError> {
+        let keys: Vec<Pubkey> = collect_dependencies(strategies).into_iter().collect();
+        self.fetch(&keys).await
+    }
+
+    pub async fn fetch(&self, keys: &[Pubkey]) -> Result<AccountCache, PrefetchError> {
+        if keys.is_empty() {
+            return Ok(AccountCache::default());
+        }
+
+        let account_config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64Zstd),
+            commitment: Some(self.config.commitment),
+            ..RpcAccountInfoConfig::default()
+        };
+        let batches = try_join_all(keys.chunks(MAX_ACCOUNTS_PER_REQUEST).map(|chunk| {
+            self.rpc
+                .get_multiple_accounts_with_config(chunk, account_config.clone())
+        }))
+        .await?;
+
+        let slots = batches.iter().map(|response| response.context.slot);
+        let min = slots.clone().min().unwrap_or_default();
+        let max = slots.max().unwrap_or_default();
+        if max - min > self.config.max_slot_spread {
+            return Err(PrefetchError::InconsistentSlots {
+                min,
+                max,
+                max_spread: self.config.max_slot_spread,
+            });
+        }
+
+        let accounts = keys
+            .chunks(MAX_ACCOUNTS_PER_REQUEST)
+            .zip(batches)
+            .flat_map(|(chunk, response)| chunk.iter().copied().zip(response.value))
+            .collect();
+        Ok(AccountCache {
+            accounts,
+            min_context_slot: min,
+        })
+    }
+}
@@ -0,0 +1,629 @@
+/**
+ * Multi-Signature Approval Workflow
+ *
+ * Agent actions above a configurable USD notional are held until N of M
+ * operators approve them, either through the Conway control-plane API or
+ * through a Squads multisig proposal. Requests expire if they are not
+ * approved in time, and every transition (requested, approved, rejected,
+ * expired, released) produces an audit entry.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    reqwest = { version = "0.11", features = ["json"] }
+ *    serde = { version = "1", features = ["derive"] }
+ *    serde_json = "1"
+ *    thiserror = "1"
+ *    # Only for SquadsApprovals
+ *    anchor-lang = "0.29"
+ *    solana-client = "1.18"
+ *    solana-sdk = "1.18"
+ *    squads-multisig = "2"
+ *
+ * 2. Copy this file as `approval_workflow.rs` and declare `mod approval_workflow;`
+ * 3. Route every executor action through `ApprovalGate::submit` and only
+ *    execute what `ApprovalGate::release` hands back
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Default notional above which approval is required ($10,000, 6 decimals)
+pub const DEFAULT_APPROVAL_THRESHOLD_USD: u64 = 10_000_000_000;
+
+/// Default time operators have to approve (24 hours)
+pub const DEFAULT_APPROVAL_EXPIRY_SECS: i64 = 86_400;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApprovalError {
+    #[error("approval policy needs at least one operator")]
+    NoOperators,
+
+    #[error("required approvals {required} must be between 1 and {operators}")]
+    InvalidThreshold { required: usize, operators: usize },
+
+    #[error("operator {0} is listed more than once")]
+    DuplicateOperator(String),
+
+    #[error("unknown approval request {0}")]
+    UnknownRequest(String),
+
+    #[error("approval request {id} is {status:?}, not approved")]
+    NotApproved { id: String, status: ApprovalStatus },
+
+    #[error("approval backend error: {0}")]
+    Backend(String),
+}
+
+pub type ApprovalResult<T> = std::result::Result<T, ApprovalError>;
+
+// ============================================================================
+// POLICY
+// ============================================================================
+
+/// Who must approve what, and for how long a request stays open
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApprovalPolicy {
+    /// Actions with notional strictly above this need approval (USD, 6 decimals)
+    pub threshold_usd: u64,
+    /// Approvals required (N)
+    pub required_approvals: usize,
+    /// Operators allowed to vote (M); control-plane user IDs or Squads
+    /// member pubkeys in base58
+    pub operators: Vec<String>,
+    /// Seconds before an unapproved request expires
+    pub expiry_secs: i64,
+}
+
+impl ApprovalPolicy {
+    pub fn new(required_approvals: usize, operators: Vec<String>) -> ApprovalResult<Self> {
+        let policy = Self {
+            threshold_usd: DEFAULT_APPROVAL_THRESHOLD_USD,
+            required_approvals,
+            operators,
+            expiry_secs: DEFAULT_APPROVAL_EXPIRY_SECS,
+        };
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    pub fn with_threshold_usd(mut self, threshold_usd: u64) -> Self {
+        self.threshold_usd = threshold_usd;
+        self
+    }
+
+    pub fn with_expiry_secs(mut self, expiry_secs: i64) -> Self {
+        self.expiry_secs = expiry_secs;
+        self
+    }
+
+    pub fn validate(&self) -> ApprovalResult<()> {
+        if self.operators.is_empty() {
+            return Err(ApprovalError::NoOperators);
+        }
+        if self.required_approvals == 0 || self.required_approvals > self.operators.len() {
+            return Err(ApprovalError::InvalidThreshold {
+                required: self.required_approvals,
+                operators: self.operators.len(),
+            });
+        }
+        let mut seen = BTreeSet::new();
+        for operator in &self.operators {
+            if !seen.insert(operator) {
+                return Err(ApprovalError::DuplicateOperator(operator.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn requires_approval(&self, notional_usd: u64) -> bool {
+        notional_usd > self.threshold_usd
+    }
+
+    /// Votes from outside the operator set never count
+    pub fn is_operator(&self, operator: &str) -> bool {
+        self.operators.iter().any(|o| o == operator)
+    }
+}
+
+// ============================================================================
+// REQUESTS
+// ============================================================================
+
+/// An action the executor wants to run
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AgentAction {
+    /// Unique, stable ID (reused on retries so a request is never opened twice)
+    pub id: String,
+    /// Action type, e.g. "rebalance", "withdraw", "twap"
+    pub kind: String,
+    pub description: String,
+    /// USD notional (6 decimals)
+    pub notional_usd: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApprovalStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Expired,
+    /// Handed to the executor; terminal
+    Released,
+}
+
+/// Operator votes as reported by a backend
+#[derive(Clone, Debug, Default)]
+pub struct Votes {
+    pub approved: Vec<String>,
+    pub rejected: Vec<String>,
+}
+
+/// State of one held action
+#[derive(Clone, Debug, Serialize)]
+pub struct ApprovalRequest {
+    pub action: AgentAction,
+    pub status: ApprovalStatus,
+    pub created_at: i64,
+    pub expires_at: i64,
+    /// Control-plane approval ID or Squads proposal address
+    pub external_ref: String,
+    pub approvals: BTreeSet<String>,
+    pub rejections: BTreeSet<String>,
+}
+
+// ============================================================================
+// AUDIT
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// Below threshold, released without approval
+    AutoReleased,
+    Requested { required: usize, operators: usize },
+    Approved { operator: String },
+    Rejected { operator: String },
+    Expired,
+    Released { approvals: Vec<String> },
+}
+
+/// One audit log entry
+#[derive(Clone, Debug, Serialize)]
+pub struct AuditEntry {
+    pub timestamp: i64,
+    pub action_id: String,
+    pub action_kind: String,
+    pub notional_usd: u64,
+    pub event: AuditEvent,
+}
+
+impl AuditEntry {
+    /// One-line description for the audit log
+    pub fn audit_description(&self) -> String {
+        let what = match &self.event {
+            AuditEvent::AutoReleased => "released (below approval threshold)".to_string(),
+            AuditEvent::Requested { required, operators } => {
+                format!("approval requested ({}-of-{})", required, operators)
+            }
+            AuditEvent::Approved { operator } => format!("approved by {}", operator),
+            AuditEvent::Rejected { operator } => format!("rejected by {}", operator),
+            AuditEvent::Expired => "approval expired".to_string(),
+            AuditEvent::Released { approvals } => {
+                format!("released with approvals from {}", approvals.join(", "))
+            }
+        };
+        format!(
+            "{} {} ${}: {}",
+            self.action_kind,
+            self.action_id,
+            self.notional_usd / 1_000_000,
+            what
+        )
+    }
+
+    /// Full entry as JSON for the audit log entry body
+    pub fn audit_details(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Destination for audit entries (e.g. the agent's modifications log)
+pub trait AuditSink {
+    fn record(&mut self, entry: AuditEntry);
+}
+
+impl AuditSink for Vec<AuditEntry> {
+    fn record(&mut self, entry: AuditEntry) {
+        self.push(entry);
+    }
+}
+
+// ============================================================================
+// BACKENDS
+// ============================================================================
+
+/// Where operators vote
+#[allow(async_fn_in_trait)]
+pub trait ApprovalBackend {
+    /// Open an approval for the action and return its external reference
+    async fn open(&self, action: &AgentAction, policy: &ApprovalPolicy, expires_at: i64)
+        -> ApprovalResult<String>;
+
+    /// Current votes for an open approval
+    async fn votes(&self, external_ref: &str) -> ApprovalResult<Votes>;
+}
+
+/// Approvals through the Conway control-plane API
+pub struct ControlPlaneApprovals {
+    http: reqwest::Client,
+    api_url: String,
+    api_key: String,
+}
+
+#[derive(Serialize)]
+struct OpenApprovalBody<'a> {
+    action_id: &'a str,
+    kind: &'a str,
+    description: &'a str,
+    notional_usd: u64,
+    required_approvals: usize,
+    operators: &'a [String],
+    expires_at: i64,
+}
+
+#[derive(Deserialize)]
+struct OpenApprovalResponse {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct ApprovalVote {
+    operator: String,
+    decision: String,
+}
+
+#[derive(Deserialize)]
+struct ApprovalVotesResponse {
+    #[serde(default)]
+    votes: Vec<ApprovalVote>,
+}
+
+impl ControlPlaneApprovals {
+    pub fn new(api_url: &str, api_key: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string(),
+        }
+    }
+
+    async fn send<T: for<'de> Deserialize<'de>>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> ApprovalResult<T> {
+        let response = request
+            .header("Authorization", &self.api_key)
+            .send()
+            .await
+            .map_err(|e| ApprovalError::Backend(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(ApprovalError::Backend(format!(
+                "control plane returned {}: {}",
+                response.status().as_u16(),
+                response.text().await.unwrap_or_default()
+            )));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| ApprovalError::Backend(e.to_string()))
+    }
+}
+
+impl ApprovalBackend for ControlPlaneApprovals {
+    async fn open(
+        &self,
+        action: &AgentAction,
+        policy: &ApprovalPolicy,
+        expires_at: i64,
+    ) -> ApprovalResult<String> {
+        let body = OpenApprovalBody {
+            action_id: &action.id,
+            kind: &action.kind,
+            description: &action.description,
+            notional_usd: action.notional_usd,
+            required_approvals: policy.required_approvals,
+            operators: &policy.operators,
+            expires_at,
+        };
+        let response: OpenApprovalResponse = self
+            .send(self.http.post(format!("{}/v1/approvals", self.api_url)).json(&body))
+            .await?;
+        Ok(response.id)
+    }
+
+    async fn votes(&self, external_ref: &str) -> ApprovalResult<Votes> {
+        let response: ApprovalVotesResponse = self
+            .send(self.http.get(format!("{}/v1/approvals/{}", self.api_url, external_ref)))
+            .await?;
+
+        let mut votes = Votes::default();
+        for vote in response.votes {
+            match vote.decision.as_str() {
+                "approve" => votes.approved.push(vote.operator),
+                "reject" => votes.rejected.push(vote.operator),
+                _ => {}
+            }
+        }
+        Ok(votes)
+    }
+}
+
+/// Approvals through a Squads v4 multisig proposal
+///
+/// The vault transaction and its proposal are created by the caller (see the
+/// Squads skill); `open` records the proposal address for the action, and
+/// `votes` reads the proposal account's approved/rejected member lists.
+pub struct SquadsApprovals {
+    rpc: solana_client::nonblocking::rpc_client::RpcClient,
+    /// Proposal address per action ID
+    proposals: BTreeMap<String, solana_sdk::pubkey::Pubkey>,
+}
+
+impl SquadsApprovals {
+    pub fn new(rpc_url: &str) -> Self {
+        Self {
+            rpc: solana_client::nonblocking::rpc_client::RpcClient::new(rpc_url.to_string()),
+            proposals: BTreeMap::new(),
+        }
+    }
+
+    /// Register the proposal created for an action before submitting it
+    pub fn with_proposal(mut self, action_id: &str, proposal: solana_sdk::pubkey::Pubkey) -> Self {
+        self.proposals.insert(action_id.to_string(), proposal);
+        self
+    }
+}
+
+impl ApprovalBackend for SquadsApprovals {
+    async fn open(
+        &self,
+        action: &AgentAction,
+        _policy: &ApprovalPolicy,
+        _expires_at: i64,
+    ) -> ApprovalResult<String> {
+        self.proposals
+            .get(&action.id)
+            .map(ToString::to_string)
+            .ok_or_else(|| {
+                ApprovalError::Backend(format!("no Squads proposal registered for {}", action.id))
+            })
+    }
+
+    async fn votes(&self, external_ref: &str) -> ApprovalResult<Votes> {
+        use anchor_lang::AccountDeserialize;
+        use squads_multisig::state::Proposal;
+
+        let address: solana_sdk::pubkey::Pubkey = external_ref
+            .parse()
+            .map_err(|_| ApprovalError::Backend(format!("invalid proposal address {}", external_ref)))?;
+        let data = self
+            .rpc
+            .get_account_data(&address)
+            .await
+            .map_err(|e| ApprovalError::Backend(e.to_string()))?;
+        let proposal = Proposal::try_deserialize(&mut data.as_slice())
+            .map_err(|e| ApprovalError::Backend(e.to_string()))?;
+
+        Ok(Votes {
+            approved: proposal.approved.iter().map(ToString::to_string).collect(),
+            rejected: proposal.rejected.iter().map(ToString::to_string).collect(),
+        })
+    }
+}
+
+// ============================================================================
+// GATE
+// ============================================================================
+
+/// What the executor should do with a submitted action
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GateDecision {
+    /// Below threshold: execute now
+    Release,
+    /// Held until approved; poll with `refresh`
+    Pending { expires_at: i64 },
+}
+
+/// Holds large actions until they collect enough operator approvals
+pub struct ApprovalGate<B: ApprovalBackend, S: AuditSink> {
+    policy: ApprovalPolicy,
+    backend: B,
+    audit: S,
+    requests: BTreeMap<String, ApprovalRequest>,
+}
+
+impl<B: ApprovalBackend, S: AuditSink> ApprovalGate<B, S> {
+    pub fn new(policy: ApprovalPolicy, backend: B, audit: S) -> ApprovalResult<Self> {
+        policy.validate()?;
+        Ok(Self {
+            policy,
+            backend,
+            audit,
+            requests: BTreeMap::new(),
+        })
+    }
+
+    pub fn policy(&self) -> &ApprovalPolicy {
+        &self.policy
+    }
+
+    pub fn request(&self, action_id: &str) -> Option<&ApprovalRequest> {
+        self.requests.get(action_id)
+    }
+
+    pub fn audit_sink(&self) -> &S {
+        &self.audit
+    }
+
+    fn log(&mut self, action: &AgentAction, now: i64, event: AuditEvent) {
+        self.audit.record(AuditEntry {
+            timestamp: now,
+            action_id: action.id.clone(),
+            action_kind: action.kind.clone(),
+            notional_usd: action.notional_usd,
+            event,
+        });
+    }
+
+    /// Submit an action; small ones are released immediately, large ones
+    /// open an approval. Resubmitting a held action returns its current state.
+    pub async fn submit(&mut self, action: AgentAction, now: i64) -> ApprovalResult<GateDecision> {
+        if let Some(existing) = self.requests.get(&action.id) {
+            return Ok(GateDecision::Pending {
+                expires_at: existing.expires_at,
+            });
+        }
+
+        if !self.policy.requires_approval(action.notional_usd) {
+            self.log(&action, now, AuditEvent::AutoReleased);
+            return Ok(GateDecision::Release);
+        }
+
+        let expires_at = now + self.policy.expiry_secs;
+        let external_ref = self.backend.open(&action, &self.policy, expires_at).await?;
+        self.log(
+            &action,
+            now,
+            AuditEvent::Requested {
+                required: self.policy.required_approvals,
+                operators: self.policy.operators.len(),
+            },
+        );
+        self.requests.insert(
+            action.id.clone(),
+            ApprovalRequest {
+                action,
+                status: ApprovalStatus::Pending,
+                created_at: now,
+                expires_at,
+                external_ref,
+                approvals: BTreeSet::new(),
+                rejections: BTreeSet::new(),
+            },
+        );
+        Ok(GateDecision::Pending { expires_at })
+    }
+
+    /// Pull votes for a pending request and update its status
+    pub async fn refresh(&mut self, action_id: &str, now: i64) -> ApprovalResult<ApprovalStatus> {
+        let request = self
+            .requests
+            .get(action_id)
+            .ok_or_else(|| ApprovalError::UnknownRequest(action_id.to_string()))?;
+        if request.status != ApprovalStatus::Pending {
+            return Ok(request.status);
+        }
+
+        let votes = if now < request.expires_at {
+            Some(self.backend.votes(&request.external_ref).await?)
+        } else {
+            None
+        };
+
+        let Some(votes) = votes else {
+            let action = request.action.clone();
+            self.requests.get_mut(action_id).unwrap().status = ApprovalStatus::Expired;
+            self.log(&action, now, AuditEvent::Expired);
+            return Ok(ApprovalStatus::Expired);
+        };
+
+        let mut events = Vec::new();
+        let request = self.requests.get_mut(action_id).unwrap();
+        for operator in votes.approved {
+            if self.policy.is_operator(&operator) && request.approvals.insert(operator.clone()) {
+                events.push(AuditEvent::Approved { operator });
+            }
+        }
+        for operator in votes.rejected {
+            if self.policy.is_operator(&operator) && request.rejections.insert(operator.clone()) {
+                events.push(AuditEvent::Rejected { operator });
+            }
+        }
+
+        // Rejected once enough operators object that N approvals are impossible
+        let operators = self.policy.operators.len();
+        if request.approvals.len() >= self.policy.required_approvals {
+            request.status = ApprovalStatus::Approved;
+        } else if operators - request.rejections.len() < self.policy.required_approvals {
+            request.status = ApprovalStatus::Rejected;
+        }
+
+        let (action, status) = (request.action.clone(), request.status);
+        for event in events {
+            self.log(&action, now, event);
+        }
+        Ok(status)
+    }
+
+    /// Hand an approved action to the executor; it can only be released once
+    pub fn release(&mut self, action_id: &str, now: i64) -> ApprovalResult<AgentAction> {
+        let request = self
+            .requests
+            .get_mut(action_id)
+            .ok_or_else(|| ApprovalError::UnknownRequest(action_id.to_string()))?;
+
+        // Approvals lapse at the deadline even if nobody released in time
+        if request.status == ApprovalStatus::Approved && now >= request.expires_at {
+            request.status = ApprovalStatus::Expired;
+            let action = request.action.clone();
+            self.log(&action, now, AuditEvent::Expired);
+            return Err(ApprovalError::NotApproved {
+                id: action_id.to_string(),
+                status: ApprovalStatus::Expired,
+            });
+        }
+        if request.status != ApprovalStatus::Approved {
+            return Err(ApprovalError::NotApproved {
+                id: action_id.to_string(),
+                status: request.status,
+            });
+        }
+
+        request.status = ApprovalStatus::Released;
+        let action = request.action.clone();
+        let approvals = request.approvals.iter().cloned().collect();
+        self.log(&action, now, AuditEvent::Released { approvals });
+        Ok(action)
+    }
+
+    /// Expire every pending or approved-but-unreleased request past its deadline
+    pub fn expire_stale(&mut self, now: i64) -> Vec<String> {
+        let stale: Vec<String> = self
+            .requests
+            .values()
+            .filter(|r| {
+                matches!(r.status, ApprovalStatus::Pending | ApprovalStatus::Approved)
+                    && now >= r.expires_at
+            })
+            .map(|r| r.action.id.clone())
+            .collect();
+
+        for id in &stale {
+            let request = self.requests.get_mut(id).unwrap();
+            request.status = ApprovalStatus::Expired;
+            let action = request.action.clone();
+            self.log(&action, now, AuditEvent::Expired);
+        }
+        stale
+    }
+}
@@ -0,0 +1,382 @@
+/**
+ * Dead Man's Switch
+ *
+ * If the agent stops receiving prices or loses RPC connectivity for longer
+ * than configured, nobody is steering its positions. The switch watches
+ * both heartbeats and, when either goes silent too long, flattens risk
+ * with transactions signed in advance: cancel resting orders and,
+ * optionally, hedge. It then alerts the operators.
+ *
+ * Flatten transactions use durable nonces instead of a recent blockhash,
+ * so they stay valid however long the switch stays armed and can be sent
+ * through fallback endpoints even when the primary RPC is the failure.
+ * Each transaction consumes its own nonce account. Rebuild the plan (and
+ * re-arm) whenever positions or resting orders change.
+ *
+ * Once triggered, the switch keeps sending the steps that have not landed
+ * on every later tick, until all of them land or an operator disarms it;
+ * only then is it `Flattened`. A send that times out on confirmation may
+ * still have landed, so before each attempt the step's nonce account is
+ * read: a nonce that has moved past the one the step was signed with
+ * means the step was executed and must not be counted as failed.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    solana-client = "1.18"
+ *    solana-sdk = "1.18"
+ *    thiserror = "1"
+ *
+ * 2. Create one nonce account per flatten transaction (e.g.
+ *    `solana create-nonce-account`), with the agent key as authority
+ * 3. Copy this file as `dead_mans_switch.rs` and declare `mod dead_mans_switch;`
+ */
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::nonce_utils::nonblocking::{data_from_account, get_account_with_commitment};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum SwitchError {
+    #[error("nonce account {0} is not usable: {1}")]
+    Nonce(Pubkey, String),
+
+    #[error("flatten step {0} has no instructions")]
+    EmptyStep(String),
+}
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+#[derive(Clone, Debug)]
+pub struct SwitchConfig {
+    /// Longest tolerated gap between fresh prices
+    pub max_price_silence_secs: i64,
+    /// Longest tolerated gap between successful RPC calls
+    pub max_rpc_silence_secs: i64,
+    /// Include hedge steps when flattening, not just cancellations
+    pub hedge_on_trigger: bool,
+}
+
+impl Default for SwitchConfig {
+    fn default() -> Self {
+        Self {
+            max_price_silence_secs: 60,
+            max_rpc_silence_secs: 30,
+            hedge_on_trigger: false,
+        }
+    }
+}
+
+impl SwitchConfig {
+    /// Whether steps of `kind` are sent when the switch fires
+    fn sends(&self, kind: StepKind) -> bool {
+        kind == StepKind::Cancel || self.hedge_on_trigger
+    }
+}
+
+// ============================================================================
+// FLATTEN PLAN
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepKind {
+    /// Cancel resting orders; always sent
+    Cancel,
+    /// Offset open exposure; sent only with `hedge_on_trigger`
+    Hedge,
+}
+
+/// One flatten transaction, before signing
+pub struct FlattenStep {
+    pub label: String,
+    pub kind: StepKind,
+    pub instructions: Vec<Instruction>,
+    /// Durable nonce account reserved for this step
+    pub nonce_account: Pubkey,
+}
+
+/// A signed flatten transaction
+#[derive(Clone, Debug)]
+pub struct PresignedStep {
+    pub label: String,
+    pub kind: StepKind,
+    pub transaction: Transaction,
+    /// Nonce account the transaction advances
+    pub nonce_account: Pubkey,
+}
+
+impl PresignedStep {
+    /// Whether the step's nonce has moved past the one it was signed
+    /// with, i.e. the transaction was executed
+    async fn nonce_used(&self, rpc: &RpcClient) -> Result<bool, SwitchError> {
+        let account = get_account_with_commitment(rpc, &self.nonce_account, CommitmentConfig::confirmed())
+            .await
+            .map_err(|e| SwitchError::Nonce(self.nonce_account, e.to_string()))?;
+        let nonce = data_from_account(&account)
+            .map_err(|e| SwitchError::Nonce(self.nonce_account, e.to_string()))?;
+        Ok(nonce.blockhash() != self.transaction.message.recent_blockhash)
+    }
+}
+
+/// Sign `step` against the current value of its nonce account. The nonce
+/// authority must sign; the advance instruction comes first, as the
+/// runtime requires.
+pub async fn presign_step(
+    rpc: &RpcClient,
+    step: FlattenStep,
+    payer: &Keypair,
+    nonce_authority: &Keypair,
+) -> Result<PresignedStep, SwitchError> {
+    if step.instructions.is_empty() {
+        return Err(SwitchError::EmptyStep(step.label));
+    }
+
+    let account = get_account_with_commitment(rpc, &step.nonce_account, CommitmentConfig::confirmed())
+        .await
+        .map_err(|e| SwitchError::Nonce(step.nonce_account, e.to_string()))?;
+    let nonce = data_from_account(&account)
+        .map_err(|e| SwitchError::Nonce(step.nonce_account, e.to_string()))?;
+    if nonce.authority != nonce_authority.pubkey() {
+        return Err(SwitchError::Nonce(
+            step.nonce_account,
+            format!("authority is {}", nonce.authority),
+        ));
+    }
+
+    let mut instructions = Vec::with_capacity(step.instructions.len() + 1);
+    instructions.push(system_instruction::advance_nonce_account(
+        &step.nonce_account,
+        &nonce_authority.pubkey(),
+    ));
+    instructions.extend(step.instructions);
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    if payer.pubkey() == nonce_authority.pubkey() {
+        transaction.sign(&[payer], nonce.blockhash());
+    } else {
+        transaction.sign(&[payer, nonce_authority], nonce.blockhash());
+    }
+
+    Ok(PresignedStep {
+        label: step.label,
+        kind: step.kind,
+        transaction,
+        nonce_account: step.nonce_account,
+    })
+}
+
+// ============================================================================
+// SWITCH
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TriggerReason {
+    PriceSilence { silent_secs: i64 },
+    RpcSilence { silent_secs: i64 },
+}
+
+impl std::fmt::Display for TriggerReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PriceSilence { silent_secs } => write!(f, "no fresh price for {}s", silent_secs),
+            Self::RpcSilence { silent_secs } => write!(f, "no RPC response for {}s", silent_secs),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SwitchState {
+    Disarmed,
+    Armed,
+    /// Fired; steps that have not landed are resent on every tick
+    Triggered { reason: TriggerReason, at: i64 },
+    /// Every step of the plan landed
+    Flattened { reason: TriggerReason, at: i64, completed_at: i64 },
+}
+
+/// Result of sending one flatten step
+#[derive(Debug)]
+pub struct StepResult {
+    pub label: String,
+    /// Endpoint that accepted it, if any
+    pub endpoint: Option<String>,
+    pub signature: Option<Signature>,
+    pub errors: Vec<String>,
+}
+
+/// Where operator alerts go (pager, chat webhook, email)
+pub trait OperatorAlerts {
+    fn alert(&self, message: &str);
+}
+
+pub struct DeadMansSwitch {
+    config: SwitchConfig,
+    state: SwitchState,
+    plan: Vec<PresignedStep>,
+    /// Signature of each plan step once it has landed
+    landed: Vec<Option<Signature>>,
+    last_price_at: i64,
+    last_rpc_ok_at: i64,
+}
+
+impl DeadMansSwitch {
+    pub fn new(config: SwitchConfig, now: i64) -> Self {
+        Self {
+            config,
+            state: SwitchState::Disarmed,
+            plan: Vec::new(),
+            landed: Vec::new(),
+            last_price_at: now,
+            last_rpc_ok_at: now,
+        }
+    }
+
+    pub fn state(&self) -> &SwitchState {
+        &self.state
+    }
+
+    /// Replace the flatten plan and arm. Silence is measured from `now`, so
+    /// re-arming after an outage does not fire immediately.
+    pub fn arm(&mut self, plan: Vec<PresignedStep>, now: i64) {
+        self.landed = vec![None; plan.len()];
+        self.plan = plan;
+        self.state = SwitchState::Armed;
+        self.last_price_at = now;
+        self.last_rpc_ok_at = now;
+    }
+
+    pub fn disarm(&mut self) {
+        self.state = SwitchState::Disarmed;
+        self.plan.clear();
+        self.landed.clear();
+    }
+
+    /// Call for every fresh, validated price
+    pub fn price_heartbeat(&mut self, now: i64) {
+        self.last_price_at = self.last_price_at.max(now);
+    }
+
+    /// Call after every successful RPC round trip
+    pub fn rpc_heartbeat(&mut self, now: i64) {
+        self.last_rpc_ok_at = self.last_rpc_ok_at.max(now);
+    }
+
+    /// Whether the switch should fire at `now`
+    pub fn check(&self, now: i64) -> Option<TriggerReason> {
+        if self.state != SwitchState::Armed {
+            return None;
+        }
+        let rpc_silence = now - self.last_rpc_ok_at;
+        if rpc_silence > self.config.max_rpc_silence_secs {
+            return Some(TriggerReason::RpcSilence {
+                silent_secs: rpc_silence,
+            });
+        }
+        let price_silence = now - self.last_price_at;
+        if price_silence > self.config.max_price_silence_secs {
+            return Some(TriggerReason::PriceSilence {
+                silent_secs: price_silence,
+            });
+        }
+        None
+    }
+
+    /// Check and, if silent too long, send the plan and alert; once
+    /// triggered, resend the steps that have not landed on every call.
+    /// Each step is tried on every endpoint in order until one accepts it;
+    /// the primary RPC may be the thing that failed, so pass fallbacks.
+    /// `None` when there is nothing to send.
+    pub async fn tick(
+        &mut self,
+        now: i64,
+        endpoints: &[RpcClient],
+        alerts: &dyn OperatorAlerts,
+    ) -> Option<Vec<StepResult>> {
+        let (reason, at) = match &self.state {
+            SwitchState::Armed => {
+                let reason = self.check(now)?;
+                alerts.alert(&format!("dead man's switch triggered: {}; flattening", reason));
+                self.state = SwitchState::Triggered {
+                    reason: reason.clone(),
+                    at: now,
+                };
+                (reason, now)
+            }
+            SwitchState::Triggered { reason, at } => (reason.clone(), *at),
+            SwitchState::Disarmed | SwitchState::Flattened { .. } => return None,
+        };
+
+        let mut results = Vec::new();
+        for (index, step) in self.plan.iter().enumerate() {
+            if !self.config.sends(step.kind) || self.landed[index].is_some() {
+                continue;
+            }
+            let mut result = StepResult {
+                label: step.label.clone(),
+                endpoint: None,
+                signature: None,
+                errors: Vec::new(),
+            };
+            for rpc in endpoints {
+                // An earlier send may have landed after its confirmation
+                // timed out; resending it would only fail on the nonce
+                match step.nonce_used(rpc).await {
+                    Ok(true) => {
+                        result.endpoint = Some(rpc.url());
+                        result.signature = Some(step.transaction.signatures[0]);
+                        break;
+                    }
+                    Ok(false) => {}
+                    Err(err) => {
+                        result.errors.push(format!("{}: {}", rpc.url(), err));
+                        continue;
+                    }
+                }
+                match rpc.send_and_confirm_transaction(&step.transaction).await {
+                    Ok(signature) => {
+                        result.endpoint = Some(rpc.url());
+                        result.signature = Some(signature);
+                        break;
+                    }
+                    Err(err) => result.errors.push(format!("{}: {}", rpc.url(), err)),
+                }
+            }
+            match result.signature {
+                Some(signature) => alerts.alert(&format!("flatten step {} landed: {}", step.label, signature)),
+                None => alerts.alert(&format!(
+                    "flatten step {} FAILED on every endpoint, retrying next tick: {}",
+                    step.label,
+                    result.errors.join("; ")
+                )),
+            }
+            self.landed[index] = result.signature;
+            results.push(result);
+        }
+
+        let done = self
+            .plan
+            .iter()
+            .zip(&self.landed)
+            .all(|(step, landed)| landed.is_some() || !self.config.sends(step.kind));
+        if done {
+            self.state = SwitchState::Flattened {
+                reason,
+                at,
+                completed_at: now,
+            };
+            alerts.alert("dead man's switch: every flatten step landed");
+        }
+        Some(results)
+    }
+}
@@ -0,0 +1,270 @@
+/**
+ * Withdrawal Allow-List and Destination Policy
+ *
+ * Last line of defense against a compromised strategy: every transaction
+ * the agent builds is checked, before signing, against a fixed list of
+ * destinations it may ever send value to and programs it may ever invoke.
+ * The check is static - it decodes each instruction's data and accounts
+ * (System and SPL Token / Token-2022 transfers, approvals, account closes,
+ * authority changes) and never trusts what the strategy says it is doing.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    solana-sdk = "1.18"
+ *    spl-associated-token-account = "3"
+ *    thiserror = "1"
+ *
+ * 2. Copy this file as `destination_policy.rs` and declare `mod destination_policy;`
+ * 3. Call `DestinationPolicy::check_instructions` in the single place that
+ *    signs transactions, and refuse to sign on any violation
+ */
+
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeSet;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+pub const SYSTEM_PROGRAM_ID: Pubkey = solana_sdk::system_program::ID;
+
+pub const TOKEN_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+pub const TOKEN_2022_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("ComputeBudget111111111111111111111111111111");
+
+/// System program instruction tags (bincode u32, little-endian)
+mod system_ix {
+    pub const ASSIGN: u32 = 1;
+    pub const TRANSFER: u32 = 2;
+    pub const WITHDRAW_NONCE_ACCOUNT: u32 = 5;
+    pub const ASSIGN_WITH_SEED: u32 = 10;
+    pub const TRANSFER_WITH_SEED: u32 = 11;
+}
+
+/// SPL Token instruction tags (shared by Token-2022)
+mod token_ix {
+    pub const TRANSFER: u8 = 3;
+    pub const APPROVE: u8 = 4;
+    pub const SET_AUTHORITY: u8 = 6;
+    pub const CLOSE_ACCOUNT: u8 = 9;
+    pub const TRANSFER_CHECKED: u8 = 12;
+    pub const APPROVE_CHECKED: u8 = 13;
+}
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Clone, Debug, thiserror::Error, PartialEq, Eq)]
+pub enum PolicyViolation {
+    #[error("instruction {index}: program {program} is not allowed")]
+    ProgramNotAllowed { index: usize, program: Pubkey },
+
+    #[error("instruction {index}: {action} to {destination} is not on the allow-list")]
+    DestinationNotAllowed {
+        index: usize,
+        action: &'static str,
+        destination: Pubkey,
+    },
+
+    #[error("instruction {index}: {action} is never allowed")]
+    ForbiddenAction { index: usize, action: &'static str },
+
+    #[error("instruction {index}: could not decode {program} instruction")]
+    Undecodable { index: usize, program: Pubkey },
+}
+
+// ============================================================================
+// POLICY
+// ============================================================================
+
+/// Where the agent may send value and which programs it may call
+#[derive(Clone, Debug, Default)]
+pub struct DestinationPolicy {
+    /// Wallets and token accounts value may be sent to
+    pub allowed_destinations: BTreeSet<Pubkey>,
+    /// Programs the agent may invoke besides System, Token, Token-2022,
+    /// Associated Token and Compute Budget (e.g. a DEX aggregator). Their
+    /// instructions are not decoded, so only list programs that cannot pay
+    /// out to arbitrary accounts.
+    pub allowed_programs: BTreeSet<Pubkey>,
+    /// The agent's own wallet; transfers back to it are always allowed
+    pub treasury: Option<Pubkey>,
+}
+
+impl DestinationPolicy {
+    pub fn new(treasury: Pubkey) -> Self {
+        Self {
+            treasury: Some(treasury),
+            ..Self::default()
+        }
+    }
+
+    /// Allow a wallet (SOL transfers only)
+    pub fn allow_destination(mut self, destination: Pubkey) -> Self {
+        self.allowed_destinations.insert(destination);
+        self
+    }
+
+    /// Allow a wallet and its associated token accounts for `mints`
+    pub fn allow_wallet(mut self, wallet: Pubkey, mints: &[Pubkey], token_program: &Pubkey) -> Self {
+        self.allowed_destinations.insert(wallet);
+        for mint in mints {
+            self.allowed_destinations.insert(
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &wallet,
+                    mint,
+                    token_program,
+                ),
+            );
+        }
+        self
+    }
+
+    pub fn allow_program(mut self, program: Pubkey) -> Self {
+        self.allowed_programs.insert(program);
+        self
+    }
+
+    fn is_allowed_destination(&self, destination: &Pubkey) -> bool {
+        self.treasury.as_ref() == Some(destination) || self.allowed_destinations.contains(destination)
+    }
+
+    fn is_allowed_program(&self, program: &Pubkey) -> bool {
+        [
+            SYSTEM_PROGRAM_ID,
+            TOKEN_PROGRAM_ID,
+            TOKEN_2022_PROGRAM_ID,
+            ASSOCIATED_TOKEN_PROGRAM_ID,
+            COMPUTE_BUDGET_PROGRAM_ID,
+        ]
+        .contains(program)
+            || self.allowed_programs.contains(program)
+    }
+
+    fn require_destination(
+        &self,
+        index: usize,
+        action: &'static str,
+        ix: &Instruction,
+        account_index: usize,
+    ) -> Result<(), PolicyViolation> {
+        let destination = ix
+            .accounts
+            .get(account_index)
+            .map(|meta| meta.pubkey)
+            .ok_or(PolicyViolation::Undecodable {
+                index,
+                program: ix.program_id,
+            })?;
+        if self.is_allowed_destination(&destination) {
+            Ok(())
+        } else {
+            Err(PolicyViolation::DestinationNotAllowed {
+                index,
+                action,
+                destination,
+            })
+        }
+    }
+
+    fn check_system(&self, index: usize, ix: &Instruction) -> Result<(), PolicyViolation> {
+        let Some(tag) = ix.data.get(..4) else {
+            return Err(PolicyViolation::Undecodable {
+                index,
+                program: ix.program_id,
+            });
+        };
+        match u32::from_le_bytes(tag.try_into().unwrap()) {
+            system_ix::TRANSFER => self.require_destination(index, "SOL transfer", ix, 1),
+            system_ix::TRANSFER_WITH_SEED => self.require_destination(index, "SOL transfer", ix, 2),
+            system_ix::WITHDRAW_NONCE_ACCOUNT => self.require_destination(index, "nonce withdrawal", ix, 1),
+            system_ix::ASSIGN | system_ix::ASSIGN_WITH_SEED => Err(PolicyViolation::ForbiddenAction {
+                index,
+                action: "account reassignment",
+            }),
+            // Account creation (rent stays with the new account), nonce
+            // advance/authorize, allocation
+            _ => Ok(()),
+        }
+    }
+
+    fn check_token(&self, index: usize, ix: &Instruction) -> Result<(), PolicyViolation> {
+        let Some(&tag) = ix.data.first() else {
+            return Err(PolicyViolation::Undecodable {
+                index,
+                program: ix.program_id,
+            });
+        };
+        match tag {
+            token_ix::TRANSFER => self.require_destination(index, "token transfer", ix, 1),
+            token_ix::TRANSFER_CHECKED => self.require_destination(index, "token transfer", ix, 2),
+            token_ix::APPROVE => self.require_destination(index, "token approval", ix, 1),
+            token_ix::APPROVE_CHECKED => self.require_destination(index, "token approval", ix, 2),
+            token_ix::CLOSE_ACCOUNT => self.require_destination(index, "account close", ix, 1),
+            token_ix::SET_AUTHORITY => Err(PolicyViolation::ForbiddenAction {
+                index,
+                action: "token authority change",
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check one instruction; `index` is its position for error reporting
+    pub fn check_instruction(&self, index: usize, ix: &Instruction) -> Result<(), PolicyViolation> {
+        if !self.is_allowed_program(&ix.program_id) {
+            return Err(PolicyViolation::ProgramNotAllowed {
+                index,
+                program: ix.program_id,
+            });
+        }
+
+        if ix.program_id == SYSTEM_PROGRAM_ID {
+            self.check_system(index, ix)
+        } else if ix.program_id == TOKEN_PROGRAM_ID || ix.program_id == TOKEN_2022_PROGRAM_ID {
+            self.check_token(index, ix)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check every instruction of a transaction before signing
+    pub fn check_instructions(&self, instructions: &[Instruction]) -> Result<(), PolicyViolation> {
+        instructions
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, ix)| self.check_instruction(index, ix))
+    }
+
+    /// Check a compiled message (e.g. a transaction returned by an aggregator API)
+    pub fn check_message(&self, message: &solana_sdk::message::Message) -> Result<(), PolicyViolation> {
+        let instructions: Vec<Instruction> = message
+            .instructions
+            .iter()
+            .map(|compiled| Instruction {
+                program_id: message.account_keys[compiled.program_id_index as usize],
+                accounts: compiled
+                    .accounts
+                    .iter()
+                    .map(|&i| solana_sdk::instruction::AccountMeta {
+                        pubkey: message.account_keys[i as usize],
+                        is_signer: message.is_signer(i as usize),
+                        is_writable: message.is_writable(i as usize),
+                    })
+                    .collect(),
+                data: compiled.data.clone(),
+            })
+            .collect();
+        self.check_instructions(&instructions)
+    }
+}
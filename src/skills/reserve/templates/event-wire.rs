@@ -0,0 +1,261 @@
+/**
+ * Versioned Wire Format for Agent Events
+ *
+ * One JSON envelope for everything the agent publishes outside the
+ * process: oracle prices it acted on, actions it took, and fills it
+ * reconciled. Message sinks (Redis streams, Kafka topics), replay
+ * recordings, and the control plane should all encode with `encode` and
+ * decode with `decode`, so external consumers depend on this schema and
+ * not on internal structs.
+ *
+ * Compatibility rules for schema version `WIRE_VERSION`:
+ * - Adding an optional field (`#[serde(default)]`) or a new event kind is
+ *   compatible and keeps the version.
+ * - Renaming, removing, retyping, or changing the meaning of a field bumps
+ *   `WIRE_VERSION`; decoders reject versions they do not know.
+ * - Decoders ignore unknown fields, and decode unknown kinds as
+ *   `EventBody::Unknown` with the payload kept, so older consumers (and
+ *   relays that re-encode) keep running when producers add fields or
+ *   kinds.
+ * - Integers that can exceed 2^53 (amounts, prices) are strings on the
+ *   wire, so JavaScript consumers do not lose precision.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    pyth-oracle = { path = "../pyth-oracle", features = ["no-entrypoint"] }
+ *    serde = { version = "1", features = ["derive"] }
+ *    serde_json = "1"
+ *    serde_with = "3"
+ *    thiserror = "1"
+ *
+ * 2. Copy this file as `event_wire.rs` and declare `mod event_wire;`
+ * 3. Run the compatibility tests in examples/event-wire-compat.rs after
+ *    every change to this file
+ */
+
+use pyth_oracle::OraclePrice;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Schema version written by `encode`
+pub const WIRE_VERSION: u16 = 1;
+
+/// Oldest schema version `decode` accepts
+pub const MIN_WIRE_VERSION: u16 = 1;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum WireError {
+    #[error("unsupported wire version {0} (supported {MIN_WIRE_VERSION}..={WIRE_VERSION})")]
+    UnsupportedVersion(u16),
+
+    #[error("malformed event: {0}")]
+    Malformed(#[from] serde_json::Error),
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+/// Envelope shared by every event. On the wire the body is two fields,
+/// `kind` and `data`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WireEvent {
+    /// Schema version
+    pub v: u16,
+    /// Unique per event; consumers use it to drop duplicates
+    pub id: String,
+    /// Unix milliseconds the event was produced
+    pub ts_ms: i64,
+    /// Producing component, e.g. "executor", "price-stream"
+    pub source: String,
+    pub body: EventBody,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EventBody {
+    Price(PriceEvent),
+    Action(ActionEvent),
+    Fill(FillEvent),
+    /// A kind added after this decoder was built, passed through as is
+    Unknown { kind: String, data: serde_json::Value },
+}
+
+/// An oracle price the agent observed or acted on
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceEvent {
+    /// Hex feed ID without 0x
+    pub feed_id: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub price: i64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+}
+
+/// Something the agent decided to do
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ActionEvent {
+    /// Action name, e.g. "swap", "rebalance", "flatten"
+    pub action: String,
+    /// Action-specific parameters
+    #[serde(default)]
+    pub params: serde_json::Value,
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Transaction signature once sent
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+/// Same meanings as `Outcome` in slippage-reconciliation.rs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FillOutcome {
+    Filled,
+    Failed,
+    Expired,
+    Mismatched,
+}
+
+/// A reconciled trade (a `Reconciliation` from slippage-reconciliation.rs)
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FillEvent {
+    pub signature: String,
+    pub venue: String,
+    pub outcome: FillOutcome,
+    pub input_mint: String,
+    pub output_mint: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub input_amount: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub expected_output: u64,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub realized_output: Option<u64>,
+    #[serde(default)]
+    pub slippage_bps: Option<i64>,
+}
+
+impl PriceEvent {
+    pub fn from_oracle(feed_id: &[u8; 32], price: &OraclePrice) -> Self {
+        Self {
+            feed_id: feed_id.iter().map(|b| format!("{:02x}", b)).collect(),
+            price: price.price,
+            conf: price.conf,
+            exponent: price.exponent,
+            publish_time: price.publish_time,
+        }
+    }
+}
+
+impl EventBody {
+    /// Value of the `kind` field
+    pub fn kind(&self) -> &str {
+        match self {
+            EventBody::Price(_) => "price",
+            EventBody::Action(_) => "action",
+            EventBody::Fill(_) => "fill",
+            EventBody::Unknown { kind, .. } => kind,
+        }
+    }
+}
+
+impl WireEvent {
+    /// An event at the current schema version
+    pub fn new(id: impl Into<String>, ts_ms: i64, source: impl Into<String>, body: EventBody) -> Self {
+        Self {
+            v: WIRE_VERSION,
+            id: id.into(),
+            ts_ms,
+            source: source.into(),
+            body,
+        }
+    }
+
+    /// Topic or stream name, versioned so a breaking change gets a new one:
+    /// `automaton.v1.price`
+    pub fn topic(&self) -> String {
+        format!("automaton.v{}.{}", self.v, self.body.kind())
+    }
+
+    /// Partition key that keeps one feed's or one venue's events in order
+    pub fn partition_key(&self) -> &str {
+        match &self.body {
+            EventBody::Price(price) => &price.feed_id,
+            EventBody::Action(action) => &action.action,
+            EventBody::Fill(fill) => &fill.venue,
+            EventBody::Unknown { .. } => &self.source,
+        }
+    }
+}
+
+// ============================================================================
+// ENCODING
+// ============================================================================
+
+/// The envelope as it appears on the wire
+#[derive(Serialize, Deserialize)]
+struct RawEvent {
+    v: u16,
+    id: String,
+    ts_ms: i64,
+    source: String,
+    kind: String,
+    #[serde(default)]
+    data: serde_json::Value,
+}
+
+/// Serialize an event as one line of JSON
+pub fn encode(event: &WireEvent) -> Result<Vec<u8>, WireError> {
+    let data = match &event.body {
+        EventBody::Price(price) => serde_json::to_value(price)?,
+        EventBody::Action(action) => serde_json::to_value(action)?,
+        EventBody::Fill(fill) => serde_json::to_value(fill)?,
+        EventBody::Unknown { data, .. } => data.clone(),
+    };
+    Ok(serde_json::to_vec(&RawEvent {
+        v: event.v,
+        id: event.id.clone(),
+        ts_ms: event.ts_ms,
+        source: event.source.clone(),
+        kind: event.body.kind().to_string(),
+        data,
+    })?)
+}
+
+/// Parse an event, checking the schema version before the body
+pub fn decode(bytes: &[u8]) -> Result<WireEvent, WireError> {
+    let raw: RawEvent = serde_json::from_slice(bytes)?;
+    if !(MIN_WIRE_VERSION..=WIRE_VERSION).contains(&raw.v) {
+        return Err(WireError::UnsupportedVersion(raw.v));
+    }
+
+    let body = match raw.kind.as_str() {
+        "price" => EventBody::Price(serde_json::from_value(raw.data)?),
+        "action" => EventBody::Action(serde_json::from_value(raw.data)?),
+        "fill" => EventBody::Fill(serde_json::from_value(raw.data)?),
+        _ => EventBody::Unknown {
+            kind: raw.kind,
+            data: raw.data,
+        },
+    };
+    Ok(WireEvent {
+        v: raw.v,
+        id: raw.id,
+        ts_ms: raw.ts_ms,
+        source: raw.source,
+        body,
+    })
+}
@@ -0,0 +1,1083 @@
+/**
+ * Pluggable Execution Venues
+ *
+ * Puts swap execution behind one `Venue` trait (quote, build, execute,
+ * fees) so strategies do not depend on a particular aggregator or pool.
+ * Implementations:
+ * - `JupiterVenue`: the Jupiter swap API (quote + swap-instructions)
+ * - `RaydiumCpmmVenue`: direct swaps against Raydium CPMM pools, quoted
+ *   exactly from vault balances
+ * - `OrcaWhirlpoolVenue`: direct swaps against Orca Whirlpools, quoted
+ *   from the active liquidity only (an estimate; see the venue docs)
+ * - `RfqVenue`: a market maker's HTTP RFQ endpoint returning a
+ *   transaction the maker has already signed
+ *
+ * `VenueRouter` asks every venue for a quote and picks the one closest to
+ * (or furthest above) the oracle-implied output, so the routing decision
+ * uses the same oracle math as the rebalance estimator and the slippage
+ * reconciliation.
+ *
+ * The trait uses `async_trait` rather than native async fns because the
+ * router holds venues of different types as `Box<dyn Venue>`.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    async-trait = "0.1"
+ *    base64 = "0.21"
+ *    bincode = "1.3"
+ *    futures = "0.3"
+ *    reqwest = { version = "0.11", features = ["json"] }
+ *    serde = { version = "1", features = ["derive"] }
+ *    serde_json = "1"
+ *    solana-address-lookup-table-program = "1.18"
+ *    solana-client = "1.18"
+ *    solana-sdk = "1.18"
+ *    spl-associated-token-account = "2"
+ *    spl-token = "4"
+ *    thiserror = "1"
+ *
+ * 2. Copy this file as `execution_venues.rs` and declare `mod execution_venues;`
+ * 3. Register the venues you trade on with `VenueRouter::with_venue` and
+ *    route with the oracle-implied output from `calculate_tokens_for_usd`
+ */
+
+use async_trait::async_trait;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use solana_address_lookup_table_program::state::AddressLookupTable;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::address_lookup_table_account::AddressLookupTableAccount;
+use solana_sdk::hash::hash;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::message::{v0, VersionedMessage};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::VersionedTransaction;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use std::sync::Arc;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Basis points in 100%
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Jupiter swap API
+pub const JUPITER_API_URL: &str = "https://lite-api.jup.ag/swap/v1";
+
+/// Raydium CPMM program
+pub const RAYDIUM_CPMM_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C");
+
+/// Orca Whirlpool program
+pub const ORCA_WHIRLPOOL_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+
+/// Raydium CPMM and Orca Whirlpool fee rates are in millionths
+const FEE_RATE_DENOMINATOR: u128 = 1_000_000;
+
+/// Ticks per Whirlpool tick array
+const TICK_ARRAY_SIZE: i32 = 88;
+
+/// Whirlpool sqrt price bounds (Q64.64), used as "no limit"
+const MIN_SQRT_PRICE_X64: u128 = 4_295_048_016;
+const MAX_SQRT_PRICE_X64: u128 = 79_226_673_515_401_279_992_447_579_055;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum VenueError {
+    #[error("{venue} has no route for this pair")]
+    NoRoute { venue: String },
+
+    #[error("{venue} request failed: {message}")]
+    Request { venue: String, message: String },
+
+    #[error("{venue} returned an invalid response: {message}")]
+    InvalidResponse { venue: String, message: String },
+
+    #[error("quote from {venue} was issued for another venue")]
+    WrongVenue { venue: String },
+
+    #[error("quote expired at {expires_at}")]
+    QuoteExpired { expires_at: i64 },
+
+    #[error("no venue returned a usable quote")]
+    NoQuotes,
+
+    #[error(transparent)]
+    Rpc(#[from] solana_client::client_error::ClientError),
+
+    #[error("signing failed: {0}")]
+    Signing(String),
+}
+
+pub type VenueResult<T> = std::result::Result<T, VenueError>;
+
+fn request_error(venue: &str, e: impl std::fmt::Display) -> VenueError {
+    VenueError::Request {
+        venue: venue.to_string(),
+        message: e.to_string(),
+    }
+}
+
+fn invalid_response(venue: &str, message: impl Into<String>) -> VenueError {
+    VenueError::InvalidResponse {
+        venue: venue.to_string(),
+        message: message.into(),
+    }
+}
+
+// ============================================================================
+// QUOTES
+// ============================================================================
+
+/// An exact-in swap request
+#[derive(Clone, Debug)]
+pub struct QuoteRequest {
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub amount_in: u64,
+    /// Tolerance applied to the quoted output for `min_amount_out`
+    pub slippage_bps: u64,
+    /// Wallet that signs and owns the token accounts
+    pub user: Pubkey,
+}
+
+/// Fees a venue charges on top of what its quoted output already includes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct VenueFees {
+    /// Pool or platform fee, for reporting; quoted outputs are already net
+    /// of it
+    pub fee_bps: u64,
+    /// Network and priority fees for one swap, in lamports
+    pub network_fee_lamports: u64,
+}
+
+/// A venue's answer to a `QuoteRequest`
+#[derive(Clone, Debug)]
+pub struct VenueQuote {
+    pub venue: String,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub amount_in: u64,
+    /// Output the venue expects to deliver, after its fees
+    pub amount_out: u64,
+    /// Output below which the swap fails on chain
+    pub min_amount_out: u64,
+    /// Whether `amount_out` is an estimate rather than the venue's exact
+    /// answer
+    pub estimated: bool,
+    /// Unix seconds after which the quote cannot be executed
+    pub expires_at: Option<i64>,
+    /// Venue-specific data `build` needs (route, pool, RFQ transaction)
+    pub route: serde_json::Value,
+}
+
+impl VenueQuote {
+    /// Shortfall against the oracle-implied output in bps; negative when
+    /// the venue beats the oracle
+    pub fn oracle_slippage_bps(&self, oracle_expected_out: u64) -> i64 {
+        if oracle_expected_out == 0 {
+            return 0;
+        }
+        let diff = oracle_expected_out as i128 - self.amount_out as i128;
+        (diff * BPS_DENOMINATOR as i128 / oracle_expected_out as i128) as i64
+    }
+}
+
+fn min_out(amount_out: u64, slippage_bps: u64) -> u64 {
+    (amount_out as u128 * BPS_DENOMINATOR.saturating_sub(slippage_bps) as u128
+        / BPS_DENOMINATOR as u128) as u64
+}
+
+/// What `build` returns
+pub enum SwapPayload {
+    /// Instructions for the agent to sign and send
+    Instructions {
+        instructions: Vec<Instruction>,
+        lookup_tables: Vec<Pubkey>,
+    },
+    /// A transaction the counterparty has signed; the agent adds its
+    /// signature
+    Signed(VersionedTransaction),
+}
+
+// ============================================================================
+// VENUE TRAIT
+// ============================================================================
+
+#[async_trait]
+pub trait Venue: Send + Sync {
+    /// Label used in quotes, reconciliation, and cost models
+    fn name(&self) -> &str;
+
+    fn fees(&self) -> VenueFees;
+
+    async fn quote(&self, request: &QuoteRequest) -> VenueResult<VenueQuote>;
+
+    async fn build(&self, quote: &VenueQuote, user: &Pubkey) -> VenueResult<SwapPayload>;
+
+    /// Build, sign, and send a quoted swap
+    async fn execute(
+        &self,
+        quote: &VenueQuote,
+        rpc: &RpcClient,
+        signer: &Keypair,
+        now: i64,
+    ) -> VenueResult<Signature> {
+        if quote.venue != self.name() {
+            return Err(VenueError::WrongVenue {
+                venue: quote.venue.clone(),
+            });
+        }
+        if let Some(expires_at) = quote.expires_at {
+            if now >= expires_at {
+                return Err(VenueError::QuoteExpired { expires_at });
+            }
+        }
+
+        let tx = match self.build(quote, &signer.pubkey()).await? {
+            SwapPayload::Instructions {
+                instructions,
+                lookup_tables,
+            } => {
+                let tables = load_lookup_tables(rpc, &lookup_tables).await?;
+                let blockhash = rpc.get_latest_blockhash().await?;
+                let message = v0::Message::try_compile(&signer.pubkey(), &instructions, &tables, blockhash)
+                    .map_err(|e| VenueError::Signing(e.to_string()))?;
+                VersionedTransaction::try_new(VersionedMessage::V0(message), &[signer])
+                    .map_err(|e| VenueError::Signing(e.to_string()))?
+            }
+            SwapPayload::Signed(tx) => add_signature(tx, signer)?,
+        };
+        Ok(rpc.send_and_confirm_transaction(&tx).await?)
+    }
+}
+
+async fn load_lookup_tables(
+    rpc: &RpcClient,
+    addresses: &[Pubkey],
+) -> VenueResult<Vec<AddressLookupTableAccount>> {
+    if addresses.is_empty() {
+        return Ok(Vec::new());
+    }
+    let accounts = rpc.get_multiple_accounts(addresses).await?;
+    addresses
+        .iter()
+        .zip(accounts)
+        .map(|(key, account)| {
+            let account = account.ok_or_else(|| invalid_response("rpc", format!("missing lookup table {}", key)))?;
+            let table = AddressLookupTable::deserialize(&account.data)
+                .map_err(|e| invalid_response("rpc", e.to_string()))?;
+            Ok(AddressLookupTableAccount {
+                key: *key,
+                addresses: table.addresses.to_vec(),
+            })
+        })
+        .collect()
+}
+
+/// Fill in the agent's signature on a counterparty-signed transaction
+fn add_signature(mut tx: VersionedTransaction, signer: &Keypair) -> VenueResult<VersionedTransaction> {
+    let keys = tx.message.static_account_keys();
+    let required = tx.message.header().num_required_signatures as usize;
+    let index = keys[..required.min(keys.len())]
+        .iter()
+        .position(|key| *key == signer.pubkey())
+        .ok_or_else(|| VenueError::Signing("transaction does not require our signature".into()))?;
+    tx.signatures[index] = signer.sign_message(&tx.message.serialize());
+    Ok(tx)
+}
+
+// ============================================================================
+// ROUTER
+// ============================================================================
+
+/// A quote chosen by the router, with how it compares to the oracle
+#[derive(Clone, Debug)]
+pub struct RoutedQuote {
+    pub quote: VenueQuote,
+    /// Shortfall against the oracle-implied output, in bps
+    pub oracle_slippage_bps: i64,
+}
+
+/// Why a venue was not chosen
+#[derive(Debug)]
+pub struct RejectedQuote {
+    pub venue: String,
+    pub reason: String,
+}
+
+pub struct VenueRouter {
+    venues: Vec<Box<dyn Venue>>,
+    /// Quotes more than this far below the oracle are never chosen
+    pub max_oracle_slippage_bps: i64,
+}
+
+impl VenueRouter {
+    pub fn new(max_oracle_slippage_bps: i64) -> Self {
+        Self {
+            venues: Vec::new(),
+            max_oracle_slippage_bps,
+        }
+    }
+
+    pub fn with_venue(mut self, venue: impl Venue + 'static) -> Self {
+        self.venues.push(Box::new(venue));
+        self
+    }
+
+    pub fn venue(&self, name: &str) -> Option<&dyn Venue> {
+        self.venues.iter().find(|v| v.name() == name).map(|v| v.as_ref())
+    }
+
+    pub fn venues(&self) -> impl Iterator<Item = &dyn Venue> {
+        self.venues.iter().map(|v| v.as_ref())
+    }
+
+    /// Quote every venue concurrently and return the best oracle-relative
+    /// quote, plus the reasons the others were passed over. Exact quotes
+    /// win ties against estimates.
+    pub async fn route(
+        &self,
+        request: &QuoteRequest,
+        oracle_expected_out: u64,
+    ) -> (VenueResult<RoutedQuote>, Vec<RejectedQuote>) {
+        let quotes =
+            futures::future::join_all(self.venues.iter().map(|venue| venue.quote(request))).await;
+
+        let mut rejected = Vec::new();
+        let mut best: Option<RoutedQuote> = None;
+        for (venue, quote) in self.venues.iter().zip(quotes) {
+            let quote = match quote {
+                Ok(quote) => quote,
+                Err(e) => {
+                    rejected.push(RejectedQuote {
+                        venue: venue.name().to_string(),
+                        reason: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+            let slippage = quote.oracle_slippage_bps(oracle_expected_out);
+            if slippage > self.max_oracle_slippage_bps {
+                rejected.push(RejectedQuote {
+                    venue: quote.venue.clone(),
+                    reason: format!("{} bps below oracle", slippage),
+                });
+                continue;
+            }
+
+            let better = match &best {
+                None => true,
+                Some(current) => {
+                    (slippage, quote.estimated) < (current.oracle_slippage_bps, current.quote.estimated)
+                }
+            };
+            let candidate = RoutedQuote {
+                quote,
+                oracle_slippage_bps: slippage,
+            };
+            if better {
+                if let Some(previous) = best.replace(candidate) {
+                    rejected.push(RejectedQuote {
+                        venue: previous.quote.venue,
+                        reason: "worse oracle-relative price".into(),
+                    });
+                }
+            } else {
+                rejected.push(RejectedQuote {
+                    venue: candidate.quote.venue,
+                    reason: "worse oracle-relative price".into(),
+                });
+            }
+        }
+
+        (best.ok_or(VenueError::NoQuotes), rejected)
+    }
+
+    /// Execute a routed quote on the venue that issued it
+    pub async fn execute(
+        &self,
+        routed: &RoutedQuote,
+        rpc: &RpcClient,
+        signer: &Keypair,
+        now: i64,
+    ) -> VenueResult<Signature> {
+        let venue = self.venue(&routed.quote.venue).ok_or_else(|| VenueError::WrongVenue {
+            venue: routed.quote.venue.clone(),
+        })?;
+        venue.execute(&routed.quote, rpc, signer, now).await
+    }
+}
+
+// ============================================================================
+// JUPITER
+// ============================================================================
+
+pub struct JupiterVenue {
+    http: reqwest::Client,
+    api_url: String,
+    network_fee_lamports: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JupiterInstruction {
+    program_id: String,
+    accounts: Vec<JupiterAccount>,
+    data: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JupiterAccount {
+    pubkey: String,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JupiterSwapInstructions {
+    #[serde(default)]
+    compute_budget_instructions: Vec<JupiterInstruction>,
+    #[serde(default)]
+    setup_instructions: Vec<JupiterInstruction>,
+    swap_instruction: JupiterInstruction,
+    cleanup_instruction: Option<JupiterInstruction>,
+    #[serde(default)]
+    address_lookup_table_addresses: Vec<String>,
+}
+
+impl JupiterVenue {
+    pub fn new(api_url: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            network_fee_lamports: 10_000,
+        }
+    }
+
+    fn parse_instruction(&self, ix: JupiterInstruction) -> VenueResult<Instruction> {
+        let pubkey = |s: &str| s.parse::<Pubkey>().map_err(|_| invalid_response(self.name(), format!("bad pubkey {}", s)));
+        Ok(Instruction {
+            program_id: pubkey(&ix.program_id)?,
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|a| {
+                    Ok(AccountMeta {
+                        pubkey: pubkey(&a.pubkey)?,
+                        is_signer: a.is_signer,
+                        is_writable: a.is_writable,
+                    })
+                })
+                .collect::<VenueResult<_>>()?,
+            data: base64::engine::general_purpose::STANDARD
+                .decode(&ix.data)
+                .map_err(|e| invalid_response(self.name(), e.to_string()))?,
+        })
+    }
+}
+
+fn string_amount(value: &serde_json::Value, field: &str, venue: &str) -> VenueResult<u64> {
+    value[field]
+        .as_str()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| invalid_response(venue, format!("missing {}", field)))
+}
+
+#[async_trait]
+impl Venue for JupiterVenue {
+    fn name(&self) -> &str {
+        "jupiter"
+    }
+
+    fn fees(&self) -> VenueFees {
+        VenueFees {
+            fee_bps: 0,
+            network_fee_lamports: self.network_fee_lamports,
+        }
+    }
+
+    async fn quote(&self, request: &QuoteRequest) -> VenueResult<VenueQuote> {
+        let response = self
+            .http
+            .get(format!("{}/quote", self.api_url))
+            .query(&[
+                ("inputMint", request.input_mint.to_string()),
+                ("outputMint", request.output_mint.to_string()),
+                ("amount", request.amount_in.to_string()),
+                ("slippageBps", request.slippage_bps.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(|e| request_error(self.name(), e))?;
+        if !response.status().is_success() {
+            return Err(VenueError::NoRoute {
+                venue: self.name().to_string(),
+            });
+        }
+        let route: serde_json::Value = response.json().await.map_err(|e| request_error(self.name(), e))?;
+
+        Ok(VenueQuote {
+            venue: self.name().to_string(),
+            input_mint: request.input_mint,
+            output_mint: request.output_mint,
+            amount_in: request.amount_in,
+            amount_out: string_amount(&route, "outAmount", self.name())?,
+            min_amount_out: string_amount(&route, "otherAmountThreshold", self.name())?,
+            estimated: false,
+            expires_at: None,
+            route,
+        })
+    }
+
+    async fn build(&self, quote: &VenueQuote, user: &Pubkey) -> VenueResult<SwapPayload> {
+        let response = self
+            .http
+            .post(format!("{}/swap-instructions", self.api_url))
+            .json(&serde_json::json!({
+                "quoteResponse": quote.route,
+                "userPublicKey": user.to_string(),
+                "wrapAndUnwrapSol": true,
+                "dynamicComputeUnitLimit": true,
+            }))
+            .send()
+            .await
+            .map_err(|e| request_error(self.name(), e))?;
+        if !response.status().is_success() {
+            return Err(request_error(self.name(), response.status()));
+        }
+        let parts: JupiterSwapInstructions = response.json().await.map_err(|e| request_error(self.name(), e))?;
+
+        let mut instructions = Vec::new();
+        for ix in parts
+            .compute_budget_instructions
+            .into_iter()
+            .chain(parts.setup_instructions)
+            .chain(std::iter::once(parts.swap_instruction))
+            .chain(parts.cleanup_instruction)
+        {
+            instructions.push(self.parse_instruction(ix)?);
+        }
+        let lookup_tables = parts
+            .address_lookup_table_addresses
+            .iter()
+            .map(|s| s.parse().map_err(|_| invalid_response(self.name(), format!("bad lookup table {}", s))))
+            .collect::<VenueResult<_>>()?;
+
+        Ok(SwapPayload::Instructions {
+            instructions,
+            lookup_tables,
+        })
+    }
+}
+
+// ============================================================================
+// DIRECT POOLS
+// ============================================================================
+
+fn anchor_discriminator(name: &str) -> [u8; 8] {
+    let mut out = [0u8; 8];
+    out.copy_from_slice(&hash(format!("global:{}", name).as_bytes()).to_bytes()[..8]);
+    out
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Option<Pubkey> {
+    Some(Pubkey::new_from_array(data.get(offset..offset + 32)?.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(offset..offset + 8)?.try_into().ok()?))
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn read_u128(data: &[u8], offset: usize) -> Option<u128> {
+    Some(u128::from_le_bytes(data.get(offset..offset + 16)?.try_into().ok()?))
+}
+
+fn read_i32(data: &[u8], offset: usize) -> Option<i32> {
+    Some(i32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+/// SPL token account amount
+fn token_amount(data: &[u8]) -> Option<u64> {
+    read_u64(data, 64)
+}
+
+/// Raydium CPMM pool fields this venue uses
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct CpmmPool {
+    address: Pubkey,
+    amm_config: Pubkey,
+    vault_0: Pubkey,
+    vault_1: Pubkey,
+    mint_0: Pubkey,
+    mint_1: Pubkey,
+    program_0: Pubkey,
+    program_1: Pubkey,
+    observation: Pubkey,
+    /// Fees owed to the protocol and fund, held in the vaults but not
+    /// part of the reserves
+    fees_owed_0: u64,
+    fees_owed_1: u64,
+}
+
+impl CpmmPool {
+    fn decode(address: Pubkey, data: &[u8]) -> Option<Self> {
+        // PoolState: discriminator, then amm_config, pool_creator, token_0_vault,
+        // token_1_vault, lp_mint, token_0_mint, token_1_mint, token_0_program,
+        // token_1_program, observation_key, auth_bump, status,
+        // lp_mint_decimals, mint_0_decimals, mint_1_decimals, lp_supply,
+        // protocol_fees_token_0/1, fund_fees_token_0/1
+        let fees = 8 + 32 * 10 + 5 + 8;
+        Some(Self {
+            address,
+            amm_config: read_pubkey(data, 8)?,
+            vault_0: read_pubkey(data, 8 + 32 * 2)?,
+            vault_1: read_pubkey(data, 8 + 32 * 3)?,
+            mint_0: read_pubkey(data, 8 + 32 * 5)?,
+            mint_1: read_pubkey(data, 8 + 32 * 6)?,
+            program_0: read_pubkey(data, 8 + 32 * 7)?,
+            program_1: read_pubkey(data, 8 + 32 * 8)?,
+            observation: read_pubkey(data, 8 + 32 * 9)?,
+            fees_owed_0: read_u64(data, fees)?.checked_add(read_u64(data, fees + 16)?)?,
+            fees_owed_1: read_u64(data, fees + 8)?.checked_add(read_u64(data, fees + 24)?)?,
+        })
+    }
+}
+
+/// Direct swaps against a set of Raydium CPMM pools
+pub struct RaydiumCpmmVenue {
+    rpc: Arc<RpcClient>,
+    pools: Vec<Pubkey>,
+}
+
+impl RaydiumCpmmVenue {
+    pub fn new(rpc: Arc<RpcClient>, pools: Vec<Pubkey>) -> Self {
+        Self { rpc, pools }
+    }
+
+    /// Constant-product output after the trade fee
+    fn amount_out(amount_in: u64, reserve_in: u64, reserve_out: u64, trade_fee_rate: u64) -> u64 {
+        let fee = (amount_in as u128 * trade_fee_rate as u128).div_ceil(FEE_RATE_DENOMINATOR);
+        let in_after_fee = amount_in as u128 - fee;
+        (reserve_out as u128 * in_after_fee / (reserve_in as u128 + in_after_fee)) as u64
+    }
+}
+
+#[async_trait]
+impl Venue for RaydiumCpmmVenue {
+    fn name(&self) -> &str {
+        "raydium-cpmm"
+    }
+
+    fn fees(&self) -> VenueFees {
+        // Pool fee tiers vary; quotes are net of the pool's own rate
+        VenueFees {
+            fee_bps: 25,
+            network_fee_lamports: 5_000,
+        }
+    }
+
+    async fn quote(&self, request: &QuoteRequest) -> VenueResult<VenueQuote> {
+        let accounts = self.rpc.get_multiple_accounts(&self.pools).await?;
+        let mut best: Option<(u64, CpmmPool)> = None;
+
+        for (address, account) in self.pools.iter().zip(accounts) {
+            let Some(pool) = account.and_then(|a| CpmmPool::decode(*address, &a.data)) else {
+                continue;
+            };
+            let zero_for_one = (pool.mint_0, pool.mint_1) == (request.input_mint, request.output_mint);
+            let one_for_zero = (pool.mint_1, pool.mint_0) == (request.input_mint, request.output_mint);
+            if !zero_for_one && !one_for_zero {
+                continue;
+            }
+
+            let state = self
+                .rpc
+                .get_multiple_accounts(&[pool.vault_0, pool.vault_1, pool.amm_config])
+                .await?;
+            let data = |i: usize| state[i].as_ref().map(|a| a.data.as_slice());
+            let (Some(vault_0), Some(vault_1), Some(config)) = (
+                data(0).and_then(token_amount),
+                data(1).and_then(token_amount),
+                // AmmConfig: discriminator, bump, disable_create_pool, index, trade_fee_rate
+                data(2).and_then(|d| read_u64(d, 8 + 1 + 1 + 2)),
+            ) else {
+                continue;
+            };
+            let reserve_0 = vault_0.saturating_sub(pool.fees_owed_0);
+            let reserve_1 = vault_1.saturating_sub(pool.fees_owed_1);
+            let (reserve_in, reserve_out) = if zero_for_one {
+                (reserve_0, reserve_1)
+            } else {
+                (reserve_1, reserve_0)
+            };
+
+            let out = Self::amount_out(request.amount_in, reserve_in, reserve_out, config);
+            if best.as_ref().map_or(true, |(best_out, _)| out > *best_out) {
+                best = Some((out, pool));
+            }
+        }
+
+        let (amount_out, pool) = best.ok_or_else(|| VenueError::NoRoute {
+            venue: self.name().to_string(),
+        })?;
+        Ok(VenueQuote {
+            venue: self.name().to_string(),
+            input_mint: request.input_mint,
+            output_mint: request.output_mint,
+            amount_in: request.amount_in,
+            amount_out,
+            min_amount_out: min_out(amount_out, request.slippage_bps),
+            estimated: false,
+            expires_at: None,
+            route: serde_json::to_value(&pool).map_err(|e| invalid_response(self.name(), e.to_string()))?,
+        })
+    }
+
+    async fn build(&self, quote: &VenueQuote, user: &Pubkey) -> VenueResult<SwapPayload> {
+        let pool: CpmmPool =
+            serde_json::from_value(quote.route.clone()).map_err(|e| invalid_response(self.name(), e.to_string()))?;
+        let zero_for_one = pool.mint_0 == quote.input_mint;
+        let (input_vault, output_vault, input_program, output_program) = if zero_for_one {
+            (pool.vault_0, pool.vault_1, pool.program_0, pool.program_1)
+        } else {
+            (pool.vault_1, pool.vault_0, pool.program_1, pool.program_0)
+        };
+
+        let (authority, _) =
+            Pubkey::find_program_address(&[b"vault_and_lp_mint_auth_seed"], &RAYDIUM_CPMM_PROGRAM_ID);
+        let input_account = get_associated_token_address_with_program_id(user, &quote.input_mint, &input_program);
+        let output_account = get_associated_token_address_with_program_id(user, &quote.output_mint, &output_program);
+
+        let mut data = anchor_discriminator("swap_base_input").to_vec();
+        data.extend_from_slice(&quote.amount_in.to_le_bytes());
+        data.extend_from_slice(&quote.min_amount_out.to_le_bytes());
+
+        let swap = Instruction {
+            program_id: RAYDIUM_CPMM_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(*user, true),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(pool.amm_config, false),
+                AccountMeta::new(pool.address, false),
+                AccountMeta::new(input_account, false),
+                AccountMeta::new(output_account, false),
+                AccountMeta::new(input_vault, false),
+                AccountMeta::new(output_vault, false),
+                AccountMeta::new_readonly(input_program, false),
+                AccountMeta::new_readonly(output_program, false),
+                AccountMeta::new_readonly(quote.input_mint, false),
+                AccountMeta::new_readonly(quote.output_mint, false),
+                AccountMeta::new(pool.observation, false),
+            ],
+            data,
+        };
+
+        Ok(SwapPayload::Instructions {
+            instructions: vec![
+                create_associated_token_account_idempotent(user, user, &quote.output_mint, &output_program),
+                swap,
+            ],
+            lookup_tables: Vec::new(),
+        })
+    }
+}
+
+/// Orca Whirlpool fields this venue uses
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Whirlpool {
+    address: Pubkey,
+    tick_spacing: u16,
+    /// Millionths
+    fee_rate: u16,
+    liquidity: u128,
+    sqrt_price_x64: u128,
+    tick_current_index: i32,
+    mint_a: Pubkey,
+    vault_a: Pubkey,
+    mint_b: Pubkey,
+    vault_b: Pubkey,
+}
+
+impl Whirlpool {
+    fn decode(address: Pubkey, data: &[u8]) -> Option<Self> {
+        // Whirlpool: discriminator, whirlpools_config, bump, tick_spacing,
+        // tick_spacing_seed, fee_rate, protocol_fee_rate, liquidity,
+        // sqrt_price, tick_current_index, protocol_fee_owed_a/b,
+        // token_mint_a, token_vault_a, fee_growth_global_a, token_mint_b,
+        // token_vault_b
+        Some(Self {
+            address,
+            tick_spacing: read_u16(data, 41)?,
+            fee_rate: read_u16(data, 45)?,
+            liquidity: read_u128(data, 49)?,
+            sqrt_price_x64: read_u128(data, 65)?,
+            tick_current_index: read_i32(data, 81)?,
+            mint_a: read_pubkey(data, 101)?,
+            vault_a: read_pubkey(data, 133)?,
+            mint_b: read_pubkey(data, 181)?,
+            vault_b: read_pubkey(data, 213)?,
+        })
+    }
+
+    /// Output if the whole swap stays within the active liquidity. Crossing
+    /// an initialized tick changes the liquidity, so this is an estimate;
+    /// the on-chain `other_amount_threshold` enforces the minimum.
+    fn estimate_out(&self, amount_in: u64, a_to_b: bool) -> u64 {
+        if self.liquidity == 0 {
+            return 0;
+        }
+        let q64 = 2f64.powi(64);
+        let liquidity = self.liquidity as f64;
+        let sqrt_price = self.sqrt_price_x64 as f64 / q64;
+        let fee = (amount_in as u128 * self.fee_rate as u128).div_ceil(FEE_RATE_DENOMINATOR);
+        let in_after_fee = (amount_in as u128 - fee) as f64;
+
+        let out = if a_to_b {
+            // Δ(1/√P) = Δa / L; Δb = L Δ√P
+            let next = liquidity * sqrt_price / (liquidity + in_after_fee * sqrt_price);
+            liquidity * (sqrt_price - next)
+        } else {
+            // Δ√P = Δb / L; Δa = L Δ(1/√P)
+            let next = sqrt_price + in_after_fee / liquidity;
+            liquidity * (1.0 / sqrt_price - 1.0 / next)
+        };
+        out.max(0.0).floor() as u64
+    }
+
+    fn tick_arrays(&self, a_to_b: bool) -> [Pubkey; 3] {
+        let span = self.tick_spacing as i32 * TICK_ARRAY_SIZE;
+        let start = self.tick_current_index.div_euclid(span) * span;
+        let step = if a_to_b { -span } else { span };
+        [0, 1, 2].map(|i| {
+            let start_index = (start + step * i).to_string();
+            Pubkey::find_program_address(
+                &[b"tick_array", self.address.as_ref(), start_index.as_bytes()],
+                &ORCA_WHIRLPOOL_PROGRAM_ID,
+            )
+            .0
+        })
+    }
+}
+
+/// Direct swaps against a set of Orca Whirlpools (SPL Token mints only)
+pub struct OrcaWhirlpoolVenue {
+    rpc: Arc<RpcClient>,
+    pools: Vec<Pubkey>,
+}
+
+impl OrcaWhirlpoolVenue {
+    pub fn new(rpc: Arc<RpcClient>, pools: Vec<Pubkey>) -> Self {
+        Self { rpc, pools }
+    }
+}
+
+#[async_trait]
+impl Venue for OrcaWhirlpoolVenue {
+    fn name(&self) -> &str {
+        "orca-whirlpool"
+    }
+
+    fn fees(&self) -> VenueFees {
+        VenueFees {
+            fee_bps: 30,
+            network_fee_lamports: 5_000,
+        }
+    }
+
+    async fn quote(&self, request: &QuoteRequest) -> VenueResult<VenueQuote> {
+        let accounts = self.rpc.get_multiple_accounts(&self.pools).await?;
+        let best = self
+            .pools
+            .iter()
+            .zip(accounts)
+            .filter_map(|(address, account)| Whirlpool::decode(*address, &account?.data))
+            .filter_map(|pool| {
+                let a_to_b = (pool.mint_a, pool.mint_b) == (request.input_mint, request.output_mint);
+                let b_to_a = (pool.mint_b, pool.mint_a) == (request.input_mint, request.output_mint);
+                (a_to_b || b_to_a).then(|| (pool.estimate_out(request.amount_in, a_to_b), pool))
+            })
+            .max_by_key(|(out, _)| *out);
+
+        let (amount_out, pool) = best.ok_or_else(|| VenueError::NoRoute {
+            venue: self.name().to_string(),
+        })?;
+        Ok(VenueQuote {
+            venue: self.name().to_string(),
+            input_mint: request.input_mint,
+            output_mint: request.output_mint,
+            amount_in: request.amount_in,
+            amount_out,
+            min_amount_out: min_out(amount_out, request.slippage_bps),
+            estimated: true,
+            expires_at: None,
+            route: serde_json::to_value(&pool).map_err(|e| invalid_response(self.name(), e.to_string()))?,
+        })
+    }
+
+    async fn build(&self, quote: &VenueQuote, user: &Pubkey) -> VenueResult<SwapPayload> {
+        let pool: Whirlpool =
+            serde_json::from_value(quote.route.clone()).map_err(|e| invalid_response(self.name(), e.to_string()))?;
+        let a_to_b = pool.mint_a == quote.input_mint;
+        let token_program = spl_token::ID;
+        let owner_a = get_associated_token_address_with_program_id(user, &pool.mint_a, &token_program);
+        let owner_b = get_associated_token_address_with_program_id(user, &pool.mint_b, &token_program);
+        let [tick_array_0, tick_array_1, tick_array_2] = pool.tick_arrays(a_to_b);
+        let (oracle, _) =
+            Pubkey::find_program_address(&[b"oracle", pool.address.as_ref()], &ORCA_WHIRLPOOL_PROGRAM_ID);
+
+        let mut data = anchor_discriminator("swap").to_vec();
+        data.extend_from_slice(&quote.amount_in.to_le_bytes());
+        data.extend_from_slice(&quote.min_amount_out.to_le_bytes()); // other_amount_threshold
+        let sqrt_price_limit = if a_to_b { MIN_SQRT_PRICE_X64 } else { MAX_SQRT_PRICE_X64 };
+        data.extend_from_slice(&sqrt_price_limit.to_le_bytes());
+        data.push(1); // amount_specified_is_input
+        data.push(a_to_b as u8);
+
+        let swap = Instruction {
+            program_id: ORCA_WHIRLPOOL_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new_readonly(*user, true),
+                AccountMeta::new(pool.address, false),
+                AccountMeta::new(owner_a, false),
+                AccountMeta::new(pool.vault_a, false),
+                AccountMeta::new(owner_b, false),
+                AccountMeta::new(pool.vault_b, false),
+                AccountMeta::new(tick_array_0, false),
+                AccountMeta::new(tick_array_1, false),
+                AccountMeta::new(tick_array_2, false),
+                AccountMeta::new_readonly(oracle, false),
+            ],
+            data,
+        };
+
+        Ok(SwapPayload::Instructions {
+            instructions: vec![
+                create_associated_token_account_idempotent(user, user, &quote.output_mint, &token_program),
+                swap,
+            ],
+            lookup_tables: Vec::new(),
+        })
+    }
+}
+
+// ============================================================================
+// RFQ
+// ============================================================================
+
+/// A market maker's RFQ endpoint. `POST {url}/quote` with the request
+/// returns the maker's firm price and a transaction the maker has signed;
+/// the agent countersigns it to accept.
+pub struct RfqVenue {
+    http: reqwest::Client,
+    name: String,
+    url: String,
+    api_key: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RfqQuoteBody {
+    input_mint: String,
+    output_mint: String,
+    amount_in: String,
+    taker: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RfqQuoteResponse {
+    amount_out: String,
+    expires_at: i64,
+    /// Base64 serialized VersionedTransaction, maker-signed
+    transaction: String,
+}
+
+impl RfqVenue {
+    pub fn new(name: &str, url: &str, api_key: Option<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            name: name.to_string(),
+            url: url.trim_end_matches('/').to_string(),
+            api_key,
+        }
+    }
+}
+
+#[async_trait]
+impl Venue for RfqVenue {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn fees(&self) -> VenueFees {
+        // Makers price their spread into the quote
+        VenueFees {
+            fee_bps: 0,
+            network_fee_lamports: 5_000,
+        }
+    }
+
+    async fn quote(&self, request: &QuoteRequest) -> VenueResult<VenueQuote> {
+        let mut http = self.http.post(format!("{}/quote", self.url)).json(&RfqQuoteBody {
+            input_mint: request.input_mint.to_string(),
+            output_mint: request.output_mint.to_string(),
+            amount_in: request.amount_in.to_string(),
+            taker: request.user.to_string(),
+        });
+        if let Some(key) = &self.api_key {
+            http = http.header("Authorization", key);
+        }
+        let response = http.send().await.map_err(|e| request_error(self.name(), e))?;
+        if !response.status().is_success() {
+            return Err(VenueError::NoRoute {
+                venue: self.name().to_string(),
+            });
+        }
+        let quote: RfqQuoteResponse = response.json().await.map_err(|e| request_error(self.name(), e))?;
+        let amount_out: u64 = quote
+            .amount_out
+            .parse()
+            .map_err(|_| invalid_response(self.name(), "bad amountOut"))?;
+
+        Ok(VenueQuote {
+            venue: self.name().to_string(),
+            input_mint: request.input_mint,
+            output_mint: request.output_mint,
+            amount_in: request.amount_in,
+            amount_out,
+            // Firm quote: the maker's transaction delivers exactly this
+            min_amount_out: amount_out,
+            estimated: false,
+            expires_at: Some(quote.expires_at),
+            route: serde_json::json!({ "transaction": quote.transaction }),
+        })
+    }
+
+    async fn build(&self, quote: &VenueQuote, _user: &Pubkey) -> VenueResult<SwapPayload> {
+        let encoded = quote.route["transaction"]
+            .as_str()
+            .ok_or_else(|| invalid_response(self.name(), "quote has no transaction"))?;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| invalid_response(self.name(), e.to_string()))?;
+        let tx: VersionedTransaction =
+            bincode::deserialize(&bytes).map_err(|e| invalid_response(self.name(), e.to_string()))?;
+        Ok(SwapPayload::Signed(tx))
+    }
+}
@@ -0,0 +1,518 @@
+/**
+ * Post-Hoc Fill Reconstruction from Confirmed Transactions
+ *
+ * Given a confirmed transaction signature, extracts what actually
+ * happened: token balance deltas per account and owner, SOL deltas, every
+ * program invoked (top level and CPI), and the Anchor events emitted via
+ * `emit!` (log lines) or `emit_cpi!` (self-CPI instructions). From the
+ * owner's deltas it reconstructs the fill of a swap and compares the
+ * realized price against the oracle prices the agent decided on.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    anchor-lang = "0.30.1"
+ *    base64 = "0.21"
+ *    bs58 = "0.5"
+ *    pyth-oracle = { path = "../pyth-oracle", features = ["no-entrypoint"] }
+ *    solana-client = "1.18"
+ *    solana-sdk = "1.18"
+ *    solana-transaction-status = "1.18"
+ *    thiserror = "1"
+ *
+ * 2. Copy this file as `fill_reconstruction.rs` and declare `mod fill_reconstruction;`
+ */
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::Engine;
+use pyth_oracle::{OracleDecimal, OraclePrice, Rounding};
+use solana_client::client_error::ClientError;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, UiInstruction, UiTransactionEncoding,
+};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Prefix Anchor puts on `emit_cpi!` instruction data (EVENT_IX_TAG, LE)
+pub const EVENT_IX_TAG_LE: [u8; 8] = [0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d];
+
+/// Wrapped SOL mint, used for native SOL legs of a fill
+pub const NATIVE_MINT: Pubkey = solana_sdk::pubkey!("So11111111111111111111111111111111111111112");
+
+/// Decimal places of reconstructed and oracle prices
+pub const PRICE_DECIMALS: i32 = 12;
+
+const BPS_DENOMINATOR: i128 = 10_000;
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum FillError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] ClientError),
+
+    #[error("transaction has no status metadata")]
+    MissingMeta,
+
+    #[error("transaction could not be decoded; request base64 encoding")]
+    UndecodableTransaction,
+
+    #[error("account index {0} is out of range")]
+    AccountIndex(usize),
+
+    #[error("invalid {field}: {value}")]
+    Invalid { field: &'static str, value: String },
+
+    #[error("owner {0} has no outgoing and incoming leg to form a fill")]
+    NoFill(Pubkey),
+
+    #[error("price math overflowed")]
+    Math,
+}
+
+// ============================================================================
+// PARSED TRANSACTION
+// ============================================================================
+
+/// Change in one token account's balance
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenDelta {
+    pub account: Pubkey,
+    pub owner: Option<Pubkey>,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    /// Raw units; negative when tokens left the account
+    pub delta: i128,
+}
+
+/// One program invocation, in execution order
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgramInvocation {
+    pub program_id: Pubkey,
+    /// Top-level instruction this invocation belongs to
+    pub instruction_index: usize,
+    /// 1 for top-level instructions, 2+ for CPIs
+    pub stack_height: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventSource {
+    /// `emit!`: base64 "Program data:" log line
+    Log,
+    /// `emit_cpi!`: self-CPI instruction data, not subject to log truncation
+    Cpi,
+}
+
+/// A raw Anchor event; decode with `decode::<MyEvent>()`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnchorEvent {
+    pub program_id: Pubkey,
+    pub discriminator: [u8; 8],
+    /// Borsh-encoded event body, without the discriminator
+    pub data: Vec<u8>,
+    pub source: EventSource,
+}
+
+impl AnchorEvent {
+    fn from_bytes(program_id: Pubkey, bytes: &[u8], source: EventSource) -> Option<Self> {
+        let discriminator = bytes.get(..8)?.try_into().ok()?;
+        Some(Self {
+            program_id,
+            discriminator,
+            data: bytes[8..].to_vec(),
+            source,
+        })
+    }
+
+    pub fn is<T: Discriminator>(&self) -> bool {
+        T::DISCRIMINATOR[..] == self.discriminator[..]
+    }
+
+    pub fn decode<T: AnchorDeserialize + Discriminator>(&self) -> Option<T> {
+        if !self.is::<T>() {
+            return None;
+        }
+        T::try_from_slice(&self.data).ok()
+    }
+}
+
+/// Everything the fill reconstruction needs from one transaction
+#[derive(Clone, Debug)]
+pub struct ParsedTransaction {
+    pub signature: Signature,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub succeeded: bool,
+    pub fee_payer: Pubkey,
+    pub fee_lamports: u64,
+    /// Lamport change per account, fee included for the fee payer
+    pub sol_deltas: BTreeMap<Pubkey, i64>,
+    pub token_deltas: Vec<TokenDelta>,
+    pub invocations: Vec<ProgramInvocation>,
+    pub events: Vec<AnchorEvent>,
+    pub logs: Vec<String>,
+}
+
+impl ParsedTransaction {
+    /// Net token change per mint for accounts owned by `owner`
+    pub fn owner_deltas(&self, owner: &Pubkey) -> BTreeMap<Pubkey, (i128, u8)> {
+        let mut deltas: BTreeMap<Pubkey, (i128, u8)> = BTreeMap::new();
+        for delta in self.token_deltas.iter().filter(|d| d.owner.as_ref() == Some(owner)) {
+            let entry = deltas.entry(delta.mint).or_insert((0, delta.decimals));
+            entry.0 += delta.delta;
+        }
+        deltas.retain(|_, (delta, _)| *delta != 0);
+        deltas
+    }
+
+    /// Distinct programs invoked, in first-seen order
+    pub fn programs(&self) -> Vec<Pubkey> {
+        let mut programs = Vec::new();
+        for invocation in &self.invocations {
+            if !programs.contains(&invocation.program_id) {
+                programs.push(invocation.program_id);
+            }
+        }
+        programs
+    }
+
+    /// Events of type `T` emitted by `program_id`
+    pub fn events_of<T: AnchorDeserialize + Discriminator>(&self, program_id: &Pubkey) -> Vec<T> {
+        self.events
+            .iter()
+            .filter(|event| &event.program_id == program_id)
+            .filter_map(AnchorEvent::decode::<T>)
+            .collect()
+    }
+
+    /// The swap `owner` executed: its largest outgoing and incoming legs
+    /// (intermediate hops of a route net to zero). A side with no token delta falls back to the owner's
+    /// SOL delta (fee excluded), for swaps that wrap and unwrap SOL inside
+    /// the transaction; account rent paid in the same transaction is then
+    /// counted as part of the fill.
+    pub fn fill(&self, owner: &Pubkey) -> Result<Fill, FillError> {
+        let mut deltas = self.owner_deltas(owner);
+        let has_input = deltas.values().any(|(delta, _)| *delta < 0);
+        let has_output = deltas.values().any(|(delta, _)| *delta > 0);
+        if !(has_input && has_output) && !deltas.contains_key(&NATIVE_MINT) {
+            let mut lamports = self.sol_deltas.get(owner).copied().unwrap_or(0) as i128;
+            if owner == &self.fee_payer {
+                lamports += self.fee_lamports as i128;
+            }
+            if lamports != 0 {
+                deltas.insert(NATIVE_MINT, (lamports, 9));
+            }
+        }
+
+        let input = deltas
+            .iter()
+            .filter(|(_, (delta, _))| *delta < 0)
+            .min_by_key(|(_, (delta, _))| *delta);
+        let output = deltas
+            .iter()
+            .filter(|(_, (delta, _))| *delta > 0)
+            .max_by_key(|(_, (delta, _))| *delta);
+        let (Some((input_mint, (input, input_decimals))), Some((output_mint, (output, output_decimals)))) =
+            (input, output)
+        else {
+            return Err(FillError::NoFill(*owner));
+        };
+
+        Ok(Fill {
+            input_mint: *input_mint,
+            input_amount: u64::try_from(-input).map_err(|_| FillError::Math)?,
+            input_decimals: *input_decimals,
+            output_mint: *output_mint,
+            output_amount: u64::try_from(*output).map_err(|_| FillError::Math)?,
+            output_decimals: *output_decimals,
+        })
+    }
+}
+
+// ============================================================================
+// FILLS
+// ============================================================================
+
+/// A realized swap, in raw token units
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fill {
+    pub input_mint: Pubkey,
+    pub input_amount: u64,
+    pub input_decimals: u8,
+    pub output_mint: Pubkey,
+    pub output_amount: u64,
+    pub output_decimals: u8,
+}
+
+/// Realized price against the oracle price at decision time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FillComparison {
+    /// Output tokens received per input token
+    pub fill_price: OracleDecimal,
+    /// Output tokens per input token implied by the oracle prices
+    pub oracle_price: OracleDecimal,
+    /// Positive when the fill was worse than the oracle
+    pub slippage_bps: i64,
+}
+
+impl Fill {
+    /// Output tokens received per input token, `PRICE_DECIMALS` places
+    pub fn price(&self) -> Result<OracleDecimal, FillError> {
+        OracleDecimal::from_u64(self.output_amount, self.output_decimals)
+            .checked_div(
+                OracleDecimal::from_u64(self.input_amount, self.input_decimals),
+                PRICE_DECIMALS,
+                Rounding::Floor,
+            )
+            .map_err(|_| FillError::Math)
+    }
+
+    /// Compare against the USD prices of both legs used when deciding
+    pub fn compare(
+        &self,
+        input_price_usd: &OraclePrice,
+        output_price_usd: &OraclePrice,
+    ) -> Result<FillComparison, FillError> {
+        let fill_price = self.price()?;
+        let oracle_price = OracleDecimal::from_oracle_price(input_price_usd)
+            .and_then(|input| {
+                input.checked_div(
+                    OracleDecimal::from_oracle_price(output_price_usd)?,
+                    PRICE_DECIMALS,
+                    Rounding::Floor,
+                )
+            })
+            .map_err(|_| FillError::Math)?;
+        if oracle_price.is_zero() {
+            return Err(FillError::Math);
+        }
+
+        // Both at PRICE_DECIMALS, so mantissas compare directly
+        let oracle = i128::try_from(oracle_price.mantissa).map_err(|_| FillError::Math)?;
+        let fill = i128::try_from(fill_price.mantissa).map_err(|_| FillError::Math)?;
+        let slippage_bps = (oracle - fill)
+            .checked_mul(BPS_DENOMINATOR)
+            .map(|scaled| scaled / oracle)
+            .and_then(|bps| i64::try_from(bps).ok())
+            .ok_or(FillError::Math)?;
+
+        Ok(FillComparison {
+            fill_price,
+            oracle_price,
+            slippage_bps,
+        })
+    }
+}
+
+// ============================================================================
+// PARSING
+// ============================================================================
+
+fn parse_pubkey(field: &'static str, value: &str) -> Result<Pubkey, FillError> {
+    Pubkey::from_str(value).map_err(|_| FillError::Invalid {
+        field,
+        value: value.to_string(),
+    })
+}
+
+fn option<T>(value: &OptionSerializer<T>) -> Option<&T> {
+    match value {
+        OptionSerializer::Some(value) => Some(value),
+        _ => None,
+    }
+}
+
+/// Attribute "Program data:" lines to the program executing at the time
+fn log_events(logs: &[String]) -> Vec<AnchorEvent> {
+    let mut stack: Vec<Pubkey> = Vec::new();
+    let mut events = Vec::new();
+    for line in logs {
+        if let Some(data) = line.strip_prefix(PROGRAM_DATA_PREFIX) {
+            let bytes = base64::engine::general_purpose::STANDARD.decode(data).ok();
+            if let Some(event) = stack
+                .last()
+                .zip(bytes)
+                .and_then(|(program, bytes)| AnchorEvent::from_bytes(*program, &bytes, EventSource::Log))
+            {
+                events.push(event);
+            }
+            continue;
+        }
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        let mut words = rest.split_whitespace();
+        let (Some(program), Some(action)) = (words.next(), words.next()) else {
+            continue;
+        };
+        match action {
+            "invoke" => {
+                if let Ok(program) = Pubkey::from_str(program) {
+                    stack.push(program);
+                }
+            }
+            "success" | "failed:" => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+/// Decode a transaction fetched with base64 encoding and full metadata
+pub fn parse_transaction(
+    signature: Signature,
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+) -> Result<ParsedTransaction, FillError> {
+    let meta = tx.transaction.meta.as_ref().ok_or(FillError::MissingMeta)?;
+    let versioned = tx
+        .transaction
+        .transaction
+        .decode()
+        .ok_or(FillError::UndecodableTransaction)?;
+    let message = &versioned.message;
+
+    // Static keys, then loaded writable, then loaded readonly
+    let mut keys: Vec<Pubkey> = message.static_account_keys().to_vec();
+    if let Some(loaded) = option(&meta.loaded_addresses) {
+        for key in loaded.writable.iter().chain(&loaded.readonly) {
+            keys.push(parse_pubkey("loaded address", key)?);
+        }
+    }
+    let key = |index: usize| keys.get(index).copied().ok_or(FillError::AccountIndex(index));
+
+    let mut sol_deltas = BTreeMap::new();
+    for (index, (pre, post)) in meta.pre_balances.iter().zip(&meta.post_balances).enumerate() {
+        let delta = *post as i64 - *pre as i64;
+        if delta != 0 {
+            sol_deltas.insert(key(index)?, delta);
+        }
+    }
+
+    // Keyed by account index; accounts created or closed in the transaction
+    // appear on one side only
+    let mut balances: BTreeMap<u8, TokenDelta> = BTreeMap::new();
+    let empty = Vec::new();
+    let pre = option(&meta.pre_token_balances).unwrap_or(&empty);
+    let post = option(&meta.post_token_balances).unwrap_or(&empty);
+    for (balances_side, sign) in [(pre, -1i128), (post, 1i128)] {
+        for balance in balances_side {
+            let amount: i128 = balance
+                .ui_token_amount
+                .amount
+                .parse()
+                .map_err(|_| FillError::Invalid {
+                    field: "token amount",
+                    value: balance.ui_token_amount.amount.clone(),
+                })?;
+            let owner = option(&balance.owner)
+                .map(|owner| parse_pubkey("token owner", owner))
+                .transpose()?;
+            let entry = match balances.entry(balance.account_index) {
+                std::collections::btree_map::Entry::Occupied(entry) => entry.into_mut(),
+                std::collections::btree_map::Entry::Vacant(entry) => entry.insert(TokenDelta {
+                    account: key(balance.account_index as usize)?,
+                    owner,
+                    mint: parse_pubkey("mint", &balance.mint)?,
+                    decimals: balance.ui_token_amount.decimals,
+                    delta: 0,
+                }),
+            };
+            entry.delta += sign * amount;
+        }
+    }
+    let token_deltas = balances.into_values().filter(|d| d.delta != 0).collect();
+
+    // Top-level instructions, each followed by its CPIs
+    let inner = option(&meta.inner_instructions);
+    let mut invocations = Vec::new();
+    let mut events = Vec::new();
+    for (index, instruction) in message.instructions().iter().enumerate() {
+        let program_id = key(instruction.program_id_index as usize)?;
+        invocations.push(ProgramInvocation {
+            program_id,
+            instruction_index: index,
+            stack_height: 1,
+        });
+        // Program executing at each stack height, to find each CPI's caller
+        let mut callers = vec![program_id];
+
+        let cpis = inner
+            .into_iter()
+            .flatten()
+            .filter(|group| group.index as usize == index)
+            .flat_map(|group| &group.instructions);
+        for cpi in cpis {
+            let UiInstruction::Compiled(compiled) = cpi else {
+                continue;
+            };
+            let program_id = key(compiled.program_id_index as usize)?;
+            let stack_height = compiled.stack_height.unwrap_or(2);
+            invocations.push(ProgramInvocation {
+                program_id,
+                instruction_index: index,
+                stack_height,
+            });
+            callers.truncate(stack_height.saturating_sub(1) as usize);
+            let caller = callers.last().copied();
+            callers.push(program_id);
+
+            // emit_cpi!: the program invokes itself with the event tag
+            if caller != Some(program_id) {
+                continue;
+            }
+            let data = bs58::decode(&compiled.data).into_vec().unwrap_or_default();
+            if let Some(body) = data.strip_prefix(&EVENT_IX_TAG_LE[..]) {
+                if let Some(event) = AnchorEvent::from_bytes(program_id, body, EventSource::Cpi) {
+                    events.push(event);
+                }
+            }
+        }
+    }
+
+    let logs = option(&meta.log_messages).cloned().unwrap_or_default();
+    events.extend(log_events(&logs));
+
+    Ok(ParsedTransaction {
+        signature,
+        slot: tx.slot,
+        block_time: tx.block_time,
+        succeeded: meta.err.is_none(),
+        fee_payer: key(0)?,
+        fee_lamports: meta.fee,
+        sol_deltas,
+        token_deltas,
+        invocations,
+        events,
+        logs,
+    })
+}
+
+/// Fetch a confirmed transaction and parse it
+pub async fn fetch_parsed_transaction(
+    rpc: &RpcClient,
+    signature: &Signature,
+    commitment: CommitmentConfig,
+) -> Result<ParsedTransaction, FillError> {
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: Some(commitment),
+        max_supported_transaction_version: Some(0),
+    };
+    let tx = rpc.get_transaction_with_config(signature, config).await?;
+    parse_transaction(*signature, &tx)
+}
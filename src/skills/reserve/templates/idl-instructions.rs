@@ -0,0 +1,752 @@
+/**
+ * Anchor IDL-Driven Instruction Builder
+ *
+ * Loads an Anchor IDL at runtime (from a JSON file or the program's
+ * on-chain IDL account) and builds instructions by name: accounts are
+ * passed by their IDL names and arguments as JSON values, which are
+ * checked against the IDL types and Borsh-encoded. Skills can integrate
+ * long-tail protocols without hand-writing instruction layouts.
+ *
+ * Both the current IDL format (Anchor 0.30+, explicit discriminators,
+ * `writable`/`signer`) and the legacy format (`isMut`/`isSigner`,
+ * discriminator derived from the instruction name) are accepted.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    flate2 = "1"
+ *    serde = { version = "1", features = ["derive"] }
+ *    serde_json = "1"
+ *    sha2 = "0.10"
+ *    solana-client = "1.18"
+ *    solana-sdk = "1.18"
+ *    thiserror = "1"
+ *
+ * 2. Copy this file as `idl_instructions.rs` and declare `mod idl_instructions;`
+ */
+
+use flate2::read::ZlibDecoder;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use solana_client::client_error::ClientError;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::io::Read;
+use std::str::FromStr;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum IdlError {
+    #[error("could not read IDL file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("invalid IDL JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("invalid program address in IDL: {0}")]
+    InvalidAddress(String),
+
+    #[error("unsupported IDL type at {path}: {ty}")]
+    UnsupportedType { path: String, ty: String },
+
+    #[error("type {0} is not defined in the IDL")]
+    UnknownType(String),
+
+    #[error("instruction {0} is not in the IDL")]
+    UnknownInstruction(String),
+
+    #[error("IDL has no program address; pass one to the builder")]
+    NoProgramId,
+
+    #[error("account {0} was not provided")]
+    MissingAccount(String),
+
+    #[error("account {0} is not part of the instruction")]
+    UnexpectedAccount(String),
+
+    #[error("argument {0} was not provided")]
+    MissingArg(String),
+
+    #[error("argument {0} is not part of the instruction")]
+    UnexpectedArg(String),
+
+    #[error("invalid value for {path}: expected {expected}")]
+    InvalidArg { path: String, expected: String },
+
+    #[error("RPC error: {0}")]
+    Rpc(#[from] ClientError),
+
+    #[error("program {0} has no on-chain IDL account")]
+    IdlAccountMissing(Pubkey),
+
+    #[error("on-chain IDL account is malformed: {0}")]
+    IdlAccountMalformed(String),
+}
+
+// ============================================================================
+// IDL MODEL
+// ============================================================================
+
+/// An argument or field type
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IdlType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    F32,
+    F64,
+    String,
+    Bytes,
+    Pubkey,
+    Option(Box<IdlType>),
+    Vec(Box<IdlType>),
+    Array(Box<IdlType>, usize),
+    Defined(String),
+}
+
+#[derive(Clone, Debug)]
+pub enum IdlFields {
+    Named(Vec<(String, IdlType)>),
+    Tuple(Vec<IdlType>),
+    Unit,
+}
+
+#[derive(Clone, Debug)]
+pub struct IdlVariant {
+    pub name: String,
+    pub fields: IdlFields,
+}
+
+/// A user-defined type from the IDL `types` section
+#[derive(Clone, Debug)]
+pub enum IdlTypeDef {
+    Struct(IdlFields),
+    Enum(Vec<IdlVariant>),
+}
+
+#[derive(Clone, Debug)]
+pub struct IdlAccountItem {
+    pub name: String,
+    pub writable: bool,
+    pub signer: bool,
+    pub optional: bool,
+    /// Fixed address declared in the IDL (programs, sysvars)
+    pub address: Option<Pubkey>,
+}
+
+#[derive(Clone, Debug)]
+pub struct IdlInstruction {
+    pub name: String,
+    pub discriminator: Vec<u8>,
+    /// Flattened in IDL order, nested account groups included
+    pub accounts: Vec<IdlAccountItem>,
+    pub args: Vec<(String, IdlType)>,
+}
+
+/// A parsed Anchor IDL
+#[derive(Clone, Debug)]
+pub struct Idl {
+    pub address: Option<Pubkey>,
+    pub instructions: Vec<IdlInstruction>,
+    pub types: HashMap<String, IdlTypeDef>,
+}
+
+// ============================================================================
+// PARSING
+// ============================================================================
+
+#[derive(Deserialize)]
+struct RawMetadata {
+    address: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawField {
+    name: String,
+    #[serde(rename = "type")]
+    ty: Value,
+}
+
+#[derive(Deserialize)]
+struct RawInstruction {
+    name: String,
+    discriminator: Option<Vec<u8>>,
+    #[serde(default)]
+    accounts: Vec<Value>,
+    #[serde(default)]
+    args: Vec<RawField>,
+}
+
+#[derive(Deserialize)]
+struct RawIdl {
+    address: Option<String>,
+    metadata: Option<RawMetadata>,
+    instructions: Vec<RawInstruction>,
+    #[serde(default)]
+    types: Vec<RawField>,
+}
+
+fn unsupported(path: &str, ty: &Value) -> IdlError {
+    IdlError::UnsupportedType {
+        path: path.to_string(),
+        ty: ty.to_string(),
+    }
+}
+
+fn parse_type(ty: &Value, path: &str) -> Result<IdlType, IdlError> {
+    if let Some(name) = ty.as_str() {
+        return Ok(match name {
+            "bool" => IdlType::Bool,
+            "u8" => IdlType::U8,
+            "u16" => IdlType::U16,
+            "u32" => IdlType::U32,
+            "u64" => IdlType::U64,
+            "u128" => IdlType::U128,
+            "i8" => IdlType::I8,
+            "i16" => IdlType::I16,
+            "i32" => IdlType::I32,
+            "i64" => IdlType::I64,
+            "i128" => IdlType::I128,
+            "f32" => IdlType::F32,
+            "f64" => IdlType::F64,
+            "string" => IdlType::String,
+            "bytes" => IdlType::Bytes,
+            "pubkey" | "publicKey" => IdlType::Pubkey,
+            _ => return Err(unsupported(path, ty)),
+        });
+    }
+
+    let object = ty.as_object().ok_or_else(|| unsupported(path, ty))?;
+    if let Some(inner) = object.get("option") {
+        return Ok(IdlType::Option(Box::new(parse_type(inner, path)?)));
+    }
+    if let Some(inner) = object.get("vec") {
+        return Ok(IdlType::Vec(Box::new(parse_type(inner, path)?)));
+    }
+    if let Some(array) = object.get("array").and_then(Value::as_array) {
+        let len = array
+            .get(1)
+            .and_then(Value::as_u64)
+            .ok_or_else(|| unsupported(path, ty))?;
+        let inner = array.first().ok_or_else(|| unsupported(path, ty))?;
+        return Ok(IdlType::Array(Box::new(parse_type(inner, path)?), len as usize));
+    }
+    // 0.30: {"defined": {"name": "X"}}; legacy: {"defined": "X"}
+    match object.get("defined") {
+        Some(Value::String(name)) => Ok(IdlType::Defined(name.clone())),
+        Some(Value::Object(defined)) => defined
+            .get("name")
+            .and_then(Value::as_str)
+            .map(|name| IdlType::Defined(name.to_string()))
+            .ok_or_else(|| unsupported(path, ty)),
+        _ => Err(unsupported(path, ty)),
+    }
+}
+
+fn parse_fields(fields: Option<&Value>, path: &str) -> Result<IdlFields, IdlError> {
+    let Some(fields) = fields.and_then(Value::as_array).filter(|f| !f.is_empty()) else {
+        return Ok(IdlFields::Unit);
+    };
+    let named = fields
+        .iter()
+        .all(|field| field.get("name").is_some() && field.get("type").is_some());
+    if named {
+        fields
+            .iter()
+            .map(|field| {
+                let raw: RawField = serde_json::from_value(field.clone())?;
+                let ty = parse_type(&raw.ty, &format!("{}.{}", path, raw.name))?;
+                Ok((raw.name, ty))
+            })
+            .collect::<Result<_, IdlError>>()
+            .map(IdlFields::Named)
+    } else {
+        fields
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| parse_type(ty, &format!("{}.{}", path, i)))
+            .collect::<Result<_, _>>()
+            .map(IdlFields::Tuple)
+    }
+}
+
+fn parse_type_def(def: &RawField) -> Result<IdlTypeDef, IdlError> {
+    let kind = def.ty.get("kind").and_then(Value::as_str);
+    match kind {
+        Some("struct") => Ok(IdlTypeDef::Struct(parse_fields(def.ty.get("fields"), &def.name)?)),
+        Some("enum") => {
+            let variants = def
+                .ty
+                .get("variants")
+                .and_then(Value::as_array)
+                .ok_or_else(|| unsupported(&def.name, &def.ty))?;
+            variants
+                .iter()
+                .map(|variant| {
+                    let name = variant
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .ok_or_else(|| unsupported(&def.name, variant))?;
+                    Ok(IdlVariant {
+                        name: name.to_string(),
+                        fields: parse_fields(variant.get("fields"), &format!("{}::{}", def.name, name))?,
+                    })
+                })
+                .collect::<Result<_, IdlError>>()
+                .map(IdlTypeDef::Enum)
+        }
+        _ => Err(unsupported(&def.name, &def.ty)),
+    }
+}
+
+fn flag(item: &Value, current: &str, legacy: &str) -> bool {
+    item.get(current)
+        .or_else(|| item.get(legacy))
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+fn flatten_accounts(items: &[Value], out: &mut Vec<IdlAccountItem>) -> Result<(), IdlError> {
+    for item in items {
+        // Composite account groups list their members under "accounts"
+        if let Some(nested) = item.get("accounts").and_then(Value::as_array) {
+            flatten_accounts(nested, out)?;
+            continue;
+        }
+        let name = item
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| unsupported("accounts", item))?;
+        let address = match item.get("address").and_then(Value::as_str) {
+            Some(address) => Some(Pubkey::from_str(address).map_err(|_| unsupported(name, item))?),
+            None => None,
+        };
+        out.push(IdlAccountItem {
+            name: name.to_string(),
+            writable: flag(item, "writable", "isMut"),
+            signer: flag(item, "signer", "isSigner"),
+            optional: flag(item, "optional", "isOptional"),
+            address,
+        });
+    }
+    Ok(())
+}
+
+/// "swapExactIn" -> "swap_exact_in"
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+/// Compare names across the camelCase (legacy) and snake_case IDL styles
+fn same_name(a: &str, b: &str) -> bool {
+    let normalize = |s: &str| s.replace('_', "").to_ascii_lowercase();
+    normalize(a) == normalize(b)
+}
+
+/// Anchor's sighash: first 8 bytes of sha256("global:<snake_case name>")
+pub fn instruction_discriminator(name: &str) -> [u8; 8] {
+    let hash = Sha256::digest(format!("global:{}", to_snake_case(name)).as_bytes());
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash[..8]);
+    discriminator
+}
+
+impl Idl {
+    pub fn from_json(json: &str) -> Result<Self, IdlError> {
+        let raw: RawIdl = serde_json::from_str(json)?;
+
+        let address = raw
+            .address
+            .or(raw.metadata.and_then(|metadata| metadata.address))
+            .map(|address| Pubkey::from_str(&address).map_err(|_| IdlError::InvalidAddress(address)))
+            .transpose()?;
+
+        let instructions = raw
+            .instructions
+            .into_iter()
+            .map(|ix| {
+                let mut accounts = Vec::new();
+                flatten_accounts(&ix.accounts, &mut accounts)?;
+                let args = ix
+                    .args
+                    .into_iter()
+                    .map(|arg| {
+                        let ty = parse_type(&arg.ty, &format!("{}.{}", ix.name, arg.name))?;
+                        Ok((arg.name, ty))
+                    })
+                    .collect::<Result<_, IdlError>>()?;
+                Ok(IdlInstruction {
+                    discriminator: ix
+                        .discriminator
+                        .unwrap_or_else(|| instruction_discriminator(&ix.name).to_vec()),
+                    name: ix.name,
+                    accounts,
+                    args,
+                })
+            })
+            .collect::<Result<_, IdlError>>()?;
+
+        let types = raw
+            .types
+            .iter()
+            .map(|def| Ok((def.name.clone(), parse_type_def(def)?)))
+            .collect::<Result<_, IdlError>>()?;
+
+        Ok(Self {
+            address,
+            instructions,
+            types,
+        })
+    }
+
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, IdlError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Instruction by name, in either snake_case or camelCase
+    pub fn instruction(&self, name: &str) -> Option<&IdlInstruction> {
+        self.instructions
+            .iter()
+            .find(|ix| ix.name == name)
+            .or_else(|| self.instructions.iter().find(|ix| same_name(&ix.name, name)))
+    }
+
+    /// Start building `name` for the IDL's own program address
+    pub fn builder(&self, name: &str) -> Result<InstructionBuilder<'_>, IdlError> {
+        let program_id = self.address.ok_or(IdlError::NoProgramId)?;
+        self.builder_for(program_id, name)
+    }
+
+    /// Start building `name` for a deployment at `program_id`
+    pub fn builder_for(&self, program_id: Pubkey, name: &str) -> Result<InstructionBuilder<'_>, IdlError> {
+        let instruction = self
+            .instruction(name)
+            .ok_or_else(|| IdlError::UnknownInstruction(name.to_string()))?;
+        Ok(InstructionBuilder {
+            idl: self,
+            instruction,
+            program_id,
+            accounts: HashMap::new(),
+            args: Map::new(),
+            remaining: Vec::new(),
+        })
+    }
+}
+
+// ============================================================================
+// ON-CHAIN IDL
+// ============================================================================
+
+/// Anchor's IDL account: `create_with_seed(signer PDA of [], "anchor:idl", program)`
+pub fn idl_address(program_id: &Pubkey) -> Pubkey {
+    let (base, _) = Pubkey::find_program_address(&[], program_id);
+    Pubkey::create_with_seed(&base, "anchor:idl", program_id).expect("seed is shorter than MAX_SEED_LEN")
+}
+
+/// Fetch and decompress the IDL a program published with `anchor idl init`
+pub async fn fetch_idl(rpc: &RpcClient, program_id: &Pubkey) -> Result<Idl, IdlError> {
+    let address = idl_address(program_id);
+    let account = rpc
+        .get_account_with_commitment(&address, rpc.commitment())
+        .await?
+        .value
+        .ok_or(IdlError::IdlAccountMissing(*program_id))?;
+
+    // discriminator (8) | authority (32) | data_len (u32) | zlib data
+    const HEADER_LEN: usize = 8 + 32 + 4;
+    let data = &account.data;
+    if data.len() < HEADER_LEN {
+        return Err(IdlError::IdlAccountMalformed("account shorter than header".into()));
+    }
+    let len = u32::from_le_bytes(data[40..44].try_into().unwrap()) as usize;
+    let compressed = data
+        .get(HEADER_LEN..HEADER_LEN + len)
+        .ok_or_else(|| IdlError::IdlAccountMalformed(format!("data length {} exceeds account", len)))?;
+
+    let mut json = String::new();
+    ZlibDecoder::new(compressed)
+        .read_to_string(&mut json)
+        .map_err(|e| IdlError::IdlAccountMalformed(e.to_string()))?;
+    let mut idl = Idl::from_json(&json)?;
+    idl.address.get_or_insert(*program_id);
+    Ok(idl)
+}
+
+// ============================================================================
+// BUILDER
+// ============================================================================
+
+/// Builds one instruction from named accounts and JSON arguments
+pub struct InstructionBuilder<'a> {
+    idl: &'a Idl,
+    instruction: &'a IdlInstruction,
+    program_id: Pubkey,
+    accounts: HashMap<String, Pubkey>,
+    args: Map<String, Value>,
+    remaining: Vec<AccountMeta>,
+}
+
+impl<'a> InstructionBuilder<'a> {
+    pub fn account(mut self, name: &str, pubkey: Pubkey) -> Self {
+        self.accounts.insert(name.to_string(), pubkey);
+        self
+    }
+
+    /// Integers above 2^53 (and all u128/i128) should be passed as strings;
+    /// pubkeys as base58 strings; enums as `"Variant"` or `{"Variant": ...}`
+    pub fn arg(mut self, name: &str, value: Value) -> Self {
+        self.args.insert(name.to_string(), value);
+        self
+    }
+
+    /// All arguments at once as a JSON object
+    pub fn args(mut self, args: Map<String, Value>) -> Self {
+        self.args.extend(args);
+        self
+    }
+
+    /// Extra accounts appended after the IDL accounts
+    pub fn remaining_accounts(mut self, accounts: impl IntoIterator<Item = AccountMeta>) -> Self {
+        self.remaining.extend(accounts);
+        self
+    }
+
+    pub fn build(self) -> Result<Instruction, IdlError> {
+        let ix = self.instruction;
+
+        for name in self.accounts.keys() {
+            if !ix.accounts.iter().any(|a| same_name(&a.name, name)) {
+                return Err(IdlError::UnexpectedAccount(name.clone()));
+            }
+        }
+        for name in self.args.keys() {
+            if !ix.args.iter().any(|(arg, _)| same_name(arg, name)) {
+                return Err(IdlError::UnexpectedArg(name.clone()));
+            }
+        }
+
+        let mut metas = Vec::with_capacity(ix.accounts.len() + self.remaining.len());
+        for item in &ix.accounts {
+            let provided = self
+                .accounts
+                .iter()
+                .find(|(name, _)| same_name(name, &item.name))
+                .map(|(_, pubkey)| *pubkey);
+            let meta = match provided.or(item.address) {
+                Some(pubkey) if item.writable => AccountMeta::new(pubkey, item.signer),
+                Some(pubkey) => AccountMeta::new_readonly(pubkey, item.signer),
+                // Anchor reads the program ID in place of an absent optional account
+                None if item.optional => AccountMeta::new_readonly(self.program_id, false),
+                None => return Err(IdlError::MissingAccount(item.name.clone())),
+            };
+            metas.push(meta);
+        }
+        metas.extend(self.remaining);
+
+        let mut data = ix.discriminator.clone();
+        for (name, ty) in &ix.args {
+            let value = self
+                .args
+                .iter()
+                .find(|(arg, _)| same_name(arg, name))
+                .map(|(_, value)| value)
+                .ok_or_else(|| IdlError::MissingArg(name.clone()))?;
+            encode(self.idl, ty, value, name, &mut data)?;
+        }
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts: metas,
+            data,
+        })
+    }
+}
+
+// ============================================================================
+// BORSH ENCODING
+// ============================================================================
+
+fn invalid(path: &str, expected: impl Into<String>) -> IdlError {
+    IdlError::InvalidArg {
+        path: path.to_string(),
+        expected: expected.into(),
+    }
+}
+
+/// Integer from a JSON number or decimal string
+fn integer<T: FromStr + TryFrom<i128>>(value: &Value, path: &str, name: &str) -> Result<T, IdlError> {
+    let parsed = match value {
+        Value::Number(n) => n
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| n.as_u64().map(i128::from))
+            .and_then(|n| T::try_from(n).ok()),
+        Value::String(s) => s.parse::<T>().ok(),
+        _ => None,
+    };
+    parsed.ok_or_else(|| invalid(path, name))
+}
+
+fn encode_len(len: usize, path: &str, out: &mut Vec<u8>) -> Result<(), IdlError> {
+    let len = u32::try_from(len).map_err(|_| invalid(path, "length below 2^32"))?;
+    out.extend_from_slice(&len.to_le_bytes());
+    Ok(())
+}
+
+fn encode(idl: &Idl, ty: &IdlType, value: &Value, path: &str, out: &mut Vec<u8>) -> Result<(), IdlError> {
+    match ty {
+        IdlType::Bool => out.push(value.as_bool().ok_or_else(|| invalid(path, "bool"))? as u8),
+        IdlType::U8 => out.push(integer::<u8>(value, path, "u8")?),
+        IdlType::U16 => out.extend_from_slice(&integer::<u16>(value, path, "u16")?.to_le_bytes()),
+        IdlType::U32 => out.extend_from_slice(&integer::<u32>(value, path, "u32")?.to_le_bytes()),
+        IdlType::U64 => out.extend_from_slice(&integer::<u64>(value, path, "u64")?.to_le_bytes()),
+        IdlType::U128 => out.extend_from_slice(&integer::<u128>(value, path, "u128")?.to_le_bytes()),
+        IdlType::I8 => out.extend_from_slice(&integer::<i8>(value, path, "i8")?.to_le_bytes()),
+        IdlType::I16 => out.extend_from_slice(&integer::<i16>(value, path, "i16")?.to_le_bytes()),
+        IdlType::I32 => out.extend_from_slice(&integer::<i32>(value, path, "i32")?.to_le_bytes()),
+        IdlType::I64 => out.extend_from_slice(&integer::<i64>(value, path, "i64")?.to_le_bytes()),
+        IdlType::I128 => out.extend_from_slice(&integer::<i128>(value, path, "i128")?.to_le_bytes()),
+        IdlType::F32 => {
+            let f = value.as_f64().ok_or_else(|| invalid(path, "f32"))?;
+            out.extend_from_slice(&(f as f32).to_le_bytes());
+        }
+        IdlType::F64 => {
+            let f = value.as_f64().ok_or_else(|| invalid(path, "f64"))?;
+            out.extend_from_slice(&f.to_le_bytes());
+        }
+        IdlType::String => {
+            let s = value.as_str().ok_or_else(|| invalid(path, "string"))?;
+            encode_len(s.len(), path, out)?;
+            out.extend_from_slice(s.as_bytes());
+        }
+        IdlType::Bytes => {
+            let bytes = value.as_array().ok_or_else(|| invalid(path, "array of bytes"))?;
+            encode_len(bytes.len(), path, out)?;
+            for (i, byte) in bytes.iter().enumerate() {
+                out.push(integer::<u8>(byte, &format!("{}[{}]", path, i), "u8")?);
+            }
+        }
+        IdlType::Pubkey => {
+            let pubkey = value
+                .as_str()
+                .and_then(|s| Pubkey::from_str(s).ok())
+                .ok_or_else(|| invalid(path, "base58 pubkey"))?;
+            out.extend_from_slice(pubkey.as_ref());
+        }
+        IdlType::Option(inner) => match value {
+            Value::Null => out.push(0),
+            value => {
+                out.push(1);
+                encode(idl, inner, value, path, out)?;
+            }
+        },
+        IdlType::Vec(inner) => {
+            let items = value.as_array().ok_or_else(|| invalid(path, "array"))?;
+            encode_len(items.len(), path, out)?;
+            for (i, item) in items.iter().enumerate() {
+                encode(idl, inner, item, &format!("{}[{}]", path, i), out)?;
+            }
+        }
+        IdlType::Array(inner, len) => {
+            let items = value
+                .as_array()
+                .filter(|items| items.len() == *len)
+                .ok_or_else(|| invalid(path, format!("array of {}", len)))?;
+            for (i, item) in items.iter().enumerate() {
+                encode(idl, inner, item, &format!("{}[{}]", path, i), out)?;
+            }
+        }
+        IdlType::Defined(name) => {
+            let def = idl
+                .types
+                .get(name)
+                .ok_or_else(|| IdlError::UnknownType(name.clone()))?;
+            match def {
+                IdlTypeDef::Struct(fields) => encode_fields(idl, fields, value, path, out)?,
+                IdlTypeDef::Enum(variants) => encode_enum(idl, name, variants, value, path, out)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+fn encode_fields(idl: &Idl, fields: &IdlFields, value: &Value, path: &str, out: &mut Vec<u8>) -> Result<(), IdlError> {
+    match fields {
+        IdlFields::Unit => Ok(()),
+        IdlFields::Named(fields) => {
+            let object = value.as_object().ok_or_else(|| invalid(path, "object"))?;
+            for (name, ty) in fields {
+                let field_path = format!("{}.{}", path, name);
+                let field = object
+                    .iter()
+                    .find(|(key, _)| same_name(key, name))
+                    .map(|(_, field)| field)
+                    .ok_or_else(|| IdlError::MissingArg(field_path.clone()))?;
+                encode(idl, ty, field, &field_path, out)?;
+            }
+            Ok(())
+        }
+        IdlFields::Tuple(types) => {
+            let items = value
+                .as_array()
+                .filter(|items| items.len() == types.len())
+                .ok_or_else(|| invalid(path, format!("array of {}", types.len())))?;
+            for (i, (ty, item)) in types.iter().zip(items).enumerate() {
+                encode(idl, ty, item, &format!("{}.{}", path, i), out)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn encode_enum(
+    idl: &Idl,
+    type_name: &str,
+    variants: &[IdlVariant],
+    value: &Value,
+    path: &str,
+    out: &mut Vec<u8>,
+) -> Result<(), IdlError> {
+    // "Variant" for unit variants, {"Variant": fields} otherwise
+    let (variant_name, fields) = match value {
+        Value::String(name) => (name.as_str(), &Value::Null),
+        Value::Object(object) if object.len() == 1 => {
+            let (name, fields) = object.iter().next().unwrap();
+            (name.as_str(), fields)
+        }
+        _ => return Err(invalid(path, format!("{} variant", type_name))),
+    };
+    let (index, variant) = variants
+        .iter()
+        .enumerate()
+        .find(|(_, variant)| same_name(&variant.name, variant_name))
+        .ok_or_else(|| invalid(path, format!("{} variant, got {}", type_name, variant_name)))?;
+
+    out.push(u8::try_from(index).map_err(|_| invalid(path, "enum with at most 256 variants"))?);
+    encode_fields(idl, &variant.fields, fields, &format!("{}::{}", path, variant.name), out)
+}
@@ -0,0 +1,353 @@
+/**
+ * Transaction Landing Analytics and Adaptive Resubmission
+ *
+ * Tracks landing latency and drop rate per RPC endpoint and priority fee
+ * level over a rolling window, and feeds them to an adaptive sender that
+ * raises the priority fee or moves to another endpoint when landing
+ * degrades, and steps the fee back down once it recovers.
+ *
+ * Degradation is judged per (endpoint, fee level): if one endpoint drops
+ * transactions that another lands at the same fee, the problem is the
+ * endpoint and the sender switches; if every endpoint struggles, the
+ * network is congested and the fee goes up.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    solana-sdk = "1.18"
+ *
+ * 2. Copy this file as `landing_analytics.rs` and declare `mod landing_analytics;`
+ */
+
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use std::collections::{BTreeMap, VecDeque};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Outcomes kept per (endpoint, fee level)
+pub const DEFAULT_WINDOW: usize = 200;
+
+/// Outcomes older than this no longer count (10 minutes)
+pub const DEFAULT_STATS_MAX_AGE_MS: u64 = 600_000;
+
+/// Outcomes needed before a (endpoint, fee level) is judged
+pub const DEFAULT_MIN_SAMPLES: usize = 20;
+
+pub const DEFAULT_TARGET_LANDING_RATE: f64 = 0.9;
+
+pub const DEFAULT_MAX_P90_LATENCY_MS: u64 = 4_000;
+
+/// Priority fee levels in micro-lamports per compute unit
+pub const DEFAULT_FEE_LADDER: [u64; 5] = [1_000, 10_000, 50_000, 200_000, 1_000_000];
+
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+// ============================================================================
+// ANALYTICS
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SendOutcome {
+    /// Confirmed `latency_ms` after it was sent
+    Landed { latency_ms: u64 },
+    /// Blockhash expired without the transaction landing
+    Dropped,
+}
+
+/// Landing statistics over the current window
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LandingStats {
+    pub sent: usize,
+    pub landed: usize,
+    pub p50_latency_ms: Option<u64>,
+    pub p90_latency_ms: Option<u64>,
+}
+
+impl LandingStats {
+    fn from_outcomes<'a>(outcomes: impl Iterator<Item = &'a SendOutcome>) -> Self {
+        let mut sent = 0;
+        let mut latencies = Vec::new();
+        for outcome in outcomes {
+            sent += 1;
+            if let SendOutcome::Landed { latency_ms } = outcome {
+                latencies.push(*latency_ms);
+            }
+        }
+        latencies.sort_unstable();
+        let percentile = |p: usize| {
+            (!latencies.is_empty()).then(|| latencies[(latencies.len() - 1) * p / 100])
+        };
+        Self {
+            sent,
+            landed: latencies.len(),
+            p50_latency_ms: percentile(50),
+            p90_latency_ms: percentile(90),
+        }
+    }
+
+    pub fn landing_rate(&self) -> Option<f64> {
+        (self.sent > 0).then(|| self.landed as f64 / self.sent as f64)
+    }
+
+    pub fn drop_rate(&self) -> Option<f64> {
+        self.landing_rate().map(|rate| 1.0 - rate)
+    }
+}
+
+/// Rolling landing outcomes per (endpoint, fee level)
+#[derive(Clone, Debug)]
+pub struct LandingTracker {
+    window: usize,
+    max_age_ms: u64,
+    samples: BTreeMap<(String, u64), VecDeque<(u64, SendOutcome)>>,
+}
+
+impl Default for LandingTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW, DEFAULT_STATS_MAX_AGE_MS)
+    }
+}
+
+impl LandingTracker {
+    pub fn new(window: usize, max_age_ms: u64) -> Self {
+        Self {
+            window,
+            max_age_ms,
+            samples: BTreeMap::new(),
+        }
+    }
+
+    /// Record the outcome of a transaction sent to `endpoint` at
+    /// `fee_micro_lamports`, observed at `now_ms`
+    pub fn record(&mut self, endpoint: &str, fee_micro_lamports: u64, outcome: SendOutcome, now_ms: u64) {
+        let samples = self
+            .samples
+            .entry((endpoint.to_string(), fee_micro_lamports))
+            .or_default();
+        samples.push_back((now_ms, outcome));
+        while samples.len() > self.window {
+            samples.pop_front();
+        }
+    }
+
+    fn recent<'a>(
+        &self,
+        samples: &'a VecDeque<(u64, SendOutcome)>,
+        now_ms: u64,
+    ) -> impl Iterator<Item = &'a SendOutcome> {
+        let cutoff = now_ms.saturating_sub(self.max_age_ms);
+        samples
+            .iter()
+            .filter(move |(at, _)| *at >= cutoff)
+            .map(|(_, outcome)| outcome)
+    }
+
+    pub fn stats(&self, endpoint: &str, fee_micro_lamports: u64, now_ms: u64) -> LandingStats {
+        match self.samples.get(&(endpoint.to_string(), fee_micro_lamports)) {
+            Some(samples) => LandingStats::from_outcomes(self.recent(samples, now_ms)),
+            None => LandingStats::default(),
+        }
+    }
+
+    /// Stats for an endpoint across all fee levels
+    pub fn endpoint_stats(&self, endpoint: &str, now_ms: u64) -> LandingStats {
+        LandingStats::from_outcomes(
+            self.samples
+                .iter()
+                .filter(|((e, _), _)| e == endpoint)
+                .flat_map(|(_, samples)| self.recent(samples, now_ms)),
+        )
+    }
+
+    /// Stats for every (endpoint, fee level) seen, for dashboards and logs
+    pub fn report(&self, now_ms: u64) -> Vec<(String, u64, LandingStats)> {
+        self.samples
+            .iter()
+            .map(|((endpoint, fee), samples)| {
+                (
+                    endpoint.clone(),
+                    *fee,
+                    LandingStats::from_outcomes(self.recent(samples, now_ms)),
+                )
+            })
+            .filter(|(_, _, stats)| stats.sent > 0)
+            .collect()
+    }
+}
+
+// ============================================================================
+// ADAPTIVE RESUBMISSION
+// ============================================================================
+
+#[derive(Clone, Debug)]
+pub struct ResubmitConfig {
+    /// RPC endpoints in order of preference
+    pub endpoints: Vec<String>,
+    /// Priority fee levels, ascending
+    pub fee_ladder: Vec<u64>,
+    pub target_landing_rate: f64,
+    pub max_p90_latency_ms: u64,
+    pub min_samples: usize,
+    /// Sends of one transaction before giving up
+    pub max_attempts: u32,
+}
+
+impl ResubmitConfig {
+    pub fn new(endpoints: Vec<String>) -> Self {
+        Self {
+            endpoints,
+            fee_ladder: DEFAULT_FEE_LADDER.to_vec(),
+            target_landing_rate: DEFAULT_TARGET_LANDING_RATE,
+            max_p90_latency_ms: DEFAULT_MAX_P90_LATENCY_MS,
+            min_samples: DEFAULT_MIN_SAMPLES,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+}
+
+/// Where and at what fee to send
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SendPlan {
+    pub endpoint: String,
+    pub fee_micro_lamports: u64,
+    /// 1 for the first send of a transaction
+    pub attempt: u32,
+}
+
+impl SendPlan {
+    /// Compute budget instruction to prepend for this plan's fee
+    pub fn priority_fee_instruction(&self) -> Instruction {
+        ComputeBudgetInstruction::set_compute_unit_price(self.fee_micro_lamports)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ResubmitDecision {
+    Resend(SendPlan),
+    GiveUp,
+}
+
+/// Chooses endpoint and fee for sends from observed landing
+#[derive(Clone, Debug)]
+pub struct AdaptiveSender {
+    config: ResubmitConfig,
+    tracker: LandingTracker,
+    endpoint_index: usize,
+    fee_index: usize,
+}
+
+impl AdaptiveSender {
+    pub fn new(config: ResubmitConfig, tracker: LandingTracker) -> Self {
+        assert!(!config.endpoints.is_empty(), "at least one endpoint");
+        assert!(!config.fee_ladder.is_empty(), "at least one fee level");
+        Self {
+            config,
+            tracker,
+            endpoint_index: 0,
+            fee_index: 0,
+        }
+    }
+
+    pub fn tracker(&self) -> &LandingTracker {
+        &self.tracker
+    }
+
+    /// Plan for the first send of a new transaction
+    pub fn plan(&self) -> SendPlan {
+        SendPlan {
+            endpoint: self.config.endpoints[self.endpoint_index].clone(),
+            fee_micro_lamports: self.config.fee_ladder[self.fee_index],
+            attempt: 1,
+        }
+    }
+
+    /// Record how a send went and adapt the default plan
+    pub fn record(&mut self, plan: &SendPlan, outcome: SendOutcome, now_ms: u64) {
+        self.tracker
+            .record(&plan.endpoint, plan.fee_micro_lamports, outcome, now_ms);
+        self.adapt(now_ms);
+    }
+
+    /// Next plan for a transaction whose send under `plan` was dropped:
+    /// one fee level up, on another endpoint if this one is degraded
+    pub fn resubmit(&self, plan: &SendPlan, now_ms: u64) -> ResubmitDecision {
+        if plan.attempt >= self.config.max_attempts {
+            return ResubmitDecision::GiveUp;
+        }
+        let fee_index = self
+            .config
+            .fee_ladder
+            .iter()
+            .position(|fee| *fee > plan.fee_micro_lamports)
+            .unwrap_or(self.config.fee_ladder.len() - 1);
+        let fee = self.config.fee_ladder[fee_index];
+
+        let endpoint = if self.is_degraded(&plan.endpoint, plan.fee_micro_lamports, now_ms) {
+            self.best_endpoint(fee, now_ms).unwrap_or(&plan.endpoint)
+        } else {
+            &plan.endpoint
+        };
+
+        ResubmitDecision::Resend(SendPlan {
+            endpoint: endpoint.clone(),
+            fee_micro_lamports: fee,
+            attempt: plan.attempt + 1,
+        })
+    }
+
+    /// Judged once there are enough samples; unknown counts as healthy
+    fn is_degraded(&self, endpoint: &str, fee: u64, now_ms: u64) -> bool {
+        let stats = self.tracker.stats(endpoint, fee, now_ms);
+        stats.sent >= self.config.min_samples
+            && (stats.landing_rate().unwrap_or(0.0) < self.config.target_landing_rate
+                || stats
+                    .p90_latency_ms
+                    .map_or(true, |p90| p90 > self.config.max_p90_latency_ms))
+    }
+
+    /// Healthy endpoint at `fee` with the best landing rate, preferring
+    /// earlier endpoints on ties and unmeasured ones over degraded ones
+    fn best_endpoint(&self, fee: u64, now_ms: u64) -> Option<&String> {
+        self.config
+            .endpoints
+            .iter()
+            .filter(|endpoint| !self.is_degraded(endpoint, fee, now_ms))
+            .max_by(|a, b| {
+                let rate = |e: &str| self.tracker.stats(e, fee, now_ms).landing_rate().unwrap_or(1.0);
+                rate(a).total_cmp(&rate(b)).then(std::cmp::Ordering::Greater)
+            })
+    }
+
+    fn adapt(&mut self, now_ms: u64) {
+        let fee = self.config.fee_ladder[self.fee_index];
+        let endpoint = &self.config.endpoints[self.endpoint_index];
+
+        if self.is_degraded(endpoint, fee, now_ms) {
+            // Another endpoint landing at this fee means the endpoint is the problem
+            if let Some(better) = self.best_endpoint(fee, now_ms) {
+                self.endpoint_index = self
+                    .config
+                    .endpoints
+                    .iter()
+                    .position(|e| e == better)
+                    .unwrap_or(self.endpoint_index);
+            } else if self.fee_index + 1 < self.config.fee_ladder.len() {
+                self.fee_index += 1;
+            }
+            return;
+        }
+
+        // Step back down once this level is proven healthy and the level
+        // below is not known to be degraded
+        let stats = self.tracker.stats(endpoint, fee, now_ms);
+        if self.fee_index > 0
+            && stats.sent >= self.config.min_samples
+            && !self.is_degraded(endpoint, self.config.fee_ladder[self.fee_index - 1], now_ms)
+        {
+            self.fee_index -= 1;
+        }
+    }
+}
@@ -0,0 +1,510 @@
+/**
+ * Order Lifecycle Tracking
+ *
+ * Turns fire-and-forget swaps into orders a strategy can manage. An order
+ * is created before anything is sent, gets one attempt per transaction
+ * sent for it, and moves through
+ *
+ *   Pending -> PartiallyFilled -> Filled
+ *          \-> Expired / Failed
+ *
+ * as confirmed transactions (read with fill-reconstruction.rs) and venue
+ * callbacks (RFQ maker webhooks, venue order APIs) arrive. Filled, Expired,
+ * and Failed are terminal; a partially filled order that expires keeps its
+ * filled amounts. Every change is written to an `OrderStore`, so working
+ * orders survive a restart and the next run picks them up with
+ * `open_orders`.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    serde = { version = "1", features = ["derive"] }
+ *    serde_json = "1"
+ *    serde_with = "3"
+ *    solana-client = "1.18"
+ *    solana-sdk = "1.18"
+ *    thiserror = "1"
+ *
+ * 2. Copy next to fill-reconstruction.rs as `order_lifecycle.rs`
+ * 3. Create an order before sending, call `OrderTracker::sent` with each
+ *    signature, and run `OrderTracker::poll` on the strategy loop
+ */
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::fill_reconstruction::{fetch_parsed_transaction, Fill, FillError, ParsedTransaction};
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum OrderError {
+    #[error("unknown order {0}")]
+    UnknownOrder(String),
+
+    #[error("order {0} already exists")]
+    DuplicateOrder(String),
+
+    #[error("order {id} is {state:?} and cannot change")]
+    Terminal { id: String, state: OrderState },
+
+    #[error("transaction {0} is not an attempt of this order")]
+    UnknownAttempt(String),
+
+    #[error("order store failed: {0}")]
+    Store(String),
+}
+
+pub type OrderResult<T> = std::result::Result<T, OrderError>;
+
+// ============================================================================
+// ORDERS
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderState {
+    /// Created or sent; nothing filled yet
+    Pending,
+    /// Some input filled, the rest still working
+    PartiallyFilled,
+    Filled,
+    /// Reached `expires_at` before filling completely
+    Expired,
+    /// Every attempt failed with nothing filled
+    Failed,
+}
+
+impl OrderState {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, OrderState::Filled | OrderState::Expired | OrderState::Failed)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttemptStatus {
+    /// Sent, not confirmed yet
+    InFlight,
+    Landed,
+    Failed,
+}
+
+/// One transaction (or venue-side execution) for an order
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderAttempt {
+    /// Transaction signature, or the venue's execution ID for callbacks
+    pub reference: String,
+    pub sent_at: i64,
+    pub status: AttemptStatus,
+    #[serde(default)]
+    pub filled_in: u64,
+    #[serde(default)]
+    pub filled_out: u64,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderTransition {
+    pub from: OrderState,
+    pub to: OrderState,
+    pub at: i64,
+    pub reason: String,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Order {
+    pub id: String,
+    /// Venue label, as in `VenueQuote::venue`
+    pub venue: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub input_mint: Pubkey,
+    #[serde_as(as = "DisplayFromStr")]
+    pub output_mint: Pubkey,
+    pub amount_in: u64,
+    /// Output expected for the full `amount_in`, from oracle prices
+    pub expected_output: u64,
+    pub filled_in: u64,
+    pub filled_out: u64,
+    pub state: OrderState,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub updated_at: i64,
+    pub attempts: Vec<OrderAttempt>,
+    pub history: Vec<OrderTransition>,
+}
+
+impl Order {
+    /// A pending order that never expires; set a deadline with `expires_in`
+    pub fn new(
+        id: impl Into<String>,
+        venue: impl Into<String>,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        amount_in: u64,
+        expected_output: u64,
+        now: i64,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            venue: venue.into(),
+            input_mint,
+            output_mint,
+            amount_in,
+            expected_output,
+            filled_in: 0,
+            filled_out: 0,
+            state: OrderState::Pending,
+            created_at: now,
+            expires_at: i64::MAX,
+            updated_at: now,
+            attempts: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+
+    pub fn expires_in(mut self, ttl_secs: i64) -> Self {
+        self.expires_at = self.created_at.saturating_add(ttl_secs);
+        self
+    }
+
+    /// Input not filled yet
+    pub fn remaining_in(&self) -> u64 {
+        self.amount_in.saturating_sub(self.filled_in)
+    }
+
+    pub fn in_flight(&self) -> impl Iterator<Item = &OrderAttempt> {
+        self.attempts.iter().filter(|a| a.status == AttemptStatus::InFlight)
+    }
+
+    fn check_open(&self) -> OrderResult<()> {
+        if self.state.is_terminal() {
+            return Err(OrderError::Terminal {
+                id: self.id.clone(),
+                state: self.state,
+            });
+        }
+        Ok(())
+    }
+
+    fn attempt_mut(&mut self, reference: &str) -> OrderResult<&mut OrderAttempt> {
+        self.attempts
+            .iter_mut()
+            .find(|a| a.reference == reference)
+            .ok_or_else(|| OrderError::UnknownAttempt(reference.to_string()))
+    }
+
+    fn transition(&mut self, to: OrderState, now: i64, reason: impl Into<String>) {
+        self.updated_at = now;
+        if to != self.state {
+            self.history.push(OrderTransition {
+                from: self.state,
+                to,
+                at: now,
+                reason: reason.into(),
+            });
+            self.state = to;
+        }
+    }
+
+    /// Record a transaction (or venue execution) sent for the order
+    pub fn record_sent(&mut self, reference: impl Into<String>, now: i64) -> OrderResult<()> {
+        self.check_open()?;
+        self.attempts.push(OrderAttempt {
+            reference: reference.into(),
+            sent_at: now,
+            status: AttemptStatus::InFlight,
+            filled_in: 0,
+            filled_out: 0,
+            error: None,
+        });
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Apply amounts filled by one attempt. Input beyond the order size
+    /// still counts, so the recorded amounts match the wallet.
+    pub fn apply_fill(&mut self, reference: &str, filled_in: u64, filled_out: u64, now: i64) -> OrderResult<()> {
+        self.check_open()?;
+        let attempt = self.attempt_mut(reference)?;
+        attempt.status = AttemptStatus::Landed;
+        attempt.filled_in = attempt.filled_in.saturating_add(filled_in);
+        attempt.filled_out = attempt.filled_out.saturating_add(filled_out);
+        self.filled_in = self.filled_in.saturating_add(filled_in);
+        self.filled_out = self.filled_out.saturating_add(filled_out);
+
+        let (state, reason) = if self.filled_in >= self.amount_in {
+            (OrderState::Filled, format!("{} filled", reference))
+        } else {
+            (
+                OrderState::PartiallyFilled,
+                format!("{} filled {} of {}", reference, self.filled_in, self.amount_in),
+            )
+        };
+        self.transition(state, now, reason);
+        Ok(())
+    }
+
+    /// Apply a failed attempt. The order fails only when nothing filled and
+    /// no other attempt is in flight; otherwise it keeps working.
+    pub fn apply_failure(&mut self, reference: &str, error: impl Into<String>, now: i64) -> OrderResult<()> {
+        self.check_open()?;
+        let error = error.into();
+        let attempt = self.attempt_mut(reference)?;
+        attempt.status = AttemptStatus::Failed;
+        attempt.error = Some(error.clone());
+
+        if self.filled_in == 0 && self.in_flight().next().is_none() {
+            self.transition(OrderState::Failed, now, format!("{} failed: {}", reference, error));
+        } else {
+            self.updated_at = now;
+        }
+        Ok(())
+    }
+
+    /// Apply a confirmed transaction parsed by fill-reconstruction.rs
+    pub fn apply_transaction(&mut self, tx: &ParsedTransaction, owner: &Pubkey, now: i64) -> OrderResult<()> {
+        let reference = tx.signature.to_string();
+        if !tx.succeeded {
+            return self.apply_failure(&reference, "transaction failed on chain", now);
+        }
+        match tx.fill(owner) {
+            Ok(fill) if self.matches(&fill) => {
+                self.apply_fill(&reference, fill.input_amount, fill.output_amount, now)
+            }
+            Ok(_) => self.apply_failure(&reference, "fill does not match the order's mints", now),
+            Err(e) => self.apply_failure(&reference, e.to_string(), now),
+        }
+    }
+
+    fn matches(&self, fill: &Fill) -> bool {
+        fill.input_mint == self.input_mint && fill.output_mint == self.output_mint
+    }
+
+    /// Expire the order once `expires_at` passes. Returns whether it did.
+    pub fn expire_if_due(&mut self, now: i64) -> bool {
+        if self.state.is_terminal() || now < self.expires_at {
+            return false;
+        }
+        let reason = format!("expired with {} of {} filled", self.filled_in, self.amount_in);
+        self.transition(OrderState::Expired, now, reason);
+        true
+    }
+
+    /// Output shortfall of the filled part against the oracle expectation,
+    /// in bps; negative when the fills beat the oracle
+    pub fn slippage_bps(&self) -> Option<i64> {
+        if self.filled_in == 0 || self.amount_in == 0 || self.expected_output == 0 {
+            return None;
+        }
+        let expected = self.expected_output as i128 * self.filled_in as i128 / self.amount_in as i128;
+        if expected == 0 {
+            return None;
+        }
+        Some(((expected - self.filled_out as i128) * 10_000 / expected) as i64)
+    }
+}
+
+// ============================================================================
+// VENUE CALLBACKS
+// ============================================================================
+
+/// A status report pushed by a venue (webhook, order API poll)
+#[derive(Clone, Debug, Deserialize)]
+pub struct VenueUpdate {
+    pub order_id: String,
+    /// Execution ID or transaction signature the venue reports on
+    pub reference: String,
+    pub event: VenueEvent,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum VenueEvent {
+    /// The venue accepted the order; registers the reference as an attempt
+    Accepted,
+    Filled { filled_in: u64, filled_out: u64 },
+    Rejected { reason: String },
+}
+
+// ============================================================================
+// STORE
+// ============================================================================
+
+/// Where orders are persisted
+pub trait OrderStore {
+    fn load(&self, id: &str) -> OrderResult<Option<Order>>;
+
+    fn save(&mut self, order: &Order) -> OrderResult<()>;
+
+    /// Orders not in a terminal state
+    fn open_orders(&self) -> OrderResult<Vec<Order>>;
+}
+
+impl OrderStore for BTreeMap<String, Order> {
+    fn load(&self, id: &str) -> OrderResult<Option<Order>> {
+        Ok(self.get(id).cloned())
+    }
+
+    fn save(&mut self, order: &Order) -> OrderResult<()> {
+        self.insert(order.id.clone(), order.clone());
+        Ok(())
+    }
+
+    fn open_orders(&self) -> OrderResult<Vec<Order>> {
+        Ok(self.values().filter(|o| !o.state.is_terminal()).cloned().collect())
+    }
+}
+
+/// All orders in one JSON file, rewritten atomically on every save.
+/// Terminal orders are kept for the audit trail; prune with `retain`.
+pub struct JsonFileOrderStore {
+    path: PathBuf,
+    orders: BTreeMap<String, Order>,
+}
+
+impl JsonFileOrderStore {
+    pub fn open(path: impl Into<PathBuf>) -> OrderResult<Self> {
+        let path = path.into();
+        let orders = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| OrderError::Store(e.to_string()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(OrderError::Store(e.to_string())),
+        };
+        Ok(Self { path, orders })
+    }
+
+    /// Drop orders `keep` rejects and rewrite the file
+    pub fn retain(&mut self, keep: impl Fn(&Order) -> bool) -> OrderResult<()> {
+        self.orders.retain(|_, order| keep(order));
+        self.flush()
+    }
+
+    fn flush(&self) -> OrderResult<()> {
+        let json = serde_json::to_vec_pretty(&self.orders).map_err(|e| OrderError::Store(e.to_string()))?;
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, json)
+            .and_then(|_| std::fs::rename(&tmp, &self.path))
+            .map_err(|e| OrderError::Store(e.to_string()))
+    }
+}
+
+impl OrderStore for JsonFileOrderStore {
+    fn load(&self, id: &str) -> OrderResult<Option<Order>> {
+        self.orders.load(id)
+    }
+
+    fn save(&mut self, order: &Order) -> OrderResult<()> {
+        self.orders.save(order)?;
+        self.flush()
+    }
+
+    fn open_orders(&self) -> OrderResult<Vec<Order>> {
+        self.orders.open_orders()
+    }
+}
+
+// ============================================================================
+// TRACKER
+// ============================================================================
+
+/// Applies transactions and venue callbacks to stored orders
+pub struct OrderTracker<S: OrderStore> {
+    store: S,
+    owner: Pubkey,
+    commitment: CommitmentConfig,
+}
+
+impl<S: OrderStore> OrderTracker<S> {
+    pub fn new(store: S, owner: Pubkey) -> Self {
+        Self {
+            store,
+            owner,
+            commitment: CommitmentConfig::confirmed(),
+        }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Persist a new order before anything is sent for it
+    pub fn create(&mut self, order: Order) -> OrderResult<()> {
+        if self.store.load(&order.id)?.is_some() {
+            return Err(OrderError::DuplicateOrder(order.id));
+        }
+        self.store.save(&order)
+    }
+
+    fn update(&mut self, id: &str, apply: impl FnOnce(&mut Order) -> OrderResult<()>) -> OrderResult<Order> {
+        let mut order = self
+            .store
+            .load(id)?
+            .ok_or_else(|| OrderError::UnknownOrder(id.to_string()))?;
+        apply(&mut order)?;
+        self.store.save(&order)?;
+        Ok(order)
+    }
+
+    /// Record a transaction sent for an order
+    pub fn sent(&mut self, id: &str, signature: &Signature, now: i64) -> OrderResult<Order> {
+        self.update(id, |order| order.record_sent(signature.to_string(), now))
+    }
+
+    /// Apply a venue callback
+    pub fn on_venue_update(&mut self, update: &VenueUpdate, now: i64) -> OrderResult<Order> {
+        self.update(&update.order_id, |order| match &update.event {
+            VenueEvent::Accepted => order.record_sent(update.reference.clone(), now),
+            VenueEvent::Filled { filled_in, filled_out } => {
+                order.apply_fill(&update.reference, *filled_in, *filled_out, now)
+            }
+            VenueEvent::Rejected { reason } => order.apply_failure(&update.reference, reason.clone(), now),
+        })
+    }
+
+    /// Look up every in-flight transaction attempt once and expire orders
+    /// past their deadline. Returns the orders that changed state.
+    ///
+    /// Venue-side references (not signatures) are left to callbacks.
+    pub async fn poll(&mut self, rpc: &RpcClient, now: i64) -> OrderResult<Vec<Order>> {
+        let mut changed = Vec::new();
+        for mut order in self.store.open_orders()? {
+            let before = order.clone();
+            let signatures: Vec<Signature> = order
+                .in_flight()
+                .filter_map(|a| a.reference.parse().ok())
+                .collect();
+
+            for signature in signatures {
+                match fetch_parsed_transaction(rpc, &signature, self.commitment).await {
+                    Ok(tx) => order.apply_transaction(&tx, &self.owner, now)?,
+                    // Not confirmed yet; expiry below decides
+                    Err(FillError::Rpc(_)) => {}
+                    Err(e) => order.apply_failure(&signature.to_string(), e.to_string(), now)?,
+                }
+                if order.state.is_terminal() {
+                    break;
+                }
+            }
+            order.expire_if_due(now);
+
+            if order != before {
+                self.store.save(&order)?;
+            }
+            if order.state != before.state {
+                changed.push(order);
+            }
+        }
+        Ok(changed)
+    }
+}
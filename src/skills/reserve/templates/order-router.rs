@@ -0,0 +1,397 @@
+/**
+ * Smart Order Router
+ *
+ * Routes one swap across every venue registered with a `VenueRouter`.
+ * Quotes are normalized against the Pyth fair price, so venues are
+ * compared by how much of the oracle value they deliver rather than by raw
+ * output. When splitting the order beats the best single venue by enough
+ * to pay for the extra transactions, size is spread across venues.
+ *
+ * Every plan is checked against one worst-price bound derived from the
+ * oracle confidence: the output at `input price - k·conf` and
+ * `output price + k·conf`, less a fixed tolerance. The bound applies to the
+ * total and, pro rata, to each leg's on-chain minimum, so no combination of
+ * legs filling at their minimums can land below it.
+ *
+ * Splitting: the order is cut into `split_parts` equal parts and every
+ * venue is quoted for 1..=split_parts parts. Parts are then assigned one at
+ * a time to the venue with the best marginal output, which is optimal when
+ * each venue's output is concave in size (as it is for AMM pools).
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    futures = "0.3"
+ *    pyth-oracle = { path = "../pyth-oracle", features = ["no-entrypoint"] }
+ *    serde = { version = "1", features = ["derive"] }
+ *    solana-client = "1.18"
+ *    solana-sdk = "1.18"
+ *    thiserror = "1"
+ *
+ * 2. Copy next to execution-venues.rs as `order_router.rs`
+ * 3. Route with prices validated by the same rules as the rest of the
+ *    strategy, e.g. from the Hermes client
+ */
+
+use pyth_oracle::{calculate_tokens_for_usd, calculate_usd_value, OraclePrice, Rounding, USD_DECIMALS};
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::signature::{Keypair, Signature};
+use std::collections::BTreeMap;
+
+use crate::execution_venues::{
+    QuoteRequest, RejectedQuote, Venue, VenueError, VenueQuote, VenueRouter, VenueResult, BPS_DENOMINATOR,
+};
+
+// ============================================================================
+// CONFIG
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct OrderRouterConfig {
+    /// Parts the order may be split into; 1 disables splitting
+    pub split_parts: u64,
+    /// Improvement over the best single venue a split must reach, in bps of
+    /// the fair output, to pay for its extra transactions
+    pub min_split_improvement_bps: u64,
+    /// Confidence intervals applied to both prices for the worst-price bound
+    pub confidence_sigma: u8,
+    /// Further tolerance below the confidence-adjusted output, in bps
+    pub worst_price_tolerance_bps: u64,
+    /// Slippage requested from each venue for its on-chain minimum
+    pub leg_slippage_bps: u64,
+}
+
+impl Default for OrderRouterConfig {
+    fn default() -> Self {
+        Self {
+            split_parts: 4,
+            min_split_improvement_bps: 5,
+            confidence_sigma: 2,
+            worst_price_tolerance_bps: 50,
+            leg_slippage_bps: 50,
+        }
+    }
+}
+
+/// Oracle price and decimals of one side of the swap
+#[derive(Clone, Copy, Debug)]
+pub struct OracleLeg {
+    pub price: OraclePrice,
+    pub decimals: u8,
+}
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum RouterError {
+    #[error("oracle math failed: {0}")]
+    Oracle(String),
+
+    #[error("confidence interval reaches zero; no worst-price bound")]
+    ConfidenceTooWide,
+
+    #[error("best route delivers {amount_out}, below the worst-price bound {bound}")]
+    WorstPriceExceeded { amount_out: u64, bound: u64 },
+
+    #[error(transparent)]
+    Venue(#[from] VenueError),
+}
+
+fn oracle_error(e: impl std::fmt::Display) -> RouterError {
+    RouterError::Oracle(e.to_string())
+}
+
+// ============================================================================
+// PLAN
+// ============================================================================
+
+/// One venue's share of the order
+#[derive(Clone, Debug)]
+pub struct RouteLeg {
+    pub quote: VenueQuote,
+    /// Pro-rata share of the worst-price bound; the quote's on-chain
+    /// minimum is at least this
+    pub bound: u64,
+    /// Shortfall against the pro-rata fair output, in bps
+    pub oracle_slippage_bps: i64,
+}
+
+#[derive(Debug)]
+pub struct RoutePlan {
+    pub amount_in: u64,
+    /// Output at the oracle prices
+    pub fair_amount_out: u64,
+    /// Output at the confidence-adjusted prices, less the tolerance
+    pub worst_amount_out: u64,
+    pub legs: Vec<RouteLeg>,
+    /// Quoted output of all legs
+    pub amount_out: u64,
+    /// Sum of the legs' on-chain minimums
+    pub min_amount_out: u64,
+    /// Shortfall of `amount_out` against the fair output, in bps
+    pub oracle_slippage_bps: i64,
+    pub rejected: Vec<RejectedQuote>,
+}
+
+impl RoutePlan {
+    pub fn is_split(&self) -> bool {
+        self.legs.len() > 1
+    }
+}
+
+// ============================================================================
+// ROUTER
+// ============================================================================
+
+pub struct SmartOrderRouter {
+    venues: VenueRouter,
+    pub config: OrderRouterConfig,
+}
+
+/// Outputs for 1..=parts parts of the order at one venue
+struct VenueCurve {
+    /// Index k-1 holds the quote for k parts, if the venue quoted it
+    quotes: Vec<Option<VenueQuote>>,
+}
+
+impl VenueCurve {
+    fn amount_out(&self, parts: usize) -> Option<u64> {
+        if parts == 0 {
+            return Some(0);
+        }
+        self.quotes[parts - 1].as_ref().map(|q| q.amount_out)
+    }
+}
+
+impl SmartOrderRouter {
+    pub fn new(venues: VenueRouter, config: OrderRouterConfig) -> Self {
+        Self { venues, config }
+    }
+
+    /// Output at the oracle prices, and the confidence-adjusted worst bound
+    pub fn fair_and_worst_out(
+        &self,
+        amount_in: u64,
+        input: &OracleLeg,
+        output: &OracleLeg,
+    ) -> Result<(u64, u64), RouterError> {
+        let convert = |input_price: i64, output_price: i64| -> Result<u64, RouterError> {
+            let usd = calculate_usd_value(amount_in, input.decimals, input_price, input.price.exponent, Rounding::Floor)
+                .map_err(oracle_error)?;
+            calculate_tokens_for_usd(usd, USD_DECIMALS, output.decimals, output_price, output.price.exponent, Rounding::Floor)
+                .map_err(oracle_error)
+        };
+
+        let fair = convert(input.price.price, output.price.price)?;
+
+        let sigma = self.config.confidence_sigma as i64;
+        let input_low = input.price.price.saturating_sub(input.price.conf as i64 * sigma);
+        let output_high = output.price.price.saturating_add(output.price.conf as i64 * sigma);
+        if input_low <= 0 {
+            return Err(RouterError::ConfidenceTooWide);
+        }
+        let worst = convert(input_low, output_high)?;
+        let worst = (worst as u128 * BPS_DENOMINATOR.saturating_sub(self.config.worst_price_tolerance_bps) as u128
+            / BPS_DENOMINATOR as u128) as u64;
+
+        Ok((fair, worst))
+    }
+
+    /// Quote every venue, split if it pays, and check the plan against the
+    /// worst-price bound
+    pub async fn plan(
+        &self,
+        request: &QuoteRequest,
+        input: &OracleLeg,
+        output: &OracleLeg,
+    ) -> Result<RoutePlan, RouterError> {
+        let (fair, worst) = self.fair_and_worst_out(request.amount_in, input, output)?;
+        let parts = self.config.split_parts.clamp(1, request.amount_in.max(1)) as usize;
+        let part_size = request.amount_in / parts as u64;
+        let size = |k: usize| if k == parts { request.amount_in } else { part_size * k as u64 };
+        let mut rejected = Vec::new();
+
+        let leg_request = |amount_in: u64| QuoteRequest {
+            amount_in,
+            slippage_bps: self.config.leg_slippage_bps,
+            ..request.clone()
+        };
+        let bound_for = |amount_in: u64| {
+            (worst as u128 * amount_in as u128 / request.amount_in.max(1) as u128) as u64
+        };
+
+        // Quote every venue at every size, concurrently
+        let venues: Vec<&dyn Venue> = self.venues.venues().collect();
+        let requests: Vec<(usize, usize)> =
+            (0..venues.len()).flat_map(|v| (1..=parts).map(move |k| (v, k))).collect();
+        let results = futures::future::join_all(requests.iter().map(|&(v, k)| {
+            let (venue, request) = (venues[v], leg_request(size(k)));
+            async move { venue.quote(&request).await }
+        }))
+        .await;
+
+        let mut curves: Vec<VenueCurve> = venues
+            .iter()
+            .map(|_| VenueCurve {
+                quotes: vec![None; parts],
+            })
+            .collect();
+        for (&(v, k), result) in requests.iter().zip(results) {
+            match result {
+                // A leg whose on-chain minimum is below its share of the
+                // bound could fill below it
+                Ok(quote) if quote.min_amount_out < bound_for(quote.amount_in) => {
+                    rejected.push(RejectedQuote {
+                        venue: quote.venue.clone(),
+                        reason: format!("{} of {} parts: minimum below worst-price bound", k, parts),
+                    });
+                }
+                Ok(quote) => curves[v].quotes[k - 1] = Some(quote),
+                Err(e) if k == parts => rejected.push(RejectedQuote {
+                    venue: venues[v].name().to_string(),
+                    reason: e.to_string(),
+                }),
+                Err(_) => {}
+            }
+        }
+
+        // Best single venue
+        let single = curves
+            .iter()
+            .enumerate()
+            .filter_map(|(v, curve)| curve.amount_out(parts).map(|out| (out, v)))
+            .max();
+
+        // Greedy split by marginal output
+        let mut allocation = vec![0usize; curves.len()];
+        let mut split_out = 0u64;
+        for _ in 0..parts {
+            let next = curves
+                .iter()
+                .enumerate()
+                .filter_map(|(v, curve)| {
+                    let current = curve.amount_out(allocation[v])?;
+                    let with_part = curve.amount_out(allocation[v] + 1)?;
+                    Some((with_part.saturating_sub(current), v))
+                })
+                .max();
+            match next {
+                Some((marginal, v)) => {
+                    allocation[v] += 1;
+                    split_out += marginal;
+                }
+                None => break,
+            }
+        }
+        let split_complete = allocation.iter().sum::<usize>() == parts;
+        let improvement_bps = match single {
+            Some((single_out, _)) if fair > 0 => {
+                (split_out as i128 - single_out as i128) * BPS_DENOMINATOR as i128 / fair as i128
+            }
+            _ => i128::MAX,
+        };
+        let use_split = split_complete
+            && allocation.iter().filter(|&&k| k > 0).count() > 1
+            && improvement_bps >= self.config.min_split_improvement_bps as i128;
+
+        let mut legs: Vec<VenueQuote> = if use_split {
+            curves
+                .iter()
+                .zip(&allocation)
+                .filter(|(_, &k)| k > 0)
+                .map(|(curve, &k)| curve.quotes[k - 1].clone().expect("allocated parts were quoted"))
+                .collect()
+        } else {
+            let (_, v) = single.ok_or(VenueError::NoQuotes)?;
+            vec![curves[v].quotes[parts - 1].clone().expect("single venue quoted the full size")]
+        };
+
+        // The split covers `part_size * parts`; give the remainder to the
+        // largest leg
+        let routed: u64 = legs.iter().map(|q| q.amount_in).sum();
+        if routed < request.amount_in {
+            let largest = (0..legs.len()).max_by_key(|&i| legs[i].amount_in).expect("at least one leg");
+            let venue = self
+                .venues
+                .venue(&legs[largest].venue)
+                .expect("legs come from registered venues");
+            let quote = venue
+                .quote(&leg_request(legs[largest].amount_in + request.amount_in - routed))
+                .await?;
+            if quote.min_amount_out < bound_for(quote.amount_in) {
+                return Err(RouterError::WorstPriceExceeded {
+                    amount_out: quote.min_amount_out,
+                    bound: bound_for(quote.amount_in),
+                });
+            }
+            legs[largest] = quote;
+        }
+
+        let legs: Vec<RouteLeg> = legs
+            .into_iter()
+            .map(|quote| {
+                let leg_fair = (fair as u128 * quote.amount_in as u128 / request.amount_in as u128) as u64;
+                RouteLeg {
+                    bound: bound_for(quote.amount_in),
+                    oracle_slippage_bps: quote.oracle_slippage_bps(leg_fair),
+                    quote,
+                }
+            })
+            .collect();
+        let amount_out: u64 = legs.iter().map(|l| l.quote.amount_out).sum();
+        let min_amount_out: u64 = legs.iter().map(|l| l.quote.min_amount_out).sum();
+        if amount_out < worst {
+            return Err(RouterError::WorstPriceExceeded {
+                amount_out,
+                bound: worst,
+            });
+        }
+
+        Ok(RoutePlan {
+            amount_in: request.amount_in,
+            fair_amount_out: fair,
+            worst_amount_out: worst,
+            oracle_slippage_bps: slippage_bps(fair, amount_out),
+            amount_out,
+            min_amount_out,
+            legs,
+            rejected,
+        })
+    }
+
+    /// Execute every leg, largest first, and report each result by venue.
+    /// Legs are independent transactions; a failed leg does not undo the
+    /// others.
+    pub async fn execute(
+        &self,
+        plan: &RoutePlan,
+        rpc: &RpcClient,
+        signer: &Keypair,
+        now: i64,
+    ) -> BTreeMap<String, VenueResult<Signature>> {
+        let mut legs: Vec<&RouteLeg> = plan.legs.iter().collect();
+        legs.sort_by_key(|leg| std::cmp::Reverse(leg.quote.amount_in));
+
+        let mut results = BTreeMap::new();
+        for leg in legs {
+            let result = match self.venues.venue(&leg.quote.venue) {
+                Some(venue) => venue.execute(&leg.quote, rpc, signer, now).await,
+                None => Err(VenueError::WrongVenue {
+                    venue: leg.quote.venue.clone(),
+                }),
+            };
+            results.insert(leg.quote.venue.clone(), result);
+        }
+        results
+    }
+}
+
+fn slippage_bps(fair: u64, amount_out: u64) -> i64 {
+    if fair == 0 {
+        return 0;
+    }
+    ((fair as i128 - amount_out as i128) * BPS_DENOMINATOR as i128 / fair as i128) as i64
+}
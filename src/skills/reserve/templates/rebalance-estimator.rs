@@ -0,0 +1,253 @@
+/**
+ * Rebalance Cost/Benefit Estimator
+ *
+ * Before a rebalance action from the reserve policy engine is executed,
+ * weigh the tracking-error reduction it buys against what it costs to
+ * execute (venue fees, expected spread versus oracle, priority fees).
+ * Every decision carries its full calculation so it can be written to the
+ * audit log verbatim.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    serde = { version = "1", features = ["derive"] }
+ *    serde_json = "1"
+ *
+ * 2. Copy next to reserve-policy.rs as `rebalance_estimator.rs`
+ */
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::reserve_policy::{Holding, RebalanceAction, ReservePolicy, BPS_DENOMINATOR};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Lamports per SOL
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+/// Default: benefit must at least cover cost
+pub const DEFAULT_MIN_BENEFIT_COST_RATIO_BPS: u64 = 10_000;
+
+// ============================================================================
+// INPUTS
+// ============================================================================
+
+/// Execution cost assumptions for one swap
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct ExecutionCostModel {
+    /// Venue / protocol fee in bps of notional
+    pub venue_fee_bps: u64,
+    /// Expected execution price versus oracle mid, in bps of notional
+    pub expected_spread_vs_oracle_bps: u64,
+    /// Priority fee paid per swap transaction (lamports)
+    pub priority_fee_lamports: u64,
+    /// Transactions needed per swap (e.g. post price + swap)
+    pub transactions_per_swap: u64,
+    /// SOL price in USD (6 decimals) to convert priority fees
+    pub sol_price_usd: u64,
+}
+
+/// How much being off-target is worth fixing
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct EstimatorConfig {
+    /// Expected cost of one USD of tracking error over the evaluation
+    /// horizon, in bps (e.g. 50 = mis-allocation costs 0.5% until the next
+    /// rebalance opportunity)
+    pub tracking_error_cost_bps: u64,
+    /// Benefit / cost ratio required to approve, in bps (10000 = 1x)
+    pub min_benefit_cost_ratio_bps: u64,
+}
+
+impl Default for EstimatorConfig {
+    fn default() -> Self {
+        Self {
+            tracking_error_cost_bps: 50,
+            min_benefit_cost_ratio_bps: DEFAULT_MIN_BENEFIT_COST_RATIO_BPS,
+        }
+    }
+}
+
+// ============================================================================
+// DECISION
+// ============================================================================
+
+/// Itemized execution cost (USD, 6 decimals)
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct CostBreakdown {
+    pub venue_fees_usd: u64,
+    pub spread_usd: u64,
+    pub priority_fees_usd: u64,
+    pub total_usd: u64,
+}
+
+/// Outcome of evaluating one rebalance action, suitable for the audit log
+#[derive(Clone, Debug, Serialize)]
+pub struct RebalanceDecision {
+    pub sell_asset: String,
+    pub buy_asset: String,
+    pub value_usd: u64,
+    /// Total USD away from target before the action
+    pub tracking_error_before_usd: u64,
+    /// Total USD away from target after the action
+    pub tracking_error_after_usd: u64,
+    pub benefit_usd: u64,
+    pub cost: CostBreakdown,
+    /// Benefit / cost in bps (u64::MAX when cost is zero)
+    pub benefit_cost_ratio_bps: u64,
+    pub approved: bool,
+    pub config: EstimatorConfig,
+    pub cost_model: ExecutionCostModel,
+}
+
+impl RebalanceDecision {
+    /// One-line description for the audit log
+    pub fn audit_description(&self) -> String {
+        format!(
+            "rebalance {} -> {} ${}: tracking error ${} -> ${}, benefit ${} vs cost ${} ({})",
+            self.sell_asset,
+            self.buy_asset,
+            self.value_usd / 1_000_000,
+            self.tracking_error_before_usd / 1_000_000,
+            self.tracking_error_after_usd / 1_000_000,
+            self.benefit_usd / 1_000_000,
+            self.cost.total_usd / 1_000_000,
+            if self.approved { "approved" } else { "rejected" }
+        )
+    }
+
+    /// Full calculation as JSON for the audit log entry body
+    pub fn audit_details(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+// ============================================================================
+// ESTIMATOR
+// ============================================================================
+
+fn mul_bps(value: u64, bps: u64) -> u64 {
+    ((value as u128 * bps as u128) / BPS_DENOMINATOR as u128) as u64
+}
+
+/// Sum of |value - target value| over all assets, halved so that moving
+/// X USD from an overweight to an underweight asset reduces it by X
+pub fn tracking_error_usd(
+    policy: &ReservePolicy,
+    values: &BTreeMap<String, u64>,
+    now: i64,
+) -> u64 {
+    let total: u64 = values.values().sum();
+    let deviation: u128 = policy
+        .effective_targets(now)
+        .iter()
+        .map(|target| {
+            let value = values.get(&target.asset).copied().unwrap_or(0) as i128;
+            let target_value = mul_bps(total, target.target_bps as u64) as i128;
+            (value - target_value).unsigned_abs()
+        })
+        .sum();
+    (deviation / 2) as u64
+}
+
+/// Itemized cost of executing a swap of `value_usd`
+pub fn estimate_cost(value_usd: u64, model: &ExecutionCostModel) -> CostBreakdown {
+    let venue_fees_usd = mul_bps(value_usd, model.venue_fee_bps);
+    let spread_usd = mul_bps(value_usd, model.expected_spread_vs_oracle_bps);
+    let priority_fees_usd = ((model.priority_fee_lamports as u128
+        * model.transactions_per_swap as u128
+        * model.sol_price_usd as u128)
+        / LAMPORTS_PER_SOL as u128) as u64;
+
+    CostBreakdown {
+        venue_fees_usd,
+        spread_usd,
+        priority_fees_usd,
+        total_usd: venue_fees_usd + spread_usd + priority_fees_usd,
+    }
+}
+
+/// Evaluate a single action against current holdings
+pub fn evaluate_action(
+    policy: &ReservePolicy,
+    holdings: &[Holding],
+    action: &RebalanceAction,
+    config: &EstimatorConfig,
+    cost_model: &ExecutionCostModel,
+    now: i64,
+) -> RebalanceDecision {
+    let mut values: BTreeMap<String, u64> = BTreeMap::new();
+    for holding in holdings {
+        *values.entry(holding.asset.clone()).or_default() += holding.value_usd;
+    }
+    let before = tracking_error_usd(policy, &values, now);
+
+    // The buy side receives value net of execution cost
+    let cost = estimate_cost(action.value_usd, cost_model);
+    let sell = values.entry(action.sell_asset.clone()).or_default();
+    *sell = sell.saturating_sub(action.value_usd);
+    *values.entry(action.buy_asset.clone()).or_default() +=
+        action.value_usd.saturating_sub(cost.total_usd);
+    let after = tracking_error_usd(policy, &values, now);
+
+    let benefit_usd = mul_bps(before.saturating_sub(after), config.tracking_error_cost_bps);
+    let benefit_cost_ratio_bps = if cost.total_usd == 0 {
+        u64::MAX
+    } else {
+        ((benefit_usd as u128 * BPS_DENOMINATOR as u128) / cost.total_usd as u128)
+            .min(u64::MAX as u128) as u64
+    };
+
+    RebalanceDecision {
+        sell_asset: action.sell_asset.clone(),
+        buy_asset: action.buy_asset.clone(),
+        value_usd: action.value_usd,
+        tracking_error_before_usd: before,
+        tracking_error_after_usd: after,
+        benefit_usd,
+        cost,
+        benefit_cost_ratio_bps,
+        approved: after < before && benefit_cost_ratio_bps >= config.min_benefit_cost_ratio_bps,
+        config: *config,
+        cost_model: *cost_model,
+    }
+}
+
+/// Evaluate every action in order, applying approved ones to the running
+/// holdings so later actions are judged against the updated portfolio
+pub fn evaluate_plan(
+    policy: &ReservePolicy,
+    holdings: &[Holding],
+    actions: &[RebalanceAction],
+    config: &EstimatorConfig,
+    cost_model: &ExecutionCostModel,
+    now: i64,
+) -> Vec<RebalanceDecision> {
+    let mut values: BTreeMap<String, u64> = BTreeMap::new();
+    for holding in holdings {
+        *values.entry(holding.asset.clone()).or_default() += holding.value_usd;
+    }
+    let mut decisions = Vec::with_capacity(actions.len());
+
+    for action in actions {
+        let current: Vec<Holding> = values
+            .iter()
+            .map(|(asset, value_usd)| Holding {
+                asset: asset.clone(),
+                value_usd: *value_usd,
+            })
+            .collect();
+        let decision = evaluate_action(policy, &current, action, config, cost_model, now);
+        if decision.approved {
+            let sell = values.entry(action.sell_asset.clone()).or_default();
+            *sell = sell.saturating_sub(action.value_usd);
+            *values.entry(action.buy_asset.clone()).or_default() +=
+                action.value_usd.saturating_sub(decision.cost.total_usd);
+        }
+        decisions.push(decision);
+    }
+
+    decisions
+}
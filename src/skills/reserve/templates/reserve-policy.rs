@@ -0,0 +1,405 @@
+/**
+ * Reserve Policy Engine
+ *
+ * Operators declare target allocations with rebalance bands and optional
+ * time-based glide paths. Given current holdings, the engine emits the
+ * minimal set of swaps that brings every asset back inside its band.
+ *
+ * Values are USD with 6 decimals, matching `calculate_usd_value` in the
+ * Pyth anchor-oracle template.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    thiserror = "1"
+ *
+ * 2. Copy this file as `reserve_policy.rs` and declare `mod reserve_policy;`
+ */
+
+use std::collections::BTreeMap;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Basis points in 100%
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Default band around each target (2%)
+pub const DEFAULT_BAND_BPS: u16 = 200;
+
+/// Default smallest swap worth executing ($10)
+pub const DEFAULT_MIN_TRADE_USD: u64 = 10_000_000;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ReservePolicyError {
+    #[error("target allocations sum to {0} bps, expected 10000")]
+    TargetsDoNotSum(u64),
+
+    #[error("duplicate target for asset {0}")]
+    DuplicateAsset(String),
+
+    #[error("band of {band_bps} bps is wider than target {target_bps} bps for {asset}")]
+    BandTooWide {
+        asset: String,
+        target_bps: u16,
+        band_bps: u16,
+    },
+
+    #[error("glide path ends before it starts")]
+    InvalidGlidePath,
+
+    #[error("glide path references unknown asset {0}")]
+    UnknownGlideAsset(String),
+
+    #[error("holding for {0} has no policy target")]
+    UnknownHolding(String),
+}
+
+// ============================================================================
+// POLICY DEFINITION
+// ============================================================================
+
+/// Target allocation for one asset
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssetTarget {
+    /// Asset symbol (e.g. "SOL", "USDC")
+    pub asset: String,
+    /// Target share of the reserve in basis points
+    pub target_bps: u16,
+    /// Allowed drift either side of the target before rebalancing
+    pub band_bps: u16,
+}
+
+impl AssetTarget {
+    pub fn new(asset: &str, target_bps: u16) -> Self {
+        Self {
+            asset: asset.to_string(),
+            target_bps,
+            band_bps: DEFAULT_BAND_BPS.min(target_bps),
+        }
+    }
+
+    pub fn with_band(mut self, band_bps: u16) -> Self {
+        self.band_bps = band_bps;
+        self
+    }
+
+    pub fn lower_bps(&self) -> u16 {
+        self.target_bps.saturating_sub(self.band_bps)
+    }
+
+    pub fn upper_bps(&self) -> u16 {
+        self.target_bps.saturating_add(self.band_bps).min(BPS_DENOMINATOR as u16)
+    }
+}
+
+/// Linear move from the base targets to new targets over a time window
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GlidePath {
+    /// Unix timestamp when the glide starts
+    pub start_ts: i64,
+    /// Unix timestamp when the end targets are fully in effect
+    pub end_ts: i64,
+    /// Target bps per asset at `end_ts`
+    pub end_targets_bps: BTreeMap<String, u16>,
+}
+
+/// A complete reserve policy
+#[derive(Clone, Debug)]
+pub struct ReservePolicy {
+    pub targets: Vec<AssetTarget>,
+    pub glide_path: Option<GlidePath>,
+    /// Swaps smaller than this are dropped
+    pub min_trade_usd: u64,
+}
+
+impl ReservePolicy {
+    pub fn new(targets: Vec<AssetTarget>) -> Result<Self, ReservePolicyError> {
+        let policy = Self {
+            targets,
+            glide_path: None,
+            min_trade_usd: DEFAULT_MIN_TRADE_USD,
+        };
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    pub fn with_glide_path(mut self, glide_path: GlidePath) -> Result<Self, ReservePolicyError> {
+        self.glide_path = Some(glide_path);
+        self.validate()?;
+        Ok(self)
+    }
+
+    pub fn with_min_trade_usd(mut self, min_trade_usd: u64) -> Self {
+        self.min_trade_usd = min_trade_usd;
+        self
+    }
+
+    /// Check targets, bands and glide path for consistency
+    pub fn validate(&self) -> Result<(), ReservePolicyError> {
+        let mut seen = BTreeMap::new();
+        let mut total = 0u64;
+        for target in &self.targets {
+            if seen.insert(target.asset.clone(), ()).is_some() {
+                return Err(ReservePolicyError::DuplicateAsset(target.asset.clone()));
+            }
+            if target.band_bps > target.target_bps && target.target_bps > 0 {
+                return Err(ReservePolicyError::BandTooWide {
+                    asset: target.asset.clone(),
+                    target_bps: target.target_bps,
+                    band_bps: target.band_bps,
+                });
+            }
+            total += target.target_bps as u64;
+        }
+        if total != BPS_DENOMINATOR {
+            return Err(ReservePolicyError::TargetsDoNotSum(total));
+        }
+
+        if let Some(glide) = &self.glide_path {
+            if glide.end_ts <= glide.start_ts {
+                return Err(ReservePolicyError::InvalidGlidePath);
+            }
+            let mut end_total = 0u64;
+            for (asset, bps) in &glide.end_targets_bps {
+                if !seen.contains_key(asset) {
+                    return Err(ReservePolicyError::UnknownGlideAsset(asset.clone()));
+                }
+                end_total += *bps as u64;
+            }
+            if end_total != BPS_DENOMINATOR {
+                return Err(ReservePolicyError::TargetsDoNotSum(end_total));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Targets in effect at `now`, with the glide path applied
+    pub fn effective_targets(&self, now: i64) -> Vec<AssetTarget> {
+        let Some(glide) = &self.glide_path else {
+            return self.targets.clone();
+        };
+
+        let elapsed = (now - glide.start_ts).clamp(0, glide.end_ts - glide.start_ts) as i128;
+        let duration = (glide.end_ts - glide.start_ts) as i128;
+
+        let mut targets: Vec<AssetTarget> = self
+            .targets
+            .iter()
+            .map(|t| {
+                let start = t.target_bps as i128;
+                let end = glide.end_targets_bps.get(&t.asset).copied().unwrap_or(0) as i128;
+                let bps = start + (end - start) * elapsed / duration;
+                AssetTarget {
+                    asset: t.asset.clone(),
+                    target_bps: bps as u16,
+                    band_bps: t.band_bps.min(bps as u16),
+                }
+            })
+            .collect();
+
+        // Integer interpolation can leave a few bps unassigned; give them to
+        // the largest target so the allocation still sums to 100%
+        let assigned: u64 = targets.iter().map(|t| t.target_bps as u64).sum();
+        if let Some(largest) = targets.iter_mut().max_by_key(|t| t.target_bps) {
+            largest.target_bps =
+                (largest.target_bps as i64 + BPS_DENOMINATOR as i64 - assigned as i64) as u16;
+        }
+
+        targets
+    }
+}
+
+// ============================================================================
+// ENGINE
+// ============================================================================
+
+/// Current value held in one asset
+#[derive(Clone, Debug)]
+pub struct Holding {
+    pub asset: String,
+    /// USD value (6 decimals)
+    pub value_usd: u64,
+}
+
+/// A swap that moves value between two assets
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RebalanceAction {
+    pub sell_asset: String,
+    pub buy_asset: String,
+    /// USD value to move (6 decimals)
+    pub value_usd: u64,
+}
+
+/// Where one asset stands relative to its band
+#[derive(Clone, Debug)]
+pub struct AllocationStatus {
+    pub asset: String,
+    pub current_bps: u64,
+    pub target_bps: u16,
+    pub lower_bps: u16,
+    pub upper_bps: u16,
+    pub in_band: bool,
+}
+
+/// Output of one engine run
+#[derive(Clone, Debug)]
+pub struct RebalancePlan {
+    pub total_value_usd: u64,
+    pub statuses: Vec<AllocationStatus>,
+    pub actions: Vec<RebalanceAction>,
+}
+
+impl RebalancePlan {
+    pub fn is_in_policy(&self) -> bool {
+        self.statuses.iter().all(|s| s.in_band)
+    }
+}
+
+fn bps_of(value: u64, total: u64) -> u64 {
+    if total == 0 {
+        return 0;
+    }
+    ((value as u128 * BPS_DENOMINATOR as u128) / total as u128) as u64
+}
+
+fn value_at_bps(total: u64, bps: u16) -> u64 {
+    ((total as u128 * bps as u128) / BPS_DENOMINATOR as u128) as u64
+}
+
+/// Compute the minimal swaps that bring every asset back inside its band
+///
+/// Out-of-band assets are only moved to the nearest band edge, not to the
+/// target, so the plan trades as little as possible. Any imbalance between
+/// required sells and buys is absorbed by in-band assets, starting with
+/// those furthest from target.
+pub fn plan_rebalance(
+    policy: &ReservePolicy,
+    holdings: &[Holding],
+    now: i64,
+) -> Result<RebalancePlan, ReservePolicyError> {
+    let targets = policy.effective_targets(now);
+
+    let mut values: BTreeMap<String, u64> = targets.iter().map(|t| (t.asset.clone(), 0)).collect();
+    for holding in holdings {
+        let entry = values
+            .get_mut(&holding.asset)
+            .ok_or_else(|| ReservePolicyError::UnknownHolding(holding.asset.clone()))?;
+        *entry = entry.saturating_add(holding.value_usd);
+    }
+    let total: u64 = values.values().sum();
+
+    let mut statuses = Vec::with_capacity(targets.len());
+    let mut sells: BTreeMap<String, u64> = BTreeMap::new();
+    let mut buys: BTreeMap<String, u64> = BTreeMap::new();
+    // (asset, room to sell down to lower band, room to buy up to upper band, distance from target)
+    let mut slack: Vec<(String, u64, u64, i128)> = Vec::new();
+
+    for target in &targets {
+        let value = values[&target.asset];
+        let current_bps = bps_of(value, total);
+        let lower = value_at_bps(total, target.lower_bps());
+        let upper = value_at_bps(total, target.upper_bps());
+        let in_band = value >= lower && value <= upper;
+
+        if value > upper {
+            sells.insert(target.asset.clone(), value - upper);
+        } else if value < lower {
+            buys.insert(target.asset.clone(), lower - value);
+        } else {
+            let distance = value as i128 - value_at_bps(total, target.target_bps) as i128;
+            slack.push((target.asset.clone(), value - lower, upper - value, distance));
+        }
+
+        statuses.push(AllocationStatus {
+            asset: target.asset.clone(),
+            current_bps,
+            target_bps: target.target_bps,
+            lower_bps: target.lower_bps(),
+            upper_bps: target.upper_bps(),
+            in_band,
+        });
+    }
+
+    let total_sell: u64 = sells.values().sum();
+    let total_buy: u64 = buys.values().sum();
+
+    if total_sell > total_buy {
+        // Extra proceeds go to the most underweight in-band assets first
+        slack.sort_by_key(|(_, _, _, distance)| *distance);
+        let mut remaining = total_sell - total_buy;
+        for (asset, _, room_up, _) in &slack {
+            if remaining == 0 {
+                break;
+            }
+            let amount = remaining.min(*room_up);
+            if amount > 0 {
+                *buys.entry(asset.clone()).or_default() += amount;
+                remaining -= amount;
+            }
+        }
+    } else if total_buy > total_sell {
+        // Missing funding comes from the most overweight in-band assets first
+        slack.sort_by_key(|(_, _, _, distance)| std::cmp::Reverse(*distance));
+        let mut remaining = total_buy - total_sell;
+        for (asset, room_down, _, _) in &slack {
+            if remaining == 0 {
+                break;
+            }
+            let amount = remaining.min(*room_down);
+            if amount > 0 {
+                *sells.entry(asset.clone()).or_default() += amount;
+                remaining -= amount;
+            }
+        }
+    }
+
+    let actions = match_legs(sells, buys, policy.min_trade_usd);
+
+    Ok(RebalancePlan {
+        total_value_usd: total,
+        statuses,
+        actions,
+    })
+}
+
+/// Pair sell legs with buy legs, largest first, to minimize swap count
+fn match_legs(
+    sells: BTreeMap<String, u64>,
+    buys: BTreeMap<String, u64>,
+    min_trade_usd: u64,
+) -> Vec<RebalanceAction> {
+    let mut sells: Vec<(String, u64)> = sells.into_iter().filter(|(_, v)| *v > 0).collect();
+    let mut buys: Vec<(String, u64)> = buys.into_iter().filter(|(_, v)| *v > 0).collect();
+    sells.sort_by(|a, b| b.1.cmp(&a.1));
+    buys.sort_by(|a, b| b.1.cmp(&a.1));
+
+    let mut actions = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < sells.len() && j < buys.len() {
+        let amount = sells[i].1.min(buys[j].1);
+        if amount >= min_trade_usd {
+            actions.push(RebalanceAction {
+                sell_asset: sells[i].0.clone(),
+                buy_asset: buys[j].0.clone(),
+                value_usd: amount,
+            });
+        }
+        sells[i].1 -= amount;
+        buys[j].1 -= amount;
+        if sells[i].1 == 0 {
+            i += 1;
+        }
+        if buys[j].1 == 0 {
+            j += 1;
+        }
+    }
+
+    actions
+}
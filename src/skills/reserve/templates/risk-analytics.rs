@@ -0,0 +1,372 @@
+/**
+ * Reserve Risk Analytics - Value-at-Risk and Expected Shortfall
+ *
+ * Computes parametric (variance-covariance) and historical VaR/ES for the
+ * reserve from recorded price history, renders a summary for scheduled
+ * reports, and checks the results against configured risk limits.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    thiserror = "1"
+ *
+ * 2. Copy this file as `risk_analytics.rs` and declare `mod risk_analytics;`
+ * 3. Feed it one price series per asset, sampled at the same timestamps
+ */
+
+use std::collections::BTreeMap;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Default confidence level (99%)
+pub const DEFAULT_CONFIDENCE: f64 = 0.99;
+
+/// Default horizon in sampling periods (e.g. 1 day of daily samples)
+pub const DEFAULT_HORIZON_PERIODS: u32 = 1;
+
+/// Minimum returns required for a meaningful estimate
+pub const MIN_OBSERVATIONS: usize = 30;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum RiskAnalyticsError {
+    #[error("no price history for asset {0}")]
+    MissingHistory(String),
+
+    #[error("price history for {asset} has {len} samples, expected {expected}")]
+    MisalignedHistory {
+        asset: String,
+        len: usize,
+        expected: usize,
+    },
+
+    #[error("need at least {MIN_OBSERVATIONS} returns, got {0}")]
+    InsufficientHistory(usize),
+
+    #[error("confidence must be in (0.5, 1), got {0}")]
+    InvalidConfidence(f64),
+
+    #[error("portfolio has no value")]
+    EmptyPortfolio,
+}
+
+// ============================================================================
+// INPUTS
+// ============================================================================
+
+/// Price samples for one asset, oldest first
+#[derive(Clone, Debug)]
+pub struct PriceHistory {
+    pub asset: String,
+    pub prices: Vec<f64>,
+}
+
+/// Portfolio exposure to one asset
+#[derive(Clone, Debug)]
+pub struct Exposure {
+    pub asset: String,
+    /// Current USD value held
+    pub value_usd: f64,
+}
+
+/// Parameters for a VaR/ES run
+#[derive(Clone, Copy, Debug)]
+pub struct RiskConfig {
+    /// Confidence level (e.g. 0.99)
+    pub confidence: f64,
+    /// Horizon in sampling periods; parametric results scale by sqrt
+    pub horizon_periods: u32,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            confidence: DEFAULT_CONFIDENCE,
+            horizon_periods: DEFAULT_HORIZON_PERIODS,
+        }
+    }
+}
+
+// ============================================================================
+// RESULTS
+// ============================================================================
+
+/// VaR and ES as positive USD losses
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RiskMeasure {
+    pub var_usd: f64,
+    pub es_usd: f64,
+}
+
+/// Full risk report for the reserve
+#[derive(Clone, Debug)]
+pub struct RiskReport {
+    pub portfolio_value_usd: f64,
+    pub confidence: f64,
+    pub horizon_periods: u32,
+    pub observations: usize,
+    pub parametric: RiskMeasure,
+    pub historical: RiskMeasure,
+    /// Standalone parametric VaR per asset
+    pub asset_var_usd: BTreeMap<String, f64>,
+}
+
+impl RiskReport {
+    /// The more conservative of the two methods
+    pub fn worst(&self) -> RiskMeasure {
+        RiskMeasure {
+            var_usd: self.parametric.var_usd.max(self.historical.var_usd),
+            es_usd: self.parametric.es_usd.max(self.historical.es_usd),
+        }
+    }
+
+    /// Plain-text summary for scheduled reports
+    pub fn summary(&self) -> String {
+        let pct = |usd: f64| usd / self.portfolio_value_usd * 100.0;
+        let mut out = format!(
+            "Reserve risk ({:.1}% confidence, {} period horizon, {} observations)\n\
+             Portfolio value: ${:.2}\n\
+             Parametric VaR: ${:.2} ({:.2}%)  ES: ${:.2} ({:.2}%)\n\
+             Historical VaR: ${:.2} ({:.2}%)  ES: ${:.2} ({:.2}%)\n",
+            self.confidence * 100.0,
+            self.horizon_periods,
+            self.observations,
+            self.portfolio_value_usd,
+            self.parametric.var_usd,
+            pct(self.parametric.var_usd),
+            self.parametric.es_usd,
+            pct(self.parametric.es_usd),
+            self.historical.var_usd,
+            pct(self.historical.var_usd),
+            self.historical.es_usd,
+            pct(self.historical.es_usd),
+        );
+        for (asset, var) in &self.asset_var_usd {
+            out.push_str(&format!("  {} standalone VaR: ${:.2}\n", asset, var));
+        }
+        out
+    }
+}
+
+// ============================================================================
+// LIMITS
+// ============================================================================
+
+/// Risk limits as a share of portfolio value
+#[derive(Clone, Copy, Debug)]
+pub struct RiskLimits {
+    /// Maximum VaR in basis points of portfolio value
+    pub max_var_bps: u64,
+    /// Maximum ES in basis points of portfolio value
+    pub max_es_bps: u64,
+}
+
+/// A limit that the current portfolio exceeds
+#[derive(Clone, Debug, PartialEq)]
+pub enum LimitBreach {
+    VaR { actual_bps: u64, limit_bps: u64 },
+    ExpectedShortfall { actual_bps: u64, limit_bps: u64 },
+}
+
+impl RiskLimits {
+    /// Check the conservative measure of a report against the limits
+    pub fn check(&self, report: &RiskReport) -> Vec<LimitBreach> {
+        let worst = report.worst();
+        let to_bps = |usd: f64| (usd / report.portfolio_value_usd * 10_000.0).round() as u64;
+        let mut breaches = Vec::new();
+
+        let var_bps = to_bps(worst.var_usd);
+        if var_bps > self.max_var_bps {
+            breaches.push(LimitBreach::VaR {
+                actual_bps: var_bps,
+                limit_bps: self.max_var_bps,
+            });
+        }
+
+        let es_bps = to_bps(worst.es_usd);
+        if es_bps > self.max_es_bps {
+            breaches.push(LimitBreach::ExpectedShortfall {
+                actual_bps: es_bps,
+                limit_bps: self.max_es_bps,
+            });
+        }
+
+        breaches
+    }
+}
+
+// ============================================================================
+// ENGINE
+// ============================================================================
+
+fn simple_returns(prices: &[f64]) -> Vec<f64> {
+    prices
+        .windows(2)
+        .map(|w| if w[0] > 0.0 { w[1] / w[0] - 1.0 } else { 0.0 })
+        .collect()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn covariance(a: &[f64], b: &[f64]) -> f64 {
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - mean_a) * (y - mean_b))
+        .sum::<f64>()
+        / (a.len() - 1) as f64
+}
+
+/// Standard normal density
+fn normal_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+/// Inverse standard normal CDF (Acklam's rational approximation)
+pub fn inverse_normal_cdf(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e+01,
+        2.209460984245205e+02,
+        -2.759285104469687e+02,
+        1.383577518672690e+02,
+        -3.066479806614716e+01,
+        2.506628277459239e+00,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e+01,
+        1.615858368580409e+02,
+        -1.556989798598866e+02,
+        6.680131188771972e+01,
+        -1.328068155288572e+01,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-03,
+        -3.223964580411365e-01,
+        -2.400758277161838e+00,
+        -2.549732539343734e+00,
+        4.374664141464968e+00,
+        2.938163982698783e+00,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-03,
+        3.224671290700398e-01,
+        2.445134137142996e+00,
+        3.754408661907416e+00,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    if p < P_LOW {
+        let q = (-2.0 * p.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    } else if p <= 1.0 - P_LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        -inverse_normal_cdf(1.0 - p)
+    }
+}
+
+/// Compute parametric and historical VaR/ES for the given exposures
+pub fn compute_risk(
+    exposures: &[Exposure],
+    history: &[PriceHistory],
+    config: &RiskConfig,
+) -> Result<RiskReport, RiskAnalyticsError> {
+    if !(config.confidence > 0.5 && config.confidence < 1.0) {
+        return Err(RiskAnalyticsError::InvalidConfidence(config.confidence));
+    }
+
+    let portfolio_value_usd: f64 = exposures.iter().map(|e| e.value_usd).sum();
+    if portfolio_value_usd <= 0.0 {
+        return Err(RiskAnalyticsError::EmptyPortfolio);
+    }
+
+    // Align return series with exposures
+    let expected_len = history.first().map(|h| h.prices.len()).unwrap_or(0);
+    let mut returns: Vec<Vec<f64>> = Vec::with_capacity(exposures.len());
+    for exposure in exposures {
+        let series = history
+            .iter()
+            .find(|h| h.asset == exposure.asset)
+            .ok_or_else(|| RiskAnalyticsError::MissingHistory(exposure.asset.clone()))?;
+        if series.prices.len() != expected_len {
+            return Err(RiskAnalyticsError::MisalignedHistory {
+                asset: exposure.asset.clone(),
+                len: series.prices.len(),
+                expected: expected_len,
+            });
+        }
+        returns.push(simple_returns(&series.prices));
+    }
+
+    let observations = returns.first().map(Vec::len).unwrap_or(0);
+    if observations < MIN_OBSERVATIONS {
+        return Err(RiskAnalyticsError::InsufficientHistory(observations));
+    }
+
+    let horizon_scale = (config.horizon_periods as f64).sqrt();
+    let z = inverse_normal_cdf(config.confidence);
+    let es_factor = normal_pdf(z) / (1.0 - config.confidence);
+
+    // Parametric: portfolio sigma from the covariance matrix of returns
+    let mut variance_usd = 0.0;
+    for (i, a) in exposures.iter().enumerate() {
+        for (j, b) in exposures.iter().enumerate() {
+            variance_usd += a.value_usd * b.value_usd * covariance(&returns[i], &returns[j]);
+        }
+    }
+    let sigma_usd = variance_usd.max(0.0).sqrt() * horizon_scale;
+    let parametric = RiskMeasure {
+        var_usd: z * sigma_usd,
+        es_usd: es_factor * sigma_usd,
+    };
+
+    let asset_var_usd = exposures
+        .iter()
+        .zip(&returns)
+        .map(|(e, r)| {
+            let sigma = covariance(r, r).max(0.0).sqrt() * horizon_scale;
+            (e.asset.clone(), z * sigma * e.value_usd)
+        })
+        .collect();
+
+    // Historical: replay each period's returns on current exposures
+    let mut pnl: Vec<f64> = (0..observations)
+        .map(|t| {
+            exposures
+                .iter()
+                .zip(&returns)
+                .map(|(e, r)| e.value_usd * r[t])
+                .sum::<f64>()
+                * horizon_scale
+        })
+        .collect();
+    pnl.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let tail_len = (((1.0 - config.confidence) * observations as f64).ceil() as usize).max(1);
+    let tail = &pnl[..tail_len];
+    let historical = RiskMeasure {
+        var_usd: (-tail[tail_len - 1]).max(0.0),
+        es_usd: (-mean(tail)).max(0.0),
+    };
+
+    Ok(RiskReport {
+        portfolio_value_usd,
+        confidence: config.confidence,
+        horizon_periods: config.horizon_periods,
+        observations,
+        parametric,
+        historical,
+        asset_var_usd,
+    })
+}
@@ -0,0 +1,246 @@
+/**
+ * Epoch and Leader-Schedule Aware Send Timing
+ *
+ * Scheduler hints for latency-critical transactions (liquidations, price
+ * posts). Landing rates drop around epoch boundaries, when leader schedules
+ * and stake change, and vary a lot between leaders. The advisor holds
+ * transactions back near a boundary and prefers slots whose leader has
+ * landed our transactions reliably, without ever waiting longer than the
+ * caller's budget.
+ *
+ * Leader reliability is learned from our own send outcomes
+ * (`record_outcome`) and can be seeded with a list of known-good leaders.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    solana-client = "1.18"
+ *    solana-sdk = "1.18"
+ *
+ * 2. Copy this file as `send_timing.rs` and declare `mod send_timing;`
+ */
+
+use solana_client::client_error::ClientError;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeSet, HashMap};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Consecutive slots each leader produces
+pub const LEADER_SLOTS: u64 = 4;
+
+/// Slots to stay away from an epoch boundary on either side (~1 minute)
+pub const DEFAULT_EPOCH_GUARD_SLOTS: u64 = 150;
+
+/// Landing rate a learned leader needs to count as reliable
+pub const DEFAULT_MIN_LANDING_RATE: f64 = 0.9;
+
+/// Sends observed before a leader's landing rate is trusted
+pub const DEFAULT_MIN_SAMPLES: u32 = 10;
+
+/// Upcoming slots inspected when looking for a reliable leader
+pub const DEFAULT_LOOKAHEAD_SLOTS: u64 = 64;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// How long a transaction may be held back
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Urgency {
+    /// Liquidations: wait at most `critical_max_wait_slots`, ignore the
+    /// epoch guard once the budget is spent
+    Critical,
+    /// Price posts and routine keeper work
+    Normal,
+}
+
+#[derive(Clone, Debug)]
+pub struct TimingConfig {
+    pub epoch_guard_slots: u64,
+    pub min_landing_rate: f64,
+    pub min_samples: u32,
+    pub lookahead_slots: u64,
+    pub critical_max_wait_slots: u64,
+    pub normal_max_wait_slots: u64,
+    /// Leaders treated as reliable regardless of observed outcomes
+    pub trusted_leaders: BTreeSet<Pubkey>,
+}
+
+impl Default for TimingConfig {
+    fn default() -> Self {
+        Self {
+            epoch_guard_slots: DEFAULT_EPOCH_GUARD_SLOTS,
+            min_landing_rate: DEFAULT_MIN_LANDING_RATE,
+            min_samples: DEFAULT_MIN_SAMPLES,
+            lookahead_slots: DEFAULT_LOOKAHEAD_SLOTS,
+            critical_max_wait_slots: 8,
+            normal_max_wait_slots: 300,
+            trusted_leaders: BTreeSet::new(),
+        }
+    }
+}
+
+// ============================================================================
+// HINTS
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WaitReason {
+    EpochBoundary,
+    UnreliableLeader,
+}
+
+/// What the scheduler should do with a transaction right now
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SendHint {
+    /// Send immediately; `leader` is the expected leader if known
+    SendNow { leader: Option<Pubkey> },
+    /// Re-evaluate after `slots` slots
+    Wait { slots: u64, reason: WaitReason },
+}
+
+/// Chain position and upcoming leaders, refreshed by the caller
+#[derive(Clone, Debug)]
+pub struct SlotContext {
+    pub slot: u64,
+    /// Position of `slot` within its epoch
+    pub slot_index: u64,
+    pub slots_in_epoch: u64,
+    /// Leaders for `slot`, `slot + 1`, ...
+    pub upcoming_leaders: Vec<Pubkey>,
+}
+
+impl SlotContext {
+    pub async fn fetch(rpc: &RpcClient, lookahead_slots: u64) -> Result<Self, ClientError> {
+        let epoch = rpc.get_epoch_info().await?;
+        let upcoming_leaders = rpc
+            .get_slot_leaders(epoch.absolute_slot, lookahead_slots)
+            .await?;
+        Ok(Self {
+            slot: epoch.absolute_slot,
+            slot_index: epoch.slot_index,
+            slots_in_epoch: epoch.slots_in_epoch,
+            upcoming_leaders,
+        })
+    }
+
+    /// Slots to wait until clear of the epoch boundary guard (0 if clear)
+    fn epoch_guard_wait(&self, guard: u64) -> u64 {
+        let until_boundary = self.slots_in_epoch.saturating_sub(self.slot_index);
+        if until_boundary <= guard {
+            // Before the boundary: wait through it and the guard after it
+            until_boundary + guard
+        } else if self.slot_index < guard {
+            guard - self.slot_index
+        } else {
+            0
+        }
+    }
+}
+
+// ============================================================================
+// ADVISOR
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, Default)]
+struct LeaderRecord {
+    sent: u32,
+    landed: u32,
+}
+
+/// Produces send hints and learns leader reliability from outcomes
+#[derive(Clone, Debug, Default)]
+pub struct TimingAdvisor {
+    config: TimingConfig,
+    leaders: HashMap<Pubkey, LeaderRecord>,
+}
+
+impl TimingAdvisor {
+    pub fn new(config: TimingConfig) -> Self {
+        Self {
+            config,
+            leaders: HashMap::new(),
+        }
+    }
+
+    /// Record whether a transaction sent during `leader`'s slots landed
+    pub fn record_outcome(&mut self, leader: Pubkey, landed: bool) {
+        let record = self.leaders.entry(leader).or_default();
+        record.sent += 1;
+        if landed {
+            record.landed += 1;
+        }
+    }
+
+    /// Observed landing rate, once there are enough samples
+    pub fn landing_rate(&self, leader: &Pubkey) -> Option<f64> {
+        self.leaders
+            .get(leader)
+            .filter(|r| r.sent >= self.config.min_samples)
+            .map(|r| r.landed as f64 / r.sent as f64)
+    }
+
+    /// Trusted, or learned to land at least `min_landing_rate`. Leaders
+    /// without enough samples count as reliable so they get sampled.
+    pub fn is_reliable(&self, leader: &Pubkey) -> bool {
+        self.config.trusted_leaders.contains(leader)
+            || self
+                .landing_rate(leader)
+                .map_or(true, |rate| rate >= self.config.min_landing_rate)
+    }
+
+    pub fn hint(&self, context: &SlotContext, urgency: Urgency) -> SendHint {
+        let max_wait = match urgency {
+            Urgency::Critical => self.config.critical_max_wait_slots,
+            Urgency::Normal => self.config.normal_max_wait_slots,
+        };
+        let current_leader = context.upcoming_leaders.first().copied();
+
+        let guard_wait = context.epoch_guard_wait(self.config.epoch_guard_slots);
+        if guard_wait > 0 {
+            return if guard_wait <= max_wait || urgency == Urgency::Normal {
+                SendHint::Wait {
+                    slots: guard_wait.min(max_wait).max(1),
+                    reason: WaitReason::EpochBoundary,
+                }
+            } else {
+                // A critical transaction can't sit out the boundary
+                SendHint::SendNow {
+                    leader: current_leader,
+                }
+            };
+        }
+
+        // Transactions reach the current leader or the next one, so both
+        // the current slot's leader and the next leader window count
+        let window = (LEADER_SLOTS as usize).min(context.upcoming_leaders.len());
+        let reliable_now = context.upcoming_leaders[..window]
+            .iter()
+            .any(|leader| self.is_reliable(leader));
+        if reliable_now || context.upcoming_leaders.is_empty() {
+            return SendHint::SendNow {
+                leader: current_leader,
+            };
+        }
+
+        match context
+            .upcoming_leaders
+            .iter()
+            .take(max_wait as usize + 1)
+            .position(|leader| self.is_reliable(leader))
+        {
+            Some(offset) => SendHint::Wait {
+                slots: offset as u64,
+                reason: WaitReason::UnreliableLeader,
+            },
+            // No better leader within budget
+            None => SendHint::SendNow {
+                leader: current_leader,
+            },
+        }
+    }
+}
@@ -0,0 +1,318 @@
+/**
+ * Session Keys - Delegated Signing with Scoped Permissions
+ *
+ * Long-running keepers should not hold the treasury key. The primary signer
+ * issues a short-lived session key together with a signed grant that scopes
+ * what it may do: which programs it may invoke and how much USD value
+ * (priced with Pyth, via transaction-analyzer.rs) it may move per rolling
+ * 24 hours. The keeper signs only through `SessionSigner`, which verifies
+ * the grant and enforces the scope before every signature.
+ *
+ * On-chain, the session key holds no treasury authority except SPL token
+ * delegations created by `delegate_instructions`, which cap what it can
+ * ever move even if the keeper host is compromised. `revoke_instructions`
+ * ends the session early.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    serde = { version = "1", features = ["derive"] }
+ *    serde_json = "1"
+ *    solana-sdk = "1.18"
+ *    spl-token = "4"
+ *    thiserror = "1"
+ *
+ * 2. Copy next to transaction-analyzer.rs as `session_keys.rs`
+ */
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use std::collections::{BTreeSet, VecDeque};
+
+use crate::transaction_analyzer::TransactionAnalysis;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Rolling window for the USD spend limit
+pub const SPEND_WINDOW_SECS: i64 = 86_400;
+
+/// Longest session the issuer should grant (7 days)
+pub const MAX_SESSION_TTL_SECS: i64 = 7 * 86_400;
+
+/// Domain separator so grant signatures cannot be replayed as transactions
+const GRANT_DOMAIN: &[u8] = b"automaton-session-grant-v1";
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum SessionError {
+    #[error("session TTL {0}s exceeds the maximum of {MAX_SESSION_TTL_SECS}s")]
+    TtlTooLong(i64),
+
+    #[error("grant signature does not verify against issuer {0}")]
+    InvalidGrantSignature(Pubkey),
+
+    #[error("grant is for session key {expected}, signer is {actual}")]
+    WrongSessionKey { expected: Pubkey, actual: Pubkey },
+
+    #[error("session is not valid at {now} (valid {not_before}..{expires_at})")]
+    Expired {
+        now: i64,
+        not_before: i64,
+        expires_at: i64,
+    },
+
+    #[error("program {0} is outside the session scope")]
+    ProgramNotInScope(Pubkey),
+
+    #[error("transaction violates the policy envelope")]
+    PolicyBreach,
+
+    #[error("spending ${requested_usd:.2} would exceed ${limit_usd:.2}/day (${spent_usd:.2} used)")]
+    DailyLimitExceeded {
+        requested_usd: f64,
+        spent_usd: f64,
+        limit_usd: f64,
+    },
+}
+
+// ============================================================================
+// GRANT
+// ============================================================================
+
+/// What a session key may do
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SessionScope {
+    /// Programs the session may invoke (System / Compute Budget included
+    /// only if listed)
+    pub allowed_programs: BTreeSet<Pubkey>,
+    /// USD value the session may move per rolling 24 hours
+    pub max_usd_per_day: f64,
+}
+
+/// Grant issued by the primary signer
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SessionGrant {
+    pub issuer: Pubkey,
+    pub session_key: Pubkey,
+    pub scope: SessionScope,
+    pub not_before: i64,
+    pub expires_at: i64,
+    /// Issuer signature over `signing_bytes()`
+    pub signature: Signature,
+}
+
+impl SessionGrant {
+    /// Canonical bytes the issuer signs
+    fn signing_bytes(
+        issuer: &Pubkey,
+        session_key: &Pubkey,
+        scope: &SessionScope,
+        not_before: i64,
+        expires_at: i64,
+    ) -> Vec<u8> {
+        let mut bytes = GRANT_DOMAIN.to_vec();
+        bytes.extend_from_slice(issuer.as_ref());
+        bytes.extend_from_slice(session_key.as_ref());
+        bytes.extend_from_slice(&(scope.allowed_programs.len() as u32).to_le_bytes());
+        for program in &scope.allowed_programs {
+            bytes.extend_from_slice(program.as_ref());
+        }
+        bytes.extend_from_slice(&scope.max_usd_per_day.to_le_bytes());
+        bytes.extend_from_slice(&not_before.to_le_bytes());
+        bytes.extend_from_slice(&expires_at.to_le_bytes());
+        bytes
+    }
+
+    pub fn verify(&self) -> Result<(), SessionError> {
+        let message = Self::signing_bytes(
+            &self.issuer,
+            &self.session_key,
+            &self.scope,
+            self.not_before,
+            self.expires_at,
+        );
+        if self.signature.verify(self.issuer.as_ref(), &message) {
+            Ok(())
+        } else {
+            Err(SessionError::InvalidGrantSignature(self.issuer))
+        }
+    }
+
+    pub fn is_active(&self, now: i64) -> bool {
+        now >= self.not_before && now < self.expires_at
+    }
+}
+
+/// Create a session keypair and its signed grant. Hand both to the keeper;
+/// the primary key never leaves the issuer.
+pub fn issue_session(
+    primary: &Keypair,
+    scope: SessionScope,
+    now: i64,
+    ttl_secs: i64,
+) -> Result<(Keypair, SessionGrant), SessionError> {
+    if ttl_secs > MAX_SESSION_TTL_SECS {
+        return Err(SessionError::TtlTooLong(ttl_secs));
+    }
+
+    let session = Keypair::new();
+    let expires_at = now + ttl_secs;
+    let message = SessionGrant::signing_bytes(
+        &primary.pubkey(),
+        &session.pubkey(),
+        &scope,
+        now,
+        expires_at,
+    );
+    let grant = SessionGrant {
+        issuer: primary.pubkey(),
+        session_key: session.pubkey(),
+        scope,
+        not_before: now,
+        expires_at,
+        signature: primary.sign_message(&message),
+    };
+    Ok((session, grant))
+}
+
+// ============================================================================
+// ON-CHAIN DELEGATION
+// ============================================================================
+
+/// SPL token approvals letting the session key move at most `amount` from
+/// each of the issuer's token accounts
+pub fn delegate_instructions(
+    grant: &SessionGrant,
+    token_program: &Pubkey,
+    allowances: &[(Pubkey, u64)],
+) -> Result<Vec<Instruction>, solana_sdk::program_error::ProgramError> {
+    allowances
+        .iter()
+        .map(|(token_account, amount)| {
+            spl_token::instruction::approve(
+                token_program,
+                token_account,
+                &grant.session_key,
+                &grant.issuer,
+                &[],
+                *amount,
+            )
+        })
+        .collect()
+}
+
+/// Revoke the delegations from `delegate_instructions`
+pub fn revoke_instructions(
+    grant: &SessionGrant,
+    token_program: &Pubkey,
+    token_accounts: &[Pubkey],
+) -> Result<Vec<Instruction>, solana_sdk::program_error::ProgramError> {
+    token_accounts
+        .iter()
+        .map(|token_account| {
+            spl_token::instruction::revoke(token_program, token_account, &grant.issuer, &[])
+        })
+        .collect()
+}
+
+// ============================================================================
+// SIGNER
+// ============================================================================
+
+/// Keeper-side signer that only signs inside the grant's scope
+pub struct SessionSigner {
+    keypair: Keypair,
+    grant: SessionGrant,
+    /// (timestamp, usd) of signed transactions inside the window
+    spends: VecDeque<(i64, f64)>,
+}
+
+impl SessionSigner {
+    pub fn new(keypair: Keypair, grant: SessionGrant) -> Result<Self, SessionError> {
+        grant.verify()?;
+        if keypair.pubkey() != grant.session_key {
+            return Err(SessionError::WrongSessionKey {
+                expected: grant.session_key,
+                actual: keypair.pubkey(),
+            });
+        }
+        Ok(Self {
+            keypair,
+            grant,
+            spends: VecDeque::new(),
+        })
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    pub fn grant(&self) -> &SessionGrant {
+        &self.grant
+    }
+
+    /// USD signed for in the last 24 hours
+    pub fn spent_usd(&mut self, now: i64) -> f64 {
+        while let Some(&(ts, _)) = self.spends.front() {
+            if ts > now - SPEND_WINDOW_SECS {
+                break;
+            }
+            self.spends.pop_front();
+        }
+        self.spends.iter().map(|(_, usd)| usd).sum()
+    }
+
+    /// Check a transaction against the scope without signing it
+    pub fn authorize(&mut self, analysis: &TransactionAnalysis, now: i64) -> Result<(), SessionError> {
+        if !self.grant.is_active(now) {
+            return Err(SessionError::Expired {
+                now,
+                not_before: self.grant.not_before,
+                expires_at: self.grant.expires_at,
+            });
+        }
+        if !analysis.is_allowed() {
+            return Err(SessionError::PolicyBreach);
+        }
+        if let Some(program) = analysis
+            .programs
+            .iter()
+            .find(|p| !self.grant.scope.allowed_programs.contains(p))
+        {
+            return Err(SessionError::ProgramNotInScope(*program));
+        }
+
+        let spent_usd = self.spent_usd(now);
+        if spent_usd + analysis.total_value_usd > self.grant.scope.max_usd_per_day {
+            return Err(SessionError::DailyLimitExceeded {
+                requested_usd: analysis.total_value_usd,
+                spent_usd,
+                limit_usd: self.grant.scope.max_usd_per_day,
+            });
+        }
+        Ok(())
+    }
+
+    /// Authorize, sign, and record the spend. `analysis` must come from
+    /// `analyze_transaction` on this transaction's instructions.
+    pub fn sign(
+        &mut self,
+        transaction: &mut Transaction,
+        analysis: &TransactionAnalysis,
+        now: i64,
+    ) -> Result<(), SessionError> {
+        self.authorize(analysis, now)?;
+        let blockhash = transaction.message.recent_blockhash;
+        transaction.partial_sign(&[&self.keypair], blockhash);
+        self.spends.push_back((now, analysis.total_value_usd));
+        Ok(())
+    }
+}
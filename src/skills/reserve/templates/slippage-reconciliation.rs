@@ -0,0 +1,317 @@
+/**
+ * Slippage Reconciliation: Expected vs Realized Output
+ *
+ * Every swap the agent sends is registered with the output it expected
+ * from pre-trade oracle math. Once the transaction confirms, the job reads
+ * the realized output with fill-reconstruction.rs, records the shortfall
+ * in bps per venue, and summarizes it into per-venue statistics. Those
+ * statistics replace the guessed `expected_spread_vs_oracle_bps` in the
+ * rebalance estimator's cost model, so cost/benefit decisions use what
+ * each venue actually delivered.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    serde = { version = "1", features = ["derive"] }
+ *    serde_json = "1"
+ *    solana-client = "1.18"
+ *    solana-sdk = "1.18"
+ *
+ * 2. Copy next to fill-reconstruction.rs and rebalance-estimator.rs as
+ *    `slippage_reconciliation.rs`
+ */
+
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::fill_reconstruction::{fetch_parsed_transaction, FillError, ParsedTransaction};
+use crate::rebalance_estimator::ExecutionCostModel;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+const BPS_DENOMINATOR: i128 = 10_000;
+
+// ============================================================================
+// TRADES
+// ============================================================================
+
+/// A swap as the agent planned it
+#[derive(Clone, Debug, Serialize)]
+pub struct ExpectedTrade {
+    #[serde(serialize_with = "serialize_display")]
+    pub signature: Signature,
+    /// Venue or route label, e.g. "jupiter", "orca-whirlpool"
+    pub venue: String,
+    #[serde(serialize_with = "serialize_display")]
+    pub input_mint: Pubkey,
+    #[serde(serialize_with = "serialize_display")]
+    pub output_mint: Pubkey,
+    pub input_amount: u64,
+    /// Output from oracle prices alone, before fees and price impact
+    pub expected_output: u64,
+    /// Unix seconds the transaction was sent
+    pub sent_at: i64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Outcome {
+    /// Confirmed and reconstructed
+    Filled,
+    /// Landed but failed on chain
+    Failed,
+    /// Never confirmed within the pending window
+    Expired,
+    /// Confirmed, but the wallet's deltas did not match the planned mints
+    Mismatched,
+}
+
+/// One trade after reconciliation
+#[derive(Clone, Debug, Serialize)]
+pub struct Reconciliation {
+    pub trade: ExpectedTrade,
+    pub outcome: Outcome,
+    /// Realized output, scaled to the planned input when the realized
+    /// input differs
+    pub realized_output: Option<u64>,
+    /// Shortfall against the expected output; negative when the fill beat
+    /// the oracle
+    pub slippage_bps: Option<i64>,
+}
+
+fn serialize_display<T: std::fmt::Display, S: serde::Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+/// Compare a confirmed transaction with the plan
+pub fn reconcile(trade: ExpectedTrade, tx: &ParsedTransaction, owner: &Pubkey) -> Reconciliation {
+    let mismatched = |trade| Reconciliation {
+        trade,
+        outcome: Outcome::Mismatched,
+        realized_output: None,
+        slippage_bps: None,
+    };
+
+    if !tx.succeeded {
+        return Reconciliation {
+            trade,
+            outcome: Outcome::Failed,
+            realized_output: None,
+            slippage_bps: None,
+        };
+    }
+    let Ok(fill) = tx.fill(owner) else {
+        return mismatched(trade);
+    };
+    if fill.input_mint != trade.input_mint
+        || fill.output_mint != trade.output_mint
+        || fill.input_amount == 0
+        || trade.expected_output == 0
+    {
+        return mismatched(trade);
+    }
+
+    // Exact-out routes can spend less than planned; compare per unit of input
+    let realized = fill.output_amount as i128 * trade.input_amount as i128 / fill.input_amount as i128;
+    let expected = trade.expected_output as i128;
+    let slippage_bps = ((expected - realized) * BPS_DENOMINATOR / expected) as i64;
+
+    Reconciliation {
+        realized_output: u64::try_from(realized).ok(),
+        slippage_bps: Some(slippage_bps),
+        outcome: Outcome::Filled,
+        trade,
+    }
+}
+
+// ============================================================================
+// STATISTICS
+// ============================================================================
+
+/// Slippage summary for one venue
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct VenueSlippage {
+    pub filled: usize,
+    pub failed: usize,
+    pub expired: usize,
+    pub mismatched: usize,
+    pub mean_bps: Option<f64>,
+    pub p50_bps: Option<i64>,
+    pub p90_bps: Option<i64>,
+    pub max_bps: Option<i64>,
+}
+
+impl VenueSlippage {
+    fn from_samples(samples: &VecDeque<i64>, counts: &OutcomeCounts) -> Self {
+        let mut sorted: Vec<i64> = samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let percentile = |p: usize| (!sorted.is_empty()).then(|| sorted[(sorted.len() - 1) * p / 100]);
+        Self {
+            filled: counts.filled,
+            failed: counts.failed,
+            expired: counts.expired,
+            mismatched: counts.mismatched,
+            mean_bps: (!sorted.is_empty())
+                .then(|| sorted.iter().sum::<i64>() as f64 / sorted.len() as f64),
+            p50_bps: percentile(50),
+            p90_bps: percentile(90),
+            max_bps: sorted.last().copied(),
+        }
+    }
+
+    /// Cost model with the spread replaced by what this venue delivered.
+    /// Measured slippage includes the venue fee, which the model prices
+    /// separately, so the fee is subtracted. Returns `base` unchanged until
+    /// `min_samples` fills exist.
+    pub fn apply_to(&self, base: ExecutionCostModel, min_samples: usize) -> ExecutionCostModel {
+        match self.p90_bps {
+            Some(p90) if self.filled >= min_samples => ExecutionCostModel {
+                expected_spread_vs_oracle_bps: (p90 - base.venue_fee_bps as i64).max(0) as u64,
+                ..base
+            },
+            _ => base,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct OutcomeCounts {
+    filled: usize,
+    failed: usize,
+    expired: usize,
+    mismatched: usize,
+}
+
+// ============================================================================
+// JOB
+// ============================================================================
+
+#[derive(Clone, Debug)]
+pub struct ReconciliationConfig {
+    pub commitment: CommitmentConfig,
+    /// Trades still unconfirmed after this long are recorded as expired
+    pub max_pending_secs: i64,
+    /// Most recent fills kept per venue
+    pub window: usize,
+}
+
+impl Default for ReconciliationConfig {
+    fn default() -> Self {
+        Self {
+            commitment: CommitmentConfig::confirmed(),
+            max_pending_secs: 120,
+            window: 200,
+        }
+    }
+}
+
+/// Matches sent swaps with their confirmed transactions
+pub struct ReconciliationJob {
+    owner: Pubkey,
+    config: ReconciliationConfig,
+    pending: BTreeMap<Signature, ExpectedTrade>,
+    samples: BTreeMap<String, VecDeque<i64>>,
+    counts: BTreeMap<String, OutcomeCounts>,
+}
+
+impl ReconciliationJob {
+    pub fn new(owner: Pubkey, config: ReconciliationConfig) -> Self {
+        Self {
+            owner,
+            config,
+            pending: BTreeMap::new(),
+            samples: BTreeMap::new(),
+            counts: BTreeMap::new(),
+        }
+    }
+
+    /// Register a swap right after sending it
+    pub fn expect(&mut self, trade: ExpectedTrade) {
+        self.pending.insert(trade.signature, trade);
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Fold one reconciliation into the venue statistics
+    pub fn record(&mut self, reconciliation: &Reconciliation) {
+        let venue = &reconciliation.trade.venue;
+        let counts = self.counts.entry(venue.clone()).or_default();
+        match reconciliation.outcome {
+            Outcome::Filled => counts.filled += 1,
+            Outcome::Failed => counts.failed += 1,
+            Outcome::Expired => counts.expired += 1,
+            Outcome::Mismatched => counts.mismatched += 1,
+        }
+        if let Some(bps) = reconciliation.slippage_bps {
+            let samples = self.samples.entry(venue.clone()).or_default();
+            samples.push_back(bps);
+            while samples.len() > self.config.window {
+                samples.pop_front();
+            }
+        }
+    }
+
+    /// Look up every pending trade once; unconfirmed trades stay pending
+    /// until `max_pending_secs`
+    pub async fn run_once(&mut self, rpc: &RpcClient, now: i64) -> Vec<Reconciliation> {
+        let mut done = Vec::new();
+        let signatures: Vec<Signature> = self.pending.keys().copied().collect();
+        for signature in signatures {
+            let result = fetch_parsed_transaction(rpc, &signature, self.config.commitment).await;
+            let trade = &self.pending[&signature];
+            let reconciliation = match result {
+                Ok(tx) => reconcile(trade.clone(), &tx, &self.owner),
+                Err(FillError::Rpc(_)) if now - trade.sent_at <= self.config.max_pending_secs => continue,
+                Err(FillError::Rpc(_)) => Reconciliation {
+                    trade: trade.clone(),
+                    outcome: Outcome::Expired,
+                    realized_output: None,
+                    slippage_bps: None,
+                },
+                Err(_) => Reconciliation {
+                    trade: trade.clone(),
+                    outcome: Outcome::Mismatched,
+                    realized_output: None,
+                    slippage_bps: None,
+                },
+            };
+            self.pending.remove(&signature);
+            self.record(&reconciliation);
+            done.push(reconciliation);
+        }
+        done
+    }
+
+    pub fn venue_stats(&self, venue: &str) -> Option<VenueSlippage> {
+        let counts = self.counts.get(venue)?;
+        let empty = VecDeque::new();
+        Some(VenueSlippage::from_samples(
+            self.samples.get(venue).unwrap_or(&empty),
+            counts,
+        ))
+    }
+
+    /// Statistics for every venue seen
+    pub fn report(&self) -> BTreeMap<String, VenueSlippage> {
+        self.counts
+            .keys()
+            .filter_map(|venue| Some((venue.clone(), self.venue_stats(venue)?)))
+            .collect()
+    }
+
+    /// Cost model for `venue`, calibrated from its fills
+    pub fn cost_model(&self, venue: &str, base: ExecutionCostModel, min_samples: usize) -> ExecutionCostModel {
+        self.venue_stats(venue)
+            .map_or(base, |stats| stats.apply_to(base, min_samples))
+    }
+}
@@ -0,0 +1,318 @@
+/**
+ * Reserve Stress Testing
+ *
+ * Scenario engine that shocks prices (e.g. SOL -40%, stablecoin depeg 5%)
+ * through the reserve holdings and lending positions, then reports
+ * post-shock health factors, liquidation cascades and reserve shortfalls.
+ *
+ * Liquidations sell seized collateral into the market; that selling
+ * pushes prices further down according to each asset's market depth, which
+ * can trigger the next wave of liquidations. The engine iterates until no
+ * new position becomes liquidatable.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    serde = { version = "1", features = ["derive"] }
+ *
+ * 2. Copy this file as `stress_test.rs` and declare `mod stress_test;`
+ * 3. Run scenarios from the command line with examples/stress-test-cli.rs
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Upper bound on cascade rounds to guarantee termination
+pub const MAX_CASCADE_ROUNDS: usize = 20;
+
+/// Health factor below which a position is liquidatable
+pub const LIQUIDATION_HEALTH_FACTOR: f64 = 1.0;
+
+// ============================================================================
+// PORTFOLIO MODEL
+// ============================================================================
+
+/// Market data for one asset
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AssetMarket {
+    /// Current USD price
+    pub price: f64,
+    /// USD of selling that moves the price down 1%
+    pub depth_usd_per_pct: f64,
+}
+
+/// Reserve holding
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReserveHolding {
+    pub asset: String,
+    /// Token amount (UI units)
+    pub amount: f64,
+}
+
+/// Lending position (own or a counterparty the reserve is exposed to)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LendingPosition {
+    pub id: String,
+    pub collateral_asset: String,
+    pub collateral_amount: f64,
+    pub debt_asset: String,
+    pub debt_amount: f64,
+    /// Collateral weight at liquidation (e.g. 0.85)
+    pub liquidation_threshold: f64,
+    /// Bonus paid to liquidators on seized collateral (e.g. 0.05)
+    pub liquidation_bonus: f64,
+    /// Share of debt repaid per liquidation (e.g. 0.5)
+    pub close_factor: f64,
+}
+
+impl LendingPosition {
+    pub fn collateral_value(&self, prices: &BTreeMap<String, f64>) -> f64 {
+        self.collateral_amount * prices.get(&self.collateral_asset).copied().unwrap_or(0.0)
+    }
+
+    pub fn debt_value(&self, prices: &BTreeMap<String, f64>) -> f64 {
+        self.debt_amount * prices.get(&self.debt_asset).copied().unwrap_or(0.0)
+    }
+
+    pub fn health_factor(&self, prices: &BTreeMap<String, f64>) -> f64 {
+        let debt = self.debt_value(prices);
+        if debt == 0.0 {
+            return f64::INFINITY;
+        }
+        self.collateral_value(prices) * self.liquidation_threshold / debt
+    }
+}
+
+/// Everything the stress test runs against
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Portfolio {
+    pub markets: BTreeMap<String, AssetMarket>,
+    pub holdings: Vec<ReserveHolding>,
+    pub positions: Vec<LendingPosition>,
+    /// USD value the reserve must always cover (liabilities, runway)
+    pub required_reserve_usd: f64,
+}
+
+impl Portfolio {
+    pub fn prices(&self) -> BTreeMap<String, f64> {
+        self.markets
+            .iter()
+            .map(|(asset, market)| (asset.clone(), market.price))
+            .collect()
+    }
+
+    pub fn reserve_value(&self, prices: &BTreeMap<String, f64>) -> f64 {
+        self.holdings
+            .iter()
+            .map(|h| h.amount * prices.get(&h.asset).copied().unwrap_or(0.0))
+            .sum()
+    }
+}
+
+// ============================================================================
+// SCENARIOS
+// ============================================================================
+
+/// A set of instantaneous price shocks
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    /// Relative price change per asset (-0.40 = -40%)
+    pub shocks: BTreeMap<String, f64>,
+}
+
+impl Scenario {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            shocks: BTreeMap::new(),
+        }
+    }
+
+    pub fn shock(mut self, asset: &str, change: f64) -> Self {
+        self.shocks.insert(asset.to_string(), change);
+        self
+    }
+
+    /// SOL -40%
+    pub fn sol_crash() -> Self {
+        Self::new("sol-crash-40").shock("SOL", -0.40)
+    }
+
+    /// USDC trades at $0.95
+    pub fn stable_depeg() -> Self {
+        Self::new("usdc-depeg-5").shock("USDC", -0.05)
+    }
+
+    /// SOL -40% and LSTs -45% together
+    pub fn sol_and_lst_crash() -> Self {
+        Self::new("sol-lst-crash")
+            .shock("SOL", -0.40)
+            .shock("JitoSOL", -0.45)
+            .shock("mSOL", -0.45)
+    }
+
+    pub fn presets() -> Vec<Self> {
+        vec![Self::sol_crash(), Self::stable_depeg(), Self::sol_and_lst_crash()]
+    }
+}
+
+// ============================================================================
+// RESULTS
+// ============================================================================
+
+/// One liquidation triggered during the cascade
+#[derive(Clone, Debug, Serialize)]
+pub struct LiquidationEvent {
+    pub round: usize,
+    pub position_id: String,
+    pub health_factor: f64,
+    pub debt_repaid_usd: f64,
+    pub collateral_sold_usd: f64,
+}
+
+/// Post-shock state of one position
+#[derive(Clone, Debug, Serialize)]
+pub struct PositionOutcome {
+    pub position_id: String,
+    pub health_factor_before: f64,
+    pub health_factor_after: f64,
+    pub liquidations: usize,
+    /// Debt left uncovered by collateral (bad debt)
+    pub bad_debt_usd: f64,
+}
+
+/// Full report for one scenario
+#[derive(Clone, Debug, Serialize)]
+pub struct StressReport {
+    pub scenario: String,
+    pub final_prices: BTreeMap<String, f64>,
+    pub positions: Vec<PositionOutcome>,
+    pub liquidations: Vec<LiquidationEvent>,
+    pub cascade_rounds: usize,
+    pub reserve_value_before: f64,
+    pub reserve_value_after: f64,
+    /// Required reserve minus post-shock reserve, floored at zero
+    pub reserve_shortfall_usd: f64,
+    pub total_bad_debt_usd: f64,
+}
+
+impl StressReport {
+    pub fn passes(&self) -> bool {
+        self.reserve_shortfall_usd == 0.0 && self.total_bad_debt_usd == 0.0
+    }
+}
+
+// ============================================================================
+// ENGINE
+// ============================================================================
+
+/// Run one scenario through the portfolio
+pub fn run_scenario(portfolio: &Portfolio, scenario: &Scenario) -> StressReport {
+    let initial_prices = portfolio.prices();
+    let mut prices = initial_prices.clone();
+    for (asset, change) in &scenario.shocks {
+        if let Some(price) = prices.get_mut(asset) {
+            *price = (*price * (1.0 + change)).max(0.0);
+        }
+    }
+
+    let mut positions = portfolio.positions.clone();
+    let mut liquidation_counts = vec![0usize; positions.len()];
+    let mut liquidations = Vec::new();
+    let mut rounds = 0;
+
+    for round in 1..=MAX_CASCADE_ROUNDS {
+        let mut sold_usd: BTreeMap<String, f64> = BTreeMap::new();
+
+        for (i, position) in positions.iter_mut().enumerate() {
+            let health = position.health_factor(&prices);
+            if health >= LIQUIDATION_HEALTH_FACTOR || position.collateral_amount <= 0.0 {
+                continue;
+            }
+
+            let debt_price = prices.get(&position.debt_asset).copied().unwrap_or(0.0);
+            let collateral_price = prices.get(&position.collateral_asset).copied().unwrap_or(0.0);
+            if collateral_price <= 0.0 {
+                continue;
+            }
+
+            let repay_usd = position.debt_value(&prices) * position.close_factor;
+            let seize_usd = (repay_usd * (1.0 + position.liquidation_bonus))
+                .min(position.collateral_value(&prices));
+
+            position.collateral_amount -= seize_usd / collateral_price;
+            if debt_price > 0.0 {
+                position.debt_amount -= repay_usd / debt_price;
+            }
+
+            *sold_usd.entry(position.collateral_asset.clone()).or_default() += seize_usd;
+            liquidation_counts[i] += 1;
+            liquidations.push(LiquidationEvent {
+                round,
+                position_id: position.id.clone(),
+                health_factor: health,
+                debt_repaid_usd: repay_usd,
+                collateral_sold_usd: seize_usd,
+            });
+        }
+
+        if sold_usd.is_empty() {
+            break;
+        }
+        rounds = round;
+
+        // Liquidators dump seized collateral, moving prices by market depth
+        for (asset, usd) in sold_usd {
+            let (Some(price), Some(market)) = (prices.get_mut(&asset), portfolio.markets.get(&asset))
+            else {
+                continue;
+            };
+            if market.depth_usd_per_pct > 0.0 {
+                let drop_pct = usd / market.depth_usd_per_pct;
+                *price = (*price * (1.0 - drop_pct / 100.0)).max(0.0);
+            }
+        }
+    }
+
+    let outcomes = portfolio
+        .positions
+        .iter()
+        .zip(positions.iter())
+        .zip(liquidation_counts)
+        .map(|((before, after), count)| PositionOutcome {
+            position_id: before.id.clone(),
+            health_factor_before: before.health_factor(&initial_prices),
+            health_factor_after: after.health_factor(&prices),
+            liquidations: count,
+            bad_debt_usd: (after.debt_value(&prices) - after.collateral_value(&prices)).max(0.0),
+        })
+        .collect::<Vec<_>>();
+
+    let reserve_value_before = portfolio.reserve_value(&initial_prices);
+    let reserve_value_after = portfolio.reserve_value(&prices);
+
+    StressReport {
+        scenario: scenario.name.clone(),
+        total_bad_debt_usd: outcomes.iter().map(|o| o.bad_debt_usd).sum(),
+        final_prices: prices,
+        positions: outcomes,
+        liquidations,
+        cascade_rounds: rounds,
+        reserve_value_before,
+        reserve_value_after,
+        reserve_shortfall_usd: (portfolio.required_reserve_usd - reserve_value_after).max(0.0),
+    }
+}
+
+/// Run every scenario and return reports in order
+pub fn run_scenarios(portfolio: &Portfolio, scenarios: &[Scenario]) -> Vec<StressReport> {
+    scenarios
+        .iter()
+        .map(|scenario| run_scenario(portfolio, scenario))
+        .collect()
+}
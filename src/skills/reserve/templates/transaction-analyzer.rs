@@ -0,0 +1,396 @@
+/**
+ * Static Transaction Policy Analyzer
+ *
+ * Inspects every transaction the agent builds before it is signed: which
+ * programs it invokes, which accounts it writes, and how much value its
+ * SOL and token transfers move (valued with Pyth prices). The result is
+ * checked against a declared envelope loaded from a policy file; anything
+ * outside it blocks signing.
+ *
+ * Destination checks are delegated to destination-policy.rs, so the policy
+ * file also carries the withdrawal allow-list.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    pyth-oracle = { path = "../pyth-oracle", features = ["no-entrypoint", "hermes"] }
+ *    serde = { version = "1", features = ["derive"] }
+ *    serde_json = "1"
+ *    solana-sdk = "1.18"
+ *    thiserror = "1"
+ *
+ * 2. Copy next to destination-policy.rs as `transaction_analyzer.rs`
+ *
+ * Example policy file:
+ *    {
+ *      "treasury": "<agent wallet>",
+ *      "allowed_programs": ["JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4"],
+ *      "allowed_destinations": ["<cold wallet>", "<cold wallet USDC ATA>"],
+ *      "writable_accounts": ["<agent USDC ATA>", "<agent SOL ATA>"],
+ *      "max_transfer_usd": 10000.0,
+ *      "max_transaction_usd": 25000.0,
+ *      "sol_feed_id": "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d",
+ *      "assets": {
+ *        "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v": {
+ *          "symbol": "USDC",
+ *          "decimals": 6,
+ *          "feed_id": "0xeaa020c61cc479712813461ce153894a96a6c00b21ed0cfc2798d1f9a9e9c94a"
+ *        }
+ *      }
+ *    }
+ */
+
+use pyth_oracle::hermes::{parse_feed_id, ParsedPriceUpdate};
+use serde::Deserialize;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::destination_policy::{
+    DestinationPolicy, PolicyViolation, SYSTEM_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Lamports per SOL
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum PolicyFileError {
+    #[error("could not read policy file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("invalid policy file: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("invalid address {0}")]
+    InvalidAddress(String),
+
+    #[error("invalid feed id {0}")]
+    InvalidFeedId(String),
+}
+
+/// Why a transaction falls outside the envelope
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum PolicyBreach {
+    #[error(transparent)]
+    Destination(#[from] PolicyViolation),
+
+    #[error("account {0} is writable but not declared")]
+    UndeclaredWritable(Pubkey),
+
+    #[error("instruction {index}: transfer of unpriced asset {mint:?}")]
+    UnpricedTransfer { index: usize, mint: Option<Pubkey> },
+
+    #[error("instruction {index}: transfer worth ${value_usd:.2} exceeds ${limit_usd:.2}")]
+    TransferTooLarge {
+        index: usize,
+        value_usd: f64,
+        limit_usd: f64,
+    },
+
+    #[error("transaction moves ${value_usd:.2}, above ${limit_usd:.2}")]
+    TransactionTooLarge { value_usd: f64, limit_usd: f64 },
+}
+
+// ============================================================================
+// POLICY FILE
+// ============================================================================
+
+#[derive(Deserialize)]
+struct RawAsset {
+    symbol: String,
+    decimals: u8,
+    feed_id: String,
+}
+
+#[derive(Deserialize)]
+struct RawPolicy {
+    treasury: String,
+    #[serde(default)]
+    allowed_programs: Vec<String>,
+    #[serde(default)]
+    allowed_destinations: Vec<String>,
+    #[serde(default)]
+    writable_accounts: Vec<String>,
+    max_transfer_usd: f64,
+    max_transaction_usd: f64,
+    sol_feed_id: String,
+    #[serde(default)]
+    assets: BTreeMap<String, RawAsset>,
+}
+
+/// Pricing information for one mint
+#[derive(Clone, Debug)]
+pub struct PricedAsset {
+    pub symbol: String,
+    pub decimals: u8,
+    pub feed_id: [u8; 32],
+}
+
+/// The declared envelope
+#[derive(Clone, Debug)]
+pub struct TransactionPolicy {
+    pub destinations: DestinationPolicy,
+    /// Accounts besides signers that instructions may write
+    pub writable_accounts: BTreeSet<Pubkey>,
+    pub max_transfer_usd: f64,
+    pub max_transaction_usd: f64,
+    pub sol_feed_id: [u8; 32],
+    pub assets: BTreeMap<Pubkey, PricedAsset>,
+}
+
+fn parse_pubkey(value: &str) -> Result<Pubkey, PolicyFileError> {
+    Pubkey::from_str(value).map_err(|_| PolicyFileError::InvalidAddress(value.to_string()))
+}
+
+fn parse_feed(value: &str) -> Result<[u8; 32], PolicyFileError> {
+    parse_feed_id(value).map_err(|_| PolicyFileError::InvalidFeedId(value.to_string()))
+}
+
+impl TransactionPolicy {
+    pub fn from_json(json: &str) -> Result<Self, PolicyFileError> {
+        let raw: RawPolicy = serde_json::from_str(json)?;
+
+        let mut destinations = DestinationPolicy::new(parse_pubkey(&raw.treasury)?);
+        for program in &raw.allowed_programs {
+            destinations = destinations.allow_program(parse_pubkey(program)?);
+        }
+        for destination in &raw.allowed_destinations {
+            destinations = destinations.allow_destination(parse_pubkey(destination)?);
+        }
+
+        let mut assets = BTreeMap::new();
+        for (mint, asset) in &raw.assets {
+            assets.insert(
+                parse_pubkey(mint)?,
+                PricedAsset {
+                    symbol: asset.symbol.clone(),
+                    decimals: asset.decimals,
+                    feed_id: parse_feed(&asset.feed_id)?,
+                },
+            );
+        }
+
+        Ok(Self {
+            destinations,
+            writable_accounts: raw
+                .writable_accounts
+                .iter()
+                .map(|a| parse_pubkey(a))
+                .collect::<Result<_, _>>()?,
+            max_transfer_usd: raw.max_transfer_usd,
+            max_transaction_usd: raw.max_transaction_usd,
+            sol_feed_id: parse_feed(&raw.sol_feed_id)?,
+            assets,
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PolicyFileError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Feed IDs to fetch from Hermes before analyzing
+    pub fn feed_ids(&self) -> Vec<[u8; 32]> {
+        let mut ids: Vec<[u8; 32]> = self.assets.values().map(|a| a.feed_id).collect();
+        ids.push(self.sol_feed_id);
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+}
+
+// ============================================================================
+// PRICING
+// ============================================================================
+
+/// USD price per whole unit, keyed by feed ID
+#[derive(Clone, Debug, Default)]
+pub struct UsdPrices(BTreeMap<[u8; 32], f64>);
+
+impl UsdPrices {
+    /// Use the upper confidence bound: outflows are never undervalued
+    pub fn from_updates(updates: &[ParsedPriceUpdate]) -> Self {
+        Self(
+            updates
+                .iter()
+                .map(|u| {
+                    let upper = u.price.price as f64 + u.price.conf as f64;
+                    (u.feed_id, upper * 10f64.powi(u.price.exponent))
+                })
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, feed_id: &[u8; 32]) -> Option<f64> {
+        self.0.get(feed_id).copied()
+    }
+}
+
+// ============================================================================
+// ANALYSIS
+// ============================================================================
+
+/// A value-moving instruction found in the transaction
+#[derive(Clone, Debug)]
+pub struct ValueTransfer {
+    pub index: usize,
+    /// None for SOL
+    pub mint: Option<Pubkey>,
+    pub destination: Pubkey,
+    /// Raw amount (lamports or base units)
+    pub amount: u64,
+    pub value_usd: Option<f64>,
+}
+
+/// Everything the analyzer extracted from a transaction
+#[derive(Clone, Debug, Default)]
+pub struct TransactionAnalysis {
+    pub programs: BTreeSet<Pubkey>,
+    pub writable_accounts: BTreeSet<Pubkey>,
+    pub signers: BTreeSet<Pubkey>,
+    pub transfers: Vec<ValueTransfer>,
+    pub total_value_usd: f64,
+    pub breaches: Vec<PolicyBreach>,
+}
+
+impl TransactionAnalysis {
+    pub fn is_allowed(&self) -> bool {
+        self.breaches.is_empty()
+    }
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    data.get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Decode SOL and token transfers. `token_account_mints` maps token
+/// accounts to their mint for plain `Transfer`, which does not name the mint.
+fn decode_transfer(
+    index: usize,
+    ix: &Instruction,
+    token_account_mints: &BTreeMap<Pubkey, Pubkey>,
+) -> Option<ValueTransfer> {
+    let account = |i: usize| ix.accounts.get(i).map(|meta| meta.pubkey);
+
+    if ix.program_id == SYSTEM_PROGRAM_ID {
+        let tag = u32::from_le_bytes(ix.data.get(..4)?.try_into().ok()?);
+        let (destination, amount) = match tag {
+            2 => (account(1)?, read_u64(&ix.data, 4)?),
+            // TransferWithSeed: lamports follow the tag
+            11 => (account(2)?, read_u64(&ix.data, 4)?),
+            _ => return None,
+        };
+        return Some(ValueTransfer {
+            index,
+            mint: None,
+            destination,
+            amount,
+            value_usd: None,
+        });
+    }
+
+    if ix.program_id == TOKEN_PROGRAM_ID || ix.program_id == TOKEN_2022_PROGRAM_ID {
+        let (mint, destination, amount) = match *ix.data.first()? {
+            3 => (
+                token_account_mints.get(&account(0)?).copied(),
+                account(1)?,
+                read_u64(&ix.data, 1)?,
+            ),
+            12 => (Some(account(1)?), account(2)?, read_u64(&ix.data, 1)?),
+            _ => return None,
+        };
+        return Some(ValueTransfer {
+            index,
+            mint,
+            destination,
+            amount,
+            value_usd: None,
+        });
+    }
+
+    None
+}
+
+/// Analyze a transaction's instructions against the policy
+pub fn analyze_transaction(
+    policy: &TransactionPolicy,
+    instructions: &[Instruction],
+    prices: &UsdPrices,
+    token_account_mints: &BTreeMap<Pubkey, Pubkey>,
+) -> TransactionAnalysis {
+    let mut analysis = TransactionAnalysis::default();
+
+    for (index, ix) in instructions.iter().enumerate() {
+        analysis.programs.insert(ix.program_id);
+        for meta in &ix.accounts {
+            if meta.is_signer {
+                analysis.signers.insert(meta.pubkey);
+            }
+            if meta.is_writable {
+                analysis.writable_accounts.insert(meta.pubkey);
+            }
+        }
+
+        if let Err(violation) = policy.destinations.check_instruction(index, ix) {
+            analysis.breaches.push(violation.into());
+        }
+
+        if let Some(mut transfer) = decode_transfer(index, ix, token_account_mints) {
+            transfer.value_usd = match transfer.mint {
+                None => prices
+                    .get(&policy.sol_feed_id)
+                    .map(|p| transfer.amount as f64 / LAMPORTS_PER_SOL as f64 * p),
+                Some(mint) => policy.assets.get(&mint).and_then(|asset| {
+                    prices
+                        .get(&asset.feed_id)
+                        .map(|p| transfer.amount as f64 / 10f64.powi(asset.decimals as i32) * p)
+                }),
+            };
+
+            match transfer.value_usd {
+                None => analysis.breaches.push(PolicyBreach::UnpricedTransfer {
+                    index,
+                    mint: transfer.mint,
+                }),
+                Some(value_usd) => {
+                    analysis.total_value_usd += value_usd;
+                    if value_usd > policy.max_transfer_usd {
+                        analysis.breaches.push(PolicyBreach::TransferTooLarge {
+                            index,
+                            value_usd,
+                            limit_usd: policy.max_transfer_usd,
+                        });
+                    }
+                }
+            }
+            analysis.transfers.push(transfer);
+        }
+    }
+
+    for account in &analysis.writable_accounts {
+        if !analysis.signers.contains(account) && !policy.writable_accounts.contains(account) {
+            analysis
+                .breaches
+                .push(PolicyBreach::UndeclaredWritable(*account));
+        }
+    }
+
+    if analysis.total_value_usd > policy.max_transaction_usd {
+        analysis.breaches.push(PolicyBreach::TransactionTooLarge {
+            value_usd: analysis.total_value_usd,
+            limit_usd: policy.max_transaction_usd,
+        });
+    }
+
+    analysis
+}
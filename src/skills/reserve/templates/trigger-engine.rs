@@ -0,0 +1,556 @@
+/**
+ * Price Trigger Engine
+ *
+ * Triggers on oracle prices and strategy indicators ("SOL below $120",
+ * "SOL below $120 and BTC 1h momentum below 0") that hand an action to the
+ * executor when they fire. A condition is a threshold on one input, or an
+ * AND/OR/NOT composition of conditions across inputs. Each price or
+ * indicator update stores the input's latest value and re-evaluates only
+ * the triggers that read it. An input that has not been seen yet is
+ * unknown, and a condition that depends on it neither fires nor re-arms.
+ *
+ * A bare threshold flaps: a price hovering around the level crosses it on
+ * every few updates, and paired buy/sell triggers then send alternating
+ * actions. Three controls per trigger prevent that:
+ *
+ * - **Cooldown** - minimum time between two firings
+ * - **Hysteresis band** - after firing, the input must cross back past the
+ *   level by the band (in bps of the level, or an absolute band set on the
+ *   threshold) before the trigger re-arms
+ * - **Minimum re-arm delta** - the input must also have moved this far (in
+ *   bps) from the value it fired at, so every fire/re-arm cycle spans at
+ *   least that swing whatever the band
+ *
+ * For a composite condition, the trigger re-arms once the condition is
+ * false with every threshold widened by its band and delta (narrowed under
+ * NOT), i.e. once it is clearly false rather than just false.
+ *
+ * A fourth control filters momentary prints instead of repeated ones:
+ *
+ * - **Time in state** - the condition must hold continuously for
+ *   `hold_secs` and for `hold_updates` consecutive evaluations before the
+ *   trigger fires, like the `min_duration_secs` persistence check of the
+ *   on-chain depeg guard. A single wide-confidence print or one-slot spike
+ *   resets on the next update instead of firing
+ *
+ * A trigger is `Armed` until its condition holds, `Holding` while it holds
+ * but has not held long enough (or the cooldown is running), then `Fired`
+ * until it re-arms. Any evaluation where the condition does not hold sends
+ * a holding trigger back to `Armed` and restarts the count.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    pyth-oracle = { path = "../pyth-oracle", features = ["no-entrypoint"] }
+ *    thiserror = "1"
+ *
+ * 2. Copy this file as `trigger_engine.rs` and declare `mod trigger_engine;`
+ * 3. Feed it prices validated by the same rules as the rest of the
+ *    strategy, e.g. from the Hermes stream, and the indicators your
+ *    conditions name as your strategy computes them
+ */
+
+use pyth_oracle::OraclePrice;
+use std::collections::{BTreeMap, BTreeSet};
+
+const BPS_DENOMINATOR: f64 = 10_000.0;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum TriggerError {
+    #[error("trigger {0} already exists")]
+    Duplicate(String),
+
+    #[error("trigger {0}: price levels must be positive")]
+    InvalidLevel(String),
+
+    #[error("trigger {0}: bands must be finite and not negative")]
+    InvalidBand(String),
+
+    #[error("trigger {0}: cooldown must not be negative")]
+    InvalidCooldown(String),
+
+    #[error("trigger {0}: hold time must not be negative")]
+    InvalidHold(String),
+
+    #[error("trigger {0}: AND/OR needs at least one condition")]
+    EmptyCondition(String),
+}
+
+// ============================================================================
+// CONDITIONS
+// ============================================================================
+
+/// A value conditions can be written against
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Input {
+    /// Price of a Pyth feed, in quote currency units
+    Price([u8; 32]),
+    /// A strategy indicator pushed with `on_indicator` (e.g. "btc-momentum-1h")
+    Indicator(String),
+}
+
+/// Which side of the level holds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Holds when the input is at or above the level
+    Above,
+    /// Holds when the input is at or below the level
+    Below,
+}
+
+impl Direction {
+    fn holds(self, value: f64, level: f64) -> bool {
+        match self {
+            Direction::Above => value >= level,
+            Direction::Below => value <= level,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Condition {
+    Threshold {
+        input: Input,
+        direction: Direction,
+        level: f64,
+        /// Absolute hysteresis band, in input units; overrides
+        /// `hysteresis_bps`. Use it for levels at or near zero.
+        band: Option<f64>,
+    },
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    /// Price of `feed_id` above or below `level` (e.g. 120.0 for $120)
+    pub fn price(feed_id: [u8; 32], direction: Direction, level: f64) -> Self {
+        Self::Threshold {
+            input: Input::Price(feed_id),
+            direction,
+            level,
+            band: None,
+        }
+    }
+
+    pub fn indicator(name: impl Into<String>, direction: Direction, level: f64) -> Self {
+        Self::Threshold {
+            input: Input::Indicator(name.into()),
+            direction,
+            level,
+            band: None,
+        }
+    }
+
+    /// Set an absolute hysteresis band on a threshold; no effect on
+    /// composite conditions
+    pub fn with_band(mut self, band: f64) -> Self {
+        if let Self::Threshold { band: slot, .. } = &mut self {
+            *slot = Some(band);
+        }
+        self
+    }
+
+    pub fn and(self, other: Condition) -> Self {
+        match self {
+            Self::All(mut conditions) => {
+                conditions.push(other);
+                Self::All(conditions)
+            }
+            this => Self::All(vec![this, other]),
+        }
+    }
+
+    pub fn or(self, other: Condition) -> Self {
+        match self {
+            Self::Any(mut conditions) => {
+                conditions.push(other);
+                Self::Any(conditions)
+            }
+            this => Self::Any(vec![this, other]),
+        }
+    }
+
+    /// Every input the condition reads
+    pub fn inputs(&self) -> BTreeSet<Input> {
+        let mut inputs = BTreeSet::new();
+        self.collect_inputs(&mut inputs);
+        inputs
+    }
+
+    fn collect_inputs(&self, inputs: &mut BTreeSet<Input>) {
+        match self {
+            Self::Threshold { input, .. } => {
+                inputs.insert(input.clone());
+            }
+            Self::All(conditions) | Self::Any(conditions) => {
+                conditions.iter().for_each(|c| c.collect_inputs(inputs))
+            }
+            Self::Not(condition) => condition.collect_inputs(inputs),
+        }
+    }
+
+    fn validate(&self, id: &str) -> Result<(), TriggerError> {
+        match self {
+            Self::Threshold { input, level, band, .. } => {
+                let valid_level = match input {
+                    Input::Price(_) => level.is_finite() && *level > 0.0,
+                    Input::Indicator(_) => level.is_finite(),
+                };
+                if !valid_level {
+                    return Err(TriggerError::InvalidLevel(id.to_string()));
+                }
+                if band.is_some_and(|band| !band.is_finite() || band < 0.0) {
+                    return Err(TriggerError::InvalidBand(id.to_string()));
+                }
+                Ok(())
+            }
+            Self::All(conditions) | Self::Any(conditions) => {
+                if conditions.is_empty() {
+                    return Err(TriggerError::EmptyCondition(id.to_string()));
+                }
+                conditions.iter().try_for_each(|c| c.validate(id))
+            }
+            Self::Not(condition) => condition.validate(id),
+        }
+    }
+
+    /// Three-valued evaluation: `None` when an input it needs is unknown.
+    /// With `release`, thresholds are moved by the fired trigger's band and
+    /// delta.
+    fn eval(&self, values: &BTreeMap<Input, f64>, release: Option<(&Release, Bias)>) -> Option<bool> {
+        match self {
+            Self::Threshold {
+                input,
+                direction,
+                level,
+                band,
+            } => {
+                let value = *values.get(input)?;
+                let level = match release {
+                    None => *level,
+                    Some((release, bias)) => release.level(input, *direction, *level, *band, bias),
+                };
+                Some(direction.holds(value, level))
+            }
+            Self::All(conditions) => {
+                let mut result = Some(true);
+                for condition in conditions {
+                    match condition.eval(values, release) {
+                        Some(false) => return Some(false),
+                        None => result = None,
+                        Some(true) => {}
+                    }
+                }
+                result
+            }
+            Self::Any(conditions) => {
+                let mut result = Some(false);
+                for condition in conditions {
+                    match condition.eval(values, release) {
+                        Some(true) => return Some(true),
+                        None => result = None,
+                        Some(false) => {}
+                    }
+                }
+                result
+            }
+            Self::Not(condition) => condition
+                .eval(values, release.map(|(release, bias)| (release, bias.flip())))
+                .map(|holds| !holds),
+        }
+    }
+}
+
+impl std::ops::Not for Condition {
+    type Output = Condition;
+
+    fn not(self) -> Condition {
+        Condition::Not(Box::new(self))
+    }
+}
+
+/// Whether thresholds are widened (the condition holds more easily) or
+/// narrowed while deciding to re-arm
+#[derive(Clone, Copy, Debug)]
+enum Bias {
+    Loose,
+    Strict,
+}
+
+impl Bias {
+    fn flip(self) -> Self {
+        match self {
+            Bias::Loose => Bias::Strict,
+            Bias::Strict => Bias::Loose,
+        }
+    }
+}
+
+/// Band and delta of a fired trigger, applied to its thresholds
+struct Release<'a> {
+    controls: &'a TriggerControls,
+    fired_values: &'a BTreeMap<Input, f64>,
+}
+
+impl Release<'_> {
+    fn level(&self, input: &Input, direction: Direction, level: f64, band: Option<f64>, bias: Bias) -> f64 {
+        let band = band.unwrap_or(level.abs() * self.controls.hysteresis_bps as f64 / BPS_DENOMINATOR);
+        let delta = |value: f64| value.abs() * self.controls.min_rearm_delta_bps as f64 / BPS_DENOMINATOR;
+        let fired = self.fired_values.get(input).copied();
+
+        // Loose moves the level away from the side that holds, strict
+        // towards it
+        match (direction, bias) {
+            (Direction::Below, Bias::Loose) | (Direction::Above, Bias::Strict) => {
+                fired.map_or(level + band, |v| (level + band).max(v + delta(v)))
+            }
+            (Direction::Below, Bias::Strict) | (Direction::Above, Bias::Loose) => {
+                fired.map_or(level - band, |v| (level - band).min(v - delta(v)))
+            }
+        }
+    }
+}
+
+// ============================================================================
+// TRIGGERS
+// ============================================================================
+
+/// Anti-flapping controls of one trigger
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TriggerControls {
+    /// Minimum seconds between two firings
+    pub cooldown_secs: i64,
+    /// Distance past the level, in bps of the level, an input must cross
+    /// back before the trigger re-arms
+    pub hysteresis_bps: u64,
+    /// Distance from the fired value, in bps of it, an input must move
+    /// back before the trigger re-arms
+    pub min_rearm_delta_bps: u64,
+    /// Seconds the condition must hold continuously before firing
+    pub hold_secs: i64,
+    /// Consecutive evaluations the condition must hold for before firing,
+    /// counting the first. A trigger is evaluated on every update of any
+    /// input it reads.
+    pub hold_updates: u32,
+}
+
+/// A condition and the action it hands to the executor
+#[derive(Clone, Debug)]
+pub struct Trigger<A> {
+    pub id: String,
+    pub condition: Condition,
+    pub action: A,
+    pub controls: TriggerControls,
+}
+
+impl<A> Trigger<A> {
+    pub fn new(id: impl Into<String>, condition: Condition, action: A) -> Self {
+        Self {
+            id: id.into(),
+            condition,
+            action,
+            controls: TriggerControls::default(),
+        }
+    }
+
+    pub fn with_controls(mut self, controls: TriggerControls) -> Self {
+        self.controls = controls;
+        self
+    }
+
+    fn validate(&self) -> Result<(), TriggerError> {
+        self.condition.validate(&self.id)?;
+        if self.controls.cooldown_secs < 0 {
+            return Err(TriggerError::InvalidCooldown(self.id.clone()));
+        }
+        if self.controls.hold_secs < 0 {
+            return Err(TriggerError::InvalidHold(self.id.clone()));
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TriggerState {
+    /// Waiting for the condition to hold
+    Armed,
+    /// The condition has held since `since`, for `updates` consecutive
+    /// evaluations; fires once that meets the hold controls and the
+    /// cooldown is over
+    Holding { since: i64, updates: u32 },
+    /// Fired at `at` with the condition's inputs at `values`; waits for
+    /// the condition to be clearly false before re-arming
+    Fired { at: i64, values: BTreeMap<Input, f64> },
+}
+
+/// A trigger that fired, for the executor
+#[derive(Clone, Debug)]
+pub struct TriggerFired<A> {
+    pub trigger_id: String,
+    pub action: A,
+    /// The condition's inputs when it fired
+    pub values: BTreeMap<Input, f64>,
+    pub at: i64,
+}
+
+#[derive(Clone, Debug)]
+struct TriggerEntry<A> {
+    trigger: Trigger<A>,
+    state: TriggerState,
+    last_fired_at: Option<i64>,
+}
+
+impl<A: Clone> TriggerEntry<A> {
+    fn evaluate(&mut self, values: &BTreeMap<Input, f64>, now: i64) -> Option<TriggerFired<A>> {
+        let trigger = &self.trigger;
+        match &self.state {
+            TriggerState::Fired { values: fired_values, .. } => {
+                let release = Release {
+                    controls: &trigger.controls,
+                    fired_values,
+                };
+                if trigger.condition.eval(values, Some((&release, Bias::Loose))) == Some(false) {
+                    self.state = TriggerState::Armed;
+                }
+                None
+            }
+            TriggerState::Armed | TriggerState::Holding { .. } => {
+                if trigger.condition.eval(values, None) != Some(true) {
+                    self.state = TriggerState::Armed;
+                    return None;
+                }
+
+                let (since, updates) = match self.state {
+                    TriggerState::Holding { since, updates } => (since, updates.saturating_add(1)),
+                    _ => (now, 1),
+                };
+                let held = now - since >= trigger.controls.hold_secs && updates >= trigger.controls.hold_updates;
+                let cooling_down = self
+                    .last_fired_at
+                    .is_some_and(|at| now < at + trigger.controls.cooldown_secs);
+                if !held || cooling_down {
+                    self.state = TriggerState::Holding { since, updates };
+                    return None;
+                }
+
+                let snapshot: BTreeMap<Input, f64> = trigger
+                    .condition
+                    .inputs()
+                    .into_iter()
+                    .filter_map(|input| values.get(&input).map(|value| (input, *value)))
+                    .collect();
+                let fired = TriggerFired {
+                    trigger_id: trigger.id.clone(),
+                    action: trigger.action.clone(),
+                    values: snapshot.clone(),
+                    at: now,
+                };
+                self.state = TriggerState::Fired { at: now, values: snapshot };
+                self.last_fired_at = Some(now);
+                Some(fired)
+            }
+        }
+    }
+}
+
+// ============================================================================
+// ENGINE
+// ============================================================================
+
+/// Evaluates triggers as the inputs they read are updated
+#[derive(Clone, Debug)]
+pub struct TriggerEngine<A> {
+    triggers: BTreeMap<String, TriggerEntry<A>>,
+    /// Latest value of every input seen
+    values: BTreeMap<Input, f64>,
+    /// Triggers to re-evaluate when an input changes
+    readers: BTreeMap<Input, BTreeSet<String>>,
+}
+
+impl<A> Default for TriggerEngine<A> {
+    fn default() -> Self {
+        Self {
+            triggers: BTreeMap::new(),
+            values: BTreeMap::new(),
+            readers: BTreeMap::new(),
+        }
+    }
+}
+
+impl<A: Clone> TriggerEngine<A> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, trigger: Trigger<A>) -> Result<(), TriggerError> {
+        trigger.validate()?;
+        if self.triggers.contains_key(&trigger.id) {
+            return Err(TriggerError::Duplicate(trigger.id));
+        }
+        for input in trigger.condition.inputs() {
+            self.readers.entry(input).or_default().insert(trigger.id.clone());
+        }
+        self.triggers.insert(
+            trigger.id.clone(),
+            TriggerEntry {
+                trigger,
+                state: TriggerState::Armed,
+                last_fired_at: None,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<Trigger<A>> {
+        let entry = self.triggers.remove(id)?;
+        for input in entry.trigger.condition.inputs() {
+            if let Some(readers) = self.readers.get_mut(&input) {
+                readers.remove(id);
+            }
+        }
+        Some(entry.trigger)
+    }
+
+    pub fn state(&self, id: &str) -> Option<&TriggerState> {
+        self.triggers.get(id).map(|entry| &entry.state)
+    }
+
+    /// Re-arm a fired trigger by hand, e.g. after an operator review. The
+    /// cooldown still applies.
+    pub fn rearm(&mut self, id: &str) {
+        if let Some(entry) = self.triggers.get_mut(id) {
+            entry.state = TriggerState::Armed;
+        }
+    }
+
+    /// Record a new price of `feed_id` and evaluate the triggers reading it
+    pub fn on_price(&mut self, feed_id: &[u8; 32], price: &OraclePrice, now: i64) -> Vec<TriggerFired<A>> {
+        let value = price.price as f64 * 10f64.powi(price.exponent);
+        self.on_input(Input::Price(*feed_id), value, now)
+    }
+
+    /// Record a new indicator value and evaluate the triggers reading it
+    pub fn on_indicator(&mut self, name: &str, value: f64, now: i64) -> Vec<TriggerFired<A>> {
+        self.on_input(Input::Indicator(name.to_string()), value, now)
+    }
+
+    fn on_input(&mut self, input: Input, value: f64, now: i64) -> Vec<TriggerFired<A>> {
+        self.values.insert(input.clone(), value);
+        let Some(readers) = self.readers.get(&input) else {
+            return Vec::new();
+        };
+        readers
+            .iter()
+            .filter_map(|id| self.triggers.get_mut(id)?.evaluate(&self.values, now))
+            .collect()
+    }
+}
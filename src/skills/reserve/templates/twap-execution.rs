@@ -0,0 +1,387 @@
+/**
+ * Treasury Diversification - TWAP / Iceberg Execution
+ *
+ * Executes a large treasury conversion (e.g. SOL -> USDC) as a schedule of
+ * child orders spread over hours. Each child is sized from the remaining
+ * schedule, capped by available liquidity, and shrunk (or skipped) when the
+ * oracle confidence interval is wide. Fills are compared against the oracle
+ * expected output to report slippage.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    [dependencies]
+ *    thiserror = "1"
+ *
+ * 2. Copy this file as `twap_execution.rs` and declare `mod twap_execution;`
+ * 3. Drive `TwapExecution::step` from your scheduler (e.g. every minute)
+ */
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Basis points in 100%
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Default share of visible liquidity a child order may consume (5%)
+pub const DEFAULT_MAX_PARTICIPATION_BPS: u64 = 500;
+
+/// Default confidence above which child orders are skipped (1%)
+pub const DEFAULT_MAX_CONFIDENCE_BPS: u64 = 100;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum TwapError {
+    #[error("execution duration must be a positive multiple of the interval")]
+    InvalidSchedule,
+
+    #[error("total amount must be greater than zero")]
+    ZeroAmount,
+
+    #[error("fill of {filled} exceeds remaining amount {remaining}")]
+    Overfill { filled: u64, remaining: u64 },
+
+    #[error("child order execution failed: {0}")]
+    ExecutionFailed(String),
+}
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// How child orders are exposed to the market
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecutionStyle {
+    /// One child order per interval
+    Twap,
+    /// Child orders are further split into clips of at most `clip_amount`
+    Iceberg { clip_amount: u64 },
+}
+
+/// Parameters for one conversion
+#[derive(Clone, Debug)]
+pub struct TwapConfig {
+    /// Input asset symbol
+    pub sell_asset: String,
+    /// Output asset symbol
+    pub buy_asset: String,
+    /// Total input amount (token base units)
+    pub total_amount_in: u64,
+    /// Unix timestamp when execution starts
+    pub start_ts: i64,
+    /// Total execution window in seconds
+    pub duration_secs: i64,
+    /// Seconds between child orders
+    pub interval_secs: i64,
+    /// Max share of visible liquidity per child order
+    pub max_participation_bps: u64,
+    /// Oracle confidence (bps of price) above which a slice is skipped
+    pub max_confidence_bps: u64,
+    /// Children smaller than this are not sent (dust)
+    pub min_child_amount: u64,
+    pub style: ExecutionStyle,
+}
+
+impl TwapConfig {
+    pub fn new(
+        sell_asset: &str,
+        buy_asset: &str,
+        total_amount_in: u64,
+        start_ts: i64,
+        duration_secs: i64,
+        interval_secs: i64,
+    ) -> Result<Self, TwapError> {
+        if total_amount_in == 0 {
+            return Err(TwapError::ZeroAmount);
+        }
+        if interval_secs <= 0 || duration_secs < interval_secs {
+            return Err(TwapError::InvalidSchedule);
+        }
+        Ok(Self {
+            sell_asset: sell_asset.to_string(),
+            buy_asset: buy_asset.to_string(),
+            total_amount_in,
+            start_ts,
+            duration_secs,
+            interval_secs,
+            max_participation_bps: DEFAULT_MAX_PARTICIPATION_BPS,
+            max_confidence_bps: DEFAULT_MAX_CONFIDENCE_BPS,
+            min_child_amount: 0,
+            style: ExecutionStyle::Twap,
+        })
+    }
+
+    pub fn with_participation(mut self, max_participation_bps: u64) -> Self {
+        self.max_participation_bps = max_participation_bps;
+        self
+    }
+
+    pub fn with_max_confidence(mut self, max_confidence_bps: u64) -> Self {
+        self.max_confidence_bps = max_confidence_bps;
+        self
+    }
+
+    pub fn with_min_child(mut self, min_child_amount: u64) -> Self {
+        self.min_child_amount = min_child_amount;
+        self
+    }
+
+    pub fn iceberg(mut self, clip_amount: u64) -> Self {
+        self.style = ExecutionStyle::Iceberg { clip_amount };
+        self
+    }
+
+    pub fn total_slices(&self) -> u64 {
+        (self.duration_secs / self.interval_secs) as u64
+    }
+
+    pub fn end_ts(&self) -> i64 {
+        self.start_ts + self.duration_secs
+    }
+}
+
+// ============================================================================
+// MARKET INPUTS AND FILLS
+// ============================================================================
+
+/// Market conditions observed before sending a slice
+#[derive(Clone, Copy, Debug)]
+pub struct MarketSnapshot {
+    /// Input amount the venue can absorb within acceptable impact
+    pub available_liquidity_in: u64,
+    /// Oracle confidence of the pair, in bps of price
+    pub oracle_confidence_bps: u64,
+}
+
+/// Result of executing one child order
+#[derive(Clone, Copy, Debug)]
+pub struct Fill {
+    pub ts: i64,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Output the oracle mid price implied for `amount_in`
+    pub oracle_expected_out: u64,
+}
+
+impl Fill {
+    /// Slippage versus oracle in bps (positive = worse than oracle)
+    pub fn slippage_bps(&self) -> i64 {
+        if self.oracle_expected_out == 0 {
+            return 0;
+        }
+        let diff = self.oracle_expected_out as i128 - self.amount_out as i128;
+        (diff * BPS_DENOMINATOR as i128 / self.oracle_expected_out as i128) as i64
+    }
+}
+
+/// Why a slice produced no order
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    NotDue,
+    Completed,
+    ConfidenceTooWide,
+    NoLiquidity,
+    BelowMinimum,
+}
+
+/// Outcome of a scheduler tick
+#[derive(Clone, Debug)]
+pub enum StepOutcome {
+    Executed(Vec<Fill>),
+    Skipped(SkipReason),
+}
+
+// ============================================================================
+// EXECUTION STATE
+// ============================================================================
+
+/// Progress and slippage summary
+#[derive(Clone, Debug)]
+pub struct ExecutionReport {
+    pub sell_asset: String,
+    pub buy_asset: String,
+    pub total_amount_in: u64,
+    pub filled_in: u64,
+    pub filled_out: u64,
+    pub complete_bps: u64,
+    /// Share of the amount that should be filled by now
+    pub scheduled_bps: u64,
+    pub fills: usize,
+    pub skipped_slices: u64,
+    /// Volume-weighted slippage versus oracle
+    pub avg_slippage_bps: i64,
+    pub worst_slippage_bps: i64,
+}
+
+impl ExecutionReport {
+    pub fn is_behind_schedule(&self) -> bool {
+        self.complete_bps < self.scheduled_bps
+    }
+}
+
+/// A running TWAP / iceberg execution
+#[derive(Clone, Debug)]
+pub struct TwapExecution {
+    pub config: TwapConfig,
+    pub fills: Vec<Fill>,
+    pub skipped_slices: u64,
+    next_slice_ts: i64,
+}
+
+impl TwapExecution {
+    pub fn new(config: TwapConfig) -> Self {
+        let next_slice_ts = config.start_ts;
+        Self {
+            config,
+            fills: Vec::new(),
+            skipped_slices: 0,
+            next_slice_ts,
+        }
+    }
+
+    pub fn filled_in(&self) -> u64 {
+        self.fills.iter().map(|f| f.amount_in).sum()
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.config.total_amount_in - self.filled_in()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    fn remaining_slices(&self, now: i64) -> u64 {
+        let left = (self.config.end_ts() - now).max(0) / self.config.interval_secs;
+        (left as u64).max(1)
+    }
+
+    /// Size the next child order from schedule, liquidity and confidence
+    pub fn size_child(&self, now: i64, market: &MarketSnapshot) -> Result<u64, SkipReason> {
+        let remaining = self.remaining();
+        if remaining == 0 {
+            return Err(SkipReason::Completed);
+        }
+        if market.oracle_confidence_bps > self.config.max_confidence_bps {
+            return Err(SkipReason::ConfidenceTooWide);
+        }
+
+        // Even split of what is left over the slices that are left; past the
+        // deadline everything remaining becomes due
+        let scheduled = remaining.div_ceil(self.remaining_slices(now));
+
+        // Shrink linearly, down to half size, as confidence approaches the limit
+        let max_conf = self.config.max_confidence_bps.max(1);
+        let confidence_scaled = (scheduled as u128
+            * (max_conf - market.oracle_confidence_bps / 2) as u128
+            / max_conf as u128) as u64;
+
+        let liquidity_cap = (market.available_liquidity_in as u128
+            * self.config.max_participation_bps as u128
+            / BPS_DENOMINATOR as u128) as u64;
+        if liquidity_cap == 0 {
+            return Err(SkipReason::NoLiquidity);
+        }
+
+        let size = confidence_scaled.min(liquidity_cap).min(remaining);
+        if size < self.config.min_child_amount && size < remaining {
+            return Err(SkipReason::BelowMinimum);
+        }
+        Ok(size)
+    }
+
+    /// Run one scheduler tick
+    ///
+    /// `execute` sends a single order for the given input amount and returns
+    /// its fill. Iceberg executions call it once per clip.
+    pub fn step<F>(
+        &mut self,
+        now: i64,
+        market: &MarketSnapshot,
+        mut execute: F,
+    ) -> Result<StepOutcome, TwapError>
+    where
+        F: FnMut(u64) -> Result<Fill, TwapError>,
+    {
+        if now < self.next_slice_ts {
+            return Ok(StepOutcome::Skipped(SkipReason::NotDue));
+        }
+        self.next_slice_ts = now + self.config.interval_secs;
+
+        let size = match self.size_child(now, market) {
+            Ok(size) => size,
+            Err(reason) => {
+                if reason != SkipReason::Completed {
+                    self.skipped_slices += 1;
+                }
+                return Ok(StepOutcome::Skipped(reason));
+            }
+        };
+
+        let clip = match self.config.style {
+            ExecutionStyle::Twap => size,
+            ExecutionStyle::Iceberg { clip_amount } => clip_amount.max(1),
+        };
+
+        let mut fills = Vec::new();
+        let mut left = size;
+        while left > 0 {
+            let amount = left.min(clip);
+            let fill = execute(amount)?;
+            let remaining = self.remaining();
+            if fill.amount_in > remaining {
+                return Err(TwapError::Overfill {
+                    filled: fill.amount_in,
+                    remaining,
+                });
+            }
+            self.fills.push(fill);
+            fills.push(fill);
+            // A partial fill ends the slice; the shortfall is rescheduled
+            if fill.amount_in < amount {
+                break;
+            }
+            left -= amount;
+        }
+
+        Ok(StepOutcome::Executed(fills))
+    }
+
+    /// Progress and slippage report at `now`
+    pub fn report(&self, now: i64) -> ExecutionReport {
+        let total = self.config.total_amount_in;
+        let filled_in = self.filled_in();
+        let filled_out = self.fills.iter().map(|f| f.amount_out).sum();
+
+        let elapsed = (now - self.config.start_ts).clamp(0, self.config.duration_secs);
+        let scheduled_bps = (elapsed as u64 * BPS_DENOMINATOR) / self.config.duration_secs as u64;
+
+        let weighted: i128 = self
+            .fills
+            .iter()
+            .map(|f| f.slippage_bps() as i128 * f.amount_in as i128)
+            .sum();
+        let avg_slippage_bps = if filled_in > 0 {
+            (weighted / filled_in as i128) as i64
+        } else {
+            0
+        };
+
+        ExecutionReport {
+            sell_asset: self.config.sell_asset.clone(),
+            buy_asset: self.config.buy_asset.clone(),
+            total_amount_in: total,
+            filled_in,
+            filled_out,
+            complete_bps: filled_in * BPS_DENOMINATOR / total,
+            scheduled_bps,
+            fills: self.fills.len(),
+            skipped_slices: self.skipped_slices,
+            avg_slippage_bps,
+            worst_slippage_bps: self.fills.iter().map(Fill::slippage_bps).max().unwrap_or(0),
+        }
+    }
+}
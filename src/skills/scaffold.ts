@@ -0,0 +1,519 @@
+/**
+ * Skill Scaffolding
+ *
+ * Generates the directory layout shared by the provider skills (SKILL.md,
+ * templates/, examples/on-chain/, examples/off-chain/, tests/) with stub
+ * modules, so a new skill starts from the same skeleton as pyth, chainlink
+ * or switchboard. Oracle skills get a `PriceSource` adapter over the
+ * pyth-oracle crate and a row in the oracle skill's Implementations table.
+ */
+
+import fs from "fs";
+import path from "path";
+
+export const SKILL_KINDS = ["oracle", "defi", "infra"] as const;
+export type SkillKind = (typeof SKILL_KINDS)[number];
+
+export interface ScaffoldFile {
+  /** Relative to the skill directory */
+  path: string;
+  content: string;
+}
+
+export interface ScaffoldResult {
+  skillDir: string;
+  /** Absolute paths, in generation order */
+  written: string[];
+  /** Oracle skill table the new provider was added to, if any */
+  wired?: string;
+}
+
+const SKILL_NAME = /^[a-z][a-z0-9]*(-[a-z0-9]+)*$/;
+
+export function isSkillKind(kind: string): kind is SkillKind {
+  return (SKILL_KINDS as readonly string[]).includes(kind);
+}
+
+/**
+ * Throws unless `name` is a kebab-case directory name.
+ */
+export function validateSkillName(name: string): void {
+  if (!SKILL_NAME.test(name)) {
+    throw new Error(`Skill name must be kebab-case (e.g. "my-oracle"), got "${name}"`);
+  }
+}
+
+function pascalCase(name: string): string {
+  return name
+    .split("-")
+    .map((part) => part[0].toUpperCase() + part.slice(1))
+    .join("");
+}
+
+function snakeCase(name: string): string {
+  return name.replace(/-/g, "_");
+}
+
+/**
+ * Files of a new skill. Pure: nothing is written.
+ */
+export function scaffoldSkill(name: string, kind: SkillKind): ScaffoldFile[] {
+  validateSkillName(name);
+  const files = [
+    templateFile(name, kind),
+    onChainExample(name, kind),
+    offChainExample(name),
+    testFile(name, kind),
+  ];
+  return [{ path: "SKILL.md", content: skillMd(name, kind, files) }, ...files];
+}
+
+/**
+ * Write a new skill under `skillsRoot`. Refuses to touch an existing
+ * directory. For oracle skills, also lists the provider in
+ * `<skillsRoot>/oracle/SKILL.md` when that file exists.
+ */
+export function writeSkillScaffold(
+  skillsRoot: string,
+  name: string,
+  kind: SkillKind,
+): ScaffoldResult {
+  const files = scaffoldSkill(name, kind);
+  const skillDir = path.join(skillsRoot, name);
+  if (fs.existsSync(skillDir)) {
+    throw new Error(`${skillDir} already exists`);
+  }
+
+  const written: string[] = [];
+  for (const file of files) {
+    const filePath = path.join(skillDir, file.path);
+    fs.mkdirSync(path.dirname(filePath), { recursive: true });
+    fs.writeFileSync(filePath, file.content);
+    written.push(filePath);
+  }
+
+  const result: ScaffoldResult = { skillDir, written };
+  if (kind === "oracle") {
+    const oracleSkill = path.join(skillsRoot, "oracle", "SKILL.md");
+    if (fs.existsSync(oracleSkill)) {
+      const content = fs.readFileSync(oracleSkill, "utf-8");
+      const updated = addOracleImplementation(content, name);
+      if (updated !== content) {
+        fs.writeFileSync(oracleSkill, updated);
+        result.wired = oracleSkill;
+      }
+    }
+  }
+  return result;
+}
+
+/**
+ * Append a provider row to the Implementations table of the oracle skill.
+ * Returns the content unchanged if the table is missing or already lists
+ * the provider.
+ */
+export function addOracleImplementation(content: string, name: string): string {
+  const lines = content.split("\n");
+  const heading = lines.indexOf("## Implementations");
+  if (heading === -1) return content;
+
+  let last = -1;
+  for (let i = heading + 1; i < lines.length; i++) {
+    if (lines[i].startsWith("|")) last = i;
+    else if (last !== -1) break;
+  }
+  if (last === -1) return content;
+
+  const provider = pascalCase(name);
+  const listed = lines
+    .slice(heading + 1, last + 1)
+    .some((line) => line.split("|")[1]?.trim() === provider);
+  if (listed) return content;
+
+  lines.splice(last + 1, 0, `| ${provider} | \`${provider}Price\` | Seconds |`);
+  return lines.join("\n");
+}
+
+// ─── Files ─────────────────────────────────────────────────────
+
+const KIND_SUMMARY: Record<SkillKind, string> = {
+  oracle: "price adapter implementing the shared PriceSource trait",
+  defi: "Anchor program integration",
+  infra: "off-chain infrastructure client",
+};
+
+function skillMd(name: string, kind: SkillKind, files: ScaffoldFile[]): string {
+  const title = pascalCase(name);
+  const tree = files
+    .map((file, i) => {
+      const branch = i === files.length - 1 ? "└──" : "├──";
+      return `${branch} ${file.path.padEnd(34)}# ${describe(file.path)}`;
+    })
+    .join("\n");
+  return `---
+name: ${name}
+description: ${title} ${KIND_SUMMARY[kind]}. TODO: describe when to use this skill.
+---
+
+# ${title} on Solana Guide
+
+TODO: what ${title} provides and how this skill integrates it.
+
+## Program IDs
+
+| Program | Address | Description |
+|---------|---------|-------------|
+| TODO | \`TODO\` | TODO |
+
+## Quick Start
+
+${quickStart(name, kind)}
+
+## Core Concepts
+
+TODO: accounts, staleness or limits, and failure modes integrators must handle.
+
+## Skill Structure
+
+\`\`\`
+${name}/
+├── ${"SKILL.md".padEnd(34)}# This file
+${tree}
+\`\`\`
+`;
+}
+
+function describe(filePath: string): string {
+  if (filePath.startsWith("templates/")) return "Template";
+  if (filePath.startsWith("examples/on-chain/")) return "On-chain example";
+  if (filePath.startsWith("examples/off-chain/")) return "Off-chain example";
+  return "Tests";
+}
+
+function quickStart(name: string, kind: SkillKind): string {
+  const crate = snakeCase(name);
+  if (kind === "oracle") {
+    return `\`\`\`rust
+use ${crate}_oracle::{get_validated_${crate}_price, presets};
+
+let price = get_validated_${crate}_price(&ctx.accounts.feed, &presets::strict(), &Clock::get()?)?;
+\`\`\``;
+  }
+  if (kind === "defi") {
+    return `\`\`\`rust
+use ${crate}_program::${crate}_cpi;
+\`\`\``;
+  }
+  return `\`\`\`bash
+cargo run --example ${name}-client
+\`\`\``;
+}
+
+function templateFile(name: string, kind: SkillKind): ScaffoldFile {
+  const title = pascalCase(name);
+  const crate = snakeCase(name);
+  if (kind === "oracle") {
+    return {
+      path: `templates/${name}-oracle.rs`,
+      content: `/**
+ * ${title} Oracle Template for Anchor Programs
+ *
+ * Reads ${title} prices and plugs them into the same \`get_validated_price\`
+ * pipeline as Pyth, returning a \`ValidatedPrice\`.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    anchor-lang = "0.30.1"
+ *    pyth-oracle = { path = "../pyth-oracle", features = ["no-entrypoint"] }
+ *    (pyth-oracle is pyth/templates/anchor-oracle.rs built as a library)
+ *
+ * 2. Import this module in your program
+ * 3. Call \`get_validated_${crate}_price\` with one of the presets below
+ */
+
+use anchor_lang::prelude::*;
+use pyth_oracle::oracle::{OraclePrice, PriceSource, PriceSourceError, StalenessMode};
+use pyth_oracle::{get_validated_price, PriceValidationConfig, ValidatedPrice};
+
+// ============================================================================
+// CONFIG PRESETS
+// ============================================================================
+
+pub mod presets {
+    use super::*;
+
+    pub fn default() -> PriceValidationConfig {
+        PriceValidationConfig::default()
+    }
+
+    pub fn strict() -> PriceValidationConfig {
+        PriceValidationConfig::strict()
+    }
+
+    pub fn lenient() -> PriceValidationConfig {
+        PriceValidationConfig::lenient()
+    }
+}
+
+// ============================================================================
+// PRICE SOURCE
+// ============================================================================
+
+/// A ${title} price read from its feed account
+pub struct ${title}Price {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+}
+
+impl ${title}Price {
+    /// Deserialize and check the feed account
+    pub fn load(_feed: &AccountInfo) -> Result<Self> {
+        // TODO: check the owner and deserialize the provider's layout
+        err!(${title}OracleError::InvalidFeedAccount)
+    }
+}
+
+impl PriceSource for ${title}Price {
+    fn feed_id(&self) -> [u8; 32] {
+        self.feed_id
+    }
+
+    fn staleness_mode(&self) -> StalenessMode {
+        StalenessMode::Seconds
+    }
+
+    fn get_price(&self, clock: &Clock, max_age: u64) -> Result<OraclePrice> {
+        require!(
+            clock.unix_timestamp - self.publish_time <= max_age as i64,
+            ${title}OracleError::PriceTooStale
+        );
+        Ok(OraclePrice {
+            price: self.price,
+            conf: self.conf,
+            exponent: self.exponent,
+            publish_time: self.publish_time,
+        })
+    }
+
+    fn get_ema_price(&self, _clock: &Clock, _max_age: u64) -> Result<OraclePrice> {
+        Err(error!(PriceSourceError::PriceUnavailable))
+    }
+}
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+pub fn get_validated_${crate}_price(
+    feed: &AccountInfo,
+    config: &PriceValidationConfig,
+    clock: &Clock,
+) -> Result<ValidatedPrice> {
+    get_validated_price(&${title}Price::load(feed)?, config, clock)
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct Single${title}FeedContext<'info> {
+    /// CHECK: validated by \`${title}Price::load\`
+    pub feed: UncheckedAccount<'info>,
+}
+
+// ============================================================================
+// ERROR CODES
+// ============================================================================
+
+#[error_code]
+pub enum ${title}OracleError {
+    #[msg("Feed account is not a ${title} feed")]
+    InvalidFeedAccount,
+
+    #[msg("Price is too stale")]
+    PriceTooStale,
+}
+`,
+    };
+  }
+  if (kind === "defi") {
+    return {
+      path: `templates/${name}-program.rs`,
+      content: `/**
+ * ${title} Integration Template for Anchor Programs
+ *
+ * TODO: what the integration does and which ${title} instructions it calls.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    anchor-lang = "0.30.1"
+ *
+ * 2. Import this module in your program
+ */
+
+use anchor_lang::prelude::*;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// ${title} program
+pub const ${crate.toUpperCase()}_PROGRAM_ID: Pubkey = pubkey!("11111111111111111111111111111111");
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct ${title}Context<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: address checked against the program ID
+    #[account(address = ${crate.toUpperCase()}_PROGRAM_ID)]
+    pub ${crate}_program: UncheckedAccount<'info>,
+}
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+/// TODO: build and invoke the ${title} instruction
+pub fn ${crate}_cpi(_ctx: &Context<${title}Context>) -> Result<()> {
+    err!(${title}Error::NotImplemented)
+}
+
+// ============================================================================
+// ERROR CODES
+// ============================================================================
+
+#[error_code]
+pub enum ${title}Error {
+    #[msg("Not implemented")]
+    NotImplemented,
+}
+`,
+    };
+  }
+  return {
+    path: `templates/${name}-client.rs`,
+    content: `/**
+ * ${title} Client Template
+ *
+ * TODO: what the client talks to and what it returns.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    reqwest = { version = "0.12", features = ["json"] }
+ *    serde = { version = "1", features = ["derive"] }
+ *
+ * 2. Import this module in your off-chain crate
+ */
+
+pub struct ${title}Client {
+    endpoint: String,
+    http: reqwest::Client,
+}
+
+impl ${title}Client {
+    pub fn new(endpoint: &str) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// TODO: replace with the service's first call
+    pub async fn health(&self) -> Result<bool, reqwest::Error> {
+        let response = self.http.get(format!("{}/health", self.endpoint)).send().await?;
+        Ok(response.status().is_success())
+    }
+}
+`,
+  };
+}
+
+function onChainExample(name: string, kind: SkillKind): ScaffoldFile {
+  const title = pascalCase(name);
+  const crate = snakeCase(name);
+  const body =
+    kind === "oracle"
+      ? `use ${crate}_oracle::{get_validated_${crate}_price, presets, Single${title}FeedContext};
+
+pub fn use_price(ctx: Context<Single${title}FeedContext>) -> Result<()> {
+    let price = get_validated_${crate}_price(&ctx.accounts.feed, &presets::strict(), &Clock::get()?)?;
+    msg!("Price: {} × 10^{}", price.price, price.exponent);
+    Ok(())
+}`
+      : `// TODO: an instruction handler that uses templates/${name}-${kind === "defi" ? "program" : "client"}.rs`;
+  return {
+    path: `examples/on-chain/${name}-example.rs`,
+    content: `/**
+ * ${title} On-Chain Example
+ *
+ * TODO: what this example shows.
+ */
+
+use anchor_lang::prelude::*;
+
+${body}
+`,
+  };
+}
+
+function offChainExample(name: string): ScaffoldFile {
+  const title = pascalCase(name);
+  return {
+    path: `examples/off-chain/${name}-client.rs`,
+    content: `/**
+ * ${title} Off-Chain Example
+ *
+ * TODO: what this example fetches or sends.
+ *
+ * Run: cargo run --example ${name}-client
+ */
+
+fn main() {
+    // TODO
+}
+`,
+  };
+}
+
+function testFile(name: string, kind: SkillKind): ScaffoldFile {
+  const title = pascalCase(name);
+  const crate = snakeCase(name);
+  const test =
+    kind === "oracle"
+      ? `use anchor_lang::prelude::Clock;
+use pyth_oracle::oracle::PriceSource;
+use ${crate}_oracle::${title}Price;
+
+#[test]
+fn stale_price_is_rejected() {
+    let price = ${title}Price { feed_id: [0; 32], price: 100, conf: 1, exponent: -2, publish_time: 1_000 };
+    let clock = Clock { unix_timestamp: 1_061, ..Clock::default() };
+    assert!(price.get_price(&clock, 60).is_err());
+    assert!(price.get_price(&clock, 61).is_ok());
+}`
+      : `#[test]
+fn todo() {
+    // TODO
+}`;
+  return {
+    path: `tests/${name}.rs`,
+    content: `/**
+ * Tests for ${title}
+ *
+ * Setup:
+ * 1. Copy this file to \`tests/${name}.rs\` of the program crate
+ * 2. Run: cargo test --test ${name}
+ */
+
+${test}
+`,
+  };
+}
@@ -0,0 +1,133 @@
+/**
+ * Oracle-Agnostic Swap - Pyth or Switchboard
+ *
+ * Demonstrates a swap whose input and output prices may each come from
+ * either a Pyth PriceUpdateV2 account or a Switchboard On-Demand pull feed.
+ * Both go through the same `get_validated_price` pipeline and produce the
+ * same `ValidatedPrice`, so the swap math is written once.
+ *
+ * Add to Cargo.toml:
+ * [dependencies]
+ * anchor-lang = "0.30.1"
+ * pyth-solana-receiver-sdk = "0.3.0"
+ * switchboard-on-demand = "0.8.0"
+ * pyth-oracle = { path = "../pyth-oracle", features = ["no-entrypoint"] }
+ */
+
+use anchor_lang::prelude::*;
+use pyth_oracle::{
+    calculate_tokens_for_usd, calculate_usd_value, get_validated_price, OracleError,
+    PriceValidationConfig, Rounding, ValidatedPrice, PYTH_RECEIVER_PROGRAM_ID,
+};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+#[path = "../../templates/switchboard-oracle.rs"]
+pub mod switchboard_oracle;
+use switchboard_oracle::{get_validated_switchboard_price, SWITCHBOARD_ON_DEMAND_PROGRAM_ID};
+
+declare_id!("YourProgramId11111111111111111111111111111111");
+
+// ============================================================================
+// Oracle Dispatch
+// ============================================================================
+
+/// Validate a price account from whichever oracle owns it
+pub fn get_validated_price_any(
+    price_account: &AccountInfo,
+    config: &PriceValidationConfig,
+    clock: &Clock,
+) -> Result<ValidatedPrice> {
+    if *price_account.owner == PYTH_RECEIVER_PROGRAM_ID {
+        let price_update: Account<PriceUpdateV2> = Account::try_from(price_account)?;
+        get_validated_price(&*price_update, config, clock)
+    } else if *price_account.owner == SWITCHBOARD_ON_DEMAND_PROGRAM_ID {
+        get_validated_switchboard_price(price_account, config, clock)
+    } else {
+        Err(error!(SwapError::UnsupportedOracle))
+    }
+}
+
+// ============================================================================
+// Program
+// ============================================================================
+
+#[program]
+pub mod oracle_agnostic_swap {
+    use super::*;
+
+    /// Swap with slippage protection priced by Pyth or Switchboard
+    pub fn swap(
+        ctx: Context<OracleAgnosticSwap>,
+        amount_in: u64,
+        min_amount_out: u64,
+        input_decimals: u8,
+        output_decimals: u8,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let config = PriceValidationConfig::strict();
+
+        let input_price = get_validated_price_any(&ctx.accounts.input_price, &config, &clock)?;
+        let output_price = get_validated_price_any(&ctx.accounts.output_price, &config, &clock)?;
+
+        // Selling input: use lower bound
+        // Buying output: use upper bound
+        let input_usd = calculate_usd_value(
+            amount_in,
+            input_decimals,
+            input_price.sell_price(),
+            input_price.exponent,
+            Rounding::Floor, // value credited to the user
+        )?;
+
+        let expected_out = calculate_tokens_for_usd(
+            input_usd,
+            6,
+            output_decimals,
+            output_price.buy_price(),
+            output_price.exponent,
+            Rounding::Floor, // tokens paid out to the user
+        )?;
+
+        msg!("Input value (USD): {}", input_usd);
+        msg!("Expected output: {}", expected_out);
+
+        require!(
+            expected_out >= min_amount_out,
+            OracleError::SlippageExceeded
+        );
+
+        // Execute swap logic...
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Accounts
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct OracleAgnosticSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// Pyth PriceUpdateV2 or Switchboard pull feed for the input token
+    /// CHECK: owner is dispatched and verified by get_validated_price_any
+    pub input_price: AccountInfo<'info>,
+
+    /// Pyth PriceUpdateV2 or Switchboard pull feed for the output token
+    /// CHECK: owner is dispatched and verified by get_validated_price_any
+    pub output_price: AccountInfo<'info>,
+
+    // Add your token accounts, pool accounts, etc.
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[error_code]
+pub enum SwapError {
+    #[msg("Price account is not owned by a supported oracle")]
+    UnsupportedOracle,
+}
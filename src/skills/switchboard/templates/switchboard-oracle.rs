@@ -0,0 +1,143 @@
+/**
+ * Switchboard On-Demand Oracle Template for Anchor Programs
+ *
+ * Adapter that lets Switchboard On-Demand pull feeds plug into the same
+ * validation pipeline as Pyth: it implements the oracle `PriceSource` trait,
+ * so `get_validated_price` applies the usual staleness and confidence checks
+ * and returns the same `ValidatedPrice`.
+ *
+ * Setup:
+ * 1. Add to Cargo.toml:
+ *    switchboard-on-demand = "0.8.0"
+ *    anchor-lang = "0.30.1"
+ *    pyth-oracle = { path = "../pyth-oracle", features = ["no-entrypoint"] }
+ *    (pyth-oracle is pyth/templates/anchor-oracle.rs built as a library)
+ *
+ * 2. Import this module in your program
+ * 3. Pass a loaded PullFeedAccountData to `get_validated_price`
+ */
+
+use anchor_lang::prelude::*;
+use pyth_oracle::oracle::{Now, OraclePrice, PriceSource, PriceSourceError, StalenessMode};
+use pyth_oracle::{get_validated_price, PriceValidationConfig, ValidatedPrice};
+use switchboard_on_demand::PullFeedAccountData;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Switchboard On-Demand program ID (mainnet)
+pub const SWITCHBOARD_ON_DEMAND_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f");
+
+/// Switchboard On-Demand program ID (devnet)
+pub const SWITCHBOARD_ON_DEMAND_DEVNET_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("Aio4gaXjXzJNVLtzwtNVmSqGKpANtXhybbkhtAC94ji2");
+
+/// Switchboard values are fixed-point with 18 decimals
+pub const SWITCHBOARD_DECIMALS: u32 = 18;
+
+/// Exponent of prices returned by this adapter (matches most Pyth USD feeds)
+pub const SWITCHBOARD_PRICE_EXPONENT: i32 = -8;
+
+/// Minimum oracle samples required for a feed value
+pub const MIN_SAMPLES: u32 = 1;
+
+// ============================================================================
+// PRICE SOURCE
+// ============================================================================
+
+/// Rescale an 18-decimal Switchboard value to SWITCHBOARD_PRICE_EXPONENT
+pub fn rescale_switchboard_value(value: i128) -> Result<i64> {
+    let divisor = 10i128.pow(SWITCHBOARD_DECIMALS - (-SWITCHBOARD_PRICE_EXPONENT) as u32);
+    i64::try_from(value / divisor).map_err(|_| error!(SwitchboardOracleError::ValueOutOfRange))
+}
+
+impl PriceSource for PullFeedAccountData {
+    fn feed_id(&self) -> [u8; 32] {
+        self.feed_hash
+    }
+
+    fn staleness_mode(&self) -> StalenessMode {
+        StalenessMode::Slots
+    }
+
+    fn get_price(&self, clock: &impl Now, max_age: u64) -> Result<OraclePrice> {
+        // get_value enforces max staleness (in slots) and minimum samples
+        let value = self.get_value(&clock.to_clock(), max_age, MIN_SAMPLES)?;
+        let std_dev = rescale_switchboard_value(self.result.std_dev)?;
+
+        Ok(OraclePrice {
+            price: rescale_switchboard_value(value)?,
+            conf: std_dev.unsigned_abs(),
+            exponent: SWITCHBOARD_PRICE_EXPONENT,
+            publish_time: self.last_update_timestamp,
+        })
+    }
+
+    fn get_ema_price(&self, _clock: &impl Now, _max_age: u64) -> Result<OraclePrice> {
+        // On-Demand feeds do not publish an EMA; configure a TWAP job instead
+        Err(error!(PriceSourceError::PriceUnavailable))
+    }
+}
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+/// Load and validate a Switchboard feed account
+pub fn get_validated_switchboard_price(
+    feed_account: &AccountInfo,
+    config: &PriceValidationConfig,
+    clock: &impl Now,
+) -> Result<ValidatedPrice> {
+    require_keys_eq!(
+        *feed_account.owner,
+        SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
+        SwitchboardOracleError::InvalidFeedOwner
+    );
+
+    let feed = PullFeedAccountData::parse(feed_account.clone())
+        .map_err(|_| error!(SwitchboardOracleError::InvalidFeedAccount))?;
+
+    get_validated_price(&*feed, config, clock)
+}
+
+// ============================================================================
+// ACCOUNT STRUCTURES
+// ============================================================================
+
+/// Accounts for a single Switchboard feed operation
+#[derive(Accounts)]
+pub struct SingleSwitchboardFeedContext<'info> {
+    /// The Switchboard On-Demand pull feed
+    /// CHECK: owner and layout are verified by get_validated_switchboard_price
+    pub feed: AccountInfo<'info>,
+}
+
+/// Accounts for a dual feed operation (e.g., swaps)
+#[derive(Accounts)]
+pub struct DualSwitchboardFeedContext<'info> {
+    /// Feed for the input asset
+    /// CHECK: owner and layout are verified by get_validated_switchboard_price
+    pub input_feed: AccountInfo<'info>,
+    /// Feed for the output asset
+    /// CHECK: owner and layout are verified by get_validated_switchboard_price
+    pub output_feed: AccountInfo<'info>,
+}
+
+// ============================================================================
+// ERROR CODES
+// ============================================================================
+
+#[error_code]
+pub enum SwitchboardOracleError {
+    #[msg("Feed account is not owned by the Switchboard On-Demand program")]
+    InvalidFeedOwner,
+
+    #[msg("Feed account could not be parsed")]
+    InvalidFeedAccount,
+
+    #[msg("Feed value does not fit the target price format")]
+    ValueOutOfRange,
+}
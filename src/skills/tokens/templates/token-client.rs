@@ -0,0 +1,53 @@
+/**
+ * SPL Token Instruction Builders (Off-Chain)
+ *
+ * The client side of token-utils.rs: instructions that create associated
+ * token accounts and wrap or unwrap SOL in the owner's own wSOL account,
+ * for bots and scripts to put before or after a program's instruction.
+ *
+ * Wrapped SOL always uses the Token program's native mint, so these
+ * builders use the Token program.
+ *
+ * Setup:
+ * 1. Declared by token-utils.rs behind the `client` feature
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_instruction;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anchor_spl::token::spl_token;
+
+use crate::associated_token_address;
+
+/// Create the associated token account of `wallet` for `mint` if it does
+/// not exist, paid by `payer`
+pub fn create_associated_token_account(
+    payer: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    create_associated_token_account_idempotent(payer, wallet, mint, token_program)
+}
+
+/// The owner's wSOL associated token account
+pub fn wsol_address(owner: &Pubkey) -> Pubkey {
+    associated_token_address(owner, &spl_token::native_mint::ID, &spl_token::ID)
+}
+
+/// Wrap `lamports` into the owner's wSOL account, creating it if needed
+pub fn wrap_sol(owner: &Pubkey, lamports: u64) -> [Instruction; 3] {
+    let wsol = wsol_address(owner);
+    [
+        create_associated_token_account(owner, owner, &spl_token::native_mint::ID, &spl_token::ID),
+        system_instruction::transfer(owner, &wsol, lamports),
+        spl_token::instruction::sync_native(&spl_token::ID, &wsol).expect("valid program ID"),
+    ]
+}
+
+/// Close the owner's wSOL account, returning all of it to the owner as SOL
+pub fn unwrap_sol(owner: &Pubkey) -> Instruction {
+    spl_token::instruction::close_account(&spl_token::ID, &wsol_address(owner), owner, owner, &[])
+        .expect("valid program ID")
+}
@@ -0,0 +1,207 @@
+/**
+ * SPL Token Utilities for Anchor Programs
+ *
+ * The token plumbing programs repeat around every swap, deposit and
+ * withdrawal, for both the Token and Token-2022 programs:
+ *
+ * - Associated token accounts: derive, check, and create idempotently
+ * - `transfer_checked` wrappers that require both token accounts to hold
+ *   the mint passed in and take the decimals from that mint, so a
+ *   mismatched account or decimals cannot slip through
+ * - Wrapped SOL: fund a native token account and sync it, or close it to
+ *   return the lamports
+ *
+ * Setup:
+ * 1. Cargo.toml of the library crate:
+ *    [package]
+ *    name = "token-utils"
+ *
+ *    [dependencies]
+ *    anchor-lang = "0.30.1"
+ *    anchor-spl = "0.30.1"
+ *
+ *    [features]
+ *    client = []          # token-client.rs
+ *
+ * 2. Depend on it from your program crate:
+ *    token-utils = { path = "../token-utils" }
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_spl::associated_token::{self, get_associated_token_address_with_program_id, AssociatedToken, Create};
+use anchor_spl::token_interface::{self, CloseAccount, Mint, SyncNative, TokenAccount, TokenInterface, TransferChecked};
+
+#[cfg(feature = "client")]
+#[path = "token-client.rs"]
+pub mod client;
+
+// ============================================================================
+// ASSOCIATED TOKEN ACCOUNTS
+// ============================================================================
+
+/// Associated token account of `wallet` for `mint` under `token_program`
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(wallet, mint, token_program)
+}
+
+/// Require `account` to be the associated token account of `wallet` for
+/// its mint. Use where a payout must land in the wallet's canonical account
+/// rather than any account the caller names.
+pub fn assert_associated_token_account(
+    account: &InterfaceAccount<TokenAccount>,
+    wallet: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<()> {
+    require_keys_eq!(account.owner, *wallet, TokenUtilsError::NotAssociatedTokenAccount);
+    require_keys_eq!(
+        account.key(),
+        associated_token_address(wallet, &account.mint, token_program),
+        TokenUtilsError::NotAssociatedTokenAccount
+    );
+    Ok(())
+}
+
+/// Create the associated token account of `wallet` for `mint`, or do
+/// nothing if it exists. `associated_token` must be its address.
+pub fn create_associated_token_account<'info>(
+    payer: &Signer<'info>,
+    wallet: &AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    associated_token: &AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+    system_program: &Program<'info, System>,
+    associated_token_program: &Program<'info, AssociatedToken>,
+) -> Result<()> {
+    associated_token::create_idempotent(CpiContext::new(
+        associated_token_program.to_account_info(),
+        Create {
+            payer: payer.to_account_info(),
+            associated_token: associated_token.clone(),
+            authority: wallet.clone(),
+            mint: mint.to_account_info(),
+            system_program: system_program.to_account_info(),
+            token_program: token_program.to_account_info(),
+        },
+    ))
+}
+
+// ============================================================================
+// TRANSFERS
+// ============================================================================
+
+/// `transfer_checked` signed by `authority` (a wallet, or the delegate)
+pub fn transfer_checked<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    transfer_checked_signed(token_program, from, mint, to, authority, amount, &[])
+}
+
+/// `transfer_checked` signed by a PDA `authority` with `signer_seeds`
+pub fn transfer_checked_signed<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    // The token program checks the source; checking both here names the
+    // failing side and catches a destination of another mint early
+    require_keys_eq!(from.mint, mint.key(), TokenUtilsError::MintMismatch);
+    require_keys_eq!(to.mint, mint.key(), TokenUtilsError::MintMismatch);
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: from.to_account_info(),
+                mint: mint.to_account_info(),
+                to: to.to_account_info(),
+                authority: authority.clone(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        mint.decimals,
+    )
+}
+
+// ============================================================================
+// WRAPPED SOL
+// ============================================================================
+
+/// Move `lamports` from `payer` into the native token account `wsol` and
+/// sync its token balance
+pub fn wrap_sol<'info>(
+    payer: &Signer<'info>,
+    wsol: &InterfaceAccount<'info, TokenAccount>,
+    lamports: u64,
+    token_program: &Interface<'info, TokenInterface>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    require!(wsol.is_native(), TokenUtilsError::NotNativeAccount);
+
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            Transfer {
+                from: payer.to_account_info(),
+                to: wsol.to_account_info(),
+            },
+        ),
+        lamports,
+    )?;
+    token_interface::sync_native(CpiContext::new(
+        token_program.to_account_info(),
+        SyncNative {
+            account: wsol.to_account_info(),
+        },
+    ))
+}
+
+/// Close the native token account `wsol`, sending its wrapped balance and
+/// rent to `destination`. Token accounts cannot unwrap part of a balance:
+/// transfer the rest to another wSOL account first. Pass `signer_seeds`
+/// when `authority` is a PDA, or `&[]`.
+pub fn unwrap_sol<'info>(
+    wsol: &InterfaceAccount<'info, TokenAccount>,
+    destination: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    require!(wsol.is_native(), TokenUtilsError::NotNativeAccount);
+
+    token_interface::close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: wsol.to_account_info(),
+            destination: destination.clone(),
+            authority: authority.clone(),
+        },
+        signer_seeds,
+    ))
+}
+
+// ============================================================================
+// ERROR CODES
+// ============================================================================
+
+#[error_code]
+pub enum TokenUtilsError {
+    #[msg("Token account does not hold the expected mint")]
+    MintMismatch,
+
+    #[msg("Token account is not the wallet's associated token account")]
+    NotAssociatedTokenAccount,
+
+    #[msg("Token account is not a wrapped SOL account")]
+    NotNativeAccount,
+}
@@ -0,0 +1,287 @@
+/**
+ * Maintenance Windows
+ *
+ * Operators schedule windows during which the automaton must not trade.
+ * Ahead of each window the agent is told to wind down to the window's
+ * safe posture; during the window it stays in the "maintenance" state
+ * and ignores wake requests; afterwards it resumes normally.
+ * Windows are stored in the KV table so the CLI can schedule them while
+ * the automaton runs.
+ */
+
+import { ulid } from "ulid";
+import type {
+  AutomatonConfig,
+  AutomatonDatabase,
+  SocialClientInterface,
+} from "../types.js";
+
+const WINDOWS_KEY = "maintenance_windows";
+const LAST_ALERT_KEY = "last_maintenance_alert";
+
+/** Ended windows are kept this long for the audit trail. */
+const RETAIN_ENDED_MS = 7 * 24 * 60 * 60 * 1000;
+
+export const DEFAULT_WIND_DOWN_MINUTES = 30;
+
+export const DEFAULT_SAFE_POSTURE =
+  "Cancel all resting orders, close or hedge open positions, and do not open new ones. " +
+  "Keep the treasury in stable assets until maintenance ends.";
+
+export interface MaintenanceWindow {
+  id: string;
+  startsAt: string;
+  endsAt: string;
+  reason: string;
+  /** How long before startsAt the agent starts winding down. */
+  windDownMinutes: number;
+  /** Instruction given to the agent when winding down. */
+  safePosture: string;
+  createdAt: string;
+  windDownStartedAt?: string;
+  startedAt?: string;
+  endedAt?: string;
+  cancelledAt?: string;
+}
+
+export type MaintenancePhase = "none" | "winding_down" | "in_maintenance";
+
+export interface MaintenanceStatus {
+  phase: MaintenancePhase;
+  window?: MaintenanceWindow;
+}
+
+export type MaintenanceEventKind =
+  | "wind_down_started"
+  | "maintenance_started"
+  | "maintenance_ended";
+
+export interface MaintenanceEvent {
+  kind: MaintenanceEventKind;
+  window: MaintenanceWindow;
+  message: string;
+}
+
+// ─── Storage ───────────────────────────────────────────────────
+
+export function loadMaintenanceWindows(db: AutomatonDatabase): MaintenanceWindow[] {
+  const raw = db.getKV(WINDOWS_KEY);
+  if (!raw) return [];
+  try {
+    const parsed = JSON.parse(raw);
+    return Array.isArray(parsed) ? parsed : [];
+  } catch {
+    return [];
+  }
+}
+
+function saveMaintenanceWindows(
+  db: AutomatonDatabase,
+  windows: MaintenanceWindow[],
+): void {
+  db.setKV(WINDOWS_KEY, JSON.stringify(windows));
+}
+
+function isActive(window: MaintenanceWindow): boolean {
+  return !window.cancelledAt && !window.endedAt;
+}
+
+/**
+ * Schedule a window. Rejects windows that end before they start, have
+ * already ended, overlap another active window, or have a wind-down that
+ * is not a finite, non-negative number of minutes.
+ */
+export function scheduleMaintenanceWindow(
+  db: AutomatonDatabase,
+  params: {
+    startsAt: Date;
+    endsAt: Date;
+    reason: string;
+    windDownMinutes?: number;
+    safePosture?: string;
+  },
+  now: Date = new Date(),
+): MaintenanceWindow {
+  const { startsAt, endsAt } = params;
+  if (isNaN(startsAt.getTime()) || isNaN(endsAt.getTime())) {
+    throw new Error("Invalid maintenance window dates");
+  }
+  if (endsAt <= startsAt) {
+    throw new Error("Maintenance window must end after it starts");
+  }
+  if (endsAt <= now) {
+    throw new Error("Maintenance window has already ended");
+  }
+  const windDownMinutes = params.windDownMinutes ?? DEFAULT_WIND_DOWN_MINUTES;
+  if (!Number.isFinite(windDownMinutes) || windDownMinutes < 0) {
+    throw new Error("Invalid wind-down minutes");
+  }
+
+  const windows = loadMaintenanceWindows(db);
+  const overlapping = windows.find(
+    (w) =>
+      isActive(w) &&
+      new Date(w.startsAt) < endsAt &&
+      startsAt < new Date(w.endsAt),
+  );
+  if (overlapping) {
+    throw new Error(`Overlaps maintenance window ${overlapping.id}`);
+  }
+
+  const window: MaintenanceWindow = {
+    id: ulid(),
+    startsAt: startsAt.toISOString(),
+    endsAt: endsAt.toISOString(),
+    reason: params.reason,
+    windDownMinutes,
+    safePosture: params.safePosture || DEFAULT_SAFE_POSTURE,
+    createdAt: now.toISOString(),
+  };
+  windows.push(window);
+  saveMaintenanceWindows(db, windows);
+  return window;
+}
+
+/**
+ * Cancel a window. A window already in progress ends at the next check.
+ */
+export function cancelMaintenanceWindow(
+  db: AutomatonDatabase,
+  id: string,
+  now: Date = new Date(),
+): boolean {
+  const windows = loadMaintenanceWindows(db);
+  const window = windows.find((w) => w.id === id && isActive(w));
+  if (!window) return false;
+  window.cancelledAt = now.toISOString();
+  saveMaintenanceWindows(db, windows);
+  return true;
+}
+
+// ─── Status ────────────────────────────────────────────────────
+
+function phaseOf(window: MaintenanceWindow, now: Date): MaintenancePhase {
+  if (!isActive(window)) return "none";
+  const startsAt = new Date(window.startsAt).getTime();
+  const endsAt = new Date(window.endsAt).getTime();
+  const windDownAt = startsAt - window.windDownMinutes * 60_000;
+  const t = now.getTime();
+
+  if (t >= endsAt) return "none";
+  if (t >= startsAt) return "in_maintenance";
+  if (t >= windDownAt) return "winding_down";
+  return "none";
+}
+
+/**
+ * Current phase, from the earliest window that affects `now`.
+ */
+export function getMaintenanceStatus(
+  db: AutomatonDatabase,
+  now: Date = new Date(),
+): MaintenanceStatus {
+  const windows = loadMaintenanceWindows(db)
+    .filter(isActive)
+    .sort((a, b) => a.startsAt.localeCompare(b.startsAt));
+
+  for (const window of windows) {
+    const phase = phaseOf(window, now);
+    if (phase !== "none") return { phase, window };
+  }
+  return { phase: "none" };
+}
+
+/**
+ * Move windows through their phases and return one event per transition.
+ * Each transition fires once, however often this is called.
+ */
+export function advanceMaintenance(
+  db: AutomatonDatabase,
+  now: Date = new Date(),
+): MaintenanceEvent[] {
+  const windows = loadMaintenanceWindows(db);
+  const events: MaintenanceEvent[] = [];
+  const stamp = now.toISOString();
+
+  for (const window of windows) {
+    // A window cancelled after wind-down began still needs its end event
+    const cancelledMidway =
+      !!window.cancelledAt &&
+      !!(window.startedAt || window.windDownStartedAt) &&
+      !window.endedAt;
+    if (!isActive(window) && !cancelledMidway) continue;
+
+    const ended = cancelledMidway || now >= new Date(window.endsAt);
+    if (ended) {
+      if (window.startedAt || window.windDownStartedAt) {
+        events.push({
+          kind: "maintenance_ended",
+          window,
+          message: `Maintenance ${window.cancelledAt ? "cancelled" : "ended"} (${window.reason}). Resuming normal operation.`,
+        });
+      }
+      window.endedAt = stamp;
+      continue;
+    }
+
+    const phase = phaseOf(window, now);
+    if (phase === "in_maintenance" && !window.startedAt) {
+      window.startedAt = stamp;
+      events.push({
+        kind: "maintenance_started",
+        window,
+        message: `Maintenance started (${window.reason}). Trading halted until ${window.endsAt}.`,
+      });
+    } else if (phase === "winding_down" && !window.windDownStartedAt) {
+      window.windDownStartedAt = stamp;
+      events.push({
+        kind: "wind_down_started",
+        window,
+        message: `Maintenance at ${window.startsAt} (${window.reason}). Winding down: ${window.safePosture}`,
+      });
+    }
+  }
+
+  const kept = windows.filter(
+    (w) =>
+      !(w.endedAt || w.cancelledAt) ||
+      now.getTime() - new Date(w.endsAt).getTime() < RETAIN_ENDED_MS,
+  );
+  saveMaintenanceWindows(db, kept);
+  return events;
+}
+
+// ─── Alerting ──────────────────────────────────────────────────
+
+/**
+ * Record a maintenance alert and send it to the creator over the social
+ * relay when one is configured.
+ */
+export async function alertOperators(
+  config: AutomatonConfig,
+  db: AutomatonDatabase,
+  social: SocialClientInterface | undefined,
+  message: string,
+): Promise<void> {
+  console.log(`[MAINTENANCE] ${message}`);
+  db.setKV(
+    LAST_ALERT_KEY,
+    JSON.stringify({ message, timestamp: new Date().toISOString() }),
+  );
+  if (!social) return;
+  try {
+    await social.send(config.creatorAddress, `[${config.name}] ${message}`);
+  } catch (err: any) {
+    console.error(`[MAINTENANCE] Alert delivery failed: ${err.message}`);
+  }
+}
+
+/**
+ * Prompt given to the agent when a wind-down starts.
+ */
+export function buildWindDownPrompt(window: MaintenanceWindow): string {
+  return `Scheduled maintenance starts at ${window.startsAt} and ends at ${window.endsAt} (${window.reason}).
+Before it starts, reach this safe posture:
+${window.safePosture}
+Trading is halted during maintenance. Report what you closed or left open.`;
+}
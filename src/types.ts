@@ -32,6 +32,8 @@ export interface ProvisionResult {
 // ─── Configuration ───────────────────────────────────────────────
 
 export interface AutomatonConfig {
+  /** Config schema version; see config-migration.ts */
+  configVersion?: number;
   name: string;
   genesisPrompt: string;
   creatorMessage?: string;
@@ -91,6 +93,7 @@ export type AgentState =
   | "sleeping"
   | "low_compute"
   | "critical"
+  | "maintenance"
   | "dead";
 
 export interface AgentTurn {